@@ -0,0 +1,191 @@
+use anyhow::{anyhow, bail, Result};
+use chain_rpc::ChainRpc;
+use ibc::clients::ics07_tendermint::client_state::{AllowUpdate, ClientState};
+use ibc::core::ics02_client::trust_threshold::TrustThreshold;
+use ibc::core::ics23_commitment::specs::ProofSpecs;
+use ibc::core::ics24_host::identifier::ChainId;
+use ibc::Height;
+use ibc_chain_registry::{chain::ChainData, fetchable::Fetchable};
+use ibc_proto::cosmos::staking::v1beta1::{QueryParamsRequest, QueryParamsResponse};
+use ibc_proto::google::protobuf::{Any as ProtoAny, Timestamp};
+use ibc_proto::ibc::core::commitment::v1::MerkleRoot;
+use ibc_proto::ibc::lightclients::tendermint::v1::ConsensusState as RawConsensusState;
+use lcp_types::Any;
+use log::info;
+use prost::Message;
+use std::time::Duration;
+
+const TENDERMINT_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.ConsensusState";
+const STAKING_PARAMS_QUERY_PATH: &str = "/cosmos.staking.v1beta1.Query/Params";
+
+/// TrustParams are the trust parameters of a bootstrapped tendermint client
+#[derive(Clone, Debug)]
+pub struct TrustParams {
+    pub trusting_period: Duration,
+    pub unbonding_period: Duration,
+    pub max_clock_drift: Duration,
+}
+
+impl TrustParams {
+    /// Create the parameters, where the trusting period defaults to 2/3 of the unbonding period
+    pub fn new(
+        unbonding_period: Duration,
+        trusting_period: Option<Duration>,
+        max_clock_drift: Duration,
+    ) -> Result<Self> {
+        let trusting_period = trusting_period.unwrap_or(unbonding_period * 2 / 3);
+        if trusting_period.is_zero() || trusting_period >= unbonding_period {
+            bail!(
+                "the trusting period must be positive and shorter than the unbonding period: trusting_period={:?} unbonding_period={:?}",
+                trusting_period,
+                unbonding_period
+            );
+        }
+        Ok(Self {
+            trusting_period,
+            unbonding_period,
+            max_clock_drift,
+        })
+    }
+}
+
+/// BootstrapStates are the states to initialize a light client with
+pub struct BootstrapStates {
+    pub client_state: Any,
+    pub consensus_state: Any,
+}
+
+/// Returns the RPC endpoints of the chain listed in the cosmos chain registry
+///
+/// The registry is keyed by the chain name, which is derived from the chain-id by dropping the revision,
+/// e.g. `osmosis-1` is looked up as `osmosis`.
+pub fn resolve_rpc_addrs(rt: &tokio::runtime::Runtime, chain_id: &str) -> Result<Vec<String>> {
+    let chain_name = registry_chain_name(chain_id);
+    let chain_data = rt
+        .block_on(ChainData::fetch(chain_name.to_string(), None))
+        .map_err(|e| {
+            anyhow!(
+                "failed to fetch the chain registry entry of {}: {}",
+                chain_name,
+                e
+            )
+        })?;
+    if chain_data.chain_id.to_string() != chain_id {
+        bail!(
+            "unexpected chain-id in the chain registry: expected={} actual={}",
+            chain_id,
+            chain_data.chain_id
+        );
+    }
+    if chain_data.apis.rpc.is_empty() {
+        bail!("no RPC endpoint is registered for {}", chain_id);
+    }
+    Ok(chain_data
+        .apis
+        .rpc
+        .into_iter()
+        .map(|rpc| rpc.address)
+        .collect())
+}
+
+/// Fetch the unbonding period from the staking params of the chain
+///
+/// The trust parameters of a client depend on it, so an assumed value must not be used instead.
+pub fn fetch_unbonding_period(rpc: &dyn ChainRpc) -> Result<Duration> {
+    let value = rpc
+        .abci_query(
+            STAKING_PARAMS_QUERY_PATH,
+            QueryParamsRequest {}.encode_to_vec(),
+        )
+        .map_err(|e| anyhow!("failed to fetch the staking params: {}", e))?;
+    let unbonding_time = QueryParamsResponse::decode(value.as_slice())?
+        .params
+        .and_then(|params| params.unbonding_time)
+        .ok_or_else(|| anyhow!("the staking params have no unbonding time"))?;
+    if unbonding_time.seconds < 0 || unbonding_time.nanos < 0 {
+        bail!("invalid unbonding time: {:?}", unbonding_time);
+    }
+    let unbonding_period =
+        Duration::new(unbonding_time.seconds as u64, unbonding_time.nanos as u32);
+    if unbonding_period.is_zero() {
+        bail!("the unbonding time of the chain is zero");
+    }
+    info!("bootstrap: unbonding_period={:?}", unbonding_period);
+    Ok(unbonding_period)
+}
+
+/// Fetch the latest block of the chain and build the client and consensus states at its height
+///
+/// If `chain_id` is given, the chain that the RPC endpoint serves must have it.
+pub fn fetch_latest_states(
+    rpc: &dyn ChainRpc,
+    chain_id: Option<&str>,
+    params: &TrustParams,
+) -> Result<BootstrapStates> {
+    let header = rpc
+        .latest_commit()
+        .map_err(|e| anyhow!("failed to fetch the latest commit: {}", e))?
+        .signed_header
+        .header;
+    if let Some(chain_id) = chain_id {
+        if header.chain_id.as_str() != chain_id {
+            bail!(
+                "the RPC endpoint serves another chain: expected={} actual={}",
+                chain_id,
+                header.chain_id
+            );
+        }
+    }
+    let chain_id = ChainId::from_string(header.chain_id.as_str());
+    let height = Height::new(chain_id.version(), header.height.value())
+        .map_err(|e| anyhow!("invalid height: {}", e))?;
+    info!(
+        "bootstrap: chain_id={} height={} time={}",
+        chain_id, height, header.time
+    );
+
+    #[allow(deprecated)]
+    let client_state = ClientState::new(
+        chain_id,
+        TrustThreshold::ONE_THIRD,
+        params.trusting_period,
+        params.unbonding_period,
+        params.max_clock_drift,
+        height,
+        ProofSpecs::cosmos(),
+        vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+        AllowUpdate {
+            after_expiry: false,
+            after_misbehaviour: false,
+        },
+        None,
+    )
+    .map_err(|e| anyhow!("invalid client state: {}", e))?;
+
+    let timestamp = header.time.unix_timestamp_nanos();
+    let consensus_state = RawConsensusState {
+        timestamp: Some(Timestamp {
+            seconds: timestamp.div_euclid(1_000_000_000) as i64,
+            nanos: timestamp.rem_euclid(1_000_000_000) as i32,
+        }),
+        root: Some(MerkleRoot {
+            hash: header.app_hash.value(),
+        }),
+        next_validators_hash: header.next_validators_hash.as_bytes().to_vec(),
+    };
+
+    Ok(BootstrapStates {
+        client_state: ProtoAny::from(client_state).into(),
+        consensus_state: Any::new(
+            TENDERMINT_CONSENSUS_STATE_TYPE_URL.to_string(),
+            consensus_state.encode_to_vec(),
+        ),
+    })
+}
+
+fn registry_chain_name(chain_id: &str) -> &str {
+    match chain_id.rsplit_once('-') {
+        Some((name, revision)) if revision.parse::<u64>().is_ok() => name,
+        _ => chain_id,
+    }
+}
@@ -1,15 +1,21 @@
-use self::{attestation::AttestationCmd, elc::ELCCmd, enclave::EnclaveCmd, service::ServiceCmd};
+use self::{
+    attestation::AttestationCmd, elc::ELCCmd, enclave::EnclaveCmd, replay::ReplayCmd,
+    service::ServiceCmd, status::StatusCmd,
+};
 use crate::{enclave::build_enclave_loader, opts::Opts};
 use anyhow::Result;
 use clap::Parser;
-use host_environment::Environment;
+use host_environment::{Environment, HomeLock};
 use std::sync::{Arc, RwLock};
-use store::{host::HostStore, rocksdb::RocksDBStore};
+use store::{host::HostStore, memory::MemStore, rocksdb::RocksDBStore};
+use tempfile::TempDir;
 
 mod attestation;
 mod elc;
 mod enclave;
+mod replay;
 mod service;
+mod status;
 
 /// Cli Subcommands
 #[allow(clippy::upper_case_acronyms)]
@@ -23,6 +29,16 @@ pub enum CliCmd {
     ELC(ELCCmd),
     #[clap(subcommand, display_order = 4, about = "Service subcommands")]
     Service(ServiceCmd),
+    #[clap(
+        display_order = 5,
+        about = "Replay a command log against a fresh enclave and compare the resulting states"
+    )]
+    Replay(ReplayCmd),
+    #[clap(
+        display_order = 6,
+        about = "Show the status of the enclave, its keys and the tracked clients"
+    )]
+    Status(StatusCmd),
 }
 
 impl CliCmd {
@@ -30,19 +46,30 @@ impl CliCmd {
         Self::setup_logger(opts)?;
         match self {
             CliCmd::Enclave(cmd) => {
-                Self::setup_read_only_env(opts);
+                Self::setup_read_only_env(opts)?;
                 cmd.run(opts, build_enclave_loader::<RocksDBStore>())
             }
             CliCmd::Attestation(cmd) => {
-                Self::setup_read_only_env(opts);
+                if cmd.requires_enclave() {
+                    Self::setup_read_only_env(opts)?;
+                }
                 cmd.run(opts, build_enclave_loader::<RocksDBStore>())
             }
             CliCmd::Service(cmd) => {
-                Self::setup_env(opts);
+                Self::setup_env(opts)?;
                 cmd.run(opts, build_enclave_loader::<RocksDBStore>())
             }
             CliCmd::ELC(cmd) => {
-                Self::setup_env(opts);
+                Self::setup_env(opts)?;
+                cmd.run(opts, build_enclave_loader::<RocksDBStore>())
+            }
+            CliCmd::Replay(cmd) => {
+                // the home is removed when it is dropped
+                let _home = Self::setup_replay_env()?;
+                cmd.run(opts, build_enclave_loader::<MemStore>())
+            }
+            CliCmd::Status(cmd) => {
+                Self::setup_read_only_env(opts)?;
                 cmd.run(opts, build_enclave_loader::<RocksDBStore>())
             }
         }
@@ -57,15 +84,47 @@ impl CliCmd {
         Ok(())
     }
 
-    fn setup_env(opts: &Opts) {
-        let store = HostStore::RocksDB(RocksDBStore::open(opts.get_state_store_path()));
-        let env = Environment::new(opts.get_home(), Arc::new(RwLock::new(store)));
+    fn setup_env(opts: &Opts) -> Result<()> {
+        // the lock must be acquired before opening the store to prevent concurrent writers
+        let lock = HomeLock::acquire(&opts.get_home())?;
+        let store = HostStore::RocksDB(
+            match opts.get_store_key()? {
+                Some(key) => RocksDBStore::open_encrypted(opts.get_state_store_path(), key)?,
+                None => RocksDBStore::open(opts.get_state_store_path()),
+            }
+            .with_sync(opts.get_store_sync_policy()?),
+        );
+        let env = Environment::new(opts.get_home(), Arc::new(RwLock::new(store)))
+            .with_home_lock(lock)
+            .with_ias_endpoint(opts.get_ias_endpoint()?);
+        host::set_environment(env).unwrap();
+        Ok(())
+    }
+
+    /// Set up an environment with an empty in-memory store and a temporary home for the keys,
+    /// so that the replay doesn't touch the state of the home
+    fn setup_replay_env() -> Result<TempDir> {
+        let home = TempDir::new()?;
+        let store = HostStore::Memory(MemStore::default());
+        let env = Environment::new(home.path().to_path_buf(), Arc::new(RwLock::new(store)));
         host::set_environment(env).unwrap();
+        Ok(home)
     }
 
-    fn setup_read_only_env(opts: &Opts) {
-        let store = HostStore::RocksDB(RocksDBStore::open_read_only(opts.get_state_store_path()));
+    fn setup_read_only_env(opts: &Opts) -> Result<()> {
+        let store = Self::open_read_only_store(opts)?;
         let env = Environment::new(opts.get_home(), Arc::new(RwLock::new(store)));
         host::set_environment(env).unwrap();
+        Ok(())
+    }
+
+    /// Open the state store without the lock of the home, so that it can be read while the service is running
+    ///
+    /// The store sees the writes committed before it is opened, so it must be reopened to follow the service.
+    pub(crate) fn open_read_only_store(opts: &Opts) -> Result<HostStore> {
+        Ok(HostStore::RocksDB(match opts.get_store_key()? {
+            Some(key) => RocksDBStore::open_read_only_encrypted(opts.get_state_store_path(), key)?,
+            None => RocksDBStore::open_read_only(opts.get_state_store_path()),
+        }))
     }
 }
@@ -2,11 +2,21 @@ use crate::{
     enclave::EnclaveLoader,
     opts::{EnclaveOpts, Opts},
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use attestation_report::{AdvisoryPolicy, EndorsedAttestationVerificationReport};
 use clap::Parser;
 use crypto::Address;
-use ecall_commands::IASRemoteAttestationInput;
-use enclave_api::{Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
+use ecall_commands::{IASRemoteAttestationInput, QuoteSignType, ValidateDCAPCollateralInput};
+use enclave_api::{
+    build_cosmos_registration, build_evm_registration, registration_evidence, Enclave,
+    EnclaveCommandAPI, EnclaveProtoAPI, RegistrationTarget,
+};
+use host::{probe_pccs, PlatformCapabilities};
+use host_environment::credentials;
+use host_environment::dcap::{CollateralFetcher, PckCa};
+use lcp_types::{Mrenclave, Time};
+use serde_json::json;
+use std::time::Duration;
 use store::transaction::CommitStore;
 
 /// `attestation` subcommand
@@ -18,9 +28,34 @@ pub enum AttestationCmd {
     #[cfg(feature = "sgx-sw")]
     #[clap(display_order = 2, about = "Simulate Remote Attestation")]
     Simulate(SimulateRemoteAttestation),
+    #[clap(
+        display_order = 3,
+        about = "Verify an Attestation Verification Report offline"
+    )]
+    Verify(VerifyAVR),
+    #[clap(
+        display_order = 4,
+        about = "Fetch DCAP collateral for a quote and validate it in the enclave"
+    )]
+    DCAP(DCAPCollateralBundle),
+    #[clap(
+        display_order = 5,
+        about = "Build a transaction that registers an attested enclave key to the LCP client on a chain"
+    )]
+    Register(RegisterEnclaveKey),
+    #[clap(
+        display_order = 6,
+        about = "Show the advisory policy that is in effect at a given time"
+    )]
+    Policy(ShowAdvisoryPolicy),
 }
 
 impl AttestationCmd {
+    /// Returns true if the subcommand needs to load the enclave and the host environment
+    pub fn requires_enclave(&self) -> bool {
+        !matches!(self, AttestationCmd::Verify(_) | AttestationCmd::Policy(_))
+    }
+
     pub fn run<S, L>(&self, opts: &Opts, enclave_loader: L) -> Result<()>
     where
         S: CommitStore,
@@ -48,6 +83,26 @@ impl AttestationCmd {
                     cmd,
                 )
             }
+            AttestationCmd::Verify(cmd) => run_verify_avr(cmd),
+            AttestationCmd::Policy(cmd) => run_show_advisory_policy(cmd),
+            AttestationCmd::DCAP(cmd) => {
+                if !home.exists() {
+                    bail!("home directory doesn't exist at {:?}", home);
+                }
+                run_dcap_collateral_bundle(
+                    enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                    cmd,
+                )
+            }
+            AttestationCmd::Register(cmd) => {
+                if !home.exists() {
+                    bail!("home directory doesn't exist at {:?}", home);
+                }
+                run_register_enclave_key(
+                    enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                    cmd,
+                )
+            }
         }
     }
 }
@@ -63,25 +118,211 @@ pub struct IASRemoteAttestation {
         help = "An enclave key attested by Remote Attestation"
     )]
     pub enclave_key: String,
+    /// Source of the SPID and IAS_KEY credentials
+    #[clap(
+        long = "credentials",
+        default_value = "env",
+        help = "Source of the SPID and IAS_KEY credentials: `env`, `file:<path>`, `keychain:<service>` or `vault:<path>`"
+    )]
+    pub credentials: String,
+    /// Signature type of the EPID quote
+    #[clap(
+        long = "quote_type",
+        default_value = "unlinkable",
+        help = "Signature type of the EPID quote: `linkable` or `unlinkable`. It must match the type of the SPID"
+    )]
+    pub quote_type: String,
+    /// Path to a JSON file that contains the advisory policy
+    #[clap(
+        long = "advisory_policy",
+        help = "Path to a JSON file that contains the advisory policy which the report must satisfy"
+    )]
+    pub advisory_policy: Option<std::path::PathBuf>,
+    /// Mitigations applied to the platform
+    #[clap(
+        long = "mitigations",
+        value_delimiter = ',',
+        help = "Mitigations applied to the platform, which the advisory policy may require"
+    )]
+    pub mitigations: Vec<String>,
+}
+
+fn load_advisory_policy(path: &std::path::Path) -> Result<AdvisoryPolicy> {
+    serde_json::from_slice(&std::fs::read(path)?).map_err(|e| {
+        anyhow!(
+            "failed to parse the advisory policy: path={:?} err={}",
+            path,
+            e
+        )
+    })
 }
 
 fn run_ias_remote_attestation<E: EnclaveCommandAPI<S>, S: CommitStore>(
     enclave: E,
     cmd: &IASRemoteAttestation,
 ) -> Result<()> {
-    let spid = std::env::var("SPID")?;
-    let ias_key = std::env::var("IAS_KEY")?;
+    let provider = credentials::from_spec(&cmd.credentials)?;
+    let creds = provider.ias_credentials()?;
     let target_enclave_key = Address::from_hex_string(&cmd.enclave_key)?;
     match enclave.ias_remote_attestation(IASRemoteAttestationInput {
         target_enclave_key,
-        spid: spid.as_bytes().to_vec(),
-        ias_key: ias_key.as_bytes().to_vec(),
+        spid: creds.spid,
+        ias_key: creds.ias_key,
+        quote_type: cmd
+            .quote_type
+            .parse::<QuoteSignType>()
+            .map_err(|e| anyhow!("{:?}", e))?,
+        advisory_policy: cmd
+            .advisory_policy
+            .as_deref()
+            .map(load_advisory_policy)
+            .transpose()?,
+        applied_mitigations: cmd.mitigations.clone(),
     }) {
         Ok(_) => Ok(()),
-        Err(e) => bail!("failed to perform IAS Remote Attestation: {:?}!", e),
+        Err(e) => match PlatformCapabilities::probe().check_epid() {
+            Ok(()) => bail!("failed to perform IAS Remote Attestation: {:?}!", e),
+            Err(hint) => bail!(
+                "failed to perform IAS Remote Attestation: {}: {:?}!",
+                hint,
+                e
+            ),
+        },
     }
 }
 
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct DCAPCollateralBundle {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// An enclave key whose address is in the report data of the quote
+    #[clap(
+        long = "enclave_key",
+        help = "An enclave key whose address is in the report data of the quote"
+    )]
+    pub enclave_key: String,
+    /// Path to a raw DCAP quote
+    #[clap(long = "quote", help = "Path to a raw DCAP quote")]
+    pub quote_path: std::path::PathBuf,
+    /// FMSPC of the platform in hex
+    #[clap(long = "fmspc", help = "FMSPC of the platform in hex")]
+    pub fmspc: String,
+    /// CA that issued the PCK certificate
+    #[clap(
+        long = "pck_ca",
+        default_value = "processor",
+        help = "CA that issued the PCK certificate: `processor` or `platform`"
+    )]
+    pub pck_ca: String,
+    /// Base URL of the PCS/PCCS certification API
+    #[clap(
+        long = "pccs_url",
+        default_value = host_environment::dcap::INTEL_PCS_URL,
+        help = "Base URL of the PCS/PCCS certification API"
+    )]
+    pub pccs_url: String,
+    /// URL of the root CA CRL
+    #[clap(
+        long = "root_ca_crl_url",
+        default_value = host_environment::dcap::INTEL_ROOT_CA_CRL_URL,
+        help = "URL of the root CA CRL"
+    )]
+    pub root_ca_crl_url: String,
+}
+
+fn run_dcap_collateral_bundle<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    cmd: &DCAPCollateralBundle,
+) -> Result<()> {
+    let target_enclave_key = Address::from_hex_string(&cmd.enclave_key)?;
+    let quote = std::fs::read(&cmd.quote_path)?;
+    let fetcher = CollateralFetcher::new(cmd.pccs_url.clone(), cmd.root_ca_crl_url.clone());
+    let bundle = fetcher
+        .fetch_bundle(quote, &cmd.fmspc, cmd.pck_ca.parse::<PckCa>()?)
+        .map_err(|e| match probe_pccs(&cmd.pccs_url) {
+            Ok(()) => anyhow!("failed to fetch DCAP collateral: {}", e),
+            Err(hint) => anyhow!("failed to fetch DCAP collateral: {}: {}", hint, e),
+        })?;
+    match enclave.validate_dcap_collateral(ValidateDCAPCollateralInput {
+        target_enclave_key,
+        bundle,
+    }) {
+        Ok(res) => {
+            println!(
+                "{}",
+                json! {{
+                    "enclave_key": target_enclave_key.to_hex_string(),
+                    "root_ca_hash": hex::encode(res.root_ca_hash),
+                }}
+            );
+            Ok(())
+        }
+        Err(e) => bail!("failed to validate DCAP collateral: {:?}!", e),
+    }
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct RegisterEnclaveKey {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// An attested enclave key to register
+    #[clap(long = "enclave_key", help = "An attested enclave key to register")]
+    pub enclave_key: String,
+    /// Client ID of the LCP client on the target chain
+    #[clap(
+        long = "client_id",
+        help = "Client ID of the LCP client on the target chain"
+    )]
+    pub client_id: String,
+    /// Kind of the target chain
+    #[clap(long = "target", help = "Kind of the target chain: `cosmos` or `evm`")]
+    pub target: String,
+    /// Bech32 address of the account that broadcasts the transaction
+    /// This is required for `cosmos`.
+    #[clap(
+        long = "signer",
+        help = "Bech32 address of the account that broadcasts the transaction (required for `cosmos`)"
+    )]
+    pub signer: Option<String>,
+}
+
+fn run_register_enclave_key<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    cmd: &RegisterEnclaveKey,
+) -> Result<()> {
+    let target = cmd.target.parse::<RegistrationTarget>()?;
+    let enclave_key = Address::from_hex_string(&cmd.enclave_key)?;
+    let eavr = registration_evidence(&enclave, enclave_key)?;
+    let tx = match target {
+        RegistrationTarget::Cosmos => {
+            let signer = cmd
+                .signer
+                .as_ref()
+                .ok_or_else(|| anyhow!("`--signer` is required for the target `cosmos`"))?;
+            let msg = build_cosmos_registration(&cmd.client_id, signer, &eavr);
+            json! {{
+                "type_url": msg.type_url,
+                "value": format!("0x{}", hex::encode(msg.value)),
+            }}
+        }
+        RegistrationTarget::Evm => json! {{
+            "data": format!("0x{}", hex::encode(build_evm_registration(&cmd.client_id, &eavr))),
+        }},
+    };
+    println!(
+        "{}",
+        json! {{
+            "target": target.to_string(),
+            "enclave_key": enclave_key.to_hex_string(),
+            "client_id": cmd.client_id,
+            "tx": tx,
+        }}
+    );
+    Ok(())
+}
+
 #[cfg(feature = "sgx-sw")]
 #[derive(Clone, Debug, Parser, PartialEq)]
 pub struct SimulateRemoteAttestation {
@@ -197,3 +438,164 @@ fn run_simulate_remote_attestation<E: EnclaveCommandAPI<S>, S: CommitStore>(
         Err(e) => bail!("failed to simulate Remote Attestation: {:?}!", e),
     }
 }
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct VerifyAVR {
+    /// Path to a JSON file that contains an endorsed AVR
+    #[clap(
+        long = "avr",
+        help = "Path to a JSON file that contains an endorsed AVR"
+    )]
+    pub avr: std::path::PathBuf,
+
+    /// Expected MRENCLAVE of the attested enclave
+    #[clap(
+        long = "mrenclave",
+        help = "Expected MRENCLAVE(hex) of the attested enclave"
+    )]
+    pub mrenclave: Option<String>,
+
+    /// Key expiration in seconds from the attestation time
+    #[clap(
+        long = "key_expiration",
+        help = "Key expiration in seconds from the attestation time"
+    )]
+    pub key_expiration: Option<u64>,
+
+    /// Quote statuses accepted in addition to `OK`
+    #[clap(
+        long = "allowed_quote_statuses",
+        value_delimiter = ',',
+        help = "Quote statuses accepted in addition to `OK`"
+    )]
+    pub allowed_quote_statuses: Vec<String>,
+
+    /// Intel security advisory IDs accepted in the report
+    #[clap(
+        long = "allowed_advisory_ids",
+        value_delimiter = ',',
+        help = "Intel security advisory IDs accepted in the report"
+    )]
+    pub allowed_advisory_ids: Vec<String>,
+
+    /// Path to a JSON file that contains the advisory policy
+    /// Its rules take precedence over `allowed_advisory_ids`.
+    #[clap(
+        long = "advisory_policy",
+        help = "Path to a JSON file that contains the advisory policy. Its rules take precedence over `--allowed_advisory_ids`"
+    )]
+    pub advisory_policy: Option<std::path::PathBuf>,
+
+    /// Mitigations applied to the platform
+    #[clap(
+        long = "mitigations",
+        value_delimiter = ',',
+        help = "Mitigations applied to the platform, which the advisory policy may require"
+    )]
+    pub mitigations: Vec<String>,
+}
+
+const QUOTE_STATUS_OK: &str = "OK";
+
+fn run_verify_avr(cmd: &VerifyAVR) -> Result<()> {
+    let eavr: EndorsedAttestationVerificationReport =
+        serde_json::from_slice(&std::fs::read(&cmd.avr)?)?;
+    let now = Time::now();
+
+    attestation_report::verify_report(now, &eavr)
+        .map_err(|e| anyhow!("failed to verify the report signature: {:?}", e))?;
+    let avr = eavr.get_avr()?;
+    let quote = avr.parse_quote()?;
+    let mrenclave = Mrenclave::from(quote.get_mrenclave());
+
+    if let Some(expected) = cmd.mrenclave.as_ref() {
+        let expected = Mrenclave::try_from(hex::decode(
+            expected.strip_prefix("0x").unwrap_or(expected),
+        )?)?;
+        if expected != mrenclave {
+            bail!(
+                "mrenclave mismatch: expected={} actual={}",
+                expected,
+                mrenclave
+            );
+        }
+    }
+    if quote.status != QUOTE_STATUS_OK && !cmd.allowed_quote_statuses.contains(&quote.status) {
+        bail!("quote status is not allowed: status={}", quote.status);
+    }
+    let mut policy = AdvisoryPolicy::from_allowed_ids(cmd.allowed_advisory_ids.clone());
+    if let Some(path) = cmd.advisory_policy.as_ref() {
+        policy = policy.merge(load_advisory_policy(path)?);
+    }
+    policy
+        .evaluate(&avr.advisory_ids, now, &cmd.mitigations)
+        .map_err(|e| anyhow!("advisory IDs are not allowed: {}", e))?;
+    let expired_at = match cmd.key_expiration {
+        Some(secs) => {
+            let expired_at = (quote.attestation_time + Duration::from_secs(secs))?;
+            if now > expired_at {
+                bail!(
+                    "the report has expired: attestation_time={} expired_at={}",
+                    quote.attestation_time,
+                    expired_at
+                );
+            }
+            Some(expired_at.as_unix_timestamp_secs())
+        }
+        None => None,
+    };
+
+    println!(
+        "{}",
+        json! {{
+            "mrenclave": mrenclave.to_hex_string(),
+            "enclave_key": quote.get_enclave_key_address()?.to_hex_string(),
+            "isv_enclave_quote_status": quote.status,
+            "advisory_ids": avr.advisory_ids,
+            "attested_at": quote.attestation_time.as_unix_timestamp_secs(),
+            "expired_at": expired_at,
+        }}
+    );
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct ShowAdvisoryPolicy {
+    /// Path to a JSON file that contains the advisory policy
+    #[clap(
+        long = "advisory_policy",
+        help = "Path to a JSON file that contains the advisory policy"
+    )]
+    pub advisory_policy: std::path::PathBuf,
+
+    /// UNIX time in seconds at which the policy is evaluated
+    #[clap(
+        long = "at",
+        help = "UNIX time in seconds at which the policy is evaluated (default: now)"
+    )]
+    pub at: Option<u64>,
+}
+
+fn run_show_advisory_policy(cmd: &ShowAdvisoryPolicy) -> Result<()> {
+    let policy = load_advisory_policy(&cmd.advisory_policy)?;
+    let at = match cmd.at {
+        Some(secs) => Time::from_unix_timestamp_nanos(secs as u128 * 1_000_000_000)?,
+        None => Time::now(),
+    };
+    let effective = policy.effective_at(at);
+    let expired: Vec<&String> = policy
+        .advisories
+        .keys()
+        .filter(|id| !effective.advisories.contains_key(*id))
+        .collect();
+    println!(
+        "{}",
+        json! {{
+            "at": at.as_unix_timestamp_secs(),
+            "effective": effective,
+            "expired_advisory_ids": expired,
+        }}
+    );
+    Ok(())
+}
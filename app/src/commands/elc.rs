@@ -1,30 +1,102 @@
 use crate::{
+    bootstrap::{fetch_latest_states, fetch_unbonding_period, resolve_rpc_addrs, TrustParams},
     enclave::EnclaveLoader,
     opts::{EnclaveOpts, Opts},
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use chain_rpc::CometBftRpc;
 use clap::Parser;
-use enclave_api::{Enclave, EnclaveProtoAPI};
+use crypto::{Address, DigestAlgorithm, SignatureFormat, SignatureScheme};
+use ecall_commands::{
+    InitClientInput, MigrateClientInput, QueryCapabilitiesInput, UpdateClientParamsInput,
+};
+use enclave_api::{export_genesis, Enclave, EnclaveCommandAPI, EnclaveProtoAPI, GenesisParams};
+use lcp_proto::lcp::service::elc::v1::MsgUpdateClient;
+use lcp_types::{ClientParams, Time};
 use serde::de::DeserializeOwned;
+use serde_json::json;
+use service::ClientAcl;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use store::transaction::CommitStore;
 
 // `client` subcommand
 #[derive(Debug, Parser)]
 pub enum ELCCmd {
     #[clap(display_order = 1, about = "Create Light Client")]
-    CreateClient(ELCOpts),
+    CreateClient(CreateClientOpts),
     #[clap(display_order = 2, about = "Update Light Client")]
     UpdateClient(ELCOpts),
+    #[clap(
+        display_order = 3,
+        about = "Change the parameters of a Light Client with the operators' approvals"
+    )]
+    UpdateClientParams(UpdateClientParamsOpts),
+    #[clap(
+        display_order = 4,
+        about = "Sign the latest state of a Light Client again with a specified enclave key"
+    )]
+    ResignLatestState(ResignLatestStateOpts),
+    #[clap(
+        display_order = 5,
+        about = "Export a signed genesis document to instantiate the LCP client of a Light Client on a downstream chain"
+    )]
+    ExportGenesis(ExportGenesisOpts),
+    #[clap(
+        display_order = 6,
+        about = "Show the optional features that the implementation of a Light Client supports"
+    )]
+    Capabilities(CapabilitiesOpts),
+    #[clap(
+        display_order = 7,
+        about = "Check that the stored states of all Light Clients can be decoded by the current implementations"
+    )]
+    ValidateStore(ValidateStoreOpts),
+    #[clap(
+        display_order = 8,
+        about = "Migrate a Light Client to another registered implementation with the operators' approvals"
+    )]
+    MigrateClient(MigrateClientOpts),
 }
 
 impl ELCCmd {
-    fn opts(&self) -> &ELCOpts {
+    fn enclave_opts(&self) -> &EnclaveOpts {
         match self {
-            ELCCmd::CreateClient(opts) => opts,
-            ELCCmd::UpdateClient(opts) => opts,
+            ELCCmd::CreateClient(opts) => &opts.enclave,
+            ELCCmd::UpdateClient(opts) => &opts.enclave,
+            ELCCmd::UpdateClientParams(opts) => &opts.enclave,
+            ELCCmd::ResignLatestState(opts) => &opts.enclave,
+            ELCCmd::ExportGenesis(opts) => &opts.enclave,
+            ELCCmd::Capabilities(opts) => &opts.enclave,
+            ELCCmd::ValidateStore(opts) => &opts.enclave,
+            ELCCmd::MigrateClient(opts) => &opts.enclave,
         }
     }
+
+    /// Returns the client that the command changes and the owner token given to the command
+    fn owned_client(&self) -> Result<Option<(String, Option<&str>)>> {
+        Ok(match self {
+            ELCCmd::UpdateClient(opts) => Some((
+                opts.load::<MsgUpdateClient>()?.client_id,
+                opts.owner_token.as_deref(),
+            )),
+            ELCCmd::UpdateClientParams(opts) => {
+                Some((opts.client_id.clone(), opts.owner_token.as_deref()))
+            }
+            ELCCmd::ResignLatestState(opts) => {
+                Some((opts.client_id.clone(), opts.owner_token.as_deref()))
+            }
+            ELCCmd::MigrateClient(opts) => {
+                Some((opts.client_id.clone(), opts.owner_token.as_deref()))
+            }
+            ELCCmd::CreateClient(_)
+            | ELCCmd::ExportGenesis(_)
+            | ELCCmd::Capabilities(_)
+            | ELCCmd::ValidateStore(_) => None,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -35,6 +107,9 @@ pub struct ELCOpts {
     /// Path to the proto msg
     #[clap(long = "msg", help = "Path to proto msg")]
     pub msg: PathBuf,
+    /// Token of the owner of the client, which is required if the client was created with one
+    #[clap(long = "owner_token", help = "Token of the owner of the client")]
+    pub owner_token: Option<String>,
 }
 
 impl ELCOpts {
@@ -44,6 +119,400 @@ impl ELCOpts {
     }
 }
 
+/// CreateClientOpts creates a client from a proto msg, or bootstraps it from the latest state of a chain
+#[derive(Clone, Debug, Parser)]
+pub struct CreateClientOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Path to the proto msg
+    #[clap(
+        long = "msg",
+        conflicts_with_all = &["chain", "rpc_addr"],
+        required_unless_present_any = &["chain", "rpc_addr"],
+        help = "Path to proto msg"
+    )]
+    pub msg: Option<PathBuf>,
+    /// Chain-id of the counterparty chain, which is looked up in the cosmos chain registry if `--rpc_addr` is not given
+    #[clap(
+        long = "chain",
+        help = "Chain-id of the chain to bootstrap the client from"
+    )]
+    pub chain: Option<String>,
+    /// RPC endpoints of the counterparty chain, which are failed over in order
+    #[clap(
+        long = "rpc_addr",
+        help = "Tendermint RPC address of the chain (repeatable). The following ones are tried if the first one fails"
+    )]
+    pub rpc_addr: Vec<String>,
+    /// Minimum interval between the requests to each RPC endpoint
+    #[clap(
+        long = "rpc_min_interval",
+        default_value = "0",
+        help = "Minimum interval in milliseconds between the requests to each RPC endpoint"
+    )]
+    pub rpc_min_interval: u64,
+    /// An enclave key that signs the message of the client creation
+    #[clap(
+        long = "signer",
+        required_unless_present = "msg",
+        help = "An enclave key that signs the message"
+    )]
+    pub signer: Option<String>,
+    /// Trusting period of the client in seconds, which must be shorter than the unbonding period that the chain reports
+    #[clap(
+        long = "trusting_period",
+        help = "Trusting period of the client in seconds. Defaults to 2/3 of the unbonding period of the chain"
+    )]
+    pub trusting_period: Option<u64>,
+    /// Maximum clock drift between the chain and LCP in seconds
+    #[clap(
+        long = "max_clock_drift",
+        default_value = "10",
+        help = "Maximum clock drift in seconds"
+    )]
+    pub max_clock_drift: u64,
+    /// Commitment prefix of the IBC store
+    #[clap(
+        long = "commitment_prefix",
+        default_value = "ibc",
+        help = "Commitment prefix of the IBC store"
+    )]
+    pub commitment_prefix: String,
+    /// Sign a message that attests the initial state of the client
+    #[clap(
+        long = "prove_genesis",
+        help = "Sign a message that attests the initial state of the client"
+    )]
+    pub prove_genesis: bool,
+    /// Digest algorithm of the commitments of the client
+    #[clap(
+        long = "digest_algorithm",
+        help = "Digest algorithm of the commitments: `keccak256` or `sha256`. Defaults to `keccak256`"
+    )]
+    pub digest_algorithm: Option<String>,
+    /// Format of the signatures over the commitments of the client
+    #[clap(
+        long = "signature_format",
+        help = "Signature format of the commitments: `recoverable`, `ethereum` or `compact`. Defaults to `recoverable`"
+    )]
+    pub signature_format: Option<String>,
+    /// Version of the message schema that the verifier of the client accepts
+    #[clap(
+        long = "message_schema_version",
+        help = "Version of the message schema that the verifier of the client accepts. Defaults to 1, which cannot carry an expiry"
+    )]
+    pub message_schema_version: Option<u16>,
+}
+
+impl CreateClientOpts {
+    fn signature_scheme(&self) -> Result<Option<SignatureScheme>> {
+        if self.digest_algorithm.is_none() && self.signature_format.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(SignatureScheme {
+            digest: self
+                .digest_algorithm
+                .as_deref()
+                .map(DigestAlgorithm::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+            format: self
+                .signature_format
+                .as_deref()
+                .map(SignatureFormat::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+        }))
+    }
+
+    fn trust_params(&self, unbonding_period: Duration) -> Result<TrustParams> {
+        TrustParams::new(
+            unbonding_period,
+            self.trusting_period.map(Duration::from_secs),
+            Duration::from_secs(self.max_clock_drift),
+        )
+    }
+
+    /// Build the input from the latest state of the chain
+    fn bootstrap(&self) -> Result<InitClientInput> {
+        let signer = match self.signer.as_ref() {
+            Some(signer) => Address::from_hex_string(signer)?,
+            None => bail!("`--signer` is required to bootstrap a client"),
+        };
+        let rt = Arc::new(tokio::runtime::Runtime::new()?);
+        let rpc_addrs = if !self.rpc_addr.is_empty() {
+            self.rpc_addr.clone()
+        } else if let Some(chain) = self.chain.as_ref() {
+            resolve_rpc_addrs(&rt, chain)?
+        } else {
+            bail!("either `--chain` or `--rpc_addr` is required")
+        };
+        let rpc = CometBftRpc::new(&rpc_addrs, rt)?
+            .with_min_interval(Duration::from_millis(self.rpc_min_interval));
+        let params = self.trust_params(fetch_unbonding_period(&rpc)?)?;
+        let states = fetch_latest_states(&rpc, self.chain.as_deref(), &params)?;
+        Ok(InitClientInput {
+            any_client_state: states.client_state,
+            any_consensus_state: states.consensus_state,
+            commitment_prefix: Some(
+                self.commitment_prefix
+                    .as_str()
+                    .try_into()
+                    .map_err(|e| anyhow!("invalid commitment prefix: {}", e))?,
+            ),
+            verification_mode: None,
+            prove_genesis: self.prove_genesis,
+            max_clock_drift: None,
+            vote_extension_policy: None,
+            signature_scheme: self.signature_scheme()?,
+            message_schema_version: self.message_schema_version,
+            current_timestamp: Time::now(),
+            signer,
+        })
+    }
+}
+
+/// UpdateClientParamsOpts changes the parameters of a client, e.g. after the chain changes its unbonding period
+#[derive(Clone, Debug, Parser)]
+pub struct UpdateClientParamsOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Client ID of the client to change
+    #[clap(long = "client_id", help = "Client ID of the client to change")]
+    pub client_id: String,
+    /// Trusting period of the client in seconds
+    #[clap(
+        long = "trusting_period",
+        help = "Trusting period of the client in seconds"
+    )]
+    pub trusting_period: Option<u64>,
+    /// Maximum clock drift between the chain and LCP in seconds
+    #[clap(long = "max_clock_drift", help = "Maximum clock drift in seconds")]
+    pub max_clock_drift: Option<u64>,
+    /// Path to a JSON array of the hex-encoded protobuf `cosmos.ics23.v1.ProofSpec`s
+    #[clap(
+        long = "proof_specs",
+        help = "Path to a JSON array of hex-encoded proof specs"
+    )]
+    pub proof_specs: Option<PathBuf>,
+    /// An enclave key that signs the notice of the change
+    #[clap(long = "signer", help = "An enclave key that signs the message")]
+    pub signer: String,
+    /// Token of the owner of the client, which is required if the client was created with one
+    #[clap(long = "owner_token", help = "Token of the owner of the client")]
+    pub owner_token: Option<String>,
+}
+
+impl UpdateClientParamsOpts {
+    fn input(&self) -> Result<UpdateClientParamsInput> {
+        let proof_specs = match self.proof_specs.as_ref() {
+            Some(path) => {
+                let specs: Vec<String> = serde_json::from_slice(&std::fs::read(path)?)?;
+                Some(
+                    specs
+                        .iter()
+                        .map(|spec| hex::decode(spec.trim_start_matches("0x")))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            None => None,
+        };
+        let params = ClientParams {
+            trusting_period: self.trusting_period.map(Duration::from_secs),
+            max_clock_drift: self.max_clock_drift.map(Duration::from_secs),
+            proof_specs,
+        };
+        if params.is_empty() {
+            bail!("at least one of `--trusting_period`, `--max_clock_drift` and `--proof_specs` is required");
+        }
+        Ok(UpdateClientParamsInput {
+            client_id: self.client_id.parse()?,
+            params,
+            current_timestamp: Time::now(),
+            signer: Address::from_hex_string(&self.signer)?,
+        })
+    }
+}
+
+/// MigrateClientOpts moves a client to another implementation, e.g. to a newer version of the same client type
+#[derive(Clone, Debug, Parser)]
+pub struct MigrateClientOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Client ID of the client to migrate
+    #[clap(long = "client_id", help = "Client ID of the client to migrate")]
+    pub client_id: String,
+    /// Type URL of the client state that the target implementation is registered for
+    #[clap(
+        long = "target_type_url",
+        help = "Type URL of the client state of the target implementation"
+    )]
+    pub target_type_url: String,
+    /// Version of the target implementation. The latest registered version is used if not given
+    #[clap(
+        long = "target_version",
+        help = "Version of the target implementation (default: the latest)"
+    )]
+    pub target_version: Option<u32>,
+    /// An enclave key that signs the notice of the migration
+    #[clap(long = "signer", help = "An enclave key that signs the message")]
+    pub signer: String,
+    /// Token of the owner of the client, which is required if the client was created with one
+    #[clap(long = "owner_token", help = "Token of the owner of the client")]
+    pub owner_token: Option<String>,
+}
+
+impl MigrateClientOpts {
+    fn input(&self) -> Result<MigrateClientInput> {
+        Ok(MigrateClientInput {
+            client_id: self.client_id.parse()?,
+            target_type_url: self.target_type_url.clone(),
+            target_version: self.target_version,
+            current_timestamp: Time::now(),
+            signer: Address::from_hex_string(&self.signer)?,
+        })
+    }
+}
+
+/// ResignLatestStateOpts signs the latest state of a client with a key that the downstream chain has just registered
+#[derive(Clone, Debug, Parser)]
+pub struct ResignLatestStateOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Client ID of the client to sign the latest state of
+    #[clap(long = "client_id", help = "Client ID of the client")]
+    pub client_id: String,
+    /// An attested enclave key that signs the message
+    #[clap(
+        long = "signer",
+        help = "An attested enclave key that signs the message"
+    )]
+    pub signer: String,
+    /// Token of the owner of the client, which is required if the client was created with one
+    #[clap(long = "owner_token", help = "Token of the owner of the client")]
+    pub owner_token: Option<String>,
+}
+
+/// ExportGenesisOpts exports what a downstream chain needs to instantiate the LCP client of a client
+#[derive(Clone, Debug, Parser)]
+pub struct ExportGenesisOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Client ID of the client to export
+    #[clap(long = "client_id", help = "Client ID of the client")]
+    pub client_id: String,
+    /// An enclave key attested with IAS that signs the latest state of the client
+    #[clap(
+        long = "signer",
+        help = "An enclave key attested with IAS that signs the latest state"
+    )]
+    pub signer: String,
+    /// Key expiration in seconds from the attestation time
+    #[clap(
+        long = "key_expiration",
+        default_value = "604800",
+        help = "Key expiration in seconds from the attestation time"
+    )]
+    pub key_expiration: u64,
+    /// Quote statuses accepted in addition to `OK`
+    #[clap(
+        long = "allowed_quote_statuses",
+        value_delimiter = ',',
+        help = "Quote statuses accepted in addition to `OK`"
+    )]
+    pub allowed_quote_statuses: Vec<String>,
+    /// Intel security advisory IDs accepted in the report
+    #[clap(
+        long = "allowed_advisory_ids",
+        value_delimiter = ',',
+        help = "Intel security advisory IDs accepted in the report"
+    )]
+    pub allowed_advisory_ids: Vec<String>,
+    /// Digest algorithm of the commitments of the client
+    #[clap(
+        long = "digest_algorithm",
+        help = "Digest algorithm of the commitments, which must match the one of the client. Defaults to `keccak256`"
+    )]
+    pub digest_algorithm: Option<String>,
+    /// Format of the signatures over the commitments of the client
+    #[clap(
+        long = "signature_format",
+        help = "Signature format of the commitments, which must match the one of the client. Defaults to `recoverable`"
+    )]
+    pub signature_format: Option<String>,
+    /// Path to write the document to
+    #[clap(
+        long = "output",
+        help = "Path to write the document to. Defaults to stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+impl ExportGenesisOpts {
+    fn params(&self) -> Result<GenesisParams> {
+        // validate the names here, as the document carries them as they are
+        if let Some(digest) = self.digest_algorithm.as_deref() {
+            DigestAlgorithm::from_str(digest)?;
+        }
+        if let Some(format) = self.signature_format.as_deref() {
+            SignatureFormat::from_str(format)?;
+        }
+        Ok(GenesisParams {
+            key_expiration: self.key_expiration,
+            allowed_quote_statuses: self.allowed_quote_statuses.clone(),
+            allowed_advisory_ids: self.allowed_advisory_ids.clone(),
+            digest_algorithm: self.digest_algorithm.clone().unwrap_or_default(),
+            signature_format: self.signature_format.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// CapabilitiesOpts selects a Light Client implementation by a client or by the type of its client state
+#[derive(Clone, Debug, Parser)]
+pub struct CapabilitiesOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Client ID of the client whose implementation is queried
+    #[clap(
+        long = "client_id",
+        conflicts_with = "client_state_type_url",
+        help = "Client ID of the client whose implementation is queried"
+    )]
+    pub client_id: Option<String>,
+    /// Type URL of the client state, whose latest implementation is queried
+    #[clap(
+        long = "client_state_type_url",
+        help = "Type URL of the client state, e.g. `/ibc.lightclients.tendermint.v1.ClientState`"
+    )]
+    pub client_state_type_url: Option<String>,
+}
+
+impl CapabilitiesOpts {
+    fn input(&self) -> Result<QueryCapabilitiesInput> {
+        match (self.client_id.as_ref(), self.client_state_type_url.as_ref()) {
+            (Some(client_id), _) => Ok(QueryCapabilitiesInput::ClientId(client_id.parse()?)),
+            (None, Some(type_url)) => {
+                Ok(QueryCapabilitiesInput::ClientStateTypeUrl(type_url.clone()))
+            }
+            (None, None) => bail!("either `--client_id` or `--client_state_type_url` is required"),
+        }
+    }
+}
+
+/// ValidateStoreOpts checks the stored states after an upgrade of the enclave
+#[derive(Clone, Debug, Parser)]
+pub struct ValidateStoreOpts {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+}
+
 impl ELCCmd {
     pub fn run<S, L>(&self, opts: &Opts, enclave_loader: L) -> Result<()>
     where
@@ -51,18 +520,108 @@ impl ELCCmd {
         Enclave<S>: EnclaveProtoAPI<S>,
         L: EnclaveLoader<S>,
     {
-        let elc_opts = self.opts();
-        let enclave = enclave_loader.load(
-            opts,
-            elc_opts.enclave.path.as_ref(),
-            elc_opts.enclave.is_debug(),
-        )?;
+        if let Some((client_id, token)) = self.owned_client()? {
+            ClientAcl::load(&opts.get_home())?.authorize(&client_id, token)?;
+        }
+        let enclave_opts = self.enclave_opts();
+        let enclave =
+            enclave_loader.load(opts, enclave_opts.path.as_ref(), enclave_opts.is_debug())?;
+        let _ = enclave.start_instance()?;
         match self {
-            Self::CreateClient(_) => {
-                let _ = enclave.proto_create_client(elc_opts.load()?)?;
+            Self::CreateClient(cmd) => match cmd.msg.as_ref() {
+                Some(msg) => {
+                    let bz = std::fs::read(msg)?;
+                    let _ = enclave.proto_create_client(serde_json::from_slice(&bz)?)?;
+                }
+                None => {
+                    let res = enclave.init_client(cmd.bootstrap()?)?;
+                    println!(
+                        "{}",
+                        json!({
+                            "client_id": res.client_id.to_string(),
+                            "message": res
+                                .proof
+                                .message()
+                                .map_err(|e| anyhow!("invalid proxy message: {}", e))?
+                                .to_string(),
+                        })
+                    );
+                }
+            },
+            Self::UpdateClient(cmd) => {
+                let _ = enclave.proto_update_client(cmd.load()?)?;
+            }
+            Self::UpdateClientParams(cmd) => {
+                let res = enclave.update_client_params(cmd.input()?)?;
+                println!(
+                    "{}",
+                    json!({
+                        "message": hex::encode(&res.0.message),
+                        "signer": res.0.signer.to_hex_string(),
+                        "signature": hex::encode(&res.0.signature),
+                    })
+                );
+            }
+            Self::MigrateClient(cmd) => {
+                let res = enclave.migrate_client(cmd.input()?)?;
+                println!(
+                    "{}",
+                    json!({
+                        "message": hex::encode(&res.0.message),
+                        "signer": res.0.signer.to_hex_string(),
+                        "signature": hex::encode(&res.0.signature),
+                    })
+                );
+            }
+            Self::ResignLatestState(cmd) => {
+                let res = enclave.resign_latest_state(
+                    cmd.client_id.parse()?,
+                    Address::from_hex_string(&cmd.signer)?,
+                )?;
+                println!(
+                    "{}",
+                    json!({
+                        "message": hex::encode(&res.0.message),
+                        "signer": res.0.signer.to_hex_string(),
+                        "signature": hex::encode(&res.0.signature),
+                    })
+                );
+            }
+            Self::ExportGenesis(cmd) => {
+                let doc = export_genesis(
+                    &enclave,
+                    cmd.client_id.parse()?,
+                    Address::from_hex_string(&cmd.signer)?,
+                    cmd.params()?,
+                )?;
+                let json = serde_json::to_string_pretty(&doc)?;
+                match cmd.output.as_ref() {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => println!("{}", json),
+                }
+            }
+            Self::Capabilities(cmd) => {
+                let res = enclave.query_capabilities(cmd.input()?)?;
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&json!({
+                        "client_type": res.client_type,
+                        "version": res.version,
+                        "capabilities": res.capabilities,
+                    }))?
+                );
             }
-            Self::UpdateClient(_) => {
-                let _ = enclave.proto_update_client(elc_opts.load()?)?;
+            Self::ValidateStore(_) => {
+                let clients = enclave.validate_all_clients()?;
+                println!("{}", serde_json::to_string_pretty(&clients)?);
+                let incompatible = clients.iter().filter(|c| !c.is_compatible()).count();
+                if incompatible > 0 {
+                    bail!(
+                        "the stored states of {} of {} clients are incompatible with the current implementations",
+                        incompatible,
+                        clients.len()
+                    );
+                }
             }
         }
         Ok(())
@@ -4,11 +4,16 @@ use crate::{
 };
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use ecall_commands::GenerateEnclaveKeyInput;
-use enclave_api::{Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
-use lcp_types::Mrenclave;
+use core::str::FromStr;
+use crypto::Address;
+use ecall_commands::{GenerateEnclaveKeyInput, PageRequest};
+use enclave_api::{
+    Enclave, EnclaveCommandAPI, EnclavePrimitiveAPI, EnclaveProtoAPI, UPDATE_HISTORY_LIMIT,
+};
+use lcp_types::{ClientId, Height, Mrenclave};
 use log::*;
 use serde_json::json;
+use std::time::Duration;
 use store::transaction::CommitStore;
 
 // `enclave` subcommand
@@ -22,6 +27,33 @@ pub enum EnclaveCmd {
     PruneKeys(PruneKeys),
     #[clap(about = "Print metadata of the enclave", display_order = 4)]
     Metadata(Metadata),
+    #[clap(
+        about = "Begin a key rotation: the incoming key co-signs commitments of the outgoing key",
+        display_order = 5
+    )]
+    BeginKeyRotation(BeginKeyRotation),
+    #[clap(about = "End a key rotation", display_order = 6)]
+    EndKeyRotation(EndKeyRotation),
+    #[clap(
+        about = "Show the history of executed light client commands",
+        display_order = 7
+    )]
+    CommandHistory(CommandHistory),
+    #[clap(
+        about = "Show the signed updates of a client to resubmit them",
+        display_order = 8
+    )]
+    UpdateHistory(UpdateHistory),
+    #[clap(
+        about = "Make the most recently attested standby key available",
+        display_order = 9
+    )]
+    ActivateStandbyKey(ActivateStandbyKey),
+    #[clap(
+        about = "Show or set the retention policy of the update records of a client",
+        display_order = 10
+    )]
+    UpdateRetention(UpdateRetention),
 }
 
 impl EnclaveCmd {
@@ -49,7 +81,31 @@ impl EnclaveCmd {
                 enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
                 cmd,
             ),
-            Self::Metadata(cmd) => run_print_metadata(opts, cmd),
+            Self::Metadata(cmd) => run_print_metadata(opts, cmd, enclave_loader),
+            Self::BeginKeyRotation(cmd) => run_begin_key_rotation(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
+            Self::EndKeyRotation(cmd) => run_end_key_rotation(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
+            Self::CommandHistory(cmd) => run_command_history(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
+            Self::UpdateHistory(cmd) => run_update_history(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
+            Self::ActivateStandbyKey(cmd) => run_activate_standby_key(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
+            Self::UpdateRetention(cmd) => run_update_retention(
+                enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?,
+                cmd,
+            ),
         }
     }
 }
@@ -145,24 +201,285 @@ fn run_prune_keys<E: EnclaveCommandAPI<S>, S: CommitStore>(
 }
 
 #[derive(Clone, Debug, Parser, PartialEq)]
-pub struct Metadata {
+pub struct BeginKeyRotation {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Address of the enclave key to be rotated out
+    #[clap(
+        long = "outgoing",
+        help = "Address of the enclave key to be rotated out"
+    )]
+    pub outgoing: String,
+    /// Address of the enclave key to be rotated in
+    #[clap(
+        long = "incoming",
+        help = "Address of the enclave key to be rotated in"
+    )]
+    pub incoming: String,
+}
+
+fn run_begin_key_rotation<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &BeginKeyRotation,
+) -> Result<()> {
+    let outgoing = Address::from_hex_string(&input.outgoing)?;
+    let incoming = Address::from_hex_string(&input.incoming)?;
+    enclave
+        .get_key_manager()
+        .begin_key_rotation(outgoing, incoming)?;
+    info!(
+        "began key rotation: outgoing={} incoming={}",
+        outgoing, incoming
+    );
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct EndKeyRotation {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Address of the enclave key that was rotated out
+    #[clap(
+        long = "outgoing",
+        help = "Address of the enclave key that was rotated out"
+    )]
+    pub outgoing: String,
+}
+
+fn run_end_key_rotation<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &EndKeyRotation,
+) -> Result<()> {
+    let outgoing = Address::from_hex_string(&input.outgoing)?;
+    if !enclave.get_key_manager().end_key_rotation(outgoing)? {
+        return Err(anyhow!(
+            "no key rotation in progress: outgoing={}",
+            outgoing
+        ));
+    }
+    info!("ended key rotation: outgoing={}", outgoing);
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct CommandHistory {
     /// Options for enclave
     #[clap(flatten)]
     pub enclave: EnclaveOpts,
+    #[clap(
+        long = "limit",
+        default_value = "100",
+        help = "Maximum number of records to show"
+    )]
+    pub limit: u64,
 }
 
-fn run_print_metadata(opts: &Opts, cmd: &Metadata) -> Result<()> {
-    let metadata = host::sgx_get_metadata(
-        cmd.enclave
-            .path
-            .clone()
-            .unwrap_or_else(|| opts.default_enclave()),
-    )?;
+fn run_command_history<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &CommandHistory,
+) -> Result<()> {
+    let records: Vec<_> = enclave
+        .command_history(input.limit)?
+        .into_iter()
+        .map(|r| {
+            json! {{
+                "seq": r.seq,
+                "timestamp": r.timestamp.as_unix_timestamp_secs(),
+                "command_type": r.command_type,
+                "client_id": r.client_id.map(|id| id.to_string()),
+                "input_digest": hex::encode(r.input_digest),
+                "result_digest": hex::encode(r.result_digest),
+                "pre_state_digest": r.pre_state_digest.map(hex::encode),
+                "post_state_digest": r.post_state_digest.map(hex::encode),
+            }}
+        })
+        .collect();
+    println!("{}", serde_json::to_string(&records).unwrap());
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct UpdateHistory {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    #[clap(long = "client_id", help = "Client ID of the updates")]
+    pub client_id: String,
+    /// If specified, only the latest update to this height is shown
+    #[clap(
+        long = "height",
+        conflicts_with_all = &["offset", "limit"],
+        help = "Post height of the update to show, e.g. `0-100`"
+    )]
+    pub height: Option<String>,
+    #[clap(
+        long = "offset",
+        default_value = "0",
+        help = "Number of the latest records to skip"
+    )]
+    pub offset: u64,
+    #[clap(
+        long = "limit",
+        default_value = "100",
+        help = "Maximum number of records to show"
+    )]
+    pub limit: u64,
+}
+
+fn run_update_history<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &UpdateHistory,
+) -> Result<()> {
+    let client_id = ClientId::from_str(&input.client_id)?;
+    let records = match input.height.as_ref() {
+        Some(height) => enclave
+            .update_history_at(&client_id, &Height::from_str(height)?)?
+            .into_iter()
+            .collect(),
+        None => enclave.update_history(&client_id, PageRequest::new(input.offset, input.limit)?)?,
+    };
+    let records: Vec<_> = records
+        .into_iter()
+        .map(|r| {
+            json! {{
+                "seq": r.seq,
+                "client_id": r.client_id.to_string(),
+                "prev_height": r.prev_height.map(|h| h.to_string()),
+                "post_height": r.post_height.to_string(),
+                "recorded_at": r.recorded_at.as_unix_timestamp_secs(),
+                "message": format!("0x{}", hex::encode(&r.proof.message)),
+                "signer": r.proof.signer.to_hex_string(),
+                "signature": format!("0x{}", hex::encode(&r.proof.signature)),
+            }}
+        })
+        .collect();
+    println!("{}", serde_json::to_string(&records).unwrap());
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct UpdateRetention {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    #[clap(long = "client_id", help = "Client ID of the updates")]
+    pub client_id: String,
+    /// If neither this nor `max_age` is specified, the current policy is shown
+    #[clap(
+        long = "max_records",
+        help = "Number of the latest records to keep. Defaults to the limit of the history"
+    )]
+    pub max_records: Option<u64>,
+    #[clap(
+        long = "max_age",
+        help = "Seconds before the latest record after which the records are pruned"
+    )]
+    pub max_age: Option<u64>,
+}
+
+fn run_update_retention<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &UpdateRetention,
+) -> Result<()> {
+    let client_id = ClientId::from_str(&input.client_id)?;
+    let policy = if input.max_records.is_none() && input.max_age.is_none() {
+        enclave.update_retention(&client_id)?
+    } else {
+        let policy = enclave_api::UpdateRetention {
+            max_records: input.max_records.unwrap_or(UPDATE_HISTORY_LIMIT),
+            max_age: input.max_age.map(Duration::from_secs),
+        };
+        let pruned = enclave.set_update_retention(&client_id, policy)?;
+        info!(
+            "set the retention policy of the update records: client_id={} pruned={}",
+            client_id, pruned
+        );
+        policy
+    };
     println!(
         "{}",
         json! {{
-            "mrenclave": format!("{}", Mrenclave::from(metadata.enclave_css.body.enclave_hash.m)),
+            "client_id": client_id.to_string(),
+            "max_records": policy.max_records,
+            "max_age": policy.max_age.map(|d| d.as_secs()),
         }}
     );
     Ok(())
 }
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct ActivateStandbyKey {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// If specified, a key rotation from this key to the activated key begins
+    #[clap(
+        long = "outgoing",
+        help = "Address of the enclave key to be rotated out to the activated key"
+    )]
+    pub outgoing: Option<String>,
+}
+
+fn run_activate_standby_key<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: E,
+    input: &ActivateStandbyKey,
+) -> Result<()> {
+    let outgoing = input
+        .outgoing
+        .as_deref()
+        .map(Address::from_hex_string)
+        .transpose()?;
+    let km = enclave.get_key_manager();
+    let mrenclave: Mrenclave = enclave.metadata()?.enclave_css.body.enclave_hash.m.into();
+    let key = km
+        .activate_standby_key(mrenclave)?
+        .ok_or_else(|| anyhow!("no attested standby keys found: mrenclave={}", mrenclave))?;
+    info!("activated standby key: address={}", key.address);
+    if let Some(outgoing) = outgoing {
+        km.begin_key_rotation(outgoing, key.address)?;
+        info!(
+            "began key rotation: outgoing={} incoming={}",
+            outgoing, key.address
+        );
+    }
+    println!("{}", key.address);
+    Ok(())
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct Metadata {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Load the enclave and print the metadata embedded at build time
+    #[clap(
+        long = "build_info",
+        help = "Load the enclave and print the metadata embedded at build time"
+    )]
+    pub build_info: bool,
+}
+
+fn run_print_metadata<S, L>(opts: &Opts, cmd: &Metadata, enclave_loader: L) -> Result<()>
+where
+    S: CommitStore,
+    Enclave<S>: EnclaveProtoAPI<S>,
+    L: EnclaveLoader<S>,
+{
+    let path = cmd
+        .enclave
+        .path
+        .clone()
+        .unwrap_or_else(|| opts.default_enclave());
+    let metadata = host::sgx_get_metadata(&path)?;
+    let mut res = json! {{
+        "mrenclave": format!("{}", Mrenclave::from(metadata.enclave_css.body.enclave_hash.m)),
+    }};
+    if cmd.build_info {
+        let enclave = enclave_loader.load(opts, Some(&path), cmd.enclave.is_debug())?;
+        res["build_info"] = serde_json::to_value(enclave.query_build_info()?.build_info)?;
+    }
+    println!("{}", res);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::enclave::EnclaveLoader;
+use crate::opts::{EnclaveOpts, Opts};
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use ecall_commands::GenerateEnclaveKeyInput;
+use enclave_api::{read_command_log, replay_command, Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
+use log::*;
+use serde_json::json;
+use std::path::PathBuf;
+use store::transaction::CommitStore;
+
+/// `replay` subcommand
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct ReplayCmd {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Path to the command log written by `lcp service start --command_log`
+    #[clap(help = "Path to the command log to replay")]
+    pub command_log: PathBuf,
+    /// Stop at the first command whose result diverges from the recorded one
+    #[clap(
+        long = "stop_on_mismatch",
+        help = "Stop at the first command whose result diverges from the recorded one"
+    )]
+    pub stop_on_mismatch: bool,
+}
+
+impl ReplayCmd {
+    /// Replay the logged commands in order against a fresh enclave and compare the resulting client states
+    ///
+    /// The enclave must run with an empty in-memory store and a temporary home, which `CliCmd` sets up.
+    pub fn run<S, L>(&self, opts: &Opts, enclave_loader: L) -> Result<()>
+    where
+        S: CommitStore,
+        Enclave<S>: EnclaveProtoAPI<S>,
+        L: EnclaveLoader<S>,
+    {
+        let entries = read_command_log(&self.command_log)
+            .map_err(|e| anyhow!("failed to read the command log: err={:?}", e))?;
+        info!(
+            "replay the command log: path={:?} commands={}",
+            self.command_log,
+            entries.len()
+        );
+        let enclave =
+            enclave_loader.load(opts, self.enclave.path.as_ref(), self.enclave.is_debug())?;
+        let _ = enclave.start_instance()?;
+        let signer = enclave
+            .generate_enclave_key(GenerateEnclaveKeyInput::default())
+            .map_err(|e| anyhow!("failed to generate an enclave key: {:?}", e))?
+            .pub_key
+            .as_address();
+
+        let (mut replayed, mut mismatches) = (0, 0);
+        for entry in entries.iter() {
+            let outcome = replay_command(&enclave, entry, signer)
+                .map_err(|e| anyhow!("failed to replay the command: err={:?}", e))?;
+            replayed += 1;
+            println!(
+                "{}",
+                json!({
+                    "seq": outcome.seq,
+                    "command_type": outcome.command_type,
+                    "client_id": outcome.client_id.as_ref().map(|id| id.to_string()),
+                    "expected_state_digest": outcome.expected_state_digest.map(hex::encode),
+                    "actual_state_digest": outcome.actual_state_digest.map(hex::encode),
+                    "error": outcome.error,
+                    "match": outcome.is_match(),
+                })
+            );
+            if !outcome.is_match() {
+                mismatches += 1;
+                if self.stop_on_mismatch {
+                    break;
+                }
+            }
+        }
+        println!(
+            "{}",
+            json!({
+                "total": entries.len(),
+                "replayed": replayed,
+                "mismatches": mismatches,
+            })
+        );
+        if mismatches > 0 {
+            bail!(
+                "the replayed state diverged from the recorded one: mismatches={}",
+                mismatches
+            );
+        }
+        Ok(())
+    }
+}
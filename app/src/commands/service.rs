@@ -1,11 +1,22 @@
 use crate::enclave::EnclaveLoader;
 use crate::opts::{EnclaveOpts, Opts};
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use clap::Parser;
-use enclave_api::{Enclave, EnclaveProtoAPI};
+use crypto::Address;
+use ecall_commands::{IASRemoteAttestationInput, QuoteSignType};
+use enclave_api::{
+    CircuitBreaker, CommandLog, Enclave, EnclaveCommandAPI, EnclaveProtoAPI, HealthStatus,
+    ENCLAVE_TCS_NUM,
+};
+use host_environment::credentials;
 use log::*;
-use service::{run_service, AppService};
+use service::{
+    parse_compression_encoding, run_service, AppService, CommandHook, EventWatcherConfig,
+    KeyPoolConfig, ProofCacheConfig, PrunerConfig, ResponseCompression, WebhookHook,
+};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use store::transaction::CommitStore;
 use tokio::runtime::Builder;
 
@@ -35,6 +46,211 @@ pub struct Start {
         help = "Worker thread number the tokio `Runtime` will use"
     )]
     pub threads: Option<usize>,
+    /// Interval in seconds between the runs of the pruner
+    /// If not specified, expired consensus states are never pruned automatically.
+    #[clap(
+        long = "prune_interval",
+        help = "Interval in seconds between the runs of the pruner"
+    )]
+    pub prune_interval: Option<u64>,
+    /// Consensus states are retained for the trusting period of the client plus this margin
+    #[clap(
+        long = "prune_margin",
+        default_value = "3600",
+        help = "Margin in seconds added to the trusting period to determine the retention of consensus states"
+    )]
+    pub prune_margin: u64,
+    /// Interval in seconds between the checks for the key attestations and the clients near expiry
+    /// If not specified, these events are never streamed.
+    #[clap(
+        long = "event_watch_interval",
+        help = "Interval in seconds between the checks for the key attestations and the clients near expiry"
+    )]
+    pub event_watch_interval: Option<u64>,
+    /// A client is reported as near expiry when its latest state expires within this duration
+    #[clap(
+        long = "expiry_threshold",
+        default_value = "3600",
+        help = "Duration in seconds before the expiry of the latest state at which a client is reported as near expiry"
+    )]
+    pub expiry_threshold: u64,
+    /// The mutating commands are halted after this number of consecutive failed commits to the store
+    #[clap(
+        long = "max_commit_failures",
+        default_value = "3",
+        help = "Number of consecutive failed commits to the store after which the mutating commands are halted"
+    )]
+    pub max_commit_failures: u32,
+    /// Shell command that is run when the mutating commands are halted
+    /// The health status is passed in the `LCP_HEALTH_STATUS` environment variable.
+    #[clap(
+        long = "failover_command",
+        help = "Shell command that is run when the mutating commands are halted"
+    )]
+    pub failover_command: Option<String>,
+    /// URLs to POST the commitment of each successful update to
+    #[clap(
+        long = "on_update_webhook",
+        help = "URL to POST the JSON of the commitment of each successful update_client and aggregate_messages to (repeatable)"
+    )]
+    pub on_update_webhooks: Vec<String>,
+    /// Shell commands that receive the commitment of each successful update on the stdin
+    /// e.g. a command that publishes it to a message queue
+    #[clap(
+        long = "on_update_command",
+        help = "Shell command that receives the JSON of the commitment of each successful update_client and aggregate_messages on the stdin (repeatable)"
+    )]
+    pub on_update_commands: Vec<String>,
+    /// Timeout in seconds of a webhook request
+    #[clap(
+        long = "on_update_webhook_timeout",
+        default_value = "10",
+        help = "Timeout in seconds of a request to the on-update webhooks"
+    )]
+    pub on_update_webhook_timeout: u64,
+    /// Maximum number of concurrent requests to the enclave
+    /// If specified, the update requests are served before the queries while the requests wait for a slot.
+    #[clap(
+        long = "max_concurrent_requests",
+        help = "Maximum number of concurrent requests to the enclave, over which update_client is prioritized over the queries"
+    )]
+    pub max_concurrent_requests: Option<usize>,
+    /// Number of the concurrent ecalls that verify the messages of an aggregate_messages request
+    /// It is clamped to the number of the TCSs of the enclave.
+    #[clap(
+        long = "aggregation_parallelism",
+        help = "Number of the concurrent ecalls that verify the messages of aggregate_messages, which is clamped to the TCSNum of the enclave"
+    )]
+    pub aggregation_parallelism: Option<usize>,
+    /// Validator set overlap below which an update of a tendermint client is warned of
+    #[clap(
+        long = "drift_warning_ratio",
+        help = "Ratio of the trusted voting power remaining in the new validator set, below which an update of a tendermint client is warned of (default: 2/3)"
+    )]
+    pub drift_warning_ratio: Option<f64>,
+    /// Compression encodings of the responses, which are used if the client accepts one of them
+    #[clap(
+        long = "response_compression",
+        value_delimiter = ',',
+        help = "Comma-separated compression encodings of the responses that the clients can negotiate (supported: gzip)"
+    )]
+    pub response_compression: Vec<String>,
+    /// Responses smaller than this size are sent uncompressed
+    #[clap(
+        long = "min_compressed_response_size",
+        default_value = "1024",
+        help = "Size in bytes of the smallest unary response that is compressed"
+    )]
+    pub min_compressed_response_size: usize,
+    /// The responses of `verify_membership` are served from the cache for this duration
+    /// If not specified, every request is verified by the enclave.
+    #[clap(
+        long = "proof_cache_ttl",
+        help = "Duration in seconds for which an identical verify_membership request is answered from the cache without an ecall"
+    )]
+    pub proof_cache_ttl: Option<u64>,
+    /// Maximum number of the cached responses of `verify_membership`
+    #[clap(
+        long = "proof_cache_size",
+        default_value = "10000",
+        help = "Maximum number of the cached verify_membership responses"
+    )]
+    pub proof_cache_size: usize,
+    /// Number of the standby keys that are generated and attested with IAS in advance
+    /// A standby key is not available to the relayers until it is activated with `lcp enclave activate-standby-key`.
+    #[clap(
+        long = "standby_keys",
+        help = "Number of the standby keys that are generated and attested with IAS in advance of a key rotation"
+    )]
+    pub standby_keys: Option<usize>,
+    /// Interval in seconds between the refills of the standby keys
+    #[clap(
+        long = "standby_key_interval",
+        default_value = "600",
+        help = "Interval in seconds between the refills of the standby keys"
+    )]
+    pub standby_key_interval: u64,
+    /// Source of the SPID and IAS_KEY credentials for the attestation of the standby keys
+    #[clap(
+        long = "standby_key_credentials",
+        default_value = "env",
+        help = "Source of the SPID and IAS_KEY credentials for the standby keys: `env`, `file:<path>`, `keychain:<service>` or `vault:<path>`"
+    )]
+    pub standby_key_credentials: String,
+    /// Signature type of the EPID quote of the standby keys
+    #[clap(
+        long = "standby_key_quote_type",
+        default_value = "unlinkable",
+        help = "Signature type of the EPID quote of the standby keys: `linkable` or `unlinkable`"
+    )]
+    pub standby_key_quote_type: String,
+    /// File to append the executed light client commands to
+    /// The log can be replayed against a fresh enclave with `lcp replay`.
+    #[clap(
+        long = "command_log",
+        help = "File to append the executed light client commands to, which can be replayed with `lcp replay`"
+    )]
+    pub command_log: Option<PathBuf>,
+    /// The enclave is re-created when an ecall has not returned within this duration
+    /// If not specified, the enclave is never re-created automatically.
+    #[clap(
+        long = "watchdog_timeout",
+        help = "Timeout in seconds of an ecall after which the enclave is considered hung and re-created"
+    )]
+    pub watchdog_timeout: Option<u64>,
+    /// Interval in seconds between the checks of the watchdog
+    #[clap(
+        long = "watchdog_interval",
+        default_value = "10",
+        help = "Interval in seconds between the checks for a hung enclave"
+    )]
+    pub watchdog_interval: u64,
+    /// On SIGTERM, the service waits for the commands in flight up to this duration before it destroys the enclave
+    #[clap(
+        long = "drain_timeout",
+        default_value = "30",
+        help = "Timeout in seconds to wait for the commands in flight to finish on shutdown"
+    )]
+    pub drain_timeout: u64,
+    /// Accept the `Shutdown` RPC, which anyone who can reach the service can call
+    #[clap(
+        long = "enable_shutdown_rpc",
+        help = "Accept the Shutdown RPC of the enclave Msg service"
+    )]
+    pub enable_shutdown_rpc: bool,
+    /// Accept the `CompactStore` RPC, which blocks the commands while the state store is compacted
+    #[clap(
+        long = "enable_compaction_rpc",
+        help = "Accept the CompactStore RPC of the enclave Msg service"
+    )]
+    pub enable_compaction_rpc: bool,
+    /// Encrypt the mirrored state of each owned client with a key derived for its owner, which requires `--store_encryption`
+    #[clap(
+        long = "encrypt_per_owner",
+        help = "Encrypt the state of each owned client in the state store with a key derived for its owner"
+    )]
+    pub encrypt_per_owner: bool,
+}
+
+impl Start {
+    fn circuit_breaker(&self) -> CircuitBreaker {
+        let breaker = CircuitBreaker::new(self.max_commit_failures);
+        match self.failover_command.clone() {
+            Some(command) => breaker.with_hook(Box::new(move |status: &HealthStatus| {
+                match std::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(&command)
+                    .env("LCP_HEALTH_STATUS", status.to_string())
+                    .status()
+                {
+                    Ok(exit) if exit.success() => info!("failover command succeeded"),
+                    Ok(exit) => error!("failover command failed: status={}", exit),
+                    Err(e) => error!("failed to run the failover command: err={}", e),
+                }
+            })),
+            None => breaker,
+        }
+    }
 }
 
 impl ServiceCmd {
@@ -47,8 +263,18 @@ impl ServiceCmd {
         match self {
             Self::Start(cmd) => {
                 let addr = cmd.address.parse()?;
-                let enclave =
-                    enclave_loader.load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?;
+                let mut enclave = enclave_loader
+                    .load(opts, cmd.enclave.path.as_ref(), cmd.enclave.is_debug())?
+                    .with_circuit_breaker(cmd.circuit_breaker());
+                if let Some(path) = cmd.command_log.as_ref() {
+                    info!("enable command log: path={:?}", path);
+                    enclave = enclave.with_command_log(CommandLog::open(path)?);
+                }
+                if let Some(timeout) = cmd.watchdog_timeout {
+                    enclave = enclave.with_watchdog(Duration::from_secs(timeout));
+                }
+                let instance = enclave.start_instance()?;
+                info!("started instance: counter={}", instance.counter);
 
                 let mut rb = Builder::new_multi_thread();
                 let rb = if let Some(threads) = cmd.threads {
@@ -57,7 +283,156 @@ impl ServiceCmd {
                     &mut rb
                 };
                 let rt = Arc::new(rb.enable_all().build()?);
-                let srv = AppService::new(opts.get_home(), enclave);
+                let mut srv = AppService::new(opts.get_home(), enclave)?
+                    .with_drain_timeout(Duration::from_secs(cmd.drain_timeout));
+                if cmd.enable_shutdown_rpc {
+                    info!("enable shutdown RPC");
+                    srv = srv.with_shutdown_rpc();
+                }
+                if cmd.enable_compaction_rpc {
+                    info!("enable compaction RPC");
+                    srv = srv.with_compaction_rpc();
+                }
+                if cmd.encrypt_per_owner {
+                    if opts.store_encryption.is_none() {
+                        bail!("`--encrypt_per_owner` requires `--store_encryption`");
+                    }
+                    info!("enable per-owner encryption of the state store");
+                    srv = srv.with_owner_encryption();
+                }
+                if let Some(interval) = cmd.prune_interval {
+                    info!(
+                        "enable pruner: interval={}s margin={}s",
+                        interval, cmd.prune_margin
+                    );
+                    srv = srv.with_pruner(PrunerConfig {
+                        interval: Duration::from_secs(interval),
+                        margin: Duration::from_secs(cmd.prune_margin),
+                    });
+                }
+                if let Some(interval) = cmd.event_watch_interval {
+                    info!(
+                        "enable event watcher: interval={}s expiry_threshold={}s",
+                        interval, cmd.expiry_threshold
+                    );
+                    srv = srv.with_event_watcher(EventWatcherConfig {
+                        interval: Duration::from_secs(interval),
+                        expiry_threshold: Duration::from_secs(cmd.expiry_threshold),
+                    });
+                }
+
+                if let Some(timeout) = cmd.watchdog_timeout {
+                    info!(
+                        "enable watchdog: timeout={}s interval={}s",
+                        timeout, cmd.watchdog_interval
+                    );
+                    srv = srv.with_watchdog(Duration::from_secs(cmd.watchdog_interval));
+                }
+
+                if let Some(max) = cmd.max_concurrent_requests {
+                    if max == 0 {
+                        bail!("max_concurrent_requests must be positive");
+                    }
+                    info!("enable request queue: max_concurrent_requests={}", max);
+                    srv = srv.with_request_queue(max);
+                }
+
+                if let Some(parallelism) = cmd.aggregation_parallelism.filter(|p| *p > 1) {
+                    if parallelism > ENCLAVE_TCS_NUM {
+                        warn!(
+                            "aggregation_parallelism is clamped to the TCSNum of the enclave: parallelism={} tcs_num={}",
+                            parallelism, ENCLAVE_TCS_NUM
+                        );
+                    }
+                    let parallelism = parallelism.min(ENCLAVE_TCS_NUM);
+                    info!("enable parallel aggregation: parallelism={}", parallelism);
+                    srv = srv.with_aggregation_parallelism(parallelism);
+                }
+
+                if let Some(ratio) = cmd.drift_warning_ratio {
+                    if !(0.0..=1.0).contains(&ratio) {
+                        bail!("drift_warning_ratio must be in [0, 1]: {}", ratio);
+                    }
+                    srv = srv.with_drift_warning_ratio(ratio);
+                }
+
+                if !cmd.response_compression.is_empty() {
+                    let encodings = cmd
+                        .response_compression
+                        .iter()
+                        .map(|e| parse_compression_encoding(e))
+                        .collect::<Result<Vec<_>>>()?;
+                    info!(
+                        "enable response compression: encodings={:?} min_response_size={}",
+                        encodings, cmd.min_compressed_response_size
+                    );
+                    srv = srv.with_response_compression(
+                        ResponseCompression::new(encodings)
+                            .with_min_response_size(cmd.min_compressed_response_size),
+                    );
+                }
+
+                if let Some(ttl) = cmd.proof_cache_ttl.filter(|ttl| *ttl > 0) {
+                    info!(
+                        "enable proof cache: ttl={}s max_entries={}",
+                        ttl, cmd.proof_cache_size
+                    );
+                    srv = srv.with_proof_cache(ProofCacheConfig {
+                        ttl: Duration::from_secs(ttl),
+                        max_entries: cmd.proof_cache_size,
+                    });
+                }
+
+                if let Some(size) = cmd.standby_keys.filter(|size| *size > 0) {
+                    let provider = credentials::from_spec(&cmd.standby_key_credentials)?;
+                    let quote_type = cmd
+                        .standby_key_quote_type
+                        .parse::<QuoteSignType>()
+                        .map_err(|e| anyhow!("{:?}", e))?;
+                    info!(
+                        "enable key pool: size={} interval={}s credentials={}",
+                        size,
+                        cmd.standby_key_interval,
+                        provider.name()
+                    );
+                    srv = srv.with_key_pool(
+                        KeyPoolConfig {
+                            size,
+                            interval: Duration::from_secs(cmd.standby_key_interval),
+                        },
+                        Arc::new(
+                            move |enclave: &Enclave<S>,
+                                  target_enclave_key: Address|
+                                  -> Result<()> {
+                                // the credentials are loaded for each attestation as they may be rotated
+                                let creds = provider.ias_credentials()?;
+                                enclave
+                                    .ias_remote_attestation(IASRemoteAttestationInput {
+                                        target_enclave_key,
+                                        spid: creds.spid,
+                                        ias_key: creds.ias_key,
+                                        quote_type,
+                                        advisory_policy: None,
+                                        applied_mitigations: vec![],
+                                    })
+                                    .map_err(|e| anyhow!("{:?}", e))?;
+                                Ok(())
+                            },
+                        ),
+                    );
+                }
+
+                for url in cmd.on_update_webhooks.iter() {
+                    info!("enable on-update webhook: url={}", url);
+                    srv = srv.with_on_update_hook(Arc::new(WebhookHook::new(
+                        url.clone(),
+                        Duration::from_secs(cmd.on_update_webhook_timeout),
+                    )));
+                }
+                for command in cmd.on_update_commands.iter() {
+                    info!("enable on-update command: command={}", command);
+                    srv = srv.with_on_update_hook(Arc::new(CommandHook::new(command.clone())));
+                }
 
                 info!("start service: addr={addr}");
                 run_service(srv, rt, addr)
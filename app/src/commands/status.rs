@@ -0,0 +1,332 @@
+use super::CliCmd;
+use crate::enclave::EnclaveLoader;
+use crate::opts::{EnclaveOpts, Opts};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use commitments::{ProxyMessage, ValidationContext};
+use ecall_commands::{PageRequest, QueryConsensusHeightsInput};
+use enclave_api::{Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
+use lcp_proto::lcp::service::enclave::v1::{query_client::QueryClient, QueryErrorMetricsRequest};
+use lcp_types::{ClientId, Height, Mrenclave, Time};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::time::Duration;
+use store::transaction::CommitStore;
+use tokio::runtime::Builder;
+
+/// `status` subcommand
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct StatusCmd {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Print the status as JSON instead of the table view
+    #[clap(long = "json", help = "Print the status as JSON")]
+    pub json: bool,
+    /// If specified, the status is refreshed every interval until interrupted
+    #[clap(
+        long = "watch",
+        help = "Refresh the status every interval in seconds until interrupted"
+    )]
+    pub watch: Option<u64>,
+    /// If specified, the expiry of each attested key is shown as `attested_at + key_expiration`
+    #[clap(
+        long = "key_expiration",
+        help = "Expiration in seconds from attested_at of the enclave keys, e.g. the one given to `enclave prune-keys`"
+    )]
+    pub key_expiration: Option<u64>,
+    /// The error counts are kept in the memory of the service, so they are shown only if this is specified
+    #[clap(
+        long = "service_address",
+        help = "Address of a running App service to query the error counts from, e.g. `[::1]:50051`"
+    )]
+    pub service_address: Option<String>,
+}
+
+impl StatusCmd {
+    pub fn run<S, L>(&self, opts: &Opts, enclave_loader: L) -> Result<()>
+    where
+        S: CommitStore,
+        Enclave<S>: EnclaveProtoAPI<S>,
+        L: EnclaveLoader<S>,
+    {
+        let enclave =
+            enclave_loader.load(opts, self.enclave.path.as_ref(), self.enclave.is_debug())?;
+        let interval = match self.watch {
+            Some(interval) => Duration::from_secs(interval.max(1)),
+            None => return self.print(&self.collect(&enclave)?),
+        };
+        loop {
+            // the read-only store only sees the writes committed before it was opened
+            *host::get_environment().unwrap().get_mut_store() = CliCmd::open_read_only_store(opts)?;
+            let status = self.collect(&enclave)?;
+            if !self.json {
+                // clear the screen and move the cursor to the top-left corner
+                print!("\x1b[2J\x1b[H");
+            }
+            self.print(&status)?;
+            std::io::stdout().flush()?;
+            std::thread::sleep(interval);
+        }
+    }
+
+    fn print(&self, status: &Value) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string(status)?);
+        } else {
+            print!("{}", render(status));
+        }
+        Ok(())
+    }
+
+    /// Aggregate the status of the enclave, its keys, the clients, the store and the errors of the service
+    fn collect<E: EnclaveCommandAPI<S>, S: CommitStore>(&self, enclave: &E) -> Result<Value> {
+        let now = Time::now().as_unix_timestamp_secs();
+        let mrenclave: Mrenclave = enclave.metadata()?.enclave_css.body.enclave_hash.m.into();
+        let build_info = enclave.query_build_info()?.build_info;
+
+        let km = enclave.get_key_manager();
+        let available: Vec<_> = km
+            .available_keys(mrenclave)?
+            .into_iter()
+            .map(|k| k.address)
+            .collect();
+        let mut keys = Vec::new();
+        for eki in km.all_keys()? {
+            let attested_at = match eki.avr.as_ref() {
+                Some(eavr) => Some(eavr.get_avr()?.attestation_time()?.as_unix_timestamp_secs()),
+                None => None,
+            };
+            let expires_at = attested_at
+                .zip(self.key_expiration)
+                .map(|(t, exp)| t.saturating_add(exp));
+            keys.push(json! {{
+                "address": eki.address.to_hex_string(),
+                "mrenclave": eki.mrenclave.to_hex_string(),
+                "available": available.contains(&eki.address),
+                "revoked": km.revocation_reason(eki.address)?,
+                "attested_at": attested_at,
+                "expires_at": expires_at,
+                "expires_in": expires_at.map(|t| seconds_until(t, now)),
+            }});
+        }
+
+        let mut clients = Vec::new();
+        for client_id in enclave.all_client_ids()? {
+            clients.push(client_status(enclave, &client_id, now)?);
+        }
+
+        let store = enclave.store_stats()?.map(|stats| {
+            json! {{
+                "live_data_size": stats.live_data_size,
+                "total_sst_files_size": stats.total_sst_files_size,
+                "pending_compaction_bytes": stats.pending_compaction_bytes,
+                "space_amplification": stats.space_amplification(),
+            }}
+        });
+
+        let errors = match self.service_address.as_ref() {
+            Some(address) => Some(query_error_counts(address)?),
+            None => None,
+        };
+
+        Ok(json! {{
+            "timestamp": now,
+            "enclave": {
+                "mrenclave": mrenclave.to_hex_string(),
+                "version": build_info.version,
+                "git_commit": build_info.git_commit,
+                "protocol_version": build_info.protocol_version,
+                "features": build_info.features,
+            },
+            "keys": keys,
+            "clients": clients,
+            "store": store,
+            "errors": errors,
+        }})
+    }
+}
+
+/// Returns the latest height of the client and the expiry of the state of its latest update
+///
+/// The expiry is known only if the latest update carries a trusting period.
+fn client_status<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: &E,
+    client_id: &ClientId,
+    now: u64,
+) -> Result<Value> {
+    let latest_height = latest_consensus_height(enclave, client_id)?;
+    let latest_update = enclave
+        .update_history(client_id, PageRequest::new(0, 1)?)?
+        .into_iter()
+        .next();
+    let (updated_at, expires_at) = match latest_update {
+        Some(record) => {
+            let expires_at = match ProxyMessage::from_bytes(&record.proof.message) {
+                Ok(ProxyMessage::UpdateState(msg)) => match msg.context {
+                    ValidationContext::TrustingPeriod(ctx) => {
+                        Some((msg.timestamp + ctx.trusting_period())?.as_unix_timestamp_secs())
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            (
+                Some(record.recorded_at.as_unix_timestamp_secs()),
+                expires_at,
+            )
+        }
+        None => (None, None),
+    };
+    Ok(json! {{
+        "client_id": client_id.to_string(),
+        "latest_height": latest_height.map(|h| h.to_string()),
+        "updated_at": updated_at,
+        "expires_at": expires_at,
+        "expires_in": expires_at.map(|t| seconds_until(t, now)),
+    }})
+}
+
+/// Returns the highest height of the consensus states stored for the client
+fn latest_consensus_height<E: EnclaveCommandAPI<S>, S: CommitStore>(
+    enclave: &E,
+    client_id: &ClientId,
+) -> Result<Option<Height>> {
+    let query = |offset| -> Result<_> {
+        Ok(enclave.query_consensus_heights(QueryConsensusHeightsInput {
+            client_id: client_id.clone(),
+            pagination: PageRequest::new(offset, 1)?,
+        })?)
+    };
+    // the heights are in ascending order, so the last page of a single item is the latest one
+    let total = query(0)?.pagination.total;
+    if total == 0 {
+        return Ok(None);
+    }
+    Ok(query(total - 1)?.heights.into_iter().next())
+}
+
+fn query_error_counts(address: &str) -> Result<Value> {
+    let rt = Builder::new_current_thread().enable_all().build()?;
+    let res = rt
+        .block_on(async {
+            let mut client = QueryClient::connect(format!("http://{}", address)).await?;
+            let res = client.error_metrics(QueryErrorMetricsRequest {}).await?;
+            Ok::<_, anyhow::Error>(res.into_inner())
+        })
+        .map_err(|e| {
+            anyhow!(
+                "failed to query the error counts: address={} err={:?}",
+                address,
+                e
+            )
+        })?;
+    Ok(serde_json::to_value(res.counts)?)
+}
+
+/// Returns the seconds from `now` to `t`, which is negative if `t` has passed
+fn seconds_until(t: u64, now: u64) -> i64 {
+    t as i64 - now as i64
+}
+
+/// Render the status as the table view for a terminal
+fn render(status: &Value) -> String {
+    let mut out = String::new();
+    let enclave = &status["enclave"];
+    out += &format!(
+        "ENCLAVE  mrenclave={} version={} git_commit={} protocol_version={}\n\n",
+        enclave["mrenclave"].as_str().unwrap_or_default(),
+        enclave["version"].as_str().unwrap_or_default(),
+        enclave["git_commit"].as_str().unwrap_or_default(),
+        enclave["protocol_version"]
+    );
+
+    out += &format!(
+        "KEYS\n{:<44} {:<10} {:<9} {:<12}\n",
+        "ADDRESS", "AVAILABLE", "ATTESTED", "EXPIRES IN"
+    );
+    for key in status["keys"].as_array().into_iter().flatten() {
+        let state = if key["revoked"].is_string() {
+            "revoked"
+        } else if key["available"].as_bool().unwrap_or_default() {
+            "yes"
+        } else {
+            "no"
+        };
+        out += &format!(
+            "{:<44} {:<10} {:<9} {:<12}\n",
+            key["address"].as_str().unwrap_or_default(),
+            state,
+            if key["attested_at"].is_null() {
+                "no"
+            } else {
+                "yes"
+            },
+            format_remaining(&key["expires_in"])
+        );
+    }
+
+    out += &format!(
+        "\nCLIENTS\n{:<32} {:<20} {:<12}\n",
+        "CLIENT ID", "LATEST HEIGHT", "EXPIRES IN"
+    );
+    for client in status["clients"].as_array().into_iter().flatten() {
+        out += &format!(
+            "{:<32} {:<20} {:<12}\n",
+            client["client_id"].as_str().unwrap_or_default(),
+            client["latest_height"].as_str().unwrap_or("-"),
+            format_remaining(&client["expires_in"])
+        );
+    }
+
+    out += "\nSTORE\n";
+    match status["store"].as_object() {
+        Some(store) => {
+            out += &format!(
+                "live_data_size={} total_sst_files_size={} pending_compaction_bytes={} space_amplification={:.2}\n",
+                store["live_data_size"],
+                store["total_sst_files_size"],
+                store["pending_compaction_bytes"],
+                store["space_amplification"].as_f64().unwrap_or_default()
+            );
+        }
+        None => out += "not persistent\n",
+    }
+
+    out += "\nERRORS\n";
+    match status["errors"].as_array() {
+        Some(counts) if counts.is_empty() => out += "none since the service started\n",
+        Some(counts) => {
+            out += &format!("{:<16} {:<32} {:<8}\n", "CATEGORY", "KIND", "COUNT");
+            for count in counts {
+                out += &format!(
+                    "{:<16} {:<32} {:<8}\n",
+                    count["category"].as_str().unwrap_or_default(),
+                    count["kind"].as_str().unwrap_or_default(),
+                    count["count"]
+                );
+            }
+        }
+        None => out += "unknown: `--service_address` is not specified\n",
+    }
+    out
+}
+
+/// Format the remaining seconds as e.g. `2d3h` or `expired`
+fn format_remaining(secs: &Value) -> String {
+    let secs = match secs.as_i64() {
+        Some(secs) => secs,
+        None => return "-".to_string(),
+    };
+    if secs <= 0 {
+        return "expired".to_string();
+    }
+    let (d, h, m) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if d > 0 {
+        format!("{}d{}h", d, h)
+    } else if h > 0 {
+        format!("{}h{}m", h, m)
+    } else {
+        format!("{}m{}s", m, secs % 60)
+    }
+}
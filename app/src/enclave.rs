@@ -1,9 +1,12 @@
-use crate::opts::Opts;
-use anyhow::{bail, Result};
-use enclave_api::{Enclave, EnclaveProtoAPI};
+use crate::opts::{is_debug_env, Opts};
+use anyhow::{anyhow, bail, Result};
+use enclave_api::{Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
 use keymanager::EnclaveKeyManager;
 use std::path::PathBuf;
-use store::transaction::CommitStore;
+use std::sync::{Arc, RwLock};
+use store::{host::HostStore, memory::MemStore, transaction::CommitStore};
+
+const SEALED_STORE_KEY: &str = "store_key.sealed";
 
 pub trait EnclaveLoader<S: CommitStore> {
     fn load(&self, opts: &Opts, path: Option<&PathBuf>, debug: bool) -> Result<Enclave<S>>;
@@ -24,16 +27,20 @@ where
         };
         let env = host::get_environment().unwrap();
         let km = EnclaveKeyManager::new(&env.home)?;
-        match Enclave::create(&path, debug, km, env.store.clone()) {
-            Ok(enclave) => Ok(enclave),
+        let enclave = match Enclave::create(&path, debug, km, env.store.clone()) {
+            Ok(enclave) => enclave,
             Err(x) => {
-                bail!(
-                    "Init Enclave Failed: status={} path={:?}",
-                    x.as_str(),
-                    path.as_path()
-                );
+                bail!("Init Enclave Failed: err={} path={:?}", x, path.as_path());
             }
+        };
+        let enclave = match opts.get_session_credential()? {
+            Some(credential) => enclave.with_session_credential(Box::new(credential)),
+            None => enclave,
+        };
+        if let Err(x) = enclave.open_session() {
+            bail!("Open Session Failed: err={} path={:?}", x, path.as_path());
         }
+        Ok(enclave)
     }
 }
 
@@ -43,3 +50,32 @@ where
 {
     DefaultEnclaveLoader(std::marker::PhantomData)
 }
+
+/// Unseal the key of the state store with the default enclave, generating and sealing a new one on the first run
+///
+/// The enclave runs against an in-memory store because the state store is not opened yet.
+pub fn unseal_store_key(opts: &Opts) -> Result<[u8; 32]> {
+    let home = opts.get_home();
+    let path = home.join(SEALED_STORE_KEY);
+    let sealed_key = if path.exists() {
+        std::fs::read(&path)?
+    } else {
+        vec![]
+    };
+    let store = Arc::new(RwLock::new(HostStore::Memory(MemStore::default())));
+    let enclave = Enclave::<MemStore>::create(
+        opts.default_enclave(),
+        is_debug_env(),
+        EnclaveKeyManager::new(&home)?,
+        store,
+    )
+    .map_err(|e| anyhow!("Init Enclave Failed: err={}", e))?;
+    let res = enclave.unseal_store_key(sealed_key.clone());
+    enclave.destroy();
+    let res = res?;
+    if sealed_key.is_empty() {
+        std::fs::create_dir_all(&home)?;
+        std::fs::write(&path, &res.sealed_key)?;
+    }
+    Ok(res.key)
+}
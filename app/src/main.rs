@@ -2,6 +2,7 @@ use anyhow::Result;
 use clap::Parser;
 use cli::Cli;
 
+mod bootstrap;
 mod cli;
 mod commands;
 mod enclave;
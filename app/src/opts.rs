@@ -1,9 +1,15 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use crypto::EnclaveKey;
+use host_environment::{credentials, IASEndpoint};
 use log::LevelFilter;
 use std::{path::PathBuf, str::FromStr};
+use store::encryption::StoreKey;
+use store::rocksdb::SyncPolicy;
 
 const ENV_VAR_DEBUG: &str = "LCP_ENCLAVE_DEBUG";
+const ENV_VAR_STORE_SECRET: &str = "LCP_STORE_SECRET";
+const ENV_VAR_OPERATOR_KEY: &str = "LCP_OPERATOR_KEY";
 
 const DEFAULT_HOME: &str = ".lcp";
 const DEFAULT_ENCLAVE: &str = "enclave.signed.so";
@@ -19,6 +25,44 @@ pub struct Opts {
     /// 2. environment variable
     #[clap(long = "log_level", help = "Verbosity level of the logger")]
     pub log_level: Option<String>,
+    /// Source of the key to encrypt the state store
+    /// `sealed` uses a key sealed by the default enclave, otherwise the key is derived from the given secret.
+    /// If not set, the store is not encrypted.
+    #[clap(
+        long = "store_encryption",
+        help = "Source of the key that encrypts the state store: `sealed` for a key sealed by the enclave, or the source of `LCP_STORE_SECRET`: `env`, `file:<path>`, `keychain:<service>` or `vault:<path>`"
+    )]
+    pub store_encryption: Option<String>,
+    /// Policy to sync the writes of the state store to the disk
+    #[clap(
+        long = "store_sync",
+        default_value = "on-commit",
+        help = "When the writes of the state store are synced to the disk: `always`, `on-commit` or `interval:<seconds>`"
+    )]
+    pub store_sync: String,
+    /// Source of the operator key that opens a session with the enclave
+    /// If not set, no session is opened and the enclave rejects the commands that require one.
+    #[clap(
+        long = "session_credential",
+        help = "Source of the hex-encoded `LCP_OPERATOR_KEY` that opens a session with the enclave: `env`, `file:<path>`, `keychain:<service>` or `vault:<path>`"
+    )]
+    pub session_credential: Option<String>,
+    /// Address of a mock IAS to connect to instead of Intel's
+    #[cfg(feature = "sgx-sw")]
+    #[clap(
+        long = "mock_ias",
+        requires = "mock_ias_root_ca",
+        help = "Address of a mock IAS to run the IAS remote attestation against, e.g. `127.0.0.1:8443`"
+    )]
+    pub mock_ias: Option<String>,
+    /// Path to the PEM-encoded root CA of the mock IAS
+    #[cfg(feature = "sgx-sw")]
+    #[clap(
+        long = "mock_ias_root_ca",
+        requires = "mock_ias",
+        help = "Path to the PEM-encoded root CA that issues the signing certificate of the mock IAS"
+    )]
+    pub mock_ias_root_ca: Option<PathBuf>,
 }
 
 impl Opts {
@@ -38,6 +82,62 @@ impl Opts {
         self.get_home().join("state")
     }
 
+    /// Returns the key of the state store if the encryption is enabled
+    pub fn get_store_key(&self) -> Result<Option<StoreKey>> {
+        match self.store_encryption.as_deref() {
+            Some("sealed") => Ok(Some(StoreKey::Sealed(crate::enclave::unseal_store_key(
+                self,
+            )?))),
+            Some(spec) => {
+                let secret = credentials::from_spec(spec)?.get(ENV_VAR_STORE_SECRET)?;
+                Ok(Some(StoreKey::Secret(secret.into_bytes())))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the operator key that opens a session with the enclave if it is configured
+    pub fn get_session_credential(&self) -> Result<Option<EnclaveKey>> {
+        match self.session_credential.as_ref() {
+            Some(spec) => {
+                let secret = credentials::from_spec(spec)?.get(ENV_VAR_OPERATOR_KEY)?;
+                let bz = hex::decode(secret.trim().trim_start_matches("0x"))?;
+                let secret: [u8; 32] = bz.as_slice().try_into().map_err(|_| {
+                    anyhow!(
+                        "{} must be 32 bytes: len={}",
+                        ENV_VAR_OPERATOR_KEY,
+                        bz.len()
+                    )
+                })?;
+                Ok(Some(EnclaveKey::from_secret(&secret)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_store_sync_policy(&self) -> Result<SyncPolicy> {
+        Ok(self.store_sync.parse()?)
+    }
+
+    pub fn get_ias_endpoint(&self) -> Result<IASEndpoint> {
+        #[cfg(feature = "sgx-sw")]
+        if let (Some(addr), Some(root_ca)) =
+            (self.mock_ias.as_ref(), self.mock_ias_root_ca.as_ref())
+        {
+            return Ok(IASEndpoint::Mock {
+                addr: addr.clone(),
+                root_ca: std::fs::read(root_ca).map_err(|e| {
+                    anyhow!(
+                        "failed to read the root CA of the mock IAS: path={:?} err={}",
+                        root_ca,
+                        e
+                    )
+                })?,
+            });
+        }
+        Ok(IASEndpoint::Intel)
+    }
+
     pub fn get_log_level_filter(&self) -> Result<Option<LevelFilter>> {
         if let Some(log_level) = self.log_level.as_ref() {
             Ok(Some(LevelFilter::from_str(log_level).map_err(|_| {
@@ -63,13 +163,14 @@ pub struct EnclaveOpts {
 
 impl EnclaveOpts {
     pub fn is_debug(&self) -> bool {
-        if self.debug {
-            true
-        } else {
-            match std::env::var(ENV_VAR_DEBUG).map(|val| val.to_lowercase()) {
-                Ok(val) => val == "1" || val == "true",
-                Err(_) => false,
-            }
-        }
+        self.debug || is_debug_env()
+    }
+}
+
+/// Returns true if the debug mode is enabled by the environment variable
+pub fn is_debug_env() -> bool {
+    match std::env::var(ENV_VAR_DEBUG).map(|val| val.to_lowercase()) {
+        Ok(val) => val == "1" || val == "true",
+        Err(_) => false,
     }
 }
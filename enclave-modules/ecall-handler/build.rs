@@ -0,0 +1,69 @@
+use std::env;
+use std::process::Command;
+
+/// The revision of the Rust SGX SDK that the enclave crates are pinned to
+const DEFAULT_SGX_SDK_VERSION: &str = "v1.1.6";
+
+fn main() {
+    // builds without the git directory, e.g. in a docker context, can set the commit explicitly
+    let git_commit = env::var("LCP_GIT_COMMIT").unwrap_or_else(|_| git_commit());
+    let sgx_sdk_version =
+        env::var("SGX_SDK_VERSION").unwrap_or_else(|_| DEFAULT_SGX_SDK_VERSION.to_string());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = output(Command::new(rustc).arg("--version")).unwrap_or_default();
+
+    println!("cargo:rustc-env=LCP_GIT_COMMIT={}", git_commit);
+    println!(
+        "cargo:rustc-env=LCP_BUILD_PROFILE={}",
+        env::var("PROFILE").unwrap_or_default()
+    );
+    println!("cargo:rustc-env=LCP_SGX_SDK_VERSION={}", sgx_sdk_version);
+    println!("cargo:rustc-env=LCP_RUSTC_VERSION={}", rustc_version);
+    println!(
+        "cargo:rustc-env=LCP_BOOTSTRAP_OPERATOR={}",
+        bootstrap_operator()
+    );
+
+    println!("cargo:rerun-if-env-changed=LCP_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SGX_SDK_VERSION");
+    println!("cargo:rerun-if-env-changed=LCP_BOOTSTRAP_OPERATOR");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/index");
+}
+
+/// The address of the operator that approves the initial operator set, which becomes a part of the measurement
+///
+/// An enclave built without it cannot configure the operator set.
+fn bootstrap_operator() -> String {
+    let operator = env::var("LCP_BOOTSTRAP_OPERATOR").unwrap_or_default();
+    let hex = operator.strip_prefix("0x").unwrap_or(&operator);
+    if !hex.is_empty() && (hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit())) {
+        panic!(
+            "LCP_BOOTSTRAP_OPERATOR must be the hex address of an operator: {}",
+            operator
+        );
+    }
+    hex.to_lowercase()
+}
+
+fn git_commit() -> String {
+    let commit = match output(Command::new("git").args(["rev-parse", "HEAD"])) {
+        Some(commit) => commit,
+        None => return "unknown".to_string(),
+    };
+    let dirty = output(Command::new("git").args(["status", "--porcelain", "--untracked-files=no"]))
+        .map_or(false, |status| !status.is_empty());
+    if dirty {
+        format!("{}-dirty", commit)
+    } else {
+        commit
+    }
+}
+
+fn output(cmd: &mut Command) -> Option<String> {
+    let out = cmd.output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(out.stdout).ok()?.trim().to_string())
+}
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+use crate::revocation::check_not_revoked;
+use crate::{Error, Result};
+use attestation_report::verify_dcap_quote;
+use crypto::{Address, EnclaveKey, SealingKey};
+use ecall_commands::{CommandContext, KeyAttestation};
+use lcp_types::Time;
+use sgx_tse::rsgx_self_report;
+use store::KVStore;
+
+/// Returns an error unless every constituent key in the context is attested to have been generated
+/// by an enclave with the same MRENCLAVE as this one, and none of them has been revoked
+///
+/// A key that this enclave can unseal was sealed by an enclave of the same signer, which may be another
+/// version of the enclave, so the attestation of the key binds it to this version.
+pub(crate) fn verify_constituent_keys(store: &dyn KVStore, cctx: &CommandContext) -> Result<()> {
+    if cctx.constituent_sealed_eks.len() != cctx.constituent_attestations.len() {
+        return Err(Error::invalid_command(format!(
+            "constituent keys and attestations must have the same length: keys={} attestations={}",
+            cctx.constituent_sealed_eks.len(),
+            cctx.constituent_attestations.len()
+        )));
+    }
+    if cctx.constituent_sealed_eks.is_empty() {
+        return Ok(());
+    }
+    let mrenclave = rsgx_self_report().body.mr_enclave.m;
+    let addresses = cctx
+        .constituent_sealed_eks
+        .iter()
+        .zip(cctx.constituent_attestations.iter())
+        .enumerate()
+        .map(|(index, (sealed_ek, attestation))| {
+            let address = EnclaveKey::unseal(sealed_ek)?.get_pubkey().as_address();
+            let (quoted_key, quoted_mrenclave) =
+                verify_attestation(cctx.current_timestamp, attestation)
+                    .map_err(|e| Error::invalid_constituent_key(index, e.to_string()))?;
+            if quoted_key != address {
+                return Err(Error::invalid_constituent_key(
+                    index,
+                    format!(
+                        "the attestation is bound to another key: key={} attestation={}",
+                        address, quoted_key
+                    ),
+                ));
+            }
+            if quoted_mrenclave != mrenclave {
+                return Err(Error::invalid_constituent_key(
+                    index,
+                    format!(
+                        "the key has been generated by another enclave: mrenclave={} expected={}",
+                        hex::encode(quoted_mrenclave),
+                        hex::encode(mrenclave)
+                    ),
+                ));
+            }
+            Ok(address)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    check_not_revoked(store, &addresses)
+}
+
+/// Returns the key address and the MRENCLAVE in the verified attestation
+fn verify_attestation(
+    current_timestamp: Time,
+    attestation: &KeyAttestation,
+) -> core::result::Result<(Address, [u8; 32]), attestation_report::Error> {
+    match attestation {
+        KeyAttestation::IAS(eavr) => {
+            #[cfg(not(feature = "sgx-sw"))]
+            attestation_report::verify_report(current_timestamp, eavr)?;
+            let quote = eavr.get_avr()?.parse_quote()?;
+            Ok((quote.get_enclave_key_address()?, quote.get_mrenclave().m))
+        }
+        KeyAttestation::DCAP(bundle) => {
+            verify_dcap_quote(current_timestamp, bundle)?;
+            Ok((
+                bundle.get_enclave_key_address()?,
+                bundle.report_body()?.mr_enclave.m,
+            ))
+        }
+    }
+}
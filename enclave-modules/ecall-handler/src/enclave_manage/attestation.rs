@@ -1,8 +1,11 @@
 use crate::enclave_manage::errors::Error;
 use crate::prelude::*;
-use attestation_report::verify_report;
+use attestation_report::verify_dcap_quote;
 use crypto::{EnclaveKey, SealingKey};
-use ecall_commands::{CommandContext, IASRemoteAttestationInput, IASRemoteAttestationResponse};
+use ecall_commands::{
+    CommandContext, IASRemoteAttestationInput, IASRemoteAttestationResponse, QuoteSignType,
+    ValidateDCAPCollateralInput, ValidateDCAPCollateralResponse,
+};
 use enclave_remote_attestation::{
     attestation::create_attestation_report, report::validate_quote_status,
 };
@@ -17,19 +20,52 @@ pub(crate) fn ias_remote_attestation(
         EnclaveKey::unseal(&cctx.sealed_ek.ok_or(Error::enclave_key_not_found())?)?.get_pubkey();
     let report = {
         let spid = decode_spid(&input.spid);
-        let report = create_attestation_report(
+        create_attestation_report(
+            cctx.current_timestamp,
             pub_key.as_report_data(),
-            sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
+            to_sgx_quote_sign_type(input.quote_type),
             spid,
             &input.ias_key,
-        )?;
-        verify_report(cctx.current_timestamp, &report)?;
-        report
+        )?
     };
-    validate_quote_status(cctx.current_timestamp, &report.get_avr()?)?;
+    let avr = report.get_avr()?;
+    // IAS accepts a quote only if its sign type matches the SPID, so this is a sanity check against the quoting enclave
+    let sign_type = avr.parse_quote()?.raw.sign_type;
+    if sign_type != input.quote_type.as_u16() {
+        return Err(Error::quote_sign_type_mismatch(
+            input.quote_type.as_u16(),
+            sign_type,
+        ));
+    }
+    validate_quote_status(cctx.current_timestamp, &avr)?;
+    if let Some(policy) = input.advisory_policy.as_ref() {
+        policy.evaluate(
+            &avr.advisory_ids,
+            cctx.current_timestamp,
+            &input.applied_mitigations,
+        )?;
+    }
     Ok(IASRemoteAttestationResponse { report })
 }
 
+pub(crate) fn validate_dcap_collateral(
+    cctx: CommandContext,
+    input: ValidateDCAPCollateralInput,
+) -> Result<ValidateDCAPCollateralResponse, Error> {
+    input.validate()?;
+    let pub_key =
+        EnclaveKey::unseal(&cctx.sealed_ek.ok_or(Error::enclave_key_not_found())?)?.get_pubkey();
+    let quoted_key = input.bundle.get_enclave_key_address()?;
+    if quoted_key != pub_key.as_address() {
+        return Err(Error::enclave_key_mismatch(
+            pub_key.as_address(),
+            quoted_key,
+        ));
+    }
+    let root_ca_hash = verify_dcap_quote(cctx.current_timestamp, &input.bundle)?;
+    Ok(ValidateDCAPCollateralResponse { root_ca_hash })
+}
+
 #[cfg(feature = "sgx-sw")]
 pub(crate) fn simulate_remote_attestation(
     cctx: CommandContext,
@@ -48,6 +84,13 @@ pub(crate) fn simulate_remote_attestation(
     Ok(ecall_commands::SimulateRemoteAttestationResponse { avr })
 }
 
+fn to_sgx_quote_sign_type(quote_type: QuoteSignType) -> sgx_quote_sign_type_t {
+    match quote_type {
+        QuoteSignType::Linkable => sgx_quote_sign_type_t::SGX_LINKABLE_SIGNATURE,
+        QuoteSignType::Unlinkable => sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE,
+    }
+}
+
 // CONTRACT: `hex` length must be 32
 fn decode_spid(hex: &[u8]) -> sgx_spid_t {
     assert!(hex.len() == 32);
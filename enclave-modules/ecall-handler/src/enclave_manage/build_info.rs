@@ -0,0 +1,37 @@
+use crate::enclave_manage::Error;
+use crate::prelude::*;
+use ecall_commands::{BuildInfo, QueryBuildInfoInput, QueryBuildInfoResponse, PROTOCOL_VERSION};
+
+pub(crate) fn query_build_info(_: QueryBuildInfoInput) -> Result<QueryBuildInfoResponse, Error> {
+    Ok(QueryBuildInfoResponse {
+        build_info: build_info(),
+    })
+}
+
+fn build_info() -> BuildInfo {
+    let mut features = Vec::new();
+    if cfg!(feature = "sgx-sw") {
+        features.push("sgx-sw".to_string());
+    }
+    if cfg!(feature = "production") {
+        features.push("production".to_string());
+    }
+    if cfg!(feature = "attestation") {
+        features.push("attestation".to_string());
+    }
+    if cfg!(feature = "verify") {
+        features.push("verify".to_string());
+    }
+    if cfg!(feature = "update") {
+        features.push("update".to_string());
+    }
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("LCP_GIT_COMMIT").to_string(),
+        build_profile: env!("LCP_BUILD_PROFILE").to_string(),
+        sgx_sdk_version: env!("LCP_SGX_SDK_VERSION").to_string(),
+        rustc_version: env!("LCP_RUSTC_VERSION").to_string(),
+        features,
+        protocol_version: PROTOCOL_VERSION,
+    }
+}
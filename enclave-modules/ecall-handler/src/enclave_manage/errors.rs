@@ -1,4 +1,6 @@
 use crate::prelude::*;
+use crypto::Address;
+use ecall_commands::ErrorCode;
 use flex_error::*;
 use sgx_types::sgx_status_t;
 
@@ -16,6 +18,35 @@ define_error! {
         EnclaveKeyNotFound
         |_| { "Enclave Key not found" },
 
+        InvalidSealedStoreKey
+        |_| { "the sealed store key is invalid" },
+
+        CommandDisabled
+        {
+            feature: String
+        }
+        |e| {
+            format_args!("the command is not supported by this enclave build: feature={}", e.feature)
+        },
+
+        EnclaveKeyMismatch
+        {
+            expected: Address,
+            actual: Address
+        }
+        |e| {
+            format_args!("the quote is not bound to the enclave key: expected={} actual={}", e.expected, e.actual)
+        },
+
+        QuoteSignTypeMismatch
+        {
+            expected: u16,
+            actual: u16
+        }
+        |e| {
+            format_args!("unexpected sign type of the quote: expected={} actual={}", e.expected, e.actual)
+        },
+
         Crypto
         [crypto::Error]
         |_| { "Crypto error" },
@@ -61,3 +92,15 @@ impl From<ecall_commands::InputValidationError> for Error {
         Error::ecall_command(err)
     }
 }
+
+impl Error {
+    /// Maps the error into the stable error code exposed to the host
+    pub fn code(&self) -> ErrorCode {
+        match self.detail() {
+            ErrorDetail::AttestationReport(e) if e.source.is_policy_violation() => {
+                ErrorCode::AttestationPolicyViolation
+            }
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
@@ -1,7 +1,10 @@
 pub use errors::Error;
 pub use router::dispatch;
 
+#[cfg(feature = "attestation")]
 mod attestation;
+mod build_info;
 mod enclave;
 mod errors;
 mod router;
+mod store_key;
@@ -1,11 +1,14 @@
+#[cfg(feature = "attestation")]
+use crate::enclave_manage::attestation::{ias_remote_attestation, validate_dcap_collateral};
 use crate::enclave_manage::{
-    attestation::ias_remote_attestation, enclave::generate_enclave_key, Error,
+    build_info::query_build_info, enclave::generate_enclave_key, store_key::unseal_store_key, Error,
 };
 use crate::prelude::*;
 use ecall_commands::{
     CommandContext, CommandResponse, EnclaveManageCommand, EnclaveManageResponse,
 };
 
+#[cfg_attr(not(feature = "attestation"), allow(unused_variables))]
 pub fn dispatch(
     cctx: CommandContext,
     command: EnclaveManageCommand,
@@ -16,15 +19,42 @@ pub fn dispatch(
         GenerateEnclaveKey(input) => CommandResponse::EnclaveManage(
             EnclaveManageResponse::GenerateEnclaveKey(generate_enclave_key(input)?),
         ),
+        #[cfg(feature = "attestation")]
         IASRemoteAttestation(input) => CommandResponse::EnclaveManage(
             EnclaveManageResponse::IASRemoteAttestation(ias_remote_attestation(cctx, input)?),
         ),
-        #[cfg(feature = "sgx-sw")]
+        #[cfg(all(feature = "sgx-sw", feature = "attestation"))]
         SimulateRemoteAttestation(input) => {
             CommandResponse::EnclaveManage(EnclaveManageResponse::SimulateRemoteAttestation(
                 crate::enclave_manage::attestation::simulate_remote_attestation(cctx, input)?,
             ))
         }
+        #[cfg(feature = "attestation")]
+        ValidateDCAPCollateral(input) => CommandResponse::EnclaveManage(
+            EnclaveManageResponse::ValidateDCAPCollateral(validate_dcap_collateral(cctx, input)?),
+        ),
+        #[cfg(all(feature = "sgx-sw", not(feature = "attestation")))]
+        SimulateRemoteAttestation(_) => return Err(Error::command_disabled("attestation".into())),
+        #[cfg(not(feature = "attestation"))]
+        IASRemoteAttestation(_) | ValidateDCAPCollateral(_) => {
+            return Err(Error::command_disabled("attestation".into()))
+        }
+        QueryBuildInfo(input) => CommandResponse::EnclaveManage(
+            EnclaveManageResponse::QueryBuildInfo(query_build_info(input)?),
+        ),
+        UnsealStoreKey(input) => CommandResponse::EnclaveManage(
+            EnclaveManageResponse::UnsealStoreKey(unseal_store_key(input)?),
+        ),
+        // these commands are handled by the top-level router because they require the store or the session state
+        StartInstance(_)
+        | UpdateOperators(_)
+        | QueryOperators(_)
+        | BeginSession(_)
+        | OpenSession(_)
+        | RevokeEnclaveKeys(_)
+        | FinalizeStoreSequence(_) => {
+            unreachable!()
+        }
     };
     Ok(res)
 }
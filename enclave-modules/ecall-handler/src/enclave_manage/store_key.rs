@@ -0,0 +1,34 @@
+use crate::enclave_manage::Error;
+use crate::prelude::*;
+use crypto::sgx::{
+    rand::rand_slice,
+    sealing::{seal_bytes, unseal_bytes},
+};
+use ecall_commands::{UnsealStoreKeyInput, UnsealStoreKeyResponse};
+
+const STORE_KEY_DOMAIN: &[u8] = b"lcp-store-key:";
+
+/// Unseal the store encryption key, or generate and seal a new one if no sealed key is given
+pub(crate) fn unseal_store_key(
+    input: UnsealStoreKeyInput,
+) -> Result<UnsealStoreKeyResponse, Error> {
+    if input.sealed_key.is_empty() {
+        let mut key = [0u8; 32];
+        rand_slice(&mut key)?;
+        let mut bz = STORE_KEY_DOMAIN.to_vec();
+        bz.extend_from_slice(&key);
+        return Ok(UnsealStoreKeyResponse {
+            key,
+            sealed_key: seal_bytes(&bz)?,
+        });
+    }
+    let bz = unseal_bytes(&input.sealed_key)?;
+    let key = bz
+        .strip_prefix(STORE_KEY_DOMAIN)
+        .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        .ok_or_else(Error::invalid_sealed_store_key)?;
+    Ok(UnsealStoreKeyResponse {
+        key,
+        sealed_key: input.sealed_key,
+    })
+}
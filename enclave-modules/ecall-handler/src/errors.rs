@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use ecall_commands::ErrorCode;
 use flex_error::*;
 use sgx_types::sgx_status_t;
 
@@ -30,6 +31,165 @@ define_error! {
         Crypto
         [crypto::Error]
         |_| { "Crypto error" },
+
+        InvalidCommand
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid command: descr={}", e.descr)
+        },
+
+        InstanceNotStarted
+        |_| { "no instance has been started in the enclave" },
+
+        StaleInstance
+        {
+            started: u64,
+            latest: u64
+        }
+        |e| {
+            format_args!("the instance is stale because another instance has been started on the store: started={} latest={}", e.started, e.latest)
+        },
+
+        StoreRollback
+        {
+            sequence: u64,
+            checkpoint: u64
+        }
+        |e| {
+            format_args!("the store has been rolled back behind the sealed sequence: sequence={} checkpoint={}", e.sequence, e.checkpoint)
+        },
+
+        StoreCheckpointRequired
+        {
+            sequence: u64
+        }
+        |e| {
+            format_args!("the checkpoint of the store sequence is required once the store has a sequence: sequence={}", e.sequence)
+        },
+
+        InvalidStoreCheckpoint
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid store checkpoint: descr={}", e.descr)
+        },
+
+        InvalidStoreSequence
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("the store sequence is neither the latest one that the enclave has observed nor the next one: descr={}", e.descr)
+        },
+
+        StoreSequenceNotFinalized
+        {
+            sequence: u64
+        }
+        |e| {
+            format_args!("the host has committed the prepared commit of the store sequence but not acknowledged it: sequence={}", e.sequence)
+        },
+
+        StoreSequenceNotCommitted
+        {
+            sequence: u64
+        }
+        |e| {
+            format_args!("the store doesn't have the prepared commit of the store sequence: sequence={}", e.sequence)
+        },
+
+        NoPreparedStoreSequence
+        {
+            sequence: u64
+        }
+        |e| {
+            format_args!("no commit of the store sequence has been prepared: sequence={}", e.sequence)
+        },
+
+        SealedRecordMismatch
+        {
+            key: String
+        }
+        |e| {
+            format_args!("the sealed record is not the one bound to the store sequence: key={}", e.key)
+        },
+
+        InvalidOperatorSet
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid operator set: descr={}", e.descr)
+        },
+
+        InvalidOperatorSetNonce
+        {
+            expected: u64,
+            actual: u64
+        }
+        |e| {
+            format_args!("invalid operator set nonce: expected={} actual={}", e.expected, e.actual)
+        },
+
+        InsufficientOperatorApprovals
+        {
+            approvals: u64,
+            threshold: u64
+        }
+        |e| {
+            format_args!("insufficient operator approvals: approvals={} threshold={}", e.approvals, e.threshold)
+        },
+
+        BootstrapOperatorNotConfigured
+        |_| { "the enclave has been built without the bootstrap operator, which must approve the initial operator set" },
+
+        SessionNotBegun
+        |_| { "no session challenge has been issued, or it has already been used" },
+
+        NotAnOperator
+        {
+            signer: String
+        }
+        |e| {
+            format_args!("the signer of the session challenge is not an operator: signer={}", e.signer)
+        },
+
+        SessionRequired
+        |_| { "a session must be opened before executing commands" },
+
+        OperatorCredentialRequired
+        |_| { "a session must be opened with the signature of an operator" },
+
+        InvalidSessionToken
+        |_| { "the session token doesn't match the open session" },
+
+        InvalidConstituentKey
+        {
+            index: usize,
+            descr: String
+        }
+        |e| {
+            format_args!("invalid constituent key: index={} descr={}", e.index, e.descr)
+        },
+
+        EnclaveKeyRevoked
+        {
+            address: String
+        }
+        |e| {
+            format_args!("the enclave key has been revoked: address={}", e.address)
+        },
+
+        InvalidRevocationRecord
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid revocation record: descr={}", e.descr)
+        },
     }
 }
 
@@ -38,3 +198,25 @@ impl From<crypto::Error> for Error {
         Self::crypto(value)
     }
 }
+
+impl Error {
+    /// Maps the error into the stable error code exposed to the host
+    pub fn code(&self) -> ErrorCode {
+        match self.detail() {
+            ErrorDetail::LightClientCommand(e) => e.source.code(),
+            ErrorDetail::EnclaveManageCommand(e) => e.source.code(),
+            ErrorDetail::InvalidCommand(_)
+            | ErrorDetail::InvalidOperatorSet(_)
+            | ErrorDetail::InvalidOperatorSetNonce(_)
+            | ErrorDetail::InsufficientOperatorApprovals(_)
+            | ErrorDetail::SessionNotBegun(_)
+            | ErrorDetail::NotAnOperator(_)
+            | ErrorDetail::SessionRequired(_)
+            | ErrorDetail::OperatorCredentialRequired(_)
+            | ErrorDetail::InvalidSessionToken(_)
+            | ErrorDetail::InvalidConstituentKey(_)
+            | ErrorDetail::EnclaveKeyRevoked(_) => ErrorCode::InvalidInput,
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
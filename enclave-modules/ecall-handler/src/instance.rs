@@ -0,0 +1,340 @@
+use crate::prelude::*;
+use crate::{Error, Result};
+use core::sync::atomic::{AtomicU64, Ordering};
+use crypto::sgx::rand::rand_slice;
+use crypto::sgx::sealing::{seal_bytes, seal_counter, unseal_bytes, unseal_counter};
+use crypto::{EnclaveKey, Keccak256, SealedEnclaveKey, SealingKey, Signer};
+use ecall_commands::{
+    FinalizeStoreSequenceInput, FinalizeStoreSequenceResponse, FinalizedStoreSequence,
+    StartInstanceInput, StartInstanceResponse, StoreCheckpoint, StoreRecovery, StoreSequenceRecord,
+    REVOCATION_RECORD_KEY, STORE_ID_SIZE, STORE_SEQUENCE_KEY,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use spin::Mutex;
+use store::KVStore;
+
+const INSTANCE_COUNTER_KEY: &[u8] = b"lcp/instance_counter";
+
+/// The domain of the sealed sequence record, which must not be confused with other sealed data
+const SEQUENCE_RECORD_DOMAIN: &[u8] = b"lcp-store-sequence:";
+
+/// The domain of the sealed finalized sequence, which the host must not be able to give as a sequence record
+const FINALIZED_SEQUENCE_DOMAIN: &[u8] = b"lcp-store-finalized-sequence:";
+
+/// The keys of the sealed records that each sequence record binds with their digests
+const BOUND_RECORD_KEYS: &[&[u8]] = &[REVOCATION_RECORD_KEY, OPERATOR_SET_KEY];
+
+/// The counter of the instance started by this enclave. Zero means that no instance has been started.
+static INSTANCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The sequence of the store that this enclave has finalized, which the store must never go behind
+static STORE_SEQUENCE: Mutex<Option<SequenceState>> = Mutex::new(None);
+
+struct SequenceState {
+    finalized: FinalizedStoreSequence,
+    /// keccak256 of the sealed record that the finalized one is chained to
+    previous: [u8; 32],
+    /// The prepared commit that waits for the host to acknowledge the commit of its transaction
+    pending: Option<(FinalizedStoreSequence, [u8; 32])>,
+}
+
+/// Increment the sealed counter in the store and bind this enclave to the new value
+///
+/// Any enclave that started an instance earlier on the same store becomes stale and can no longer execute commands.
+/// Once a command has changed the store, the host must give the checkpoint of the finalized sequence,
+/// and the instance recovers the sequence of the store with the following rules:
+/// - the record in the store is the finalized one: the host discarded or never committed the transaction
+///   of the last prepared commit, or there has been none since the checkpoint.
+/// - the record is the prepared commit chained to the finalized one: the host committed the transaction but failed
+///   to acknowledge it, or to record the finalized sequence. The instance finalizes it and returns the new checkpoint.
+/// - otherwise the store has been rolled back or replaced, and the instance refuses to start.
+///
+/// The first instance on a store writes the genesis record, whose finalized sequence is the first checkpoint.
+pub(crate) fn start_instance(
+    store: &mut dyn KVStore,
+    input: StartInstanceInput,
+) -> Result<StartInstanceResponse> {
+    let checkpoint: Option<FinalizedStoreSequence> = input
+        .store_checkpoint
+        .map(|sealed| unseal_domain(FINALIZED_SEQUENCE_DOMAIN, &sealed))
+        .transpose()?;
+    let (record, sealed) = match (load_record(store)?, checkpoint) {
+        (Some((record, sealed)), Some(checkpoint)) => {
+            if record.sequence < checkpoint.sequence {
+                return Err(Error::store_rollback(record.sequence, checkpoint.sequence));
+            }
+            checkpoint
+                .recover(&record, &sealed)
+                .map_err(|e| Error::invalid_store_checkpoint(e.to_string()))?;
+            (record, sealed)
+        }
+        // the host failed to record the genesis sequence, on which no command has changed the store
+        (Some((record, sealed)), None) if record.sequence == 0 => (record, sealed),
+        (Some((record, _)), None) => {
+            return Err(Error::store_checkpoint_required(record.sequence));
+        }
+        // the store has lost the sequence that the host checkpointed
+        (None, Some(checkpoint)) => return Err(Error::store_rollback(0, checkpoint.sequence)),
+        (None, None) => {
+            let mut store_id = [0u8; STORE_ID_SIZE];
+            rand_slice(&mut store_id)?;
+            let record = StoreSequenceRecord::genesis(store_id);
+            let sealed = seal_domain(SEQUENCE_RECORD_DOMAIN, &record)?;
+            store.set(STORE_SEQUENCE_KEY.to_vec(), sealed.clone());
+            (record, sealed)
+        }
+    };
+    let finalized = FinalizedStoreSequence::new(&record, &sealed);
+    {
+        // an enclave that has finalized a later sequence must not start on an earlier store
+        let mut state = STORE_SEQUENCE.lock();
+        if let Some(state) = state.as_ref() {
+            if record.sequence < state.finalized.sequence {
+                return Err(Error::store_rollback(
+                    record.sequence,
+                    state.finalized.sequence,
+                ));
+            }
+            state
+                .finalized
+                .recover(&record, &sealed)
+                .map_err(|e| Error::invalid_store_sequence(e.to_string()))?;
+        }
+        *state = Some(SequenceState {
+            finalized: finalized.clone(),
+            previous: record.previous,
+            pending: None,
+        });
+    }
+
+    let counter = latest_counter(store)? + 1;
+    store.set(INSTANCE_COUNTER_KEY.to_vec(), seal_counter(counter)?);
+    INSTANCE_COUNTER.store(counter, Ordering::SeqCst);
+    Ok(StartInstanceResponse {
+        counter,
+        store_sequence: record.sequence,
+        sealed_sequence: Some(seal_domain(FINALIZED_SEQUENCE_DOMAIN, &finalized)?),
+    })
+}
+
+/// Returns an error unless this enclave has started the latest instance on the store
+pub(crate) fn check_instance(store: &dyn KVStore) -> Result<()> {
+    let started = INSTANCE_COUNTER.load(Ordering::SeqCst);
+    if started == 0 {
+        return Err(Error::instance_not_started());
+    }
+    let latest = latest_counter(store)?;
+    if started != latest {
+        return Err(Error::stale_instance(started, latest));
+    }
+    Ok(())
+}
+
+/// Prepare the commit of the next sequence in the transaction of a command that changes the store
+///
+/// The next record is chained to the finalized one and binds the sealed records that the command may have written.
+/// The host verifies the returned prepared commit with the enclave key of the command, commits the transaction,
+/// and then acknowledges the commit with `finalize_store_sequence`.
+/// Until then, the enclave refuses to prepare another commit on the committed one. If the host discarded the transaction
+/// instead, the prepared commit is abandoned and the next one is prepared on the finalized sequence again.
+///
+/// This is called only in the transactions that the host serializes with the lock of the store sequence.
+pub(crate) fn advance_store_sequence(
+    store: &mut dyn KVStore,
+    sealed_ek: Option<&SealedEnclaveKey>,
+) -> Result<StoreCheckpoint> {
+    check_instance(store)?;
+    let mut state = STORE_SEQUENCE.lock();
+    let state = state.as_mut().ok_or_else(Error::instance_not_started)?;
+    let (record, sealed) =
+        load_record(store)?.ok_or_else(|| Error::store_rollback(0, state.finalized.sequence))?;
+    if let Some((pending, _)) = state.pending.as_ref() {
+        if *pending == FinalizedStoreSequence::new(&record, &sealed) {
+            return Err(Error::store_sequence_not_finalized(pending.sequence));
+        }
+        // the host discarded the transaction of the prepared commit
+        state.pending = None;
+    }
+    if record.sequence < state.finalized.sequence {
+        return Err(Error::store_rollback(
+            record.sequence,
+            state.finalized.sequence,
+        ));
+    }
+    match state
+        .finalized
+        .recover(&record, &sealed)
+        .map_err(|e| Error::invalid_store_sequence(e.to_string()))?
+    {
+        StoreRecovery::Finalized => {}
+        StoreRecovery::Prepared => {
+            return Err(Error::invalid_store_sequence(
+                "the store has a prepared commit that has been abandoned".to_string(),
+            ))
+        }
+    }
+
+    let bound_records = BOUND_RECORD_KEYS
+        .iter()
+        .filter_map(|key| store.get(key).map(|bz| (key.to_vec(), bz.keccak256())))
+        .collect();
+    let next = record.next(&sealed, bound_records);
+    let sealed_sequence = seal_domain(SEQUENCE_RECORD_DOMAIN, &next)?;
+    store.set(STORE_SEQUENCE_KEY.to_vec(), sealed_sequence.clone());
+    state.pending = Some((
+        FinalizedStoreSequence::new(&next, &sealed_sequence),
+        next.previous,
+    ));
+    let mut checkpoint = StoreCheckpoint {
+        sequence: next.sequence,
+        sealed_sequence,
+        signature: vec![],
+    };
+    if let Some(sealed_ek) = sealed_ek {
+        checkpoint.signature = EnclaveKey::unseal(sealed_ek)?.sign(&checkpoint.sign_bytes())?;
+    }
+    Ok(checkpoint)
+}
+
+/// Finalize the prepared commit whose transaction the host has committed, and returns the sealed finalized sequence
+///
+/// The store must have the prepared record, which proves that the host has committed it.
+/// Acknowledging the finalized sequence again returns it again, so the host can retry a lost response.
+pub(crate) fn finalize_store_sequence(
+    store: &dyn KVStore,
+    input: FinalizeStoreSequenceInput,
+) -> Result<FinalizeStoreSequenceResponse> {
+    let mut state = STORE_SEQUENCE.lock();
+    let state = state.as_mut().ok_or_else(Error::instance_not_started)?;
+    let (pending, previous) = match state.pending.clone() {
+        Some((pending, previous)) if pending.sequence == input.sequence => (pending, previous),
+        _ if state.finalized.sequence == input.sequence => {
+            return Ok(FinalizeStoreSequenceResponse {
+                sealed_sequence: seal_domain(FINALIZED_SEQUENCE_DOMAIN, &state.finalized)?,
+            })
+        }
+        _ => return Err(Error::no_prepared_store_sequence(input.sequence)),
+    };
+    let committed = load_record(store)?.map_or(false, |(record, sealed)| {
+        FinalizedStoreSequence::new(&record, &sealed) == pending
+    });
+    if !committed {
+        return Err(Error::store_sequence_not_committed(input.sequence));
+    }
+    state.finalized = pending;
+    state.previous = previous;
+    state.pending = None;
+    Ok(FinalizeStoreSequenceResponse {
+        sealed_sequence: seal_domain(FINALIZED_SEQUENCE_DOMAIN, &state.finalized)?,
+    })
+}
+
+/// Returns an error unless `sealed` is the sealed record bound to `key` by the sequence of the store,
+/// or `sealed` is None and no record is bound to it
+///
+/// The record of the store must be the finalized one or the prepared commit that the host has committed.
+/// The commands that don't change the store may read the snapshot taken just before the latest commit,
+/// so the record that the finalized one is chained to is also accepted.
+/// Before an instance starts, e.g. to open a session, the record is only checked against the sequence record of the store.
+pub(crate) fn check_bound_record(
+    store: &dyn KVStore,
+    key: &[u8],
+    sealed: Option<&[u8]>,
+) -> Result<()> {
+    let record = {
+        let guard = STORE_SEQUENCE.lock();
+        let state = match guard.as_ref() {
+            Some(state) => state,
+            // the freshness of the store can't be verified until an instance starts from the checkpoint,
+            // so the record is only checked against the sequence record of the store
+            None => {
+                let record = load_record(store)?.map(|(record, _)| record);
+                return check_record_digest(record.as_ref(), key, sealed);
+            }
+        };
+        let (record, sealed_record) = load_record(store)?
+            .ok_or_else(|| Error::store_rollback(0, state.finalized.sequence))?;
+        let digest = sealed_record.keccak256();
+        let is_previous =
+            record.sequence + 1 == state.finalized.sequence && digest == state.previous;
+        let is_pending =
+            matches!(state.pending.as_ref(), Some((pending, _)) if pending.record == digest);
+        if !is_previous && !is_pending {
+            if record.sequence < state.finalized.sequence {
+                return Err(Error::store_rollback(
+                    record.sequence,
+                    state.finalized.sequence,
+                ));
+            }
+            match state
+                .finalized
+                .recover(&record, &sealed_record)
+                .map_err(|e| Error::invalid_store_sequence(e.to_string()))?
+            {
+                StoreRecovery::Finalized => {}
+                StoreRecovery::Prepared => {
+                    return Err(Error::invalid_store_sequence(
+                        "the store has a prepared commit that has been abandoned".to_string(),
+                    ))
+                }
+            }
+        }
+        record
+    };
+    check_record_digest(Some(&record), key, sealed)
+}
+
+fn check_record_digest(
+    record: Option<&StoreSequenceRecord>,
+    key: &[u8],
+    sealed: Option<&[u8]>,
+) -> Result<()> {
+    if record.and_then(|record| record.bound_record(key)) != sealed.map(|bz| bz.keccak256()) {
+        return Err(Error::sealed_record_mismatch(
+            String::from_utf8_lossy(key).into_owned(),
+        ));
+    }
+    Ok(())
+}
+
+fn latest_counter(store: &dyn KVStore) -> Result<u64> {
+    match store.get(INSTANCE_COUNTER_KEY) {
+        Some(sealed) => Ok(unseal_counter(&sealed)?),
+        None => Ok(0),
+    }
+}
+
+/// Returns the sequence of the store, which is zero until a command changes the store
+pub(crate) fn store_sequence(store: &dyn KVStore) -> Result<u64> {
+    Ok(load_record(store)?.map_or(0, |(record, _)| record.sequence))
+}
+
+/// Returns the sequence record of the store with its sealed bytes
+fn load_record(store: &dyn KVStore) -> Result<Option<(StoreSequenceRecord, Vec<u8>)>> {
+    match store.get(STORE_SEQUENCE_KEY) {
+        Some(sealed) => Ok(Some((
+            unseal_domain(SEQUENCE_RECORD_DOMAIN, &sealed)?,
+            sealed,
+        ))),
+        None => Ok(None),
+    }
+}
+
+fn seal_domain<T: Serialize>(domain: &[u8], value: &T) -> Result<Vec<u8>> {
+    let mut bz = domain.to_vec();
+    bz.extend(
+        bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map_err(|e| Error::invalid_command(e.to_string()))?,
+    );
+    Ok(seal_bytes(&bz)?)
+}
+
+fn unseal_domain<T: DeserializeOwned>(domain: &[u8], sealed: &[u8]) -> Result<T> {
+    let bz = unseal_bytes(sealed)?;
+    let value = bz
+        .strip_prefix(domain)
+        .ok_or_else(|| Error::invalid_command("invalid sealed store sequence".into()))?;
+    let (value, _) = bincode::serde::decode_from_slice(value, bincode::config::standard())
+        .map_err(|e| Error::invalid_command(e.to_string()))?;
+    Ok(value)
+}
@@ -22,7 +22,12 @@ mod prelude {
 pub use errors::{Error, Result};
 pub use router::dispatch;
 
+mod constituents;
 mod enclave_manage;
 mod errors;
+mod instance;
 mod light_client;
+mod operators;
+mod revocation;
 mod router;
+mod session;
@@ -1,11 +1,16 @@
+use super::message_cache::{insert_signed_message, is_signed_message};
+use super::prover::prove_client_message;
 use crate::light_client::Error;
 use crate::prelude::*;
 use context::Context;
-use crypto::{EnclavePublicKey, Signer, Verifier};
-use ecall_commands::{AggregateMessagesInput, AggregateMessagesResponse, LightClientResponse};
+use crypto::{Address, SignatureScheme, Signer};
+use ecall_commands::{
+    AggregateMessagesInput, AggregateMessagesResponse, LightClientResponse,
+    VerifyAggregateMessagesInput, VerifyAggregateMessagesResponse,
+};
 use light_client::{
-    commitments::{self, prove_commitment, ProxyMessage, UpdateStateProxyMessage},
-    HostContext, LightClientResolver,
+    commitments::{self, ProxyMessage, UpdateStateProxyMessage},
+    ClientReader, HostContext, LightClientResolver,
 };
 use store::KVStore;
 
@@ -25,40 +30,130 @@ pub fn aggregate_messages<R: LightClientResolver, S: KVStore, K: Signer>(
             "messages and signatures must have the same length".into(),
         ));
     }
+    if !input.signers.is_empty() && input.messages.len() != input.signers.len() {
+        return Err(Error::invalid_argument(
+            "signers must be empty or have the same length as messages".into(),
+        ));
+    }
+
+    // the messages were signed with the scheme of the client, and so is the aggregate
+    ctx.client_type(&input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
 
-    let ek = ctx.get_enclave_key();
-    let pk = ek.pubkey().map_err(Error::crypto)?;
+    // the router has verified that the other keys are attested to this enclave and not revoked,
+    // so the messages signed by them can be trusted as well
+    let mut keys = vec![ctx
+        .get_enclave_key()
+        .pubkey()
+        .map_err(Error::crypto)?
+        .as_address()];
+    for ek in ctx.get_constituent_enclave_keys() {
+        keys.push(ek.pubkey().map_err(Error::crypto)?.as_address());
+    }
+    let signers = input.constituent_signers();
+    let signer_of: Vec<Address> = (0..input.messages.len())
+        .map(|index| input.signer_of(index))
+        .collect();
 
     let messages = input
         .messages
         .into_iter()
-        .map(|m| ProxyMessage::from_bytes(&m)?.try_into())
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
         .zip(input.signatures.iter())
-        .map(|(m, s)| -> Result<_, Error> {
-            verify_message(&pk, &m, s)?;
-            m.context.validate(ctx.host_timestamp())?;
+        .enumerate()
+        .map(|(index, (any, s))| {
+            let signer = signer_of[index];
+            if !keys.contains(&signer) {
+                return Err(Error::unknown_aggregate_message_signer(index, signer));
+            }
+            let bz = any.value.clone();
+            let m: UpdateStateProxyMessage = ProxyMessage::try_from(any)
+                .and_then(TryInto::try_into)
+                .map_err(|e| Error::invalid_aggregate_message(index, e))?;
+            // the messages signed by this enclave were verified when they were created
+            if !is_signed_message(&input.client_id, scheme, signer, &bz, s) {
+                verify_message(scheme, signer, &bz, s)
+                    .map_err(|e| Error::invalid_aggregate_message_signature(index, e))?;
+            }
+            m.context
+                .validate(ctx.host_timestamp())
+                .map_err(|e| Error::invalid_aggregate_message(index, e))?;
             Ok(m)
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let message = ProxyMessage::from(commitments::aggregate_messages(messages)?);
-    let proof = prove_commitment(ek, input.signer, message)?;
+    let proof = prove_client_message(
+        ctx,
+        &input.client_id,
+        input.signer,
+        scheme,
+        schema_version,
+        message,
+    )?;
+    // the signers are not a part of the message, so they are bound to it by another signature
+    let constituents_signature = ctx
+        .get_enclave_key()
+        .sign(&AggregateMessagesResponse::constituents_sign_bytes(
+            &proof.message,
+            &signers,
+        ))
+        .map_err(Error::crypto)?;
 
     Ok(LightClientResponse::AggregateMessages(
-        AggregateMessagesResponse(proof),
+        AggregateMessagesResponse(proof, signers, constituents_signature),
+    ))
+}
+
+/// Verify the signatures of a batch of the messages to aggregate, and keep the verified messages in the cache
+///
+/// Nothing is signed or stored, so the batches of an aggregation can be verified concurrently.
+/// `aggregate_messages` still checks that each signer is one of its keys before it trusts the cache.
+pub fn verify_aggregate_messages<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: VerifyAggregateMessagesInput,
+) -> Result<LightClientResponse, Error> {
+    if input.messages.len() != input.signatures.len() || input.messages.len() != input.signers.len()
+    {
+        return Err(Error::invalid_argument(
+            "messages, signatures and signers must have the same length".into(),
+        ));
+    }
+    ctx.client_type(&input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+
+    let mut verified = 0;
+    for (index, ((any, s), signer)) in input
+        .messages
+        .into_iter()
+        .zip(input.signatures.iter())
+        .zip(input.signers.into_iter())
+        .enumerate()
+    {
+        let bz = any.value.clone();
+        if is_signed_message(&input.client_id, scheme, signer, &bz, s) {
+            continue;
+        }
+        let _: UpdateStateProxyMessage = ProxyMessage::try_from(any)
+            .and_then(TryInto::try_into)
+            .map_err(|e| Error::invalid_aggregate_message(index, e))?;
+        verify_message(scheme, signer, &bz, s)
+            .map_err(|e| Error::invalid_aggregate_message_signature(index, e))?;
+        insert_signed_message(&input.client_id, scheme, signer, &bz, s);
+        verified += 1;
+    }
+    Ok(LightClientResponse::VerifyAggregateMessages(
+        VerifyAggregateMessagesResponse { verified },
     ))
 }
 
+/// Verify the signature over the bytes of the message as they were signed,
+/// which may be in any schema version that the message decodes from
 fn verify_message(
-    verifier: &EnclavePublicKey,
-    message: &UpdateStateProxyMessage,
+    scheme: SignatureScheme,
+    signer: Address,
+    message_bytes: &[u8],
     signature: &[u8],
-) -> Result<(), Error> {
-    let message_bytes = ProxyMessage::UpdateState(message.clone()).to_bytes();
-    verifier
-        .verify(&message_bytes, signature)
-        .map_err(Error::crypto)?;
-    Ok(())
+) -> Result<(), crypto::Error> {
+    scheme.verify_signer(message_bytes, signature, signer)
 }
@@ -0,0 +1,40 @@
+use super::prover::prove;
+use super::registry::get_light_client_by_client_id;
+use crate::light_client::Error;
+use crate::prelude::*;
+use context::Context;
+use crypto::Signer;
+use ecall_commands::{LightClientResponse, UpdateClientParamsInput, UpdateClientParamsResponse};
+use light_client::commitments::ClientParamsProxyMessage;
+use light_client::{ClientKeeper, ClientReader, LightClientResolver};
+use store::KVStore;
+
+pub fn update_client_params<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: UpdateClientParamsInput,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+
+    let message = ClientParamsProxyMessage {
+        client_id: input.client_id.to_string(),
+        params: input.params,
+        timestamp: input.current_timestamp,
+    };
+    message.validate()?;
+
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    lc.validate_client_params(ctx, &input.client_id, &message.params)?;
+    ctx.store_client_params(input.client_id, message.params.clone())?;
+
+    Ok(LightClientResponse::UpdateClientParams(
+        UpdateClientParamsResponse(prove(
+            ctx,
+            input.signer,
+            scheme,
+            schema_version,
+            message.into(),
+        )?),
+    ))
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
+use ecall_commands::ErrorCode;
 use flex_error::*;
+use light_client::ErrorKind;
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
@@ -12,9 +14,44 @@ define_error! {
             format_args!("invalid argument: descr={}", e.descr)
         },
 
+        InvalidAggregateMessage
+        {
+            index: usize
+        }
+        [light_client::commitments::Error]
+        |e| {
+            format_args!("invalid message to aggregate: index={}", e.index)
+        },
+
+        InvalidAggregateMessageSignature
+        {
+            index: usize
+        }
+        [crypto::Error]
+        |e| {
+            format_args!("invalid signature of the message to aggregate: index={}", e.index)
+        },
+
+        UnknownAggregateMessageSigner
+        {
+            index: usize,
+            signer: crypto::Address
+        }
+        |e| {
+            format_args!("the sealed key of the signer of the message to aggregate is not given: index={} signer={}", e.index, e.signer)
+        },
+
         SealedEnclaveKeyNotFound
         |_| { "Sealed EnclaveKey not found" },
 
+        CommandDisabled
+        {
+            feature: String
+        }
+        |e| {
+            format_args!("the command is not supported by this enclave build: feature={}", e.feature)
+        },
+
         LightClient
         [light_client::Error]
         |_| { "LightClient error" },
@@ -35,6 +72,10 @@ define_error! {
         {}
         [lcp_types::TypeError]
         |_| {"Type error"},
+
+        Time
+        [lcp_types::TimeError]
+        |_| {"Time error"},
     }
 }
 
@@ -55,3 +96,42 @@ impl From<lcp_types::TypeError> for Error {
         Error::lcp_type(err)
     }
 }
+
+impl Error {
+    /// Maps the error into the stable error code exposed to the host
+    pub fn code(&self) -> ErrorCode {
+        self.detail().code()
+    }
+}
+
+impl ErrorDetail {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidArgument(_)
+            | Self::LcpType(_)
+            | Self::UnknownAggregateMessageSigner(_) => ErrorCode::InvalidInput,
+            Self::LightClient(e) => kind_to_code(e.source.kind()),
+            Self::Commitment(e) => kind_to_code(ErrorKind::from_commitment_error(&e.source)),
+            Self::InvalidAggregateMessage(e) => {
+                kind_to_code(ErrorKind::from_commitment_error(&e.source))
+            }
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
+
+fn kind_to_code(kind: ErrorKind) -> ErrorCode {
+    match kind {
+        ErrorKind::Unknown => ErrorCode::Unknown,
+        ErrorKind::ClientNotFound => ErrorCode::ClientNotFound,
+        ErrorKind::ConsensusStateNotFound => ErrorCode::ConsensusStateNotFound,
+        ErrorKind::ClientFrozen => ErrorCode::ClientFrozen,
+        ErrorKind::Expired => ErrorCode::TrustingPeriodExpired,
+        ErrorKind::HeaderFromFuture => ErrorCode::HeaderFromFuture,
+        ErrorKind::HeaderOlderThanTrusted => ErrorCode::HeaderOlderThanTrusted,
+        ErrorKind::InvalidHeader => ErrorCode::InvalidHeader,
+        ErrorKind::InvalidValidatorSet => ErrorCode::InvalidValidatorSet,
+        ErrorKind::ProofMismatch => ErrorCode::ProofMismatch,
+        ErrorKind::BisectionRequired => ErrorCode::BisectionRequired,
+    }
+}
@@ -0,0 +1,71 @@
+use crate::light_client::Error;
+use crate::prelude::*;
+use context::Context;
+use crypto::Signer;
+use lcp_types::{ClientId, Time};
+use light_client::{
+    ClientKeeper, ClientReader, Error as LightClientError, HostContext, LightClient,
+    LightClientResolver,
+};
+use store::KVStore;
+
+/// Returns an error if the client has expired at the host timestamp of the context
+///
+/// A client expires when its trusting period has elapsed since the timestamp of its latest consensus state.
+/// Once the client is marked by `mark_if_expired`, it stays expired even if the host timestamp goes back.
+#[cfg(feature = "verify")]
+pub(crate) fn ensure_not_expired<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    lc: &dyn LightClient,
+    client_id: &ClientId,
+) -> Result<(), Error> {
+    let expired_at = match ctx.expired_at(client_id)? {
+        Some(expired_at) => Some(expired_at),
+        None => client_expiry(ctx, lc, client_id)?.filter(|expiry| *expiry <= ctx.host_timestamp()),
+    };
+    match expired_at {
+        Some(expired_at) => {
+            Err(LightClientError::client_expired(client_id.clone(), expired_at).into())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Mark the client as expired if `expiry` returned by `client_expiry` has passed at the host timestamp of the context
+///
+/// Returns true if the client is marked as expired, including by a previous call.
+#[cfg(feature = "update")]
+pub(crate) fn mark_if_expired<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    client_id: &ClientId,
+    expiry: Option<Time>,
+) -> Result<bool, Error> {
+    if ctx.expired_at(client_id)?.is_some() {
+        return Ok(true);
+    }
+    match expiry {
+        Some(expiry) if expiry <= ctx.host_timestamp() => {
+            ctx.store_expired_at(client_id.clone(), expiry)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Returns the time when the trusting period of the client elapses, if the light client has the notion of it
+pub(crate) fn client_expiry<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    lc: &dyn LightClient,
+    client_id: &ClientId,
+) -> Result<Option<Time>, Error> {
+    let trusting_period = match lc.trusting_period(ctx, client_id)? {
+        Some(trusting_period) => trusting_period,
+        None => return Ok(None),
+    };
+    match lc.latest_timestamp(ctx, client_id)? {
+        Some(latest_timestamp) => Ok(Some(
+            (latest_timestamp + trusting_period).map_err(Error::time)?,
+        )),
+        None => Ok(None),
+    }
+}
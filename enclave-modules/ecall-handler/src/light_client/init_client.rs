@@ -1,37 +1,111 @@
+use super::prover::{prove, prove_client_message};
 use crate::light_client::Error;
 use crate::prelude::*;
 use context::Context;
 use core::str::FromStr;
 use crypto::Signer;
-use ecall_commands::{InitClientInput, InitClientResponse, LightClientResponse};
+use ecall_commands::{CommandEvent, InitClientInput, InitClientResponse, LightClientResponse};
 use lcp_types::{Any, ClientId};
-use light_client::commitments::{prove_commitment, CommitmentProof};
-use light_client::{ClientKeeper, ClientReader, LightClientResolver};
+use light_client::commitments::{
+    gen_state_digest, gen_state_id_from_any, CommitmentProof, CreateClientProxyMessage,
+    MESSAGE_SCHEMA_VERSION, MESSAGE_SCHEMA_VERSION_1,
+};
+use light_client::{
+    ClientKeeper, ClientReader, HostClientKeeper, LightClientResolver, RegistryError,
+};
 use store::KVStore;
 
 pub fn init_client<R: LightClientResolver, S: KVStore, K: Signer>(
     ctx: &mut Context<R, S, K>,
     input: InitClientInput,
+    events: &mut Vec<CommandEvent>,
 ) -> Result<LightClientResponse, Error> {
     ctx.set_timestamp(input.current_timestamp);
 
+    if let Some(version) = input.message_schema_version {
+        if version != MESSAGE_SCHEMA_VERSION_1 && version != MESSAGE_SCHEMA_VERSION {
+            return Err(Error::invalid_argument(format!(
+                "unsupported message schema version: version={}",
+                version
+            )));
+        }
+    }
+
     let any_client_state: Any = input.any_client_state.into();
     let any_consensus_state: Any = input.any_consensus_state.into();
-    let lc = ctx.get_light_client(&any_client_state.type_url).unwrap();
-    let ek = ctx.get_enclave_key();
+    let (version, lc) = ctx
+        .get_versioned_light_client(&any_client_state.type_url, None)
+        .ok_or_else(|| {
+            Error::light_client_registry(RegistryError::type_url_not_found(
+                any_client_state.type_url.clone(),
+            ))
+        })?;
     let res = lc.create_client(ctx, any_client_state.clone(), any_consensus_state.clone())?;
     let client_type = lc.client_type();
     let client_id = gen_client_id(client_type.clone(), ctx.client_counter()?)?;
 
-    ctx.store_client_type(client_id.clone(), client_type)?;
+    ctx.store_client_type(client_id.clone(), client_type.clone())?;
+    ctx.store_light_client_version(client_id.clone(), version)?;
+    let genesis = if input.prove_genesis {
+        Some(CreateClientProxyMessage {
+            client_id: client_id.to_string(),
+            height: res.height,
+            client_state_digest: gen_state_digest(&any_client_state),
+            consensus_state_digest: gen_state_digest(&any_consensus_state),
+            state_id: gen_state_id_from_any(&any_client_state, &any_consensus_state)?,
+            timestamp: input.current_timestamp,
+        })
+    } else {
+        None
+    };
     ctx.store_any_client_state(client_id.clone(), any_client_state)?;
     ctx.store_any_consensus_state(client_id.clone(), res.height, any_consensus_state)?;
+    ctx.index_consensus_state(&client_id, res.height)?;
+    if let Some(prefix) = input.commitment_prefix {
+        ctx.store_commitment_prefix(client_id.clone(), prefix.into_vec())?;
+    }
+    if let Some(mode) = input.verification_mode {
+        ctx.store_verification_mode(client_id.clone(), mode)?;
+    }
+    if let Some(max_clock_drift) = input.max_clock_drift {
+        ctx.store_max_clock_drift(client_id.clone(), max_clock_drift)?;
+    }
+    if let Some(policy) = input.vote_extension_policy {
+        ctx.store_vote_extension_policy(client_id.clone(), policy)?;
+    }
+    let scheme = input.signature_scheme.unwrap_or_default();
+    if let Some(scheme) = input.signature_scheme {
+        ctx.store_signature_scheme(client_id.clone(), scheme)?;
+    }
+    let schema_version = input
+        .message_schema_version
+        .unwrap_or(MESSAGE_SCHEMA_VERSION_1);
+    if input.message_schema_version.is_some() {
+        ctx.store_message_schema_version(client_id.clone(), schema_version)?;
+    }
+    ctx.store_client_id(client_id.clone())?;
     ctx.increase_client_counter();
+    events.push(CommandEvent::ClientCreated {
+        client_id: client_id.clone(),
+        client_type,
+        height: res.height,
+    });
 
-    let proof = if res.prove {
-        prove_commitment(ek, input.signer, res.message)?
+    let proof = if let Some(genesis) = genesis {
+        prove(ctx, input.signer, scheme, schema_version, genesis.into())?
+    } else if res.prove {
+        prove_client_message(
+            ctx,
+            &client_id,
+            input.signer,
+            scheme,
+            schema_version,
+            res.message,
+        )?
     } else {
-        CommitmentProof::new_with_no_signature(res.message.to_bytes())
+        CommitmentProof::new_with_no_signature(
+            res.message.into_schema_version(schema_version)?.to_bytes(),
+        )
     };
     Ok(LightClientResponse::InitClient(InitClientResponse {
         client_id,
@@ -0,0 +1,58 @@
+use super::prover::prove;
+use super::registry::get_light_client_by_client_id;
+use crate::light_client::Error;
+use crate::prelude::*;
+use context::Context;
+use crypto::{SignatureScheme, Signer};
+use ecall_commands::{LightClientResponse, SignLatestHeightsInput, SignLatestHeightsResponse};
+use light_client::commitments::{
+    ClientLatestHeight, LatestHeightsProxyMessage, MESSAGE_SCHEMA_VERSION,
+};
+use light_client::{ClientReader, LightClientResolver};
+use store::KVStore;
+
+pub fn sign_latest_heights<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: SignLatestHeightsInput,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+
+    let mut client_ids = if input.client_ids.is_empty() {
+        ctx.client_ids()?
+    } else {
+        input.client_ids
+    };
+    // the message requires the clients to be sorted by client_id
+    client_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    client_ids.dedup();
+
+    let mut clients = Vec::with_capacity(client_ids.len());
+    for client_id in client_ids {
+        let lc = get_light_client_by_client_id(ctx, &client_id)?;
+        let height = lc.latest_height(ctx, &client_id)?;
+        let updated_at = ctx
+            .consensus_state_index(&client_id)?
+            .into_iter()
+            .find_map(|(h, t)| (h == height).then_some(t));
+        clients.push(ClientLatestHeight {
+            client_id: client_id.to_string(),
+            height,
+            updated_at,
+        });
+    }
+    let message = LatestHeightsProxyMessage {
+        timestamp: input.current_timestamp,
+        clients,
+    };
+    message.validate()?;
+
+    Ok(LightClientResponse::SignLatestHeights(
+        SignLatestHeightsResponse(prove(
+            ctx,
+            input.signer,
+            SignatureScheme::default(),
+            MESSAGE_SCHEMA_VERSION,
+            message.into(),
+        )?),
+    ))
+}
@@ -0,0 +1,81 @@
+use crate::prelude::*;
+use alloc::collections::{BTreeSet, VecDeque};
+use crypto::{Address, Keccak256, SignatureScheme};
+use lcp_types::ClientId;
+use spin::Mutex;
+
+/// The maximum number of messages kept in the cache. The oldest message is evicted first.
+const MESSAGE_CACHE_CAPACITY: usize = 1024;
+
+/// Messages signed by this enclave or whose signatures it has verified, which don't need to be verified again when they are aggregated
+///
+/// The cache lives in the enclave memory, so the host cannot insert entries into it.
+static SIGNED_MESSAGES: Mutex<Option<MessageCache>> = Mutex::new(None);
+
+#[derive(Default)]
+struct MessageCache {
+    digests: BTreeSet<[u8; 32]>,
+    order: VecDeque<[u8; 32]>,
+}
+
+/// Record the message of the client signed by the signer with the scheme, which must have been signed or verified by this enclave
+pub(crate) fn insert_signed_message(
+    client_id: &ClientId,
+    scheme: SignatureScheme,
+    signer: Address,
+    message: &[u8],
+    signature: &[u8],
+) {
+    let digest = message_digest(client_id, scheme, signer, message, signature);
+    let mut cache = SIGNED_MESSAGES.lock();
+    let cache = cache.get_or_insert_with(Default::default);
+    if !cache.digests.insert(digest) {
+        return;
+    }
+    cache.order.push_back(digest);
+    if cache.order.len() > MESSAGE_CACHE_CAPACITY {
+        if let Some(oldest) = cache.order.pop_front() {
+            cache.digests.remove(&oldest);
+        }
+    }
+}
+
+/// Returns true if the signature over the message of the client was produced or verified by this enclave with the signer and the scheme
+#[cfg(feature = "update")]
+pub(crate) fn is_signed_message(
+    client_id: &ClientId,
+    scheme: SignatureScheme,
+    signer: Address,
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let digest = message_digest(client_id, scheme, signer, message, signature);
+    SIGNED_MESSAGES
+        .lock()
+        .as_ref()
+        .map_or(false, |cache| cache.digests.contains(&digest))
+}
+
+/// The client and the scheme are a part of the digest, so that a message verified for a client is not trusted for another
+fn message_digest(
+    client_id: &ClientId,
+    scheme: SignatureScheme,
+    signer: Address,
+    message: &[u8],
+    signature: &[u8],
+) -> [u8; 32] {
+    let mut bz = Vec::new();
+    for field in [
+        client_id.as_str().as_bytes(),
+        scheme.digest.as_str().as_bytes(),
+        scheme.format.as_str().as_bytes(),
+    ] {
+        bz.extend_from_slice(&(field.len() as u64).to_be_bytes());
+        bz.extend_from_slice(field);
+    }
+    bz.extend_from_slice(&signer.to_vec());
+    bz.extend_from_slice(&(message.len() as u64).to_be_bytes());
+    bz.extend_from_slice(message);
+    bz.extend_from_slice(signature);
+    bz.keccak256()
+}
@@ -0,0 +1,130 @@
+use super::prover::prove;
+use super::registry::get_light_client_by_client_id;
+use crate::light_client::Error;
+use crate::prelude::*;
+use context::Context;
+use crypto::Signer;
+use ecall_commands::{
+    CommandEvent, LightClientResponse, MigrateClientInput, MigrateClientResponse,
+};
+use light_client::commitments::ClientMigrationProxyMessage;
+use light_client::{
+    versioned_client_type, ClientKeeper, ClientReader, LightClientResolver, RegistryError,
+};
+use store::KVStore;
+
+pub fn migrate_client<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: MigrateClientInput,
+    events: &mut Vec<CommandEvent>,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+
+    let client_id = input.client_id;
+    let scheme = ctx.signature_scheme(&client_id)?;
+    let schema_version = ctx.message_schema_version(&client_id)?;
+    let from_type_url = ctx.client_state(&client_id)?.to_proto().type_url;
+    let from_version = ctx.light_client_version(&client_id)?;
+
+    let source = get_light_client_by_client_id(ctx, &client_id)?;
+    let prev_state = source.latest_state(ctx, &client_id)?;
+    let height = prev_state.post_height;
+
+    let (to_version, target) = ctx
+        .get_versioned_light_client(&input.target_type_url, input.target_version)
+        .ok_or_else(|| match input.target_version {
+            Some(version) => Error::light_client_registry(RegistryError::version_not_found(
+                input.target_type_url.clone(),
+                version,
+            )),
+            None => Error::invalid_argument(format!(
+                "no light client is registered for the type url: {}",
+                input.target_type_url
+            )),
+        })?;
+    if from_type_url == input.target_type_url && to_version <= from_version {
+        return Err(Error::invalid_argument(format!(
+            "the client can only be migrated to a newer version of its implementation: current={} target={}",
+            versioned_client_type(&from_type_url, from_version),
+            versioned_client_type(&input.target_type_url, to_version)
+        )));
+    }
+    if source.client_type() != target.client_type() {
+        return Err(Error::invalid_argument(format!(
+            "the target implementation has another client type: current={} target={}",
+            source.client_type(),
+            target.client_type()
+        )));
+    }
+
+    // convert all the states before storing any of them, so that a failure leaves the client as it was
+    let mut heights: Vec<_> = ctx
+        .consensus_state_index(&client_id)?
+        .into_iter()
+        .map(|(height, _)| height)
+        .collect();
+    if !heights.contains(&height) {
+        heights.push(height);
+    }
+    let mut consensus_states = Vec::with_capacity(heights.len());
+    for height in heights {
+        let any_consensus_state = target.migrate_consensus_state(
+            &from_type_url,
+            from_version,
+            ctx.consensus_state(&client_id, &height)?,
+        )?;
+        target.validate_consensus_state(any_consensus_state.clone())?;
+        consensus_states.push((height, any_consensus_state));
+    }
+    let any_client_state =
+        target.migrate_client_state(&from_type_url, from_version, ctx.client_state(&client_id)?)?;
+    if any_client_state.type_url != input.target_type_url {
+        return Err(Error::invalid_argument(format!(
+            "the migrated client state has an unexpected type url: expected={} actual={}",
+            input.target_type_url, any_client_state.type_url
+        )));
+    }
+    target.validate_client_state(any_client_state.clone())?;
+
+    ctx.store_any_client_state(client_id.clone(), any_client_state)?;
+    for (height, any_consensus_state) in consensus_states {
+        ctx.store_any_consensus_state(client_id.clone(), height, any_consensus_state)?;
+    }
+    ctx.store_light_client_version(client_id.clone(), to_version)?;
+
+    // the migrated client must be readable by the target and keep its latest height
+    let target = get_light_client_by_client_id(ctx, &client_id)?;
+    let post_state = target.latest_state(ctx, &client_id)?;
+    if post_state.post_height != height {
+        return Err(Error::invalid_argument(format!(
+            "the migration changed the latest height: prev={} post={}",
+            height, post_state.post_height
+        )));
+    }
+
+    let message = ClientMigrationProxyMessage {
+        client_id: client_id.to_string(),
+        from_type_url: from_type_url.clone(),
+        from_version,
+        to_type_url: input.target_type_url.clone(),
+        to_version,
+        height,
+        prev_state_id: prev_state.post_state_id,
+        post_state_id: post_state.post_state_id,
+        timestamp: input.current_timestamp,
+    };
+    message.validate()?;
+
+    let proof = prove(ctx, input.signer, scheme, schema_version, message.into())?;
+    events.push(CommandEvent::ClientMigrated {
+        client_id,
+        from_type_url,
+        from_version,
+        to_type_url: input.target_type_url,
+        to_version,
+        height,
+    });
+    Ok(LightClientResponse::MigrateClient(MigrateClientResponse(
+        proof,
+    )))
+}
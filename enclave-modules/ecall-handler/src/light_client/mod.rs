@@ -1,16 +1,52 @@
-pub use aggregate_messages::aggregate_messages;
-pub use errors::Error;
+#[cfg(feature = "update")]
+pub use aggregate_messages::{aggregate_messages, verify_aggregate_messages};
+#[cfg(feature = "update")]
+pub use client_params::update_client_params;
+pub use errors::{Error, ErrorDetail};
+#[cfg(feature = "update")]
 pub use init_client::init_client;
-pub use query::query_client;
+#[cfg(feature = "verify")]
+pub use latest_heights::sign_latest_heights;
+#[cfg(feature = "update")]
+pub use migrate_client::migrate_client;
+#[cfg(feature = "update")]
+pub use prune::prune_client;
+pub use query::{
+    list_clients, query_capabilities, query_client, query_consensus_heights, validate_store,
+};
+#[cfg(feature = "update")]
+pub use resign_state::resign_latest_state;
 pub use router::dispatch;
+#[cfg(feature = "update")]
 pub use update_client::update_client;
-pub use verify_state::{verify_membership, verify_non_membership};
+#[cfg(feature = "verify")]
+pub use verify_state::{
+    verify_key_value, verify_membership, verify_membership_batch, verify_non_membership,
+};
 
+#[cfg(feature = "update")]
 mod aggregate_messages;
+#[cfg(feature = "update")]
+mod client_params;
 mod errors;
+#[cfg(any(feature = "verify", feature = "update"))]
+mod expiry;
+#[cfg(feature = "update")]
 mod init_client;
+#[cfg(feature = "verify")]
+mod latest_heights;
+mod message_cache;
+#[cfg(feature = "update")]
+mod migrate_client;
+mod prover;
+#[cfg(feature = "update")]
+mod prune;
 mod query;
 mod registry;
+#[cfg(feature = "update")]
+mod resign_state;
 mod router;
+#[cfg(feature = "update")]
 mod update_client;
+#[cfg(feature = "verify")]
 mod verify_state;
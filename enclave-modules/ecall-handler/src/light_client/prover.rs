@@ -0,0 +1,74 @@
+use super::message_cache::insert_signed_message;
+use crate::light_client::Error;
+use context::Context;
+use crypto::{Address, SignatureScheme, Signer};
+use lcp_types::{ClientId, Time};
+use light_client::commitments::{
+    prove_commitment_with_scheme, CommitmentProof, ProxyMessage, MESSAGE_SCHEMA_VERSION_1,
+};
+use light_client::LightClientResolver;
+use store::KVStore;
+
+/// Proves the message with the enclave key of the context
+///
+/// The messages of a client are signed with the scheme and converted to the message schema version
+/// configured for it at the client creation, and the messages that are not tied to a single client
+/// are signed with the default scheme and carry no field that depends on the version.
+/// If the key is being rotated out, the proof is also co-signed by the incoming key.
+pub(crate) fn prove<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    signer: Address,
+    scheme: SignatureScheme,
+    schema_version: u16,
+    message: ProxyMessage,
+) -> Result<CommitmentProof, Error> {
+    let message = message.into_schema_version(schema_version)?;
+    let mut proof = prove_commitment_with_scheme(ctx.get_enclave_key(), signer, scheme, message)?;
+    if let Some(co_ek) = ctx.get_co_enclave_key() {
+        proof.add_co_signature(co_ek)?;
+    }
+    Ok(proof)
+}
+
+/// Returns an error if an expiry is requested for a client whose verifier accepts the message schema version 1
+///
+/// The verifier would accept the message after the expiry, so the request is rejected before the light client runs.
+pub(crate) fn ensure_expiry_supported(
+    client_id: &ClientId,
+    schema_version: u16,
+    expires_at: Option<Time>,
+) -> Result<(), Error> {
+    if expires_at.is_some() && schema_version == MESSAGE_SCHEMA_VERSION_1 {
+        return Err(Error::invalid_argument(format!(
+            "expires_at is not supported by the message schema version of the client: client_id={} version={}",
+            client_id, schema_version
+        )));
+    }
+    Ok(())
+}
+
+/// Proves the message of the client like `prove`
+///
+/// Signed `UpdateState` messages are cached with the client and the scheme, so that aggregating them later
+/// for the same client skips the signature verification.
+pub(crate) fn prove_client_message<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    client_id: &ClientId,
+    signer: Address,
+    scheme: SignatureScheme,
+    schema_version: u16,
+    message: ProxyMessage,
+) -> Result<CommitmentProof, Error> {
+    let is_update_state = matches!(message, ProxyMessage::UpdateState(_));
+    let proof = prove(ctx, signer, scheme, schema_version, message)?;
+    if is_update_state {
+        insert_signed_message(
+            client_id,
+            scheme,
+            proof.signer,
+            &proof.message,
+            &proof.signature,
+        );
+    }
+    Ok(proof)
+}
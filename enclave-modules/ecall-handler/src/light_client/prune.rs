@@ -0,0 +1,34 @@
+use super::expiry::{client_expiry, mark_if_expired};
+use super::registry::get_light_client_by_client_id;
+use crate::light_client::Error;
+use context::Context;
+use crypto::Signer;
+use ecall_commands::{LightClientResponse, PruneClientInput, PruneClientResponse};
+use light_client::{HostClientKeeper, LightClientResolver};
+use store::KVStore;
+
+pub fn prune_client<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: PruneClientInput,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    // a light client without a trusting period may use any of its consensus states
+    let trusting_period = match lc.trusting_period(ctx, &input.client_id)? {
+        Some(trusting_period) => trusting_period,
+        None => return Ok(LightClientResponse::PruneClient(Default::default())),
+    };
+    let latest_height = lc.latest_height(ctx, &input.client_id)?;
+    let client_expiry = client_expiry(ctx, lc.as_ref(), &input.client_id)?;
+    let expiry =
+        (input.current_timestamp - (trusting_period + input.margin)).map_err(Error::time)?;
+    let pruned = ctx.prune_consensus_states(&input.client_id, latest_height, expiry)?;
+    let expired = mark_if_expired(ctx, &input.client_id, client_expiry)?;
+
+    Ok(LightClientResponse::PruneClient(PruneClientResponse {
+        pruned_heights: pruned.heights,
+        reclaimed_bytes: pruned.reclaimed_bytes,
+        expired,
+    }))
+}
@@ -1,9 +1,18 @@
 use super::registry::get_light_client_by_client_id;
 use crate::light_client::Error;
+use crate::prelude::*;
 use context::Context;
 use crypto::Signer;
-use ecall_commands::{LightClientResponse, QueryClientInput, QueryClientResponse};
-use light_client::{ClientReader, LightClientResolver};
+use ecall_commands::{
+    ClientValidation, LightClientResponse, ListClientsInput, ListClientsResponse, PageRequest,
+    PageResponse, QueryCapabilitiesInput, QueryCapabilitiesResponse, QueryClientInput,
+    QueryClientResponse, QueryConsensusHeightsInput, QueryConsensusHeightsResponse,
+    StateIncompatibility, ValidateStoreInput, ValidateStoreResponse, MAX_QUERY_RESPONSE_SIZE,
+    MAX_REPORTED_INCOMPATIBILITIES,
+};
+use lcp_types::{ClientId, Height};
+use light_client::{ClientReader, LightClientResolver, RegistryError};
+use serde::Serialize;
 use store::KVStore;
 
 pub fn query_client<R: LightClientResolver, S: KVStore, K: Signer>(
@@ -20,3 +29,180 @@ pub fn query_client<R: LightClientResolver, S: KVStore, K: Signer>(
         any_consensus_state,
     }))
 }
+
+pub fn list_clients<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: ListClientsInput,
+) -> Result<LightClientResponse, Error> {
+    let (client_ids, pagination) = paginate(ctx.client_ids()?, &input.pagination)?;
+    Ok(LightClientResponse::ListClients(ListClientsResponse {
+        client_ids,
+        pagination,
+    }))
+}
+
+pub fn query_consensus_heights<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: QueryConsensusHeightsInput,
+) -> Result<LightClientResponse, Error> {
+    // ensure that the client exists
+    ctx.client_type(&input.client_id)?;
+    let heights = ctx
+        .consensus_state_index(&input.client_id)?
+        .into_iter()
+        .map(|(height, _)| height)
+        .collect();
+    let (heights, pagination) = paginate(heights, &input.pagination)?;
+    Ok(LightClientResponse::QueryConsensusHeights(
+        QueryConsensusHeightsResponse {
+            heights,
+            pagination,
+        },
+    ))
+}
+
+pub fn query_capabilities<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: QueryCapabilitiesInput,
+) -> Result<LightClientResponse, Error> {
+    let (version, lc) = match input {
+        QueryCapabilitiesInput::ClientId(client_id) => (
+            ctx.light_client_version(&client_id)?,
+            get_light_client_by_client_id(ctx, &client_id)?,
+        ),
+        QueryCapabilitiesInput::ClientStateTypeUrl(type_url) => ctx
+            .get_versioned_light_client(&type_url, None)
+            .ok_or_else(|| {
+                Error::light_client_registry(RegistryError::type_url_not_found(type_url))
+            })?,
+    };
+    Ok(LightClientResponse::QueryCapabilities(
+        QueryCapabilitiesResponse {
+            client_type: lc.client_type(),
+            version,
+            capabilities: lc.capabilities(),
+        },
+    ))
+}
+
+/// Decodes the stored states of a page of the clients with the implementations they are pinned to
+///
+/// The incompatibilities are reported instead of failing the command, so that all clients are checked in a single pass.
+pub fn validate_store<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: ValidateStoreInput,
+) -> Result<LightClientResponse, Error> {
+    let page = input.pagination;
+    let client_ids = ctx.client_ids()?;
+    let total = client_ids.len() as u64;
+    let offset = page.offset.min(total);
+    let clients = client_ids
+        .into_iter()
+        .skip(offset as usize)
+        .take(page.limit as usize)
+        .map(|client_id| validate_client(ctx, client_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    // the page may be cut short by the size of the reports
+    let (clients, _) = paginate(clients, &PageRequest { offset: 0, ..page })?;
+    let next_offset = offset + clients.len() as u64;
+    Ok(LightClientResponse::ValidateStore(ValidateStoreResponse {
+        clients,
+        pagination: PageResponse {
+            next_offset: (next_offset < total).then_some(next_offset),
+            total,
+        },
+    }))
+}
+
+fn validate_client<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    client_id: ClientId,
+) -> Result<ClientValidation, Error> {
+    let heights: Vec<Height> = ctx
+        .consensus_state_index(&client_id)?
+        .into_iter()
+        .map(|(height, _)| height)
+        .collect();
+    let mut validation = ClientValidation {
+        client_id,
+        consensus_states: heights.len() as u64,
+        incompatibilities: vec![],
+        incompatible_states: 0,
+    };
+    let lc = match get_light_client_by_client_id(ctx, &validation.client_id) {
+        Ok(lc) => lc,
+        Err(e) => {
+            // no state can be decoded without the implementation
+            report_incompatibility(&mut validation, None, e.to_string());
+            return Ok(validation);
+        }
+    };
+    if let Err(e) = lc.validate_client_state(ctx.client_state(&validation.client_id)?) {
+        report_incompatibility(&mut validation, None, e.to_string());
+    }
+    for height in heights {
+        let any_consensus_state = ctx.consensus_state(&validation.client_id, &height)?;
+        if let Err(e) = lc.validate_consensus_state(any_consensus_state) {
+            report_incompatibility(&mut validation, Some(height), e.to_string());
+        }
+    }
+    Ok(validation)
+}
+
+fn report_incompatibility(
+    validation: &mut ClientValidation,
+    height: Option<Height>,
+    reason: String,
+) {
+    validation.incompatible_states += 1;
+    if validation.incompatibilities.len() < MAX_REPORTED_INCOMPATIBILITIES {
+        validation
+            .incompatibilities
+            .push(StateIncompatibility { height, reason });
+    }
+}
+
+/// Returns the items in the page, which is cut short if the items exceed `MAX_QUERY_RESPONSE_SIZE` when encoded
+fn paginate<T: Serialize>(
+    items: Vec<T>,
+    page: &PageRequest,
+) -> Result<(Vec<T>, PageResponse), Error> {
+    if page.limit == 0 || page.limit > PageRequest::MAX_LIMIT {
+        return Err(Error::invalid_argument(format!(
+            "page limit must be in [1, {}]: limit={}",
+            PageRequest::MAX_LIMIT,
+            page.limit
+        )));
+    }
+    let total = items.len() as u64;
+    let mut size = 0;
+    let mut next_offset = page.offset;
+    let mut selected = Vec::new();
+    for item in items
+        .into_iter()
+        .skip(page.offset.min(total) as usize)
+        .take(page.limit as usize)
+    {
+        size += bincode::serde::encode_to_vec(&item, bincode::config::standard())
+            .map_err(|e| Error::invalid_argument(format!("failed to encode an item: {}", e)))?
+            .len();
+        if size > MAX_QUERY_RESPONSE_SIZE {
+            if selected.is_empty() {
+                return Err(Error::invalid_argument(format!(
+                    "an item exceeds the maximum response size: max={}",
+                    MAX_QUERY_RESPONSE_SIZE
+                )));
+            }
+            break;
+        }
+        selected.push(item);
+        next_offset += 1;
+    }
+    Ok((
+        selected,
+        PageResponse {
+            next_offset: (next_offset < total).then_some(next_offset),
+            total,
+        },
+    ))
+}
@@ -11,8 +11,13 @@ pub fn get_light_client_by_client_id<'a, R: LightClientResolver, S: KVStore, K:
     client_id: &ClientId,
 ) -> Result<&'a Box<dyn LightClient>, Error> {
     let any_client_state = ctx.client_state(client_id)?.to_proto();
-    ctx.get_light_client(any_client_state.type_url.as_ref())
-        .ok_or(Error::light_client_registry(
-            RegistryError::type_url_not_found(any_client_state.type_url),
-        ))
+    let version = ctx.light_client_version(client_id)?;
+    ctx.get_versioned_light_client(any_client_state.type_url.as_ref(), Some(version))
+        .map(|(_, lc)| lc)
+        .ok_or_else(|| {
+            Error::light_client_registry(RegistryError::version_not_found(
+                any_client_state.type_url,
+                version,
+            ))
+        })
 }
@@ -0,0 +1,33 @@
+use super::prover::prove_client_message;
+use super::registry::get_light_client_by_client_id;
+use crate::light_client::Error;
+use crate::prelude::*;
+use context::Context;
+use crypto::Signer;
+use ecall_commands::{LightClientResponse, ResignLatestStateInput, ResignLatestStateResponse};
+use light_client::{ClientReader, LightClientResolver};
+use store::KVStore;
+
+pub fn resign_latest_state<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: ResignLatestStateInput,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    let message = lc.latest_state(ctx, &input.client_id)?;
+    message.validate()?;
+
+    Ok(LightClientResponse::ResignLatestState(
+        ResignLatestStateResponse(prove_client_message(
+            ctx,
+            &input.client_id,
+            input.signer,
+            scheme,
+            schema_version,
+            message.into(),
+        )?),
+    ))
+}
@@ -1,44 +1,132 @@
+#[cfg(feature = "update")]
 use crate::light_client::{
-    aggregate_messages, init_client, query_client, update_client, verify_membership,
-    verify_non_membership, Error,
+    aggregate_messages, init_client, migrate_client, prune_client, resign_latest_state,
+    update_client, update_client_params, verify_aggregate_messages,
 };
-use context::Context;
+use crate::light_client::{
+    list_clients, query_capabilities, query_client, query_consensus_heights, validate_store, Error,
+};
+#[cfg(feature = "verify")]
+use crate::light_client::{
+    sign_latest_heights, verify_key_value, verify_membership, verify_membership_batch,
+    verify_non_membership,
+};
+use crate::prelude::*;
+use context::{Context, SharedStateCache, DEFAULT_STATE_CACHE_CAPACITY};
 use crypto::NopSigner;
 use ecall_commands::{
-    CommandContext, CommandResponse, LightClientCommand, LightClientExecuteCommand,
-    LightClientQueryCommand,
+    CommandContext, CommandResponse, CommandResult, LightClientCommand, LightClientExecuteCommand,
+    LightClientQueryCommand, LightClientResponse,
 };
 use enclave_environment::Env;
 
+/// The decoded states shared by the commands, which are keyed by the sequence of the store they were read at
+static STATE_CACHE: SharedStateCache = SharedStateCache::new(DEFAULT_STATE_CACHE_CAPACITY);
+
+/// `store_sequence` is the sequence of the store that the command reads, which every command that changes the store advances
 pub fn dispatch<E: Env>(
     env: E,
     cctx: CommandContext,
     command: LightClientCommand,
-) -> Result<CommandResponse, Error> {
+    request_digest: Option<[u8; 32]>,
+    store_sequence: u64,
+) -> Result<CommandResult, Error> {
+    // the events of the state changes, which the handlers report only if they are persisted
+    #[cfg_attr(not(feature = "update"), allow(unused_mut))]
+    let mut events = Vec::new();
     let res = match command {
+        // pruning removes only the states that can no longer be used for verification, so it needs no enclave key
+        #[cfg(feature = "update")]
+        LightClientCommand::Execute(LightClientExecuteCommand::PruneClient(input)) => {
+            let mut ctx =
+                Context::new(env.get_lc_registry(), env.new_store(cctx.tx_id), &NopSigner);
+            ctx.set_state_cache(&STATE_CACHE, store_sequence);
+            prune_client(&mut ctx, input)?
+        }
         LightClientCommand::Execute(cmd) => {
             use LightClientExecuteCommand::*;
             let sealed_ek = cctx
                 .sealed_ek
                 .ok_or(Error::sealed_enclave_key_not_found())?;
+            let co_sealed_ek = cctx.co_sealed_ek;
+            let constituent_sealed_eks = cctx.constituent_sealed_eks;
             let mut ctx =
                 Context::new(env.get_lc_registry(), env.new_store(cctx.tx_id), &sealed_ek);
-            match cmd {
-                InitClient(input) => init_client(&mut ctx, input)?,
-                UpdateClient(input) => update_client(&mut ctx, input)?,
+            ctx.set_state_cache(&STATE_CACHE, store_sequence);
+            if let Some(co_sealed_ek) = co_sealed_ek.as_ref() {
+                ctx.set_co_enclave_key(co_sealed_ek);
+            }
+            ctx.set_constituent_enclave_keys(&constituent_sealed_eks);
+            let mut res: LightClientResponse = match cmd {
+                #[cfg(feature = "update")]
+                InitClient(input) => init_client(&mut ctx, input, &mut events)?,
+                #[cfg(feature = "update")]
+                UpdateClient(input) => update_client(&mut ctx, input, &mut events)?,
+                #[cfg(feature = "update")]
                 AggregateMessages(input) => aggregate_messages(&mut ctx, input)?,
+                #[cfg(feature = "update")]
+                UpdateClientParams(input) => update_client_params(&mut ctx, input)?,
+                #[cfg(feature = "update")]
+                ResignLatestState(input) => resign_latest_state(&mut ctx, input)?,
+                #[cfg(feature = "update")]
+                MigrateClient(input) => migrate_client(&mut ctx, input, &mut events)?,
+                #[cfg(feature = "update")]
+                PruneClient(_) => unreachable!(),
+                #[cfg(not(feature = "update"))]
+                InitClient(_)
+                | UpdateClient(_)
+                | AggregateMessages(_)
+                | UpdateClientParams(_)
+                | ResignLatestState(_)
+                | MigrateClient(_)
+                | PruneClient(_) => return Err(Error::command_disabled("update".into())),
+                #[cfg(feature = "verify")]
                 VerifyMembership(input) => verify_membership(&mut ctx, input)?,
+                #[cfg(feature = "verify")]
                 VerifyNonMembership(input) => verify_non_membership(&mut ctx, input)?,
+                #[cfg(feature = "verify")]
+                VerifyMembershipBatch(input) => verify_membership_batch(&mut ctx, input)?,
+                #[cfg(feature = "verify")]
+                VerifyKeyValue(input) => verify_key_value(&mut ctx, input)?,
+                #[cfg(feature = "verify")]
+                SignLatestHeights(input) => sign_latest_heights(&mut ctx, input)?,
+                #[cfg(not(feature = "verify"))]
+                VerifyMembership(_)
+                | VerifyNonMembership(_)
+                | VerifyMembershipBatch(_)
+                | VerifyKeyValue(_)
+                | SignLatestHeights(_) => return Err(Error::command_disabled("verify".into())),
+            };
+            // bind the proofs to the command so that the host can detect a swapped result
+            if let Some(request_digest) = request_digest {
+                for proof in res.proofs_mut() {
+                    if proof.is_proven() {
+                        proof.bind_request(ctx.get_enclave_key(), request_digest)?;
+                    }
+                }
             }
+            res
         }
         LightClientCommand::Query(cmd) => {
             use LightClientQueryCommand::*;
             let mut ctx =
                 Context::new(env.get_lc_registry(), env.new_store(cctx.tx_id), &NopSigner);
+            ctx.set_state_cache(&STATE_CACHE, store_sequence);
             match cmd {
                 QueryClient(input) => query_client(&mut ctx, input)?,
+                ListClients(input) => list_clients(&mut ctx, input)?,
+                QueryConsensusHeights(input) => query_consensus_heights(&mut ctx, input)?,
+                QueryCapabilities(input) => query_capabilities(&mut ctx, input)?,
+                ValidateStore(input) => validate_store(&mut ctx, input)?,
+                #[cfg(feature = "update")]
+                VerifyAggregateMessages(input) => verify_aggregate_messages(&mut ctx, input)?,
+                #[cfg(not(feature = "update"))]
+                VerifyAggregateMessages(_) => return Err(Error::command_disabled("update".into())),
             }
         }
     };
-    Ok(CommandResponse::LightClient(res))
+    Ok(CommandResult::new(
+        CommandResponse::LightClient(res),
+        events,
+    ))
 }
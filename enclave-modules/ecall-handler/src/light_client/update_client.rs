@@ -1,51 +1,141 @@
+use super::prover::{ensure_expiry_supported, prove_client_message};
 use super::registry::get_light_client_by_client_id;
 use crate::light_client::Error;
 use crate::prelude::*;
 use context::Context;
 use crypto::Signer;
-use ecall_commands::{LightClientResponse, UpdateClientInput, UpdateClientResponse};
-use light_client::commitments::{prove_commitment, CommitmentProof, EmittedState, ProxyMessage};
-use light_client::{ClientKeeper, LightClientResolver, UpdateClientResult};
+use ecall_commands::{CommandEvent, LightClientResponse, UpdateClientInput, UpdateClientResponse};
+use light_client::commitments::{
+    CommitmentProof, EmittedState, MisbehaviourProxyMessage, PrevState, ProxyMessage,
+};
+use light_client::{
+    ClientKeeper, ClientReader, HostClientKeeper, LightClientResolver, UpdateClientResult,
+};
 use store::KVStore;
 
 pub fn update_client<R: LightClientResolver, S: KVStore, K: Signer>(
     ctx: &mut Context<R, S, K>,
     input: UpdateClientInput,
+    events: &mut Vec<CommandEvent>,
 ) -> Result<LightClientResponse, Error> {
     ctx.set_timestamp(input.current_timestamp);
+    if let Some(expires_at) = input.expires_at {
+        if expires_at <= input.current_timestamp {
+            return Err(Error::invalid_argument(format!(
+                "expires_at must be after the current timestamp: expires_at={} current_timestamp={}",
+                expires_at, input.current_timestamp
+            )));
+        }
+    }
 
     let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
-    let ek = ctx.get_enclave_key();
-    match lc.update_client(ctx, input.client_id.clone(), input.any_header.into())? {
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    ensure_expiry_supported(&input.client_id, schema_version, input.expires_at)?;
+    // a dry run verifies the header as usual, but neither stores the new states nor signs the message
+    let (dry_run, signer, client_id) = (input.dry_run, input.signer, input.client_id.clone());
+    let sign = move |ctx: &Context<R, S, K>, message: ProxyMessage| -> Result<_, Error> {
+        if dry_run {
+            Ok(CommitmentProof::new_with_no_signature(
+                message.into_schema_version(schema_version)?.to_bytes(),
+            ))
+        } else {
+            prove_client_message(ctx, &client_id, signer, scheme, schema_version, message)
+        }
+    };
+    match lc.update_client(
+        ctx,
+        input.client_id.clone(),
+        input.any_header.clone().into(),
+    )? {
+        // the light client freezes itself if the verified header conflicts with a stored consensus state
+        UpdateClientResult::UpdateState(data)
+            if input.detect_misbehaviour && data.message.frozen_height.is_some() =>
+        {
+            if !dry_run {
+                ctx.store_any_client_state(input.client_id.clone(), data.new_any_client_state)?;
+                events.push(CommandEvent::ClientFrozen {
+                    client_id: input.client_id,
+                    frozen_height: data.message.frozen_height,
+                });
+            }
+
+            let prev_states = match (data.message.prev_height, data.message.prev_state_id) {
+                (Some(height), Some(state_id)) => vec![PrevState { height, state_id }],
+                _ => vec![],
+            };
+            let message = MisbehaviourProxyMessage {
+                prev_states,
+                context: data.message.context,
+                client_message: input.any_header,
+            };
+            let proof = sign(ctx, message.into())?;
+            Ok(LightClientResponse::UpdateClient(UpdateClientResponse(
+                proof,
+            )))
+        }
         UpdateClientResult::UpdateState(mut data) => {
-            let message: ProxyMessage = {
-                if input.include_state && data.message.emitted_states.is_empty() {
-                    data.message.emitted_states =
-                        vec![EmittedState(data.height, data.new_any_client_state.clone())];
+            if input.include_state && data.message.emitted_states.is_empty() {
+                data.message.emitted_states =
+                    vec![EmittedState(data.height, data.new_any_client_state.clone())];
+            }
+            data.message
+                .emitted_states
+                .retain(|EmittedState(_, any)| input.is_state_included(any));
+            data.message.expires_at = input.expires_at;
+            if !dry_run {
+                events.push(CommandEvent::ClientUpdated {
+                    client_id: input.client_id.clone(),
+                    prev_height: data.message.prev_height,
+                    post_height: data.height,
+                });
+                events.extend(data.message.emitted_states.iter().map(
+                    |EmittedState(height, state)| CommandEvent::StateEmitted {
+                        client_id: input.client_id.clone(),
+                        height: *height,
+                        state: state.clone(),
+                    },
+                ));
+                if let Some(frozen_height) = data.message.frozen_height {
+                    events.push(CommandEvent::ClientFrozen {
+                        client_id: input.client_id.clone(),
+                        frozen_height: Some(frozen_height),
+                    });
                 }
-                data.message.into()
-            };
+            }
+            let message: ProxyMessage = data.message.into();
 
-            ctx.store_any_client_state(input.client_id.clone(), data.new_any_client_state)?;
-            ctx.store_any_consensus_state(
-                input.client_id,
-                data.height,
-                data.new_any_consensus_state,
-            )?;
+            if !dry_run {
+                ctx.store_any_client_state(input.client_id.clone(), data.new_any_client_state)?;
+                ctx.store_any_consensus_state(
+                    input.client_id.clone(),
+                    data.height,
+                    data.new_any_consensus_state,
+                )?;
+                ctx.index_consensus_state(&input.client_id, data.height)?;
+            }
 
             let proof = if data.prove {
-                prove_commitment(ek, input.signer, message)?
+                sign(ctx, message)?
             } else {
-                CommitmentProof::new_with_no_signature(message.to_bytes())
+                CommitmentProof::new_with_no_signature(
+                    message.into_schema_version(schema_version)?.to_bytes(),
+                )
             };
             Ok(LightClientResponse::UpdateClient(UpdateClientResponse(
                 proof,
             )))
         }
         UpdateClientResult::Misbehaviour(data) => {
-            ctx.store_any_client_state(input.client_id, data.new_any_client_state)?;
+            if !dry_run {
+                ctx.store_any_client_state(input.client_id.clone(), data.new_any_client_state)?;
+                events.push(CommandEvent::ClientFrozen {
+                    client_id: input.client_id,
+                    frozen_height: None,
+                });
+            }
 
-            let proof = prove_commitment(ek, input.signer, data.message.into())?;
+            let proof = sign(ctx, data.message.into())?;
             Ok(LightClientResponse::UpdateClient(UpdateClientResponse(
                 proof,
             )))
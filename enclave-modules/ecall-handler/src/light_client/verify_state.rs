@@ -1,34 +1,52 @@
+use super::expiry::ensure_not_expired;
+use super::prover::{ensure_expiry_supported, prove};
 use super::registry::get_light_client_by_client_id;
 use crate::light_client::Error;
+use crate::prelude::*;
 use context::Context;
 use crypto::Signer;
 use ecall_commands::{
-    LightClientResponse, VerifyMembershipInput, VerifyMembershipResponse, VerifyNonMembershipInput,
-    VerifyNonMembershipResponse,
+    CommitmentPath, LightClientResponse, VerifyKeyValueInput, VerifyKeyValueResponse,
+    VerifyMembershipBatchInput, VerifyMembershipBatchResponse, VerifyMembershipInput,
+    VerifyMembershipResponse, VerifyNonMembershipInput, VerifyNonMembershipResponse,
 };
-use light_client::commitments::prove_commitment;
-use light_client::LightClientResolver;
+use lcp_types::{ClientId, Height};
+use light_client::{ClientReader, LightClientResolver, MembershipItem};
 use store::KVStore;
 
 pub fn verify_membership<R: LightClientResolver, S: KVStore, K: Signer>(
     ctx: &mut Context<R, S, K>,
     input: VerifyMembershipInput,
 ) -> Result<LightClientResponse, Error> {
-    let ek = ctx.get_enclave_key();
+    ctx.set_timestamp(input.current_timestamp);
     let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    ensure_expiry_supported(&input.client_id, schema_version, input.expires_at)?;
+    ensure_not_expired(ctx, lc.as_ref(), &input.client_id)?;
+    ctx.validate_commitment_prefix(&input.client_id, input.prefix.as_bytes())?;
+    let proof_height =
+        select_proof_height(ctx, &input.client_id, input.proof.0, input.consensus_height)?;
 
-    let res = lc.verify_membership(
+    let mut res = lc.verify_membership(
         ctx,
         input.client_id,
-        input.prefix,
-        input.path,
+        input.prefix.into_vec(),
+        path_string(&input.path)?,
         input.value,
-        input.proof.0,
+        proof_height,
         input.proof.1,
     )?;
+    res.message.expires_at = input.expires_at;
 
     Ok(LightClientResponse::VerifyMembership(
-        VerifyMembershipResponse(prove_commitment(ek, input.signer, res.message.into())?),
+        VerifyMembershipResponse(prove(
+            ctx,
+            input.signer,
+            scheme,
+            schema_version,
+            res.message.into(),
+        )?),
     ))
 }
 
@@ -36,19 +54,149 @@ pub fn verify_non_membership<R: LightClientResolver, S: KVStore, K: Signer>(
     ctx: &mut Context<R, S, K>,
     input: VerifyNonMembershipInput,
 ) -> Result<LightClientResponse, Error> {
-    let ek = ctx.get_enclave_key();
+    ctx.set_timestamp(input.current_timestamp);
     let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    ensure_expiry_supported(&input.client_id, schema_version, input.expires_at)?;
+    ensure_not_expired(ctx, lc.as_ref(), &input.client_id)?;
+    ctx.validate_commitment_prefix(&input.client_id, input.prefix.as_bytes())?;
 
-    let res = lc.verify_non_membership(
+    let mut res = lc.verify_non_membership(
         ctx,
         input.client_id,
-        input.prefix,
-        input.path,
+        input.prefix.into_vec(),
+        path_string(&input.path)?,
         input.proof.0,
         input.proof.1,
     )?;
+    res.message.expires_at = input.expires_at;
 
     Ok(LightClientResponse::VerifyNonMembership(
-        VerifyNonMembershipResponse(prove_commitment(ek, input.signer, res.message.into())?),
+        VerifyNonMembershipResponse(prove(
+            ctx,
+            input.signer,
+            scheme,
+            schema_version,
+            res.message.into(),
+        )?),
     ))
 }
+
+pub fn verify_membership_batch<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: VerifyMembershipBatchInput,
+) -> Result<LightClientResponse, Error> {
+    if input.items.is_empty() || input.items.len() > VerifyMembershipBatchInput::MAX_ITEMS {
+        return Err(Error::invalid_argument(format!(
+            "the number of items must be in [1, {}]: items={}",
+            VerifyMembershipBatchInput::MAX_ITEMS,
+            input.items.len()
+        )));
+    }
+    ctx.set_timestamp(input.current_timestamp);
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    ensure_expiry_supported(&input.client_id, schema_version, input.expires_at)?;
+    ensure_not_expired(ctx, lc.as_ref(), &input.client_id)?;
+    ctx.validate_commitment_prefix(&input.client_id, input.prefix.as_bytes())?;
+
+    let items = input
+        .items
+        .into_iter()
+        .map(|item| {
+            Ok(MembershipItem {
+                path: path_string(&item.path)?,
+                value: item.value,
+                proof: item.proof,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    let results = lc.verify_membership_batch(
+        ctx,
+        input.client_id,
+        input.prefix.into_vec(),
+        items,
+        input.proof_height,
+    )?;
+    let proofs = results
+        .into_iter()
+        .map(|mut res| {
+            res.message.expires_at = input.expires_at;
+            prove(
+                ctx,
+                input.signer,
+                scheme,
+                schema_version,
+                res.message.into(),
+            )
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(LightClientResponse::VerifyMembershipBatch(
+        VerifyMembershipBatchResponse(proofs),
+    ))
+}
+
+pub fn verify_key_value<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: VerifyKeyValueInput,
+) -> Result<LightClientResponse, Error> {
+    ctx.set_timestamp(input.current_timestamp);
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+    let scheme = ctx.signature_scheme(&input.client_id)?;
+    let schema_version = ctx.message_schema_version(&input.client_id)?;
+    ensure_not_expired(ctx, lc.as_ref(), &input.client_id)?;
+    ctx.validate_commitment_prefix(&input.client_id, input.prefix.as_bytes())?;
+
+    let mut res = lc.verify_key_value(
+        ctx,
+        input.client_id,
+        input.prefix.into_vec(),
+        input.key,
+        input.value,
+        input.proof.0,
+        input.proof.1,
+    )?;
+    res.message.expires_at = input.expires_at;
+
+    Ok(LightClientResponse::VerifyKeyValue(VerifyKeyValueResponse(
+        prove(
+            ctx,
+            input.signer,
+            scheme,
+            schema_version,
+            res.message.into(),
+        )?,
+    )))
+}
+
+/// Returns the height of the consensus state to verify the proof against
+///
+/// An explicit `consensus_height` must be stored and not pruned, and the proof height must be zero or equal to it.
+fn select_proof_height<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &Context<R, S, K>,
+    client_id: &ClientId,
+    proof_height: Height,
+    consensus_height: Option<Height>,
+) -> Result<Height, Error> {
+    let consensus_height = match consensus_height {
+        Some(consensus_height) => consensus_height,
+        None => return Ok(proof_height),
+    };
+    if !proof_height.is_zero() && proof_height != consensus_height {
+        return Err(Error::invalid_argument(format!(
+            "the proof height must be zero or equal to the consensus height: proof_height={} consensus_height={}",
+            proof_height, consensus_height
+        )));
+    }
+    ctx.ensure_consensus_state(client_id, &consensus_height)?;
+    Ok(consensus_height)
+}
+
+/// Validate the path again in the enclave, so that a malformed path is rejected before the light client parses it
+fn path_string(path: &CommitmentPath) -> Result<String, Error> {
+    path.to_path_string()
+        .map_err(|e| Error::invalid_argument(e.to_string()))
+}
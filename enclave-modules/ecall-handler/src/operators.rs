@@ -0,0 +1,129 @@
+use crate::instance::check_bound_record;
+use crate::prelude::*;
+use crate::{Error, Result};
+use crypto::sgx::sealing::{seal_bytes, unseal_bytes};
+use crypto::Address;
+use ecall_commands::{
+    Command, CommandContext, OperatorSet, QueryOperatorsInput, QueryOperatorsResponse,
+    UpdateOperatorsInput, UpdateOperatorsResponse, OPERATOR_SET_KEY,
+};
+use store::KVStore;
+
+/// The address of the operator that approves the initial operator set, or empty if the enclave has none
+///
+/// It is given with `LCP_BOOTSTRAP_OPERATOR` when the enclave is built, so it is a part of the measurement.
+const BOOTSTRAP_OPERATOR: &str = env!("LCP_BOOTSTRAP_OPERATOR");
+
+/// Replace the operator set with the one approved by the current operators
+///
+/// The initial operator set must be approved by the bootstrap operator of the enclave.
+/// The sealed set is bound to the store sequence that the command advances in the same transaction.
+pub(crate) fn update_operators(
+    store: &mut dyn KVStore,
+    input: UpdateOperatorsInput,
+) -> Result<UpdateOperatorsResponse> {
+    let new_set = input.operator_set;
+    new_set
+        .validate()
+        .map_err(|e| Error::invalid_operator_set(e.to_string()))?;
+    let (current, expected_nonce) = match load_operator_set(store)? {
+        Some(current) => {
+            let nonce = current.nonce + 1;
+            (current, nonce)
+        }
+        None => (bootstrap_operator_set()?, 0),
+    };
+    verify_approvals(&current, &new_set.sign_bytes(), &input.signatures)?;
+    if new_set.nonce != expected_nonce {
+        return Err(Error::invalid_operator_set_nonce(
+            expected_nonce,
+            new_set.nonce,
+        ));
+    }
+    let bz = bincode::serde::encode_to_vec(&new_set, bincode::config::standard())
+        .map_err(|e| Error::invalid_operator_set(e.to_string()))?;
+    store.set(OPERATOR_SET_KEY.to_vec(), seal_bytes(&bz)?);
+    Ok(UpdateOperatorsResponse)
+}
+
+pub(crate) fn query_operators(
+    store: &dyn KVStore,
+    _: QueryOperatorsInput,
+) -> Result<QueryOperatorsResponse> {
+    Ok(QueryOperatorsResponse {
+        operator_set: load_operator_set(store)?,
+    })
+}
+
+/// Returns an error unless the command is approved by the threshold of the operators
+///
+/// Commands are not restricted until the initial operator set is configured.
+pub(crate) fn authorize_command(
+    store: &dyn KVStore,
+    cmd: &Command,
+    ctx: &CommandContext,
+) -> Result<()> {
+    if !cmd.requires_operator_approval() {
+        return Ok(());
+    }
+    match load_operator_set(store)? {
+        Some(operator_set) => {
+            let sign_bytes = if ctx.legacy_encoding {
+                cmd.legacy_operator_sign_bytes()
+            } else {
+                cmd.operator_sign_bytes()
+            }
+            .map_err(|e| Error::invalid_operator_set(e.to_string()))?;
+            verify_approvals(&operator_set, &sign_bytes, &ctx.operator_signatures)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Returns the operator set configured in the store, or the set of the bootstrap operator until then
+pub(crate) fn effective_operator_set(store: &dyn KVStore) -> Result<OperatorSet> {
+    match load_operator_set(store)? {
+        Some(operator_set) => Ok(operator_set),
+        None => bootstrap_operator_set(),
+    }
+}
+
+fn bootstrap_operator_set() -> Result<OperatorSet> {
+    if BOOTSTRAP_OPERATOR.is_empty() {
+        return Err(Error::bootstrap_operator_not_configured());
+    }
+    let operator = Address::from_hex_string(BOOTSTRAP_OPERATOR)?;
+    Ok(OperatorSet::bootstrap(operator))
+}
+
+fn verify_approvals(
+    operator_set: &OperatorSet,
+    sign_bytes: &[u8],
+    signatures: &[Vec<u8>],
+) -> Result<()> {
+    let approvals = operator_set
+        .count_approvals(sign_bytes, signatures)
+        .map_err(|e| Error::invalid_operator_set(e.to_string()))?;
+    if approvals < operator_set.threshold {
+        return Err(Error::insufficient_operator_approvals(
+            approvals,
+            operator_set.threshold,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the operator set configured in the store, or an error unless it is the one bound to the store sequence
+pub(crate) fn load_operator_set(store: &dyn KVStore) -> Result<Option<OperatorSet>> {
+    let sealed = store.get(OPERATOR_SET_KEY);
+    check_bound_record(store, OPERATOR_SET_KEY, sealed.as_deref())?;
+    let sealed = match sealed {
+        Some(sealed) => sealed,
+        None => return Ok(None),
+    };
+    let bz = unseal_bytes(&sealed)?;
+    let (operator_set, _): (OperatorSet, _) =
+        bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+            .map_err(|e| Error::invalid_operator_set(e.to_string()))?;
+    Ok(Some(operator_set))
+}
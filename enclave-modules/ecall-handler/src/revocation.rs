@@ -0,0 +1,55 @@
+use crate::instance::check_bound_record;
+use crate::prelude::*;
+use crate::{Error, Result};
+use crypto::sgx::sealing::{seal_bytes, unseal_bytes};
+use crypto::Address;
+use ecall_commands::{
+    RevocationRecord, RevokeEnclaveKeysInput, RevokeEnclaveKeysResponse, REVOCATION_RECORD_KEY,
+};
+use store::KVStore;
+
+/// Add the keys to the revocation record
+///
+/// Revocations can only be added, so they require no approvals. The sealed record is bound to the store sequence
+/// that the command advances in the same transaction.
+pub(crate) fn revoke_enclave_keys(
+    store: &mut dyn KVStore,
+    input: RevokeEnclaveKeysInput,
+) -> Result<RevokeEnclaveKeysResponse> {
+    let mut record = load_record(store)?.unwrap_or_default();
+    let revoked = record.revoke(&input.addresses);
+    if revoked.is_empty() {
+        return Ok(RevokeEnclaveKeysResponse { revoked });
+    }
+    let bz = bincode::serde::encode_to_vec(&record, bincode::config::standard())
+        .map_err(|e| Error::invalid_revocation_record(e.to_string()))?;
+    store.set(REVOCATION_RECORD_KEY.to_vec(), seal_bytes(&bz)?);
+    Ok(RevokeEnclaveKeysResponse { revoked })
+}
+
+/// Returns an error if any of the keys has been revoked
+pub(crate) fn check_not_revoked(store: &dyn KVStore, addresses: &[Address]) -> Result<()> {
+    let record = match load_record(store)? {
+        Some(record) => record,
+        None => return Ok(()),
+    };
+    match addresses.iter().find(|a| record.is_revoked(a)) {
+        Some(address) => Err(Error::enclave_key_revoked(address.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Returns the sealed record, or an error unless it is the one bound to the store sequence
+fn load_record(store: &dyn KVStore) -> Result<Option<RevocationRecord>> {
+    let sealed = store.get(REVOCATION_RECORD_KEY);
+    check_bound_record(store, REVOCATION_RECORD_KEY, sealed.as_deref())?;
+    let sealed = match sealed {
+        Some(sealed) => sealed,
+        None => return Ok(None),
+    };
+    let bz = unseal_bytes(&sealed)?;
+    let (record, _): (RevocationRecord, _) =
+        bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+            .map_err(|e| Error::invalid_revocation_record(e.to_string()))?;
+    Ok(Some(record))
+}
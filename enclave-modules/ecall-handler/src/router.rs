@@ -1,16 +1,118 @@
+use crate::constituents;
 use crate::enclave_manage;
+use crate::instance;
 use crate::light_client;
+use crate::operators;
+use crate::prelude::*;
+use crate::revocation;
+use crate::session;
 use crate::{Error, Result};
-use ecall_commands::{Command, CommandResponse, ECallCommand};
+use ecall_commands::{
+    Command, CommandResponse, CommandResult, ECallCommand, EnclaveKeySelector,
+    EnclaveManageCommand, EnclaveManageResponse, LightClientCommand,
+};
 use enclave_environment::Env;
 
-pub fn dispatch<E: Env>(env: E, command: ECallCommand) -> Result<CommandResponse> {
-    match command.cmd {
+pub fn dispatch<E: Env>(env: E, command: ECallCommand) -> Result<CommandResult> {
+    if command.cmd.requires_session() {
+        session::check_session(command.ctx.session_token.as_ref())?;
+    }
+    let changes_store = command.cmd.changes_store();
+    let mut request_digest = None;
+    if let Command::LightClient(LightClientCommand::Execute(_)) = &command.cmd {
+        let digest = if command.ctx.legacy_encoding {
+            command.cmd.legacy_request_digest()
+        } else {
+            command.cmd.request_digest()
+        };
+        request_digest = Some(digest.map_err(|e| Error::invalid_command(e.to_string()))?);
+        let store = env.new_store(command.ctx.tx_id);
+        instance::check_instance(store.as_ref())?;
+        operators::authorize_command(store.as_ref(), &command.cmd, &command.ctx)?;
+        // a revoked key must neither sign nor be aggregated
+        let signer: Vec<_> = command.cmd.get_enclave_key().into_iter().collect();
+        revocation::check_not_revoked(store.as_ref(), &signer)?;
+        constituents::verify_constituent_keys(store.as_ref(), &command.ctx)?;
+    }
+    let res = match command.cmd {
+        Command::EnclaveManage(EnclaveManageCommand::StartInstance(input)) => {
+            let mut store = env.new_store(command.ctx.tx_id);
+            let res = instance::start_instance(store.as_mut(), input)?;
+            // the operator set is verified against the checkpoint only after the instance has started
+            session::verify_session_operator(store.as_ref())?;
+            Ok(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::StartInstance(res),
+            ))
+        }
+        Command::EnclaveManage(EnclaveManageCommand::UpdateOperators(input)) => {
+            let mut store = env.new_store(command.ctx.tx_id);
+            let res = CommandResult::from(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::UpdateOperators(operators::update_operators(
+                    store.as_mut(),
+                    input,
+                )?),
+            ));
+            // the operator set is bound to the sequence in the same transaction
+            let checkpoint = instance::advance_store_sequence(store.as_mut(), None)?;
+            return Ok(res.with_store_checkpoint(checkpoint));
+        }
+        Command::EnclaveManage(EnclaveManageCommand::QueryOperators(input)) => {
+            let store = env.new_store(command.ctx.tx_id);
+            Ok(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::QueryOperators(operators::query_operators(
+                    store.as_ref(),
+                    input,
+                )?),
+            ))
+        }
+        Command::EnclaveManage(EnclaveManageCommand::BeginSession(input)) => {
+            Ok(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::BeginSession(session::begin_session(input)?),
+            ))
+        }
+        Command::EnclaveManage(EnclaveManageCommand::OpenSession(input)) => {
+            let store = env.new_store(command.ctx.tx_id);
+            Ok(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::OpenSession(session::open_session(store.as_ref(), input)?),
+            ))
+        }
+        Command::EnclaveManage(EnclaveManageCommand::RevokeEnclaveKeys(input)) => {
+            let mut store = env.new_store(command.ctx.tx_id);
+            let res = CommandResult::from(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::RevokeEnclaveKeys(revocation::revoke_enclave_keys(
+                    store.as_mut(),
+                    input,
+                )?),
+            ));
+            // the revocation record is bound to the sequence in the same transaction
+            let checkpoint = instance::advance_store_sequence(store.as_mut(), None)?;
+            return Ok(res.with_store_checkpoint(checkpoint));
+        }
+        Command::EnclaveManage(EnclaveManageCommand::FinalizeStoreSequence(input)) => {
+            let store = env.new_store(command.ctx.tx_id);
+            Ok(CommandResponse::EnclaveManage(
+                EnclaveManageResponse::FinalizeStoreSequence(instance::finalize_store_sequence(
+                    store.as_ref(),
+                    input,
+                )?),
+            ))
+        }
         Command::EnclaveManage(cmd) => {
             enclave_manage::dispatch(command.ctx, cmd).map_err(Error::enclave_manage_command)
         }
         Command::LightClient(cmd) => {
-            light_client::dispatch(env, command.ctx, cmd).map_err(Error::light_client_command)
+            let mut store = env.new_store(command.ctx.tx_id);
+            let sealed_ek = command.ctx.sealed_ek.clone();
+            let store_sequence = instance::store_sequence(store.as_ref())?;
+            let res = light_client::dispatch(env, command.ctx, cmd, request_digest, store_sequence)
+                .map_err(Error::light_client_command)?;
+            if !changes_store {
+                return Ok(res);
+            }
+            // the prepared commit is written in the transaction of the command, so they are committed or discarded together
+            let checkpoint = instance::advance_store_sequence(store.as_mut(), sealed_ek.as_ref())?;
+            return Ok(res.with_store_checkpoint(checkpoint));
         }
-    }
+    };
+    res.map(CommandResult::from)
 }
@@ -0,0 +1,85 @@
+use crate::operators::effective_operator_set;
+use crate::{Error, Result};
+use crypto::sgx::rand::rand_slice;
+use crypto::{verify_signature_address, Address};
+use ecall_commands::{
+    session_sign_bytes, BeginSessionInput, BeginSessionResponse, OpenSessionInput,
+    OpenSessionResponse, SESSION_TOKEN_SIZE,
+};
+use spin::Mutex;
+use store::KVStore;
+
+/// The challenge issued by the last `BeginSession`, which is consumed by the next `OpenSession`
+static CHALLENGE: Mutex<Option<[u8; SESSION_TOKEN_SIZE]>> = Mutex::new(None);
+
+/// The token of the open session with the address that opened it. Opening a new session invalidates the previous one.
+static SESSION: Mutex<Option<([u8; SESSION_TOKEN_SIZE], Address)>> = Mutex::new(None);
+
+pub(crate) fn begin_session(_: BeginSessionInput) -> Result<BeginSessionResponse> {
+    let mut challenge = [0u8; SESSION_TOKEN_SIZE];
+    rand_slice(&mut challenge)?;
+    *CHALLENGE.lock() = Some(challenge);
+    Ok(BeginSessionResponse { challenge })
+}
+
+/// Open a session if the challenge is signed by one of the operators
+///
+/// Until the operator set is configured, the challenge must be signed by the bootstrap operator of the enclave.
+/// The challenge can be used only once, whether the session is opened or not.
+pub(crate) fn open_session(
+    store: &dyn KVStore,
+    input: OpenSessionInput,
+) -> Result<OpenSessionResponse> {
+    let challenge = CHALLENGE
+        .lock()
+        .take()
+        .ok_or_else(Error::session_not_begun)?;
+    if input.signature.is_empty() {
+        return Err(Error::operator_credential_required());
+    }
+    let operator = verify_signature_address(&session_sign_bytes(&challenge), &input.signature)?;
+    if !effective_operator_set(store)?.is_operator(&operator) {
+        return Err(Error::not_an_operator(operator.to_hex_string()));
+    }
+    let mut session_token = [0u8; SESSION_TOKEN_SIZE];
+    rand_slice(&mut session_token)?;
+    *SESSION.lock() = Some((session_token, operator));
+    Ok(OpenSessionResponse {
+        session_token,
+        operator,
+    })
+}
+
+/// Close the session unless its operator is in the operator set of the store
+///
+/// A session can be opened before an instance starts, when the operator set can't be verified against the checkpoint
+/// of the store, so the instance verifies the operator again when it starts.
+pub(crate) fn verify_session_operator(store: &dyn KVStore) -> Result<()> {
+    let operator_set = effective_operator_set(store)?;
+    let mut session = SESSION.lock();
+    match session.as_ref() {
+        Some((_, operator)) if !operator_set.is_operator(operator) => {
+            let operator = operator.to_hex_string();
+            *session = None;
+            Err(Error::not_an_operator(operator))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error unless the token is the one of the open session
+pub(crate) fn check_session(token: Option<&[u8; SESSION_TOKEN_SIZE]>) -> Result<()> {
+    let session_token = SESSION.lock().as_ref().map(|(token, _)| *token);
+    match (session_token, token) {
+        (Some(expected), Some(token)) if constant_time_eq(&expected, token) => Ok(()),
+        (Some(_), _) => Err(Error::invalid_session_token()),
+        (None, _) => Err(Error::session_required()),
+    }
+}
+
+fn constant_time_eq(a: &[u8; SESSION_TOKEN_SIZE], b: &[u8; SESSION_TOKEN_SIZE]) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
@@ -17,8 +17,13 @@ impl Environment {
 }
 
 impl LightClientResolver for Environment {
-    fn get_light_client(&self, type_url: &str) -> Option<&alloc::boxed::Box<dyn LightClient>> {
-        self.lc_registry.get_light_client(type_url)
+    fn get_versioned_light_client(
+        &self,
+        type_url: &str,
+        version: Option<u32>,
+    ) -> Option<(u32, &alloc::boxed::Box<dyn LightClient>)> {
+        self.lc_registry
+            .get_versioned_light_client(type_url, version)
     }
 }
 
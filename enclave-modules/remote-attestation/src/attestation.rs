@@ -1,11 +1,15 @@
 use crate::errors::Error;
 use crate::prelude::*;
+use crate::tls::{default_tls_client, TlsClient};
 use crate::{IAS_HOSTNAME, REPORT_SUFFIX, SIGRL_SUFFIX};
-use alloc::str;
-use attestation_report::EndorsedAttestationVerificationReport;
+use attestation_report::{
+    parse_ias_report_response, parse_ias_sigrl_response, verify_report, verify_report_with_root_ca,
+    EndorsedAttestationVerificationReport,
+};
 use crypto::sgx::rand::fill_bytes;
 use host_api::remote_attestation::{get_ias_socket, get_quote, init_quote};
 use itertools::Itertools;
+use lcp_types::Time;
 use log::*;
 use ocall_commands::{GetIASSocketResult, GetQuoteInput, GetQuoteResult, InitQuoteResult};
 use sgx_tcrypto::rsgx_sha256_slice;
@@ -13,7 +17,6 @@ use sgx_tse::{rsgx_create_report, rsgx_verify_report};
 use sgx_tstd::{
     io::{Read, Write},
     net::TcpStream,
-    sync::Arc,
 };
 use sgx_types::{c_int, sgx_spid_t};
 use sgx_types::{sgx_quote_nonce_t, sgx_quote_sign_type_t, sgx_report_data_t};
@@ -22,6 +25,7 @@ pub const REPORT_DATA_SIZE: usize = 32;
 
 //input: pub_k: &sgx_ec256_public_t, todo: make this the pubkey of the node
 pub fn create_attestation_report(
+    current_timestamp: Time,
     report_data: sgx_report_data_t,
     sign_type: sgx_quote_sign_type_t,
     spid: sgx_spid_t,
@@ -42,15 +46,23 @@ pub fn create_attestation_report(
 
     trace!("EPID group = {:?}", epid_group_id);
 
-    let eg_num = as_u32_le(&epid_group_id);
+    let eg_num = u32::from_le_bytes(epid_group_id);
+    let tls = default_tls_client(current_timestamp);
 
     // (1.5) get sigrl
-    let GetIASSocketResult { fd } = get_ias_socket().map_err(Error::host_api)?;
+    let GetIASSocketResult { fd, mock_root_ca } = get_ias_socket().map_err(Error::host_api)?;
+    check_ias_endpoint(&mock_root_ca)?;
 
     trace!("Got ias_sock successfully = {}", fd);
 
     // Now sigrl_vec is the revocation list, a vec<u8>
-    let sigrl_vec: Vec<u8> = get_sigrl_from_intel(fd, eg_num, api_hex_str_bytes);
+    let sigrl_vec: Vec<u8> = get_sigrl_from_intel(
+        fd,
+        eg_num,
+        api_hex_str_bytes,
+        mock_root_ca.is_some(),
+        tls.as_ref(),
+    )?;
 
     // (2) Generate the report
     // Fill secp256k1 public key into report_data
@@ -139,21 +151,40 @@ pub fn create_attestation_report(
         ));
     }
 
-    let GetIASSocketResult { fd } = get_ias_socket().map_err(Error::host_api)?;
-
-    let (attn_report, signature, signing_cert) =
-        get_report_from_intel(fd, quote, api_hex_str_bytes);
+    let GetIASSocketResult { fd, mock_root_ca } = get_ias_socket().map_err(Error::host_api)?;
+    check_ias_endpoint(&mock_root_ca)?;
+
+    let report = get_report_from_intel(
+        fd,
+        quote,
+        api_hex_str_bytes,
+        mock_root_ca.is_some(),
+        tls.as_ref(),
+    )?;
+    match mock_root_ca {
+        Some(root_ca) => verify_report_with_root_ca(current_timestamp, &report, &root_ca),
+        None => verify_report(current_timestamp, &report),
+    }
+    .map_err(Error::attestation_report)?;
+    Ok(report)
+}
 
-    Ok(EndorsedAttestationVerificationReport {
-        avr: attn_report,
-        signature,
-        signing_cert,
-    })
+/// A mock IAS is not trustworthy, so only a simulated enclave, which is not trustworthy either, may use it
+fn check_ias_endpoint(mock_root_ca: &Option<Vec<u8>>) -> Result<(), Error> {
+    if mock_root_ca.is_some() && !cfg!(feature = "sgx-sw") {
+        return Err(Error::mock_ias_not_allowed());
+    }
+    Ok(())
 }
 
-pub fn get_sigrl_from_intel(fd: c_int, gid: u32, ias_key: &[u8]) -> Vec<u8> {
+pub fn get_sigrl_from_intel(
+    fd: c_int,
+    gid: u32,
+    ias_key: &[u8],
+    mock: bool,
+    tls: &dyn TlsClient,
+) -> Result<Vec<u8>, Error> {
     trace!("get_sigrl_from_intel fd = {:?}", fd);
-    let config = make_ias_client_config();
     let ias_key = String::from_utf8_lossy(ias_key).trim_end().to_owned();
 
     let req = format!("GET {}{:08x} HTTP/1.1\r\nHOST: {}\r\nOcp-Apim-Subscription-Key: {}\r\nConnection: Close\r\n\r\n",
@@ -164,31 +195,19 @@ pub fn get_sigrl_from_intel(fd: c_int, gid: u32, ias_key: &[u8]) -> Vec<u8> {
 
     trace!("get_sigrl_from_intel: {}", req);
 
-    let dns_name = webpki::DNSNameRef::try_from_ascii_str(IAS_HOSTNAME).unwrap();
-    let mut sess = rustls::ClientSession::new(&Arc::new(config), dns_name);
-    let mut sock = TcpStream::new(fd).unwrap();
-    let mut tls = rustls::Stream::new(&mut sess, &mut sock);
-
-    let _result = tls.write(req.as_bytes());
-    let mut plaintext = Vec::new();
-
-    info!("write complete");
-
-    match tls.read_to_end(&mut plaintext) {
-        Ok(_) => (),
+    let plaintext = match send_ias_request(fd, &req, mock, tls) {
+        Ok(plaintext) => plaintext,
         Err(e) => {
             warn!("get_sigrl_from_intel tls.read_to_end: {:?}", e);
             panic!("Communication error with IAS");
         }
-    }
+    };
     info!("read_to_end complete");
     let resp_string = String::from_utf8(plaintext.clone()).unwrap();
 
     trace!("{}", resp_string);
 
-    // resp_string
-
-    parse_response_sigrl(&plaintext)
+    parse_ias_sigrl_response(&plaintext).map_err(Error::attestation_report)
 }
 
 // TODO: support pse
@@ -196,9 +215,10 @@ pub fn get_report_from_intel(
     fd: c_int,
     quote: Vec<u8>,
     ias_key: &[u8],
-) -> (String, Vec<u8>, Vec<u8>) {
+    mock: bool,
+    tls: &dyn TlsClient,
+) -> Result<EndorsedAttestationVerificationReport, Error> {
     trace!("get_report_from_intel fd = {:?}", fd);
-    let config = make_ias_client_config();
     let encoded_quote = base64::encode(&quote[..]);
     let encoded_json = format!("{{\"isvEnclaveQuote\":\"{}\"}}\r\n", encoded_quote);
     let ias_key = String::from_utf8_lossy(ias_key).trim_end().to_owned();
@@ -211,170 +231,33 @@ pub fn get_report_from_intel(
                       encoded_json);
 
     trace!("{}", req);
-    let dns_name = webpki::DNSNameRef::try_from_ascii_str(IAS_HOSTNAME).unwrap();
-    let mut sess = rustls::ClientSession::new(&Arc::new(config), dns_name);
-    let mut sock = TcpStream::new(fd).unwrap();
-    let mut tls = rustls::Stream::new(&mut sess, &mut sock);
-
-    let _result = tls.write(req.as_bytes());
-    let mut plaintext = Vec::new();
-
-    info!("write complete");
-
-    tls.read_to_end(&mut plaintext).unwrap();
+    let plaintext = send_ias_request(fd, &req, mock, tls).unwrap();
     info!("read_to_end complete");
     let resp_string = String::from_utf8(plaintext.clone()).unwrap();
 
     trace!("resp_string = {}", resp_string);
 
-    let (attn_report, sig, cert) = parse_response_attn_report(&plaintext);
-
-    (attn_report, sig, cert)
+    let eavr = parse_ias_report_response(&plaintext).map_err(Error::attestation_report)?;
+    info!("Attestation report: {}", eavr.avr);
+    Ok(eavr)
 }
 
-fn parse_response_attn_report(resp: &[u8]) -> (String, Vec<u8>, Vec<u8>) {
-    trace!("parse_response_attn_report");
-    let mut headers = [httparse::EMPTY_HEADER; 16];
-    let mut respp = httparse::Response::new(&mut headers);
-    let result = respp.parse(resp);
-    trace!("parse result {:?}", result);
-
-    let msg: &'static str;
-
-    match respp.code {
-        Some(200) => msg = "OK Operation Successful",
-        Some(401) => msg = "Unauthorized Failed to authenticate or authorize request.",
-        Some(404) => msg = "Not Found GID does not refer to a valid EPID group ID.",
-        Some(500) => msg = "Internal error occurred",
-        Some(503) => {
-            msg = "Service is currently not able to process the request (due to
-            a temporary overloading or maintenance). This is a
-            temporary state – the same request can be repeated after
-            some time. "
-        }
-        _ => {
-            warn!("DBG:{}", respp.code.unwrap());
-            msg = "Unknown error occured"
-        }
-    }
-
-    info!("{}", msg);
-    let mut len_num: u32 = 0;
-
-    let mut sig = String::new();
-    let mut cert = String::new();
-    let mut attn_report = String::new();
-
-    for i in 0..respp.headers.len() {
-        let h = respp.headers[i];
-        match h.name {
-            "Content-Length" => {
-                let len_str = String::from_utf8(h.value.to_vec()).unwrap();
-                len_num = len_str.parse::<u32>().unwrap();
-                trace!("content length = {}", len_num);
-            }
-            "X-IASReport-Signature" => sig = str::from_utf8(h.value).unwrap().to_string(),
-            "X-IASReport-Signing-Certificate" => {
-                cert = str::from_utf8(h.value).unwrap().to_string()
-            }
-            _ => (),
-        }
-    }
-
-    // Remove %0A from cert, and only obtain the signing cert
-    cert = cert.replace("%0A", "");
-    cert = percent_decode(cert);
-
-    let v: Vec<&str> = cert.split("-----").collect();
-    let sig_cert = v[2].to_string();
-
-    if len_num != 0 {
-        let header_len = result.unwrap().unwrap();
-        let resp_body = &resp[header_len..];
-        attn_report = str::from_utf8(resp_body).unwrap().to_string();
-        info!("Attestation report: {}", attn_report);
-    }
-
-    let sig_bytes = base64::decode(&sig).unwrap();
-    let sig_cert_bytes = base64::decode(&sig_cert).unwrap();
-    // len_num == 0
-    (attn_report, sig_bytes, sig_cert_bytes)
-}
-
-fn parse_response_sigrl(resp: &[u8]) -> Vec<u8> {
-    trace!("parse_response_sigrl");
-    let mut headers = [httparse::EMPTY_HEADER; 16];
-    let mut respp = httparse::Response::new(&mut headers);
-    let result = respp.parse(resp);
-    trace!("parse result {:?}", result);
-    trace!("parse response{:?}", respp);
-
-    let msg: &'static str;
-
-    match respp.code {
-        Some(200) => msg = "OK Operation Successful",
-        Some(401) => msg = "Unauthorized Failed to authenticate or authorize request.",
-        Some(404) => msg = "Not Found GID does not refer to a valid EPID group ID.",
-        Some(500) => msg = "Internal error occurred",
-        Some(503) => {
-            msg = "Service is currently not able to process the request (due to
-            a temporary overloading or maintenance). This is a
-            temporary state – the same request can be repeated after
-            some time. "
-        }
-        _ => msg = "Unknown error occured",
-    }
-
-    info!("{}", msg);
-    let mut len_num: u32 = 0;
-
-    for i in 0..respp.headers.len() {
-        let h = respp.headers[i];
-        if h.name == "content-length" {
-            let len_str = String::from_utf8(h.value.to_vec()).unwrap();
-            len_num = len_str.parse::<u32>().unwrap();
-            trace!("content length = {}", len_num);
-        }
-    }
-
-    if len_num != 0 {
-        let header_len = result.unwrap().unwrap();
-        let resp_body = &resp[header_len..];
-        trace!("Base64-encoded SigRL: {:?}", resp_body);
-
-        return base64::decode(str::from_utf8(resp_body).unwrap()).unwrap();
-    }
-
-    // len_num == 0
-    Vec::new()
-}
-
-pub fn make_ias_client_config() -> rustls::ClientConfig {
-    let mut config = rustls::ClientConfig::new();
-
-    config
-        .root_store
-        .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
-
-    config
-}
-
-pub(crate) fn as_u32_le(array: &[u8; 4]) -> u32 {
-    ((array[0] as u32) << 0)
-        + ((array[1] as u32) << 8)
-        + ((array[2] as u32) << 16)
-        + ((array[3] as u32) << 24)
-}
-
-fn percent_decode(orig: String) -> String {
-    let v: Vec<&str> = orig.split("%").collect();
-    let mut ret = String::new();
-    ret.push_str(v[0]);
-    if v.len() > 1 {
-        for s in v[1..].iter() {
-            ret.push(u8::from_str_radix(&s[0..2], 16).unwrap() as char);
-            ret.push_str(&s[2..]);
-        }
+/// Send the request over the socket and read the whole response
+///
+/// The request to Intel is sent over TLS, while the one to a mock IAS is sent in plain HTTP.
+fn send_ias_request(
+    fd: c_int,
+    req: &str,
+    mock: bool,
+    tls: &dyn TlsClient,
+) -> sgx_tstd::io::Result<Vec<u8>> {
+    let mut sock = TcpStream::new(fd).unwrap();
+    if mock {
+        let mut plaintext = Vec::new();
+        sock.write_all(req.as_bytes())?;
+        info!("write complete");
+        sock.read_to_end(&mut plaintext)?;
+        return Ok(plaintext);
     }
-    ret
+    tls.request(&mut sock, IAS_HOSTNAME, req.as_bytes())
 }
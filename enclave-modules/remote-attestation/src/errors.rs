@@ -32,6 +32,9 @@ define_error! {
             format_args!("UnexpectedQuoteError: {}", e.descr)
         },
 
+        MockIasNotAllowed
+        |_| { "MockIasNotAllowed: the host connected to a mock IAS, which only an enclave built with `sgx-sw` accepts" },
+
         SgxError {
             status: sgx_status_t,
             descr: String
@@ -24,6 +24,7 @@ pub use errors::Error;
 pub mod attestation;
 mod errors;
 pub mod report;
+pub mod tls;
 
 #[cfg(feature = "sgx-sw")]
 pub mod simulate;
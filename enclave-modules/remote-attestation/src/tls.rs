@@ -0,0 +1,213 @@
+use crate::prelude::*;
+use lcp_types::Time;
+use sgx_tstd::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    sync::Arc,
+};
+
+/// `TlsClient` sends an HTTP request to a server over TLS and reads the whole response
+///
+/// The TLS session is terminated in the enclave, so the host that relays the socket can neither read nor forge the response.
+pub trait TlsClient {
+    /// Send `req` to `hostname` over the connected socket, and read the response until the server closes the connection
+    fn request(&self, sock: &mut TcpStream, hostname: &str, req: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// Returns the TLS client of the stack that the enclave is built with
+///
+/// `now` is the time to check the validity of the server certificates against.
+pub fn default_tls_client(now: Time) -> Box<dyn TlsClient> {
+    #[cfg(feature = "tls-rustls")]
+    {
+        Box::new(RustlsClient::new(now))
+    }
+    #[cfg(not(feature = "tls-rustls"))]
+    {
+        let _ = now;
+        Box::new(MesalockRustlsClient)
+    }
+}
+
+/// `MesalockRustlsClient` is a TLS client on the SGX fork of rustls by mesalock-linux
+///
+/// The fork is no longer maintained, and the `tls-rustls` feature replaces it with `RustlsClient`.
+pub struct MesalockRustlsClient;
+
+impl TlsClient for MesalockRustlsClient {
+    fn request(&self, sock: &mut TcpStream, hostname: &str, req: &[u8]) -> io::Result<Vec<u8>> {
+        let mut config = rustls::ClientConfig::new();
+        config
+            .root_store
+            .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+        let dns_name = webpki::DNSNameRef::try_from_ascii_str(hostname)
+            .map_err(|e| other_error(format!("invalid hostname: {:?}", e)))?;
+        let mut sess = rustls::ClientSession::new(&Arc::new(config), dns_name);
+        let mut tls = rustls::Stream::new(&mut sess, sock);
+
+        tls.write_all(req)?;
+        let mut plaintext = Vec::new();
+        tls.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+}
+
+#[cfg(feature = "tls-rustls")]
+pub use self::unbuffered::RustlsClient;
+
+#[cfg(feature = "tls-rustls")]
+mod unbuffered {
+    use super::*;
+    use core::time::Duration;
+    use rustls_nostd::client::UnbufferedClientConnection;
+    use rustls_nostd::pki_types::{ServerName, UnixTime};
+    use rustls_nostd::time_provider::TimeProvider;
+    use rustls_nostd::unbuffered::{
+        AppDataRecord, ConnectionState, EncodeError, EncryptError, InsufficientSizeError,
+        UnbufferedStatus,
+    };
+    use rustls_nostd::{crypto::ring::default_provider, ClientConfig, RootCertStore};
+
+    const INITIAL_BUFFER_SIZE: usize = 16 * 1024;
+
+    /// `RustlsClient` is a TLS client on the unbuffered API of rustls 0.23, which works without `std`
+    ///
+    /// The socket I/O is driven by the client itself, so rustls needs neither `std::io` nor the system time.
+    pub struct RustlsClient {
+        now: Time,
+    }
+
+    #[derive(Debug)]
+    struct FixedTime(Time);
+
+    impl TimeProvider for FixedTime {
+        fn current_time(&self) -> Option<UnixTime> {
+            Some(UnixTime::since_unix_epoch(Duration::from_secs(
+                self.0.as_unix_timestamp_secs(),
+            )))
+        }
+    }
+
+    impl RustlsClient {
+        pub fn new(now: Time) -> Self {
+            Self { now }
+        }
+
+        fn config(&self) -> io::Result<Arc<ClientConfig>> {
+            let mut root_store = RootCertStore::empty();
+            root_store.extend(webpki_roots_nostd::TLS_SERVER_ROOTS.iter().cloned());
+            let config = ClientConfig::builder_with_details(
+                Arc::new(default_provider()),
+                Arc::new(FixedTime(self.now)),
+            )
+            .with_safe_default_protocol_versions()
+            .map_err(|e| other_error(format!("{:?}", e)))?
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+            Ok(Arc::new(config))
+        }
+    }
+
+    impl TlsClient for RustlsClient {
+        fn request(&self, sock: &mut TcpStream, hostname: &str, req: &[u8]) -> io::Result<Vec<u8>> {
+            let server_name = ServerName::try_from(hostname.to_string())
+                .map_err(|e| other_error(format!("invalid hostname: {:?}", e)))?;
+            let mut conn = UnbufferedClientConnection::new(self.config()?, server_name)
+                .map_err(|e| other_error(format!("{:?}", e)))?;
+
+            let mut incoming = vec![0u8; INITIAL_BUFFER_SIZE];
+            let mut incoming_used = 0;
+            let mut outgoing = vec![0u8; INITIAL_BUFFER_SIZE];
+            let mut outgoing_used = 0;
+            let mut sent = false;
+            let mut plaintext = Vec::new();
+            loop {
+                let UnbufferedStatus { mut discard, state } =
+                    conn.process_tls_records(&mut incoming[..incoming_used]);
+                let mut must_read = false;
+                match state.map_err(|e| other_error(format!("{:?}", e)))? {
+                    ConnectionState::ReadTraffic(mut state) => {
+                        while let Some(res) = state.next_record() {
+                            let AppDataRecord {
+                                discard: new_discard,
+                                payload,
+                            } = res.map_err(|e| other_error(format!("{:?}", e)))?;
+                            discard += new_discard;
+                            plaintext.extend_from_slice(payload);
+                        }
+                    }
+                    ConnectionState::EncodeTlsData(mut state) => {
+                        outgoing_used += match state.encode(&mut outgoing[outgoing_used..]) {
+                            Ok(n) => n,
+                            Err(EncodeError::InsufficientSize(InsufficientSizeError {
+                                required_size,
+                            })) => {
+                                outgoing.resize(outgoing_used + required_size, 0);
+                                state
+                                    .encode(&mut outgoing[outgoing_used..])
+                                    .map_err(|e| other_error(format!("{:?}", e)))?
+                            }
+                            Err(e) => return Err(other_error(format!("{:?}", e))),
+                        };
+                    }
+                    ConnectionState::TransmitTlsData(state) => {
+                        sock.write_all(&outgoing[..outgoing_used])?;
+                        outgoing_used = 0;
+                        state.done();
+                    }
+                    ConnectionState::WriteTraffic(mut state) if !sent => {
+                        outgoing_used += match state.encrypt(req, &mut outgoing[outgoing_used..]) {
+                            Ok(n) => n,
+                            Err(EncryptError::InsufficientSize(InsufficientSizeError {
+                                required_size,
+                            })) => {
+                                outgoing.resize(outgoing_used + required_size, 0);
+                                state
+                                    .encrypt(req, &mut outgoing[outgoing_used..])
+                                    .map_err(|e| other_error(format!("{:?}", e)))?
+                            }
+                            Err(e) => return Err(other_error(format!("{:?}", e))),
+                        };
+                        sock.write_all(&outgoing[..outgoing_used])?;
+                        outgoing_used = 0;
+                        sent = true;
+                    }
+                    ConnectionState::WriteTraffic(_) | ConnectionState::BlockedHandshake => {
+                        must_read = true;
+                    }
+                    ConnectionState::PeerClosed | ConnectionState::Closed => return Ok(plaintext),
+                    state => {
+                        return Err(other_error(format!(
+                            "unexpected TLS connection state: {:?}",
+                            state
+                        )))
+                    }
+                }
+
+                incoming.copy_within(discard..incoming_used, 0);
+                incoming_used -= discard;
+                if must_read {
+                    if incoming_used == incoming.len() {
+                        incoming.resize(incoming.len() * 2, 0);
+                    }
+                    let n = sock.read(&mut incoming[incoming_used..])?;
+                    if n == 0 {
+                        // some servers close the connection without a close_notify after the response
+                        if sent {
+                            return Ok(plaintext);
+                        }
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "the server closed the connection during the handshake",
+                        ));
+                    }
+                    incoming_used += n;
+                }
+            }
+        }
+    }
+}
+
+fn other_error(descr: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, descr)
+}
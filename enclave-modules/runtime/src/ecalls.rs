@@ -1,5 +1,9 @@
 use crate::prelude::*;
-use ecall_commands::{CommandResponse, ECallCommand};
+use ecall_commands::{
+    decode_command, decode_legacy_command, decode_versioned_frame, encode_frame,
+    encode_legacy_response, encode_response, encode_versioned_frame, CommandResponse,
+    CommandResult, ECallCommand, ErrorCode, ProtocolError, LEGACY_PROTOCOL_VERSION,
+};
 use ecall_handler::dispatch;
 use enclave_environment::Env;
 use enclave_utils::validate_const_ptr;
@@ -33,40 +37,58 @@ pub fn ecall_execute_command(
         sgx_status_t::SGX_ERROR_UNEXPECTED
     );
 
-    let (status, result) = execute_command(command, command_len);
-    let res = match bincode::serde::encode_to_vec(&result, bincode::config::standard()) {
-        Ok(res) => {
-            if res.len() > output_buf_maxlen as usize {
-                error!(
-                    "output_buf will be overflow: res_len={} output_buf_maxlen={}",
-                    res.len(),
-                    output_buf_maxlen
-                );
-                return sgx_status_t::SGX_ERROR_UNEXPECTED;
+    let frame = unsafe { alloc::slice::from_raw_parts(command, command_len as usize) };
+    let (status, res) = match decode_versioned_frame(frame) {
+        // a handshake is answered with the header only
+        // the frames are answered in the version of the host, so that the legacy hosts can decode them
+        Ok((version, [])) => (
+            sgx_status_t::SGX_SUCCESS,
+            encode_versioned_frame(version, &[]),
+        ),
+        Ok((LEGACY_PROTOCOL_VERSION, payload)) => {
+            let (status, result) = execute_command(decode_legacy_command(payload));
+            match encode_legacy_response(&result) {
+                Ok(res) => (
+                    status,
+                    encode_versioned_frame(LEGACY_PROTOCOL_VERSION, &res),
+                ),
+                Err(e) => {
+                    error!("failed to serialize: result={:?} error={:?}", result, e);
+                    return sgx_status_t::SGX_ERROR_UNEXPECTED;
+                }
             }
-            res
         }
+        Ok((_, payload)) => {
+            let (status, result) = execute_command(decode_command(payload));
+            (status, encode_frame(&encode_response(&result)))
+        }
+        // the command cannot be decoded, so the host is only told the version of the enclave
         Err(e) => {
-            error!("failed to serialize: result={:?} error={:?}", result, e);
-            return sgx_status_t::SGX_ERROR_UNEXPECTED;
+            error!("rejected the command: err={}", e);
+            (sgx_status_t::SGX_ERROR_UNEXPECTED, encode_frame(&[]))
         }
     };
+    if res.len() > output_buf_maxlen as usize {
+        error!(
+            "output_buf will be overflow: res_len={} output_buf_maxlen={}",
+            res.len(),
+            output_buf_maxlen
+        );
+        return sgx_status_t::SGX_ERROR_UNEXPECTED;
+    }
     unsafe { core::ptr::copy_nonoverlapping(res.as_ptr(), output_buf, res.len()) };
     *output_len = res.len() as u32;
 
     status
 }
 
-fn execute_command(command: *const u8, command_len: u32) -> (sgx_status_t, CommandResponse) {
-    let cmd: ECallCommand = match bincode::serde::decode_borrowed_from_slice(
-        unsafe { alloc::slice::from_raw_parts(command, command_len as usize) },
-        bincode::config::standard(),
-    ) {
+fn execute_command(cmd: Result<ECallCommand, ProtocolError>) -> (sgx_status_t, CommandResult) {
+    let cmd = match cmd {
         Ok(cmd) => cmd,
         Err(e) => {
             return (
                 sgx_status_t::SGX_ERROR_UNEXPECTED,
-                CommandResponse::CommandError(format!("failed to bincode::deserialize: {:?}", e)),
+                CommandResponse::CommandError(ErrorCode::InvalidInput, e.to_string()).into(),
             );
         }
     };
@@ -79,7 +101,7 @@ fn execute_command(command: *const u8, command_len: u32) -> (sgx_status_t, Comma
         Ok(result) => (sgx_status_t::SGX_SUCCESS, result),
         Err(e) => (
             sgx_status_t::SGX_ERROR_UNEXPECTED,
-            CommandResponse::CommandError(format!("{:?}", e)),
+            CommandResponse::CommandError(e.code(), format!("{:?}", e)).into(),
         ),
     }
 }
@@ -0,0 +1,13 @@
+#![no_main]
+use commitments::{CommitmentProof, EthABIEncoder, ProxyMessage};
+use libfuzzer_sys::fuzz_target;
+
+// the commitment proofs that are submitted back to the enclave, e.g. in `AggregateMessages`
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = CommitmentProof::ethabi_decode(data) {
+        let _ = proof.message();
+    }
+    if let Ok(msg) = ProxyMessage::from_bytes(data) {
+        let _ = msg.validate();
+    }
+});
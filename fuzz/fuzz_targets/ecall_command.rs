@@ -0,0 +1,18 @@
+#![no_main]
+use ecall_commands::{
+    decode_command, decode_legacy_command, decode_versioned_frame, LEGACY_PROTOCOL_VERSION,
+};
+use libfuzzer_sys::fuzz_target;
+
+// the frame that the enclave receives from the host in `ecall_execute_command`
+fuzz_target!(|frame: &[u8]| {
+    match decode_versioned_frame(frame) {
+        Ok((LEGACY_PROTOCOL_VERSION, payload)) => {
+            let _ = decode_legacy_command(payload);
+        }
+        Ok((_, payload)) => {
+            let _ = decode_command(payload);
+        }
+        Err(_) => {}
+    }
+});
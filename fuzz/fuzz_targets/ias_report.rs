@@ -0,0 +1,13 @@
+#![no_main]
+use attestation_report::{parse_ias_report_response, parse_ias_sigrl_response};
+use libfuzzer_sys::fuzz_target;
+
+// the responses that the enclave receives from IAS through the socket opened by the host
+fuzz_target!(|resp: &[u8]| {
+    let _ = parse_ias_sigrl_response(resp);
+    if let Ok(eavr) = parse_ias_report_response(resp) {
+        if let Ok(avr) = eavr.get_avr() {
+            let _ = avr.parse_quote();
+        }
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use ibc::core::ics23_commitment::commitment::CommitmentProofBytes;
+use libfuzzer_sys::fuzz_target;
+use tendermint_lc::client::decode_merkle_proof;
+
+// the ICS-23 proofs that relayers submit with the verification commands
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = CommitmentProofBytes::try_from(data.to_vec()) {
+        let _ = decode_merkle_proof(&proof);
+    }
+});
@@ -0,0 +1,994 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use crate::sgx::parse_report_body;
+use crypto::Address;
+use serde::{Deserialize, Serialize};
+use sgx_types::{metadata::metadata_t, sgx_report_body_t};
+
+/// Offset of the report body in a DCAP quote (= size of the quote header)
+const QUOTE_HEADER_SIZE: usize = 48;
+/// Size of the ISV enclave report body in a DCAP quote
+const QUOTE_REPORT_BODY_SIZE: usize = 384;
+/// Quote version generated by the DCAP quoting library
+const QUOTE_VERSION_3: u16 = 3;
+/// Attestation key type: ECDSA-256-with-P-256 curve
+const ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+
+/// DCAPCollateral is the Intel-signed data that a verifier needs in addition to a DCAP quote
+///
+/// The JSON collaterals and the issuer chains are kept exactly as returned by the PCS/PCCS
+/// so that their signatures can be verified by the enclave and on-chain verifiers.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DCAPCollateral {
+    /// TCB info: `{"tcbInfo":{...},"signature":"<hex>"}`
+    pub tcb_info: String,
+    /// PEM certificate chain of the TCB info signing key (leaf first)
+    pub tcb_info_issuer_chain: String,
+    /// QE identity: `{"enclaveIdentity":{...},"signature":"<hex>"}`
+    pub qe_identity: String,
+    /// PEM certificate chain of the QE identity signing key (leaf first)
+    pub qe_identity_issuer_chain: String,
+    /// DER encoded CRL of the PCK CA
+    #[serde(with = "crate::report::serde_base64")]
+    pub pck_crl: Vec<u8>,
+    /// PEM certificate chain of the PCK CRL issuer (leaf first)
+    pub pck_crl_issuer_chain: String,
+    /// DER encoded CRL of the root CA
+    #[serde(with = "crate::report::serde_base64")]
+    pub root_ca_crl: Vec<u8>,
+}
+
+/// DCAPQuoteBundle packages a DCAP quote with its collateral
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DCAPQuoteBundle {
+    /// Raw quote generated by the quoting enclave
+    #[serde(with = "crate::report::serde_base64")]
+    pub quote: Vec<u8>,
+    pub collateral: DCAPCollateral,
+}
+
+impl DCAPQuoteBundle {
+    /// Returns the ISV enclave report body of the quote
+    pub fn report_body(&self) -> Result<sgx_report_body_t, Error> {
+        if self.quote.len() < QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE {
+            return Err(Error::invalid_dcap_quote(format!(
+                "quote is too short: len={}",
+                self.quote.len()
+            )));
+        }
+        let version = u16::from_le_bytes([self.quote[0], self.quote[1]]);
+        let att_key_type = u16::from_le_bytes([self.quote[2], self.quote[3]]);
+        if version != QUOTE_VERSION_3 || att_key_type != ATT_KEY_TYPE_ECDSA_P256 {
+            return Err(Error::invalid_dcap_quote(format!(
+                "unsupported quote: version={} att_key_type={}",
+                version, att_key_type
+            )));
+        }
+        parse_report_body(
+            &self.quote[QUOTE_HEADER_SIZE..QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE],
+        )
+    }
+
+    pub fn get_enclave_key_address(&self) -> Result<Address, Error> {
+        Ok(crypto::address_from_report_data(
+            &self.report_body()?.report_data.d,
+        )?)
+    }
+
+    pub fn match_metadata(&self, metadata: &metadata_t) -> Result<(), Error> {
+        let mr_enclave = self.report_body()?.mr_enclave;
+        if mr_enclave.m != metadata.enclave_css.body.enclave_hash.m {
+            Err(Error::mrenclave_mismatch(
+                mr_enclave.m.into(),
+                metadata.enclave_css.body.enclave_hash.m.into(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Splits a PCS signed JSON (`{"<key>":<body>,"signature":"<hex>"}`) into the signed body and the raw signature
+pub(crate) fn split_signed_json<'a>(json: &'a str, key: &str) -> Result<(&'a str, Vec<u8>), Error> {
+    let json = json.trim();
+    let prefix = format!("{{\"{}\":", key);
+    let sig_marker = ",\"signature\":\"";
+    let rest = json.strip_prefix(prefix.as_str()).ok_or_else(|| {
+        Error::invalid_dcap_collateral(format!("`{}` is not found at the beginning", key))
+    })?;
+    let sig_pos = rest.rfind(sig_marker).ok_or_else(|| {
+        Error::invalid_dcap_collateral(format!("signature of `{}` not found", key))
+    })?;
+    let sig_hex = rest[sig_pos + sig_marker.len()..]
+        .strip_suffix("\"}")
+        .ok_or_else(|| {
+            Error::invalid_dcap_collateral(format!("malformed signature of `{}`", key))
+        })?;
+    let signature = hex::decode(sig_hex)
+        .map_err(|e| Error::invalid_dcap_collateral(format!("invalid signature hex: {}", e)))?;
+    Ok((&rest[..sig_pos], signature))
+}
+
+/// Converts a raw ECDSA P-256 signature (r || s) into the ASN.1 DER encoding
+pub(crate) fn ecdsa_raw_to_der(raw: &[u8]) -> Result<Vec<u8>, Error> {
+    if raw.len() != 64 {
+        return Err(Error::invalid_dcap_collateral(format!(
+            "invalid ECDSA signature length: len={}",
+            raw.len()
+        )));
+    }
+    let mut seq = Vec::with_capacity(70);
+    for half in raw.chunks(32) {
+        let mut int: Vec<u8> = half.iter().copied().skip_while(|b| *b == 0).collect();
+        if int.is_empty() || int[0] & 0x80 != 0 {
+            int.insert(0, 0);
+        }
+        seq.push(0x02);
+        seq.push(int.len() as u8);
+        seq.extend(int);
+    }
+    let mut der = vec![0x30, seq.len() as u8];
+    der.extend(seq);
+    Ok(der)
+}
+
+/// Reads a DER element with the given tag and returns `(element, contents, rest)`
+pub(crate) fn read_der(bz: &[u8], tag: u8) -> Result<(&[u8], &[u8], &[u8]), Error> {
+    let malformed = || Error::invalid_dcap_collateral("malformed DER".into());
+    if bz.len() < 2 || bz[0] != tag {
+        return Err(malformed());
+    }
+    let (len, header_len) = match bz[1] {
+        l if l < 0x80 => (l as usize, 2),
+        l @ 0x81..=0x83 => {
+            let n = (l & 0x7f) as usize;
+            let len_bytes = bz.get(2..2 + n).ok_or_else(malformed)?;
+            (
+                len_bytes
+                    .iter()
+                    .fold(0usize, |acc, b| (acc << 8) | *b as usize),
+                2 + n,
+            )
+        }
+        _ => return Err(malformed()),
+    };
+    let end = header_len.checked_add(len).ok_or_else(malformed)?;
+    if bz.len() < end {
+        return Err(malformed());
+    }
+    Ok((&bz[..end], &bz[header_len..end], &bz[end..]))
+}
+
+#[cfg(any(feature = "std", feature = "sgx"))]
+pub use verification::{verify_dcap_quote, INTEL_SGX_ROOT_CA_DER};
+
+#[cfg(any(feature = "std", feature = "sgx"))]
+mod verification {
+    use super::*;
+    use chrono::DateTime;
+    use crypto::Keccak256;
+    use lcp_types::Time;
+    use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+    use serde_json::Value;
+    use sha2::{Digest, Sha256};
+    #[cfg(feature = "sgx")]
+    use webpki_sgx as webpki;
+
+    /// The Intel SGX Root CA, which every certificate chain of a quote and its collateral must terminate at
+    pub const INTEL_SGX_ROOT_CA_DER: &[u8] =
+        include_bytes!("../certs/Intel_SGX_Provisioning_Certification_RootCA.der");
+
+    /// The TCB statuses of the platform and the quoting enclave that the verification accepts
+    const ACCEPTED_TCB_STATUSES: &[&str] = &["UpToDate", "SWHardeningNeeded"];
+
+    /// Type of the certification data that carries the PCK certificate chain
+    const CERT_DATA_TYPE_PCK_CHAIN: u16 = 5;
+    const ECDSA_SIGNATURE_SIZE: usize = 64;
+    const ECDSA_PUBLIC_KEY_SIZE: usize = 64;
+
+    /// OID of the SGX extension of a PCK certificate: 1.2.840.113741.1.13.1
+    const SGX_EXTENSION_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+    const SGX_TCB_COMPONENTS: usize = 16;
+
+    const DER_BOOLEAN: u8 = 0x01;
+    const DER_INTEGER: u8 = 0x02;
+    const DER_BIT_STRING: u8 = 0x03;
+    const DER_OCTET_STRING: u8 = 0x04;
+    const DER_OID: u8 = 0x06;
+    const DER_UTC_TIME: u8 = 0x17;
+    const DER_GENERALIZED_TIME: u8 = 0x18;
+    const DER_SEQUENCE: u8 = 0x30;
+    const DER_CONTEXT_0: u8 = 0xa0;
+    const DER_CONTEXT_3: u8 = 0xa3;
+
+    /// Verifies the quote and its collateral against the Intel SGX Root CA, and returns the keccak256 hash of the root
+    ///
+    /// The verification covers the PCK certificate chain in the quote, the signatures of the quote and the QE report,
+    /// the revocations in the CRLs, and the TCB statuses of the platform and the QE in the collateral.
+    pub fn verify_dcap_quote(
+        current_timestamp: Time,
+        bundle: &DCAPQuoteBundle,
+    ) -> Result<[u8; 32], Error> {
+        verify_dcap_quote_with_root(current_timestamp, bundle, INTEL_SGX_ROOT_CA_DER)
+    }
+
+    pub(crate) fn verify_dcap_quote_with_root(
+        current_timestamp: Time,
+        bundle: &DCAPQuoteBundle,
+        trusted_root: &[u8],
+    ) -> Result<[u8; 32], Error> {
+        let now = current_timestamp.as_unix_timestamp_secs() as i64;
+        let collateral = &bundle.collateral;
+
+        let root_crl = verify_crl(&collateral.root_ca_crl, trusted_root, now)?;
+        let tcb_chain = verify_chain(
+            &collateral.tcb_info_issuer_chain,
+            trusted_root,
+            &root_crl,
+            now,
+        )?;
+        let qe_chain = verify_chain(
+            &collateral.qe_identity_issuer_chain,
+            trusted_root,
+            &root_crl,
+            now,
+        )?;
+        let crl_chain = verify_chain(
+            &collateral.pck_crl_issuer_chain,
+            trusted_root,
+            &root_crl,
+            now,
+        )?;
+        let pck_crl = verify_crl(&collateral.pck_crl, &crl_chain[0], now)?;
+        let tcb_info = verify_signed_json(&collateral.tcb_info, "tcbInfo", &tcb_chain[0], now)?;
+        let qe_identity = verify_signed_json(
+            &collateral.qe_identity,
+            "enclaveIdentity",
+            &qe_chain[0],
+            now,
+        )?;
+
+        bundle.report_body()?;
+        let quote = Quote::parse(&bundle.quote)?;
+        let pck_chain = verify_chain(quote.pck_chain, trusted_root, &root_crl, now)?;
+        // the PCK CRL only lists the certificates issued by its issuer
+        if pck_chain.len() < 2 || pck_chain[1] != crl_chain[0] {
+            return Err(Error::invalid_dcap_quote(
+                "the PCK certificate is not issued by the issuer of the PCK CRL".into(),
+            ));
+        }
+        check_not_revoked(&pck_chain[0], &pck_crl)?;
+
+        verify_signature(
+            &pck_chain[0],
+            quote.qe_report,
+            &ecdsa_raw_to_der(quote.qe_report_signature)?,
+        )?;
+        let qe_report = parse_report_body(quote.qe_report)?;
+        let attestation_key_hash = Sha256::new()
+            .chain_update(quote.attestation_key)
+            .chain_update(quote.qe_auth_data)
+            .finalize();
+        if qe_report.report_data.d[..32] != attestation_key_hash[..] {
+            return Err(Error::invalid_dcap_quote(
+                "the QE report doesn't bind the attestation key".into(),
+            ));
+        }
+        verify_attestation_key_signature(quote.attestation_key, quote.signed, quote.isv_signature)?;
+
+        check_qe_identity(&qe_identity, &qe_report)?;
+        check_tcb_info(&tcb_info, &PckTcb::parse(&pck_chain[0])?)?;
+
+        Ok(trusted_root.keccak256())
+    }
+
+    /// Quote is a DCAP quote of version 3, whose fields borrow the raw quote
+    struct Quote<'a> {
+        /// The header and the ISV enclave report body, which the attestation key signs
+        signed: &'a [u8],
+        isv_signature: &'a [u8],
+        attestation_key: &'a [u8],
+        qe_report: &'a [u8],
+        qe_report_signature: &'a [u8],
+        qe_auth_data: &'a [u8],
+        pck_chain: &'a str,
+    }
+
+    impl<'a> Quote<'a> {
+        fn parse(quote: &'a [u8]) -> Result<Self, Error> {
+            let mut bz = quote;
+            let signed = take(&mut bz, QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE)?;
+            let sig_data_len = u32::from_le_bytes(take(&mut bz, 4)?.try_into().unwrap());
+            if sig_data_len as usize != bz.len() {
+                return Err(Error::invalid_dcap_quote(format!(
+                    "unexpected signature data length: expected={} actual={}",
+                    sig_data_len,
+                    bz.len()
+                )));
+            }
+            let isv_signature = take(&mut bz, ECDSA_SIGNATURE_SIZE)?;
+            let attestation_key = take(&mut bz, ECDSA_PUBLIC_KEY_SIZE)?;
+            let qe_report = take(&mut bz, QUOTE_REPORT_BODY_SIZE)?;
+            let qe_report_signature = take(&mut bz, ECDSA_SIGNATURE_SIZE)?;
+            let qe_auth_data_len = u16::from_le_bytes(take(&mut bz, 2)?.try_into().unwrap());
+            let qe_auth_data = take(&mut bz, qe_auth_data_len as usize)?;
+            let cert_data_type = u16::from_le_bytes(take(&mut bz, 2)?.try_into().unwrap());
+            let cert_data_len = u32::from_le_bytes(take(&mut bz, 4)?.try_into().unwrap());
+            if cert_data_type != CERT_DATA_TYPE_PCK_CHAIN {
+                return Err(Error::invalid_dcap_quote(format!(
+                    "unsupported certification data type: {}",
+                    cert_data_type
+                )));
+            }
+            let pck_chain = core::str::from_utf8(take(&mut bz, cert_data_len as usize)?)
+                .map_err(|e| Error::invalid_dcap_quote(format!("invalid PCK chain: {}", e)))?;
+            Ok(Self {
+                signed,
+                isv_signature,
+                attestation_key,
+                qe_report,
+                qe_report_signature,
+                qe_auth_data,
+                pck_chain: pck_chain.trim_end_matches('\0'),
+            })
+        }
+    }
+
+    fn take<'a>(bz: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+        if bz.len() < n {
+            return Err(Error::invalid_dcap_quote("quote is too short".into()));
+        }
+        let (head, rest) = bz.split_at(n);
+        *bz = rest;
+        Ok(head)
+    }
+
+    /// PckTcb is the TCB of the platform that the SGX extension of the PCK certificate describes
+    struct PckTcb {
+        fmspc: Vec<u8>,
+        pceid: Vec<u8>,
+        components: [u64; SGX_TCB_COMPONENTS],
+        pcesvn: u64,
+    }
+
+    impl PckTcb {
+        fn parse(pck: &[u8]) -> Result<Self, Error> {
+            let mut extensions = parse_tbs(pck)?.extensions.ok_or_else(|| {
+                Error::invalid_dcap_quote("the PCK certificate has no extensions".into())
+            })?;
+            while !extensions.is_empty() {
+                let (_, extension, rest) = read_der(extensions, DER_SEQUENCE)?;
+                extensions = rest;
+                let (_, oid, mut extension) = read_der(extension, DER_OID)?;
+                if oid != SGX_EXTENSION_OID {
+                    continue;
+                }
+                if extension.first() == Some(&DER_BOOLEAN) {
+                    extension = read_der(extension, DER_BOOLEAN)?.2;
+                }
+                let (_, value, _) = read_der(extension, DER_OCTET_STRING)?;
+                let (_, entries, _) = read_der(value, DER_SEQUENCE)?;
+                return Self::parse_sgx_extension(entries);
+            }
+            Err(Error::invalid_dcap_quote(
+                "the PCK certificate has no SGX extension".into(),
+            ))
+        }
+
+        fn parse_sgx_extension(entries: &[u8]) -> Result<Self, Error> {
+            let mut tcb = Self {
+                fmspc: vec![],
+                pceid: vec![],
+                components: [0; SGX_TCB_COMPONENTS],
+                pcesvn: 0,
+            };
+            for (suffix, value) in sgx_entries(entries)? {
+                match suffix {
+                    [2] => {
+                        let (_, components, _) = read_der(value, DER_SEQUENCE)?;
+                        for (suffix, value) in sgx_entries(components)? {
+                            match suffix {
+                                [2, n @ 1..=16] => {
+                                    tcb.components[*n as usize - 1] = read_der_u64(value)?
+                                }
+                                [2, 17] => tcb.pcesvn = read_der_u64(value)?,
+                                _ => {}
+                            }
+                        }
+                    }
+                    [3] => tcb.pceid = read_der(value, DER_OCTET_STRING)?.1.to_vec(),
+                    [4] => tcb.fmspc = read_der(value, DER_OCTET_STRING)?.1.to_vec(),
+                    _ => {}
+                }
+            }
+            if tcb.fmspc.is_empty() || tcb.pceid.is_empty() {
+                return Err(Error::invalid_dcap_quote(
+                    "the SGX extension has no FMSPC or PCEID".into(),
+                ));
+            }
+            Ok(tcb)
+        }
+    }
+
+    /// Returns the entries of a sequence in the SGX extension as the pairs of the OID suffix and the value
+    fn sgx_entries(mut bz: &[u8]) -> Result<Vec<(&[u8], &[u8])>, Error> {
+        let mut entries = Vec::new();
+        while !bz.is_empty() {
+            let (_, entry, rest) = read_der(bz, DER_SEQUENCE)?;
+            bz = rest;
+            let (_, oid, value) = read_der(entry, DER_OID)?;
+            let suffix = oid.strip_prefix(SGX_EXTENSION_OID).ok_or_else(|| {
+                Error::invalid_dcap_quote("unexpected OID in the SGX extension".into())
+            })?;
+            entries.push((suffix, value));
+        }
+        Ok(entries)
+    }
+
+    fn check_tcb_info(tcb_info: &Value, pck: &PckTcb) -> Result<(), Error> {
+        if hex_field(tcb_info, "fmspc")? != pck.fmspc || hex_field(tcb_info, "pceId")? != pck.pceid
+        {
+            return Err(Error::invalid_dcap_collateral(
+                "the TCB info is not for the platform of the PCK certificate".into(),
+            ));
+        }
+        for level in array_field(tcb_info, "tcbLevels")? {
+            let tcb = level
+                .get("tcb")
+                .ok_or_else(|| Error::invalid_dcap_collateral("TCB level has no tcb".into()))?;
+            let components = tcb_components(tcb)?;
+            let satisfied = pck
+                .components
+                .iter()
+                .zip(components.iter())
+                .all(|(pck, level)| pck >= level)
+                && pck.pcesvn >= u64_field(tcb, "pcesvn")?;
+            if satisfied {
+                return check_tcb_status("platform", level);
+            }
+        }
+        Err(Error::unacceptable_tcb_status(
+            "platform".into(),
+            "no TCB level matches the PCK certificate".into(),
+        ))
+    }
+
+    /// Returns the SVNs of the TCB components, which are in an array since the version 3 of the TCB info
+    fn tcb_components(tcb: &Value) -> Result<[u64; SGX_TCB_COMPONENTS], Error> {
+        let mut components = [0; SGX_TCB_COMPONENTS];
+        for (i, component) in components.iter_mut().enumerate() {
+            *component = match tcb.get("sgxtcbcomponents") {
+                Some(Value::Array(array)) => array.get(i).map(|c| u64_field(c, "svn")),
+                _ => Some(u64_field(tcb, &format!("sgxtcbcomp{:02}svn", i + 1))),
+            }
+            .ok_or_else(|| {
+                Error::invalid_dcap_collateral("TCB level has too few components".into())
+            })??;
+        }
+        Ok(components)
+    }
+
+    fn check_qe_identity(identity: &Value, qe: &sgx_report_body_t) -> Result<(), Error> {
+        let mismatch = |field: &str| {
+            Error::invalid_dcap_quote(format!("the QE doesn't match its identity: {}", field))
+        };
+        let miscselect = be_u32(&hex_field(identity, "miscselect")?)?;
+        let miscselect_mask = be_u32(&hex_field(identity, "miscselectMask")?)?;
+        if qe.misc_select & miscselect_mask != miscselect {
+            return Err(mismatch("miscselect"));
+        }
+        let attributes = hex_field(identity, "attributes")?;
+        let attributes_mask = hex_field(identity, "attributesMask")?;
+        let qe_attributes = [
+            qe.attributes.flags.to_le_bytes(),
+            qe.attributes.xfrm.to_le_bytes(),
+        ]
+        .concat();
+        if attributes.len() != qe_attributes.len()
+            || attributes_mask.len() != qe_attributes.len()
+            || qe_attributes
+                .iter()
+                .zip(attributes_mask.iter())
+                .zip(attributes.iter())
+                .any(|((a, mask), expected)| a & mask != *expected)
+        {
+            return Err(mismatch("attributes"));
+        }
+        if hex_field(identity, "mrsigner")? != qe.mr_signer.m {
+            return Err(mismatch("mrsigner"));
+        }
+        if u64_field(identity, "isvprodid")? != qe.isv_prod_id as u64 {
+            return Err(mismatch("isvprodid"));
+        }
+        for level in array_field(identity, "tcbLevels")? {
+            let isvsvn = level
+                .get("tcb")
+                .map(|tcb| u64_field(tcb, "isvsvn"))
+                .ok_or_else(|| Error::invalid_dcap_collateral("TCB level has no tcb".into()))??;
+            if qe.isv_svn as u64 >= isvsvn {
+                return check_tcb_status("QE", level);
+            }
+        }
+        Err(Error::unacceptable_tcb_status(
+            "QE".into(),
+            "no TCB level matches the QE".into(),
+        ))
+    }
+
+    fn check_tcb_status(target: &str, level: &Value) -> Result<(), Error> {
+        let status = level
+            .get("tcbStatus")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::invalid_dcap_collateral("TCB level has no status".into()))?;
+        if ACCEPTED_TCB_STATUSES.contains(&status) {
+            Ok(())
+        } else {
+            Err(Error::unacceptable_tcb_status(target.into(), status.into()))
+        }
+    }
+
+    fn hex_field(value: &Value, field: &str) -> Result<Vec<u8>, Error> {
+        let hex = value
+            .get(field)
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::invalid_dcap_collateral(format!("`{}` is not found", field)))?;
+        hex::decode(hex)
+            .map_err(|e| Error::invalid_dcap_collateral(format!("invalid `{}`: {}", field, e)))
+    }
+
+    fn u64_field(value: &Value, field: &str) -> Result<u64, Error> {
+        value
+            .get(field)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Error::invalid_dcap_collateral(format!("`{}` is not found", field)))
+    }
+
+    fn array_field<'a>(value: &'a Value, field: &str) -> Result<&'a Vec<Value>, Error> {
+        value
+            .get(field)
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::invalid_dcap_collateral(format!("`{}` is not found", field)))
+    }
+
+    fn be_u32(bz: &[u8]) -> Result<u32, Error> {
+        bz.try_into()
+            .map(u32::from_be_bytes)
+            .map_err(|_| Error::invalid_dcap_collateral("expected 4 bytes".into()))
+    }
+
+    /// Verifies the signature of the JSON collateral and its freshness, and returns the signed body
+    fn verify_signed_json(json: &str, key: &str, signer: &[u8], now: i64) -> Result<Value, Error> {
+        let (body, signature) = split_signed_json(json, key)?;
+        verify_signature(signer, body.as_bytes(), &ecdsa_raw_to_der(&signature)?)?;
+        let value: Value = serde_json::from_str(body).map_err(Error::serde_json)?;
+        let next_update = value
+            .get("nextUpdate")
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                Error::invalid_dcap_collateral(format!("`{}` has no nextUpdate", key))
+            })?;
+        let next_update = DateTime::parse_from_rfc3339(next_update)
+            .map_err(|e| Error::invalid_dcap_collateral(format!("invalid nextUpdate: {}", e)))?;
+        if next_update.timestamp() < now {
+            return Err(Error::invalid_dcap_collateral(format!(
+                "`{}` is outdated: next_update={}",
+                key, next_update
+            )));
+        }
+        Ok(value)
+    }
+
+    fn verify_attestation_key_signature(
+        attestation_key: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Error> {
+        let key = VerifyingKey::from_sec1_bytes(&[&[0x04], attestation_key].concat())
+            .map_err(|e| Error::invalid_dcap_quote(format!("invalid attestation key: {}", e)))?;
+        let signature = Signature::from_slice(signature)
+            .map_err(|e| Error::invalid_dcap_quote(format!("invalid quote signature: {}", e)))?;
+        key.verify(msg, &signature).map_err(|_| {
+            Error::invalid_dcap_quote("the attestation key didn't sign the quote".into())
+        })
+    }
+
+    /// Verifies that the PEM chain (leaf first) terminates at the trusted root and returns the DER certificates
+    fn verify_chain(
+        chain: &str,
+        trusted_root: &[u8],
+        root_crl: &[&[u8]],
+        now: i64,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let certs: Vec<Vec<u8>> = pem::parse_many(chain)
+            .map_err(|e| Error::invalid_dcap_collateral(format!("invalid PEM chain: {}", e)))?
+            .into_iter()
+            .map(|p| p.contents().to_vec())
+            .collect();
+        if certs.len() < 2 {
+            return Err(Error::invalid_dcap_collateral(
+                "the chain must have an issuer and the root".into(),
+            ));
+        }
+        if certs.last().unwrap() != trusted_root {
+            return Err(Error::untrusted_dcap_root());
+        }
+        for pair in certs.windows(2) {
+            verify_signed_der(&pair[0], &pair[1])?;
+            let tbs = parse_tbs(&pair[0])?;
+            if now < tbs.not_before || tbs.not_after < now {
+                return Err(Error::invalid_dcap_collateral(
+                    "a certificate in the chain is not valid at the current time".into(),
+                ));
+            }
+        }
+        // the CRL of the root lists the certificates that the root issued
+        check_not_revoked(&certs[certs.len() - 2], root_crl)?;
+        Ok(certs)
+    }
+
+    /// Verifies the signature and the freshness of the CRL, and returns the serial numbers of the revoked certificates
+    fn verify_crl<'a>(crl: &'a [u8], issuer: &[u8], now: i64) -> Result<Vec<&'a [u8]>, Error> {
+        verify_signed_der(crl, issuer)?;
+        let (_, contents, _) = read_der(crl, DER_SEQUENCE)?;
+        let (_, mut tbs, _) = read_der(contents, DER_SEQUENCE)?;
+        if tbs.first() == Some(&DER_INTEGER) {
+            tbs = read_der(tbs, DER_INTEGER)?.2;
+        }
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (_, tbs) = read_time(tbs)?;
+        let (next_update, tbs) = read_time(tbs)?;
+        if next_update < now {
+            return Err(Error::invalid_dcap_collateral(format!(
+                "the CRL is outdated: next_update={}",
+                next_update
+            )));
+        }
+        let mut revoked = Vec::new();
+        if tbs.first() == Some(&DER_SEQUENCE) {
+            let (_, mut entries, _) = read_der(tbs, DER_SEQUENCE)?;
+            while !entries.is_empty() {
+                let (_, entry, rest) = read_der(entries, DER_SEQUENCE)?;
+                entries = rest;
+                revoked.push(read_der(entry, DER_INTEGER)?.1);
+            }
+        }
+        Ok(revoked)
+    }
+
+    fn check_not_revoked(cert: &[u8], revoked: &[&[u8]]) -> Result<(), Error> {
+        let serial = parse_tbs(cert)?.serial;
+        if revoked.contains(&serial) {
+            return Err(Error::revoked_dcap_certificate(hex::encode(serial)));
+        }
+        Ok(())
+    }
+
+    /// TbsCertificate has the fields of a certificate that the verification uses
+    struct TbsCertificate<'a> {
+        serial: &'a [u8],
+        not_before: i64,
+        not_after: i64,
+        extensions: Option<&'a [u8]>,
+    }
+
+    fn parse_tbs(cert: &[u8]) -> Result<TbsCertificate, Error> {
+        let (_, contents, _) = read_der(cert, DER_SEQUENCE)?;
+        let (_, mut tbs, _) = read_der(contents, DER_SEQUENCE)?;
+        if tbs.first() == Some(&DER_CONTEXT_0) {
+            tbs = read_der(tbs, DER_CONTEXT_0)?.2;
+        }
+        let (_, serial, tbs) = read_der(tbs, DER_INTEGER)?;
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (_, validity, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (not_before, validity) = read_time(validity)?;
+        let (not_after, _) = read_time(validity)?;
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let (_, _, tbs) = read_der(tbs, DER_SEQUENCE)?;
+        let extensions = if tbs.first() == Some(&DER_CONTEXT_3) {
+            let (_, extensions, _) = read_der(tbs, DER_CONTEXT_3)?;
+            Some(read_der(extensions, DER_SEQUENCE)?.1)
+        } else {
+            None
+        };
+        Ok(TbsCertificate {
+            serial,
+            not_before,
+            not_after,
+            extensions,
+        })
+    }
+
+    /// Reads an UTCTime or a GeneralizedTime in the form of `YYMMDDHHMMSSZ` or `YYYYMMDDHHMMSSZ` as UNIX seconds
+    fn read_time(bz: &[u8]) -> Result<(i64, &[u8]), Error> {
+        let (tag, year_digits) = match bz.first() {
+            Some(&DER_UTC_TIME) => (DER_UTC_TIME, 2),
+            Some(&DER_GENERALIZED_TIME) => (DER_GENERALIZED_TIME, 4),
+            _ => return Err(Error::invalid_dcap_collateral("malformed time".into())),
+        };
+        let (_, time, rest) = read_der(bz, tag)?;
+        let digits = time
+            .strip_suffix(b"Z")
+            .filter(|d| d.len() == year_digits + 10 && d.iter().all(u8::is_ascii_digit))
+            .ok_or_else(|| Error::invalid_dcap_collateral("malformed time".into()))?;
+        let num = |range: core::ops::Range<usize>| {
+            digits[range]
+                .iter()
+                .fold(0i64, |acc, d| acc * 10 + (d - b'0') as i64)
+        };
+        let mut year = num(0..year_digits);
+        if year_digits == 2 {
+            year += if year < 50 { 2000 } else { 1900 };
+        }
+        let d = year_digits;
+        let days = days_from_civil(year, num(d..d + 2), num(d + 2..d + 4));
+        Ok((
+            days * 86400 + num(d + 4..d + 6) * 3600 + num(d + 6..d + 8) * 60 + num(d + 8..d + 10),
+            rest,
+        ))
+    }
+
+    /// Returns the number of days since 1970-01-01 of the date in the proleptic Gregorian calendar
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    fn read_der_u64(bz: &[u8]) -> Result<u64, Error> {
+        let (_, int, _) = read_der(bz, DER_INTEGER)?;
+        let int = int.strip_prefix(&[0]).unwrap_or(int);
+        if int.len() > 8 {
+            return Err(Error::invalid_dcap_quote("integer is too large".into()));
+        }
+        Ok(int.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+    }
+
+    /// Verifies a signed DER object (a certificate or a CRL): `SEQUENCE { tbs, algorithm, BIT STRING signature }`
+    fn verify_signed_der(signed: &[u8], issuer: &[u8]) -> Result<(), Error> {
+        let (_, contents, _) = read_der(signed, DER_SEQUENCE)?;
+        let (tbs, _, rest) = read_der(contents, DER_SEQUENCE)?;
+        let (_, _, rest) = read_der(rest, DER_SEQUENCE)?;
+        let (_, signature, _) = read_der(rest, DER_BIT_STRING)?;
+        // the first byte of a BIT STRING is the number of unused bits
+        match signature.split_first() {
+            Some((0, signature)) => verify_signature(issuer, tbs, signature),
+            _ => Err(Error::invalid_dcap_collateral(
+                "unexpected signature encoding".into(),
+            )),
+        }
+    }
+
+    fn verify_signature(issuer: &[u8], msg: &[u8], signature: &[u8]) -> Result<(), Error> {
+        webpki::EndEntityCert::from(issuer)
+            .map_err(|e| Error::web_pki(e.to_string()))?
+            .verify_signature(&webpki::ECDSA_P256_SHA256, msg, signature)
+            .map_err(|e| Error::web_pki(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_signed_json() {
+        let json =
+            r#"{"tcbInfo":{"version":3,"nextUpdate":"2030-01-01T00:00:00Z"},"signature":"0a0b"}"#;
+        let (body, signature) = split_signed_json(json, "tcbInfo").unwrap();
+        assert_eq!(body, r#"{"version":3,"nextUpdate":"2030-01-01T00:00:00Z"}"#);
+        assert_eq!(signature, vec![0x0a, 0x0b]);
+        assert!(split_signed_json(json, "enclaveIdentity").is_err());
+        assert!(split_signed_json(r#"{"tcbInfo":{}}"#, "tcbInfo").is_err());
+    }
+
+    #[test]
+    fn test_ecdsa_raw_to_der() {
+        let mut raw = [0u8; 64];
+        raw[31] = 0x01;
+        raw[32] = 0x80;
+        let der = ecdsa_raw_to_der(&raw).unwrap();
+        let (_, contents, rest) = read_der(&der, 0x30).unwrap();
+        assert!(rest.is_empty());
+        let (_, r, contents) = read_der(contents, 0x02).unwrap();
+        let (_, s, contents) = read_der(contents, 0x02).unwrap();
+        assert!(contents.is_empty());
+        assert_eq!(r, &[0x01]);
+        assert_eq!(s.len(), 33);
+        assert_eq!(s[..2], [0x00, 0x80]);
+        assert!(ecdsa_raw_to_der(&raw[..63]).is_err());
+    }
+
+    #[test]
+    fn test_read_der_long_form() {
+        let mut bz = vec![0x30, 0x81, 0x80];
+        bz.extend([0u8; 0x80]);
+        bz.push(0xff);
+        let (element, contents, rest) = read_der(&bz, 0x30).unwrap();
+        assert_eq!(element.len(), 0x83);
+        assert_eq!(contents.len(), 0x80);
+        assert_eq!(rest, &[0xff]);
+        assert!(read_der(&bz[..10], 0x30).is_err());
+        assert!(read_der(&bz, 0x02).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    mod verification {
+        use super::super::verification::verify_dcap_quote_with_root;
+        use super::super::*;
+        use lcp_types::Time;
+
+        // generated by testdata/dcap/generate.py with a test root instead of the Intel SGX Root CA
+        const ROOT_CA: &[u8] = include_bytes!("../testdata/dcap/root_ca.der");
+
+        fn bundle() -> DCAPQuoteBundle {
+            DCAPQuoteBundle {
+                quote: include_bytes!("../testdata/dcap/quote.bin").to_vec(),
+                collateral: DCAPCollateral {
+                    tcb_info: include_str!("../testdata/dcap/tcb_info.json").into(),
+                    tcb_info_issuer_chain: include_str!(
+                        "../testdata/dcap/tcb_info_issuer_chain.pem"
+                    )
+                    .into(),
+                    qe_identity: include_str!("../testdata/dcap/qe_identity.json").into(),
+                    qe_identity_issuer_chain: include_str!(
+                        "../testdata/dcap/qe_identity_issuer_chain.pem"
+                    )
+                    .into(),
+                    pck_crl: include_bytes!("../testdata/dcap/pck_crl.der").to_vec(),
+                    pck_crl_issuer_chain: include_str!("../testdata/dcap/pck_crl_issuer_chain.pem")
+                        .into(),
+                    root_ca_crl: include_bytes!("../testdata/dcap/root_ca_crl.der").to_vec(),
+                },
+            }
+        }
+
+        fn now() -> Time {
+            // 2030-01-01T00:00:00Z
+            Time::from_unix_timestamp_nanos(1893456000 * 1_000_000_000).unwrap()
+        }
+
+        fn verify(bundle: &DCAPQuoteBundle) -> Result<[u8; 32], Error> {
+            verify_dcap_quote_with_root(now(), bundle, ROOT_CA)
+        }
+
+        #[test]
+        fn test_verify_dcap_quote() {
+            assert!(verify(&bundle()).is_ok());
+            assert_eq!(
+                bundle().get_enclave_key_address().unwrap(),
+                Address([1u8; 20])
+            );
+        }
+
+        #[test]
+        fn test_match_metadata() {
+            let bundle = bundle();
+            let mut metadata: metadata_t = unsafe { core::mem::zeroed() };
+            metadata.enclave_css.body.enclave_hash = bundle.report_body().unwrap().mr_enclave;
+            assert!(bundle.match_metadata(&metadata).is_ok());
+            metadata.enclave_css.body.enclave_hash.m[0] ^= 0xff;
+            assert!(bundle.match_metadata(&metadata).is_err());
+        }
+
+        #[test]
+        fn test_untrusted_root() {
+            // the chains of the test PKI don't terminate at the pinned Intel SGX Root CA
+            assert!(verify_dcap_quote(now(), &bundle()).is_err());
+        }
+
+        #[test]
+        fn test_invalid_quote_signature() {
+            let mut bundle = bundle();
+            // the report data of the ISV enclave report
+            bundle.quote[QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE - 1] ^= 1;
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_invalid_qe_report_signature() {
+            let mut bundle = bundle();
+            // the QE report follows the ISV signature and the attestation key
+            let qe_report_offset = QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE + 4 + 128;
+            bundle.quote[qe_report_offset + 300] ^= 1;
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_invalid_pck_chain() {
+            let mut bundle = bundle();
+            // replace the PCK certificate chain with a chain that doesn't include the PCK certificate
+            let chain = bundle.collateral.pck_crl_issuer_chain.clone();
+            let start = bundle.quote.windows(5).position(|w| w == b"-----").unwrap();
+            bundle.quote.truncate(start);
+            bundle.quote.extend(chain.as_bytes());
+            let sig_data_offset = QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE;
+            let sig_data_len = (bundle.quote.len() - sig_data_offset - 4) as u32;
+            bundle.quote[sig_data_offset..sig_data_offset + 4]
+                .copy_from_slice(&sig_data_len.to_le_bytes());
+            bundle.quote[start - 4..start].copy_from_slice(&(chain.len() as u32).to_le_bytes());
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_revoked_pck_certificate() {
+            let mut bundle = bundle();
+            bundle.collateral.pck_crl =
+                include_bytes!("../testdata/dcap/pck_crl_revoked.der").to_vec();
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_revoked_pck_ca() {
+            let mut bundle = bundle();
+            bundle.collateral.root_ca_crl =
+                include_bytes!("../testdata/dcap/root_ca_crl_revoked.der").to_vec();
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_crl_signed_by_another_issuer() {
+            let mut bundle = bundle();
+            bundle.collateral.pck_crl = bundle.collateral.root_ca_crl.clone();
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_out_of_date_tcb() {
+            let mut bundle = bundle();
+            bundle.collateral.tcb_info =
+                include_str!("../testdata/dcap/tcb_info_out_of_date.json").into();
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_tampered_tcb_info() {
+            let mut bundle = bundle();
+            bundle.collateral.tcb_info =
+                bundle
+                    .collateral
+                    .tcb_info
+                    .replacen("\"tcbType\":0", "\"tcbType\":1", 1);
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_qe_identity_mismatch() {
+            let mut bundle = bundle();
+            bundle.collateral.qe_identity =
+                include_str!("../testdata/dcap/qe_identity_other_signer.json").into();
+            assert!(verify(&bundle).is_err());
+        }
+
+        #[test]
+        fn test_outdated_collateral() {
+            // after the nextUpdate of the collateral and the CRLs
+            let later = Time::from_unix_timestamp_nanos(2240611200 * 1_000_000_000).unwrap();
+            assert!(verify_dcap_quote_with_root(later, &bundle(), ROOT_CA).is_err());
+        }
+    }
+
+    #[test]
+    fn test_report_body() {
+        let mut bundle = DCAPQuoteBundle {
+            quote: vec![0u8; QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE],
+            ..Default::default()
+        };
+        assert!(bundle.report_body().is_err());
+        bundle.quote[0] = QUOTE_VERSION_3 as u8;
+        bundle.quote[2] = ATT_KEY_TYPE_ECDSA_P256 as u8;
+        // report_data is located at the end of the report body
+        let report_data_offset = QUOTE_HEADER_SIZE + QUOTE_REPORT_BODY_SIZE - 64;
+        bundle.quote[report_data_offset..report_data_offset + 20].copy_from_slice(&[1u8; 20]);
+        assert_eq!(
+            bundle.get_enclave_key_address().unwrap(),
+            Address([1u8; 20])
+        );
+        bundle.quote.pop();
+        assert!(bundle.report_body().is_err());
+    }
+}
@@ -31,6 +31,50 @@ define_error! {
             format_args!("Mrenclave mismatch error: expected={} actual={}", e.expected, e.actual)
         },
 
+        InvalidSgxStructure
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid SGX structure: descr={}", e.descr)
+        },
+
+        InvalidDcapQuote
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid DCAP quote: descr={}", e.descr)
+        },
+
+        InvalidDcapCollateral
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid DCAP collateral: descr={}", e.descr)
+        },
+
+        UntrustedDcapRoot
+        |_| { "the certificate chain doesn't terminate at the Intel SGX Root CA" },
+
+        RevokedDcapCertificate
+        {
+            serial: String
+        }
+        |e| {
+            format_args!("the certificate has been revoked: serial={}", e.serial)
+        },
+
+        UnacceptableTcbStatus
+        {
+            target: String,
+            status: String
+        }
+        |e| {
+            format_args!("the TCB status is not acceptable: target={} status={}", e.target, e.status)
+        },
+
         WebPki
         {
             descr: String
@@ -39,6 +83,48 @@ define_error! {
             format_args!("WebPKI error: descr={}", e.descr)
         },
 
+        AdvisoryNotAllowed
+        {
+            advisory_id: String
+        }
+        |e| {
+            format_args!("advisory is not allowed by the policy: advisory_id={}", e.advisory_id)
+        },
+
+        AdvisoryExpired
+        {
+            advisory_id: String,
+            allowed_until: u64
+        }
+        |e| {
+            format_args!("advisory is no longer allowed by the policy: advisory_id={} allowed_until={}", e.advisory_id, e.allowed_until)
+        },
+
+        MissingMitigations
+        {
+            advisory_id: String,
+            missing: Vec<String>
+        }
+        |e| {
+            format_args!("advisory requires mitigations that are not applied: advisory_id={} missing={:?}", e.advisory_id, e.missing)
+        },
+
+        InvalidIasResponse
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid response from IAS: descr={}", e.descr)
+        },
+
+        UnexpectedIasStatus
+        {
+            code: u16
+        }
+        |e| {
+            format_args!("unexpected status of the response from IAS: code={}", e.code)
+        },
+
         SerdeJson
         [TraceError<serde_json::Error>]
         |_| { "serde_json error" },
@@ -62,3 +148,15 @@ impl From<crypto::Error> for Error {
         Self::crypto_error(value)
     }
 }
+
+impl Error {
+    /// Returns true if the report was rejected by the advisory policy rather than being invalid
+    pub fn is_policy_violation(&self) -> bool {
+        matches!(
+            self.detail(),
+            ErrorDetail::AdvisoryNotAllowed(_)
+                | ErrorDetail::AdvisoryExpired(_)
+                | ErrorDetail::MissingMitigations(_)
+        )
+    }
+}
@@ -0,0 +1,185 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use crate::EndorsedAttestationVerificationReport;
+use core::str;
+
+/// The maximum number of headers in a response from IAS
+const MAX_HEADERS: usize = 16;
+
+/// Parse the HTTP response of the IAS `report` API into an endorsed report
+///
+/// The response comes from outside the enclave, so any malformed input results in an error instead of a panic.
+pub fn parse_ias_report_response(
+    resp: &[u8],
+) -> Result<EndorsedAttestationVerificationReport, Error> {
+    let (header_len, headers) = parse_response(resp)?;
+
+    let mut signature = None;
+    let mut signing_cert = None;
+    for (name, value) in headers.iter() {
+        if name.eq_ignore_ascii_case("X-IASReport-Signature") {
+            signature = Some(base64::decode(value).map_err(Error::base64)?);
+        } else if name.eq_ignore_ascii_case("X-IASReport-Signing-Certificate") {
+            signing_cert = Some(parse_signing_cert(value)?);
+        }
+    }
+    let avr = str::from_utf8(&resp[header_len..])
+        .map_err(|e| Error::invalid_ias_response(format!("the report is not UTF-8: {}", e)))?
+        .to_string();
+    if avr.is_empty() {
+        return Err(Error::invalid_ias_response("the report is empty".into()));
+    }
+    Ok(EndorsedAttestationVerificationReport {
+        avr,
+        signature: signature
+            .ok_or_else(|| Error::invalid_ias_response("missing the report signature".into()))?,
+        signing_cert: signing_cert
+            .ok_or_else(|| Error::invalid_ias_response("missing the signing certificate".into()))?,
+    })
+}
+
+/// Parse the HTTP response of the IAS `sigrl` API into the signature revocation list
+///
+/// An empty body means that the EPID group has no revoked signatures.
+pub fn parse_ias_sigrl_response(resp: &[u8]) -> Result<Vec<u8>, Error> {
+    let (header_len, _) = parse_response(resp)?;
+    let body = str::from_utf8(&resp[header_len..])
+        .map_err(|e| Error::invalid_ias_response(format!("the SigRL is not UTF-8: {}", e)))?;
+    if body.is_empty() {
+        return Ok(Vec::new());
+    }
+    base64::decode(body).map_err(Error::base64)
+}
+
+/// Returns the length of the header and the headers of a complete response with the status 200
+fn parse_response(resp: &[u8]) -> Result<(usize, Vec<(String, String)>), Error> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut parsed = httparse::Response::new(&mut headers);
+    let header_len = match parsed.parse(resp) {
+        Ok(httparse::Status::Complete(len)) => len,
+        Ok(httparse::Status::Partial) => {
+            return Err(Error::invalid_ias_response(
+                "the response is incomplete".into(),
+            ))
+        }
+        Err(e) => return Err(Error::invalid_ias_response(format!("{}", e))),
+    };
+    match parsed.code {
+        Some(200) => {}
+        Some(code) => return Err(Error::unexpected_ias_status(code)),
+        None => {
+            return Err(Error::invalid_ias_response(
+                "missing the status code".into(),
+            ))
+        }
+    }
+    let headers = parsed
+        .headers
+        .iter()
+        .map(|h| {
+            str::from_utf8(h.value)
+                .map(|v| (h.name.to_string(), v.to_string()))
+                .map_err(|e| {
+                    Error::invalid_ias_response(format!(
+                        "the header `{}` is not UTF-8: {}",
+                        h.name, e
+                    ))
+                })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((header_len, headers))
+}
+
+/// Returns the DER of the first certificate in the percent-encoded PEM chain
+fn parse_signing_cert(value: &str) -> Result<Vec<u8>, Error> {
+    let pem = percent_decode(&value.replace("%0A", ""))?;
+    let cert = pem.split("-----").nth(2).ok_or_else(|| {
+        Error::invalid_ias_response("the signing certificate is not a PEM chain".into())
+    })?;
+    base64::decode(cert).map_err(Error::base64)
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            out.push(b);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let decoded = match hex {
+            [Some(h), Some(l)] => str::from_utf8(&[h, l])
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok()),
+            _ => None,
+        };
+        out.push(decoded.ok_or_else(|| {
+            Error::invalid_ias_response("invalid percent-encoding in the header".into())
+        })?);
+    }
+    String::from_utf8(out)
+        .map_err(|e| Error::invalid_ias_response(format!("the decoded header is not UTF-8: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: &str, headers: &[(&str, &str)], body: &str) -> Vec<u8> {
+        let mut resp = format!("HTTP/1.1 {}\r\n", status);
+        for (name, value) in headers {
+            resp.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        resp.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+        resp.into_bytes()
+    }
+
+    #[test]
+    fn test_parse_ias_report_response() {
+        let cert = "-----BEGIN%20CERTIFICATE-----%0AAQID%0A-----END%20CERTIFICATE-----%0A";
+        let resp = response(
+            "200 OK",
+            &[
+                ("X-IASReport-Signature", "BAUG"),
+                ("X-IASReport-Signing-Certificate", cert),
+            ],
+            "{}",
+        );
+        let eavr = parse_ias_report_response(&resp).unwrap();
+        assert_eq!(eavr.avr, "{}");
+        assert_eq!(eavr.signature, vec![4, 5, 6]);
+        assert_eq!(eavr.signing_cert, vec![1, 2, 3]);
+
+        // missing signature
+        let resp = response("200 OK", &[("X-IASReport-Signing-Certificate", cert)], "{}");
+        assert!(parse_ias_report_response(&resp).is_err());
+        // error status
+        assert!(parse_ias_report_response(&response("401 Unauthorized", &[], "")).is_err());
+        // truncated response
+        assert!(parse_ias_report_response(b"HTTP/1.1 200 OK\r\nX-IAS").is_err());
+        // broken percent-encoding
+        let resp = response(
+            "200 OK",
+            &[
+                ("X-IASReport-Signature", "BAUG"),
+                ("X-IASReport-Signing-Certificate", "%"),
+            ],
+            "{}",
+        );
+        assert!(parse_ias_report_response(&resp).is_err());
+    }
+
+    #[test]
+    fn test_parse_ias_sigrl_response() {
+        assert_eq!(
+            parse_ias_sigrl_response(&response("200 OK", &[], "")).unwrap(),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            parse_ias_sigrl_response(&response("200 OK", &[], "AQID")).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(parse_ias_sigrl_response(&response("404 Not Found", &[], "")).is_err());
+    }
+}
@@ -22,10 +22,27 @@ mod prelude {
 pub use errors::Error;
 mod errors;
 
+#[cfg(any(feature = "std", feature = "sgx"))]
+pub use dcap::{verify_dcap_quote, INTEL_SGX_ROOT_CA_DER};
+pub use dcap::{DCAPCollateral, DCAPQuoteBundle};
+mod dcap;
+
+pub use ias::{parse_ias_report_response, parse_ias_sigrl_response};
+mod ias;
+
+pub use policy::{AdvisoryPolicy, AdvisoryRule};
+mod policy;
+
 pub use report::{AttestationVerificationReport, EndorsedAttestationVerificationReport, Quote};
 mod report;
 
+pub use sgx::{
+    encode_quote_body, encode_report_body, parse_quote_body, parse_report_body, QUOTE_BODY_SIZE,
+    REPORT_BODY_SIZE,
+};
+mod sgx;
+
 #[cfg(any(feature = "std", feature = "sgx"))]
-pub use verification::verify_report;
+pub use verification::{verify_report, verify_report_with_root_ca};
 #[cfg(any(feature = "std", feature = "sgx"))]
 mod verification;
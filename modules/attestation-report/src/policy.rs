@@ -0,0 +1,156 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use lcp_types::Time;
+use serde::{Deserialize, Serialize};
+
+/// AdvisoryPolicy decides which Intel security advisories are acceptable in an attestation report
+///
+/// A report is accepted only if every advisory ID in it has a rule in the policy and the rule is satisfied.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdvisoryPolicy {
+    /// Rules keyed by the advisory ID (e.g. `INTEL-SA-00334`)
+    #[serde(default)]
+    pub advisories: BTreeMap<String, AdvisoryRule>,
+}
+
+/// AdvisoryRule is the condition under which an advisory is accepted
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdvisoryRule {
+    /// The advisory is accepted until this UNIX time in seconds. If None, it is accepted indefinitely.
+    #[serde(default)]
+    pub allowed_until: Option<u64>,
+    /// Mitigations that the platform must have applied for the advisory to be accepted
+    #[serde(default)]
+    pub required_mitigations: Vec<String>,
+}
+
+impl AdvisoryRule {
+    /// Returns true if the rule still accepts the advisory at `now`
+    pub fn is_active(&self, now: Time) -> bool {
+        self.allowed_until
+            .map_or(true, |until| now.as_unix_timestamp_secs() <= until)
+    }
+}
+
+impl AdvisoryPolicy {
+    /// Build a policy that accepts the advisories unconditionally, which is equivalent to a flat allowlist
+    pub fn from_allowed_ids<I: IntoIterator<Item = String>>(advisory_ids: I) -> Self {
+        Self {
+            advisories: advisory_ids
+                .into_iter()
+                .map(|id| (id, AdvisoryRule::default()))
+                .collect(),
+        }
+    }
+
+    /// Add the rules of `other` to the policy
+    ///
+    /// A rule in `other` replaces the rule of the same advisory in the policy.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.advisories.extend(other.advisories);
+        self
+    }
+
+    /// Returns the rules that still accept their advisories at `now`
+    pub fn effective_at(&self, now: Time) -> Self {
+        Self {
+            advisories: self
+                .advisories
+                .iter()
+                .filter(|(_, rule)| rule.is_active(now))
+                .map(|(id, rule)| (id.clone(), rule.clone()))
+                .collect(),
+        }
+    }
+
+    /// Evaluate the advisory IDs of a report at `now`
+    ///
+    /// `applied_mitigations` are the mitigations that the operator declares to have applied to the platform.
+    pub fn evaluate(
+        &self,
+        advisory_ids: &[String],
+        now: Time,
+        applied_mitigations: &[String],
+    ) -> Result<(), Error> {
+        for id in advisory_ids {
+            let rule = self
+                .advisories
+                .get(id)
+                .ok_or_else(|| Error::advisory_not_allowed(id.clone()))?;
+            if let Some(until) = rule.allowed_until {
+                if now.as_unix_timestamp_secs() > until {
+                    return Err(Error::advisory_expired(id.clone(), until));
+                }
+            }
+            let missing: Vec<String> = rule
+                .required_mitigations
+                .iter()
+                .filter(|m| !applied_mitigations.contains(m))
+                .cloned()
+                .collect();
+            if !missing.is_empty() {
+                return Err(Error::missing_mitigations(id.clone(), missing));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AdvisoryPolicy {
+        serde_json::from_str(
+            r#"{
+                "advisories": {
+                    "INTEL-SA-00334": {},
+                    "INTEL-SA-00615": { "allowed_until": 1700000000 },
+                    "INTEL-SA-00219": { "required_mitigations": ["LVI"] }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_advisory_policy() {
+        let policy = policy();
+        let before = Time::from_unix_timestamp_nanos(1_600_000_000_000_000_000).unwrap();
+        let after = Time::from_unix_timestamp_nanos(1_800_000_000_000_000_000).unwrap();
+        let ids = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert!(policy.evaluate(&[], after, &[]).is_ok());
+        assert!(policy
+            .evaluate(&ids(&["INTEL-SA-00334", "INTEL-SA-00615"]), before, &[])
+            .is_ok());
+        assert!(policy
+            .evaluate(&ids(&["INTEL-SA-00001"]), before, &[])
+            .is_err());
+        assert!(policy
+            .evaluate(&ids(&["INTEL-SA-00615"]), after, &[])
+            .is_err());
+        assert!(policy
+            .evaluate(&ids(&["INTEL-SA-00219"]), before, &[])
+            .is_err());
+        assert!(policy
+            .evaluate(&ids(&["INTEL-SA-00219"]), before, &ids(&["LVI"]))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_effective_advisory_policy() {
+        let policy = policy();
+        let after = Time::from_unix_timestamp_nanos(1_800_000_000_000_000_000).unwrap();
+        let effective = policy.effective_at(after);
+        assert_eq!(effective.advisories.len(), 2);
+        assert!(!effective.advisories.contains_key("INTEL-SA-00615"));
+
+        let merged = AdvisoryPolicy::from_allowed_ids(["INTEL-SA-00615".to_string()]).merge(policy);
+        assert_eq!(
+            merged.advisories["INTEL-SA-00615"].allowed_until,
+            Some(1700000000)
+        );
+    }
+}
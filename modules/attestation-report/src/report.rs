@@ -1,5 +1,6 @@
 use crate::errors::Error;
 use crate::prelude::*;
+use crate::sgx::parse_quote_body;
 use chrono::prelude::DateTime;
 use core::fmt::Debug;
 use crypto::Address;
@@ -79,9 +80,8 @@ impl AttestationVerificationReport {
         }
 
         let quote = base64::decode(&self.isv_enclave_quote_body).map_err(Error::base64)?;
-        let sgx_quote: sgx_quote_t = unsafe { core::ptr::read(quote.as_ptr() as *const _) };
         Ok(Quote {
-            raw: sgx_quote,
+            raw: parse_quote_body(&quote)?,
             status: self.isv_enclave_quote_status.clone(),
             attestation_time: self.attestation_time()?,
         })
@@ -120,12 +120,9 @@ pub struct Quote {
 
 impl Quote {
     pub fn get_enclave_key_address(&self) -> Result<Address, Error> {
-        let data = self.raw.report_body.report_data.d;
-        if data.len() < 20 {
-            Err(Error::invalid_report_data_size(data.len()))
-        } else {
-            Ok(Address::try_from(&data[..20])?)
-        }
+        Ok(crypto::address_from_report_data(
+            &self.raw.report_body.report_data.d,
+        )?)
     }
 
     pub fn get_mrenclave(&self) -> sgx_measurement_t {
@@ -144,7 +141,7 @@ impl Quote {
     }
 }
 
-mod serde_base64 {
+pub(crate) mod serde_base64 {
     use super::*;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -0,0 +1,225 @@
+//! Typed parsers of the SGX structures contained in the quotes
+//!
+//! The structures are decoded field by field in little-endian as specified by Intel,
+//! instead of reinterpreting the bytes with the memory layout of the host,
+//! so that the parsing doesn't depend on the endianness and the alignment of the target.
+use crate::errors::Error;
+use crate::prelude::*;
+use sgx_types::{
+    sgx_attributes_t, sgx_basename_t, sgx_cpu_svn_t, sgx_measurement_t, sgx_quote_t,
+    sgx_report_body_t, sgx_report_data_t,
+};
+
+/// Size of `sgx_report_body_t`
+pub const REPORT_BODY_SIZE: usize = 384;
+/// Size of the EPID quote body, which excludes `signature_len` and the signature
+///
+/// IAS returns only this part of the quote as `isvEnclaveQuoteBody`.
+pub const QUOTE_BODY_SIZE: usize = 48 + REPORT_BODY_SIZE;
+
+/// Parses `sgx_report_body_t` from exactly `REPORT_BODY_SIZE` bytes
+pub fn parse_report_body(bz: &[u8]) -> Result<sgx_report_body_t, Error> {
+    if bz.len() != REPORT_BODY_SIZE {
+        return Err(Error::invalid_sgx_structure(format!(
+            "unexpected report body size: expected={} actual={}",
+            REPORT_BODY_SIZE,
+            bz.len()
+        )));
+    }
+    read_report_body(&mut Reader::new(bz))
+}
+
+/// Parses `sgx_quote_t` from an EPID quote
+///
+/// `signature_len` is read if the bytes contain it, otherwise it is set to 0. The signature itself is not parsed.
+pub fn parse_quote_body(bz: &[u8]) -> Result<sgx_quote_t, Error> {
+    if bz.len() < QUOTE_BODY_SIZE {
+        return Err(Error::invalid_sgx_structure(format!(
+            "quote body is too short: expected>={} actual={}",
+            QUOTE_BODY_SIZE,
+            bz.len()
+        )));
+    }
+    let mut r = Reader::new(bz);
+    Ok(sgx_quote_t {
+        version: r.u16()?,
+        sign_type: r.u16()?,
+        epid_group_id: r.array()?,
+        qe_svn: r.u16()?,
+        pce_svn: r.u16()?,
+        xeid: r.u32()?,
+        basename: sgx_basename_t { name: r.array()? },
+        report_body: read_report_body(&mut r)?,
+        signature_len: if r.remaining() >= 4 { r.u32()? } else { 0 },
+        ..Default::default()
+    })
+}
+
+/// Encodes `sgx_report_body_t` into `REPORT_BODY_SIZE` bytes
+pub fn encode_report_body(body: &sgx_report_body_t) -> Vec<u8> {
+    let mut w = Vec::with_capacity(REPORT_BODY_SIZE);
+    write_report_body(&mut w, body);
+    w
+}
+
+/// Encodes the body of `sgx_quote_t` into `QUOTE_BODY_SIZE` bytes as IAS returns it
+pub fn encode_quote_body(quote: &sgx_quote_t) -> Vec<u8> {
+    let mut w = Vec::with_capacity(QUOTE_BODY_SIZE);
+    w.extend_from_slice(&quote.version.to_le_bytes());
+    w.extend_from_slice(&quote.sign_type.to_le_bytes());
+    w.extend_from_slice(&quote.epid_group_id);
+    w.extend_from_slice(&quote.qe_svn.to_le_bytes());
+    w.extend_from_slice(&quote.pce_svn.to_le_bytes());
+    w.extend_from_slice(&quote.xeid.to_le_bytes());
+    w.extend_from_slice(&quote.basename.name);
+    write_report_body(&mut w, &quote.report_body);
+    w
+}
+
+fn read_report_body(r: &mut Reader) -> Result<sgx_report_body_t, Error> {
+    // the fields are read in the order of the struct definition
+    Ok(sgx_report_body_t {
+        cpu_svn: sgx_cpu_svn_t { svn: r.array()? },
+        misc_select: r.u32()?,
+        reserved1: r.array()?,
+        isv_ext_prod_id: r.array()?,
+        attributes: sgx_attributes_t {
+            flags: r.u64()?,
+            xfrm: r.u64()?,
+        },
+        mr_enclave: sgx_measurement_t { m: r.array()? },
+        reserved2: r.array()?,
+        mr_signer: sgx_measurement_t { m: r.array()? },
+        reserved3: r.array()?,
+        config_id: r.array()?,
+        isv_prod_id: r.u16()?,
+        isv_svn: r.u16()?,
+        config_svn: r.u16()?,
+        reserved4: r.array()?,
+        isv_family_id: r.array()?,
+        report_data: sgx_report_data_t { d: r.array()? },
+    })
+}
+
+fn write_report_body(w: &mut Vec<u8>, body: &sgx_report_body_t) {
+    w.extend_from_slice(&body.cpu_svn.svn);
+    w.extend_from_slice(&body.misc_select.to_le_bytes());
+    w.extend_from_slice(&body.reserved1);
+    w.extend_from_slice(&body.isv_ext_prod_id);
+    w.extend_from_slice(&body.attributes.flags.to_le_bytes());
+    w.extend_from_slice(&body.attributes.xfrm.to_le_bytes());
+    w.extend_from_slice(&body.mr_enclave.m);
+    w.extend_from_slice(&body.reserved2);
+    w.extend_from_slice(&body.mr_signer.m);
+    w.extend_from_slice(&body.reserved3);
+    w.extend_from_slice(&body.config_id);
+    w.extend_from_slice(&body.isv_prod_id.to_le_bytes());
+    w.extend_from_slice(&body.isv_svn.to_le_bytes());
+    w.extend_from_slice(&body.config_svn.to_le_bytes());
+    w.extend_from_slice(&body.reserved4);
+    w.extend_from_slice(&body.isv_family_id);
+    w.extend_from_slice(&body.report_data.d);
+}
+
+/// A cursor that reads little-endian values from bytes
+struct Reader<'a> {
+    bz: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bz: &'a [u8]) -> Self {
+        Self { bz, offset: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.bz.len() - self.offset
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let end = self.offset + N;
+        let bz = self.bz.get(self.offset..end).ok_or_else(|| {
+            Error::invalid_sgx_structure(format!(
+                "unexpected end of bytes: offset={} len={}",
+                self.offset,
+                self.bz.len()
+            ))
+        })?;
+        self.offset = end;
+        // the length of `bz` is always N here
+        let mut arr = [0u8; N];
+        arr.copy_from_slice(bz);
+        Ok(arr)
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy_quote() -> sgx_quote_t {
+        let mut quote = sgx_quote_t {
+            version: 2,
+            sign_type: 1,
+            epid_group_id: [1, 2, 3, 4],
+            qe_svn: 0x0102,
+            xeid: 0x01020304,
+            ..Default::default()
+        };
+        quote.report_body.misc_select = 0x05060708;
+        quote.report_body.attributes.flags = 0x0102030405060708;
+        quote.report_body.mr_enclave.m = [0xaa; 32];
+        quote.report_body.isv_svn = 3;
+        quote.report_body.report_data.d = [0xbb; 64];
+        quote
+    }
+
+    #[test]
+    fn test_quote_body_roundtrip() {
+        let quote = dummy_quote();
+        let bz = encode_quote_body(&quote);
+        assert_eq!(bz.len(), QUOTE_BODY_SIZE);
+        // the fields are encoded in little-endian at their offsets
+        assert_eq!(&bz[0..2], &[2, 0]);
+        assert_eq!(&bz[8..10], &[2, 1]);
+        assert_eq!(&bz[12..16], &[4, 3, 2, 1]);
+        assert_eq!(&bz[48 + 16..48 + 20], &[8, 7, 6, 5]);
+        assert_eq!(&bz[48 + 64..48 + 96], &[0xaa; 32]);
+        assert_eq!(&bz[QUOTE_BODY_SIZE - 64..], &[0xbb; 64]);
+
+        let parsed = parse_quote_body(&bz).unwrap();
+        assert_eq!(parsed.signature_len, 0);
+        assert_eq!(encode_quote_body(&parsed), bz);
+
+        // parsing doesn't require the bytes to be aligned
+        let mut unaligned = vec![0u8];
+        unaligned.extend_from_slice(&bz);
+        unaligned.extend_from_slice(&16u32.to_le_bytes());
+        let parsed = parse_quote_body(&unaligned[1..]).unwrap();
+        assert_eq!(parsed.signature_len, 16);
+        assert_eq!(parsed.report_body.mr_enclave.m, [0xaa; 32]);
+
+        assert!(parse_quote_body(&bz[..QUOTE_BODY_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_report_body_roundtrip() {
+        let body = dummy_quote().report_body;
+        let bz = encode_report_body(&body);
+        assert_eq!(bz.len(), REPORT_BODY_SIZE);
+        assert_eq!(encode_report_body(&parse_report_body(&bz).unwrap()), bz);
+        assert!(parse_report_body(&bz[1..]).is_err());
+    }
+}
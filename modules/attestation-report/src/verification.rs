@@ -28,6 +28,17 @@ static SUPPORTED_SIG_ALGS: SignatureAlgorithms = &[
 pub fn verify_report(
     current_timestamp: Time,
     report: &EndorsedAttestationVerificationReport,
+) -> Result<(), Error> {
+    verify_report_with_root_ca(current_timestamp, report, IAS_REPORT_CA)
+}
+
+/// Verify the report against the given PEM-encoded root CA instead of the one of Intel
+///
+/// This is for the reports issued by a mock IAS in tests and local development.
+pub fn verify_report_with_root_ca(
+    current_timestamp: Time,
+    report: &EndorsedAttestationVerificationReport,
+    root_ca_pem: &[u8],
 ) -> Result<(), Error> {
     let current_unix_timestamp = current_timestamp
         .duration_since(TmTime::unix_epoch())
@@ -40,8 +51,8 @@ pub fn verify_report(
         current_unix_timestamp.as_secs() + 1
     };
     let now = webpki::Time::from_seconds_since_unix_epoch(secs);
-    let root_ca_pem = pem::parse(IAS_REPORT_CA).expect("failed to parse pem bytes");
-    let root_ca = root_ca_pem.contents();
+    let root_ca = pem::parse(root_ca_pem).map_err(|e| Error::web_pki(e.to_string()))?;
+    let root_ca = root_ca.contents();
 
     let mut root_store = rustls::RootCertStore::empty();
     root_store
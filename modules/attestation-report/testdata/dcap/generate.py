@@ -0,0 +1,234 @@
+#!/usr/bin/env python3
+"""Generates a test PKI, collateral and quote in the layouts of Intel DCAP.
+
+The certificates are issued by a test root instead of the Intel SGX Root CA,
+so they are accepted only by the verification with an explicit trusted root.
+
+Usage: python3 generate.py (writes the files into this directory)
+"""
+import datetime
+import hashlib
+import json
+import os
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+from cryptography.x509.oid import NameOID
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+NOT_BEFORE = datetime.datetime(2024, 1, 1, tzinfo=datetime.timezone.utc)
+NOT_AFTER = datetime.datetime(2049, 12, 31, tzinfo=datetime.timezone.utc)
+NEXT_UPDATE = "2040-01-01T00:00:00Z"
+FMSPC = bytes.fromhex("00906ed50000")
+PCEID = bytes.fromhex("0000")
+PCK_CPUSVN_COMPS = [5, 5, 2, 2, 3, 1, 0, 3] + [0] * 8
+PCK_PCESVN = 11
+QE_MRSIGNER = bytes.fromhex("8c4f5775d796503e96137f77c68a829a0056ac8ded70140b081b094490c57bff")
+QE_ISVPRODID = 1
+QE_ISVSVN = 6
+# deterministic keys, so that the fixtures are reproducible except for the ECDSA nonces
+KEYS = {name: ec.derive_private_key(i + 1, ec.SECP256R1()) for i, name in enumerate(
+    ["root", "pck_ca", "pck", "tcb", "attestation"])}
+
+
+def name(cn):
+    return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+
+def cert(subject, subject_key, issuer, issuer_key, serial, ca, extensions=()):
+    builder = (
+        x509.CertificateBuilder()
+        .subject_name(name(subject))
+        .issuer_name(name(issuer))
+        .public_key(KEYS[subject_key].public_key())
+        .serial_number(serial)
+        .not_valid_before(NOT_BEFORE)
+        .not_valid_after(NOT_AFTER)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+    )
+    for ext in extensions:
+        builder = builder.add_extension(ext, critical=False)
+    return builder.sign(KEYS[issuer_key], hashes.SHA256())
+
+
+def crl(issuer, issuer_key, revoked_serials):
+    builder = (
+        x509.CertificateRevocationListBuilder()
+        .issuer_name(name(issuer))
+        .last_update(NOT_BEFORE)
+        .next_update(datetime.datetime(2040, 1, 1, tzinfo=datetime.timezone.utc))
+    )
+    for serial in revoked_serials:
+        builder = builder.add_revoked_certificate(
+            x509.RevokedCertificateBuilder()
+            .serial_number(serial)
+            .revocation_date(NOT_BEFORE)
+            .build()
+        )
+    return builder.sign(KEYS[issuer_key], hashes.SHA256()).public_bytes(serialization.Encoding.DER)
+
+
+def der(tag, contents):
+    n = len(contents)
+    if n < 0x80:
+        return bytes([tag, n]) + contents
+    length = n.to_bytes((n.bit_length() + 7) // 8, "big")
+    return bytes([tag, 0x80 | len(length)]) + length + contents
+
+
+def der_int(v):
+    bz = v.to_bytes(max(1, (v.bit_length() + 8) // 8), "big")
+    return der(0x02, bz)
+
+
+SGX_OID = bytes.fromhex("2a864886f84d010d01")
+
+
+def sgx_entry(suffix, value):
+    return der(0x30, der(0x06, SGX_OID + bytes(suffix)) + value)
+
+
+def sgx_extension():
+    tcb = b"".join(sgx_entry([2, i + 1], der_int(svn)) for i, svn in enumerate(PCK_CPUSVN_COMPS))
+    tcb += sgx_entry([2, 17], der_int(PCK_PCESVN))
+    tcb += sgx_entry([2, 18], der(0x04, bytes(PCK_CPUSVN_COMPS)))
+    value = der(0x30, b"".join([
+        sgx_entry([1], der(0x04, bytes(16))),
+        sgx_entry([2], der(0x30, tcb)),
+        sgx_entry([3], der(0x04, PCEID)),
+        sgx_entry([4], der(0x04, FMSPC)),
+    ]))
+    return x509.UnrecognizedExtension(x509.ObjectIdentifier("1.2.840.113741.1.13.1"), value)
+
+
+def raw_signature(key, msg):
+    r, s = decode_dss_signature(key.sign(msg, ec.ECDSA(hashes.SHA256())))
+    return r.to_bytes(32, "big") + s.to_bytes(32, "big")
+
+
+def pem(*certs):
+    return "".join(c.public_bytes(serialization.Encoding.PEM).decode() for c in certs)
+
+
+def signed_json(key, name, body):
+    body = json.dumps(body, separators=(",", ":"))
+    return '{"%s":%s,"signature":"%s"}' % (name, body, raw_signature(KEYS[key], body.encode()).hex())
+
+
+def tcb_info(status):
+    return signed_json("tcb", "tcbInfo", {
+        "id": "SGX",
+        "version": 3,
+        "issueDate": "2024-01-01T00:00:00Z",
+        "nextUpdate": NEXT_UPDATE,
+        "fmspc": FMSPC.hex(),
+        "pceId": PCEID.hex(),
+        "tcbType": 0,
+        "tcbEvaluationDataNumber": 16,
+        "tcbLevels": [
+            {
+                "tcb": {
+                    "sgxtcbcomponents": [{"svn": svn + 1} for svn in PCK_CPUSVN_COMPS],
+                    "pcesvn": PCK_PCESVN + 1,
+                },
+                "tcbDate": "2024-01-01T00:00:00Z",
+                "tcbStatus": "UpToDate",
+            },
+            {
+                "tcb": {
+                    "sgxtcbcomponents": [{"svn": svn} for svn in PCK_CPUSVN_COMPS],
+                    "pcesvn": PCK_PCESVN,
+                },
+                "tcbDate": "2023-01-01T00:00:00Z",
+                "tcbStatus": status,
+            },
+        ],
+    })
+
+
+def qe_identity(mrsigner):
+    return signed_json("tcb", "enclaveIdentity", {
+        "id": "QE",
+        "version": 2,
+        "issueDate": "2024-01-01T00:00:00Z",
+        "nextUpdate": NEXT_UPDATE,
+        "tcbEvaluationDataNumber": 16,
+        "miscselect": "00000000",
+        "miscselectMask": "FFFFFFFF",
+        "attributes": "11000000000000000000000000000000",
+        "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+        "mrsigner": mrsigner.hex().upper(),
+        "isvprodid": QE_ISVPRODID,
+        "tcbLevels": [
+            {"tcb": {"isvsvn": QE_ISVSVN}, "tcbDate": "2024-01-01T00:00:00Z", "tcbStatus": "UpToDate"},
+            {"tcb": {"isvsvn": 0}, "tcbDate": "2018-01-01T00:00:00Z", "tcbStatus": "OutOfDate"},
+        ],
+    })
+
+
+def report_body(mrsigner, isvprodid, isvsvn, report_data, attributes):
+    body = bytearray(384)
+    body[48:64] = attributes
+    body[128:160] = mrsigner
+    struct.pack_into("<HH", body, 256, isvprodid, isvsvn)
+    body[320:384] = report_data
+    return bytes(body)
+
+
+def quote(pck_chain_pem):
+    header = bytearray(48)
+    struct.pack_into("<HH", header, 0, 3, 2)
+    # the enclave key address in the report data
+    isv_report = report_body(bytes(32), 0, 0, bytes([1] * 20) + bytes(44), bytes(16))
+    attestation_key = KEYS["attestation"].public_key().public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint)[1:]
+    qe_auth_data = bytes(range(32))
+    qe_report_data = hashlib.sha256(attestation_key + qe_auth_data).digest() + bytes(32)
+    qe_report = report_body(QE_MRSIGNER, QE_ISVPRODID, QE_ISVSVN, qe_report_data,
+                            bytes.fromhex("11000000000000000700000000000000"))
+    signed = bytes(header) + isv_report
+    cert_data = pck_chain_pem.encode()
+    sig_data = (
+        raw_signature(KEYS["attestation"], signed)
+        + attestation_key
+        + qe_report
+        + raw_signature(KEYS["pck"], qe_report)
+        + struct.pack("<H", len(qe_auth_data)) + qe_auth_data
+        + struct.pack("<HI", 5, len(cert_data)) + cert_data
+    )
+    return signed + struct.pack("<I", len(sig_data)) + sig_data
+
+
+def write(path, data):
+    mode = "wb" if isinstance(data, bytes) else "w"
+    with open(os.path.join(OUT, path), mode) as f:
+        f.write(data)
+
+
+def main():
+    root = cert("Test SGX Root CA", "root", "Test SGX Root CA", "root", 1, True)
+    pck_ca = cert("Test SGX PCK Platform CA", "pck_ca", "Test SGX Root CA", "root", 2, True)
+    tcb = cert("Test SGX TCB Signing", "tcb", "Test SGX Root CA", "root", 3, False)
+    pck = cert("Test SGX PCK Certificate", "pck", "Test SGX PCK Platform CA", "pck_ca", 4, False,
+               [sgx_extension()])
+
+    write("root_ca.der", root.public_bytes(serialization.Encoding.DER))
+    write("quote.bin", quote(pem(pck, pck_ca, root)))
+    write("tcb_info.json", tcb_info("UpToDate"))
+    write("tcb_info_out_of_date.json", tcb_info("OutOfDate"))
+    write("tcb_info_issuer_chain.pem", pem(tcb, root))
+    write("qe_identity.json", qe_identity(QE_MRSIGNER))
+    write("qe_identity_other_signer.json", qe_identity(bytes(32)))
+    write("qe_identity_issuer_chain.pem", pem(tcb, root))
+    write("pck_crl.der", crl("Test SGX PCK Platform CA", "pck_ca", []))
+    write("pck_crl_revoked.der", crl("Test SGX PCK Platform CA", "pck_ca", [4]))
+    write("pck_crl_issuer_chain.pem", pem(pck_ca, root))
+    write("root_ca_crl.der", crl("Test SGX Root CA", "root", []))
+    write("root_ca_crl_revoked.der", crl("Test SGX Root CA", "root", [2]))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,79 @@
+use crate::endpoints::{Endpoints, RetryConfig};
+use crate::errors::Error;
+use crate::ChainRpc;
+use std::sync::Arc;
+use std::time::Duration;
+use tendermint::block::Height;
+use tendermint_rpc::endpoint::{commit, status};
+use tendermint_rpc::{Client, HttpClient};
+use tokio::runtime::Runtime;
+
+/// CometBftRpc is a `ChainRpc` over the CometBFT RPC endpoints of a chain
+///
+/// The requests block on `rt`, so they must not be made from an async context.
+pub struct CometBftRpc {
+    endpoints: Endpoints<HttpClient>,
+    rt: Arc<Runtime>,
+}
+
+impl CometBftRpc {
+    pub fn new(rpc_addrs: &[String], rt: Arc<Runtime>) -> Result<Self, Error> {
+        let endpoints = rpc_addrs
+            .iter()
+            .map(|addr| {
+                let client = HttpClient::new(addr.as_str())
+                    .map_err(|e| Error::invalid_address(addr.clone(), e))?;
+                Ok((addr.clone(), client))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            endpoints: Endpoints::new(endpoints)?,
+            rt,
+        })
+    }
+
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.endpoints = self.endpoints.with_retry_config(retry);
+        self
+    }
+
+    /// Space the requests to each endpoint by `min_interval`
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.endpoints = self.endpoints.with_min_interval(min_interval);
+        self
+    }
+}
+
+impl ChainRpc for CometBftRpc {
+    fn status(&self) -> Result<status::Response, Error> {
+        self.endpoints
+            .call("status", |client| self.rt.block_on(client.status()))
+    }
+
+    fn latest_commit(&self) -> Result<commit::Response, Error> {
+        self.endpoints.call("latest_commit", |client| {
+            self.rt.block_on(client.latest_commit())
+        })
+    }
+
+    fn commit(&self, height: u64) -> Result<commit::Response, Error> {
+        let height = Height::try_from(height).map_err(|e| Error::invalid_height(height, e))?;
+        self.endpoints
+            .call("commit", |client| self.rt.block_on(client.commit(height)))
+    }
+
+    fn abci_query(&self, path: &str, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let res = self.endpoints.call("abci_query", |client| {
+            self.rt
+                .block_on(client.abci_query(Some(path.to_string()), data.clone(), None, false))
+        })?;
+        if res.code.is_err() {
+            return Err(Error::abci_query(
+                path.to_string(),
+                res.code.value(),
+                res.log.to_string(),
+            ));
+        }
+        Ok(res.value)
+    }
+}
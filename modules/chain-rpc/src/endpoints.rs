@@ -0,0 +1,238 @@
+use crate::errors::Error;
+use log::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// RetryConfig is the policy of retrying the requests to the node that fail transiently
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// The number of retries after the first attempt
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Returns the duration to wait before the `attempt`-th retry, which doubles with each attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(self.max_backoff, |d| d.min(self.max_backoff))
+    }
+}
+
+/// Endpoints are the clients of the RPC endpoints of a chain that serve the requests in turn
+///
+/// A request is sent to the endpoint that served the last successful one, and fails over to the next
+/// endpoints in order. Once every endpoint has failed, the whole round is retried with backoff.
+/// The requests to each endpoint are spaced by `min_interval` to stay within the rate limit of the provider.
+pub struct Endpoints<C> {
+    endpoints: Vec<Endpoint<C>>,
+    current: AtomicUsize,
+    retry: RetryConfig,
+    min_interval: Duration,
+}
+
+struct Endpoint<C> {
+    addr: String,
+    client: C,
+    /// the time of the last request sent to the endpoint
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl<C> Endpoints<C> {
+    pub fn new(endpoints: Vec<(String, C)>) -> Result<Self, Error> {
+        if endpoints.is_empty() {
+            return Err(Error::no_endpoint());
+        }
+        Ok(Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(addr, client)| Endpoint {
+                    addr,
+                    client,
+                    last_request: Mutex::new(None),
+                })
+                .collect(),
+            current: AtomicUsize::new(0),
+            retry: RetryConfig::default(),
+            min_interval: Duration::ZERO,
+        })
+    }
+
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Returns the address of the endpoint that the next request is sent to first
+    pub fn current_addr(&self) -> &str {
+        &self.endpoints[self.current.load(Ordering::Relaxed)].addr
+    }
+
+    /// Run the request `f` on the endpoints until it succeeds or the retries are exhausted
+    pub fn call<T, E: ToString>(
+        &self,
+        name: &str,
+        f: impl Fn(&C) -> Result<T, E>,
+    ) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            let start = self.current.load(Ordering::Relaxed);
+            let mut last_error = None;
+            for i in 0..self.endpoints.len() {
+                let index = (start + i) % self.endpoints.len();
+                let endpoint = &self.endpoints[index];
+                self.wait_turn(endpoint);
+                match f(&endpoint.client) {
+                    Ok(res) => {
+                        if index != start {
+                            info!("{} failed over to {}", name, endpoint.addr);
+                            self.current.store(index, Ordering::Relaxed);
+                        }
+                        return Ok(res);
+                    }
+                    Err(e) => {
+                        let e = e.to_string();
+                        warn!("{} failed: endpoint={} err={}", name, endpoint.addr, e);
+                        last_error = Some((endpoint.addr.clone(), e));
+                    }
+                }
+            }
+            // `new` ensures that there is at least one endpoint
+            let (endpoint, descr) = last_error.unwrap();
+            if attempt >= self.retry.max_retries {
+                return Err(Error::request(
+                    name.to_string(),
+                    endpoint,
+                    attempt + 1,
+                    descr,
+                ));
+            }
+            let backoff = self.retry.backoff(attempt);
+            warn!(
+                "{} failed on all the endpoints, retrying: attempt={} backoff={:?}",
+                name,
+                attempt + 1,
+                backoff
+            );
+            std::thread::sleep(backoff);
+            attempt += 1;
+        }
+    }
+
+    /// Wait until `min_interval` has passed since the last request to the endpoint
+    fn wait_turn(&self, endpoint: &Endpoint<C>) {
+        let mut last_request = endpoint.last_request.lock().unwrap();
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                std::thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A client that fails the given number of requests before succeeding
+    struct FlakyClient {
+        failures: Cell<u32>,
+        requests: Cell<u32>,
+    }
+
+    impl FlakyClient {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures: Cell::new(failures),
+                requests: Cell::new(0),
+            }
+        }
+
+        fn request(&self) -> Result<u32, String> {
+            self.requests.set(self.requests.get() + 1);
+            if self.failures.get() > 0 {
+                self.failures.set(self.failures.get() - 1);
+                Err("unavailable".to_string())
+            } else {
+                Ok(self.requests.get())
+            }
+        }
+    }
+
+    fn no_backoff(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_failover() {
+        let endpoints = Endpoints::new(vec![
+            ("a".to_string(), FlakyClient::new(u32::MAX)),
+            ("b".to_string(), FlakyClient::new(0)),
+        ])
+        .unwrap()
+        .with_retry_config(no_backoff(0));
+        assert_eq!(endpoints.call("test", FlakyClient::request).unwrap(), 1);
+        // the endpoint that succeeded serves the following requests first
+        assert_eq!(endpoints.current_addr(), "b");
+        assert_eq!(endpoints.call("test", FlakyClient::request).unwrap(), 2);
+        assert_eq!(endpoints.endpoints[0].client.requests.get(), 1);
+    }
+
+    #[test]
+    fn test_retry() {
+        let endpoints = Endpoints::new(vec![
+            ("a".to_string(), FlakyClient::new(2)),
+            ("b".to_string(), FlakyClient::new(2)),
+        ])
+        .unwrap()
+        .with_retry_config(no_backoff(2));
+        assert_eq!(endpoints.call("test", FlakyClient::request).unwrap(), 3);
+        assert_eq!(endpoints.current_addr(), "a");
+
+        let endpoints = Endpoints::new(vec![("a".to_string(), FlakyClient::new(3))])
+            .unwrap()
+            .with_retry_config(no_backoff(2));
+        assert!(endpoints.call("test", FlakyClient::request).is_err());
+        assert_eq!(endpoints.endpoints[0].client.requests.get(), 3);
+
+        assert!(Endpoints::<FlakyClient>::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let min_interval = Duration::from_millis(20);
+        let endpoints = Endpoints::new(vec![("a".to_string(), FlakyClient::new(0))])
+            .unwrap()
+            .with_min_interval(min_interval);
+        let start = Instant::now();
+        for _ in 0..3 {
+            endpoints.call("test", FlakyClient::request).unwrap();
+        }
+        assert!(start.elapsed() >= min_interval * 2);
+    }
+}
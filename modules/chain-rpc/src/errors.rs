@@ -0,0 +1,44 @@
+use flex_error::*;
+
+define_error! {
+    #[derive(Debug)]
+    Error {
+        NoEndpoint
+        |_| { "no RPC endpoint is given" },
+
+        InvalidAddress {
+            addr: String
+        }
+        [TraceError<tendermint_rpc::Error>]
+        |e| {
+            format_args!("invalid RPC address: addr={}", e.addr)
+        },
+
+        InvalidHeight {
+            height: u64
+        }
+        [TraceError<tendermint::Error>]
+        |e| {
+            format_args!("invalid height: height={}", e.height)
+        },
+
+        Request {
+            name: String,
+            endpoint: String,
+            attempts: u32,
+            descr: String
+        }
+        |e| {
+            format_args!("RPC request failed on all the endpoints: name={} last_endpoint={} attempts={} descr={}", e.name, e.endpoint, e.attempts, e.descr)
+        },
+
+        AbciQuery {
+            path: String,
+            code: u32,
+            log: String
+        }
+        |e| {
+            format_args!("ABCI query failed: path={} code={} log={}", e.path, e.code, e.log)
+        },
+    }
+}
@@ -0,0 +1,29 @@
+pub use cometbft::CometBftRpc;
+pub use endpoints::{Endpoints, RetryConfig};
+pub use errors::Error;
+
+mod cometbft;
+mod endpoints;
+mod errors;
+
+use tendermint_rpc::endpoint::{commit, status};
+
+/// ChainRpc is the access to the RPC of a chain that the relayer and the bootstrap tooling depend on
+///
+/// An implementation is expected to tolerate the transient failures of the providers, so a caller
+/// doesn't retry an error returned from it.
+pub trait ChainRpc {
+    /// Returns the status of the node, e.g. its identifier and the range of the blocks it keeps
+    fn status(&self) -> Result<status::Response, Error>;
+
+    /// Returns the signed header of the latest block
+    fn latest_commit(&self) -> Result<commit::Response, Error>;
+
+    /// Returns the signed header of the block at `height`
+    fn commit(&self, height: u64) -> Result<commit::Response, Error>;
+
+    /// Returns the value of an ABCI query at the latest height, e.g. `/cosmos.staking.v1beta1.Query/Params`
+    ///
+    /// A query that the application rejects is returned as an error without being retried.
+    fn abci_query(&self, path: &str, data: Vec<u8>) -> Result<Vec<u8>, Error>;
+}
@@ -192,6 +192,10 @@ impl TrustingPeriodContext {
         }
     }
 
+    pub fn trusting_period(&self) -> Duration {
+        self.trusting_period
+    }
+
     pub fn validate(&self, current_timestamp: Time) -> Result<(), Error> {
         // ensure that trusted consensus state's timestamp hasn't passed the trusting period
         Self::ensure_within_trust_period(
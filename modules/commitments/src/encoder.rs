@@ -2,7 +2,7 @@ use crate::message::EmittedState;
 use crate::prelude::*;
 use crate::Error;
 use alloy_sol_types::sol;
-use lcp_types::{Any, Height};
+use lcp_types::{Any, Height, Time};
 use prost::Message;
 
 pub trait EthABIEncoder {
@@ -76,3 +76,16 @@ impl TryFrom<EthABIEmittedState> for EmittedState {
         Ok(Self(value.height.into(), Any::try_from(value.state)?))
     }
 }
+
+/// Encode an optional time as unix timestamp nanos, where zero means None
+pub(crate) fn encode_optional_time(time: Option<Time>) -> u128 {
+    time.map_or(0, |t| t.as_unix_timestamp_nanos())
+}
+
+pub(crate) fn decode_optional_time(nanos: u128) -> Result<Option<Time>, Error> {
+    if nanos == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(Time::from_unix_timestamp_nanos(nanos)?))
+    }
+}
@@ -1,7 +1,7 @@
 use crate::prelude::*;
 use crate::STATE_ID_SIZE;
 use flex_error::*;
-use lcp_types::Time;
+use lcp_types::{Height, Time};
 
 define_error! {
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,6 +40,15 @@ define_error! {
             format_args!("invalid bytes length: expected=0or{} actual={}", e.expected, e.actual)
         },
 
+        UnexpectedTypeUrl
+        {
+            expected: String,
+            actual: String
+        }
+        |e| {
+            format_args!("unexpected type_url: expected={} actual={}", e.expected, e.actual)
+        },
+
         UnexpectedMessageType
         {
             expected: u16,
@@ -91,6 +100,32 @@ define_error! {
             format_args!("not truncated timestamp: timestamp_nanos={}", e.timestamp_nanos)
         },
 
+        UnsupportedMessageSchemaVersion
+        {
+            version: u16
+        }
+        |e| {
+            format_args!("unsupported message schema version: version={}", e.version)
+        },
+
+        NotRepresentableInSchemaVersion
+        {
+            version: u16,
+            descr: String
+        }
+        |e| {
+            format_args!("the message cannot be represented in the schema version: version={} descr={}", e.version, e.descr)
+        },
+
+        MessageExpired
+        {
+            current_timestamp: Time,
+            expires_at: Time
+        }
+        |e| {
+            format_args!("message expired: current_timestamp={} expires_at={}", e.current_timestamp, e.expires_at)
+        },
+
         MessageAggregationFailed
         {
             descr: String
@@ -99,6 +134,15 @@ define_error! {
             format_args!("message aggregation failed: descr={}", e.descr)
         },
 
+        FrozenHeightMismatch
+        {
+            expected: Option<Height>,
+            actual: Option<Height>
+        }
+        |e| {
+            format_args!("cannot aggregate messages spanning a freeze event: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
         ContextAggregationFailed
         {
             descr: String
@@ -111,6 +155,14 @@ define_error! {
         {}
         |_| {"empty path"},
 
+        EmptyKey
+        {}
+        |_| {"empty key"},
+
+        EmptyClientId
+        {}
+        |_| {"empty client_id"},
+
         ZeroHeight
         {}
         |_| {"zero height"},
@@ -127,6 +179,38 @@ define_error! {
         {}
         |_| {"empty prev_states in misbehaviour message"},
 
+        UnsortedLatestHeights
+        {
+            client_id: String
+        }
+        |e| {
+            format_args!("latest heights must be sorted by client_id without duplicates: client_id={}", e.client_id)
+        },
+
+        InvalidClientParams
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid client params: descr={}", e.descr)
+        },
+
+        InvalidClientMigration
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid client migration: descr={}", e.descr)
+        },
+
+        RequestBindingMismatch
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("the proof is not bound to the request: descr={}", e.descr)
+        },
+
         ProtoDecodeError
         [TraceError<prost::DecodeError>]
         |_| {"proto decode error"},
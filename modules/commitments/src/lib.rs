@@ -21,14 +21,23 @@ mod prelude {
 
 pub use context::{TrustingPeriodContext, ValidationContext};
 pub use encoder::EthABIEncoder;
-pub use errors::Error;
+pub use errors::{Error, ErrorDetail};
 pub use message::{
-    aggregate_messages, CommitmentPrefix, EmittedState, MisbehaviourProxyMessage, PrevState,
-    ProxyMessage, UpdateStateProxyMessage, VerifyMembershipProxyMessage,
+    aggregate_messages, ClientLatestHeight, ClientMigrationProxyMessage, ClientParamsProxyMessage,
+    CommitmentPrefix, CreateClientProxyMessage, EmittedState, LatestHeightsProxyMessage,
+    MisbehaviourProxyMessage, PrevState, ProxyMessage, UpdateStateProxyMessage,
+    VerifyKeyValueProxyMessage, VerifyMembershipProxyMessage, MESSAGE_SCHEMA_VERSION,
+    MESSAGE_SCHEMA_VERSION_1, PROXY_MESSAGE_TYPE_URL,
+};
+pub use proof::{CoSignature, CommitmentProof, RequestBinding};
+pub use prover::{prove_commitment, prove_commitment_with_scheme};
+pub use schema::{
+    message_schema, ContextLayout, FieldSchema, MessageLayout, MessageSchema, SigningDomain,
+    StructSchema,
+};
+pub use state::{
+    gen_state_digest, gen_state_id_from_any, gen_state_id_from_bytes, StateID, STATE_ID_SIZE,
 };
-pub use proof::CommitmentProof;
-pub use prover::prove_commitment;
-pub use state::{gen_state_id_from_any, gen_state_id_from_bytes, StateID, STATE_ID_SIZE};
 
 mod context;
 mod encoder;
@@ -36,4 +45,5 @@ mod errors;
 mod message;
 mod proof;
 mod prover;
+mod schema;
 mod state;
@@ -1,22 +1,57 @@
+pub use self::client_migration::ClientMigrationProxyMessage;
+pub use self::client_params::ClientParamsProxyMessage;
+pub use self::create_client::CreateClientProxyMessage;
+pub use self::latest_heights::{ClientLatestHeight, LatestHeightsProxyMessage};
 pub use self::misbehaviour::{MisbehaviourProxyMessage, PrevState};
 pub use self::update_state::{aggregate_messages, EmittedState, UpdateStateProxyMessage};
+pub use self::verify_key_value::VerifyKeyValueProxyMessage;
 pub use self::verify_membership::{CommitmentPrefix, VerifyMembershipProxyMessage};
+pub(crate) use self::{
+    client_migration::EthABIClientMigrationProxyMessage,
+    client_params::EthABIClientParamsProxyMessage,
+    create_client::EthABICreateClientProxyMessage,
+    latest_heights::{EthABIClientLatestHeight, EthABILatestHeightsProxyMessage},
+    misbehaviour::{EthABIMisbehaviourProxyMessage, EthABIPrevState},
+    update_state::EthABIUpdateStateProxyMessage,
+    verify_key_value::EthABIVerifyKeyValueProxyMessage,
+    verify_membership::EthABIVerifyMembershipProxyMessage,
+};
 use crate::encoder::EthABIEncoder;
 use crate::prelude::*;
 use crate::Error;
 use alloy_sol_types::{sol, SolValue};
 use core::fmt::Display;
+use lcp_types::{Any, Time};
 use serde::{Deserialize, Serialize};
+mod client_migration;
+mod client_params;
+mod create_client;
+mod latest_heights;
 mod misbehaviour;
 mod update_state;
+mod verify_key_value;
 mod verify_membership;
 
-pub const MESSAGE_SCHEMA_VERSION: u16 = 1;
+/// The latest version of the schema of the messages that the enclave signs
+///
+/// Version 2 added `frozen_height` and `expires_at` to `UpdateState`, and `expires_at` and `trusted_timestamp`
+/// to `VerifyMembership`. A message is encoded in the version 1 unless it carries one of them.
+pub const MESSAGE_SCHEMA_VERSION: u16 = 2;
+/// The version of the schema that the verifiers which have not been upgraded accept
+pub const MESSAGE_SCHEMA_VERSION_1: u16 = 1;
 pub const MESSAGE_HEADER_SIZE: usize = 32;
 
 pub const MESSAGE_TYPE_UPDATE_STATE: u16 = 1;
 pub const MESSAGE_TYPE_STATE: u16 = 2;
 pub const MESSAGE_TYPE_MISBEHAVIOUR: u16 = 3;
+pub const MESSAGE_TYPE_LATEST_HEIGHTS: u16 = 4;
+pub const MESSAGE_TYPE_KEY_VALUE: u16 = 5;
+pub const MESSAGE_TYPE_CREATE_CLIENT: u16 = 6;
+pub const MESSAGE_TYPE_CLIENT_PARAMS: u16 = 7;
+pub const MESSAGE_TYPE_CLIENT_MIGRATION: u16 = 8;
+
+/// The type URL of a `ProxyMessage` wrapped in `Any`, whose value is the headered ethabi encoding
+pub const PROXY_MESSAGE_TYPE_URL: &str = "/lcp.commitments.v1.ProxyMessage";
 
 /// ProxyMessage is a message generated by the ELC to be submit to the LCP client on the chain.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,13 +59,99 @@ pub enum ProxyMessage {
     UpdateState(UpdateStateProxyMessage),
     VerifyMembership(VerifyMembershipProxyMessage),
     Misbehaviour(MisbehaviourProxyMessage),
+    LatestHeights(LatestHeightsProxyMessage),
+    VerifyKeyValue(VerifyKeyValueProxyMessage),
+    CreateClient(CreateClientProxyMessage),
+    ClientParams(ClientParamsProxyMessage),
+    ClientMigration(ClientMigrationProxyMessage),
 }
 
 impl ProxyMessage {
+    /// Encodes the message in the schema of `schema_version()`
     pub fn to_bytes(self) -> Vec<u8> {
         self.ethabi_encode()
     }
 
+    /// Returns the lowest version of the schema that can represent the message
+    ///
+    /// The verifiers that have not been upgraded keep accepting the messages that carry no field of the version 2.
+    pub fn schema_version(&self) -> u16 {
+        match self {
+            Self::UpdateState(m) if m.frozen_height.is_some() || m.expires_at.is_some() => {
+                MESSAGE_SCHEMA_VERSION
+            }
+            Self::VerifyMembership(m)
+                if m.expires_at.is_some() || m.trusted_timestamp.is_some() =>
+            {
+                MESSAGE_SCHEMA_VERSION
+            }
+            _ => MESSAGE_SCHEMA_VERSION_1,
+        }
+    }
+
+    /// Converts the message for a client whose verifier accepts the schema of `version`
+    ///
+    /// For the version 1, the fields that only inform the verifier, i.e. `frozen_height` and `trusted_timestamp`, are dropped,
+    /// and an error is returned if the message carries `expires_at`, which the verifier would not enforce.
+    pub fn into_schema_version(self, version: u16) -> Result<Self, Error> {
+        match version {
+            MESSAGE_SCHEMA_VERSION => Ok(self),
+            MESSAGE_SCHEMA_VERSION_1 => match self {
+                Self::UpdateState(m) if m.expires_at.is_some() => {
+                    Err(Error::not_representable_in_schema_version(
+                        version,
+                        format!("expires_at requires the schema version {MESSAGE_SCHEMA_VERSION}"),
+                    ))
+                }
+                Self::UpdateState(m) => Ok(Self::UpdateState(UpdateStateProxyMessage {
+                    frozen_height: None,
+                    ..m
+                })),
+                Self::VerifyMembership(m) if m.expires_at.is_some() => {
+                    Err(Error::not_representable_in_schema_version(
+                        version,
+                        format!("expires_at requires the schema version {MESSAGE_SCHEMA_VERSION}"),
+                    ))
+                }
+                Self::VerifyMembership(m) => {
+                    Ok(Self::VerifyMembership(VerifyMembershipProxyMessage {
+                        trusted_timestamp: None,
+                        ..m
+                    }))
+                }
+                other => Ok(other),
+            },
+            _ => Err(Error::unsupported_message_schema_version(version)),
+        }
+    }
+
+    /// Encodes the message in the schema of `version`
+    ///
+    /// Returns an error if the message has a field that the schema doesn't have.
+    pub fn to_versioned_bytes(self, version: u16) -> Result<Vec<u8>, Error> {
+        match version {
+            MESSAGE_SCHEMA_VERSION => Ok(EthABIHeaderedMessage {
+                header: self.versioned_header(MESSAGE_SCHEMA_VERSION).into(),
+                message: self.encode_message(),
+            }
+            .abi_encode()),
+            MESSAGE_SCHEMA_VERSION_1 => {
+                let header = self.versioned_header(MESSAGE_SCHEMA_VERSION_1);
+                let message = match self {
+                    Self::UpdateState(c) => c.ethabi_encode_v1()?,
+                    Self::VerifyMembership(c) => c.ethabi_encode_v1()?,
+                    other => other.encode_message(),
+                };
+                Ok(EthABIHeaderedMessage {
+                    header: header.into(),
+                    message,
+                }
+                .abi_encode())
+            }
+            _ => Err(Error::unsupported_message_schema_version(version)),
+        }
+    }
+
     pub fn from_bytes(bz: &[u8]) -> Result<Self, Error> {
         Self::ethabi_decode(bz)
     }
@@ -40,8 +161,12 @@ impl ProxyMessage {
     // 2-3:  message type
     // 4-31: reserved
     pub fn header(&self) -> [u8; MESSAGE_HEADER_SIZE] {
+        self.versioned_header(self.schema_version())
+    }
+
+    fn versioned_header(&self, version: u16) -> [u8; MESSAGE_HEADER_SIZE] {
         let mut header = [0u8; MESSAGE_HEADER_SIZE];
-        header[0..=1].copy_from_slice(&MESSAGE_SCHEMA_VERSION.to_be_bytes());
+        header[0..=1].copy_from_slice(&version.to_be_bytes());
         header[2..=3].copy_from_slice(&self.message_type().to_be_bytes());
         header
     }
@@ -51,6 +176,11 @@ impl ProxyMessage {
             Self::UpdateState(_) => MESSAGE_TYPE_UPDATE_STATE,
             Self::VerifyMembership(_) => MESSAGE_TYPE_STATE,
             Self::Misbehaviour(_) => MESSAGE_TYPE_MISBEHAVIOUR,
+            Self::LatestHeights(_) => MESSAGE_TYPE_LATEST_HEIGHTS,
+            Self::VerifyKeyValue(_) => MESSAGE_TYPE_KEY_VALUE,
+            Self::CreateClient(_) => MESSAGE_TYPE_CREATE_CLIENT,
+            Self::ClientParams(_) => MESSAGE_TYPE_CLIENT_PARAMS,
+            Self::ClientMigration(_) => MESSAGE_TYPE_CLIENT_MIGRATION,
         }
     }
 
@@ -59,6 +189,35 @@ impl ProxyMessage {
             Self::UpdateState(c) => c.validate(),
             Self::VerifyMembership(c) => c.validate(),
             Self::Misbehaviour(c) => c.validate(),
+            Self::LatestHeights(c) => c.validate(),
+            Self::VerifyKeyValue(c) => c.validate(),
+            Self::CreateClient(c) => c.validate(),
+            Self::ClientParams(c) => c.validate(),
+            Self::ClientMigration(c) => c.validate(),
+        }
+    }
+
+    /// Returns the time after which the message should be rejected, if any
+    pub fn expires_at(&self) -> Option<Time> {
+        match self {
+            Self::UpdateState(c) => c.expires_at,
+            Self::VerifyMembership(c) => c.expires_at,
+            Self::VerifyKeyValue(c) => c.expires_at,
+            Self::Misbehaviour(_)
+            | Self::LatestHeights(_)
+            | Self::CreateClient(_)
+            | Self::ClientParams(_)
+            | Self::ClientMigration(_) => None,
+        }
+    }
+
+    /// Returns an error if the message has expired at `current_timestamp`
+    pub fn check_expiry(&self, current_timestamp: Time) -> Result<(), Error> {
+        match self.expires_at() {
+            Some(expires_at) if current_timestamp > expires_at => {
+                Err(Error::message_expired(current_timestamp, expires_at))
+            }
+            _ => Ok(()),
         }
     }
 }
@@ -69,7 +228,31 @@ impl Display for ProxyMessage {
             Self::UpdateState(c) => write!(f, "{}", c),
             Self::VerifyMembership(c) => write!(f, "{}", c),
             Self::Misbehaviour(c) => write!(f, "{}", c),
+            Self::LatestHeights(c) => write!(f, "{}", c),
+            Self::VerifyKeyValue(c) => write!(f, "{}", c),
+            Self::CreateClient(c) => write!(f, "{}", c),
+            Self::ClientParams(c) => write!(f, "{}", c),
+            Self::ClientMigration(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+impl From<ProxyMessage> for Any {
+    fn from(value: ProxyMessage) -> Self {
+        Any::new(PROXY_MESSAGE_TYPE_URL.to_string(), value.to_bytes())
+    }
+}
+
+impl TryFrom<Any> for ProxyMessage {
+    type Error = Error;
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        if value.type_url != PROXY_MESSAGE_TYPE_URL {
+            return Err(Error::unexpected_type_url(
+                PROXY_MESSAGE_TYPE_URL.to_string(),
+                value.type_url,
+            ));
         }
+        Self::from_bytes(&value.value)
     }
 }
 
@@ -112,6 +295,71 @@ impl TryFrom<ProxyMessage> for MisbehaviourProxyMessage {
     }
 }
 
+impl TryFrom<ProxyMessage> for LatestHeightsProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::LatestHeights(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_LATEST_HEIGHTS,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<ProxyMessage> for VerifyKeyValueProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::VerifyKeyValue(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_KEY_VALUE,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<ProxyMessage> for CreateClientProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::CreateClient(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_CREATE_CLIENT,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<ProxyMessage> for ClientParamsProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::ClientParams(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_CLIENT_PARAMS,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<ProxyMessage> for ClientMigrationProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::ClientMigration(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_CLIENT_MIGRATION,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
 impl From<UpdateStateProxyMessage> for ProxyMessage {
     fn from(value: UpdateStateProxyMessage) -> Self {
         ProxyMessage::UpdateState(value)
@@ -130,6 +378,36 @@ impl From<MisbehaviourProxyMessage> for ProxyMessage {
     }
 }
 
+impl From<LatestHeightsProxyMessage> for ProxyMessage {
+    fn from(value: LatestHeightsProxyMessage) -> Self {
+        ProxyMessage::LatestHeights(value)
+    }
+}
+
+impl From<VerifyKeyValueProxyMessage> for ProxyMessage {
+    fn from(value: VerifyKeyValueProxyMessage) -> Self {
+        ProxyMessage::VerifyKeyValue(value)
+    }
+}
+
+impl From<CreateClientProxyMessage> for ProxyMessage {
+    fn from(value: CreateClientProxyMessage) -> Self {
+        ProxyMessage::CreateClient(value)
+    }
+}
+
+impl From<ClientParamsProxyMessage> for ProxyMessage {
+    fn from(value: ClientParamsProxyMessage) -> Self {
+        ProxyMessage::ClientParams(value)
+    }
+}
+
+impl From<ClientMigrationProxyMessage> for ProxyMessage {
+    fn from(value: ClientMigrationProxyMessage) -> Self {
+        ProxyMessage::ClientMigration(value)
+    }
+}
+
 sol! {
     struct EthABIHeaderedMessage {
         bytes32 header;
@@ -137,17 +415,27 @@ sol! {
     }
 }
 
+impl ProxyMessage {
+    /// Encodes the message without the header in the latest schema
+    fn encode_message(self) -> Vec<u8> {
+        match self {
+            Self::UpdateState(c) => c.ethabi_encode(),
+            Self::VerifyMembership(c) => c.ethabi_encode(),
+            Self::Misbehaviour(c) => c.ethabi_encode(),
+            Self::LatestHeights(c) => c.ethabi_encode(),
+            Self::VerifyKeyValue(c) => c.ethabi_encode(),
+            Self::CreateClient(c) => c.ethabi_encode(),
+            Self::ClientParams(c) => c.ethabi_encode(),
+            Self::ClientMigration(c) => c.ethabi_encode(),
+        }
+    }
+}
+
 impl EthABIEncoder for ProxyMessage {
     fn ethabi_encode(self) -> Vec<u8> {
-        EthABIHeaderedMessage {
-            header: self.header().into(),
-            message: match self {
-                Self::UpdateState(c) => c.ethabi_encode(),
-                Self::VerifyMembership(c) => c.ethabi_encode(),
-                Self::Misbehaviour(c) => c.ethabi_encode(),
-            },
-        }
-        .abi_encode()
+        let version = self.schema_version();
+        self.to_versioned_bytes(version)
+            .expect("a message is representable in its own schema version")
     }
 
     fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
@@ -170,21 +458,42 @@ impl EthABIEncoder for ProxyMessage {
                 u16::from_be_bytes(commitment_type),
             )
         };
-        if version != MESSAGE_SCHEMA_VERSION {
+        if version != MESSAGE_SCHEMA_VERSION && version != MESSAGE_SCHEMA_VERSION_1 {
             return Err(Error::invalid_message_header(format!(
-                "invalid version: expected={} actual={} header={:?}",
-                MESSAGE_SCHEMA_VERSION, version, eth_abi_message.header
+                "invalid version: expected={} or {} actual={} header={:?}",
+                MESSAGE_SCHEMA_VERSION, MESSAGE_SCHEMA_VERSION_1, version, eth_abi_message.header
             )));
         }
         let message = eth_abi_message.message;
         match message_type {
+            MESSAGE_TYPE_UPDATE_STATE if version == MESSAGE_SCHEMA_VERSION_1 => {
+                Ok(UpdateStateProxyMessage::ethabi_decode_v1(&message)?.into())
+            }
             MESSAGE_TYPE_UPDATE_STATE => {
                 Ok(UpdateStateProxyMessage::ethabi_decode(&message)?.into())
             }
+            MESSAGE_TYPE_STATE if version == MESSAGE_SCHEMA_VERSION_1 => {
+                Ok(VerifyMembershipProxyMessage::ethabi_decode_v1(&message)?.into())
+            }
             MESSAGE_TYPE_STATE => Ok(VerifyMembershipProxyMessage::ethabi_decode(&message)?.into()),
             MESSAGE_TYPE_MISBEHAVIOUR => {
                 Ok(MisbehaviourProxyMessage::ethabi_decode(&message)?.into())
             }
+            MESSAGE_TYPE_LATEST_HEIGHTS => {
+                Ok(LatestHeightsProxyMessage::ethabi_decode(&message)?.into())
+            }
+            MESSAGE_TYPE_KEY_VALUE => {
+                Ok(VerifyKeyValueProxyMessage::ethabi_decode(&message)?.into())
+            }
+            MESSAGE_TYPE_CREATE_CLIENT => {
+                Ok(CreateClientProxyMessage::ethabi_decode(&message)?.into())
+            }
+            MESSAGE_TYPE_CLIENT_PARAMS => {
+                Ok(ClientParamsProxyMessage::ethabi_decode(&message)?.into())
+            }
+            MESSAGE_TYPE_CLIENT_MIGRATION => {
+                Ok(ClientMigrationProxyMessage::ethabi_decode(&message)?.into())
+            }
             _ => Err(Error::invalid_abi(format!(
                 "invalid message type: {}",
                 message_type
@@ -198,11 +507,12 @@ mod tests {
     use super::*;
     use crate::{
         message::verify_membership::CommitmentPrefix, CommitmentProof, StateID,
-        TrustingPeriodContext,
+        TrustingPeriodContext, ValidationContext,
     };
     use crypto::Address;
-    use lcp_types::{nanos_to_duration, Any, Height, Time, MAX_UNIX_TIMESTAMP_NANOS};
+    use lcp_types::{nanos_to_duration, Any, ClientParams, Height, Time, MAX_UNIX_TIMESTAMP_NANOS};
     use proptest::prelude::*;
+    use std::collections::BTreeMap;
 
     fn height_from_tuple(tuple: (u64, u64)) -> Height {
         Height::new(tuple.0, tuple.1)
@@ -217,11 +527,11 @@ mod tests {
         let c2 = UpdateStateProxyMessage::ethabi_decode(&v).unwrap();
         assert_eq!(c1, c2);
 
-        let p1 = CommitmentProof {
-            message: ProxyMessage::from(c1).to_bytes(),
-            signer: proof_signer,
-            signature: proof_signature.to_vec(),
-        };
+        let p1 = CommitmentProof::new(
+            ProxyMessage::from(c1).to_bytes(),
+            proof_signer,
+            proof_signature.to_vec(),
+        );
         // TODO uncomment this line when we want to generate the test data
         // println!("{{\"{}\"}},", hex::encode(p1.clone().ethabi_encode()));
         let p2 = CommitmentProof::ethabi_decode(&p1.clone().ethabi_encode()).unwrap();
@@ -235,6 +545,8 @@ mod tests {
             prev_state_id in any::<Option<[u8; 32]>>().prop_map(|v| v.map(StateID::from)),
             post_height in any::<(u64, u64)>().prop_map(height_from_tuple),
             post_state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            frozen_height in any::<Option<(u64, u64)>>().prop_map(|v| v.map(height_from_tuple)),
+            expires_at in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
             emitted_states in any::<Vec<((u64, u64), (String, Vec<u8>))>>(),
             timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
             proof_signer in any::<[u8; 20]>(),
@@ -245,6 +557,8 @@ mod tests {
                 prev_state_id,
                 post_height,
                 post_state_id,
+                frozen_height,
+                expires_at: expires_at.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
                 emitted_states: emitted_states.into_iter().map(|(height, (type_url, value))| {
                     EmittedState(height_from_tuple(height), Any::new(format!("/{}", type_url), value))
                 }).collect(),
@@ -260,6 +574,8 @@ mod tests {
             prev_state_id in any::<Option<[u8; 32]>>().prop_map(|v| v.map(StateID::from)),
             post_height in any::<(u64, u64)>().prop_map(height_from_tuple),
             post_state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            frozen_height in any::<Option<(u64, u64)>>().prop_map(|v| v.map(height_from_tuple)),
+            expires_at in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
             emitted_states in any::<Vec<((u64, u64), (String, Vec<u8>))>>(),
             timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
             trusting_period in ..=MAX_UNIX_TIMESTAMP_NANOS,
@@ -274,6 +590,8 @@ mod tests {
                 prev_state_id,
                 post_height,
                 post_state_id,
+                frozen_height,
+                expires_at: expires_at.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
                 emitted_states: emitted_states.into_iter().map(|(height, (type_url, value))| {
                     EmittedState(height_from_tuple(height), Any::new(format!("/{}", type_url), value))
                 }).collect(),
@@ -295,6 +613,8 @@ mod tests {
             value in any::<Option<[u8; 32]>>(),
             height in any::<(u64, u64)>().prop_map(height_from_tuple),
             state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            expires_at in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
+            trusted_timestamp in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
             proof_signer in any::<[u8; 20]>(),
             proof_signature in any::<[u8; 65]>()
         ) {
@@ -304,20 +624,145 @@ mod tests {
                 value,
                 height,
                 state_id,
+                expires_at: expires_at.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
+                trusted_timestamp: trusted_timestamp.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
             };
             let v = c1.clone().ethabi_encode();
             let c2 = VerifyMembershipProxyMessage::ethabi_decode(&v).unwrap();
             assert_eq!(c1, c2);
 
-            let p1 = CommitmentProof {
-                message: ProxyMessage::from(c1).to_bytes(),
-                signer: Address(proof_signer),
-                signature: proof_signature.to_vec(),
-            };
+            let p1 = CommitmentProof::new(
+                ProxyMessage::from(c1).to_bytes(),
+                Address(proof_signer),
+                proof_signature.to_vec(),
+            );
             let p2 = CommitmentProof::ethabi_decode(&p1.clone().ethabi_encode()).unwrap();
             assert_eq!(p1, p2);
         }
 
+        #[test]
+        fn pt_verify_key_value(
+            prefix in any::<CommitmentPrefix>(),
+            key in any::<Vec<u8>>().prop_filter("empty key", |v| !v.is_empty()),
+            value in any::<Option<[u8; 32]>>(),
+            height in any::<(u64, u64)>().prop_map(height_from_tuple),
+            state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            expires_at in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
+            trusted_timestamp in proptest::option::of(1..=MAX_UNIX_TIMESTAMP_NANOS),
+        ) {
+            let msg: ProxyMessage = VerifyKeyValueProxyMessage {
+                prefix,
+                key,
+                value,
+                height,
+                state_id,
+                expires_at: expires_at.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
+                trusted_timestamp: trusted_timestamp.map(|t| Time::from_unix_timestamp_nanos(t).unwrap()),
+            }.into();
+            assert_eq!(msg.message_type(), MESSAGE_TYPE_KEY_VALUE);
+            let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg2);
+        }
+
+        #[test]
+        fn pt_create_client(
+            client_id in any::<String>().prop_filter("empty client_id", |v| !v.is_empty()),
+            height in any::<(u64, u64)>().prop_map(height_from_tuple),
+            client_state_digest in any::<[u8; 32]>(),
+            consensus_state_digest in any::<[u8; 32]>(),
+            state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
+        ) {
+            let msg: ProxyMessage = CreateClientProxyMessage {
+                client_id,
+                height,
+                client_state_digest,
+                consensus_state_digest,
+                state_id,
+                timestamp: Time::from_unix_timestamp_nanos(timestamp).unwrap(),
+            }.into();
+            assert_eq!(msg.message_type(), MESSAGE_TYPE_CREATE_CLIENT);
+            let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg2);
+            let any = Any::from(msg.clone());
+            assert_eq!(ProxyMessage::try_from(any.clone()).unwrap(), msg);
+            assert!(ProxyMessage::try_from(Any::new("/other".into(), any.value)).is_err());
+        }
+
+        #[test]
+        fn pt_client_params(
+            client_id in any::<String>().prop_filter("empty client_id", |v| !v.is_empty()),
+            trusting_period in proptest::option::of(1..=u64::MAX),
+            max_clock_drift in proptest::option::of(1..=u64::MAX),
+            proof_specs in proptest::option::of(proptest::collection::vec(proptest::collection::vec(any::<u8>(), 1..64), 1..4)),
+            timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
+        ) {
+            let params = ClientParams {
+                trusting_period: trusting_period.map(|nanos| nanos_to_duration(nanos.into()).unwrap()),
+                max_clock_drift: max_clock_drift.map(|nanos| nanos_to_duration(nanos.into()).unwrap()),
+                proof_specs,
+            };
+            prop_assume!(!params.is_empty());
+            let msg: ProxyMessage = ClientParamsProxyMessage {
+                client_id,
+                params,
+                timestamp: Time::from_unix_timestamp_nanos(timestamp).unwrap(),
+            }.into();
+            let res = msg.validate();
+            assert!(res.is_ok(), "validation failed: {}", res.unwrap_err());
+            assert_eq!(msg.message_type(), MESSAGE_TYPE_CLIENT_PARAMS);
+            let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg2);
+        }
+
+        #[test]
+        fn pt_client_migration(
+            client_id in any::<String>().prop_filter("empty client_id", |v| !v.is_empty()),
+            from_type_url in any::<String>(),
+            from_version in any::<u32>(),
+            to_version in any::<u32>(),
+            height in any::<(u64, u64)>().prop_map(height_from_tuple),
+            prev_state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            post_state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
+        ) {
+            let msg: ProxyMessage = ClientMigrationProxyMessage {
+                client_id,
+                to_type_url: from_type_url.clone(),
+                from_type_url,
+                from_version,
+                to_version,
+                height,
+                prev_state_id,
+                post_state_id,
+                timestamp: Time::from_unix_timestamp_nanos(timestamp).unwrap(),
+            }.into();
+            assert_eq!(msg.message_type(), MESSAGE_TYPE_CLIENT_MIGRATION);
+            let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg2);
+        }
+
+        #[test]
+        fn pt_latest_heights(
+            timestamp in ..=MAX_UNIX_TIMESTAMP_NANOS,
+            clients in any::<BTreeMap<String, ((u64, u64), Option<u64>)>>(),
+        ) {
+            let msg: ProxyMessage = LatestHeightsProxyMessage {
+                timestamp: Time::from_unix_timestamp_nanos(timestamp).unwrap(),
+                clients: clients.into_iter().map(|(client_id, (height, updated_at))| {
+                    ClientLatestHeight {
+                        client_id,
+                        height: height_from_tuple(height),
+                        updated_at: updated_at.map(|t| Time::from_unix_timestamp_nanos(t as u128 + 1).unwrap()),
+                    }
+                }).collect(),
+            }.into();
+            let res = msg.validate();
+            assert!(res.is_ok(), "validation failed: {}", res.unwrap_err());
+            let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg2);
+        }
+
         #[test]
         fn pt_misbehaviour_with_empty_context(
             prev_states in any::<Vec<((u64, u64), [u8; 32])>>().prop_filter("empty prev_states", |v| !v.is_empty()),
@@ -369,4 +814,194 @@ mod tests {
             assert_eq!(msg, msg2);
         }
     }
+
+    fn test_update_state_message(frozen_height: Option<Height>) -> UpdateStateProxyMessage {
+        UpdateStateProxyMessage {
+            prev_height: Some(Height::new(1, 1)),
+            prev_state_id: Some(StateID::from([1u8; 32])),
+            post_height: Height::new(1, 2),
+            post_state_id: StateID::from([2u8; 32]),
+            frozen_height,
+            timestamp: Time::from_unix_timestamp_nanos(1_000_000_000).unwrap(),
+            expires_at: None,
+            context: ValidationContext::default(),
+            emitted_states: vec![],
+        }
+    }
+
+    #[test]
+    fn test_message_schema_version() {
+        let msg = ProxyMessage::from(test_update_state_message(Some(Height::new(1, 2))));
+        assert_eq!(msg.schema_version(), MESSAGE_SCHEMA_VERSION);
+        let bz = msg.clone().to_bytes();
+        let header = EthABIHeaderedMessage::abi_decode(&bz, true).unwrap().header;
+        assert_eq!(header[0..=1], MESSAGE_SCHEMA_VERSION.to_be_bytes());
+        assert_eq!(
+            msg.clone()
+                .to_versioned_bytes(MESSAGE_SCHEMA_VERSION)
+                .unwrap(),
+            bz
+        );
+        assert!(msg.to_versioned_bytes(MESSAGE_SCHEMA_VERSION + 1).is_err());
+
+        // a message without the fields of the version 2 is encoded in the version 1
+        let msg = ProxyMessage::from(test_update_state_message(None));
+        assert_eq!(msg.schema_version(), MESSAGE_SCHEMA_VERSION_1);
+        let bz = msg.clone().to_bytes();
+        let header = EthABIHeaderedMessage::abi_decode(&bz, true).unwrap().header;
+        assert_eq!(header[0..=1], MESSAGE_SCHEMA_VERSION_1.to_be_bytes());
+        assert_eq!(ProxyMessage::from_bytes(&bz).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_into_schema_version() {
+        let msg = ProxyMessage::from(test_update_state_message(Some(Height::new(1, 2))));
+        assert_eq!(
+            msg.clone()
+                .into_schema_version(MESSAGE_SCHEMA_VERSION)
+                .unwrap(),
+            msg
+        );
+        // the frozen height only informs the verifier, so it is dropped for the version 1
+        assert_eq!(
+            msg.clone()
+                .into_schema_version(MESSAGE_SCHEMA_VERSION_1)
+                .unwrap(),
+            test_update_state_message(None).into()
+        );
+        assert!(msg.into_schema_version(MESSAGE_SCHEMA_VERSION + 1).is_err());
+
+        // the verifiers of the version 1 would not enforce an expiry
+        let msg = ProxyMessage::from(UpdateStateProxyMessage {
+            expires_at: Some(Time::from_unix_timestamp_nanos(1_000_000_000).unwrap()),
+            ..test_update_state_message(None)
+        });
+        assert!(msg
+            .clone()
+            .into_schema_version(MESSAGE_SCHEMA_VERSION_1)
+            .is_err());
+        assert!(msg.into_schema_version(MESSAGE_SCHEMA_VERSION).is_ok());
+
+        let msg = VerifyMembershipProxyMessage {
+            trusted_timestamp: Some(Time::from_unix_timestamp_nanos(1_000_000_000).unwrap()),
+            ..VerifyMembershipProxyMessage::new(
+                b"ibc".to_vec(),
+                "clients/07-tendermint-0/clientState".to_string(),
+                Some([1u8; 32]),
+                Height::new(1, 2),
+                StateID::from([2u8; 32]),
+            )
+        };
+        let converted = ProxyMessage::from(msg.clone())
+            .into_schema_version(MESSAGE_SCHEMA_VERSION_1)
+            .unwrap();
+        assert_eq!(converted.schema_version(), MESSAGE_SCHEMA_VERSION_1);
+        assert_eq!(
+            converted,
+            VerifyMembershipProxyMessage {
+                trusted_timestamp: None,
+                ..msg
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_update_state_message_v1() {
+        let msg = ProxyMessage::from(test_update_state_message(None));
+        let bz = msg
+            .clone()
+            .to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1)
+            .unwrap();
+        let header = EthABIHeaderedMessage::abi_decode(&bz, true).unwrap().header;
+        assert_eq!(header[0..=1], MESSAGE_SCHEMA_VERSION_1.to_be_bytes());
+        assert_eq!(bz, msg.clone().to_bytes());
+        assert_ne!(
+            bz,
+            msg.clone()
+                .to_versioned_bytes(MESSAGE_SCHEMA_VERSION)
+                .unwrap()
+        );
+        assert_eq!(ProxyMessage::from_bytes(&bz).unwrap(), msg);
+
+        // the layout of the version 1 cannot represent a frozen height
+        let msg = ProxyMessage::from(test_update_state_message(Some(Height::new(1, 2))));
+        assert!(msg.to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1).is_err());
+    }
+
+    #[test]
+    fn test_verify_membership_message_v1() {
+        let msg = VerifyMembershipProxyMessage::new(
+            b"ibc".to_vec(),
+            "clients/07-tendermint-0/clientState".to_string(),
+            Some([1u8; 32]),
+            Height::new(1, 2),
+            StateID::from([2u8; 32]),
+        );
+        let bz = ProxyMessage::from(msg.clone())
+            .to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1)
+            .unwrap();
+        assert_eq!(ProxyMessage::from_bytes(&bz).unwrap(), msg.clone().into());
+
+        // the layout of the version 1 cannot represent an expiry
+        let msg = VerifyMembershipProxyMessage {
+            expires_at: Some(Time::from_unix_timestamp_nanos(1_000_000_000).unwrap()),
+            ..msg
+        };
+        let res = ProxyMessage::from(msg.clone()).to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1);
+        assert!(res.is_err());
+        let bz = ProxyMessage::from(msg.clone()).to_bytes();
+        assert_eq!(ProxyMessage::from_bytes(&bz).unwrap(), msg.into());
+    }
+
+    #[test]
+    fn test_check_expiry() {
+        let expires_at = Time::from_unix_timestamp_nanos(1_000_000_000).unwrap();
+        let msg = ProxyMessage::from(UpdateStateProxyMessage {
+            expires_at: Some(expires_at),
+            ..test_update_state_message(None)
+        });
+        assert!(msg.check_expiry(expires_at).is_ok());
+        assert!(msg
+            .check_expiry((expires_at + core::time::Duration::from_nanos(1)).unwrap())
+            .is_err());
+        assert!(ProxyMessage::from(test_update_state_message(None))
+            .check_expiry(Time::from_unix_timestamp_nanos(MAX_UNIX_TIMESTAMP_NANOS).unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_latest_heights_must_be_sorted() {
+        let client = |client_id: &str| ClientLatestHeight {
+            client_id: client_id.to_string(),
+            height: Height::new(0, 1),
+            updated_at: None,
+        };
+        let timestamp = Time::unix_epoch();
+        for clients in [
+            vec![client("client-1"), client("client-0")],
+            vec![client("client-0"), client("client-0")],
+        ] {
+            let msg = LatestHeightsProxyMessage { timestamp, clients };
+            assert!(msg.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_client_params_must_change_a_parameter() {
+        let msg = ClientParamsProxyMessage {
+            client_id: "07-tendermint-0".into(),
+            params: Default::default(),
+            timestamp: Time::unix_epoch(),
+        };
+        assert!(msg.validate().is_err());
+        let msg = ClientParamsProxyMessage {
+            params: ClientParams {
+                max_clock_drift: Some(core::time::Duration::ZERO),
+                ..Default::default()
+            },
+            ..msg
+        };
+        assert!(msg.validate().is_err());
+    }
 }
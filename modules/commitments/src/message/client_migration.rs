@@ -0,0 +1,193 @@
+use crate::encoder::{EthABIEncoder, EthABIHeight};
+use crate::prelude::*;
+use crate::{Error, StateID};
+use alloy_sol_types::{private::B256, sol, SolValue};
+use core::fmt::Display;
+use lcp_types::{Height, Time};
+use serde::{Deserialize, Serialize};
+
+/// ClientMigrationProxyMessage is a notice that the operators have migrated a client to another light client implementation
+///
+/// The migration converts the stored states without advancing the height, so the state at `height`
+/// is identified by `prev_state_id` before the migration and by `post_state_id` after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientMigrationProxyMessage {
+    pub client_id: String,
+    /// The type URL of the client state and the version of the implementation before the migration
+    pub from_type_url: String,
+    pub from_version: u32,
+    /// The type URL of the client state and the version of the implementation after the migration
+    pub to_type_url: String,
+    pub to_version: u32,
+    /// The latest height of the client, which the migration does not change
+    pub height: Height,
+    pub prev_state_id: StateID,
+    pub post_state_id: StateID,
+    /// The time when the client was migrated in the enclave
+    pub timestamp: Time,
+}
+
+impl ClientMigrationProxyMessage {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.client_id.is_empty() {
+            return Err(Error::empty_client_id());
+        }
+        if self.from_type_url == self.to_type_url && self.from_version == self.to_version {
+            return Err(Error::invalid_client_migration(
+                "the implementation is unchanged".into(),
+            ));
+        }
+        if self.height.is_zero() {
+            return Err(Error::zero_height());
+        }
+        if self.prev_state_id.is_zero() || self.post_state_id.is_zero() {
+            return Err(Error::zero_state_id());
+        }
+        Ok(())
+    }
+}
+
+impl Display for ClientMigrationProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ClientMigration(client_id: {}, from: {}@v{}, to: {}@v{}, height: {}, prev_state_id: {}, post_state_id: {}, timestamp: {})",
+            self.client_id,
+            self.from_type_url,
+            self.from_version,
+            self.to_type_url,
+            self.to_version,
+            self.height,
+            self.prev_state_id,
+            self.post_state_id,
+            self.timestamp.as_unix_timestamp_nanos()
+        )
+    }
+}
+
+sol! {
+    struct EthABIClientMigrationProxyMessage {
+        string client_id;
+        string from_type_url;
+        uint32 from_version;
+        string to_type_url;
+        uint32 to_version;
+        EthABIHeight height;
+        bytes32 prev_state_id;
+        bytes32 post_state_id;
+        uint128 timestamp;
+    }
+}
+
+impl From<ClientMigrationProxyMessage> for EthABIClientMigrationProxyMessage {
+    fn from(msg: ClientMigrationProxyMessage) -> Self {
+        Self {
+            client_id: msg.client_id,
+            from_type_url: msg.from_type_url,
+            from_version: msg.from_version,
+            to_type_url: msg.to_type_url,
+            to_version: msg.to_version,
+            height: EthABIHeight::from(msg.height),
+            prev_state_id: B256::from_slice(&msg.prev_state_id.to_vec()),
+            post_state_id: B256::from_slice(&msg.post_state_id.to_vec()),
+            timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+        }
+    }
+}
+
+impl TryFrom<EthABIClientMigrationProxyMessage> for ClientMigrationProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABIClientMigrationProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            client_id: msg.client_id,
+            from_type_url: msg.from_type_url,
+            from_version: msg.from_version,
+            to_type_url: msg.to_type_url,
+            to_version: msg.to_version,
+            height: msg.height.into(),
+            prev_state_id: msg.prev_state_id.as_slice().try_into()?,
+            post_state_id: msg.post_state_id.as_slice().try_into()?,
+            timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+        })
+    }
+}
+
+impl EthABIEncoder for ClientMigrationProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABIClientMigrationProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABIClientMigrationProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prove_commitment_with_scheme, ProxyMessage};
+    use crypto::{EnclaveKey, SignatureScheme};
+
+    fn message() -> ClientMigrationProxyMessage {
+        ClientMigrationProxyMessage {
+            client_id: "07-tendermint-0".to_string(),
+            from_type_url: "/ibc.lightclients.tendermint.v1.ClientState".to_string(),
+            from_version: 1,
+            to_type_url: "/ibc.lightclients.tendermint.v1.ClientState".to_string(),
+            to_version: 2,
+            height: Height::new(0, 10),
+            prev_state_id: StateID::from([1u8; 32]),
+            post_state_id: StateID::from([2u8; 32]),
+            timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_signed_client_migration() {
+        let ek = EnclaveKey::new().unwrap();
+        let signer = ek.get_pubkey().as_address();
+        let scheme = SignatureScheme::default();
+        let proof = prove_commitment_with_scheme(&ek, signer, scheme, message().into()).unwrap();
+        assert!(proof.is_proven());
+        scheme
+            .verify_signer(&proof.message, &proof.signature, signer)
+            .unwrap();
+
+        let msg: ClientMigrationProxyMessage = proof.message().unwrap().try_into().unwrap();
+        assert_eq!(msg, message());
+
+        // the notice is bound to the signature
+        let mut tampered = message();
+        tampered.to_version = 3;
+        let tampered = ProxyMessage::from(tampered).to_bytes();
+        assert!(scheme
+            .verify_signer(&tampered, &proof.signature, signer)
+            .is_err());
+    }
+
+    #[test]
+    fn test_client_migration_validation() {
+        assert!(message().validate().is_ok());
+
+        let mut msg = message();
+        msg.to_version = msg.from_version;
+        assert!(msg.validate().is_err());
+        // the implementation must not be signed as migrated if it is unchanged
+        let ek = EnclaveKey::new().unwrap();
+        assert!(prove_commitment_with_scheme(
+            &ek,
+            ek.get_pubkey().as_address(),
+            SignatureScheme::default(),
+            msg.into()
+        )
+        .is_err());
+
+        let mut msg = message();
+        msg.client_id = String::new();
+        assert!(msg.validate().is_err());
+
+        let mut msg = message();
+        msg.post_state_id = StateID::from([0u8; 32]);
+        assert!(msg.validate().is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::encoder::EthABIEncoder;
+use crate::prelude::*;
+use crate::Error;
+use alloy_sol_types::{sol, SolValue};
+use core::fmt::Display;
+use core::time::Duration;
+use lcp_types::{nanos_to_duration, ClientParams, Time};
+use serde::{Deserialize, Serialize};
+
+/// ClientParamsProxyMessage is a notice that the operators have changed the parameters of a client
+///
+/// The parameters take effect in the verifications after the change, so verifiers can track
+/// the policy that the enclave applies to each client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientParamsProxyMessage {
+    pub client_id: String,
+    /// The changed parameters, where the ones that are None are unchanged
+    pub params: ClientParams,
+    /// The time when the parameters were changed in the enclave
+    pub timestamp: Time,
+}
+
+impl ClientParamsProxyMessage {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.client_id.is_empty() {
+            return Err(Error::empty_client_id());
+        }
+        if self.params.is_empty() {
+            return Err(Error::invalid_client_params(
+                "no parameter is changed".into(),
+            ));
+        }
+        if self.params.trusting_period == Some(Duration::ZERO)
+            || self.params.max_clock_drift == Some(Duration::ZERO)
+        {
+            return Err(Error::invalid_client_params(
+                "durations must be non-zero".into(),
+            ));
+        }
+        if let Some(specs) = self.params.proof_specs.as_ref() {
+            if specs.is_empty() || specs.iter().any(|spec| spec.is_empty()) {
+                return Err(Error::invalid_client_params(
+                    "proof specs must not be empty".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for ClientParamsProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ClientParams(client_id: {}, trusting_period: {:?}, max_clock_drift: {:?}, proof_specs: {:?}, timestamp: {})",
+            self.client_id,
+            self.params.trusting_period,
+            self.params.max_clock_drift,
+            self.params.proof_specs.as_ref().map(|specs| specs.len()),
+            self.timestamp.as_unix_timestamp_nanos()
+        )
+    }
+}
+
+sol! {
+    struct EthABIClientParamsProxyMessage {
+        string client_id;
+        // zero means unchanged
+        uint128 trusting_period;
+        // zero means unchanged
+        uint128 max_clock_drift;
+        // empty means unchanged
+        bytes[] proof_specs;
+        uint128 timestamp;
+    }
+}
+
+impl From<ClientParamsProxyMessage> for EthABIClientParamsProxyMessage {
+    fn from(msg: ClientParamsProxyMessage) -> Self {
+        Self {
+            client_id: msg.client_id,
+            trusting_period: encode_optional_duration(msg.params.trusting_period),
+            max_clock_drift: encode_optional_duration(msg.params.max_clock_drift),
+            proof_specs: msg.params.proof_specs.unwrap_or_default(),
+            timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+        }
+    }
+}
+
+impl TryFrom<EthABIClientParamsProxyMessage> for ClientParamsProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABIClientParamsProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            client_id: msg.client_id,
+            params: ClientParams {
+                trusting_period: decode_optional_duration(msg.trusting_period)?,
+                max_clock_drift: decode_optional_duration(msg.max_clock_drift)?,
+                proof_specs: if msg.proof_specs.is_empty() {
+                    None
+                } else {
+                    Some(msg.proof_specs)
+                },
+            },
+            timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+        })
+    }
+}
+
+impl EthABIEncoder for ClientParamsProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABIClientParamsProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABIClientParamsProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
+
+fn encode_optional_duration(duration: Option<Duration>) -> u128 {
+    duration.map_or(0, |d| d.as_nanos())
+}
+
+fn decode_optional_duration(nanos: u128) -> Result<Option<Duration>, Error> {
+    if nanos == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(nanos_to_duration(nanos)?))
+    }
+}
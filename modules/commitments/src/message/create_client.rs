@@ -0,0 +1,102 @@
+use crate::encoder::{EthABIEncoder, EthABIHeight};
+use crate::prelude::*;
+use crate::{Error, StateID};
+use alloy_sol_types::{private::B256, sol, SolValue};
+use core::fmt::Display;
+use lcp_types::{Height, Time};
+use serde::{Deserialize, Serialize};
+
+/// CreateClientProxyMessage attests the initial state of a client created in the enclave
+///
+/// Verifiers can check the genesis of the client against the counterparty chain,
+/// instead of trusting the state that the first `UpdateState` message starts from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateClientProxyMessage {
+    pub client_id: String,
+    pub height: Height,
+    /// The sha256 digest of the proto-encoded `Any` of the client state
+    pub client_state_digest: [u8; 32],
+    /// The sha256 digest of the proto-encoded `Any` of the consensus state
+    pub consensus_state_digest: [u8; 32],
+    pub state_id: StateID,
+    /// The time when the client was created in the enclave
+    pub timestamp: Time,
+}
+
+impl CreateClientProxyMessage {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.client_id.is_empty() {
+            return Err(Error::empty_client_id());
+        }
+        if self.height.is_zero() {
+            return Err(Error::zero_height());
+        }
+        if self.state_id.is_zero() {
+            return Err(Error::zero_state_id());
+        }
+        Ok(())
+    }
+}
+
+impl Display for CreateClientProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CreateClient(client_id: {}, height: {}, client_state_digest: 0x{}, consensus_state_digest: 0x{}, state_id: {}, timestamp: {})",
+            self.client_id,
+            self.height,
+            hex::encode(self.client_state_digest),
+            hex::encode(self.consensus_state_digest),
+            self.state_id,
+            self.timestamp.as_unix_timestamp_nanos()
+        )
+    }
+}
+
+sol! {
+    struct EthABICreateClientProxyMessage {
+        string client_id;
+        EthABIHeight height;
+        bytes32 client_state_digest;
+        bytes32 consensus_state_digest;
+        bytes32 state_id;
+        uint128 timestamp;
+    }
+}
+
+impl From<CreateClientProxyMessage> for EthABICreateClientProxyMessage {
+    fn from(msg: CreateClientProxyMessage) -> Self {
+        Self {
+            client_id: msg.client_id,
+            height: EthABIHeight::from(msg.height),
+            client_state_digest: B256::from_slice(&msg.client_state_digest),
+            consensus_state_digest: B256::from_slice(&msg.consensus_state_digest),
+            state_id: B256::from_slice(&msg.state_id.to_vec()),
+            timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+        }
+    }
+}
+
+impl TryFrom<EthABICreateClientProxyMessage> for CreateClientProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABICreateClientProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            client_id: msg.client_id,
+            height: msg.height.into(),
+            client_state_digest: msg.client_state_digest.0,
+            consensus_state_digest: msg.consensus_state_digest.0,
+            state_id: msg.state_id.as_slice().try_into()?,
+            timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+        })
+    }
+}
+
+impl EthABIEncoder for CreateClientProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABICreateClientProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABICreateClientProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
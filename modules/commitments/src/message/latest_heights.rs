@@ -0,0 +1,113 @@
+use crate::encoder::{decode_optional_time, encode_optional_time, EthABIEncoder, EthABIHeight};
+use crate::prelude::*;
+use crate::Error;
+use alloy_sol_types::{sol, SolValue};
+use core::fmt::Display;
+use lcp_types::{Height, Time};
+use serde::{Deserialize, Serialize};
+
+/// LatestHeightsProxyMessage is a summary of the latest heights of the clients in the enclave
+///
+/// A monitoring contract can compare it against the chains to flag a proxy that stops updating its clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatestHeightsProxyMessage {
+    /// The time when the summary was produced
+    pub timestamp: Time,
+    /// The latest heights sorted by client_id
+    pub clients: Vec<ClientLatestHeight>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientLatestHeight {
+    pub client_id: String,
+    pub height: Height,
+    /// The time when the consensus state at `height` was stored, if known
+    pub updated_at: Option<Time>,
+}
+
+impl LatestHeightsProxyMessage {
+    pub fn validate(&self) -> Result<(), Error> {
+        for pair in self.clients.windows(2) {
+            if pair[0].client_id >= pair[1].client_id {
+                return Err(Error::unsorted_latest_heights(pair[1].client_id.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for LatestHeightsProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "LatestHeights(timestamp: {}, clients: [{}])",
+            self.timestamp.as_unix_timestamp_nanos(),
+            self.clients
+                .iter()
+                .map(|c| format!("{}@{}", c.client_id, c.height))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+sol! {
+    struct EthABIClientLatestHeight {
+        string client_id;
+        EthABIHeight height;
+        uint128 updated_at;
+    }
+
+    struct EthABILatestHeightsProxyMessage {
+        uint128 timestamp;
+        EthABIClientLatestHeight[] clients;
+    }
+}
+
+impl From<LatestHeightsProxyMessage> for EthABILatestHeightsProxyMessage {
+    fn from(msg: LatestHeightsProxyMessage) -> Self {
+        Self {
+            timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+            clients: msg
+                .clients
+                .into_iter()
+                .map(|c| EthABIClientLatestHeight {
+                    client_id: c.client_id,
+                    height: c.height.into(),
+                    updated_at: encode_optional_time(c.updated_at),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<EthABILatestHeightsProxyMessage> for LatestHeightsProxyMessage {
+    type Error = Error;
+
+    fn try_from(msg: EthABILatestHeightsProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+            clients: msg
+                .clients
+                .into_iter()
+                .map(|c| {
+                    Ok(ClientLatestHeight {
+                        client_id: c.client_id,
+                        height: c.height.into(),
+                        updated_at: decode_optional_time(c.updated_at)?,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+}
+
+impl EthABIEncoder for LatestHeightsProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABILatestHeightsProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABILatestHeightsProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
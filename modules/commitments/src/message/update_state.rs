@@ -1,5 +1,8 @@
 use crate::context::ValidationContext;
-use crate::encoder::{EthABIEmittedState, EthABIEncoder, EthABIHeight};
+use crate::encoder::{
+    decode_optional_time, encode_optional_time, EthABIEmittedState, EthABIEncoder, EthABIHeight,
+};
+use crate::message::MESSAGE_SCHEMA_VERSION_1;
 use crate::prelude::*;
 use crate::{Error, StateID};
 use alloy_sol_types::{private::B256, sol, SolValue};
@@ -14,7 +17,11 @@ pub struct UpdateStateProxyMessage {
     pub prev_state_id: Option<StateID>,
     pub post_height: Height,
     pub post_state_id: StateID,
+    /// The height at which the client has been frozen, if any.
+    pub frozen_height: Option<Height>,
     pub timestamp: Time,
+    /// The time after which verifiers should reject the message, if any.
+    pub expires_at: Option<Time>,
     pub context: ValidationContext,
     pub emitted_states: Vec<EmittedState>,
 }
@@ -56,12 +63,21 @@ impl UpdateStateProxyMessage {
                 other.prev_height.unwrap_or_default()
             )));
         }
+        // messages that straddle a freeze event must not be aggregated
+        if self.frozen_height != other.frozen_height {
+            return Err(Error::frozen_height_mismatch(
+                self.frozen_height,
+                other.frozen_height,
+            ));
+        }
         Ok(Self {
             prev_height: self.prev_height,
             prev_state_id: self.prev_state_id,
             post_height: other.post_height,
             post_state_id: other.post_state_id,
+            frozen_height: other.frozen_height,
             timestamp: other.timestamp,
+            expires_at: earliest_expiry(self.expires_at, other.expires_at),
             context: self.context.aggregate(other.context)?,
             emitted_states: [self.emitted_states, other.emitted_states].concat(),
         })
@@ -72,18 +88,28 @@ impl Display for UpdateStateProxyMessage {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "UpdateClient(prev_height: {}, prev_state_id: {}, post_height: {}, post_state_id: {}, timestamp: {}, context: {}, emitted_states: [{}])",
+            "UpdateClient(prev_height: {}, prev_state_id: {}, post_height: {}, post_state_id: {}, frozen_height: {}, timestamp: {}, expires_at: {}, context: {}, emitted_states: [{}])",
             self.prev_height.as_ref().map_or("None".to_string(), |h| h.to_string()),
             self.prev_state_id.as_ref().map_or("None".to_string(), |id| id.to_string()),
             self.post_height,
             self.post_state_id,
+            self.frozen_height.as_ref().map_or("None".to_string(), |h| h.to_string()),
             self.timestamp.as_unix_timestamp_nanos(),
+            self.expires_at.map_or("None".to_string(), |t| t.as_unix_timestamp_nanos().to_string()),
             self.context,
             self.emitted_states.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
         )
     }
 }
 
+/// Returns the earlier of the two expiries, where `None` means the message never expires
+fn earliest_expiry(a: Option<Time>, b: Option<Time>) -> Option<Time> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
 /// Aggregate a list of messages into a single message
 pub fn aggregate_messages(
     messages: Vec<UpdateStateProxyMessage>,
@@ -107,7 +133,9 @@ sol! {
         bytes32 prev_state_id;
         EthABIHeight post_height;
         bytes32 post_state_id;
+        EthABIHeight frozen_height;
         uint128 timestamp;
+        uint128 expires_at;
         bytes context;
         EthABIEmittedState[] emitted_states;
     }
@@ -122,7 +150,9 @@ impl From<UpdateStateProxyMessage> for EthABIUpdateStateProxyMessage {
             ),
             post_height: msg.post_height.into(),
             post_state_id: B256::from_slice(msg.post_state_id.to_vec().as_slice()),
+            frozen_height: msg.frozen_height.into(),
             timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+            expires_at: encode_optional_time(msg.expires_at),
             context: msg.context.ethabi_encode(),
             emitted_states: msg
                 .emitted_states
@@ -142,7 +172,9 @@ impl TryFrom<EthABIUpdateStateProxyMessage> for UpdateStateProxyMessage {
                 .then_some(StateID::from(msg.prev_state_id.0)),
             post_height: msg.post_height.into(),
             post_state_id: msg.post_state_id.as_slice().try_into()?,
+            frozen_height: msg.frozen_height.into(),
             timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+            expires_at: decode_optional_time(msg.expires_at)?,
             context: ValidationContext::ethabi_decode(msg.context.as_slice())?,
             emitted_states: msg
                 .emitted_states
@@ -163,10 +195,67 @@ impl EthABIEncoder for UpdateStateProxyMessage {
     }
 }
 
+// the layout of the schema version 1, which has neither `frozen_height` nor `expires_at`
+sol! {
+    struct EthABIUpdateStateProxyMessageV1 {
+        EthABIHeight prev_height;
+        bytes32 prev_state_id;
+        EthABIHeight post_height;
+        bytes32 post_state_id;
+        uint128 timestamp;
+        bytes context;
+        EthABIEmittedState[] emitted_states;
+    }
+}
+
+impl UpdateStateProxyMessage {
+    /// Encodes the message in the layout of the schema version 1
+    ///
+    /// Returns an error if the message carries a frozen height or an expiry, which the layout cannot represent.
+    pub(crate) fn ethabi_encode_v1(self) -> Result<Vec<u8>, Error> {
+        if self.frozen_height.is_some() || self.expires_at.is_some() {
+            return Err(Error::not_representable_in_schema_version(
+                MESSAGE_SCHEMA_VERSION_1,
+                format!(
+                    "frozen_height and expires_at must be empty: frozen_height={:?} expires_at={:?}",
+                    self.frozen_height, self.expires_at
+                ),
+            ));
+        }
+        let msg = EthABIUpdateStateProxyMessage::from(self);
+        Ok(EthABIUpdateStateProxyMessageV1 {
+            prev_height: msg.prev_height,
+            prev_state_id: msg.prev_state_id,
+            post_height: msg.post_height,
+            post_state_id: msg.post_state_id,
+            timestamp: msg.timestamp,
+            context: msg.context,
+            emitted_states: msg.emitted_states,
+        }
+        .abi_encode())
+    }
+
+    pub(crate) fn ethabi_decode_v1(bz: &[u8]) -> Result<Self, Error> {
+        let msg = EthABIUpdateStateProxyMessageV1::abi_decode(bz, true)?;
+        EthABIUpdateStateProxyMessage {
+            prev_height: msg.prev_height,
+            prev_state_id: msg.prev_state_id,
+            post_height: msg.post_height,
+            post_state_id: msg.post_state_id,
+            frozen_height: Default::default(),
+            timestamp: msg.timestamp,
+            expires_at: 0,
+            context: msg.context,
+            emitted_states: msg.emitted_states,
+        }
+        .try_into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::TrustingPeriodContext;
+    use crate::{ProxyMessage, TrustingPeriodContext};
     use core::time::Duration;
 
     #[test]
@@ -177,7 +266,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -186,7 +277,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([2u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -195,7 +288,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -207,7 +302,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![EmittedState(
                     Height::new(1, 1),
@@ -219,7 +316,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([2u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![EmittedState(
                     Height::new(2, 2),
@@ -231,7 +330,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![
                     EmittedState(
@@ -253,7 +354,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: TrustingPeriodContext::new(
                     Duration::from_secs(1),
                     Duration::from_secs(2),
@@ -268,7 +371,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([2u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: TrustingPeriodContext::new(
                     Duration::from_secs(1),
                     Duration::from_secs(2),
@@ -283,7 +388,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: TrustingPeriodContext::new(
                     Duration::from_secs(1),
                     Duration::from_secs(2),
@@ -302,7 +409,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -311,7 +420,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([3u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -324,7 +435,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -333,12 +446,84 @@ mod tests {
                 prev_state_id: Some(StateID::from([2u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
             assert!(msg0.aggregate(msg1).is_err());
         }
+        {
+            // messages spanning a freeze event
+            let msg0 = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(1, 1)),
+                prev_state_id: Some(StateID::from([1u8; 32])),
+                post_height: Height::new(2, 2),
+                post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
+                timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
+                context: ValidationContext::default(),
+                emitted_states: vec![],
+            };
+            let msg1 = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(2, 2)),
+                prev_state_id: Some(StateID::from([2u8; 32])),
+                post_height: Height::new(3, 3),
+                post_state_id: StateID::from([3u8; 32]),
+                frozen_height: Some(Height::new(3, 3)),
+                timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
+                context: ValidationContext::default(),
+                emitted_states: vec![],
+            };
+            assert!(msg0.aggregate(msg1).is_err());
+        }
+        {
+            // the aggregated message expires at the earliest expiry
+            let expiry =
+                |secs: u64| Some((Time::unix_epoch() + Duration::from_secs(secs)).unwrap());
+            let msg0 = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(1, 1)),
+                prev_state_id: Some(StateID::from([1u8; 32])),
+                post_height: Height::new(2, 2),
+                post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
+                timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: expiry(200),
+                context: ValidationContext::default(),
+                emitted_states: vec![],
+            };
+            let msg1 = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(2, 2)),
+                prev_state_id: Some(StateID::from([2u8; 32])),
+                post_height: Height::new(3, 3),
+                post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
+                timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: expiry(100),
+                context: ValidationContext::default(),
+                emitted_states: vec![],
+            };
+            let msg2 = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(3, 3)),
+                prev_state_id: Some(StateID::from([3u8; 32])),
+                post_height: Height::new(4, 4),
+                post_state_id: StateID::from([4u8; 32]),
+                frozen_height: None,
+                timestamp: Time::from_unix_timestamp_nanos(3).unwrap(),
+                expires_at: None,
+                context: ValidationContext::default(),
+                emitted_states: vec![],
+            };
+            let msg = aggregate_messages(vec![msg0, msg1, msg2]).unwrap();
+            assert_eq!(msg.expires_at, expiry(100));
+
+            let msg = ProxyMessage::from(msg);
+            assert!(msg.check_expiry(expiry(100).unwrap()).is_ok());
+            assert!(msg.check_expiry(expiry(101).unwrap()).is_err());
+        }
         {
             // empty messages
             assert!(aggregate_messages(vec![]).is_err());
@@ -350,7 +535,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -363,7 +550,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(2, 2),
                 post_state_id: StateID::from([2u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -372,7 +561,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([2u8; 32])),
                 post_height: Height::new(3, 3),
                 post_state_id: StateID::from([3u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -381,7 +572,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([3u8; 32])),
                 post_height: Height::new(4, 4),
                 post_state_id: StateID::from([4u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(3).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -390,7 +583,9 @@ mod tests {
                 prev_state_id: Some(StateID::from([1u8; 32])),
                 post_height: Height::new(4, 4),
                 post_state_id: StateID::from([4u8; 32]),
+                frozen_height: None,
                 timestamp: Time::from_unix_timestamp_nanos(3).unwrap(),
+                expires_at: None,
                 context: ValidationContext::default(),
                 emitted_states: vec![],
             };
@@ -0,0 +1,127 @@
+use super::verify_membership::CommitmentPrefix;
+use crate::encoder::{decode_optional_time, encode_optional_time, EthABIEncoder, EthABIHeight};
+use crate::prelude::*;
+use crate::{Error, StateID};
+use alloy_sol_types::{private::B256, sol, SolValue};
+use core::fmt::Display;
+use lcp_types::{Height, Time};
+use serde::{Deserialize, Serialize};
+
+/// VerifyKeyValueProxyMessage attests the value of an arbitrary key in a store of the counterparty chain
+///
+/// Unlike `VerifyMembershipProxyMessage`, the key is not an ICS-24 path, so the message is not meant for IBC
+/// but for consumers that read general chain state, e.g. oracles and bridges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyKeyValueProxyMessage {
+    /// The store that contains the key, e.g. `bank` for Cosmos SDK chains
+    pub prefix: CommitmentPrefix,
+    pub key: Vec<u8>,
+    /// The keccak256 hash of the value, or None if the absence of the key is proven
+    pub value: Option<[u8; 32]>,
+    pub height: Height,
+    pub state_id: StateID,
+    /// The time after which verifiers should reject the message, if any.
+    pub expires_at: Option<Time>,
+    /// The timestamp of the stored consensus state at `height` that the proof was verified against
+    pub trusted_timestamp: Option<Time>,
+}
+
+impl Display for VerifyKeyValueProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VerifyKeyValue(prefix: {:?}, key: 0x{}, value: {}, height: {}, state_id: {}, expires_at: {}, trusted_timestamp: {})",
+            self.prefix,
+            hex::encode(&self.key),
+            self.value.map_or("None".to_string(), hex::encode),
+            self.height,
+            self.state_id,
+            self.expires_at.map_or("None".to_string(), |t| t.as_unix_timestamp_nanos().to_string()),
+            self.trusted_timestamp.map_or("None".to_string(), |t| t.as_unix_timestamp_nanos().to_string()),
+        )
+    }
+}
+
+sol! {
+    struct EthABIVerifyKeyValueProxyMessage {
+        bytes prefix;
+        bytes key;
+        bytes32 value;
+        EthABIHeight height;
+        bytes32 state_id;
+        uint128 expires_at;
+        uint128 trusted_timestamp;
+    }
+}
+
+impl From<VerifyKeyValueProxyMessage> for EthABIVerifyKeyValueProxyMessage {
+    fn from(msg: VerifyKeyValueProxyMessage) -> Self {
+        Self {
+            prefix: msg.prefix,
+            key: msg.key,
+            value: B256::from_slice(msg.value.unwrap_or_default().as_slice()),
+            height: EthABIHeight::from(msg.height),
+            state_id: B256::from_slice(&msg.state_id.to_vec()),
+            expires_at: encode_optional_time(msg.expires_at),
+            trusted_timestamp: encode_optional_time(msg.trusted_timestamp),
+        }
+    }
+}
+
+impl TryFrom<EthABIVerifyKeyValueProxyMessage> for VerifyKeyValueProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABIVerifyKeyValueProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prefix: msg.prefix,
+            key: msg.key,
+            value: (!msg.value.is_zero()).then_some(msg.value.0),
+            height: msg.height.into(),
+            state_id: msg.state_id.as_slice().try_into()?,
+            expires_at: decode_optional_time(msg.expires_at)?,
+            trusted_timestamp: decode_optional_time(msg.trusted_timestamp)?,
+        })
+    }
+}
+
+impl VerifyKeyValueProxyMessage {
+    pub fn new(
+        prefix: CommitmentPrefix,
+        key: Vec<u8>,
+        value: Option<[u8; 32]>,
+        height: Height,
+        state_id: StateID,
+    ) -> Self {
+        Self {
+            prefix,
+            key,
+            value,
+            height,
+            state_id,
+            expires_at: None,
+            trusted_timestamp: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.key.is_empty() {
+            return Err(Error::empty_key());
+        }
+        if self.height.is_zero() {
+            return Err(Error::zero_height());
+        }
+        if self.state_id.is_zero() {
+            return Err(Error::zero_state_id());
+        }
+        Ok(())
+    }
+}
+
+impl EthABIEncoder for VerifyKeyValueProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABIVerifyKeyValueProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABIVerifyKeyValueProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
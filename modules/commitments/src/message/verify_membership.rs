@@ -1,9 +1,10 @@
-use crate::encoder::{EthABIEncoder, EthABIHeight};
+use crate::encoder::{decode_optional_time, encode_optional_time, EthABIEncoder, EthABIHeight};
+use crate::message::MESSAGE_SCHEMA_VERSION_1;
 use crate::prelude::*;
 use crate::{Error, StateID};
 use alloy_sol_types::{private::B256, sol, SolValue};
 use core::fmt::Display;
-use lcp_types::Height;
+use lcp_types::{Height, Time};
 use serde::{Deserialize, Serialize};
 
 pub type CommitmentPrefix = Vec<u8>;
@@ -15,18 +16,27 @@ pub struct VerifyMembershipProxyMessage {
     pub value: Option<[u8; 32]>,
     pub height: Height,
     pub state_id: StateID,
+    /// The time after which verifiers should reject the message, if any.
+    pub expires_at: Option<Time>,
+    /// The timestamp of the stored consensus state at `height` that the proof was verified against
+    ///
+    /// Together with `height`, this discloses the trusted state so that verifiers can apply their own recency policy.
+    /// None if the light client does not disclose it.
+    pub trusted_timestamp: Option<Time>,
 }
 
 impl Display for VerifyMembershipProxyMessage {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "VerifyMembership(prefix: {:?}, path: {}, value: {}, height: {}, state_id: {})",
+            "VerifyMembership(prefix: {:?}, path: {}, value: {}, height: {}, state_id: {}, expires_at: {}, trusted_timestamp: {})",
             self.prefix,
             self.path,
             self.value.map_or("None".to_string(), hex::encode),
             self.height,
             self.state_id,
+            self.expires_at.map_or("None".to_string(), |t| t.as_unix_timestamp_nanos().to_string()),
+            self.trusted_timestamp.map_or("None".to_string(), |t| t.as_unix_timestamp_nanos().to_string()),
         )
     }
 }
@@ -38,6 +48,8 @@ sol! {
         bytes32 value;
         EthABIHeight height;
         bytes32 state_id;
+        uint128 expires_at;
+        uint128 trusted_timestamp;
     }
 }
 
@@ -49,6 +61,8 @@ impl From<VerifyMembershipProxyMessage> for EthABIVerifyMembershipProxyMessage {
             value: B256::from_slice(msg.value.unwrap_or_default().as_slice()),
             height: EthABIHeight::from(msg.height),
             state_id: B256::from_slice(&msg.state_id.to_vec()),
+            expires_at: encode_optional_time(msg.expires_at),
+            trusted_timestamp: encode_optional_time(msg.trusted_timestamp),
         }
     }
 }
@@ -62,6 +76,8 @@ impl TryFrom<EthABIVerifyMembershipProxyMessage> for VerifyMembershipProxyMessag
             value: (!msg.value.is_zero()).then_some(msg.value.0),
             height: msg.height.into(),
             state_id: msg.state_id.as_slice().try_into()?,
+            expires_at: decode_optional_time(msg.expires_at)?,
+            trusted_timestamp: decode_optional_time(msg.trusted_timestamp)?,
         })
     }
 }
@@ -80,6 +96,8 @@ impl VerifyMembershipProxyMessage {
             value,
             height,
             state_id,
+            expires_at: None,
+            trusted_timestamp: None,
         }
     }
 
@@ -106,3 +124,115 @@ impl EthABIEncoder for VerifyMembershipProxyMessage {
         EthABIVerifyMembershipProxyMessage::abi_decode(bz, true)?.try_into()
     }
 }
+
+// the layout of the schema version 1, which has neither `expires_at` nor `trusted_timestamp`
+sol! {
+    struct EthABIVerifyMembershipProxyMessageV1 {
+        bytes prefix;
+        bytes path;
+        bytes32 value;
+        EthABIHeight height;
+        bytes32 state_id;
+    }
+}
+
+impl VerifyMembershipProxyMessage {
+    /// Encodes the message in the layout of the schema version 1
+    ///
+    /// Returns an error if the message carries an expiry or a trusted timestamp, which the layout cannot represent.
+    pub(crate) fn ethabi_encode_v1(self) -> Result<Vec<u8>, Error> {
+        if self.expires_at.is_some() || self.trusted_timestamp.is_some() {
+            return Err(Error::not_representable_in_schema_version(
+                MESSAGE_SCHEMA_VERSION_1,
+                format!(
+                    "expires_at and trusted_timestamp must be empty: expires_at={:?} trusted_timestamp={:?}",
+                    self.expires_at, self.trusted_timestamp
+                ),
+            ));
+        }
+        let msg = EthABIVerifyMembershipProxyMessage::from(self);
+        Ok(EthABIVerifyMembershipProxyMessageV1 {
+            prefix: msg.prefix,
+            path: msg.path,
+            value: msg.value,
+            height: msg.height,
+            state_id: msg.state_id,
+        }
+        .abi_encode())
+    }
+
+    pub(crate) fn ethabi_decode_v1(bz: &[u8]) -> Result<Self, Error> {
+        let msg = EthABIVerifyMembershipProxyMessageV1::abi_decode(bz, true)?;
+        EthABIVerifyMembershipProxyMessage {
+            prefix: msg.prefix,
+            path: msg.path,
+            value: msg.value,
+            height: msg.height,
+            state_id: msg.state_id,
+            expires_at: 0,
+            trusted_timestamp: 0,
+        }
+        .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{EthABIHeaderedMessage, MESSAGE_HEADER_SIZE, MESSAGE_TYPE_STATE};
+    use crate::ProxyMessage;
+
+    fn test_message() -> VerifyMembershipProxyMessage {
+        VerifyMembershipProxyMessage::new(
+            b"ibc".to_vec(),
+            "clients/07-tendermint-0/clientState".to_string(),
+            Some([1u8; 32]),
+            Height::new(1, 2),
+            StateID::from([2u8; 32]),
+        )
+    }
+
+    #[test]
+    fn test_decode_v1_layout() {
+        let msg = test_message();
+        let mut header = [0u8; MESSAGE_HEADER_SIZE];
+        header[0..=1].copy_from_slice(&MESSAGE_SCHEMA_VERSION_1.to_be_bytes());
+        header[2..=3].copy_from_slice(&MESSAGE_TYPE_STATE.to_be_bytes());
+        // the encoding of the verifiers that have not been upgraded
+        let bz = EthABIHeaderedMessage {
+            header: header.into(),
+            message: EthABIVerifyMembershipProxyMessageV1 {
+                prefix: msg.prefix.clone(),
+                path: msg.path.clone().into_bytes(),
+                value: B256::from_slice(&msg.value.unwrap()),
+                height: msg.height.into(),
+                state_id: B256::from_slice(&msg.state_id.to_vec()),
+            }
+            .abi_encode(),
+        }
+        .abi_encode();
+
+        let decoded = ProxyMessage::from_bytes(&bz).unwrap();
+        assert_eq!(decoded, msg.clone().into());
+        assert_eq!(
+            decoded
+                .to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1)
+                .unwrap(),
+            bz
+        );
+    }
+
+    #[test]
+    fn test_trusted_timestamp() {
+        let msg = VerifyMembershipProxyMessage {
+            trusted_timestamp: Some(Time::from_unix_timestamp_nanos(1_000_000_000).unwrap()),
+            ..test_message()
+        };
+        let bz = ProxyMessage::from(msg.clone()).to_bytes();
+        assert_eq!(ProxyMessage::from_bytes(&bz).unwrap(), msg.clone().into());
+        // the layout of the version 1 cannot disclose the trusted timestamp
+        assert!(ProxyMessage::from(msg)
+            .to_versioned_bytes(MESSAGE_SCHEMA_VERSION_1)
+            .is_err());
+    }
+}
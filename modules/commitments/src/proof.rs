@@ -1,6 +1,6 @@
 use crate::{encoder::EthABIEncoder, prelude::*, Error, ProxyMessage};
 use alloy_sol_types::{private::Address as SolAddress, sol, SolValue};
-use crypto::Address;
+use crypto::{verify_signature_address, Address, SignatureScheme, Signer};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -8,6 +8,51 @@ pub struct CommitmentProof {
     pub message: Vec<u8>,
     pub signer: Address,
     pub signature: Vec<u8>,
+    /// Additional signatures over the same message by other enclave keys
+    ///
+    /// This is populated while the signer key is being rotated: the incoming key co-signs
+    /// the message so that verifiers which have already registered it can accept the proof.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_signatures: Vec<CoSignature>,
+    /// The binding of the proof to the request that produced it
+    ///
+    /// The host checks it to detect a result that was swapped with the result of another request.
+    /// It is not a part of the ethabi encoding, so downstream verifiers are unaffected.
+    #[serde(default)]
+    pub request_binding: Option<RequestBinding>,
+    /// The scheme that the message was signed with, which is configured per client
+    ///
+    /// The verifiers are configured with the scheme of the client, so it is not a part of the ethabi encoding.
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+}
+
+/// The domain separator of the bytes signed by a request binding
+pub(crate) const REQUEST_BINDING_DOMAIN: &[u8] = b"lcp-request-binding";
+
+/// RequestBinding is a signature by the signer of a proof over the digest of the request and the message
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RequestBinding {
+    /// keccak256 of the request, see `ecall_commands::Command::request_digest`
+    pub request_digest: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl RequestBinding {
+    fn sign_bytes(request_digest: &[u8; 32], message: &[u8]) -> Vec<u8> {
+        let mut bz =
+            Vec::with_capacity(REQUEST_BINDING_DOMAIN.len() + request_digest.len() + message.len());
+        bz.extend_from_slice(REQUEST_BINDING_DOMAIN);
+        bz.extend_from_slice(request_digest);
+        bz.extend_from_slice(message);
+        bz
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoSignature {
+    pub signer: Address,
+    pub signature: Vec<u8>,
 }
 
 impl CommitmentProof {
@@ -16,6 +61,9 @@ impl CommitmentProof {
             message,
             signer,
             signature,
+            co_signatures: Default::default(),
+            request_binding: None,
+            signature_scheme: Default::default(),
         }
     }
 
@@ -24,7 +72,84 @@ impl CommitmentProof {
             message,
             signer: Default::default(),
             signature: Default::default(),
+            co_signatures: Default::default(),
+            request_binding: None,
+            signature_scheme: Default::default(),
+        }
+    }
+
+    /// Signs the message with the given key and appends the signature as a co-signature
+    pub fn add_co_signature(&mut self, signer: &dyn Signer) -> Result<(), Error> {
+        let address = signer.pubkey()?.as_address();
+        let signature = signer.sign_with_scheme(self.signature_scheme, &self.message)?;
+        self.co_signatures.push(CoSignature {
+            signer: address,
+            signature,
+        });
+        Ok(())
+    }
+
+    /// Binds the proof to the request by signing its digest together with the message
+    ///
+    /// `signer` must be the key that signed the message.
+    pub fn bind_request(
+        &mut self,
+        signer: &dyn Signer,
+        request_digest: [u8; 32],
+    ) -> Result<(), Error> {
+        let signature = signer.sign(&RequestBinding::sign_bytes(&request_digest, &self.message))?;
+        self.request_binding = Some(RequestBinding {
+            request_digest,
+            signature,
+        });
+        Ok(())
+    }
+
+    /// Verify that the proof was produced for the request with `request_digest` by its signer
+    pub fn verify_request_binding(&self, request_digest: &[u8; 32]) -> Result<(), Error> {
+        let binding = self.request_binding.as_ref().ok_or_else(|| {
+            Error::request_binding_mismatch("the proof has no request binding".into())
+        })?;
+        if &binding.request_digest != request_digest {
+            return Err(Error::request_binding_mismatch(format!(
+                "request digest mismatch: expected=0x{} actual=0x{}",
+                hex::encode(request_digest),
+                hex::encode(binding.request_digest)
+            )));
+        }
+        if binding.signature.len() != 65 {
+            return Err(Error::request_binding_mismatch(format!(
+                "invalid signature length: {}",
+                binding.signature.len()
+            )));
         }
+        let recovered = verify_signature_address(
+            &RequestBinding::sign_bytes(request_digest, &self.message),
+            &binding.signature,
+        )?;
+        if recovered != self.signer {
+            return Err(Error::request_binding_mismatch(format!(
+                "signer mismatch: signer={} recovered={}",
+                self.signer, recovered
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the proofs signed by each co-signer
+    ///
+    /// The ethabi encoding only carries a single signature, so a relayer submits one of these
+    /// to a chain that only knows a co-signer key.
+    pub fn co_signed_proofs(&self) -> Vec<CommitmentProof> {
+        self.co_signatures
+            .iter()
+            .map(|cs| {
+                let mut proof =
+                    CommitmentProof::new(self.message.clone(), cs.signer, cs.signature.clone());
+                proof.signature_scheme = self.signature_scheme;
+                proof
+            })
+            .collect()
     }
 
     pub fn is_proven(&self) -> bool {
@@ -60,6 +185,9 @@ impl From<EthABICommitmentProof> for CommitmentProof {
             message: value.message,
             signer: Address(*value.signer.0),
             signature: value.signature,
+            co_signatures: Default::default(),
+            request_binding: None,
+            signature_scheme: Default::default(),
         }
     }
 }
@@ -73,3 +201,63 @@ impl From<CommitmentProof> for EthABICommitmentProof {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::EnclaveKey;
+
+    #[test]
+    fn test_request_binding() {
+        let ek = EnclaveKey::new().unwrap();
+        let signer = ek.get_pubkey().as_address();
+        let message = b"message".to_vec();
+        let mut proof = CommitmentProof::new(message.clone(), signer, ek.sign(&message).unwrap());
+        assert!(proof.verify_request_binding(&[1; 32]).is_err());
+
+        proof.bind_request(&ek, [1; 32]).unwrap();
+        assert!(proof.verify_request_binding(&[1; 32]).is_ok());
+        // the result of another request
+        assert!(proof.verify_request_binding(&[2; 32]).is_err());
+
+        // the binding is not transferable to another message
+        let mut swapped = proof.clone();
+        swapped.message = b"other".to_vec();
+        assert!(swapped.verify_request_binding(&[1; 32]).is_err());
+
+        // the binding must be signed by the signer of the proof
+        let other = EnclaveKey::new().unwrap();
+        let mut forged = proof.clone();
+        forged.bind_request(&other, [1; 32]).unwrap();
+        assert!(forged.verify_request_binding(&[1; 32]).is_err());
+    }
+
+    #[test]
+    fn test_co_signature_with_scheme() {
+        let ek = EnclaveKey::new().unwrap();
+        let co_ek = EnclaveKey::new().unwrap();
+        let scheme = SignatureScheme::new(
+            crypto::DigestAlgorithm::Sha256,
+            crypto::SignatureFormat::Compact,
+        );
+        let message = b"message".to_vec();
+        let mut proof = CommitmentProof::new(
+            message.clone(),
+            ek.get_pubkey().as_address(),
+            ek.sign_with_scheme(scheme, &message).unwrap(),
+        );
+        proof.signature_scheme = scheme;
+        proof.add_co_signature(&co_ek).unwrap();
+
+        // the co-signature is produced with the scheme of the proof
+        let co_signed = proof.co_signed_proofs().pop().unwrap();
+        assert_eq!(co_signed.signature_scheme, scheme);
+        scheme
+            .verify_signer(
+                &message,
+                &co_signed.signature,
+                co_ek.get_pubkey().as_address(),
+            )
+            .unwrap();
+    }
+}
@@ -1,19 +1,29 @@
 use crate::errors::Error;
 use crate::{prelude::*, CommitmentProof, ProxyMessage};
-use crypto::{Address, Signer};
+use crypto::{Address, SignatureScheme, Signer};
 
 /// Calculate the commitment of a message and sign it
 pub fn prove_commitment(
     signer: &dyn Signer,
     signer_address: Address,
     message: ProxyMessage,
+) -> Result<CommitmentProof, Error> {
+    prove_commitment_with_scheme(signer, signer_address, SignatureScheme::default(), message)
+}
+
+/// Calculate the commitment of a message and sign it with the signature scheme of the client
+pub fn prove_commitment_with_scheme(
+    signer: &dyn Signer,
+    signer_address: Address,
+    scheme: SignatureScheme,
+    message: ProxyMessage,
 ) -> Result<CommitmentProof, Error> {
     message.validate()?;
     let message_bytes = message.to_bytes();
-    let signature = signer.sign(&message_bytes).map_err(Error::crypto)?;
-    Ok(CommitmentProof::new(
-        message_bytes,
-        signer_address,
-        signature,
-    ))
+    let signature = signer
+        .sign_with_scheme(scheme, &message_bytes)
+        .map_err(Error::crypto)?;
+    let mut proof = CommitmentProof::new(message_bytes, signer_address, signature);
+    proof.signature_scheme = scheme;
+    Ok(proof)
 }
@@ -0,0 +1,280 @@
+//! Machine-readable schema of the proxy messages and the bytes signed over them.
+//!
+//! The layouts are derived from the ethabi types that the messages are encoded with,
+//! so the schema cannot diverge from the encoding of this release.
+use crate::context::{
+    EthABITrustingPeriodContext, EthABIValidationContext, VALIDATION_CONTEXT_HEADER_SIZE,
+    VALIDATION_CONTEXT_TYPE_EMPTY_EMPTY, VALIDATION_CONTEXT_TYPE_EMPTY_WITHIN_TRUSTING_PERIOD,
+};
+use crate::encoder::{EthABIEmittedState, EthABIHeight};
+use crate::message::{
+    EthABIClientLatestHeight, EthABIClientMigrationProxyMessage, EthABIClientParamsProxyMessage,
+    EthABICreateClientProxyMessage, EthABIHeaderedMessage, EthABILatestHeightsProxyMessage,
+    EthABIMisbehaviourProxyMessage, EthABIPrevState, EthABIUpdateStateProxyMessage,
+    EthABIVerifyKeyValueProxyMessage, EthABIVerifyMembershipProxyMessage, MESSAGE_HEADER_SIZE,
+    MESSAGE_SCHEMA_VERSION, MESSAGE_TYPE_CLIENT_MIGRATION, MESSAGE_TYPE_CLIENT_PARAMS,
+    MESSAGE_TYPE_CREATE_CLIENT, MESSAGE_TYPE_KEY_VALUE, MESSAGE_TYPE_LATEST_HEIGHTS,
+    MESSAGE_TYPE_MISBEHAVIOUR, MESSAGE_TYPE_STATE, MESSAGE_TYPE_UPDATE_STATE,
+};
+use crate::prelude::*;
+use crate::proof::{EthABICommitmentProof, REQUEST_BINDING_DOMAIN};
+use crate::PROXY_MESSAGE_TYPE_URL;
+use alloy_sol_types::SolStruct;
+use crypto::{DigestAlgorithm, SignatureFormat};
+use serde::{Deserialize, Serialize};
+
+/// MessageSchema describes the encoding of every proxy message and the signing domains of the commitments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSchema {
+    /// The version of the layouts, while a message that carries none of the fields added in it
+    /// is encoded in the layout of the version 1 with the version 1 in the header
+    pub schema_version: u16,
+    pub type_url: String,
+    /// The size of the header of `envelope`, which carries the schema version in bytes 0-1
+    /// and the message type in bytes 2-3, MSB first
+    pub header_size: usize,
+    /// The struct that `ProxyMessage::to_bytes()` ethabi-encodes, where `message` is the ethabi encoding of the message struct
+    pub envelope: String,
+    pub messages: Vec<MessageLayout>,
+    /// The struct that the `context` field of a message ethabi-encodes
+    pub context_envelope: String,
+    /// The size of the header of `context_envelope`
+    pub context_header_size: usize,
+    pub contexts: Vec<ContextLayout>,
+    /// The struct of the ethabi encoding of a `CommitmentProof`
+    pub proof: String,
+    /// All structs referenced by the layouts
+    pub structs: Vec<StructSchema>,
+    pub signing_domains: Vec<SigningDomain>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageLayout {
+    pub name: String,
+    pub message_type: u16,
+    pub root: String,
+}
+
+/// ContextLayout is a validation context, whose header carries the context type in bytes 0-1, MSB first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextLayout {
+    pub name: String,
+    pub context_type: u16,
+    /// The struct encoded in `context_bytes`, or None if the bytes are empty
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructSchema {
+    pub name: String,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    /// The solidity type of the field, which is another struct of the schema if it is not elementary
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// SigningDomain describes the bytes that a signature in a proof is computed over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningDomain {
+    pub name: String,
+    /// The concatenation of the parts that make the signed bytes
+    pub sign_bytes: Vec<String>,
+    /// Empty if the signed bytes have no domain separator
+    pub domain_separator: String,
+    /// The digest algorithms of the signed bytes, where the first is the default
+    pub digest_algorithms: Vec<String>,
+    /// The signature formats, where the first is the default
+    pub signature_formats: Vec<String>,
+}
+
+impl StructSchema {
+    fn of<T: SolStruct>() -> Self {
+        // the root type is `Name(type1 field1,type2 field2,...)`
+        let root = T::eip712_root_type();
+        let fields = root
+            .trim_start_matches(T::NAME)
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(|f| {
+                let (ty, name) = f.rsplit_once(' ').unwrap_or((f, ""));
+                FieldSchema {
+                    name: name.to_string(),
+                    ty: ty.to_string(),
+                }
+            })
+            .collect();
+        Self {
+            name: T::NAME.to_string(),
+            fields,
+        }
+    }
+}
+
+/// Returns the schema of the proxy messages of this release
+pub fn message_schema() -> MessageSchema {
+    let scheme_digests: Vec<String> = [DigestAlgorithm::Keccak256, DigestAlgorithm::Sha256]
+        .iter()
+        .map(|d| d.as_str().to_string())
+        .collect();
+    let scheme_formats: Vec<String> = [
+        SignatureFormat::Recoverable,
+        SignatureFormat::Ethereum,
+        SignatureFormat::Compact,
+    ]
+    .iter()
+    .map(|f| f.as_str().to_string())
+    .collect();
+    let signed_message = format!("abi.encode({})", EthABIHeaderedMessage::NAME);
+    MessageSchema {
+        schema_version: MESSAGE_SCHEMA_VERSION,
+        type_url: PROXY_MESSAGE_TYPE_URL.to_string(),
+        header_size: MESSAGE_HEADER_SIZE,
+        envelope: EthABIHeaderedMessage::NAME.to_string(),
+        messages: vec![
+            layout::<EthABIUpdateStateProxyMessage>("UpdateState", MESSAGE_TYPE_UPDATE_STATE),
+            layout::<EthABIVerifyMembershipProxyMessage>("VerifyMembership", MESSAGE_TYPE_STATE),
+            layout::<EthABIMisbehaviourProxyMessage>("Misbehaviour", MESSAGE_TYPE_MISBEHAVIOUR),
+            layout::<EthABILatestHeightsProxyMessage>("LatestHeights", MESSAGE_TYPE_LATEST_HEIGHTS),
+            layout::<EthABIVerifyKeyValueProxyMessage>("VerifyKeyValue", MESSAGE_TYPE_KEY_VALUE),
+            layout::<EthABICreateClientProxyMessage>("CreateClient", MESSAGE_TYPE_CREATE_CLIENT),
+            layout::<EthABIClientParamsProxyMessage>("ClientParams", MESSAGE_TYPE_CLIENT_PARAMS),
+            layout::<EthABIClientMigrationProxyMessage>(
+                "ClientMigration",
+                MESSAGE_TYPE_CLIENT_MIGRATION,
+            ),
+        ],
+        context_envelope: EthABIValidationContext::NAME.to_string(),
+        context_header_size: VALIDATION_CONTEXT_HEADER_SIZE,
+        contexts: vec![
+            ContextLayout {
+                name: "Empty".to_string(),
+                context_type: VALIDATION_CONTEXT_TYPE_EMPTY_EMPTY,
+                root: None,
+            },
+            ContextLayout {
+                name: "TrustingPeriod".to_string(),
+                context_type: VALIDATION_CONTEXT_TYPE_EMPTY_WITHIN_TRUSTING_PERIOD,
+                root: Some(EthABITrustingPeriodContext::NAME.to_string()),
+            },
+        ],
+        proof: EthABICommitmentProof::NAME.to_string(),
+        structs: vec![
+            StructSchema::of::<EthABIHeaderedMessage>(),
+            StructSchema::of::<EthABIHeight>(),
+            StructSchema::of::<EthABIEmittedState>(),
+            StructSchema::of::<EthABIPrevState>(),
+            StructSchema::of::<EthABIClientLatestHeight>(),
+            StructSchema::of::<EthABIUpdateStateProxyMessage>(),
+            StructSchema::of::<EthABIVerifyMembershipProxyMessage>(),
+            StructSchema::of::<EthABIMisbehaviourProxyMessage>(),
+            StructSchema::of::<EthABILatestHeightsProxyMessage>(),
+            StructSchema::of::<EthABIVerifyKeyValueProxyMessage>(),
+            StructSchema::of::<EthABICreateClientProxyMessage>(),
+            StructSchema::of::<EthABIClientParamsProxyMessage>(),
+            StructSchema::of::<EthABIClientMigrationProxyMessage>(),
+            StructSchema::of::<EthABIValidationContext>(),
+            StructSchema::of::<EthABITrustingPeriodContext>(),
+            StructSchema::of::<EthABICommitmentProof>(),
+        ],
+        signing_domains: vec![
+            SigningDomain {
+                name: "commitment".to_string(),
+                sign_bytes: vec![signed_message.clone()],
+                domain_separator: String::new(),
+                digest_algorithms: scheme_digests.clone(),
+                signature_formats: scheme_formats.clone(),
+            },
+            SigningDomain {
+                name: "co_signature".to_string(),
+                sign_bytes: vec![signed_message.clone()],
+                domain_separator: String::new(),
+                digest_algorithms: scheme_digests,
+                signature_formats: scheme_formats,
+            },
+            SigningDomain {
+                name: "request_binding".to_string(),
+                sign_bytes: vec![
+                    "domain_separator".to_string(),
+                    "request_digest(bytes32)".to_string(),
+                    signed_message,
+                ],
+                domain_separator: String::from_utf8_lossy(REQUEST_BINDING_DOMAIN).into_owned(),
+                digest_algorithms: vec![DigestAlgorithm::Keccak256.as_str().to_string()],
+                signature_formats: vec![SignatureFormat::Recoverable.as_str().to_string()],
+            },
+        ],
+    }
+}
+
+fn layout<T: SolStruct>(name: &str, message_type: u16) -> MessageLayout {
+    MessageLayout {
+        name: name.to_string(),
+        message_type,
+        root: T::NAME.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_schema() {
+        let schema = message_schema();
+        assert_eq!(schema.messages.len(), 8);
+        // every struct referenced by the layouts is described
+        let names: Vec<&str> = schema.structs.iter().map(|s| s.name.as_str()).collect();
+        for root in schema
+            .messages
+            .iter()
+            .map(|m| m.root.as_str())
+            .chain(schema.contexts.iter().filter_map(|c| c.root.as_deref()))
+            .chain([schema.envelope.as_str(), schema.proof.as_str()])
+        {
+            assert!(names.contains(&root), "missing struct: {}", root);
+        }
+        for field in schema.structs.iter().flat_map(|s| s.fields.iter()) {
+            let ty = field.ty.trim_end_matches("[]");
+            if ty.starts_with("EthABI") {
+                assert!(names.contains(&ty), "missing struct: {}", ty);
+            }
+        }
+
+        let height = schema
+            .structs
+            .iter()
+            .find(|s| s.name == "EthABIHeight")
+            .unwrap();
+        assert_eq!(
+            height.fields,
+            vec![
+                FieldSchema {
+                    name: "revision_number".into(),
+                    ty: "uint64".into()
+                },
+                FieldSchema {
+                    name: "revision_height".into(),
+                    ty: "uint64".into()
+                },
+            ]
+        );
+        let update_state = schema
+            .structs
+            .iter()
+            .find(|s| s.name == "EthABIUpdateStateProxyMessage")
+            .unwrap();
+        assert_eq!(update_state.fields.len(), 9);
+        assert_eq!(update_state.fields[8].ty, "EthABIEmittedState[]");
+        assert_eq!(
+            schema.signing_domains[2].domain_separator,
+            "lcp-request-binding"
+        );
+    }
+}
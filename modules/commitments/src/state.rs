@@ -60,6 +60,11 @@ pub fn gen_state_id_from_any(
     gen_state_id_from_bytes(&buf)
 }
 
+/// Returns the sha256 digest of the proto-encoded `Any` of a state
+pub fn gen_state_digest(any_state: &Any) -> [u8; 32] {
+    sha2::Sha256::digest(any_state.encode_to_vec()).into()
+}
+
 pub fn gen_state_id_from_bytes(bz: &[u8]) -> Result<StateID, Error> {
     let mut result: [u8; STATE_ID_SIZE] = Default::default();
     let h = sha2::Sha256::digest(bz).to_vec();
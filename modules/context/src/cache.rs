@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use alloc::collections::VecDeque;
+use lcp_types::{Any, ClientId, Height};
+use spin::Mutex;
+
+/// Default number of decoded states kept by a `StateCache`
+pub const DEFAULT_STATE_CACHE_CAPACITY: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum StateKey {
+    ClientState(ClientId),
+    ConsensusState(ClientId, Height),
+}
+
+/// A small LRU cache of decoded client/consensus states.
+///
+/// Each entry is tagged with the store revision it was read at. Every command that changes the store,
+/// including the updates that freeze a client, advances the revision, so entries read before a change
+/// can never be hit again and are eventually evicted.
+pub(crate) struct StateCache {
+    capacity: usize,
+    entries: VecDeque<(StateKey, u64, Any)>,
+}
+
+impl StateCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&mut self, key: &StateKey, revision: u64) -> Option<Any> {
+        let pos = self
+            .entries
+            .iter()
+            .position(|(k, r, _)| *r == revision && k == key)?;
+        let entry = self.entries.remove(pos)?;
+        let value = entry.2.clone();
+        self.entries.push_back(entry);
+        Some(value)
+    }
+
+    pub fn put(&mut self, key: StateKey, revision: u64, value: Any) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(pos) = self.entries.iter().position(|(k, _, _)| k == &key) {
+            self.entries.remove(pos);
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, revision, value));
+    }
+}
+
+/// A `StateCache` that outlives the `Context` of a command
+///
+/// The enclave keeps it in a static so that the states decoded by a command are reused by the following ones.
+pub struct SharedStateCache {
+    capacity: usize,
+    inner: Mutex<Option<StateCache>>,
+}
+
+impl SharedStateCache {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &StateKey, revision: u64) -> Option<Any> {
+        self.inner.lock().as_mut()?.get(key, revision)
+    }
+
+    pub(crate) fn put(&self, key: StateKey, revision: u64, value: Any) {
+        self.inner
+            .lock()
+            .get_or_insert_with(|| StateCache::new(self.capacity))
+            .put(key, revision, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_key(n: u64) -> StateKey {
+        StateKey::ClientState(ClientId::new("07-tendermint", n).unwrap())
+    }
+
+    fn any(n: u8) -> Any {
+        Any::new("/test".into(), vec![n])
+    }
+
+    #[test]
+    fn test_state_cache_revision() {
+        let mut cache = StateCache::new(2);
+        cache.put(client_key(0), 0, any(0));
+        assert_eq!(cache.get(&client_key(0), 0), Some(any(0)));
+        assert_eq!(cache.get(&client_key(0), 1), None);
+    }
+
+    #[test]
+    fn test_state_cache_eviction() {
+        let mut cache = StateCache::new(2);
+        cache.put(client_key(0), 0, any(0));
+        cache.put(client_key(1), 0, any(1));
+        // touch key 0 so that key 1 becomes the least recently used entry
+        assert!(cache.get(&client_key(0), 0).is_some());
+        cache.put(client_key(2), 0, any(2));
+        assert_eq!(cache.get(&client_key(1), 0), None);
+        assert_eq!(cache.get(&client_key(0), 0), Some(any(0)));
+        assert_eq!(cache.get(&client_key(2), 0), Some(any(2)));
+    }
+}
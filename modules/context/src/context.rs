@@ -1,9 +1,10 @@
+use crate::cache::{SharedStateCache, StateKey};
 use crate::prelude::*;
 use crypto::Signer;
-use lcp_types::Time;
+use lcp_types::{Any, ClientId, Height, Time};
 use light_client::{
-    ClientKeeper, ClientReader, HostClientKeeper, HostClientReader, HostContext,
-    LightClientResolver,
+    ClientKeeper, ClientReader, Error as LightClientError, HostClientKeeper, HostClientReader,
+    HostContext, LightClientResolver,
 };
 use store::KVStore;
 
@@ -11,7 +12,15 @@ pub struct Context<'k, R: LightClientResolver, S: KVStore, K: Signer> {
     lc_registry: R,
     store: S,
     ek: &'k K,
+    /// the incoming key that co-signs commitments while `ek` is being rotated out
+    co_ek: Option<&'k K>,
+    /// the keys of the other signers of the messages to aggregate
+    constituent_eks: &'k [K],
     current_timestamp: Option<Time>,
+    /// the cache of decoded states and the revision of `store` that the command reads
+    state_cache: Option<(&'k SharedStateCache, u64)>,
+    /// set on the first write to `store`, after which the reads no longer reflect the revision
+    written: bool,
 }
 
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> Context<'k, R, S, K> {
@@ -20,10 +29,21 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> Context<'k, R, S, K> {
             lc_registry,
             store,
             ek,
+            co_ek: None,
+            constituent_eks: &[],
             current_timestamp: None,
+            state_cache: None,
+            written: false,
         }
     }
 
+    /// Sets the cache of decoded states that is shared with the contexts of other commands
+    ///
+    /// `revision` must identify the committed state of `store`, and must change whenever the store is changed.
+    pub fn set_state_cache(&mut self, cache: &'k SharedStateCache, revision: u64) {
+        self.state_cache = Some((cache, revision));
+    }
+
     pub fn set_timestamp(&mut self, timestamp: Time) {
         self.current_timestamp = Some(timestamp)
     }
@@ -31,10 +51,30 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> Context<'k, R, S, K> {
     pub fn get_enclave_key(&self) -> &'k dyn Signer {
         self.ek
     }
+
+    pub fn set_co_enclave_key(&mut self, co_ek: &'k K) {
+        self.co_ek = Some(co_ek)
+    }
+
+    pub fn get_co_enclave_key(&self) -> Option<&'k dyn Signer> {
+        self.co_ek.map(|co_ek| co_ek as &dyn Signer)
+    }
+
+    pub fn set_constituent_enclave_keys(&mut self, eks: &'k [K]) {
+        self.constituent_eks = eks
+    }
+
+    pub fn get_constituent_enclave_keys(&self) -> Vec<&'k dyn Signer> {
+        self.constituent_eks
+            .iter()
+            .map(|ek| ek as &dyn Signer)
+            .collect()
+    }
 }
 
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> KVStore for Context<'k, R, S, K> {
     fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.written = true;
         self.store.set(key, value)
     }
 
@@ -43,6 +83,7 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> KVStore for Context<'k,
     }
 
     fn remove(&mut self, key: &[u8]) {
+        self.written = true;
         self.store.remove(key)
     }
 }
@@ -53,7 +94,43 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> HostContext for Context<
     }
 }
 
-impl<'k, R: LightClientResolver, S: KVStore, K: Signer> ClientReader for Context<'k, R, S, K> {}
+impl<'k, R: LightClientResolver, S: KVStore, K: Signer> Context<'k, R, S, K> {
+    fn cached_state(
+        &self,
+        key: StateKey,
+        read: impl FnOnce(&StoreReader<S>) -> Result<Any, LightClientError>,
+    ) -> Result<Any, LightClientError> {
+        let (cache, revision) = match self.state_cache {
+            // the uncommitted writes of the command must neither be served from nor leak into the cache
+            Some(state_cache) if !self.written => state_cache,
+            _ => return read(&StoreReader(&self.store)),
+        };
+        if let Some(state) = cache.get(&key, revision) {
+            return Ok(state);
+        }
+        let state = read(&StoreReader(&self.store))?;
+        cache.put(key, revision, state.clone());
+        Ok(state)
+    }
+}
+
+impl<'k, R: LightClientResolver, S: KVStore, K: Signer> ClientReader for Context<'k, R, S, K> {
+    fn client_state(&self, client_id: &ClientId) -> Result<Any, LightClientError> {
+        self.cached_state(StateKey::ClientState(client_id.clone()), |r| {
+            r.client_state(client_id)
+        })
+    }
+
+    fn consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: &Height,
+    ) -> Result<Any, LightClientError> {
+        self.cached_state(StateKey::ConsensusState(client_id.clone(), *height), |r| {
+            r.consensus_state(client_id, height)
+        })
+    }
+}
 
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> ClientKeeper for Context<'k, R, S, K> {}
 
@@ -64,10 +141,101 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> HostClientKeeper for Con
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> LightClientResolver
     for Context<'k, R, S, K>
 {
-    fn get_light_client(
+    fn get_versioned_light_client(
         &self,
         type_url: &str,
-    ) -> Option<&alloc::boxed::Box<dyn light_client::LightClient>> {
-        self.lc_registry.get_light_client(type_url)
+        version: Option<u32>,
+    ) -> Option<(u32, &alloc::boxed::Box<dyn light_client::LightClient>)> {
+        self.lc_registry
+            .get_versioned_light_client(type_url, version)
+    }
+}
+
+/// A read-only view of the underlying store that uses the default `ClientReader` decoding
+struct StoreReader<'a, S: KVStore>(&'a S);
+
+impl<'a, S: KVStore> KVStore for StoreReader<'a, S> {
+    fn set(&mut self, _key: Vec<u8>, _value: Vec<u8>) {
+        unreachable!("StoreReader is read-only")
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key)
+    }
+
+    fn remove(&mut self, _key: &[u8]) {
+        unreachable!("StoreReader is read-only")
+    }
+}
+
+impl<'a, S: KVStore> ClientReader for StoreReader<'a, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use crypto::NopSigner;
+    use light_client::MapLightClientRegistry;
+
+    #[derive(Clone, Default)]
+    struct TestStore(Rc<RefCell<BTreeMap<Vec<u8>, Vec<u8>>>>);
+
+    impl KVStore for TestStore {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.0.borrow_mut().insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.0.borrow().get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.0.borrow_mut().remove(key);
+        }
+    }
+
+    fn any(n: u8) -> Any {
+        Any::new("/test".into(), vec![n])
+    }
+
+    #[test]
+    fn test_state_cache_across_contexts() {
+        let cache = SharedStateCache::new(DEFAULT_STATE_CACHE_CAPACITY);
+        let store = TestStore::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        let new_ctx = |revision: Option<u64>| {
+            let mut ctx = Context::new(MapLightClientRegistry::new(), store.clone(), &NopSigner);
+            if let Some(revision) = revision {
+                ctx.set_state_cache(&cache, revision);
+            }
+            ctx
+        };
+
+        new_ctx(None)
+            .store_any_client_state(client_id.clone(), any(0))
+            .unwrap();
+        assert_eq!(new_ctx(Some(1)).client_state(&client_id).unwrap(), any(0));
+
+        // change the store without advancing the revision, so that only a hit returns the old state
+        new_ctx(None)
+            .store_any_client_state(client_id.clone(), any(1))
+            .unwrap();
+        assert_eq!(new_ctx(Some(1)).client_state(&client_id).unwrap(), any(0));
+        assert_eq!(new_ctx(None).client_state(&client_id).unwrap(), any(1));
+
+        // a new revision misses the entries read at the previous one
+        assert_eq!(new_ctx(Some(2)).client_state(&client_id).unwrap(), any(1));
+
+        // a context that has written reads its own writes and does not populate the cache
+        let mut ctx = new_ctx(Some(3));
+        ctx.store_any_client_state(client_id.clone(), any(2))
+            .unwrap();
+        assert_eq!(ctx.client_state(&client_id).unwrap(), any(2));
+        new_ctx(None)
+            .store_any_client_state(client_id.clone(), any(3))
+            .unwrap();
+        assert_eq!(new_ctx(Some(3)).client_state(&client_id).unwrap(), any(3));
     }
 }
@@ -19,5 +19,7 @@ mod prelude {
     pub use core::iter::FromIterator;
 }
 
+pub use crate::cache::{SharedStateCache, DEFAULT_STATE_CACHE_CAPACITY};
 pub use crate::context::Context;
+mod cache;
 mod context;
@@ -46,6 +46,14 @@ define_error! {
             format_args!("invalid address length: expected=20 actual={}", e.length)
         },
 
+        InvalidReportData
+        {
+            descr: String,
+        }
+        |e| {
+            format_args!("invalid report data: descr={}", e.descr)
+        },
+
         InsufficientSecretKeySize
         {
             path: String,
@@ -65,6 +73,22 @@ define_error! {
             format_args!("unexpected signer: expected={:?} actual={:?}", e.expected, e.actual)
         },
 
+        InvalidSignature
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid signature: descr={}", e.descr)
+        },
+
+        InvalidSignatureScheme
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid signature scheme: descr={}", e.descr)
+        },
+
         NopSigner
         |_| { "nop signer doesn't support any operations" },
 
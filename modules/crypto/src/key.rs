@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::{Error, Keccak256, Signer, Verifier};
+use crate::{Error, Signer, Verifier};
 use alloc::fmt;
 use core::fmt::Display;
 use libsecp256k1::PublicKeyFormat;
@@ -42,6 +42,13 @@ impl EnclaveKey {
         Ok(Self { secret_key })
     }
 
+    /// Create a key from the given secret. This is intended for tests and tools that need a fixed key.
+    pub fn from_secret(secret: &[u8; SECRET_KEY_SIZE]) -> Result<Self, Error> {
+        Ok(Self {
+            secret_key: SecretKey::parse(secret).map_err(Error::secp256k1)?,
+        })
+    }
+
     pub fn get_privkey(&self) -> [u8; SECRET_KEY_SIZE] {
         self.secret_key.serialize()
     }
@@ -116,9 +123,7 @@ impl EnclavePublicKey {
     }
 
     pub fn as_report_data(&self) -> sgx_report_data_t {
-        let mut report_data = sgx_report_data_t::default();
-        report_data.d[..20].copy_from_slice(&self.as_address().0);
-        report_data
+        crate::report_data_from_address(&self.as_address())
     }
 
     pub fn as_address(&self) -> Address {
@@ -129,7 +134,7 @@ impl EnclavePublicKey {
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Address(pub [u8; 20]);
 
 impl Address {
@@ -171,9 +176,9 @@ impl TryFrom<&[u8]> for Address {
 }
 
 impl Signer for EnclaveKey {
-    fn sign(&self, bz: &[u8]) -> Result<Vec<u8>, Error> {
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<Vec<u8>, Error> {
         let mut s = Scalar::default();
-        let _ = s.set_b32(&bz.keccak256());
+        let _ = s.set_b32(digest);
         let (sig, rid) = libsecp256k1::sign(&Message(s), &self.secret_key);
         let mut ret = vec![0; 65];
         ret[..64].copy_from_slice(&sig.serialize());
@@ -199,12 +204,20 @@ impl Verifier for EnclavePublicKey {
 pub fn verify_signature(sign_bytes: &[u8], signature: &[u8]) -> Result<EnclavePublicKey, Error> {
     assert!(signature.len() == 65);
 
-    let sign_hash = keccak256(sign_bytes);
+    recover_pubkey(&keccak256(sign_bytes), &signature[..64], signature[64])
+}
+
+/// Recovers the public key from a signature `r || s` over the digest with the recovery id
+pub(crate) fn recover_pubkey(
+    digest: &[u8; 32],
+    signature: &[u8],
+    recovery_id: u8,
+) -> Result<EnclavePublicKey, Error> {
     let mut s = Scalar::default();
-    let _ = s.set_b32(&sign_hash);
+    let _ = s.set_b32(digest);
 
-    let sig = Signature::parse_overflowing_slice(&signature[..64]).map_err(Error::secp256k1)?;
-    let rid = RecoveryId::parse(signature[64]).map_err(Error::secp256k1)?;
+    let sig = Signature::parse_overflowing_slice(signature).map_err(Error::secp256k1)?;
+    let rid = RecoveryId::parse(recovery_id).map_err(Error::secp256k1)?;
     let signer = libsecp256k1::recover(&Message(s), &sig, &rid).map_err(Error::secp256k1)?;
     Ok(EnclavePublicKey(signer))
 }
@@ -247,7 +260,7 @@ impl SealedEnclaveKey {
 }
 
 // modified copy from sgx_tseal/src/internal.rs
-const fn calc_raw_sealed_data_size(add_mac_txt_size: u32, encrypt_txt_size: u32) -> u32 {
+pub(crate) const fn calc_raw_sealed_data_size(add_mac_txt_size: u32, encrypt_txt_size: u32) -> u32 {
     let max = u32::MAX;
     let sealed_data_size = core::mem::size_of::<sgx_sealed_data_t>() as u32;
 
@@ -273,7 +286,7 @@ impl Signer for NopSigner {
     fn pubkey(&self) -> Result<EnclavePublicKey, Error> {
         Err(Error::nop_signer())
     }
-    fn sign(&self, _: &[u8]) -> Result<Vec<u8>, Error> {
+    fn sign_digest(&self, _: &[u8; 32]) -> Result<Vec<u8>, Error> {
         Err(Error::nop_signer())
     }
 }
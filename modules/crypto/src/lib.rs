@@ -24,10 +24,17 @@ pub use crate::key::{
     SealedEnclaveKey,
 };
 pub use errors::Error;
+pub use report_data::{
+    address_from_report_data, report_data_from_address, verify_report_data, REPORT_DATA_SIZE,
+    REPORT_DATA_VERSION,
+};
+pub use scheme::{DigestAlgorithm, SignatureFormat, SignatureScheme};
 pub use traits::{Keccak256, SealingKey, Signer, Verifier};
 
 mod errors;
 mod key;
+mod report_data;
+mod scheme;
 mod traits;
 
 #[cfg(feature = "sgx")]
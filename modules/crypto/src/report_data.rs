@@ -0,0 +1,93 @@
+use crate::prelude::*;
+use crate::{Address, EnclavePublicKey, Error};
+use sgx_types::sgx_report_data_t;
+
+/// The size of the report data of an SGX report
+pub const REPORT_DATA_SIZE: usize = 64;
+
+/// The version of the layout of the report data that binds an enclave key
+///
+/// The layout of version 0 is `address (20 bytes) || version (1 byte) || zero padding (43 bytes)`,
+/// which is what every enclave key has been attested with so far.
+pub const REPORT_DATA_VERSION: u8 = 0;
+
+const VERSION_OFFSET: usize = 20;
+
+/// Returns the report data that binds the enclave key of the address
+pub fn report_data_from_address(address: &Address) -> sgx_report_data_t {
+    let mut report_data = sgx_report_data_t::default();
+    report_data.d[..VERSION_OFFSET].copy_from_slice(&address.0);
+    report_data.d[VERSION_OFFSET] = REPORT_DATA_VERSION;
+    report_data
+}
+
+/// Extract the enclave key address from the report data of a quote
+///
+/// Unlike taking the first 20 bytes, this rejects report data that does not follow the layout of
+/// `REPORT_DATA_VERSION` exactly, i.e. of another size, another version or with non-zero padding.
+pub fn address_from_report_data(report_data: &[u8]) -> Result<Address, Error> {
+    if report_data.len() != REPORT_DATA_SIZE {
+        return Err(Error::invalid_report_data(format!(
+            "unexpected size: expected={} actual={}",
+            REPORT_DATA_SIZE,
+            report_data.len()
+        )));
+    }
+    let version = report_data[VERSION_OFFSET];
+    if version != REPORT_DATA_VERSION {
+        return Err(Error::invalid_report_data(format!(
+            "unsupported version: expected={} actual={}",
+            REPORT_DATA_VERSION, version
+        )));
+    }
+    if report_data[VERSION_OFFSET + 1..].iter().any(|b| *b != 0) {
+        return Err(Error::invalid_report_data(
+            "the padding is not zero".to_string(),
+        ));
+    }
+    Address::try_from(&report_data[..VERSION_OFFSET])
+}
+
+/// Verify that the report data binds the public key
+pub fn verify_report_data(pubkey: &EnclavePublicKey, report_data: &[u8]) -> Result<(), Error> {
+    let expected = pubkey.as_address();
+    let actual = address_from_report_data(report_data)?;
+    if actual != expected {
+        return Err(Error::invalid_report_data(format!(
+            "the report data binds another key: expected={} actual={}",
+            expected, actual
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnclaveKey;
+
+    #[test]
+    fn test_report_data() {
+        let pubkey = EnclaveKey::from_secret(&[1u8; 32]).unwrap().get_pubkey();
+        let report_data = pubkey.as_report_data();
+        assert_eq!(
+            address_from_report_data(&report_data.d).unwrap(),
+            pubkey.as_address()
+        );
+        verify_report_data(&pubkey, &report_data.d).unwrap();
+
+        let other = EnclaveKey::from_secret(&[2u8; 32]).unwrap().get_pubkey();
+        assert!(verify_report_data(&other, &report_data.d).is_err());
+
+        // truncated
+        assert!(address_from_report_data(&report_data.d[..20]).is_err());
+        // unknown version
+        let mut data = report_data.d;
+        data[VERSION_OFFSET] = 1;
+        assert!(address_from_report_data(&data).is_err());
+        // non-zero padding
+        let mut data = report_data.d;
+        data[REPORT_DATA_SIZE - 1] = 1;
+        assert!(address_from_report_data(&data).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::key::recover_pubkey;
+use crate::prelude::*;
+use crate::{Address, Error, Keccak256};
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+/// DigestAlgorithm is the hash function of the bytes that an enclave key signs
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestAlgorithm {
+    /// keccak256, which EVM verifiers compute natively
+    #[default]
+    Keccak256,
+    /// sha256, which Cosmos SDK verifiers compute natively
+    Sha256,
+}
+
+impl DigestAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keccak256 => "keccak256",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    pub fn digest(&self, bz: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Keccak256 => bz.keccak256(),
+            Self::Sha256 => sha2::Sha256::digest(bz).into(),
+        }
+    }
+}
+
+impl Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keccak256" => Ok(Self::Keccak256),
+            "sha256" => Ok(Self::Sha256),
+            _ => Err(Error::invalid_signature_scheme(format!(
+                "unknown digest algorithm: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// SignatureFormat is the encoding of a secp256k1 signature by an enclave key
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureFormat {
+    /// `r || s || v` where `v` is the recovery id, which is 0 or 1
+    #[default]
+    Recoverable,
+    /// `r || s || v` where `v` is the recovery id plus 27, as `ecrecover` of the EVM takes
+    Ethereum,
+    /// `r || s` without the recovery id, as the secp256k1 verifier of the Cosmos SDK takes
+    Compact,
+}
+
+impl SignatureFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recoverable => "recoverable",
+            Self::Ethereum => "ethereum",
+            Self::Compact => "compact",
+        }
+    }
+
+    pub fn signature_len(&self) -> usize {
+        match self {
+            Self::Recoverable | Self::Ethereum => 65,
+            Self::Compact => 64,
+        }
+    }
+
+    /// Converts a recoverable signature returned by `Signer::sign_digest` into this format
+    pub fn encode(&self, mut signature: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if signature.len() != 65 {
+            return Err(Error::invalid_signature(format!(
+                "unexpected recoverable signature length: {}",
+                signature.len()
+            )));
+        }
+        match self {
+            Self::Recoverable => {}
+            Self::Ethereum => signature[64] += 27,
+            Self::Compact => signature.truncate(64),
+        }
+        Ok(signature)
+    }
+
+    /// Returns the candidates of the recovery id of a signature in this format
+    fn recovery_ids(&self, signature: &[u8]) -> Result<Vec<u8>, Error> {
+        if signature.len() != self.signature_len() {
+            return Err(Error::invalid_signature(format!(
+                "unexpected signature length: format={} expected={} actual={}",
+                self,
+                self.signature_len(),
+                signature.len()
+            )));
+        }
+        match self {
+            Self::Recoverable => Ok(vec![signature[64]]),
+            Self::Ethereum => signature[64]
+                .checked_sub(27)
+                .map(|v| vec![v])
+                .ok_or_else(|| Error::invalid_signature(format!("invalid v: {}", signature[64]))),
+            // the signer is one of the two keys recovered from the signature
+            Self::Compact => Ok(vec![0, 1]),
+        }
+    }
+}
+
+impl Display for SignatureFormat {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for SignatureFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recoverable" => Ok(Self::Recoverable),
+            "ethereum" => Ok(Self::Ethereum),
+            "compact" => Ok(Self::Compact),
+            _ => Err(Error::invalid_signature_scheme(format!(
+                "unknown signature format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// SignatureScheme determines how an enclave key signs the commitments of a client
+///
+/// The default is keccak256 with a recoverable signature, which is the scheme of the clients
+/// created without one.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureScheme {
+    pub digest: DigestAlgorithm,
+    pub format: SignatureFormat,
+}
+
+impl SignatureScheme {
+    pub fn new(digest: DigestAlgorithm, format: SignatureFormat) -> Self {
+        Self { digest, format }
+    }
+
+    /// Verify that the signature over `sign_bytes` was produced by the key of `signer`
+    pub fn verify_signer(
+        &self,
+        sign_bytes: &[u8],
+        signature: &[u8],
+        signer: Address,
+    ) -> Result<(), Error> {
+        let digest = self.digest.digest(sign_bytes);
+        for rid in self.format.recovery_ids(signature)? {
+            if recover_pubkey(&digest, &signature[..64], rid)
+                .map_or(false, |pk| pk.as_address() == signer)
+            {
+                return Ok(());
+            }
+        }
+        Err(Error::invalid_signature(format!(
+            "signature is not produced by the signer: scheme={} signer={}",
+            self, signer
+        )))
+    }
+}
+
+impl Display for SignatureScheme {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}-{}", self.digest, self.format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnclaveKey, Signer};
+
+    #[test]
+    fn test_signature_scheme() {
+        let ek = EnclaveKey::new().unwrap();
+        let signer = ek.get_pubkey().as_address();
+        let other = EnclaveKey::new().unwrap().get_pubkey().as_address();
+        let msg = b"message";
+
+        for digest in [DigestAlgorithm::Keccak256, DigestAlgorithm::Sha256] {
+            for format in [
+                SignatureFormat::Recoverable,
+                SignatureFormat::Ethereum,
+                SignatureFormat::Compact,
+            ] {
+                let scheme = SignatureScheme::new(digest, format);
+                let sig = ek.sign_with_scheme(scheme, msg).unwrap();
+                assert_eq!(sig.len(), format.signature_len());
+                scheme.verify_signer(msg, &sig, signer).unwrap();
+                assert!(scheme.verify_signer(msg, &sig, other).is_err());
+                assert!(scheme.verify_signer(b"other", &sig, signer).is_err());
+            }
+        }
+
+        // the default scheme is compatible with `Signer::sign`
+        let sig = ek.sign(msg).unwrap();
+        SignatureScheme::default()
+            .verify_signer(msg, &sig, signer)
+            .unwrap();
+        // a signature with another digest is rejected
+        assert!(
+            SignatureScheme::new(DigestAlgorithm::Sha256, SignatureFormat::Recoverable)
+                .verify_signer(msg, &sig, signer)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_signature_scheme_str() {
+        assert_eq!("sha256".parse(), Ok(DigestAlgorithm::Sha256));
+        assert_eq!("ethereum".parse(), Ok(SignatureFormat::Ethereum));
+        assert!("sha3".parse::<DigestAlgorithm>().is_err());
+        assert!("der".parse::<SignatureFormat>().is_err());
+        assert_eq!(
+            SignatureScheme::default().to_string(),
+            "keccak256-recoverable"
+        );
+    }
+}
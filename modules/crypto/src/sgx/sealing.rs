@@ -1,4 +1,6 @@
-use crate::key::{SealedEnclaveKey, SEALED_DATA_32_SIZE, SEALED_DATA_32_USIZE};
+use crate::key::{
+    calc_raw_sealed_data_size, SealedEnclaveKey, SEALED_DATA_32_SIZE, SEALED_DATA_32_USIZE,
+};
 use crate::traits::SealingKey;
 use crate::EnclaveKey;
 use crate::Error;
@@ -49,9 +51,79 @@ fn unseal_enclave_key(sek: &SealedEnclaveKey) -> Result<UnsealedEnclaveKey, Erro
     Ok(*sealed.unseal_data()?.get_decrypt_txt())
 }
 
+#[derive(Clone, Copy)]
+struct UnsealedCounter(u64);
+
+unsafe impl ContiguousMemory for UnsealedCounter {}
+
+const SEALED_COUNTER_SIZE: u32 = calc_raw_sealed_data_size(0, 8);
+
+/// Seal the counter so that only this enclave can read it and the host cannot forge it
+pub fn seal_counter(counter: u64) -> Result<Vec<u8>, Error> {
+    let sealed_data =
+        SgxSealedData::<UnsealedCounter>::seal_data(Default::default(), &UnsealedCounter(counter))?;
+    let mut sealed = vec![0u8; SEALED_COUNTER_SIZE as usize];
+    let _ = unsafe {
+        sealed_data.to_raw_sealed_data_t(
+            sealed.as_mut_ptr() as *mut sgx_sealed_data_t,
+            SEALED_COUNTER_SIZE,
+        )
+    };
+    Ok(sealed)
+}
+
+pub fn unseal_counter(sealed: &[u8]) -> Result<u64, Error> {
+    if sealed.len() != SEALED_COUNTER_SIZE as usize {
+        return Err(Error::failed_unseal(format!(
+            "invalid sealed counter length: expected={} actual={}",
+            SEALED_COUNTER_SIZE,
+            sealed.len()
+        )));
+    }
+    let mut sealed = sealed.to_vec();
+    let sealed = unsafe {
+        SgxSealedData::<UnsealedCounter>::from_raw_sealed_data_t(
+            sealed.as_mut_ptr() as *mut sgx_sealed_data_t,
+            SEALED_COUNTER_SIZE,
+        )
+    }
+    .ok_or_else(|| Error::failed_unseal("failed to unseal counter".to_owned()))?;
+    Ok(sealed.unseal_data()?.get_decrypt_txt().0)
+}
+
+/// Seal variable-length data so that only this enclave can read it and the host cannot forge it
+pub fn seal_bytes(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let size = u32::try_from(data.len())
+        .map(|len| calc_raw_sealed_data_size(0, len))
+        .ok()
+        .filter(|size| *size != u32::MAX)
+        .ok_or_else(|| Error::failed_seal(format!("data is too large: length={}", data.len())))?;
+    let sealed_data = SgxSealedData::<[u8]>::seal_data(Default::default(), data)?;
+    let mut sealed = vec![0u8; size as usize];
+    let _ = unsafe {
+        sealed_data.to_raw_sealed_data_t(sealed.as_mut_ptr() as *mut sgx_sealed_data_t, size)
+    };
+    Ok(sealed)
+}
+
+pub fn unseal_bytes(sealed: &[u8]) -> Result<Vec<u8>, Error> {
+    let size = u32::try_from(sealed.len()).map_err(|_| {
+        Error::failed_unseal(format!("invalid sealed data length: {}", sealed.len()))
+    })?;
+    let mut sealed = sealed.to_vec();
+    let sealed = unsafe {
+        SgxSealedData::<[u8]>::from_raw_sealed_data_t(
+            sealed.as_mut_ptr() as *mut sgx_sealed_data_t,
+            size,
+        )
+    }
+    .ok_or_else(|| Error::failed_unseal("failed to unseal data".to_owned()))?;
+    Ok(sealed.unseal_data()?.get_decrypt_txt().to_vec())
+}
+
 impl Signer for SealedEnclaveKey {
-    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
-        EnclaveKey::unseal(self)?.sign(msg)
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<Vec<u8>, Error> {
+        EnclaveKey::unseal(self)?.sign_digest(digest)
     }
     fn pubkey(&self) -> Result<EnclavePublicKey, Error> {
         Ok(EnclaveKey::unseal(self)?.get_pubkey())
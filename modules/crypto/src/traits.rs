@@ -2,6 +2,7 @@ use crate::prelude::*;
 use crate::EnclavePublicKey;
 use crate::Error;
 use crate::SealedEnclaveKey;
+use crate::SignatureScheme;
 use tiny_keccak::Keccak;
 
 pub trait Verifier {
@@ -9,7 +10,20 @@ pub trait Verifier {
 }
 
 pub trait Signer {
-    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error>;
+    /// Signs the digest and returns a recoverable signature `r || s || v`, where `v` is the recovery id
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<Vec<u8>, Error>;
+
+    /// Signs the keccak256 digest of the message, which is the default `SignatureScheme`
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        self.sign_digest(&msg.keccak256())
+    }
+
+    /// Signs the message with the digest algorithm and the signature format of the scheme
+    fn sign_with_scheme(&self, scheme: SignatureScheme, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        scheme
+            .format
+            .encode(self.sign_digest(&scheme.digest.digest(msg))?)
+    }
     fn pubkey(&self) -> Result<EnclavePublicKey, Error>;
 }
 
@@ -0,0 +1,1606 @@
+//! Conversions between the commands and the responses and the `lcp.ecall.v1` protobuf messages
+//!
+//! The decoders process the untrusted input from the host in the enclave,
+//! so they must reject malformed messages without panicking.
+use crate::prelude::*;
+use crate::{
+    AggregateMessagesInput, AggregateMessagesResponse, BeginSessionInput, BeginSessionResponse,
+    BuildInfo, ClientValidation, Command, CommandContext, CommandEvent, CommandResponse,
+    CommitmentPath, CommitmentPrefix, CommitmentProofPair, EnclaveManageCommand,
+    EnclaveManageResponse, ErrorCode, FinalizeStoreSequenceInput, FinalizeStoreSequenceResponse,
+    GenerateEnclaveKeyInput, GenerateEnclaveKeyResponse, IASRemoteAttestationInput,
+    IASRemoteAttestationResponse, InitClientInput, InitClientResponse, KeyAttestation,
+    LightClientCommand, LightClientExecuteCommand, LightClientQueryCommand, LightClientResponse,
+    ListClientsInput, ListClientsResponse, MigrateClientInput, MigrateClientResponse,
+    OpenSessionInput, OpenSessionResponse, OperatorSet, PageRequest, PageResponse, ProtocolError,
+    PruneClientInput, PruneClientResponse, QueryBuildInfoInput, QueryBuildInfoResponse,
+    QueryCapabilitiesInput, QueryCapabilitiesResponse, QueryClientInput, QueryClientResponse,
+    QueryConsensusHeightsInput, QueryConsensusHeightsResponse, QueryOperatorsInput,
+    QueryOperatorsResponse, ResignLatestStateInput, ResignLatestStateResponse,
+    RevokeEnclaveKeysInput, RevokeEnclaveKeysResponse, SignLatestHeightsInput,
+    SignLatestHeightsResponse, StartInstanceInput, StartInstanceResponse, StateIncompatibility,
+    StoreCheckpoint, UnsealStoreKeyInput, UnsealStoreKeyResponse, UpdateClientInput,
+    UpdateClientParamsInput, UpdateClientParamsResponse, UpdateClientResponse,
+    UpdateOperatorsInput, UpdateOperatorsResponse, ValidateDCAPCollateralInput,
+    ValidateDCAPCollateralResponse, ValidateStoreInput, ValidateStoreResponse,
+    VerifyAggregateMessagesInput, VerifyAggregateMessagesResponse, VerifyKeyValueInput,
+    VerifyKeyValueResponse, VerifyMembershipBatchInput, VerifyMembershipBatchResponse,
+    VerifyMembershipInput, VerifyMembershipItem, VerifyMembershipResponse,
+    VerifyNonMembershipInput, VerifyNonMembershipResponse, SESSION_TOKEN_SIZE,
+};
+use attestation_report::{
+    AdvisoryPolicy, AdvisoryRule, DCAPCollateral, DCAPQuoteBundle,
+    EndorsedAttestationVerificationReport,
+};
+use commitments::{CoSignature, CommitmentProof, RequestBinding};
+use core::fmt::Debug;
+use core::str::FromStr;
+use core::time::Duration;
+use crypto::{Address, EnclavePublicKey, SealedEnclaveKey, SignatureScheme};
+use lcp_types::proto::google::protobuf::Any as ProtoAny;
+use lcp_types::proto::ibc::core::client::v1::Height as RawHeight;
+use lcp_types::proto::lcp::ecall::v1 as proto;
+use lcp_types::{
+    Any, Capabilities, ClientId, ClientParams, Height, Time, VerificationMode, VoteExtensionPolicy,
+};
+use proto::{command_event, command_response, commitment_path, e_call_command, key_attestation};
+
+pub(crate) fn encode_context(ctx: &CommandContext) -> Result<proto::CommandContext, ProtocolError> {
+    Ok(proto::CommandContext {
+        current_timestamp: encode_time(ctx.current_timestamp)?,
+        sealed_ek: encode_sealed_ek(ctx.sealed_ek.as_ref()),
+        co_sealed_ek: encode_sealed_ek(ctx.co_sealed_ek.as_ref()),
+        operator_signatures: ctx.operator_signatures.clone(),
+        tx_id: ctx.tx_id,
+        session_token: ctx.session_token.map(|t| t.to_vec()).unwrap_or_default(),
+        constituent_sealed_eks: ctx
+            .constituent_sealed_eks
+            .iter()
+            .map(|k| k.to_vec())
+            .collect(),
+        constituent_attestations: ctx
+            .constituent_attestations
+            .iter()
+            .map(encode_key_attestation)
+            .collect(),
+    })
+}
+
+pub(crate) fn decode_context(msg: proto::CommandContext) -> Result<CommandContext, ProtocolError> {
+    let session_token = if msg.session_token.is_empty() {
+        None
+    } else {
+        Some(decode_array::<SESSION_TOKEN_SIZE>(
+            "session_token",
+            &msg.session_token,
+        )?)
+    };
+    Ok(CommandContext {
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        sealed_ek: decode_sealed_ek("sealed_ek", &msg.sealed_ek)?,
+        co_sealed_ek: decode_sealed_ek("co_sealed_ek", &msg.co_sealed_ek)?,
+        constituent_sealed_eks: msg
+            .constituent_sealed_eks
+            .iter()
+            .map(|bz| {
+                SealedEnclaveKey::new_from_bytes(bz).map_err(field_error("constituent_sealed_eks"))
+            })
+            .collect::<Result<_, _>>()?,
+        constituent_attestations: msg
+            .constituent_attestations
+            .into_iter()
+            .map(decode_key_attestation)
+            .collect::<Result<_, _>>()?,
+        operator_signatures: msg.operator_signatures,
+        tx_id: msg.tx_id,
+        session_token,
+        legacy_encoding: false,
+    })
+}
+
+fn encode_key_attestation(attestation: &KeyAttestation) -> proto::KeyAttestation {
+    let attestation = match attestation {
+        KeyAttestation::IAS(report) => {
+            key_attestation::Attestation::Ias(encode_endorsed_report(report))
+        }
+        KeyAttestation::DCAP(bundle) => {
+            key_attestation::Attestation::Dcap(encode_dcap_quote_bundle(bundle))
+        }
+    };
+    proto::KeyAttestation {
+        attestation: Some(attestation),
+    }
+}
+
+fn decode_key_attestation(msg: proto::KeyAttestation) -> Result<KeyAttestation, ProtocolError> {
+    match required("attestation", msg.attestation)? {
+        key_attestation::Attestation::Ias(report) => {
+            Ok(KeyAttestation::IAS(decode_endorsed_report(report)))
+        }
+        key_attestation::Attestation::Dcap(bundle) => {
+            Ok(KeyAttestation::DCAP(decode_dcap_quote_bundle(bundle)?))
+        }
+    }
+}
+
+fn encode_endorsed_report(
+    report: &EndorsedAttestationVerificationReport,
+) -> proto::EndorsedAttestationVerificationReport {
+    proto::EndorsedAttestationVerificationReport {
+        avr: report.avr.clone(),
+        signature: report.signature.clone(),
+        signing_cert: report.signing_cert.clone(),
+    }
+}
+
+fn decode_endorsed_report(
+    msg: proto::EndorsedAttestationVerificationReport,
+) -> EndorsedAttestationVerificationReport {
+    EndorsedAttestationVerificationReport {
+        avr: msg.avr,
+        signature: msg.signature,
+        signing_cert: msg.signing_cert,
+    }
+}
+
+fn encode_dcap_quote_bundle(bundle: &DCAPQuoteBundle) -> proto::DcapQuoteBundle {
+    let collateral = &bundle.collateral;
+    proto::DcapQuoteBundle {
+        quote: bundle.quote.clone(),
+        collateral: Some(proto::DcapCollateral {
+            tcb_info: collateral.tcb_info.clone(),
+            tcb_info_issuer_chain: collateral.tcb_info_issuer_chain.clone(),
+            qe_identity: collateral.qe_identity.clone(),
+            qe_identity_issuer_chain: collateral.qe_identity_issuer_chain.clone(),
+            pck_crl: collateral.pck_crl.clone(),
+            pck_crl_issuer_chain: collateral.pck_crl_issuer_chain.clone(),
+            root_ca_crl: collateral.root_ca_crl.clone(),
+        }),
+    }
+}
+
+fn decode_dcap_quote_bundle(msg: proto::DcapQuoteBundle) -> Result<DCAPQuoteBundle, ProtocolError> {
+    let collateral = required("collateral", msg.collateral)?;
+    Ok(DCAPQuoteBundle {
+        quote: msg.quote,
+        collateral: DCAPCollateral {
+            tcb_info: collateral.tcb_info,
+            tcb_info_issuer_chain: collateral.tcb_info_issuer_chain,
+            qe_identity: collateral.qe_identity,
+            qe_identity_issuer_chain: collateral.qe_identity_issuer_chain,
+            pck_crl: collateral.pck_crl,
+            pck_crl_issuer_chain: collateral.pck_crl_issuer_chain,
+            root_ca_crl: collateral.root_ca_crl,
+        },
+    })
+}
+
+pub(crate) fn encode_command(cmd: &Command) -> Result<e_call_command::Cmd, ProtocolError> {
+    match cmd {
+        Command::EnclaveManage(cmd) => Ok(encode_enclave_manage_command(cmd)),
+        Command::LightClient(LightClientCommand::Execute(cmd)) => encode_execute_command(cmd),
+        Command::LightClient(LightClientCommand::Query(cmd)) => Ok(encode_query_command(cmd)),
+    }
+}
+
+pub(crate) fn decode_command(cmd: e_call_command::Cmd) -> Result<Command, ProtocolError> {
+    use e_call_command::Cmd;
+    use EnclaveManageCommand as Manage;
+    use LightClientExecuteCommand as Execute;
+    use LightClientQueryCommand as Query;
+    let manage = Command::EnclaveManage;
+    let execute = |cmd| Command::LightClient(LightClientCommand::Execute(cmd));
+    let query = |cmd| Command::LightClient(LightClientCommand::Query(cmd));
+    Ok(match cmd {
+        Cmd::GenerateEnclaveKey(_) => manage(Manage::GenerateEnclaveKey(GenerateEnclaveKeyInput)),
+        Cmd::IasRemoteAttestation(msg) => manage(Manage::IASRemoteAttestation(
+            decode_ias_remote_attestation(msg)?,
+        )),
+        #[cfg(feature = "sgx-sw")]
+        Cmd::SimulateRemoteAttestation(msg) => manage(Manage::SimulateRemoteAttestation(
+            crate::SimulateRemoteAttestationInput {
+                target_enclave_key: decode_address("target_enclave_key", &msg.target_enclave_key)?,
+                advisory_ids: msg.advisory_ids,
+                isv_enclave_quote_status: msg.isv_enclave_quote_status,
+            },
+        )),
+        #[cfg(not(feature = "sgx-sw"))]
+        Cmd::SimulateRemoteAttestation(_) => {
+            return Err(ProtocolError::invalid_field(
+                "cmd".into(),
+                "simulate_remote_attestation requires the sgx-sw feature".into(),
+            ))
+        }
+        Cmd::ValidateDcapCollateral(msg) => manage(Manage::ValidateDCAPCollateral(
+            ValidateDCAPCollateralInput {
+                target_enclave_key: decode_address("target_enclave_key", &msg.target_enclave_key)?,
+                bundle: decode_dcap_quote_bundle(required("bundle", msg.bundle)?)?,
+            },
+        )),
+        Cmd::StartInstance(msg) => manage(Manage::StartInstance(StartInstanceInput {
+            store_checkpoint: msg.store_checkpoint,
+        })),
+        Cmd::UpdateOperators(msg) => manage(Manage::UpdateOperators(UpdateOperatorsInput {
+            operator_set: decode_operator_set(required("operator_set", msg.operator_set)?)?,
+            signatures: msg.signatures,
+        })),
+        Cmd::QueryOperators(_) => manage(Manage::QueryOperators(QueryOperatorsInput)),
+        Cmd::QueryBuildInfo(_) => manage(Manage::QueryBuildInfo(QueryBuildInfoInput)),
+        Cmd::BeginSession(_) => manage(Manage::BeginSession(BeginSessionInput)),
+        Cmd::OpenSession(msg) => manage(Manage::OpenSession(OpenSessionInput {
+            signature: msg.signature,
+        })),
+        Cmd::RevokeEnclaveKeys(msg) => manage(Manage::RevokeEnclaveKeys(RevokeEnclaveKeysInput {
+            addresses: decode_addresses("addresses", &msg.addresses)?,
+            reason: msg.reason,
+        })),
+        Cmd::UnsealStoreKey(msg) => manage(Manage::UnsealStoreKey(UnsealStoreKeyInput {
+            sealed_key: msg.sealed_key,
+        })),
+        Cmd::FinalizeStoreSequence(msg) => {
+            manage(Manage::FinalizeStoreSequence(FinalizeStoreSequenceInput {
+                sequence: msg.sequence,
+            }))
+        }
+
+        Cmd::InitClient(msg) => execute(Execute::InitClient(decode_init_client(msg)?)),
+        Cmd::UpdateClient(msg) => execute(Execute::UpdateClient(decode_update_client(msg)?)),
+        Cmd::AggregateMessages(msg) => {
+            execute(Execute::AggregateMessages(decode_aggregate_messages(msg)?))
+        }
+        Cmd::VerifyMembership(msg) => {
+            execute(Execute::VerifyMembership(decode_verify_membership(msg)?))
+        }
+        Cmd::VerifyNonMembership(msg) => execute(Execute::VerifyNonMembership(
+            decode_verify_non_membership(msg)?,
+        )),
+        Cmd::VerifyMembershipBatch(msg) => execute(Execute::VerifyMembershipBatch(
+            decode_verify_membership_batch(msg)?,
+        )),
+        Cmd::VerifyKeyValue(msg) => execute(Execute::VerifyKeyValue(decode_verify_key_value(msg)?)),
+        Cmd::PruneClient(msg) => execute(Execute::PruneClient(PruneClientInput {
+            client_id: decode_client_id("client_id", &msg.client_id)?,
+            margin: Duration::from_nanos(msg.margin),
+            current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        })),
+        Cmd::SignLatestHeights(msg) => {
+            execute(Execute::SignLatestHeights(SignLatestHeightsInput {
+                client_ids: decode_client_ids("client_ids", &msg.client_ids)?,
+                current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+                signer: decode_address("signer", &msg.signer)?,
+            }))
+        }
+        Cmd::UpdateClientParams(msg) => {
+            execute(Execute::UpdateClientParams(UpdateClientParamsInput {
+                client_id: decode_client_id("client_id", &msg.client_id)?,
+                params: decode_client_params(required("params", msg.params)?),
+                current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+                signer: decode_address("signer", &msg.signer)?,
+            }))
+        }
+        Cmd::ResignLatestState(msg) => {
+            execute(Execute::ResignLatestState(ResignLatestStateInput {
+                client_id: decode_client_id("client_id", &msg.client_id)?,
+                current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+                signer: decode_address("signer", &msg.signer)?,
+            }))
+        }
+        Cmd::MigrateClient(msg) => execute(Execute::MigrateClient(MigrateClientInput {
+            client_id: decode_client_id("client_id", &msg.client_id)?,
+            target_type_url: msg.target_type_url,
+            target_version: msg.target_version,
+            current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+            signer: decode_address("signer", &msg.signer)?,
+        })),
+
+        Cmd::QueryClient(msg) => query(Query::QueryClient(QueryClientInput {
+            client_id: decode_client_id("client_id", &msg.client_id)?,
+        })),
+        Cmd::ListClients(msg) => query(Query::ListClients(ListClientsInput {
+            pagination: decode_page_request(msg.pagination)?,
+        })),
+        Cmd::QueryConsensusHeights(msg) => {
+            query(Query::QueryConsensusHeights(QueryConsensusHeightsInput {
+                client_id: decode_client_id("client_id", &msg.client_id)?,
+                pagination: decode_page_request(msg.pagination)?,
+            }))
+        }
+        Cmd::QueryCapabilities(msg) => query(Query::QueryCapabilities(
+            match required("target", msg.target)? {
+                proto::query_capabilities::Target::ClientId(client_id) => {
+                    QueryCapabilitiesInput::ClientId(decode_client_id("client_id", &client_id)?)
+                }
+                proto::query_capabilities::Target::ClientStateTypeUrl(type_url) => {
+                    QueryCapabilitiesInput::ClientStateTypeUrl(type_url)
+                }
+            },
+        )),
+        Cmd::ValidateStore(msg) => query(Query::ValidateStore(ValidateStoreInput {
+            pagination: decode_page_request(msg.pagination)?,
+        })),
+        Cmd::VerifyAggregateMessages(msg) => query(Query::VerifyAggregateMessages(
+            VerifyAggregateMessagesInput {
+                client_id: decode_client_id("client_id", &msg.client_id)?,
+                messages: msg.messages.into_iter().map(Any::from).collect(),
+                signatures: msg.signatures,
+                signers: decode_addresses("signers", &msg.signers)?,
+            },
+        )),
+    })
+}
+
+fn encode_enclave_manage_command(cmd: &EnclaveManageCommand) -> e_call_command::Cmd {
+    use e_call_command::Cmd;
+    match cmd {
+        EnclaveManageCommand::GenerateEnclaveKey(_) => {
+            Cmd::GenerateEnclaveKey(proto::GenerateEnclaveKey {})
+        }
+        EnclaveManageCommand::IASRemoteAttestation(input) => {
+            Cmd::IasRemoteAttestation(proto::IasRemoteAttestation {
+                target_enclave_key: input.target_enclave_key.to_vec(),
+                spid: input.spid.clone(),
+                ias_key: input.ias_key.clone(),
+                quote_type: input.quote_type.as_str().into(),
+                advisory_policy: input.advisory_policy.as_ref().map(encode_advisory_policy),
+                applied_mitigations: input.applied_mitigations.clone(),
+            })
+        }
+        #[cfg(feature = "sgx-sw")]
+        EnclaveManageCommand::SimulateRemoteAttestation(input) => {
+            Cmd::SimulateRemoteAttestation(proto::SimulateRemoteAttestation {
+                target_enclave_key: input.target_enclave_key.to_vec(),
+                advisory_ids: input.advisory_ids.clone(),
+                isv_enclave_quote_status: input.isv_enclave_quote_status.clone(),
+            })
+        }
+        EnclaveManageCommand::ValidateDCAPCollateral(input) => {
+            Cmd::ValidateDcapCollateral(proto::ValidateDcapCollateral {
+                target_enclave_key: input.target_enclave_key.to_vec(),
+                bundle: Some(encode_dcap_quote_bundle(&input.bundle)),
+            })
+        }
+        EnclaveManageCommand::StartInstance(input) => Cmd::StartInstance(proto::StartInstance {
+            store_checkpoint: input.store_checkpoint.clone(),
+        }),
+        EnclaveManageCommand::UpdateOperators(input) => {
+            Cmd::UpdateOperators(proto::UpdateOperators {
+                operator_set: Some(encode_operator_set(&input.operator_set)),
+                signatures: input.signatures.clone(),
+            })
+        }
+        EnclaveManageCommand::QueryOperators(_) => Cmd::QueryOperators(proto::QueryOperators {}),
+        EnclaveManageCommand::QueryBuildInfo(_) => Cmd::QueryBuildInfo(proto::QueryBuildInfo {}),
+        EnclaveManageCommand::BeginSession(_) => Cmd::BeginSession(proto::BeginSession {}),
+        EnclaveManageCommand::OpenSession(input) => Cmd::OpenSession(proto::OpenSession {
+            signature: input.signature.clone(),
+        }),
+        EnclaveManageCommand::RevokeEnclaveKeys(input) => {
+            Cmd::RevokeEnclaveKeys(proto::RevokeEnclaveKeys {
+                addresses: encode_addresses(&input.addresses),
+                reason: input.reason.clone(),
+            })
+        }
+        EnclaveManageCommand::UnsealStoreKey(input) => Cmd::UnsealStoreKey(proto::UnsealStoreKey {
+            sealed_key: input.sealed_key.clone(),
+        }),
+        EnclaveManageCommand::FinalizeStoreSequence(input) => {
+            Cmd::FinalizeStoreSequence(proto::FinalizeStoreSequence {
+                sequence: input.sequence,
+            })
+        }
+    }
+}
+
+fn encode_execute_command(
+    cmd: &LightClientExecuteCommand,
+) -> Result<e_call_command::Cmd, ProtocolError> {
+    use e_call_command::Cmd;
+    Ok(match cmd {
+        LightClientExecuteCommand::InitClient(input) => Cmd::InitClient(encode_init_client(input)?),
+        LightClientExecuteCommand::UpdateClient(input) => {
+            Cmd::UpdateClient(encode_update_client(input)?)
+        }
+        LightClientExecuteCommand::AggregateMessages(input) => {
+            Cmd::AggregateMessages(proto::AggregateMessages {
+                client_id: input.client_id.to_string(),
+                signer: input.signer.to_vec(),
+                messages: input.messages.iter().cloned().map(Into::into).collect(),
+                signatures: input.signatures.clone(),
+                signers: encode_addresses(&input.signers),
+                current_timestamp: encode_time(input.current_timestamp)?,
+            })
+        }
+        LightClientExecuteCommand::VerifyMembership(input) => {
+            Cmd::VerifyMembership(proto::VerifyMembership {
+                client_id: input.client_id.to_string(),
+                prefix: input.prefix.as_bytes().to_vec(),
+                path: Some(encode_commitment_path(&input.path)),
+                value: input.value.clone(),
+                proof_height: Some(input.proof.0.into()),
+                proof: input.proof.1.clone(),
+                expires_at: input.expires_at.map(encode_time).transpose()?,
+                consensus_height: input.consensus_height.map(Into::into),
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::VerifyNonMembership(input) => {
+            Cmd::VerifyNonMembership(proto::VerifyNonMembership {
+                client_id: input.client_id.to_string(),
+                prefix: input.prefix.as_bytes().to_vec(),
+                path: Some(encode_commitment_path(&input.path)),
+                proof_height: Some(input.proof.0.into()),
+                proof: input.proof.1.clone(),
+                expires_at: input.expires_at.map(encode_time).transpose()?,
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::VerifyMembershipBatch(input) => {
+            Cmd::VerifyMembershipBatch(proto::VerifyMembershipBatch {
+                client_id: input.client_id.to_string(),
+                prefix: input.prefix.as_bytes().to_vec(),
+                proof_height: Some(input.proof_height.into()),
+                items: input
+                    .items
+                    .iter()
+                    .map(|item| proto::VerifyMembershipItem {
+                        path: Some(encode_commitment_path(&item.path)),
+                        value: item.value.clone(),
+                        proof: item.proof.clone(),
+                    })
+                    .collect(),
+                expires_at: input.expires_at.map(encode_time).transpose()?,
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::VerifyKeyValue(input) => {
+            Cmd::VerifyKeyValue(proto::VerifyKeyValue {
+                client_id: input.client_id.to_string(),
+                prefix: input.prefix.as_bytes().to_vec(),
+                key: input.key.clone(),
+                value: input.value.clone(),
+                proof_height: Some(input.proof.0.into()),
+                proof: input.proof.1.clone(),
+                expires_at: input.expires_at.map(encode_time).transpose()?,
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::PruneClient(input) => Cmd::PruneClient(proto::PruneClient {
+            client_id: input.client_id.to_string(),
+            margin: encode_duration(input.margin)?,
+            current_timestamp: encode_time(input.current_timestamp)?,
+        }),
+        LightClientExecuteCommand::SignLatestHeights(input) => {
+            Cmd::SignLatestHeights(proto::SignLatestHeights {
+                client_ids: input.client_ids.iter().map(ToString::to_string).collect(),
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::UpdateClientParams(input) => {
+            Cmd::UpdateClientParams(proto::UpdateClientParams {
+                client_id: input.client_id.to_string(),
+                params: Some(encode_client_params(&input.params)?),
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::ResignLatestState(input) => {
+            Cmd::ResignLatestState(proto::ResignLatestState {
+                client_id: input.client_id.to_string(),
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+        LightClientExecuteCommand::MigrateClient(input) => {
+            Cmd::MigrateClient(proto::MigrateClient {
+                client_id: input.client_id.to_string(),
+                target_type_url: input.target_type_url.clone(),
+                target_version: input.target_version,
+                current_timestamp: encode_time(input.current_timestamp)?,
+                signer: input.signer.to_vec(),
+            })
+        }
+    })
+}
+
+fn encode_query_command(cmd: &LightClientQueryCommand) -> e_call_command::Cmd {
+    use e_call_command::Cmd;
+    match cmd {
+        LightClientQueryCommand::QueryClient(input) => Cmd::QueryClient(proto::QueryClient {
+            client_id: input.client_id.to_string(),
+        }),
+        LightClientQueryCommand::ListClients(input) => Cmd::ListClients(proto::ListClients {
+            pagination: Some(encode_page_request(&input.pagination)),
+        }),
+        LightClientQueryCommand::QueryConsensusHeights(input) => {
+            Cmd::QueryConsensusHeights(proto::QueryConsensusHeights {
+                client_id: input.client_id.to_string(),
+                pagination: Some(encode_page_request(&input.pagination)),
+            })
+        }
+        LightClientQueryCommand::QueryCapabilities(input) => {
+            let target = match input {
+                QueryCapabilitiesInput::ClientId(client_id) => {
+                    proto::query_capabilities::Target::ClientId(client_id.to_string())
+                }
+                QueryCapabilitiesInput::ClientStateTypeUrl(type_url) => {
+                    proto::query_capabilities::Target::ClientStateTypeUrl(type_url.clone())
+                }
+            };
+            Cmd::QueryCapabilities(proto::QueryCapabilities {
+                target: Some(target),
+            })
+        }
+        LightClientQueryCommand::ValidateStore(input) => Cmd::ValidateStore(proto::ValidateStore {
+            pagination: Some(encode_page_request(&input.pagination)),
+        }),
+        LightClientQueryCommand::VerifyAggregateMessages(input) => {
+            Cmd::VerifyAggregateMessages(proto::VerifyAggregateMessages {
+                client_id: input.client_id.to_string(),
+                messages: input.messages.iter().cloned().map(Into::into).collect(),
+                signatures: input.signatures.clone(),
+                signers: encode_addresses(&input.signers),
+            })
+        }
+    }
+}
+
+fn decode_ias_remote_attestation(
+    msg: proto::IasRemoteAttestation,
+) -> Result<IASRemoteAttestationInput, ProtocolError> {
+    Ok(IASRemoteAttestationInput {
+        target_enclave_key: decode_address("target_enclave_key", &msg.target_enclave_key)?,
+        spid: msg.spid,
+        ias_key: msg.ias_key,
+        quote_type: msg.quote_type.parse().map_err(field_error("quote_type"))?,
+        advisory_policy: msg.advisory_policy.map(decode_advisory_policy),
+        applied_mitigations: msg.applied_mitigations,
+    })
+}
+
+fn encode_advisory_policy(policy: &AdvisoryPolicy) -> proto::AdvisoryPolicy {
+    proto::AdvisoryPolicy {
+        advisories: policy
+            .advisories
+            .iter()
+            .map(|(advisory_id, rule)| proto::AdvisoryRule {
+                advisory_id: advisory_id.clone(),
+                allowed_until: rule.allowed_until,
+                required_mitigations: rule.required_mitigations.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn decode_advisory_policy(msg: proto::AdvisoryPolicy) -> AdvisoryPolicy {
+    AdvisoryPolicy {
+        advisories: msg
+            .advisories
+            .into_iter()
+            .map(|rule| {
+                (
+                    rule.advisory_id,
+                    AdvisoryRule {
+                        allowed_until: rule.allowed_until,
+                        required_mitigations: rule.required_mitigations,
+                    },
+                )
+            })
+            .collect(),
+    }
+}
+
+fn encode_operator_set(operator_set: &OperatorSet) -> proto::OperatorSet {
+    proto::OperatorSet {
+        operators: encode_addresses(&operator_set.operators),
+        threshold: operator_set.threshold,
+        nonce: operator_set.nonce,
+    }
+}
+
+fn decode_operator_set(msg: proto::OperatorSet) -> Result<OperatorSet, ProtocolError> {
+    Ok(OperatorSet {
+        operators: decode_addresses("operators", &msg.operators)?,
+        threshold: msg.threshold,
+        nonce: msg.nonce,
+    })
+}
+
+fn encode_init_client(input: &InitClientInput) -> Result<proto::InitClient, ProtocolError> {
+    Ok(proto::InitClient {
+        client_state: Some(input.any_client_state.clone().into()),
+        consensus_state: Some(input.any_consensus_state.clone().into()),
+        commitment_prefix: input.commitment_prefix.clone().map(Into::into),
+        verification_mode: input
+            .verification_mode
+            .map(|mode| mode.as_str().into())
+            .unwrap_or_default(),
+        prove_genesis: input.prove_genesis,
+        max_clock_drift: input.max_clock_drift.map(encode_duration).transpose()?,
+        vote_extension_policy: input
+            .vote_extension_policy
+            .map(|policy| policy.as_str().into())
+            .unwrap_or_default(),
+        signature_scheme: input.signature_scheme.as_ref().map(encode_signature_scheme),
+        current_timestamp: encode_time(input.current_timestamp)?,
+        signer: input.signer.to_vec(),
+        message_schema_version: input.message_schema_version.map(Into::into),
+    })
+}
+
+fn decode_init_client(msg: proto::InitClient) -> Result<InitClientInput, ProtocolError> {
+    Ok(InitClientInput {
+        any_client_state: decode_any("client_state", msg.client_state)?,
+        any_consensus_state: decode_any("consensus_state", msg.consensus_state)?,
+        commitment_prefix: msg
+            .commitment_prefix
+            .map(CommitmentPrefix::try_from)
+            .transpose()
+            .map_err(field_error("commitment_prefix"))?,
+        verification_mode: decode_optional_str::<VerificationMode>(
+            "verification_mode",
+            &msg.verification_mode,
+        )?,
+        prove_genesis: msg.prove_genesis,
+        max_clock_drift: msg.max_clock_drift.map(Duration::from_nanos),
+        vote_extension_policy: decode_optional_str::<VoteExtensionPolicy>(
+            "vote_extension_policy",
+            &msg.vote_extension_policy,
+        )?,
+        signature_scheme: msg
+            .signature_scheme
+            .map(decode_signature_scheme)
+            .transpose()?,
+        message_schema_version: msg
+            .message_schema_version
+            .map(u16::try_from)
+            .transpose()
+            .map_err(field_error("message_schema_version"))?,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn encode_update_client(input: &UpdateClientInput) -> Result<proto::UpdateClient, ProtocolError> {
+    Ok(proto::UpdateClient {
+        client_id: input.client_id.to_string(),
+        header: Some(input.any_header.clone().into()),
+        include_state: input.include_state,
+        state_filters: input.state_filters.clone(),
+        detect_misbehaviour: input.detect_misbehaviour,
+        expires_at: input.expires_at.map(encode_time).transpose()?,
+        dry_run: input.dry_run,
+        current_timestamp: encode_time(input.current_timestamp)?,
+        signer: input.signer.to_vec(),
+    })
+}
+
+fn decode_update_client(msg: proto::UpdateClient) -> Result<UpdateClientInput, ProtocolError> {
+    Ok(UpdateClientInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        any_header: decode_any("header", msg.header)?,
+        include_state: msg.include_state,
+        state_filters: msg.state_filters,
+        detect_misbehaviour: msg.detect_misbehaviour,
+        expires_at: decode_optional_time("expires_at", msg.expires_at)?,
+        dry_run: msg.dry_run,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn decode_aggregate_messages(
+    msg: proto::AggregateMessages,
+) -> Result<AggregateMessagesInput, ProtocolError> {
+    Ok(AggregateMessagesInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        signer: decode_address("signer", &msg.signer)?,
+        messages: msg.messages.into_iter().map(Any::from).collect(),
+        signatures: msg.signatures,
+        signers: decode_addresses("signers", &msg.signers)?,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+    })
+}
+
+fn decode_verify_membership(
+    msg: proto::VerifyMembership,
+) -> Result<VerifyMembershipInput, ProtocolError> {
+    Ok(VerifyMembershipInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        prefix: decode_prefix(msg.prefix)?,
+        path: decode_commitment_path(msg.path)?,
+        value: msg.value,
+        proof: CommitmentProofPair(decode_height("proof_height", msg.proof_height)?, msg.proof),
+        expires_at: decode_optional_time("expires_at", msg.expires_at)?,
+        consensus_height: msg.consensus_height.map(Height::from),
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn decode_verify_non_membership(
+    msg: proto::VerifyNonMembership,
+) -> Result<VerifyNonMembershipInput, ProtocolError> {
+    Ok(VerifyNonMembershipInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        prefix: decode_prefix(msg.prefix)?,
+        path: decode_commitment_path(msg.path)?,
+        proof: CommitmentProofPair(decode_height("proof_height", msg.proof_height)?, msg.proof),
+        expires_at: decode_optional_time("expires_at", msg.expires_at)?,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn decode_verify_membership_batch(
+    msg: proto::VerifyMembershipBatch,
+) -> Result<VerifyMembershipBatchInput, ProtocolError> {
+    Ok(VerifyMembershipBatchInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        prefix: decode_prefix(msg.prefix)?,
+        proof_height: decode_height("proof_height", msg.proof_height)?,
+        items: msg
+            .items
+            .into_iter()
+            .map(|item| {
+                Ok(VerifyMembershipItem {
+                    path: decode_commitment_path(item.path)?,
+                    value: item.value,
+                    proof: item.proof,
+                })
+            })
+            .collect::<Result<_, ProtocolError>>()?,
+        expires_at: decode_optional_time("expires_at", msg.expires_at)?,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn decode_verify_key_value(
+    msg: proto::VerifyKeyValue,
+) -> Result<VerifyKeyValueInput, ProtocolError> {
+    Ok(VerifyKeyValueInput {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        prefix: decode_prefix(msg.prefix)?,
+        key: msg.key,
+        value: msg.value,
+        proof: CommitmentProofPair(decode_height("proof_height", msg.proof_height)?, msg.proof),
+        expires_at: decode_optional_time("expires_at", msg.expires_at)?,
+        current_timestamp: decode_time("current_timestamp", msg.current_timestamp)?,
+        signer: decode_address("signer", &msg.signer)?,
+    })
+}
+
+fn encode_commitment_path(path: &CommitmentPath) -> proto::CommitmentPath {
+    let path = match path {
+        CommitmentPath::Raw(path) => commitment_path::Path::Raw(path.clone()),
+        path => commitment_path::Path::Ics24(path.to_string()),
+    };
+    proto::CommitmentPath { path: Some(path) }
+}
+
+fn decode_commitment_path(
+    msg: Option<proto::CommitmentPath>,
+) -> Result<CommitmentPath, ProtocolError> {
+    match required("path", required("path", msg)?.path)? {
+        commitment_path::Path::Ics24(path) => {
+            CommitmentPath::from_str(&path).map_err(field_error("path"))
+        }
+        commitment_path::Path::Raw(path) => Ok(CommitmentPath::Raw(path)),
+    }
+}
+
+fn encode_client_params(params: &ClientParams) -> Result<proto::ClientParams, ProtocolError> {
+    Ok(proto::ClientParams {
+        trusting_period: params.trusting_period.map(encode_duration).transpose()?,
+        max_clock_drift: params.max_clock_drift.map(encode_duration).transpose()?,
+        proof_specs: params
+            .proof_specs
+            .clone()
+            .map(|specs| proto::ProofSpecs { specs }),
+    })
+}
+
+fn decode_client_params(msg: proto::ClientParams) -> ClientParams {
+    ClientParams {
+        trusting_period: msg.trusting_period.map(Duration::from_nanos),
+        max_clock_drift: msg.max_clock_drift.map(Duration::from_nanos),
+        proof_specs: msg.proof_specs.map(|specs| specs.specs),
+    }
+}
+
+fn encode_page_request(pagination: &PageRequest) -> proto::PageRequest {
+    proto::PageRequest {
+        offset: pagination.offset,
+        limit: pagination.limit,
+    }
+}
+
+fn decode_page_request(msg: Option<proto::PageRequest>) -> Result<PageRequest, ProtocolError> {
+    let msg = required("pagination", msg)?;
+    Ok(PageRequest {
+        offset: msg.offset,
+        limit: msg.limit,
+    })
+}
+
+fn encode_page_response(pagination: &PageResponse) -> proto::PageResponse {
+    proto::PageResponse {
+        next_offset: pagination.next_offset,
+        total: pagination.total,
+    }
+}
+
+fn decode_page_response(msg: Option<proto::PageResponse>) -> Result<PageResponse, ProtocolError> {
+    let msg = required("pagination", msg)?;
+    Ok(PageResponse {
+        next_offset: msg.next_offset,
+        total: msg.total,
+    })
+}
+
+pub(crate) fn encode_response(res: &CommandResponse) -> command_response::Res {
+    match res {
+        CommandResponse::EnclaveManage(res) => encode_enclave_manage_response(res),
+        CommandResponse::LightClient(res) => encode_light_client_response(res),
+        CommandResponse::CommandError(code, descr) => {
+            command_response::Res::Error(proto::CommandError {
+                code: code.code(),
+                descr: descr.clone(),
+            })
+        }
+    }
+}
+
+pub(crate) fn decode_response(
+    res: command_response::Res,
+) -> Result<CommandResponse, ProtocolError> {
+    use command_response::Res;
+    use EnclaveManageResponse as Manage;
+    use LightClientResponse as LightClient;
+    let manage = CommandResponse::EnclaveManage;
+    let light_client = CommandResponse::LightClient;
+    Ok(match res {
+        Res::Error(err) => CommandResponse::CommandError(
+            ErrorCode::from_code(err.code).unwrap_or(ErrorCode::Unknown),
+            err.descr,
+        ),
+
+        Res::GenerateEnclaveKey(res) => {
+            manage(Manage::GenerateEnclaveKey(GenerateEnclaveKeyResponse {
+                pub_key: EnclavePublicKey::try_from(res.pub_key.as_slice())
+                    .map_err(field_error("pub_key"))?,
+                sealed_ek: SealedEnclaveKey::new_from_bytes(&res.sealed_ek)
+                    .map_err(field_error("sealed_ek"))?,
+            }))
+        }
+        Res::IasRemoteAttestation(res) => {
+            manage(Manage::IASRemoteAttestation(IASRemoteAttestationResponse {
+                report: decode_endorsed_report(required("report", res.report)?),
+            }))
+        }
+        #[cfg(feature = "sgx-sw")]
+        Res::SimulateRemoteAttestation(res) => manage(Manage::SimulateRemoteAttestation(
+            crate::SimulateRemoteAttestationResponse {
+                avr: decode_avr(required("avr", res.avr)?),
+            },
+        )),
+        #[cfg(not(feature = "sgx-sw"))]
+        Res::SimulateRemoteAttestation(_) => {
+            return Err(ProtocolError::invalid_field(
+                "res".into(),
+                "simulate_remote_attestation requires the sgx-sw feature".into(),
+            ))
+        }
+        Res::ValidateDcapCollateral(res) => manage(Manage::ValidateDCAPCollateral(
+            ValidateDCAPCollateralResponse {
+                root_ca_hash: decode_array("root_ca_hash", &res.root_ca_hash)?,
+            },
+        )),
+        Res::StartInstance(res) => manage(Manage::StartInstance(StartInstanceResponse {
+            counter: res.counter,
+            store_sequence: res.store_sequence,
+            sealed_sequence: res.sealed_sequence,
+        })),
+        Res::UpdateOperators(_) => manage(Manage::UpdateOperators(UpdateOperatorsResponse)),
+        Res::QueryOperators(res) => manage(Manage::QueryOperators(QueryOperatorsResponse {
+            operator_set: res.operator_set.map(decode_operator_set).transpose()?,
+        })),
+        Res::QueryBuildInfo(res) => manage(Manage::QueryBuildInfo(QueryBuildInfoResponse {
+            build_info: decode_build_info(required("build_info", res.build_info)?),
+        })),
+        Res::BeginSession(res) => manage(Manage::BeginSession(BeginSessionResponse {
+            challenge: decode_array("challenge", &res.challenge)?,
+        })),
+        Res::OpenSession(res) => manage(Manage::OpenSession(OpenSessionResponse {
+            session_token: decode_array("session_token", &res.session_token)?,
+            operator: decode_address("operator", &res.operator)?,
+        })),
+        Res::RevokeEnclaveKeys(res) => {
+            manage(Manage::RevokeEnclaveKeys(RevokeEnclaveKeysResponse {
+                revoked: decode_addresses("revoked", &res.revoked)?,
+            }))
+        }
+        Res::UnsealStoreKey(res) => manage(Manage::UnsealStoreKey(UnsealStoreKeyResponse {
+            key: decode_array("key", &res.key)?,
+            sealed_key: res.sealed_key,
+        })),
+        Res::FinalizeStoreSequence(res) => manage(Manage::FinalizeStoreSequence(
+            FinalizeStoreSequenceResponse {
+                sealed_sequence: res.sealed_sequence,
+            },
+        )),
+
+        Res::InitClient(res) => light_client(LightClient::InitClient(InitClientResponse {
+            client_id: decode_client_id("client_id", &res.client_id)?,
+            proof: decode_commitment_proof(res.proof)?,
+        })),
+        Res::UpdateClient(res) => light_client(LightClient::UpdateClient(UpdateClientResponse(
+            decode_commitment_proof(res.proof)?,
+        ))),
+        Res::AggregateMessages(res) => {
+            light_client(LightClient::AggregateMessages(AggregateMessagesResponse(
+                decode_commitment_proof(res.proof)?,
+                decode_addresses("constituent_signers", &res.constituent_signers)?,
+                res.constituent_signers_signature,
+            )))
+        }
+        Res::VerifyMembership(res) => light_client(LightClient::VerifyMembership(
+            VerifyMembershipResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::VerifyNonMembership(res) => light_client(LightClient::VerifyNonMembership(
+            VerifyNonMembershipResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::VerifyMembershipBatch(res) => light_client(LightClient::VerifyMembershipBatch(
+            VerifyMembershipBatchResponse(
+                res.proofs
+                    .into_iter()
+                    .map(|proof| decode_commitment_proof(Some(proof)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        )),
+        Res::VerifyKeyValue(res) => light_client(LightClient::VerifyKeyValue(
+            VerifyKeyValueResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::PruneClient(res) => light_client(LightClient::PruneClient(PruneClientResponse {
+            pruned_heights: res.pruned_heights.into_iter().map(Height::from).collect(),
+            reclaimed_bytes: res.reclaimed_bytes,
+            expired: res.expired,
+        })),
+        Res::SignLatestHeights(res) => light_client(LightClient::SignLatestHeights(
+            SignLatestHeightsResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::UpdateClientParams(res) => light_client(LightClient::UpdateClientParams(
+            UpdateClientParamsResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::ResignLatestState(res) => light_client(LightClient::ResignLatestState(
+            ResignLatestStateResponse(decode_commitment_proof(res.proof)?),
+        )),
+        Res::MigrateClient(res) => light_client(LightClient::MigrateClient(MigrateClientResponse(
+            decode_commitment_proof(res.proof)?,
+        ))),
+
+        Res::QueryClient(res) => light_client(LightClient::QueryClient(QueryClientResponse {
+            any_client_state: decode_any("client_state", res.client_state)?,
+            any_consensus_state: decode_any("consensus_state", res.consensus_state)?,
+        })),
+        Res::ListClients(res) => light_client(LightClient::ListClients(ListClientsResponse {
+            client_ids: decode_client_ids("client_ids", &res.client_ids)?,
+            pagination: decode_page_response(res.pagination)?,
+        })),
+        Res::QueryConsensusHeights(res) => light_client(LightClient::QueryConsensusHeights(
+            QueryConsensusHeightsResponse {
+                heights: res.heights.into_iter().map(Height::from).collect(),
+                pagination: decode_page_response(res.pagination)?,
+            },
+        )),
+        Res::QueryCapabilities(res) => {
+            let capabilities = required("capabilities", res.capabilities)?;
+            light_client(LightClient::QueryCapabilities(QueryCapabilitiesResponse {
+                client_type: res.client_type,
+                version: res.version,
+                capabilities: Capabilities {
+                    misbehaviour: capabilities.misbehaviour,
+                    upgrade: capabilities.upgrade,
+                    proof_formats: capabilities.proof_formats,
+                    multipart_headers: capabilities.multipart_headers,
+                    key_value_verification: capabilities.key_value_verification,
+                    client_params: capabilities.client_params,
+                },
+            }))
+        }
+        Res::ValidateStore(res) => {
+            light_client(LightClient::ValidateStore(ValidateStoreResponse {
+                clients: res
+                    .clients
+                    .into_iter()
+                    .map(decode_client_validation)
+                    .collect::<Result<_, _>>()?,
+                pagination: decode_page_response(res.pagination)?,
+            }))
+        }
+        Res::VerifyAggregateMessages(res) => light_client(LightClient::VerifyAggregateMessages(
+            VerifyAggregateMessagesResponse {
+                verified: res.verified,
+            },
+        )),
+    })
+}
+
+fn encode_enclave_manage_response(res: &EnclaveManageResponse) -> command_response::Res {
+    use command_response::Res;
+    match res {
+        EnclaveManageResponse::GenerateEnclaveKey(res) => {
+            Res::GenerateEnclaveKey(proto::GenerateEnclaveKeyResponse {
+                pub_key: res.pub_key.as_array().to_vec(),
+                sealed_ek: res.sealed_ek.to_vec(),
+            })
+        }
+        EnclaveManageResponse::IASRemoteAttestation(res) => {
+            Res::IasRemoteAttestation(proto::IasRemoteAttestationResponse {
+                report: Some(encode_endorsed_report(&res.report)),
+            })
+        }
+        #[cfg(feature = "sgx-sw")]
+        EnclaveManageResponse::SimulateRemoteAttestation(res) => {
+            Res::SimulateRemoteAttestation(proto::SimulateRemoteAttestationResponse {
+                avr: Some(encode_avr(&res.avr)),
+            })
+        }
+        EnclaveManageResponse::ValidateDCAPCollateral(res) => {
+            Res::ValidateDcapCollateral(proto::ValidateDcapCollateralResponse {
+                root_ca_hash: res.root_ca_hash.to_vec(),
+            })
+        }
+        EnclaveManageResponse::StartInstance(res) => {
+            Res::StartInstance(proto::StartInstanceResponse {
+                counter: res.counter,
+                store_sequence: res.store_sequence,
+                sealed_sequence: res.sealed_sequence.clone(),
+            })
+        }
+        EnclaveManageResponse::UpdateOperators(_) => {
+            Res::UpdateOperators(proto::UpdateOperatorsResponse {})
+        }
+        EnclaveManageResponse::QueryOperators(res) => {
+            Res::QueryOperators(proto::QueryOperatorsResponse {
+                operator_set: res.operator_set.as_ref().map(encode_operator_set),
+            })
+        }
+        EnclaveManageResponse::QueryBuildInfo(res) => {
+            let info = &res.build_info;
+            Res::QueryBuildInfo(proto::QueryBuildInfoResponse {
+                build_info: Some(proto::BuildInfo {
+                    version: info.version.clone(),
+                    git_commit: info.git_commit.clone(),
+                    build_profile: info.build_profile.clone(),
+                    sgx_sdk_version: info.sgx_sdk_version.clone(),
+                    rustc_version: info.rustc_version.clone(),
+                    features: info.features.clone(),
+                    protocol_version: info.protocol_version,
+                }),
+            })
+        }
+        EnclaveManageResponse::BeginSession(res) => {
+            Res::BeginSession(proto::BeginSessionResponse {
+                challenge: res.challenge.to_vec(),
+            })
+        }
+        EnclaveManageResponse::OpenSession(res) => Res::OpenSession(proto::OpenSessionResponse {
+            session_token: res.session_token.to_vec(),
+            operator: res.operator.to_vec(),
+        }),
+        EnclaveManageResponse::RevokeEnclaveKeys(res) => {
+            Res::RevokeEnclaveKeys(proto::RevokeEnclaveKeysResponse {
+                revoked: encode_addresses(&res.revoked),
+            })
+        }
+        EnclaveManageResponse::UnsealStoreKey(res) => {
+            Res::UnsealStoreKey(proto::UnsealStoreKeyResponse {
+                key: res.key.to_vec(),
+                sealed_key: res.sealed_key.clone(),
+            })
+        }
+        EnclaveManageResponse::FinalizeStoreSequence(res) => {
+            Res::FinalizeStoreSequence(proto::FinalizeStoreSequenceResponse {
+                sealed_sequence: res.sealed_sequence.clone(),
+            })
+        }
+    }
+}
+
+fn encode_light_client_response(res: &LightClientResponse) -> command_response::Res {
+    use command_response::Res;
+    let proof = |proof: &CommitmentProof| Some(encode_commitment_proof(proof));
+    match res {
+        LightClientResponse::InitClient(res) => Res::InitClient(proto::InitClientResponse {
+            client_id: res.client_id.to_string(),
+            proof: proof(&res.proof),
+        }),
+        LightClientResponse::UpdateClient(res) => Res::UpdateClient(proto::UpdateClientResponse {
+            proof: proof(&res.0),
+        }),
+        LightClientResponse::AggregateMessages(res) => {
+            Res::AggregateMessages(proto::AggregateMessagesResponse {
+                proof: proof(&res.0),
+                constituent_signers: encode_addresses(&res.1),
+                constituent_signers_signature: res.2.clone(),
+            })
+        }
+        LightClientResponse::VerifyMembership(res) => {
+            Res::VerifyMembership(proto::VerifyMembershipResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::VerifyNonMembership(res) => {
+            Res::VerifyNonMembership(proto::VerifyNonMembershipResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::VerifyMembershipBatch(res) => {
+            Res::VerifyMembershipBatch(proto::VerifyMembershipBatchResponse {
+                proofs: res.0.iter().map(encode_commitment_proof).collect(),
+            })
+        }
+        LightClientResponse::VerifyKeyValue(res) => {
+            Res::VerifyKeyValue(proto::VerifyKeyValueResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::QueryClient(res) => Res::QueryClient(proto::QueryClientResponse {
+            client_state: Some(res.any_client_state.clone().into()),
+            consensus_state: Some(res.any_consensus_state.clone().into()),
+        }),
+        LightClientResponse::ListClients(res) => Res::ListClients(proto::ListClientsResponse {
+            client_ids: res.client_ids.iter().map(ToString::to_string).collect(),
+            pagination: Some(encode_page_response(&res.pagination)),
+        }),
+        LightClientResponse::QueryConsensusHeights(res) => {
+            Res::QueryConsensusHeights(proto::QueryConsensusHeightsResponse {
+                heights: res.heights.iter().map(|h| (*h).into()).collect(),
+                pagination: Some(encode_page_response(&res.pagination)),
+            })
+        }
+        LightClientResponse::QueryCapabilities(res) => {
+            let capabilities = &res.capabilities;
+            Res::QueryCapabilities(proto::QueryCapabilitiesResponse {
+                client_type: res.client_type.clone(),
+                version: res.version,
+                capabilities: Some(proto::Capabilities {
+                    misbehaviour: capabilities.misbehaviour,
+                    upgrade: capabilities.upgrade,
+                    proof_formats: capabilities.proof_formats.clone(),
+                    multipart_headers: capabilities.multipart_headers,
+                    key_value_verification: capabilities.key_value_verification,
+                    client_params: capabilities.client_params,
+                }),
+            })
+        }
+        LightClientResponse::PruneClient(res) => Res::PruneClient(proto::PruneClientResponse {
+            pruned_heights: res.pruned_heights.iter().map(|h| (*h).into()).collect(),
+            reclaimed_bytes: res.reclaimed_bytes,
+            expired: res.expired,
+        }),
+        LightClientResponse::SignLatestHeights(res) => {
+            Res::SignLatestHeights(proto::SignLatestHeightsResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::UpdateClientParams(res) => {
+            Res::UpdateClientParams(proto::UpdateClientParamsResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::ResignLatestState(res) => {
+            Res::ResignLatestState(proto::ResignLatestStateResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::ValidateStore(res) => {
+            Res::ValidateStore(proto::ValidateStoreResponse {
+                clients: res.clients.iter().map(encode_client_validation).collect(),
+                pagination: Some(encode_page_response(&res.pagination)),
+            })
+        }
+        LightClientResponse::MigrateClient(res) => {
+            Res::MigrateClient(proto::MigrateClientResponse {
+                proof: proof(&res.0),
+            })
+        }
+        LightClientResponse::VerifyAggregateMessages(res) => {
+            Res::VerifyAggregateMessages(proto::VerifyAggregateMessagesResponse {
+                verified: res.verified,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "sgx-sw")]
+fn encode_avr(
+    avr: &attestation_report::AttestationVerificationReport,
+) -> proto::AttestationVerificationReport {
+    proto::AttestationVerificationReport {
+        id: avr.id.clone(),
+        timestamp: avr.timestamp.clone(),
+        version: avr.version,
+        isv_enclave_quote_status: avr.isv_enclave_quote_status.clone(),
+        isv_enclave_quote_body: avr.isv_enclave_quote_body.clone(),
+        revocation_reason: avr.revocation_reason,
+        pse_manifest_status: avr.pse_manifest_status,
+        pse_manifest_hash: avr.pse_manifest_hash.clone(),
+        platform_info_blob: avr.platform_info_blob.clone(),
+        nonce: avr.nonce.clone(),
+        epid_pseudonym: avr.epid_pseudonym.clone(),
+        advisory_url: avr.advisory_url.clone(),
+        advisory_ids: avr.advisory_ids.clone(),
+    }
+}
+
+#[cfg(feature = "sgx-sw")]
+fn decode_avr(
+    msg: proto::AttestationVerificationReport,
+) -> attestation_report::AttestationVerificationReport {
+    attestation_report::AttestationVerificationReport {
+        id: msg.id,
+        timestamp: msg.timestamp,
+        version: msg.version,
+        isv_enclave_quote_status: msg.isv_enclave_quote_status,
+        isv_enclave_quote_body: msg.isv_enclave_quote_body,
+        revocation_reason: msg.revocation_reason,
+        pse_manifest_status: msg.pse_manifest_status,
+        pse_manifest_hash: msg.pse_manifest_hash,
+        platform_info_blob: msg.platform_info_blob,
+        nonce: msg.nonce,
+        epid_pseudonym: msg.epid_pseudonym,
+        advisory_url: msg.advisory_url,
+        advisory_ids: msg.advisory_ids,
+    }
+}
+
+fn decode_build_info(msg: proto::BuildInfo) -> BuildInfo {
+    BuildInfo {
+        version: msg.version,
+        git_commit: msg.git_commit,
+        build_profile: msg.build_profile,
+        sgx_sdk_version: msg.sgx_sdk_version,
+        rustc_version: msg.rustc_version,
+        features: msg.features,
+        protocol_version: msg.protocol_version,
+    }
+}
+
+fn encode_client_validation(validation: &ClientValidation) -> proto::ClientValidation {
+    proto::ClientValidation {
+        client_id: validation.client_id.to_string(),
+        consensus_states: validation.consensus_states,
+        incompatibilities: validation
+            .incompatibilities
+            .iter()
+            .map(|incompatibility| proto::StateIncompatibility {
+                height: incompatibility.height.map(Into::into),
+                reason: incompatibility.reason.clone(),
+            })
+            .collect(),
+        incompatible_states: validation.incompatible_states,
+    }
+}
+
+fn decode_client_validation(
+    msg: proto::ClientValidation,
+) -> Result<ClientValidation, ProtocolError> {
+    Ok(ClientValidation {
+        client_id: decode_client_id("client_id", &msg.client_id)?,
+        consensus_states: msg.consensus_states,
+        incompatibilities: msg
+            .incompatibilities
+            .into_iter()
+            .map(|incompatibility| StateIncompatibility {
+                height: incompatibility.height.map(Height::from),
+                reason: incompatibility.reason,
+            })
+            .collect(),
+        incompatible_states: msg.incompatible_states,
+    })
+}
+
+/// Encode the proof with its `lcp.ecall.v1` message, which the update history of the host also stores
+pub fn encode_commitment_proof(proof: &CommitmentProof) -> proto::CommitmentProof {
+    proto::CommitmentProof {
+        message: proof.message.clone(),
+        signer: proof.signer.to_vec(),
+        signature: proof.signature.clone(),
+        co_signatures: proof
+            .co_signatures
+            .iter()
+            .map(|co_signature| proto::CoSignature {
+                signer: co_signature.signer.to_vec(),
+                signature: co_signature.signature.clone(),
+            })
+            .collect(),
+        request_binding: proof
+            .request_binding
+            .as_ref()
+            .map(|binding| proto::RequestBinding {
+                request_digest: binding.request_digest.to_vec(),
+                signature: binding.signature.clone(),
+            }),
+        signature_scheme: Some(encode_signature_scheme(&proof.signature_scheme)),
+    }
+}
+
+/// Decode the proof encoded by `encode_commitment_proof`
+pub fn decode_commitment_proof(
+    msg: Option<proto::CommitmentProof>,
+) -> Result<CommitmentProof, ProtocolError> {
+    let msg = required("proof", msg)?;
+    Ok(CommitmentProof {
+        message: msg.message,
+        signer: decode_address("proof.signer", &msg.signer)?,
+        signature: msg.signature,
+        co_signatures: msg
+            .co_signatures
+            .into_iter()
+            .map(|co_signature| {
+                Ok(CoSignature {
+                    signer: decode_address("proof.co_signatures.signer", &co_signature.signer)?,
+                    signature: co_signature.signature,
+                })
+            })
+            .collect::<Result<_, ProtocolError>>()?,
+        request_binding: match msg.request_binding {
+            Some(binding) => Some(RequestBinding {
+                request_digest: decode_array(
+                    "proof.request_binding.request_digest",
+                    &binding.request_digest,
+                )?,
+                signature: binding.signature,
+            }),
+            None => None,
+        },
+        signature_scheme: msg
+            .signature_scheme
+            .map(decode_signature_scheme)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn encode_signature_scheme(scheme: &SignatureScheme) -> proto::SignatureScheme {
+    proto::SignatureScheme {
+        digest: scheme.digest.as_str().into(),
+        format: scheme.format.as_str().into(),
+    }
+}
+
+fn decode_signature_scheme(msg: proto::SignatureScheme) -> Result<SignatureScheme, ProtocolError> {
+    Ok(SignatureScheme::new(
+        msg.digest
+            .parse()
+            .map_err(field_error("signature_scheme.digest"))?,
+        msg.format
+            .parse()
+            .map_err(field_error("signature_scheme.format"))?,
+    ))
+}
+
+pub(crate) fn encode_event(event: &CommandEvent) -> proto::CommandEvent {
+    use command_event::Event;
+    let event = match event {
+        CommandEvent::ClientCreated {
+            client_id,
+            client_type,
+            height,
+        } => Event::ClientCreated(proto::ClientCreated {
+            client_id: client_id.to_string(),
+            client_type: client_type.clone(),
+            height: Some((*height).into()),
+        }),
+        CommandEvent::ClientUpdated {
+            client_id,
+            prev_height,
+            post_height,
+        } => Event::ClientUpdated(proto::ClientUpdated {
+            client_id: client_id.to_string(),
+            prev_height: prev_height.map(Into::into),
+            post_height: Some((*post_height).into()),
+        }),
+        CommandEvent::StateEmitted {
+            client_id,
+            height,
+            state,
+        } => Event::StateEmitted(proto::StateEmitted {
+            client_id: client_id.to_string(),
+            height: Some((*height).into()),
+            state: Some(state.clone().into()),
+        }),
+        CommandEvent::ClientFrozen {
+            client_id,
+            frozen_height,
+        } => Event::ClientFrozen(proto::ClientFrozen {
+            client_id: client_id.to_string(),
+            frozen_height: frozen_height.map(Into::into),
+        }),
+        CommandEvent::ClientMigrated {
+            client_id,
+            from_type_url,
+            from_version,
+            to_type_url,
+            to_version,
+            height,
+        } => Event::ClientMigrated(proto::ClientMigrated {
+            client_id: client_id.to_string(),
+            from_type_url: from_type_url.clone(),
+            from_version: *from_version,
+            to_type_url: to_type_url.clone(),
+            to_version: *to_version,
+            height: Some((*height).into()),
+        }),
+    };
+    proto::CommandEvent { event: Some(event) }
+}
+
+pub(crate) fn decode_event(msg: proto::CommandEvent) -> Result<CommandEvent, ProtocolError> {
+    use command_event::Event;
+    Ok(match required("event", msg.event)? {
+        Event::ClientCreated(event) => CommandEvent::ClientCreated {
+            client_id: decode_client_id("client_id", &event.client_id)?,
+            client_type: event.client_type,
+            height: decode_height("height", event.height)?,
+        },
+        Event::ClientUpdated(event) => CommandEvent::ClientUpdated {
+            client_id: decode_client_id("client_id", &event.client_id)?,
+            prev_height: event.prev_height.map(Height::from),
+            post_height: decode_height("post_height", event.post_height)?,
+        },
+        Event::StateEmitted(event) => CommandEvent::StateEmitted {
+            client_id: decode_client_id("client_id", &event.client_id)?,
+            height: decode_height("height", event.height)?,
+            state: decode_any("state", event.state)?,
+        },
+        Event::ClientFrozen(event) => CommandEvent::ClientFrozen {
+            client_id: decode_client_id("client_id", &event.client_id)?,
+            frozen_height: event.frozen_height.map(Height::from),
+        },
+        Event::ClientMigrated(event) => CommandEvent::ClientMigrated {
+            client_id: decode_client_id("client_id", &event.client_id)?,
+            from_type_url: event.from_type_url,
+            from_version: event.from_version,
+            to_type_url: event.to_type_url,
+            to_version: event.to_version,
+            height: decode_height("height", event.height)?,
+        },
+    })
+}
+
+pub(crate) fn encode_store_checkpoint(checkpoint: &StoreCheckpoint) -> proto::StoreCheckpoint {
+    proto::StoreCheckpoint {
+        sequence: checkpoint.sequence,
+        sealed_sequence: checkpoint.sealed_sequence.clone(),
+        signature: checkpoint.signature.clone(),
+    }
+}
+
+pub(crate) fn decode_store_checkpoint(msg: proto::StoreCheckpoint) -> StoreCheckpoint {
+    StoreCheckpoint {
+        sequence: msg.sequence,
+        sealed_sequence: msg.sealed_sequence,
+        signature: msg.signature,
+    }
+}
+
+fn field_error<E: Debug>(field: &'static str) -> impl FnOnce(E) -> ProtocolError {
+    move |e| ProtocolError::invalid_field(field.into(), format!("{:?}", e))
+}
+
+fn required<T>(field: &'static str, value: Option<T>) -> Result<T, ProtocolError> {
+    value.ok_or_else(|| ProtocolError::invalid_field(field.into(), "must be non-nil".into()))
+}
+
+fn decode_client_id(field: &'static str, client_id: &str) -> Result<ClientId, ProtocolError> {
+    ClientId::from_str(client_id).map_err(field_error(field))
+}
+
+fn decode_client_ids(
+    field: &'static str,
+    client_ids: &[String],
+) -> Result<Vec<ClientId>, ProtocolError> {
+    client_ids
+        .iter()
+        .map(|client_id| decode_client_id(field, client_id))
+        .collect()
+}
+
+fn encode_addresses(addresses: &[Address]) -> Vec<Vec<u8>> {
+    addresses.iter().map(Address::to_vec).collect()
+}
+
+fn decode_address(field: &'static str, bz: &[u8]) -> Result<Address, ProtocolError> {
+    Address::try_from(bz).map_err(field_error(field))
+}
+
+fn decode_addresses(
+    field: &'static str,
+    addresses: &[Vec<u8>],
+) -> Result<Vec<Address>, ProtocolError> {
+    addresses
+        .iter()
+        .map(|bz| decode_address(field, bz))
+        .collect()
+}
+
+/// Returns the bytes of the key, or empty if there is no key
+fn encode_sealed_ek(sealed_ek: Option<&SealedEnclaveKey>) -> Vec<u8> {
+    sealed_ek.map(|k| k.to_vec()).unwrap_or_default()
+}
+
+fn decode_sealed_ek(
+    field: &'static str,
+    bz: &[u8],
+) -> Result<Option<SealedEnclaveKey>, ProtocolError> {
+    if bz.is_empty() {
+        return Ok(None);
+    }
+    SealedEnclaveKey::new_from_bytes(bz)
+        .map(Some)
+        .map_err(field_error(field))
+}
+
+fn decode_array<const N: usize>(field: &'static str, bz: &[u8]) -> Result<[u8; N], ProtocolError> {
+    bz.try_into().map_err(|_| {
+        ProtocolError::invalid_field(
+            field.into(),
+            format!("must be {} bytes: len={}", N, bz.len()),
+        )
+    })
+}
+
+fn decode_any(field: &'static str, any: Option<ProtoAny>) -> Result<Any, ProtocolError> {
+    required(field, any).map(Any::from)
+}
+
+fn decode_height(field: &'static str, height: Option<RawHeight>) -> Result<Height, ProtocolError> {
+    required(field, height).map(Height::from)
+}
+
+fn decode_prefix(prefix: Vec<u8>) -> Result<CommitmentPrefix, ProtocolError> {
+    CommitmentPrefix::try_from(prefix).map_err(field_error("prefix"))
+}
+
+/// Returns None if the string is empty, which is the default value of an unset field
+fn decode_optional_str<T>(field: &'static str, s: &str) -> Result<Option<T>, ProtocolError>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    if s.is_empty() {
+        return Ok(None);
+    }
+    s.parse().map(Some).map_err(field_error(field))
+}
+
+fn encode_time(time: Time) -> Result<u64, ProtocolError> {
+    time.as_unix_timestamp_nanos()
+        .try_into()
+        .map_err(|_| ProtocolError::encode(format!("timestamp out of range: time={:?}", time)))
+}
+
+fn decode_time(field: &'static str, nanos: u64) -> Result<Time, ProtocolError> {
+    Time::from_unix_timestamp_nanos(nanos.into()).map_err(field_error(field))
+}
+
+fn decode_optional_time(
+    field: &'static str,
+    nanos: Option<u64>,
+) -> Result<Option<Time>, ProtocolError> {
+    nanos.map(|nanos| decode_time(field, nanos)).transpose()
+}
+
+fn encode_duration(duration: Duration) -> Result<u64, ProtocolError> {
+    duration.as_nanos().try_into().map_err(|_| {
+        ProtocolError::encode(format!("duration out of range: duration={:?}", duration))
+    })
+}
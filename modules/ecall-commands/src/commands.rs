@@ -1,8 +1,10 @@
 use crate::{
-    prelude::*, EnclaveKeySelector, EnclaveManageCommand, EnclaveManageResponse,
-    LightClientCommand, LightClientResponse,
+    encode_command_message, prelude::*, CommandEvent, EnclaveKeySelector, EnclaveManageCommand,
+    EnclaveManageResponse, ErrorCode, InputValidationError, LightClientCommand,
+    LightClientExecuteCommand, LightClientResponse, StoreCheckpoint, SESSION_TOKEN_SIZE,
 };
-use crypto::SealedEnclaveKey;
+use attestation_report::{DCAPQuoteBundle, EndorsedAttestationVerificationReport};
+use crypto::{Keccak256, SealedEnclaveKey};
 use lcp_types::Time;
 use serde::{Deserialize, Serialize};
 use store::TxId;
@@ -23,7 +25,21 @@ impl ECallCommand {
 pub struct CommandContext {
     pub current_timestamp: Time,
     pub sealed_ek: Option<SealedEnclaveKey>,
+    /// The incoming key that co-signs commitments while `sealed_ek` is being rotated out
+    pub co_sealed_ek: Option<SealedEnclaveKey>,
+    /// The keys of the other signers of the messages to aggregate, which the enclave unseals to verify the messages
+    pub constituent_sealed_eks: Vec<SealedEnclaveKey>,
+    /// The attestation of each key in `constituent_sealed_eks`, which the enclave verifies against its own MRENCLAVE
+    pub constituent_attestations: Vec<KeyAttestation>,
+    /// Signatures of the operators over `Command::operator_sign_bytes`
+    pub operator_signatures: Vec<Vec<u8>>,
     pub tx_id: TxId,
+    /// The token of the session opened by the host
+    pub session_token: Option<[u8; SESSION_TOKEN_SIZE]>,
+    /// True if the command has been received in a frame of `LEGACY_PROTOCOL_VERSION`,
+    /// whose hosts digest the bincode encoding of the command
+    #[serde(skip)]
+    pub legacy_encoding: bool,
 }
 
 impl CommandContext {
@@ -31,9 +47,49 @@ impl CommandContext {
         Self {
             current_timestamp,
             sealed_ek,
+            co_sealed_ek: None,
+            constituent_sealed_eks: vec![],
+            constituent_attestations: vec![],
+            operator_signatures: vec![],
             tx_id,
+            session_token: None,
+            legacy_encoding: false,
         }
     }
+
+    pub fn with_co_sealed_ek(mut self, co_sealed_ek: SealedEnclaveKey) -> Self {
+        self.co_sealed_ek = Some(co_sealed_ek);
+        self
+    }
+
+    pub fn with_constituent_keys(
+        mut self,
+        sealed_eks: Vec<SealedEnclaveKey>,
+        attestations: Vec<KeyAttestation>,
+    ) -> Self {
+        self.constituent_sealed_eks = sealed_eks;
+        self.constituent_attestations = attestations;
+        self
+    }
+
+    pub fn with_operator_signatures(mut self, operator_signatures: Vec<Vec<u8>>) -> Self {
+        self.operator_signatures = operator_signatures;
+        self
+    }
+
+    pub fn with_session_token(mut self, session_token: [u8; SESSION_TOKEN_SIZE]) -> Self {
+        self.session_token = Some(session_token);
+        self
+    }
+}
+
+/// KeyAttestation is the remote attestation of an enclave key
+///
+/// The report data of the quote in either of them commits to the address of the key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum KeyAttestation {
+    IAS(EndorsedAttestationVerificationReport),
+    DCAP(DCAPQuoteBundle),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -51,9 +107,116 @@ impl EnclaveKeySelector for Command {
     }
 }
 
+impl Command {
+    /// Returns keccak256 of the `lcp.ecall.v1` message of the command
+    ///
+    /// The enclave binds the proofs in the response of a light client command to this digest,
+    /// so the host can check that the response is the result of the command it submitted.
+    pub fn request_digest(&self) -> Result<[u8; 32], InputValidationError> {
+        Ok(self.digest_encoding(false)?.keccak256())
+    }
+
+    /// Returns keccak256 of the bincode-encoded command, which the hosts of `LEGACY_PROTOCOL_VERSION` bind the proofs to
+    pub fn legacy_request_digest(&self) -> Result<[u8; 32], InputValidationError> {
+        Ok(self.digest_encoding(true)?.keccak256())
+    }
+
+    /// Returns the encoding of the command that its digests are based on
+    pub(crate) fn digest_encoding(&self, legacy: bool) -> Result<Vec<u8>, InputValidationError> {
+        let encoded = if legacy {
+            bincode::serde::encode_to_vec(self, bincode::config::standard())
+                .map_err(|e| e.to_string())
+        } else {
+            encode_command_message(self).map_err(|e| e.to_string())
+        };
+        encoded.map_err(|e| {
+            InputValidationError::invalid_argument(format!("failed to encode command: {}", e))
+        })
+    }
+
+    /// Returns the signers of the messages to aggregate other than the enclave key of the command
+    pub fn constituent_signers(&self) -> Vec<crypto::Address> {
+        match self {
+            Self::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::AggregateMessages(input),
+            )) => input
+                .constituent_signers()
+                .into_iter()
+                .filter(|signer| *signer != input.signer)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns true if the command verifies its input without changing the state or signing a commitment
+    pub fn is_dry_run(&self) -> bool {
+        matches!(
+            self,
+            Self::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::UpdateClient(input)
+            )) if input.dry_run
+        )
+    }
+
+    /// Returns true if the command may change the light client states or the sealed records in the store
+    ///
+    /// The enclave advances the store sequence in the transaction of such a command.
+    pub fn changes_store(&self) -> bool {
+        use LightClientExecuteCommand::*;
+        match self {
+            Self::LightClient(LightClientCommand::Execute(cmd)) => match cmd {
+                UpdateClient(input) => !input.dry_run,
+                InitClient(_) | PruneClient(_) | UpdateClientParams(_) | MigrateClient(_) => true,
+                AggregateMessages(_)
+                | VerifyMembership(_)
+                | VerifyNonMembership(_)
+                | VerifyMembershipBatch(_)
+                | VerifyKeyValue(_)
+                | SignLatestHeights(_)
+                | ResignLatestState(_) => false,
+            },
+            Self::EnclaveManage(
+                EnclaveManageCommand::RevokeEnclaveKeys(_)
+                | EnclaveManageCommand::UpdateOperators(_),
+            ) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum CommandResponse {
     EnclaveManage(EnclaveManageResponse),
     LightClient(LightClientResponse),
-    CommandError(String),
+    CommandError(ErrorCode, String),
+}
+
+/// CommandResult is the response to a command with the events of the state changes it made
+#[derive(Debug)]
+pub struct CommandResult {
+    pub response: CommandResponse,
+    pub events: Vec<CommandEvent>,
+    /// The store sequence that the command advanced in its transaction, which the host records after the commit
+    pub store_checkpoint: Option<StoreCheckpoint>,
+}
+
+impl CommandResult {
+    pub fn new(response: CommandResponse, events: Vec<CommandEvent>) -> Self {
+        Self {
+            response,
+            events,
+            store_checkpoint: None,
+        }
+    }
+
+    pub fn with_store_checkpoint(mut self, store_checkpoint: StoreCheckpoint) -> Self {
+        self.store_checkpoint = Some(store_checkpoint);
+        self
+    }
+}
+
+impl From<CommandResponse> for CommandResult {
+    fn from(response: CommandResponse) -> Self {
+        Self::new(response, vec![])
+    }
 }
@@ -1,6 +1,6 @@
-use crate::{prelude::*, EnclaveKeySelector, InputValidationError as Error};
-use attestation_report::EndorsedAttestationVerificationReport;
-use crypto::{Address, EnclavePublicKey, SealedEnclaveKey};
+use crate::{prelude::*, EnclaveKeySelector, InputValidationError as Error, OperatorSet};
+use attestation_report::{AdvisoryPolicy, DCAPQuoteBundle, EndorsedAttestationVerificationReport};
+use crypto::{verify_signature_address, Address, EnclavePublicKey, Keccak256, SealedEnclaveKey};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -9,6 +9,16 @@ pub enum EnclaveManageCommand {
     IASRemoteAttestation(IASRemoteAttestationInput),
     #[cfg(feature = "sgx-sw")]
     SimulateRemoteAttestation(SimulateRemoteAttestationInput),
+    ValidateDCAPCollateral(ValidateDCAPCollateralInput),
+    StartInstance(StartInstanceInput),
+    UpdateOperators(UpdateOperatorsInput),
+    QueryOperators(QueryOperatorsInput),
+    QueryBuildInfo(QueryBuildInfoInput),
+    BeginSession(BeginSessionInput),
+    OpenSession(OpenSessionInput),
+    RevokeEnclaveKeys(RevokeEnclaveKeysInput),
+    UnsealStoreKey(UnsealStoreKeyInput),
+    FinalizeStoreSequence(FinalizeStoreSequenceInput),
 }
 
 impl EnclaveKeySelector for EnclaveManageCommand {
@@ -18,6 +28,16 @@ impl EnclaveKeySelector for EnclaveManageCommand {
             Self::IASRemoteAttestation(input) => Some(input.target_enclave_key),
             #[cfg(feature = "sgx-sw")]
             Self::SimulateRemoteAttestation(input) => Some(input.target_enclave_key),
+            Self::ValidateDCAPCollateral(input) => Some(input.target_enclave_key),
+            Self::StartInstance(_) => None,
+            Self::UpdateOperators(_) => None,
+            Self::QueryOperators(_) => None,
+            Self::QueryBuildInfo(_) => None,
+            Self::BeginSession(_) => None,
+            Self::OpenSession(_) => None,
+            Self::RevokeEnclaveKeys(_) => None,
+            Self::UnsealStoreKey(_) => None,
+            Self::FinalizeStoreSequence(_) => None,
         }
     }
 }
@@ -25,11 +45,297 @@ impl EnclaveKeySelector for EnclaveManageCommand {
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct GenerateEnclaveKeyInput;
 
+/// The key of the sealed sequence of the store, which the enclave increments in the transaction of each command that changes the store
+pub const STORE_SEQUENCE_KEY: &[u8] = b"lcp/store_sequence";
+
+/// The size of the random identifier of a store, which the enclave chooses when it starts the first instance on it
+pub const STORE_ID_SIZE: usize = 16;
+
+/// StoreSequenceRecord is the sequence of the store that the enclave seals in the store and in the checkpoint of the host
+///
+/// Each record is chained to the sealed record of the previous sequence and carries the digests of the sealed records
+/// bound to the sequence, so neither the record nor a bound record can be replaced with an earlier one
+/// without breaking the chain from the checkpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct StoreSequenceRecord {
+    pub store_id: [u8; STORE_ID_SIZE],
+    pub sequence: u64,
+    /// keccak256 of the sealed record of the previous sequence, or zero for the genesis record
+    pub previous: [u8; 32],
+    /// The keys of the sealed records bound to the sequence, and keccak256 of each of them
+    pub bound_records: Vec<(Vec<u8>, [u8; 32])>,
+}
+
+impl StoreSequenceRecord {
+    /// Returns the genesis record of a store, which the first instance writes before any command changes the store
+    pub fn genesis(store_id: [u8; STORE_ID_SIZE]) -> Self {
+        Self {
+            store_id,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the record of the next sequence, which is chained to `sealed`, the sealed bytes of this record
+    pub fn next(&self, sealed: &[u8], bound_records: Vec<(Vec<u8>, [u8; 32])>) -> Self {
+        Self {
+            store_id: self.store_id,
+            sequence: self.sequence + 1,
+            previous: sealed.keccak256(),
+            bound_records,
+        }
+    }
+
+    /// Returns keccak256 of the sealed record bound to `key`, or None if no record is bound to it
+    pub fn bound_record(&self, key: &[u8]) -> Option<[u8; 32]> {
+        self.bound_records
+            .iter()
+            .find(|(k, _)| k.as_slice() == key)
+            .map(|(_, digest)| *digest)
+    }
+}
+
+/// FinalizedStoreSequence is the latest sequence of the store that the enclave has finalized
+///
+/// The enclave seals it only after the host has acknowledged that the transaction of the sequence has been committed,
+/// and the host records it outside the store as the checkpoint that the next instance starts from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct FinalizedStoreSequence {
+    pub store_id: [u8; STORE_ID_SIZE],
+    pub sequence: u64,
+    /// keccak256 of the sealed record of the sequence in the store
+    pub record: [u8; 32],
+}
+
+/// StoreRecovery is the state of the sequence record in the store against the finalized sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreRecovery {
+    /// The record is the finalized one
+    Finalized,
+    /// The record is the prepared commit chained to the finalized one, which the host has committed
+    /// but the enclave has not finalized yet
+    Prepared,
+}
+
+impl FinalizedStoreSequence {
+    /// Returns the sequence of the record whose sealed bytes are `sealed`
+    pub fn new(record: &StoreSequenceRecord, sealed: &[u8]) -> Self {
+        Self {
+            store_id: record.store_id,
+            sequence: record.sequence,
+            record: sealed.keccak256(),
+        }
+    }
+
+    /// Returns whether the record in the store, whose sealed bytes are `sealed`, is the finalized one
+    /// or the prepared commit chained to it
+    ///
+    /// Any other record means that the store has been rolled back, or replaced with another one.
+    pub fn recover(
+        &self,
+        record: &StoreSequenceRecord,
+        sealed: &[u8],
+    ) -> Result<StoreRecovery, Error> {
+        if record.store_id != self.store_id {
+            return Err(Error::invalid_argument(
+                "the store sequence belongs to another store".to_string(),
+            ));
+        }
+        if record.sequence == self.sequence {
+            if sealed.keccak256() != self.record {
+                return Err(Error::invalid_argument(format!(
+                    "the store sequence has been replaced: sequence={}",
+                    record.sequence
+                )));
+            }
+            Ok(StoreRecovery::Finalized)
+        } else if record.sequence == self.sequence + 1 {
+            if record.previous != self.record {
+                return Err(Error::invalid_argument(format!(
+                    "the store sequence is not chained to the finalized one: sequence={}",
+                    record.sequence
+                )));
+            }
+            Ok(StoreRecovery::Prepared)
+        } else {
+            Err(Error::invalid_argument(format!(
+                "the store sequence doesn't follow the finalized one: sequence={} finalized={}",
+                record.sequence, self.sequence
+            )))
+        }
+    }
+}
+
+const STORE_CHECKPOINT_DOMAIN: &[u8] = b"lcp-store-checkpoint:";
+
+/// StoreCheckpoint is the prepared commit of the store sequence that a command wrote in its transaction
+///
+/// After the host commits the transaction, it acknowledges the commit with `FinalizeStoreSequence`,
+/// and records the finalized sequence that the enclave returns outside the store.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct StoreCheckpoint {
+    pub sequence: u64,
+    /// The sealed `StoreSequenceRecord` that the command wrote in the store
+    pub sealed_sequence: Vec<u8>,
+    /// The signature of the enclave key of the command over `sign_bytes`, or empty if the command has no enclave key
+    ///
+    /// The host cannot read the sealed record, so it verifies the signature before it commits the transaction.
+    pub signature: Vec<u8>,
+}
+
+impl StoreCheckpoint {
+    pub fn sign_bytes(&self) -> Vec<u8> {
+        let mut bz = STORE_CHECKPOINT_DOMAIN.to_vec();
+        bz.extend_from_slice(&self.sequence.to_be_bytes());
+        bz.extend_from_slice(&self.sealed_sequence.keccak256());
+        bz
+    }
+
+    /// Returns an error unless the checkpoint is signed by `signer`
+    pub fn verify(&self, signer: Address) -> Result<(), Error> {
+        let actual = verify_signature_address(&self.sign_bytes(), &self.signature)?;
+        if actual != signer {
+            return Err(Error::invalid_argument(format!(
+                "the store checkpoint is not signed by the enclave key of the command: expected={} actual={}",
+                signer, actual
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StartInstanceInput {
+    /// The sealed `FinalizedStoreSequence` that the host recorded outside the store at the latest finalization
+    ///
+    /// It is required once a command has changed the store, and the instance refuses to start unless the record
+    /// in the store is the finalized one or the prepared commit chained to it, i.e. the store has been rolled back or replaced.
+    pub store_checkpoint: Option<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateOperatorsInput {
+    pub operator_set: OperatorSet,
+    /// Signatures of the current operators over `operator_set.sign_bytes()`
+    ///
+    /// The initial operator set doesn't require any signatures.
+    pub signatures: Vec<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryOperatorsInput;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryBuildInfoInput;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BeginSessionInput;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpenSessionInput {
+    /// Signature of an operator over `session_sign_bytes` of the challenge of `BeginSession`
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RevokeEnclaveKeysInput {
+    /// The addresses of the keys that must no longer sign or be aggregated by the enclave
+    pub addresses: Vec<Address>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UnsealStoreKeyInput {
+    /// The key of the store encryption that this enclave sealed, or empty to generate a new one
+    pub sealed_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FinalizeStoreSequenceInput {
+    /// The sequence of the prepared commit whose transaction the host has committed
+    pub sequence: u64,
+}
+
+/// The key of the sealed revocation record in the store
+pub const REVOCATION_RECORD_KEY: &[u8] = b"lcp/revoked_keys";
+
+/// RevocationRecord is the set of the revoked enclave keys that the enclave seals in the store
+///
+/// The record is bound to the store sequence, so the host cannot replace it with an earlier one to revive a revoked key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct RevocationRecord {
+    pub revoked: Vec<Address>,
+}
+
+impl RevocationRecord {
+    pub fn is_revoked(&self, address: &Address) -> bool {
+        self.revoked.contains(address)
+    }
+
+    /// Adds the addresses that are not revoked yet and returns them
+    pub fn revoke(&mut self, addresses: &[Address]) -> Vec<Address> {
+        let mut added = Vec::new();
+        for address in addresses {
+            if !self.is_revoked(address) && !added.contains(address) {
+                added.push(*address);
+            }
+        }
+        self.revoked.extend_from_slice(&added);
+        added
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct IASRemoteAttestationInput {
     pub target_enclave_key: Address,
     pub spid: Vec<u8>,
     pub ias_key: Vec<u8>,
+    /// The signature type of the EPID quote, which must match the type the SPID is registered for
+    pub quote_type: QuoteSignType,
+    /// If set, the advisory IDs in the report must satisfy the policy at the current time
+    pub advisory_policy: Option<AdvisoryPolicy>,
+    /// Mitigations that the operator has applied to the platform, which the policy may require
+    pub applied_mitigations: Vec<String>,
+}
+
+/// QuoteSignType is the EPID signature type of a quote
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteSignType {
+    /// The quotes of the same platform can be linked, which allows IAS to track the platform
+    Linkable,
+    /// The quotes of the same platform cannot be linked to each other
+    #[default]
+    Unlinkable,
+}
+
+impl QuoteSignType {
+    /// Returns the value of `sign_type` in the quote
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            Self::Unlinkable => 0,
+            Self::Linkable => 1,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Linkable => "linkable",
+            Self::Unlinkable => "unlinkable",
+        }
+    }
+}
+
+impl core::str::FromStr for QuoteSignType {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linkable" => Ok(Self::Linkable),
+            "unlinkable" => Ok(Self::Unlinkable),
+            _ => Err(Error::invalid_argument(format!(
+                "quote type must be either `linkable` or `unlinkable`: actual={}",
+                s
+            ))),
+        }
+    }
 }
 
 impl IASRemoteAttestationInput {
@@ -59,12 +365,40 @@ impl SimulateRemoteAttestationInput {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ValidateDCAPCollateralInput {
+    pub target_enclave_key: Address,
+    pub bundle: DCAPQuoteBundle,
+}
+
+impl ValidateDCAPCollateralInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.bundle.quote.is_empty() {
+            Err(Error::invalid_argument(
+                "quote must be non-empty".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum EnclaveManageResponse {
     GenerateEnclaveKey(GenerateEnclaveKeyResponse),
     IASRemoteAttestation(IASRemoteAttestationResponse),
     #[cfg(feature = "sgx-sw")]
     SimulateRemoteAttestation(SimulateRemoteAttestationResponse),
+    ValidateDCAPCollateral(ValidateDCAPCollateralResponse),
+    StartInstance(StartInstanceResponse),
+    UpdateOperators(UpdateOperatorsResponse),
+    QueryOperators(QueryOperatorsResponse),
+    QueryBuildInfo(QueryBuildInfoResponse),
+    BeginSession(BeginSessionResponse),
+    OpenSession(OpenSessionResponse),
+    RevokeEnclaveKeys(RevokeEnclaveKeysResponse),
+    UnsealStoreKey(UnsealStoreKeyResponse),
+    FinalizeStoreSequence(FinalizeStoreSequenceResponse),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -83,3 +417,250 @@ pub struct IASRemoteAttestationResponse {
 pub struct SimulateRemoteAttestationResponse {
     pub avr: attestation_report::AttestationVerificationReport,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ValidateDCAPCollateralResponse {
+    /// keccak256 hash of the Intel SGX Root CA certificate that the quote and the collateral have been verified against
+    pub root_ca_hash: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StartInstanceResponse {
+    /// The counter of the started instance, which is incremented every time an instance starts
+    pub counter: u64,
+    /// The sequence of the store that the instance has started on
+    pub store_sequence: u64,
+    /// The sealed `FinalizedStoreSequence` that the instance has started on, which the host records as the checkpoint
+    ///
+    /// It differs from the checkpoint of the host if the instance finalized a prepared commit that the host had committed
+    /// before the restart, or if it is the genesis sequence of a new store.
+    pub sealed_sequence: Option<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateOperatorsResponse;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryOperatorsResponse {
+    /// None if the operator approvals are not required
+    pub operator_set: Option<OperatorSet>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryBuildInfoResponse {
+    pub build_info: BuildInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BeginSessionResponse {
+    /// The challenge that the host signs to open the session, which is valid only for the next `OpenSession`
+    pub challenge: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpenSessionResponse {
+    /// The token that the host must set to the context of the subsequent commands
+    pub session_token: [u8; 32],
+    /// The operator that signed the challenge, or the enclave key that the session is bound to
+    pub operator: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RevokeEnclaveKeysResponse {
+    /// The addresses that have been revoked by the command, excluding the ones revoked before
+    pub revoked: Vec<Address>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UnsealStoreKeyResponse {
+    /// The key that the host encrypts the values of the store with
+    pub key: [u8; 32],
+    /// The sealed key, which the host keeps on the disk to unseal it again after a restart
+    pub sealed_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FinalizeStoreSequenceResponse {
+    /// The sealed `FinalizedStoreSequence`, which the host records outside the store as the checkpoint
+    pub sealed_sequence: Vec<u8>,
+}
+
+/// BuildInfo is the metadata embedded in the enclave binary when it is built
+///
+/// It is not covered by the attestation on its own, but the MRENCLAVE of a reproducible build
+/// can be correlated with the source it was built from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The version of the enclave crates
+    pub version: String,
+    /// The commit hash of the source tree, with a `-dirty` suffix if it had uncommitted changes
+    pub git_commit: String,
+    /// `release` or `debug`
+    pub build_profile: String,
+    /// The version of the Rust SGX SDK
+    pub sgx_sdk_version: String,
+    pub rustc_version: String,
+    /// The enabled features that change the behavior of the enclave, e.g. `sgx-sw` and `production`
+    pub features: Vec<String>,
+    /// The version of the protocol between the host and the enclave
+    pub protocol_version: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{EnclaveKey, Signer};
+
+    /// Returns distinct bytes for each record and nonce, as the sealing does
+    fn seal(record: &StoreSequenceRecord, nonce: u8) -> Vec<u8> {
+        let mut bz = bincode::serde::encode_to_vec(record, bincode::config::standard()).unwrap();
+        bz.push(nonce);
+        bz
+    }
+
+    #[test]
+    fn test_finalized_store_sequence() {
+        let genesis = StoreSequenceRecord::genesis([7; STORE_ID_SIZE]);
+        let sealed_genesis = seal(&genesis, 0);
+        let finalized = FinalizedStoreSequence::new(&genesis, &sealed_genesis);
+        let first = genesis.next(&sealed_genesis, vec![(b"key".to_vec(), [1; 32])]);
+        let sealed_first = seal(&first, 0);
+        assert_eq!(first.sequence, 1);
+        assert_eq!(first.bound_record(b"key"), Some([1; 32]));
+        assert_eq!(first.bound_record(b"other"), None);
+
+        assert_eq!(
+            finalized.recover(&genesis, &sealed_genesis).unwrap(),
+            StoreRecovery::Finalized
+        );
+        assert_eq!(
+            finalized.recover(&first, &sealed_first).unwrap(),
+            StoreRecovery::Prepared
+        );
+        // the host replaced the record with another one of the same sequence
+        assert!(finalized.recover(&genesis, &seal(&genesis, 1)).is_err());
+        // a prepared commit chained to another record
+        let forked = genesis.next(&seal(&genesis, 1), vec![]);
+        assert!(finalized.recover(&forked, &seal(&forked, 0)).is_err());
+
+        let finalized = FinalizedStoreSequence::new(&first, &sealed_first);
+        // the host rolled the store back behind the finalized sequence
+        assert!(finalized.recover(&genesis, &sealed_genesis).is_err());
+        let second = first.next(&sealed_first, vec![]);
+        let sealed_second = seal(&second, 0);
+        let third = second.next(&sealed_second, vec![]);
+        assert!(finalized.recover(&third, &seal(&third, 0)).is_err());
+
+        let foreign = StoreSequenceRecord::genesis([8; STORE_ID_SIZE]);
+        assert!(FinalizedStoreSequence::new(&genesis, &sealed_genesis)
+            .recover(&foreign, &seal(&foreign, 0))
+            .is_err());
+    }
+
+    /// Simulates the phases of the commands that change the store with the crashes of the host between them:
+    /// the enclave prepares the record in the transaction, the host commits it and acknowledges the commit,
+    /// the enclave finalizes the record, and the host records the finalized sequence.
+    struct Instance {
+        nonce: u8,
+        store: (StoreSequenceRecord, Vec<u8>),
+        checkpoint: FinalizedStoreSequence,
+    }
+
+    impl Instance {
+        fn new() -> Self {
+            let genesis = StoreSequenceRecord::genesis([7; STORE_ID_SIZE]);
+            let sealed = seal(&genesis, 0);
+            Self {
+                nonce: 0,
+                checkpoint: FinalizedStoreSequence::new(&genesis, &sealed),
+                store: (genesis, sealed),
+            }
+        }
+
+        /// Returns the prepared commit on the record in the store
+        fn prepare(&mut self) -> (StoreSequenceRecord, Vec<u8>) {
+            self.nonce += 1;
+            let (record, sealed) = &self.store;
+            let next = record.next(sealed, vec![]);
+            let sealed_next = seal(&next, self.nonce);
+            (next, sealed_next)
+        }
+
+        /// Returns the finalized sequence that a new instance starts from
+        fn start(&self) -> Result<FinalizedStoreSequence, Error> {
+            let (record, sealed) = &self.store;
+            self.checkpoint.recover(record, sealed)?;
+            Ok(FinalizedStoreSequence::new(record, sealed))
+        }
+    }
+
+    #[test]
+    fn test_store_sequence_crash_between_phases() {
+        let mut instance = Instance::new();
+        assert_eq!(instance.start().unwrap(), instance.checkpoint);
+
+        // the host crashed before it committed the transaction of the prepared commit
+        instance.prepare();
+        assert_eq!(instance.start().unwrap(), instance.checkpoint);
+
+        // the host committed the transaction, but crashed before it acknowledged the commit
+        // or before it recorded the finalized sequence, so the next instance finalizes the prepared commit
+        instance.store = instance.prepare();
+        let finalized = instance.start().unwrap();
+        assert_eq!(finalized.sequence, 1);
+        instance.checkpoint = finalized;
+        assert_eq!(instance.start().unwrap(), instance.checkpoint);
+
+        // the host rolled the store back to the previous commit
+        let previous = instance.store.clone();
+        instance.store = instance.prepare();
+        instance.checkpoint = instance.start().unwrap();
+        let latest = core::mem::replace(&mut instance.store, previous);
+        assert!(instance.start().is_err());
+        instance.store = latest;
+
+        // the host committed a prepared commit that was abandoned for another one
+        let abandoned = instance.prepare();
+        instance.store = instance.prepare();
+        instance.checkpoint = instance.start().unwrap();
+        instance.store = abandoned;
+        assert!(instance.start().is_err());
+    }
+
+    #[test]
+    fn test_store_checkpoint_signature() {
+        let key = EnclaveKey::from_secret(&[1; 32]).unwrap();
+        let mut checkpoint = StoreCheckpoint {
+            sequence: 1,
+            sealed_sequence: vec![1, 2, 3],
+            signature: vec![],
+        };
+        checkpoint.signature = key.sign(&checkpoint.sign_bytes()).unwrap();
+        let signer = key.get_pubkey().as_address();
+        checkpoint.verify(signer).unwrap();
+
+        let other = EnclaveKey::from_secret(&[2; 32]).unwrap();
+        assert!(checkpoint.verify(other.get_pubkey().as_address()).is_err());
+        // the host cannot swap the sealed record under the signature
+        checkpoint.sealed_sequence = vec![3, 2, 1];
+        assert!(checkpoint.verify(signer).is_err());
+    }
+
+    #[test]
+    fn test_revocation_record() {
+        let a = EnclaveKey::from_secret(&[1; 32])
+            .unwrap()
+            .get_pubkey()
+            .as_address();
+        let b = EnclaveKey::from_secret(&[2; 32])
+            .unwrap()
+            .get_pubkey()
+            .as_address();
+        let mut record = RevocationRecord::default();
+        assert_eq!(record.revoke(&[a, a]), vec![a]);
+        assert_eq!(record.revoke(&[a, b]), vec![b]);
+        assert!(record.revoke(&[b]).is_empty());
+        assert!(record.is_revoked(&a) && record.is_revoked(&b));
+        assert_eq!(record.revoked, vec![a, b]);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use flex_error::*;
+use serde::{Deserialize, Serialize};
 
 define_error! {
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,3 +33,88 @@ impl From<crypto::Error> for InputValidationError {
         InputValidationError::crypto(value)
     }
 }
+
+/// ErrorCode is a stable taxonomy of command failures that is exposed to the host.
+///
+/// The numeric values are part of the host-enclave interface and must not be reassigned.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Unknown = 1,
+    InvalidInput = 2,
+    ClientNotFound = 100,
+    ConsensusStateNotFound = 101,
+    ClientFrozen = 102,
+    TrustingPeriodExpired = 103,
+    HeaderFromFuture = 104,
+    HeaderOlderThanTrusted = 105,
+    InvalidHeader = 106,
+    InvalidValidatorSet = 107,
+    ProofMismatch = 108,
+    BisectionRequired = 109,
+    AttestationPolicyViolation = 200,
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Returns the error code of the numeric value, or None if it is unknown to this release
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            1 => Self::Unknown,
+            2 => Self::InvalidInput,
+            100 => Self::ClientNotFound,
+            101 => Self::ConsensusStateNotFound,
+            102 => Self::ClientFrozen,
+            103 => Self::TrustingPeriodExpired,
+            104 => Self::HeaderFromFuture,
+            105 => Self::HeaderOlderThanTrusted,
+            106 => Self::InvalidHeader,
+            107 => Self::InvalidValidatorSet,
+            108 => Self::ProofMismatch,
+            109 => Self::BisectionRequired,
+            200 => Self::AttestationPolicyViolation,
+            _ => return None,
+        })
+    }
+
+    /// Returns a hint for operators on how to recover from the error
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            Self::Unknown => "see the error description for details",
+            Self::InvalidInput => "check the request parameters",
+            Self::ClientNotFound => "check the client_id or create the client first",
+            Self::ConsensusStateNotFound => {
+                "the trusted height is unknown to the client; use a height the client has been updated to"
+            }
+            Self::ClientFrozen => "the client is frozen due to misbehaviour; a new client is required",
+            Self::TrustingPeriodExpired => {
+                "the trusted state is outside of the trusting period; the client must be recovered or recreated"
+            }
+            Self::HeaderFromFuture => "the header is ahead of the host clock; check the clock drift or retry later",
+            Self::HeaderOlderThanTrusted => {
+                "header older than trusted height; fetch newer trusted state"
+            }
+            Self::InvalidHeader => "the header failed verification; fetch the header again from a trusted node",
+            Self::InvalidValidatorSet => {
+                "the validator set doesn't match the trusted state; fetch intermediate headers (bisection) or the correct validator set"
+            }
+            Self::ProofMismatch => {
+                "the proof doesn't match the commitment root at the proof height; query the proof at the same height"
+            }
+            Self::BisectionRequired => {
+                "the validator set changed beyond the trust level; update the client to the suggested intermediate heights first"
+            }
+            Self::AttestationPolicyViolation => {
+                "the TCB status of the platform is not allowed by the advisory policy; the attested keys have been revoked, so update the platform and attest new keys"
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}({})", self, self.code())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::prelude::*;
+use lcp_types::{Any, ClientId, Height};
+use serde::{Deserialize, Serialize};
+
+/// CommandEvent is a change of the state of a client that the enclave reports along with the response
+///
+/// The events are only reported for the commands that changed the state, so a dry run reports none.
+/// The host must not publish them before the transaction of the command is committed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CommandEvent {
+    ClientCreated {
+        client_id: ClientId,
+        client_type: String,
+        height: Height,
+    },
+    ClientUpdated {
+        client_id: ClientId,
+        prev_height: Option<Height>,
+        post_height: Height,
+    },
+    /// A state that the update emitted in the signed message
+    StateEmitted {
+        client_id: ClientId,
+        height: Height,
+        state: Any,
+    },
+    /// `frozen_height` is None if the light client does not report it, e.g. on a misbehaviour
+    ClientFrozen {
+        client_id: ClientId,
+        frozen_height: Option<Height>,
+    },
+    /// The client was migrated to another light client implementation at its latest height
+    ClientMigrated {
+        client_id: ClientId,
+        from_type_url: String,
+        from_version: u32,
+        to_type_url: String,
+        to_version: u32,
+        height: Height,
+    },
+}
+
+impl CommandEvent {
+    pub fn client_id(&self) -> &ClientId {
+        match self {
+            Self::ClientCreated { client_id, .. }
+            | Self::ClientUpdated { client_id, .. }
+            | Self::StateEmitted { client_id, .. }
+            | Self::ClientFrozen { client_id, .. }
+            | Self::ClientMigrated { client_id, .. } => client_id,
+        }
+    }
+}
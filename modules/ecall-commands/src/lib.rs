@@ -20,29 +20,65 @@ mod prelude {
     pub use core::iter::FromIterator;
 }
 
-pub use commands::{Command, CommandContext, CommandResponse, ECallCommand};
+pub use codec::{decode_commitment_proof, encode_commitment_proof};
+pub use commands::{
+    Command, CommandContext, CommandResponse, CommandResult, ECallCommand, KeyAttestation,
+};
 use crypto::Address;
 pub use enclave_manage::{
-    EnclaveManageCommand, EnclaveManageResponse, GenerateEnclaveKeyInput,
-    GenerateEnclaveKeyResponse, IASRemoteAttestationInput, IASRemoteAttestationResponse,
+    BeginSessionInput, BeginSessionResponse, BuildInfo, EnclaveManageCommand,
+    EnclaveManageResponse, FinalizeStoreSequenceInput, FinalizeStoreSequenceResponse,
+    FinalizedStoreSequence, GenerateEnclaveKeyInput, GenerateEnclaveKeyResponse,
+    IASRemoteAttestationInput, IASRemoteAttestationResponse, OpenSessionInput, OpenSessionResponse,
+    QueryBuildInfoInput, QueryBuildInfoResponse, QueryOperatorsInput, QueryOperatorsResponse,
+    QuoteSignType, RevocationRecord, RevokeEnclaveKeysInput, RevokeEnclaveKeysResponse,
+    StartInstanceInput, StartInstanceResponse, StoreCheckpoint, StoreRecovery, StoreSequenceRecord,
+    UnsealStoreKeyInput, UnsealStoreKeyResponse, UpdateOperatorsInput, UpdateOperatorsResponse,
+    ValidateDCAPCollateralInput, ValidateDCAPCollateralResponse, REVOCATION_RECORD_KEY,
+    STORE_ID_SIZE, STORE_SEQUENCE_KEY,
 };
 #[cfg(feature = "sgx-sw")]
 pub use enclave_manage::{SimulateRemoteAttestationInput, SimulateRemoteAttestationResponse};
-pub use errors::InputValidationError;
+pub use errors::{ErrorCode, InputValidationError};
+pub use events::CommandEvent;
 pub use light_client::{
-    AggregateMessagesInput, AggregateMessagesResponse, CommitmentProofPair, InitClientInput,
-    InitClientResponse, LightClientCommand, LightClientExecuteCommand, LightClientQueryCommand,
-    LightClientResponse, QueryClientInput, QueryClientResponse, UpdateClientInput,
-    UpdateClientResponse, VerifyMembershipInput, VerifyMembershipResponse,
-    VerifyNonMembershipInput, VerifyNonMembershipResponse,
+    AggregateMessagesInput, AggregateMessagesResponse, ClientValidation, CommitmentPrefix,
+    CommitmentProofPair, InitClientInput, InitClientResponse, LightClientCommand,
+    LightClientExecuteCommand, LightClientQueryCommand, LightClientResponse, ListClientsInput,
+    ListClientsResponse, MigrateClientInput, MigrateClientResponse, PageRequest, PageResponse,
+    PruneClientInput, PruneClientResponse, QueryCapabilitiesInput, QueryCapabilitiesResponse,
+    QueryClientInput, QueryClientResponse, QueryConsensusHeightsInput,
+    QueryConsensusHeightsResponse, ResignLatestStateInput, ResignLatestStateResponse,
+    SignLatestHeightsInput, SignLatestHeightsResponse, StateIncompatibility, UpdateClientInput,
+    UpdateClientParamsInput, UpdateClientParamsResponse, UpdateClientResponse, ValidateStoreInput,
+    ValidateStoreResponse, VerifyAggregateMessagesInput, VerifyAggregateMessagesResponse,
+    VerifyKeyValueInput, VerifyKeyValueResponse, VerifyMembershipBatchInput,
+    VerifyMembershipBatchResponse, VerifyMembershipInput, VerifyMembershipItem,
+    VerifyMembershipResponse, VerifyNonMembershipInput, VerifyNonMembershipResponse,
+    MAX_QUERY_RESPONSE_SIZE, MAX_REPORTED_INCOMPATIBILITIES,
+};
+pub use operators::{OperatorSet, OPERATOR_SET_KEY};
+pub use path::CommitmentPath;
+pub use protocol::{
+    decode_command, decode_command_message, decode_frame, decode_legacy_command, decode_response,
+    decode_versioned_frame, encode_command, encode_command_message, encode_frame,
+    encode_legacy_response, encode_response, encode_response_message, encode_versioned_frame,
+    ProtocolError, FRAME_HEADER_LEN, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION,
 };
+pub use session::{session_sign_bytes, SESSION_TOKEN_SIZE};
 
+mod codec;
 mod commands;
 mod enclave_manage;
 mod errors;
+mod events;
 mod light_client;
 #[cfg(feature = "std")]
 pub mod msgs;
+mod operators;
+mod path;
+mod protocol;
+mod session;
 
 pub trait EnclaveKeySelector {
     fn get_enclave_key(&self) -> Option<Address>;
@@ -1,7 +1,10 @@
-use crate::{prelude::*, EnclaveKeySelector};
+use crate::{errors::InputValidationError, prelude::*, CommitmentPath, EnclaveKeySelector};
 use commitments::CommitmentProof;
-use crypto::Address;
-use lcp_types::{Any, ClientId, Height, Time};
+use core::time::Duration;
+use crypto::{verify_signature_address, Address, Keccak256, SignatureScheme};
+use lcp_types::{
+    Any, Capabilities, ClientId, ClientParams, Height, Time, VerificationMode, VoteExtensionPolicy,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -17,11 +20,23 @@ pub enum LightClientExecuteCommand {
     AggregateMessages(AggregateMessagesInput),
     VerifyMembership(VerifyMembershipInput),
     VerifyNonMembership(VerifyNonMembershipInput),
+    VerifyMembershipBatch(VerifyMembershipBatchInput),
+    VerifyKeyValue(VerifyKeyValueInput),
+    PruneClient(PruneClientInput),
+    SignLatestHeights(SignLatestHeightsInput),
+    UpdateClientParams(UpdateClientParamsInput),
+    ResignLatestState(ResignLatestStateInput),
+    MigrateClient(MigrateClientInput),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum LightClientQueryCommand {
     QueryClient(QueryClientInput),
+    ListClients(ListClientsInput),
+    QueryConsensusHeights(QueryConsensusHeightsInput),
+    QueryCapabilities(QueryCapabilitiesInput),
+    ValidateStore(ValidateStoreInput),
+    VerifyAggregateMessages(VerifyAggregateMessagesInput),
 }
 
 impl EnclaveKeySelector for LightClientCommand {
@@ -33,6 +48,13 @@ impl EnclaveKeySelector for LightClientCommand {
                 LightClientExecuteCommand::AggregateMessages(input) => Some(input.signer),
                 LightClientExecuteCommand::VerifyMembership(input) => Some(input.signer),
                 LightClientExecuteCommand::VerifyNonMembership(input) => Some(input.signer),
+                LightClientExecuteCommand::VerifyMembershipBatch(input) => Some(input.signer),
+                LightClientExecuteCommand::VerifyKeyValue(input) => Some(input.signer),
+                LightClientExecuteCommand::PruneClient(_) => None,
+                LightClientExecuteCommand::SignLatestHeights(input) => Some(input.signer),
+                LightClientExecuteCommand::UpdateClientParams(input) => Some(input.signer),
+                LightClientExecuteCommand::ResignLatestState(input) => Some(input.signer),
+                LightClientExecuteCommand::MigrateClient(input) => Some(input.signer),
             },
             Self::Query(_) => None,
         }
@@ -43,6 +65,29 @@ impl EnclaveKeySelector for LightClientCommand {
 pub struct InitClientInput {
     pub any_client_state: Any,
     pub any_consensus_state: Any,
+    /// If set, membership proofs for the client must target the store with this prefix
+    pub commitment_prefix: Option<CommitmentPrefix>,
+    /// If set, the light client only accepts the headers allowed by this mode
+    pub verification_mode: Option<VerificationMode>,
+    /// If true, the enclave signs a `CreateClient` message that attests the initial state of the client
+    pub prove_genesis: bool,
+    /// If set, the maximum clock drift between the header time and the current time that the client tolerates
+    ///
+    /// This overrides the drift of the client state in the header verification, and it is committed to in the
+    /// context of the `UpdateState` messages.
+    pub max_clock_drift: Option<Duration>,
+    /// If set, how the light client handles the vote extensions of ABCI++ in the headers
+    pub vote_extension_policy: Option<VoteExtensionPolicy>,
+    /// If set, the scheme that the enclave signs the commitments of the client with
+    ///
+    /// The verifier of the client must be configured with the same scheme.
+    #[serde(default)]
+    pub signature_scheme: Option<SignatureScheme>,
+    /// If set, the version of the message schema that the verifier of the client accepts
+    ///
+    /// The default is the version 1, whose messages cannot carry `expires_at`, `frozen_height` or `trusted_timestamp`.
+    #[serde(default)]
+    pub message_schema_version: Option<u16>,
     pub current_timestamp: Time,
     pub signer: Address,
 }
@@ -52,45 +97,351 @@ pub struct UpdateClientInput {
     pub client_id: ClientId,
     pub any_header: Any,
     pub include_state: bool,
+    /// Type URLs of the client states to keep in the emitted states
+    ///
+    /// A filter ending with `*` matches by prefix. If empty, all emitted states are kept.
+    pub state_filters: Vec<String>,
+    /// If true, an update that freezes the client because the header conflicts with
+    /// a stored consensus state results in a misbehaviour commitment instead of an update
+    pub detect_misbehaviour: bool,
+    /// If set, the signed message carries this time after which verifiers should reject it
+    pub expires_at: Option<Time>,
+    /// If true, the header is verified and the would-be message is returned without a signature,
+    /// and the state of the client is not changed
+    #[serde(default)]
+    pub dry_run: bool,
     pub current_timestamp: Time,
     pub signer: Address,
 }
 
+impl UpdateClientInput {
+    /// Returns true if the client state should be included in the emitted states
+    pub fn is_state_included(&self, any_client_state: &Any) -> bool {
+        self.state_filters.is_empty()
+            || self
+                .state_filters
+                .iter()
+                .any(|f| match f.strip_suffix('*') {
+                    Some(prefix) => any_client_state.type_url.starts_with(prefix),
+                    None => any_client_state.type_url == *f,
+                })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AggregateMessagesInput {
+    /// The client of the messages, whose signature scheme the messages and the aggregate are signed with
+    pub client_id: ClientId,
     pub signer: Address,
-    pub messages: Vec<Vec<u8>>,
+    /// `UpdateState` messages wrapped in `Any` with `commitments::PROXY_MESSAGE_TYPE_URL`
+    pub messages: Vec<Any>,
     pub signatures: Vec<Vec<u8>>,
+    /// The signer of each message, or empty if all messages are signed by `signer`
+    ///
+    /// A batch that spans a key rotation contains messages signed by the outgoing and the incoming keys.
+    pub signers: Vec<Address>,
     pub current_timestamp: Time,
 }
 
+impl AggregateMessagesInput {
+    /// Returns the signer of the message at the index
+    pub fn signer_of(&self, index: usize) -> Address {
+        self.signers.get(index).copied().unwrap_or(self.signer)
+    }
+
+    /// Returns the distinct signers of the messages in the order of their first message
+    pub fn constituent_signers(&self) -> Vec<Address> {
+        let mut signers = Vec::new();
+        for index in 0..self.messages.len() {
+            let signer = self.signer_of(index);
+            if !signers.contains(&signer) {
+                signers.push(signer);
+            }
+        }
+        signers
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyMembershipInput {
     pub client_id: ClientId,
-    pub prefix: Vec<u8>,
-    pub path: String,
+    pub prefix: CommitmentPrefix,
+    pub path: CommitmentPath,
     pub value: Vec<u8>,
     pub proof: CommitmentProofPair,
+    /// If set, the signed message carries this time after which verifiers should reject it
+    pub expires_at: Option<Time>,
+    /// If set, the proof is verified against the consensus state at this height, which must be stored and not pruned
+    ///
+    /// The height of `proof` must be zero or equal to it.
+    pub consensus_height: Option<Height>,
+    /// The client must not be expired at this time
+    pub current_timestamp: Time,
     pub signer: Address,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyNonMembershipInput {
     pub client_id: ClientId,
-    pub prefix: Vec<u8>,
-    pub path: String,
+    pub prefix: CommitmentPrefix,
+    pub path: CommitmentPath,
+    pub proof: CommitmentProofPair,
+    /// If set, the signed message carries this time after which verifiers should reject it
+    pub expires_at: Option<Time>,
+    /// The client must not be expired at this time
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+/// VerifyMembershipBatchInput verifies multiple values at the same height of the client
+///
+/// Each value results in its own signed message, but the light client can share the verification of the common part of the proofs.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipBatchInput {
+    pub client_id: ClientId,
+    pub prefix: CommitmentPrefix,
+    pub proof_height: Height,
+    pub items: Vec<VerifyMembershipItem>,
+    /// If set, the signed messages carry this time after which verifiers should reject them
+    pub expires_at: Option<Time>,
+    /// The client must not be expired at this time
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+impl VerifyMembershipBatchInput {
+    /// The maximum number of items in a batch
+    pub const MAX_ITEMS: usize = 64;
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipItem {
+    pub path: CommitmentPath,
+    pub value: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+/// VerifyKeyValueInput verifies the value of an arbitrary key in a store, or its absence if `value` is None
+///
+/// The key is not an ICS-24 path, but `prefix` must still match the commitment prefix configured for the client.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyKeyValueInput {
+    pub client_id: ClientId,
+    pub prefix: CommitmentPrefix,
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
     pub proof: CommitmentProofPair,
+    /// If set, the signed message carries this time after which verifiers should reject it
+    pub expires_at: Option<Time>,
+    /// The client must not be expired at this time
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PruneClientInput {
+    pub client_id: ClientId,
+    /// Consensus states are kept for `trusting_period + margin` after they are stored
+    pub margin: Duration,
+    pub current_timestamp: Time,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SignLatestHeightsInput {
+    /// Clients to include in the summary. If empty, all clients are included.
+    pub client_ids: Vec<ClientId>,
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+/// UpdateClientParamsInput changes the policy values of a client after its creation,
+/// e.g. when the counterparty chain changes its unbonding period or its proof format
+///
+/// The command must be approved by the operators, and the enclave signs a `ClientParams` message as a notice of the change.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateClientParamsInput {
+    pub client_id: ClientId,
+    pub params: ClientParams,
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+/// ResignLatestStateInput signs the latest state of a client again without advancing its height
+///
+/// This is used after rotating to a key that the downstream chain has just registered,
+/// so the chain can accept a commitment signed by the new key before the next update.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResignLatestStateInput {
+    pub client_id: ClientId,
+    pub current_timestamp: Time,
+    pub signer: Address,
+}
+
+/// MigrateClientInput moves a client to another registered light client implementation,
+/// e.g. to a newer version of the same client type
+///
+/// The target implementation converts the stored states, which keep the latest height of the client.
+/// The command must be approved by the operators, and the enclave signs a `ClientMigration` message as a notice of the migration.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MigrateClientInput {
+    pub client_id: ClientId,
+    /// The type URL of the client state that the target implementation is registered for
+    pub target_type_url: String,
+    /// The version of the target implementation. If None, the latest registered version is used.
+    pub target_version: Option<u32>,
+    pub current_timestamp: Time,
     pub signer: Address,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CommitmentProofPair(pub Height, pub Vec<u8>);
 
+/// CommitmentPrefix is the prefix of the store that a membership proof targets, e.g. `ibc` for Cosmos SDK chains
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct CommitmentPrefix(Vec<u8>);
+
+impl CommitmentPrefix {
+    /// The maximum length of a prefix in bytes
+    pub const MAX_LENGTH: usize = 128;
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl TryFrom<Vec<u8>> for CommitmentPrefix {
+    type Error = InputValidationError;
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(InputValidationError::invalid_argument(
+                "commitment prefix must be non-empty".into(),
+            ));
+        }
+        if value.len() > Self::MAX_LENGTH {
+            return Err(InputValidationError::invalid_argument(format!(
+                "commitment prefix is too long: length={} max={}",
+                value.len(),
+                Self::MAX_LENGTH
+            )));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl TryFrom<&str> for CommitmentPrefix {
+    type Error = InputValidationError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.as_bytes().to_vec().try_into()
+    }
+}
+
+impl From<CommitmentPrefix> for Vec<u8> {
+    fn from(value: CommitmentPrefix) -> Self {
+        value.0
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct QueryClientInput {
     pub client_id: ClientId,
 }
 
+/// QueryCapabilitiesInput selects the light client implementation whose capabilities are queried
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum QueryCapabilitiesInput {
+    /// The implementation that the client is pinned to
+    ClientId(ClientId),
+    /// The latest implementation of the client state type, which new clients are created with
+    ClientStateTypeUrl(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ListClientsInput {
+    pub pagination: PageRequest,
+}
+
+/// VerifyAggregateMessagesInput is a batch of the messages of an `AggregateMessagesInput` whose signatures are verified in advance
+///
+/// The enclave keeps the verified messages in its memory, so that aggregating them skips the verification.
+/// Nothing is signed or stored, so the batches of an aggregation can be verified concurrently.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyAggregateMessagesInput {
+    /// The client of the messages, whose signature scheme the messages are signed with
+    pub client_id: ClientId,
+    /// `UpdateState` messages wrapped in `Any` with `commitments::PROXY_MESSAGE_TYPE_URL`
+    pub messages: Vec<Any>,
+    pub signatures: Vec<Vec<u8>>,
+    /// The signer of each message
+    pub signers: Vec<Address>,
+}
+
+/// ValidateStoreInput selects a page of the clients whose stored states are decoded with the registered implementations
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ValidateStoreInput {
+    pub pagination: PageRequest,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryConsensusHeightsInput {
+    pub client_id: ClientId,
+    pub pagination: PageRequest,
+}
+
+/// PageRequest selects a range of the items that a query command returns
+///
+/// A page may contain fewer than `limit` items if the encoded response would exceed `MAX_QUERY_RESPONSE_SIZE`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    /// The number of items to skip
+    pub offset: u64,
+    /// The maximum number of items in the page
+    pub limit: u64,
+}
+
+/// The maximum size in bytes of the items in a page
+///
+/// This is kept well below the ecall output buffer so that the rest of the response fits.
+pub const MAX_QUERY_RESPONSE_SIZE: usize = 32 * 1024;
+
+impl PageRequest {
+    pub const DEFAULT_LIMIT: u64 = 100;
+    pub const MAX_LIMIT: u64 = 1000;
+
+    pub fn new(offset: u64, limit: u64) -> Result<Self, InputValidationError> {
+        if limit == 0 || limit > Self::MAX_LIMIT {
+            return Err(InputValidationError::invalid_argument(format!(
+                "page limit must be in [1, {}]: limit={}",
+                Self::MAX_LIMIT,
+                limit
+            )));
+        }
+        Ok(Self { offset, limit })
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: Self::DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// PageResponse describes the position of a page in all the items
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PageResponse {
+    /// The offset of the next page, or None if this is the last page
+    pub next_offset: Option<u64>,
+    /// The total number of items
+    pub total: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum LightClientResponse {
     InitClient(InitClientResponse),
@@ -99,8 +450,71 @@ pub enum LightClientResponse {
 
     VerifyMembership(VerifyMembershipResponse),
     VerifyNonMembership(VerifyNonMembershipResponse),
+    VerifyMembershipBatch(VerifyMembershipBatchResponse),
+    VerifyKeyValue(VerifyKeyValueResponse),
 
     QueryClient(QueryClientResponse),
+    ListClients(ListClientsResponse),
+    QueryConsensusHeights(QueryConsensusHeightsResponse),
+    QueryCapabilities(QueryCapabilitiesResponse),
+
+    PruneClient(PruneClientResponse),
+    SignLatestHeights(SignLatestHeightsResponse),
+    UpdateClientParams(UpdateClientParamsResponse),
+    ResignLatestState(ResignLatestStateResponse),
+    ValidateStore(ValidateStoreResponse),
+    MigrateClient(MigrateClientResponse),
+    VerifyAggregateMessages(VerifyAggregateMessagesResponse),
+}
+
+impl LightClientResponse {
+    /// Returns the commitment proofs in the response
+    pub fn proofs(&self) -> Vec<&CommitmentProof> {
+        match self {
+            Self::InitClient(res) => vec![&res.proof],
+            Self::UpdateClient(res) => vec![&res.0],
+            Self::AggregateMessages(res) => vec![&res.0],
+            Self::VerifyMembership(res) => vec![&res.0],
+            Self::VerifyNonMembership(res) => vec![&res.0],
+            Self::VerifyMembershipBatch(res) => res.0.iter().collect(),
+            Self::VerifyKeyValue(res) => vec![&res.0],
+            Self::SignLatestHeights(res) => vec![&res.0],
+            Self::UpdateClientParams(res) => vec![&res.0],
+            Self::ResignLatestState(res) => vec![&res.0],
+            Self::MigrateClient(res) => vec![&res.0],
+            Self::QueryClient(_)
+            | Self::ListClients(_)
+            | Self::QueryConsensusHeights(_)
+            | Self::QueryCapabilities(_)
+            | Self::ValidateStore(_)
+            | Self::VerifyAggregateMessages(_)
+            | Self::PruneClient(_) => vec![],
+        }
+    }
+
+    /// Returns the mutable commitment proofs in the response
+    pub fn proofs_mut(&mut self) -> Vec<&mut CommitmentProof> {
+        match self {
+            Self::InitClient(res) => vec![&mut res.proof],
+            Self::UpdateClient(res) => vec![&mut res.0],
+            Self::AggregateMessages(res) => vec![&mut res.0],
+            Self::VerifyMembership(res) => vec![&mut res.0],
+            Self::VerifyNonMembership(res) => vec![&mut res.0],
+            Self::VerifyMembershipBatch(res) => res.0.iter_mut().collect(),
+            Self::VerifyKeyValue(res) => vec![&mut res.0],
+            Self::SignLatestHeights(res) => vec![&mut res.0],
+            Self::UpdateClientParams(res) => vec![&mut res.0],
+            Self::ResignLatestState(res) => vec![&mut res.0],
+            Self::MigrateClient(res) => vec![&mut res.0],
+            Self::QueryClient(_)
+            | Self::ListClients(_)
+            | Self::QueryConsensusHeights(_)
+            | Self::QueryCapabilities(_)
+            | Self::ValidateStore(_)
+            | Self::VerifyAggregateMessages(_)
+            | Self::PruneClient(_) => vec![],
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -112,8 +526,40 @@ pub struct InitClientResponse {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UpdateClientResponse(pub CommitmentProof);
 
+/// The domain separator of the signature over the signers of an aggregate
+const AGGREGATE_CONSTITUENTS_DOMAIN: &[u8] = b"lcp-aggregate-constituents:";
+
+/// The proof of the aggregate, the distinct signers of the aggregated messages,
+/// and the signature of the signer of the proof over `constituents_sign_bytes`
 #[derive(Serialize, Deserialize, Debug)]
-pub struct AggregateMessagesResponse(pub CommitmentProof);
+pub struct AggregateMessagesResponse(pub CommitmentProof, pub Vec<Address>, pub Vec<u8>);
+
+impl AggregateMessagesResponse {
+    /// Returns the bytes that bind the signers of the aggregated messages to the aggregate
+    pub fn constituents_sign_bytes(message: &[u8], signers: &[Address]) -> Vec<u8> {
+        let mut bz = AGGREGATE_CONSTITUENTS_DOMAIN.to_vec();
+        bz.extend_from_slice(&message.keccak256());
+        for signer in signers {
+            bz.extend_from_slice(signer.0.as_slice());
+        }
+        bz
+    }
+
+    /// Returns an error unless the signers are signed by the signer of the proof
+    pub fn verify_constituents(&self) -> Result<(), InputValidationError> {
+        let actual = verify_signature_address(
+            &Self::constituents_sign_bytes(&self.0.message, &self.1),
+            &self.2,
+        )?;
+        if actual != self.0.signer {
+            return Err(InputValidationError::invalid_argument(format!(
+                "the signers of the aggregate are not signed by the signer of the proof: expected={} actual={}",
+                self.0.signer, actual
+            )));
+        }
+        Ok(())
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyMembershipResponse(pub CommitmentProof);
@@ -121,8 +567,136 @@ pub struct VerifyMembershipResponse(pub CommitmentProof);
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyNonMembershipResponse(pub CommitmentProof);
 
+/// The proofs of the items in the same order as the input
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipBatchResponse(pub Vec<CommitmentProof>);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyKeyValueResponse(pub CommitmentProof);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SignLatestHeightsResponse(pub CommitmentProof);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateClientParamsResponse(pub CommitmentProof);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResignLatestStateResponse(pub CommitmentProof);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MigrateClientResponse(pub CommitmentProof);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct QueryClientResponse {
     pub any_client_state: Any,
     pub any_consensus_state: Any,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListClientsResponse {
+    pub client_ids: Vec<ClientId>,
+    pub pagination: PageResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryConsensusHeightsResponse {
+    /// Heights of the stored consensus states in ascending order
+    pub heights: Vec<Height>,
+    pub pagination: PageResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryCapabilitiesResponse {
+    pub client_type: String,
+    /// The version of the implementation in the registry
+    pub version: u32,
+    pub capabilities: Capabilities,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyAggregateMessagesResponse {
+    /// The number of the messages whose signatures were verified, excluding the ones verified before
+    pub verified: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ValidateStoreResponse {
+    pub clients: Vec<ClientValidation>,
+    pub pagination: PageResponse,
+}
+
+/// The maximum number of the incompatible states reported for a client
+pub const MAX_REPORTED_INCOMPATIBILITIES: usize = 4;
+
+/// ClientValidation is the result of decoding the stored states of a client with the implementation it is pinned to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClientValidation {
+    pub client_id: ClientId,
+    /// The number of the stored consensus states
+    pub consensus_states: u64,
+    /// The first `MAX_REPORTED_INCOMPATIBILITIES` incompatibilities in ascending order of height
+    pub incompatibilities: Vec<StateIncompatibility>,
+    /// The total number of the incompatibilities, which may exceed the reported ones
+    pub incompatible_states: u64,
+}
+
+impl ClientValidation {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatible_states == 0
+    }
+}
+
+/// StateIncompatibility is a stored state that the implementation cannot decode
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StateIncompatibility {
+    /// The height of the consensus state, or None if the client state or the implementation itself is incompatible
+    pub height: Option<Height>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PruneClientResponse {
+    /// Heights of the removed consensus states
+    pub pruned_heights: Vec<Height>,
+    /// Total size in bytes of the removed keys and values
+    pub reclaimed_bytes: u64,
+    /// True if the client is marked as expired, which refuses to verify the proofs against it
+    pub expired: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{EnclaveKey, Signer};
+
+    #[test]
+    fn test_aggregate_constituents_signature() {
+        let key = EnclaveKey::from_secret(&[1; 32]).unwrap();
+        let signer = key.get_pubkey().as_address();
+        let other = EnclaveKey::from_secret(&[2; 32])
+            .unwrap()
+            .get_pubkey()
+            .as_address();
+        let message = vec![1, 2, 3];
+        let signers = vec![signer, other];
+        let signature = key
+            .sign(&AggregateMessagesResponse::constituents_sign_bytes(
+                &message, &signers,
+            ))
+            .unwrap();
+        let mut res = AggregateMessagesResponse(
+            CommitmentProof::new(message, signer, vec![]),
+            signers,
+            signature,
+        );
+        res.verify_constituents().unwrap();
+
+        // the host cannot drop a constituent from the signed list
+        res.1.pop();
+        assert!(res.verify_constituents().is_err());
+        res.1.push(other);
+        // nor move the signed list to another aggregate
+        res.0.message = vec![3, 2, 1];
+        assert!(res.verify_constituents().is_err());
+    }
+}
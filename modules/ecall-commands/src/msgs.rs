@@ -1,15 +1,19 @@
 use crate::errors::InputValidationError as Error;
 use crate::light_client::*;
 use crate::prelude::*;
+use commitments::{CoSignature, PROXY_MESSAGE_TYPE_URL};
 use core::str::FromStr;
-use crypto::Address;
+use core::time::Duration;
+use crypto::{Address, DigestAlgorithm, SignatureFormat, SignatureScheme};
 use lcp_types::proto::lcp::service::elc::v1::{
     MsgAggregateMessages, MsgAggregateMessagesResponse, MsgCreateClient, MsgCreateClientResponse,
-    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyMembership, MsgVerifyMembershipResponse,
-    MsgVerifyNonMembership, MsgVerifyNonMembershipResponse,
-    QueryClientRequest as MsgQueryClientRequest, QueryClientResponse as MsgQueryClientResponse,
+    MsgMigrateClient, MsgMigrateClientResponse, MsgSignLatestHeights, MsgSignLatestHeightsResponse,
+    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyKeyValue, MsgVerifyKeyValueResponse,
+    MsgVerifyMembership, MsgVerifyMembershipResponse, MsgVerifyNonMembership,
+    MsgVerifyNonMembershipResponse, QueryClientRequest as MsgQueryClientRequest,
+    QueryClientResponse as MsgQueryClientResponse,
 };
-use lcp_types::{ClientId, Time};
+use lcp_types::{Any, ClientId, Time, VerificationMode, VoteExtensionPolicy};
 
 impl TryFrom<MsgCreateClient> for InitClientInput {
     type Error = Error;
@@ -22,9 +26,57 @@ impl TryFrom<MsgCreateClient> for InitClientInput {
             .consensus_state
             .ok_or_else(|| Error::invalid_argument("consensus_state must be non-nil".into()))?
             .into();
+        let commitment_prefix = if msg.commitment_prefix.is_empty() {
+            None
+        } else {
+            Some(CommitmentPrefix::try_from(msg.commitment_prefix)?)
+        };
+        let verification_mode = if msg.verification_mode.is_empty() {
+            None
+        } else {
+            Some(VerificationMode::from_str(&msg.verification_mode)?)
+        };
+        let vote_extension_policy = if msg.vote_extension_policy.is_empty() {
+            None
+        } else {
+            Some(VoteExtensionPolicy::from_str(&msg.vote_extension_policy)?)
+        };
+        let signature_scheme = if msg.digest_algorithm.is_empty() && msg.signature_format.is_empty()
+        {
+            None
+        } else {
+            Some(SignatureScheme {
+                digest: if msg.digest_algorithm.is_empty() {
+                    Default::default()
+                } else {
+                    DigestAlgorithm::from_str(&msg.digest_algorithm)?
+                },
+                format: if msg.signature_format.is_empty() {
+                    Default::default()
+                } else {
+                    SignatureFormat::from_str(&msg.signature_format)?
+                },
+            })
+        };
         Ok(Self {
             any_client_state,
             any_consensus_state,
+            commitment_prefix,
+            verification_mode,
+            prove_genesis: msg.prove_genesis,
+            max_clock_drift: (msg.max_clock_drift != 0)
+                .then_some(Duration::from_secs(msg.max_clock_drift)),
+            vote_extension_policy,
+            signature_scheme,
+            message_schema_version: (msg.message_schema_version != 0)
+                .then(|| u16::try_from(msg.message_schema_version))
+                .transpose()
+                .map_err(|_| {
+                    Error::invalid_argument(format!(
+                        "invalid message_schema_version: {}",
+                        msg.message_schema_version
+                    ))
+                })?,
             current_timestamp: Time::now(),
             signer: Address::try_from(msg.signer.as_slice())?,
         })
@@ -43,6 +95,10 @@ impl TryFrom<MsgUpdateClient> for UpdateClientInput {
             client_id,
             any_header,
             include_state: msg.include_state,
+            state_filters: msg.state_filters,
+            detect_misbehaviour: msg.detect_misbehaviour,
+            expires_at: expiry_from_unix_secs(msg.expires_at)?,
+            dry_run: msg.dry_run,
             current_timestamp: Time::now(),
             signer: Address::try_from(msg.signer.as_slice())?,
         })
@@ -53,10 +109,35 @@ impl TryFrom<MsgAggregateMessages> for AggregateMessagesInput {
     type Error = Error;
     fn try_from(msg: MsgAggregateMessages) -> Result<Self, Error> {
         let signer = Address::try_from(msg.signer.as_slice())?;
+        let messages = match (msg.messages.is_empty(), msg.any_messages.is_empty()) {
+            (_, true) => msg
+                .messages
+                .into_iter()
+                .map(|bz| Any::new(PROXY_MESSAGE_TYPE_URL.to_string(), bz))
+                .collect(),
+            (true, false) => msg.any_messages.into_iter().map(Into::into).collect(),
+            (false, false) => {
+                return Err(Error::invalid_argument(
+                    "only one of messages and any_messages can be set".into(),
+                ))
+            }
+        };
+        let signers = msg
+            .signers
+            .iter()
+            .map(|bz| Address::try_from(bz.as_slice()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !signers.is_empty() && signers.len() != messages.len() {
+            return Err(Error::invalid_argument(
+                "signers must be empty or have the same length as messages".into(),
+            ));
+        }
         Ok(Self {
+            client_id: ClientId::from_str(&msg.client_id)?,
             signer,
-            messages: msg.messages,
+            messages,
             signatures: msg.signatures,
+            signers,
             current_timestamp: Time::now(),
         })
     }
@@ -75,10 +156,13 @@ impl TryFrom<MsgVerifyMembership> for VerifyMembershipInput {
         );
         Ok(Self {
             client_id,
-            prefix: msg.prefix,
+            prefix: msg.prefix.try_into()?,
             proof,
-            path: msg.path,
+            path: msg.path.parse()?,
             value: msg.value,
+            expires_at: expiry_from_unix_secs(msg.expires_at)?,
+            consensus_height: msg.consensus_height.map(Into::into),
+            current_timestamp: Time::now(),
             signer: Address::try_from(msg.signer.as_slice())?,
         })
     }
@@ -97,9 +181,73 @@ impl TryFrom<MsgVerifyNonMembership> for VerifyNonMembershipInput {
         );
         Ok(Self {
             client_id,
-            prefix: msg.prefix,
+            prefix: msg.prefix.try_into()?,
             proof,
-            path: msg.path,
+            path: msg.path.parse()?,
+            expires_at: expiry_from_unix_secs(msg.expires_at)?,
+            current_timestamp: Time::now(),
+            signer: Address::try_from(msg.signer.as_slice())?,
+        })
+    }
+}
+
+impl TryFrom<MsgVerifyKeyValue> for VerifyKeyValueInput {
+    type Error = Error;
+
+    fn try_from(msg: MsgVerifyKeyValue) -> Result<Self, Self::Error> {
+        let client_id = ClientId::from_str(&msg.client_id)?;
+        if msg.key.is_empty() {
+            return Err(Error::invalid_argument("key must be non-empty".into()));
+        }
+        let proof = CommitmentProofPair(
+            msg.proof_height
+                .ok_or_else(|| Error::invalid_argument("proof_height must be non-nil".into()))?
+                .into(),
+            msg.proof,
+        );
+        Ok(Self {
+            client_id,
+            prefix: msg.prefix.try_into()?,
+            key: msg.key,
+            value: (!msg.value.is_empty()).then_some(msg.value),
+            proof,
+            expires_at: expiry_from_unix_secs(msg.expires_at)?,
+            current_timestamp: Time::now(),
+            signer: Address::try_from(msg.signer.as_slice())?,
+        })
+    }
+}
+
+impl TryFrom<MsgMigrateClient> for MigrateClientInput {
+    type Error = Error;
+
+    fn try_from(msg: MsgMigrateClient) -> Result<Self, Self::Error> {
+        if msg.target_type_url.is_empty() {
+            return Err(Error::invalid_argument(
+                "target_type_url must be non-empty".into(),
+            ));
+        }
+        Ok(Self {
+            client_id: ClientId::from_str(&msg.client_id)?,
+            target_type_url: msg.target_type_url,
+            target_version: (msg.target_version != 0).then_some(msg.target_version),
+            current_timestamp: Time::now(),
+            signer: Address::try_from(msg.signer.as_slice())?,
+        })
+    }
+}
+
+impl TryFrom<MsgSignLatestHeights> for SignLatestHeightsInput {
+    type Error = Error;
+
+    fn try_from(msg: MsgSignLatestHeights) -> Result<Self, Self::Error> {
+        Ok(Self {
+            client_ids: msg
+                .client_ids
+                .iter()
+                .map(|id| ClientId::from_str(id))
+                .collect::<Result<_, _>>()?,
+            current_timestamp: Time::now(),
             signer: Address::try_from(msg.signer.as_slice())?,
         })
     }
@@ -115,55 +263,129 @@ impl TryFrom<MsgQueryClientRequest> for QueryClientInput {
 
 impl From<InitClientResponse> for MsgCreateClientResponse {
     fn from(res: InitClientResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.proof.co_signatures);
         Self {
             client_id: res.client_id.to_string(),
             message: res.proof.message,
             signer: res.proof.signer.into(),
             signature: res.proof.signature,
+            co_signers,
+            co_signatures,
         }
     }
 }
 
 impl From<UpdateClientResponse> for MsgUpdateClientResponse {
     fn from(res: UpdateClientResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
         Self {
             message: res.0.message,
             signer: res.0.signer.into(),
             signature: res.0.signature,
+            co_signers,
+            co_signatures,
         }
     }
 }
 
 impl From<AggregateMessagesResponse> for MsgAggregateMessagesResponse {
     fn from(res: AggregateMessagesResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
         Self {
             message: res.0.message,
             signer: res.0.signer.into(),
             signature: res.0.signature,
+            co_signers,
+            co_signatures,
+            constituent_signers: res.1.into_iter().map(Into::into).collect(),
+            constituent_signers_signature: res.2,
         }
     }
 }
 
 impl From<VerifyMembershipResponse> for MsgVerifyMembershipResponse {
     fn from(res: VerifyMembershipResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
         Self {
             message: res.0.message,
             signer: res.0.signer.to_vec(),
             signature: res.0.signature,
+            co_signers,
+            co_signatures,
+            artifact_hash: Default::default(),
         }
     }
 }
 
 impl From<VerifyNonMembershipResponse> for MsgVerifyNonMembershipResponse {
     fn from(res: VerifyNonMembershipResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
+        Self {
+            message: res.0.message,
+            signer: res.0.signer.to_vec(),
+            signature: res.0.signature,
+            co_signers,
+            co_signatures,
+        }
+    }
+}
+
+impl From<VerifyKeyValueResponse> for MsgVerifyKeyValueResponse {
+    fn from(res: VerifyKeyValueResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
+        Self {
+            message: res.0.message,
+            signer: res.0.signer.to_vec(),
+            signature: res.0.signature,
+            co_signers,
+            co_signatures,
+        }
+    }
+}
+
+impl From<MigrateClientResponse> for MsgMigrateClientResponse {
+    fn from(res: MigrateClientResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
+        Self {
+            message: res.0.message,
+            signer: res.0.signer.to_vec(),
+            signature: res.0.signature,
+            co_signers,
+            co_signatures,
+        }
+    }
+}
+
+impl From<SignLatestHeightsResponse> for MsgSignLatestHeightsResponse {
+    fn from(res: SignLatestHeightsResponse) -> Self {
+        let (co_signers, co_signatures) = split_co_signatures(res.0.co_signatures);
         Self {
             message: res.0.message,
             signer: res.0.signer.to_vec(),
             signature: res.0.signature,
+            co_signers,
+            co_signatures,
         }
     }
 }
 
+/// Zero means that the message never expires
+fn expiry_from_unix_secs(secs: u64) -> Result<Option<Time>, Error> {
+    if secs == 0 {
+        return Ok(None);
+    }
+    Time::from_unix_timestamp_nanos(secs as u128 * 1_000_000_000)
+        .map(Some)
+        .map_err(|e| Error::invalid_argument(format!("invalid expires_at: {}", e)))
+}
+
+fn split_co_signatures(co_signatures: Vec<CoSignature>) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    co_signatures
+        .into_iter()
+        .map(|cs| (cs.signer.to_vec(), cs.signature))
+        .unzip()
+}
+
 impl From<QueryClientResponse> for MsgQueryClientResponse {
     fn from(res: QueryClientResponse) -> Self {
         Self {
@@ -172,3 +394,58 @@ impl From<QueryClientResponse> for MsgQueryClientResponse {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::CommitmentProof;
+    use lcp_types::proto::ibc::core::client::v1::Height as RawHeight;
+
+    #[test]
+    fn test_verify_membership_response() {
+        let mut proof = CommitmentProof::new(vec![1, 2, 3], Address([4; 20]), vec![5; 65]);
+        proof.co_signatures = vec![
+            CoSignature {
+                signer: Address([6; 20]),
+                signature: vec![7; 65],
+            },
+            CoSignature {
+                signer: Address([8; 20]),
+                signature: vec![9; 65],
+            },
+        ];
+        let res: MsgVerifyMembershipResponse = VerifyMembershipResponse(proof).into();
+        assert_eq!(res.message, vec![1, 2, 3]);
+        assert_eq!(res.signer, vec![4; 20]);
+        assert_eq!(res.signature, vec![5; 65]);
+        assert_eq!(res.co_signers, vec![vec![6; 20], vec![8; 20]]);
+        assert_eq!(res.co_signatures, vec![vec![7; 65], vec![9; 65]]);
+        // the service sets the hash after it stores the artifact
+        assert!(res.artifact_hash.is_empty());
+    }
+
+    #[test]
+    fn test_verify_membership_ignores_store_artifact() {
+        let msg = MsgVerifyMembership {
+            client_id: "07-tendermint-0".into(),
+            prefix: b"ibc".to_vec(),
+            path: "clients/07-tendermint-0/clientState".into(),
+            value: vec![1, 2, 3],
+            proof_height: Some(RawHeight {
+                revision_number: 0,
+                revision_height: 10,
+            }),
+            proof: vec![4, 5, 6],
+            signer: vec![7; 20],
+            ..Default::default()
+        };
+        let input = VerifyMembershipInput::try_from(msg.clone()).unwrap();
+        let mut with_artifact = VerifyMembershipInput::try_from(MsgVerifyMembership {
+            store_artifact: true,
+            ..msg
+        })
+        .unwrap();
+        with_artifact.current_timestamp = input.current_timestamp;
+        assert_eq!(format!("{:?}", input), format!("{:?}", with_artifact));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{prelude::*, Command, InputValidationError as Error, LightClientCommand};
+use crypto::{verify_signature_address, Address, Keccak256};
+use serde::{Deserialize, Serialize};
+
+const OPERATOR_SET_DOMAIN: &[u8] = b"lcp-operator-set:";
+const OPERATOR_COMMAND_DOMAIN: &[u8] = b"lcp-operator-command:";
+
+/// The key of the sealed operator set in the store
+pub const OPERATOR_SET_KEY: &[u8] = b"lcp/operator_set";
+
+/// OperatorSet is the set of operators whose approvals are required to execute mutating commands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OperatorSet {
+    pub operators: Vec<Address>,
+    /// The number of distinct operators that must approve a command
+    pub threshold: u64,
+    /// Must be incremented on every update so that approvals of a previous update cannot be replayed
+    pub nonce: u64,
+}
+
+impl OperatorSet {
+    /// Returns the set of the bootstrap operator of the enclave, which approves the initial operator set
+    pub fn bootstrap(operator: Address) -> Self {
+        Self {
+            operators: vec![operator],
+            threshold: 1,
+            nonce: 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.threshold == 0 || self.threshold > self.operators.len() as u64 {
+            return Err(Error::invalid_argument(format!(
+                "threshold must be between 1 and the number of operators: threshold={} operators={}",
+                self.threshold,
+                self.operators.len()
+            )));
+        }
+        for (i, operator) in self.operators.iter().enumerate() {
+            if self.operators[..i].contains(operator) {
+                return Err(Error::invalid_argument(format!(
+                    "duplicate operator: {}",
+                    operator
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_operator(&self, address: &Address) -> bool {
+        self.operators.contains(address)
+    }
+
+    /// Returns the number of distinct operators that signed `sign_bytes`
+    ///
+    /// The signatures of non-operators are ignored, but a malformed signature is an error.
+    pub fn count_approvals(&self, sign_bytes: &[u8], signatures: &[Vec<u8>]) -> Result<u64, Error> {
+        let mut approvers: Vec<Address> = Vec::new();
+        for signature in signatures {
+            let signer = verify_signature_address(sign_bytes, signature)?;
+            if self.is_operator(&signer) && !approvers.contains(&signer) {
+                approvers.push(signer);
+            }
+        }
+        Ok(approvers.len() as u64)
+    }
+
+    /// Returns the bytes that the current operators sign to approve this set as the next one
+    pub fn sign_bytes(&self) -> Vec<u8> {
+        let mut bz = OPERATOR_SET_DOMAIN.to_vec();
+        bz.extend_from_slice(&self.nonce.to_be_bytes());
+        bz.extend_from_slice(&self.threshold.to_be_bytes());
+        for operator in self.operators.iter() {
+            bz.extend_from_slice(&operator.0);
+        }
+        bz
+    }
+}
+
+impl Command {
+    /// Returns true if the command modifies the light client states, which requires the operators' approvals
+    pub fn requires_operator_approval(&self) -> bool {
+        matches!(self, Self::LightClient(LightClientCommand::Execute(_)))
+    }
+
+    /// Returns the bytes that the operators sign to approve the command
+    pub fn operator_sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        let encoded = self.digest_encoding(false)?;
+        Ok([OPERATOR_COMMAND_DOMAIN, encoded.keccak256().as_slice()].concat())
+    }
+
+    /// Returns the bytes that the operators of the hosts of `LEGACY_PROTOCOL_VERSION` sign to approve the command
+    pub fn legacy_operator_sign_bytes(&self) -> Result<Vec<u8>, Error> {
+        let encoded = self.digest_encoding(true)?;
+        Ok([OPERATOR_COMMAND_DOMAIN, encoded.keccak256().as_slice()].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{EnclaveKey, Signer};
+
+    fn key(b: u8) -> EnclaveKey {
+        EnclaveKey::from_secret(&[b; 32]).unwrap()
+    }
+
+    fn operator_set(keys: &[&EnclaveKey], threshold: u64) -> OperatorSet {
+        OperatorSet {
+            operators: keys.iter().map(|k| k.get_pubkey().as_address()).collect(),
+            threshold,
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_count_approvals() {
+        let (k1, k2, k3, outsider) = (key(1), key(2), key(3), key(4));
+        let set = operator_set(&[&k1, &k2, &k3], 2);
+        set.validate().unwrap();
+        let bz = set.sign_bytes();
+
+        let sigs = vec![k1.sign(&bz).unwrap(), k2.sign(&bz).unwrap()];
+        assert_eq!(set.count_approvals(&bz, &sigs).unwrap(), 2);
+
+        // duplicate signatures of an operator are counted once, and non-operators are ignored
+        let sigs = vec![
+            k1.sign(&bz).unwrap(),
+            k1.sign(&bz).unwrap(),
+            outsider.sign(&bz).unwrap(),
+        ];
+        assert_eq!(set.count_approvals(&bz, &sigs).unwrap(), 1);
+
+        // a signature over other bytes doesn't approve them
+        let sigs = vec![k1.sign(b"other").unwrap(), k2.sign(b"other").unwrap()];
+        assert_eq!(set.count_approvals(&bz, &sigs).unwrap(), 0);
+
+        assert!(set.count_approvals(&bz, &[vec![0u8; 10]]).is_err());
+    }
+
+    #[test]
+    fn test_bootstrap_operator_set() {
+        let (k1, k2) = (key(1), key(2));
+        let bootstrap = OperatorSet::bootstrap(k1.get_pubkey().as_address());
+        bootstrap.validate().unwrap();
+        let initial = operator_set(&[&k1, &k2], 2);
+        let sign_bytes = initial.sign_bytes();
+        assert_eq!(
+            bootstrap
+                .count_approvals(&sign_bytes, &[k1.sign(&sign_bytes).unwrap()])
+                .unwrap(),
+            1
+        );
+        // only the bootstrap operator can approve the initial set
+        assert_eq!(
+            bootstrap
+                .count_approvals(&sign_bytes, &[k2.sign(&sign_bytes).unwrap()])
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_operator_set_validation() {
+        let (k1, k2) = (key(1), key(2));
+        assert!(operator_set(&[&k1, &k2], 0).validate().is_err());
+        assert!(operator_set(&[&k1, &k2], 3).validate().is_err());
+        assert!(operator_set(&[&k1, &k1], 1).validate().is_err());
+        operator_set(&[&k1, &k2], 2).validate().unwrap();
+    }
+}
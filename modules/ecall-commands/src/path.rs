@@ -0,0 +1,296 @@
+use crate::{errors::InputValidationError as Error, prelude::*};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use lcp_types::{ClientId, Height};
+use serde::{Deserialize, Serialize};
+
+const VALID_SPECIAL_CHARS: &str = "._+-#[]<>";
+
+/// CommitmentPath is an ICS-24 path of a commitment to verify its membership or non-membership
+///
+/// A path that is not defined in ICS-24 can be given as `Raw`, which the enclave passes to the light client as is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentPath {
+    ClientState {
+        client_id: ClientId,
+    },
+    ClientConsensusState {
+        client_id: ClientId,
+        height: Height,
+    },
+    Connection {
+        connection_id: String,
+    },
+    ChannelEnd {
+        port_id: String,
+        channel_id: String,
+    },
+    NextSequenceSend {
+        port_id: String,
+        channel_id: String,
+    },
+    NextSequenceRecv {
+        port_id: String,
+        channel_id: String,
+    },
+    NextSequenceAck {
+        port_id: String,
+        channel_id: String,
+    },
+    Commitment {
+        port_id: String,
+        channel_id: String,
+        sequence: u64,
+    },
+    Ack {
+        port_id: String,
+        channel_id: String,
+        sequence: u64,
+    },
+    Receipt {
+        port_id: String,
+        channel_id: String,
+        sequence: u64,
+    },
+    /// A path that is not defined in ICS-24, which must be a UTF-8 string as the light clients take a string
+    Raw(Vec<u8>),
+}
+
+impl CommitmentPath {
+    /// Returns an error if an identifier in the path is malformed
+    ///
+    /// The identifiers are validated again in the enclave, as the fields of a deserialized path are not checked.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Self::ClientState { client_id } | Self::ClientConsensusState { client_id, .. } => {
+                ClientId::from_str(client_id.as_str())?;
+            }
+            Self::Connection { connection_id } => {
+                validate_identifier("connection_id", connection_id, 10, 64)?
+            }
+            Self::ChannelEnd {
+                port_id,
+                channel_id,
+            }
+            | Self::NextSequenceSend {
+                port_id,
+                channel_id,
+            }
+            | Self::NextSequenceRecv {
+                port_id,
+                channel_id,
+            }
+            | Self::NextSequenceAck {
+                port_id,
+                channel_id,
+            }
+            | Self::Commitment {
+                port_id,
+                channel_id,
+                ..
+            }
+            | Self::Ack {
+                port_id,
+                channel_id,
+                ..
+            }
+            | Self::Receipt {
+                port_id,
+                channel_id,
+                ..
+            } => {
+                validate_identifier("port_id", port_id, 2, 128)?;
+                validate_identifier("channel_id", channel_id, 8, 64)?;
+            }
+            Self::Raw(path) => {
+                if path.is_empty() {
+                    return Err(Error::invalid_argument("raw path must not be empty".into()));
+                }
+                core::str::from_utf8(path).map_err(|e| {
+                    Error::invalid_argument(format!("raw path must be a UTF-8 string: {}", e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the path and returns its string representation that the light clients take
+    pub fn to_path_string(&self) -> Result<String, Error> {
+        self.validate()?;
+        Ok(self.to_string())
+    }
+}
+
+impl Display for CommitmentPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ClientState { client_id } => write!(f, "clients/{}/clientState", client_id),
+            Self::ClientConsensusState { client_id, height } => {
+                write!(f, "clients/{}/consensusStates/{}", client_id, height)
+            }
+            Self::Connection { connection_id } => write!(f, "connections/{}", connection_id),
+            Self::ChannelEnd {
+                port_id,
+                channel_id,
+            } => write!(f, "channelEnds/ports/{}/channels/{}", port_id, channel_id),
+            Self::NextSequenceSend {
+                port_id,
+                channel_id,
+            } => write!(
+                f,
+                "nextSequenceSend/ports/{}/channels/{}",
+                port_id, channel_id
+            ),
+            Self::NextSequenceRecv {
+                port_id,
+                channel_id,
+            } => write!(
+                f,
+                "nextSequenceRecv/ports/{}/channels/{}",
+                port_id, channel_id
+            ),
+            Self::NextSequenceAck {
+                port_id,
+                channel_id,
+            } => write!(
+                f,
+                "nextSequenceAck/ports/{}/channels/{}",
+                port_id, channel_id
+            ),
+            Self::Commitment {
+                port_id,
+                channel_id,
+                sequence,
+            } => write!(
+                f,
+                "commitments/ports/{}/channels/{}/sequences/{}",
+                port_id, channel_id, sequence
+            ),
+            Self::Ack {
+                port_id,
+                channel_id,
+                sequence,
+            } => write!(
+                f,
+                "acks/ports/{}/channels/{}/sequences/{}",
+                port_id, channel_id, sequence
+            ),
+            Self::Receipt {
+                port_id,
+                channel_id,
+                sequence,
+            } => write!(
+                f,
+                "receipts/ports/{}/channels/{}/sequences/{}",
+                port_id, channel_id, sequence
+            ),
+            Self::Raw(path) => write!(f, "{}", String::from_utf8_lossy(path)),
+        }
+    }
+}
+
+/// Parses an ICS-24 path, where an unknown or malformed path is an error
+impl FromStr for CommitmentPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments: Vec<&str> = s.split('/').collect();
+        let path = match segments.as_slice() {
+            ["clients", client_id, "clientState"] => Self::ClientState {
+                client_id: ClientId::from_str(client_id)?,
+            },
+            ["clients", client_id, "consensusStates", height] => Self::ClientConsensusState {
+                client_id: ClientId::from_str(client_id)?,
+                height: parse_height(height)?,
+            },
+            ["connections", connection_id] => Self::Connection {
+                connection_id: connection_id.to_string(),
+            },
+            ["channelEnds", "ports", port_id, "channels", channel_id] => Self::ChannelEnd {
+                port_id: port_id.to_string(),
+                channel_id: channel_id.to_string(),
+            },
+            ["nextSequenceSend", "ports", port_id, "channels", channel_id] => {
+                Self::NextSequenceSend {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                }
+            }
+            ["nextSequenceRecv", "ports", port_id, "channels", channel_id] => {
+                Self::NextSequenceRecv {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                }
+            }
+            ["nextSequenceAck", "ports", port_id, "channels", channel_id] => {
+                Self::NextSequenceAck {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                }
+            }
+            ["commitments", "ports", port_id, "channels", channel_id, "sequences", sequence] => {
+                Self::Commitment {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence: parse_sequence(sequence)?,
+                }
+            }
+            ["acks", "ports", port_id, "channels", channel_id, "sequences", sequence] => {
+                Self::Ack {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence: parse_sequence(sequence)?,
+                }
+            }
+            ["receipts", "ports", port_id, "channels", channel_id, "sequences", sequence] => {
+                Self::Receipt {
+                    port_id: port_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    sequence: parse_sequence(sequence)?,
+                }
+            }
+            _ => {
+                return Err(Error::invalid_argument(format!(
+                    "unknown ICS-24 commitment path: path={}",
+                    s
+                )))
+            }
+        };
+        path.validate()?;
+        Ok(path)
+    }
+}
+
+fn parse_height(s: &str) -> Result<Height, Error> {
+    let err = || Error::invalid_argument(format!("invalid height in the path: height={}", s));
+    let (revision_number, revision_height) = s.split_once('-').ok_or_else(err)?;
+    Ok(Height::new(
+        revision_number.parse().map_err(|_| err())?,
+        revision_height.parse().map_err(|_| err())?,
+    ))
+}
+
+fn parse_sequence(s: &str) -> Result<u64, Error> {
+    s.parse().map_err(|_| {
+        Error::invalid_argument(format!("invalid sequence in the path: sequence={}", s))
+    })
+}
+
+fn validate_identifier(name: &str, id: &str, min: usize, max: usize) -> Result<(), Error> {
+    if id.len() < min || id.len() > max {
+        return Err(Error::invalid_argument(format!(
+            "{} must be between {} and {} characters: {}={}",
+            name, min, max, name, id
+        )));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || VALID_SPECIAL_CHARS.contains(c))
+    {
+        return Err(Error::invalid_argument(format!(
+            "{} contains an invalid character: {}={}",
+            name, name, id
+        )));
+    }
+    Ok(())
+}
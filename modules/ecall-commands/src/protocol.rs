@@ -0,0 +1,461 @@
+use crate::codec;
+use crate::prelude::*;
+use crate::{Command, CommandResponse, CommandResult, ECallCommand};
+use flex_error::*;
+use lcp_types::proto::lcp::ecall::v1::{
+    CommandResponse as ProtoCommandResponse, ECallCommand as ProtoECallCommand,
+};
+use prost::Message;
+
+/// The version of the protocol between the host and the enclave
+///
+/// This must be increased whenever the encoding of `ECallCommand` or `CommandResponse` changes incompatibly.
+/// Since version 3, every command and response is encoded with its own `lcp.ecall.v1` protobuf message.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// The version of the protocol that encodes `ECallCommand` and `CommandResponse` with bincode
+///
+/// The enclave still accepts the frames of this version and answers them in the same encoding,
+/// so that the hosts of the previous release keep working until they are upgraded.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// The length of the header that carries the protocol version of the sender
+pub const FRAME_HEADER_LEN: usize = 4;
+
+define_error! {
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    ProtocolError {
+        MissingHeader {
+            len: usize
+        }
+        |e| {
+            format_args!("the frame is too short to contain the protocol version: len={}", e.len)
+        },
+        IncompatibleVersion {
+            expected: u32,
+            actual: u32
+        }
+        |e| {
+            format_args!(
+                "incompatible host-enclave protocol version: expected={} actual={}; the host library and the enclave binary must be built from the same release",
+                e.expected, e.actual
+            )
+        },
+        InvalidCommand {
+            descr: String
+        }
+        |e| {
+            format_args!("failed to decode the command: descr={}", e.descr)
+        },
+        InvalidResponse {
+            descr: String
+        }
+        |e| {
+            format_args!("failed to decode the response: descr={}", e.descr)
+        },
+        InvalidField {
+            field: String,
+            descr: String
+        }
+        |e| {
+            format_args!("invalid field in the message: field={} descr={}", e.field, e.descr)
+        },
+        Encode {
+            descr: String
+        }
+        |e| {
+            format_args!("failed to encode the message: descr={}", e.descr)
+        },
+    }
+}
+
+/// Prepend the header with `PROTOCOL_VERSION` to the payload
+///
+/// A frame with an empty payload is a handshake, which the enclave answers with its own header.
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    encode_versioned_frame(PROTOCOL_VERSION, payload)
+}
+
+/// Prepend the header with `version` to the payload
+pub fn encode_versioned_frame(version: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&version.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Returns the payload of the frame if the sender uses `PROTOCOL_VERSION`
+///
+/// The header is checked before the payload is decoded, so that a peer built from another release
+/// is reported as such instead of as a malformed message.
+pub fn decode_frame(frame: &[u8]) -> Result<&[u8], ProtocolError> {
+    match decode_versioned_frame(frame)? {
+        (PROTOCOL_VERSION, payload) => Ok(payload),
+        (version, _) => Err(ProtocolError::incompatible_version(
+            PROTOCOL_VERSION,
+            version,
+        )),
+    }
+}
+
+/// Returns the version and the payload of the frame if the sender uses `PROTOCOL_VERSION` or `LEGACY_PROTOCOL_VERSION`
+pub fn decode_versioned_frame(frame: &[u8]) -> Result<(u32, &[u8]), ProtocolError> {
+    if frame.len() < FRAME_HEADER_LEN {
+        return Err(ProtocolError::missing_header(frame.len()));
+    }
+    let (header, payload) = frame.split_at(FRAME_HEADER_LEN);
+    let version = u32::from_be_bytes(header.try_into().unwrap());
+    if version != PROTOCOL_VERSION && version != LEGACY_PROTOCOL_VERSION {
+        return Err(ProtocolError::incompatible_version(
+            PROTOCOL_VERSION,
+            version,
+        ));
+    }
+    Ok((version, payload))
+}
+
+/// Encode the command with the protobuf messages of `PROTOCOL_VERSION`
+pub fn encode_command(cmd: &ECallCommand) -> Result<Vec<u8>, ProtocolError> {
+    Ok(ProtoECallCommand {
+        ctx: Some(codec::encode_context(&cmd.ctx)?),
+        cmd: Some(codec::encode_command(&cmd.cmd)?),
+    }
+    .encode_to_vec())
+}
+
+/// Decode the payload of a frame into a command
+///
+/// This is the first step that processes the untrusted input from the host,
+/// so it must reject malformed payloads without panicking.
+pub fn decode_command(payload: &[u8]) -> Result<ECallCommand, ProtocolError> {
+    let msg = ProtoECallCommand::decode(payload)
+        .map_err(|e| ProtocolError::invalid_command(format!("{:?}", e)))?;
+    let ctx = msg
+        .ctx
+        .ok_or_else(|| ProtocolError::invalid_command("ctx must be non-nil".into()))?;
+    let cmd = msg
+        .cmd
+        .ok_or_else(|| ProtocolError::invalid_command("cmd must be non-nil".into()))?;
+    Ok(ECallCommand::new(
+        codec::decode_context(ctx)?,
+        codec::decode_command(cmd)?,
+    ))
+}
+
+/// Decode the payload of a frame of `LEGACY_PROTOCOL_VERSION` into a command
+///
+/// The response of this version cannot carry the store checkpoint, which the host must acknowledge,
+/// so the commands that change the store are rejected.
+pub fn decode_legacy_command(payload: &[u8]) -> Result<ECallCommand, ProtocolError> {
+    let (mut cmd, _): (ECallCommand, _) =
+        bincode::serde::decode_borrowed_from_slice(payload, bincode::config::standard())
+            .map_err(|e| ProtocolError::invalid_command(format!("{:?}", e)))?;
+    if cmd.cmd.changes_store() {
+        return Err(ProtocolError::invalid_command(format!(
+            "the commands that change the store require the protocol version {}",
+            PROTOCOL_VERSION
+        )));
+    }
+    cmd.ctx.legacy_encoding = true;
+    Ok(cmd)
+}
+
+/// Encode the result of a command received in a frame of `LEGACY_PROTOCOL_VERSION`
+///
+/// The events are dropped, since the hosts of that version cannot decode them.
+pub fn encode_legacy_response(result: &CommandResult) -> Result<Vec<u8>, ProtocolError> {
+    bincode::serde::encode_to_vec(&result.response, bincode::config::standard())
+        .map_err(|e| ProtocolError::encode(format!("{:?}", e)))
+}
+
+/// Encode the command with its `lcp.ecall.v1` message without the context
+///
+/// This is the encoding that the request digest, the operator approvals and the command history of the host are based on.
+pub fn encode_command_message(cmd: &Command) -> Result<Vec<u8>, ProtocolError> {
+    Ok(ProtoECallCommand {
+        ctx: None,
+        cmd: Some(codec::encode_command(cmd)?),
+    }
+    .encode_to_vec())
+}
+
+/// Decode the message encoded by `encode_command_message` into a command
+pub fn decode_command_message(bz: &[u8]) -> Result<Command, ProtocolError> {
+    let msg = ProtoECallCommand::decode(bz)
+        .map_err(|e| ProtocolError::invalid_command(format!("{:?}", e)))?;
+    codec::decode_command(
+        msg.cmd
+            .ok_or_else(|| ProtocolError::invalid_command("cmd must be non-nil".into()))?,
+    )
+}
+
+/// Encode the response with its `lcp.ecall.v1` message without the events and the store checkpoint
+pub fn encode_response_message(res: &CommandResponse) -> Vec<u8> {
+    ProtoCommandResponse {
+        res: Some(codec::encode_response(res)),
+        events: vec![],
+        store_checkpoint: None,
+    }
+    .encode_to_vec()
+}
+
+/// Encode the result of a command with the protobuf messages of `PROTOCOL_VERSION`
+pub fn encode_response(result: &CommandResult) -> Vec<u8> {
+    ProtoCommandResponse {
+        res: Some(codec::encode_response(&result.response)),
+        events: result.events.iter().map(codec::encode_event).collect(),
+        store_checkpoint: result
+            .store_checkpoint
+            .as_ref()
+            .map(codec::encode_store_checkpoint),
+    }
+    .encode_to_vec()
+}
+
+/// Decode the payload of a frame into a result
+pub fn decode_response(payload: &[u8]) -> Result<CommandResult, ProtocolError> {
+    let msg = ProtoCommandResponse::decode(payload)
+        .map_err(|e| ProtocolError::invalid_response(format!("{:?}", e)))?;
+    let response = codec::decode_response(
+        msg.res
+            .ok_or_else(|| ProtocolError::invalid_response("res must be non-nil".into()))?,
+    )?;
+    let events = msg
+        .events
+        .into_iter()
+        .map(codec::decode_event)
+        .collect::<Result<_, _>>()?;
+    let result = CommandResult::new(response, events);
+    Ok(match msg.store_checkpoint {
+        Some(checkpoint) => {
+            result.with_store_checkpoint(codec::decode_store_checkpoint(checkpoint))
+        }
+        None => result,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CommandContext, CommandEvent, CommitmentPath, CommitmentProofPair, ErrorCode,
+        LightClientCommand, LightClientExecuteCommand, LightClientResponse, PruneClientInput,
+        StoreCheckpoint, VerifyMembershipBatchResponse, VerifyMembershipInput,
+    };
+    use commitments::{CoSignature, CommitmentProof, RequestBinding};
+    use core::str::FromStr;
+    use crypto::Address;
+    use lcp_types::proto::lcp::ecall::v1::{e_call_command, CommandContext as ProtoCommandContext};
+    use lcp_types::{Any, ClientId, Height, Time};
+
+    fn time() -> Time {
+        Time::from_unix_timestamp_nanos(1_700_000_000_123_456_789).unwrap()
+    }
+
+    fn verify_membership(path: CommitmentPath) -> ECallCommand {
+        let ctx = CommandContext::new(time(), None, 7)
+            .with_operator_signatures(vec![vec![1; 65]])
+            .with_session_token([2; 32]);
+        ECallCommand::new(
+            ctx,
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::VerifyMembership(VerifyMembershipInput {
+                    client_id: ClientId::from_str("07-tendermint-0").unwrap(),
+                    prefix: "ibc".try_into().unwrap(),
+                    path,
+                    value: vec![3; 32],
+                    proof: CommitmentProofPair(Height::new(1, 100), vec![4; 64]),
+                    expires_at: None,
+                    consensus_height: Some(Height::new(1, 99)),
+                    current_timestamp: time(),
+                    signer: Address([5; 20]),
+                }),
+            )),
+        )
+    }
+
+    #[test]
+    fn test_command_round_trip() {
+        for path in [
+            CommitmentPath::from_str("clients/07-tendermint-0/clientState").unwrap(),
+            CommitmentPath::Raw(b"custom/path".to_vec()),
+        ] {
+            let cmd = verify_membership(path);
+            let decoded = decode_command(&encode_command(&cmd).unwrap()).unwrap();
+            assert_eq!(
+                decoded.cmd.request_digest().unwrap(),
+                cmd.cmd.request_digest().unwrap()
+            );
+            assert_eq!(decoded.ctx.current_timestamp, cmd.ctx.current_timestamp);
+            assert_eq!(decoded.ctx.operator_signatures, cmd.ctx.operator_signatures);
+            assert_eq!(decoded.ctx.tx_id, cmd.ctx.tx_id);
+            assert_eq!(decoded.ctx.session_token, cmd.ctx.session_token);
+            assert!(decoded.ctx.sealed_ek.is_none());
+        }
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let mut proof = CommitmentProof::new(vec![1, 2, 3], Address([5; 20]), vec![6; 65]);
+        proof.co_signatures = vec![CoSignature {
+            signer: Address([7; 20]),
+            signature: vec![8; 65],
+        }];
+        proof.request_binding = Some(RequestBinding {
+            request_digest: [9; 32],
+            signature: vec![10; 65],
+        });
+        let result = CommandResult::new(
+            CommandResponse::LightClient(LightClientResponse::VerifyMembershipBatch(
+                VerifyMembershipBatchResponse(vec![
+                    proof.clone(),
+                    CommitmentProof::new_with_no_signature(vec![4, 5, 6]),
+                ]),
+            )),
+            vec![
+                CommandEvent::ClientUpdated {
+                    client_id: client_id.clone(),
+                    prev_height: None,
+                    post_height: Height::new(1, 100),
+                },
+                CommandEvent::StateEmitted {
+                    client_id,
+                    height: Height::new(1, 100),
+                    state: Any::new("/test.State".into(), vec![11]),
+                },
+            ],
+        )
+        .with_store_checkpoint(StoreCheckpoint {
+            sequence: 3,
+            sealed_sequence: vec![12; 16],
+            signature: vec![13; 65],
+        });
+
+        let decoded = decode_response(&encode_response(&result)).unwrap();
+        assert_eq!(decoded.events, result.events);
+        assert_eq!(decoded.store_checkpoint, result.store_checkpoint);
+        match (decoded.response, result.response) {
+            (CommandResponse::LightClient(decoded), CommandResponse::LightClient(expected)) => {
+                assert_eq!(decoded.proofs(), expected.proofs())
+            }
+            (decoded, _) => panic!("unexpected response: {:?}", decoded),
+        }
+
+        let error = CommandResult::from(CommandResponse::CommandError(
+            ErrorCode::InvalidInput,
+            "invalid".into(),
+        ));
+        match decode_response(&encode_response(&error)).unwrap().response {
+            CommandResponse::CommandError(ErrorCode::InvalidInput, descr) => {
+                assert_eq!(descr, "invalid")
+            }
+            res => panic!("unexpected response: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_reject_malformed_command() {
+        let cmd = verify_membership(CommitmentPath::Raw(b"custom/path".to_vec()));
+        let msg = ProtoECallCommand::decode(encode_command(&cmd).unwrap().as_slice()).unwrap();
+
+        let mut invalid = msg.clone();
+        invalid.ctx = None;
+        assert!(decode_command(&invalid.encode_to_vec()).is_err());
+
+        let mut invalid = msg.clone();
+        invalid.ctx = Some(ProtoCommandContext {
+            session_token: vec![2; 31],
+            ..invalid.ctx.unwrap()
+        });
+        assert!(decode_command(&invalid.encode_to_vec()).is_err());
+
+        let mut invalid = msg;
+        match invalid.cmd.as_mut() {
+            Some(e_call_command::Cmd::VerifyMembership(input)) => input.signer = vec![5; 19],
+            cmd => panic!("unexpected command: {:?}", cmd),
+        }
+        assert!(decode_command(&invalid.encode_to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_command_message_round_trip() {
+        let cmd = verify_membership(CommitmentPath::Raw(b"custom/path".to_vec())).cmd;
+        let bz = encode_command_message(&cmd).unwrap();
+        let decoded = decode_command_message(&bz).unwrap();
+        assert_eq!(encode_command_message(&decoded).unwrap(), bz);
+        assert_eq!(
+            decoded.request_digest().unwrap(),
+            cmd.request_digest().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_legacy_frames() {
+        let cmd = verify_membership(CommitmentPath::Raw(b"custom/path".to_vec()));
+        let payload = bincode::serde::encode_to_vec(&cmd, bincode::config::standard()).unwrap();
+        let frame = encode_versioned_frame(LEGACY_PROTOCOL_VERSION, &payload);
+        let (version, payload) = decode_versioned_frame(&frame).unwrap();
+        assert_eq!(version, LEGACY_PROTOCOL_VERSION);
+        let decoded = decode_legacy_command(payload).unwrap();
+        assert!(decoded.ctx.legacy_encoding);
+        assert_eq!(
+            decoded.cmd.legacy_request_digest().unwrap(),
+            cmd.cmd.legacy_request_digest().unwrap()
+        );
+        assert_ne!(
+            decoded.cmd.legacy_request_digest().unwrap(),
+            decoded.cmd.request_digest().unwrap()
+        );
+        // the host of the current version accepts only the frames of its own version
+        assert_eq!(
+            decode_frame(&frame).unwrap_err().detail(),
+            ProtocolError::incompatible_version(PROTOCOL_VERSION, LEGACY_PROTOCOL_VERSION).detail()
+        );
+
+        // the legacy response can't carry the store checkpoint of a command that changes the store
+        let cmd = ECallCommand::new(
+            CommandContext::new(time(), None, 7),
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::PruneClient(PruneClientInput {
+                    client_id: ClientId::from_str("07-tendermint-0").unwrap(),
+                    margin: Default::default(),
+                    current_timestamp: time(),
+                }),
+            )),
+        );
+        let payload = bincode::serde::encode_to_vec(&cmd, bincode::config::standard()).unwrap();
+        assert!(decode_legacy_command(&payload).is_err());
+
+        let result = CommandResult::from(CommandResponse::CommandError(
+            ErrorCode::InvalidInput,
+            "invalid".into(),
+        ));
+        let bz = encode_legacy_response(&result).unwrap();
+        let (res, _): (CommandResponse, _) =
+            bincode::serde::decode_from_slice(&bz, bincode::config::standard()).unwrap();
+        assert!(matches!(
+            res,
+            CommandResponse::CommandError(ErrorCode::InvalidInput, _)
+        ));
+    }
+
+    #[test]
+    fn test_reject_other_versions() {
+        let payload =
+            encode_command(&verify_membership(CommitmentPath::Raw(b"p".to_vec()))).unwrap();
+        assert_eq!(
+            decode_frame(&encode_frame(&payload)).unwrap(),
+            payload.as_slice()
+        );
+        for version in [LEGACY_PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 1] {
+            let mut frame = version.to_be_bytes().to_vec();
+            frame.extend_from_slice(&payload);
+            assert_eq!(
+                decode_frame(&frame).unwrap_err().detail(),
+                ProtocolError::incompatible_version(PROTOCOL_VERSION, version).detail()
+            );
+        }
+        assert!(decode_frame(&[0; FRAME_HEADER_LEN - 1]).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{prelude::*, Command, EnclaveManageCommand};
+
+const SESSION_DOMAIN: &[u8] = b"lcp-session:";
+
+/// The size of the challenge and the token of a session
+pub const SESSION_TOKEN_SIZE: usize = 32;
+
+/// Returns the bytes that an operator signs to open a session with the challenge
+pub fn session_sign_bytes(challenge: &[u8; SESSION_TOKEN_SIZE]) -> Vec<u8> {
+    [SESSION_DOMAIN, challenge.as_slice()].concat()
+}
+
+impl Command {
+    /// Returns true if the command must carry the token of the open session
+    ///
+    /// Only the commands that open a session, the generation of the first key that a session can be bound to,
+    /// and the unsealing of the key of the store that keeps the operators, can be executed without it.
+    pub fn requires_session(&self) -> bool {
+        !matches!(
+            self,
+            Self::EnclaveManage(
+                EnclaveManageCommand::BeginSession(_)
+                    | EnclaveManageCommand::OpenSession(_)
+                    | EnclaveManageCommand::GenerateEnclaveKey(_)
+                    | EnclaveManageCommand::UnsealStoreKey(_)
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BeginSessionInput, GenerateEnclaveKeyInput, OpenSessionInput, QueryOperatorsInput,
+        UnsealStoreKeyInput,
+    };
+
+    #[test]
+    fn test_requires_session() {
+        for cmd in [
+            EnclaveManageCommand::BeginSession(BeginSessionInput),
+            EnclaveManageCommand::OpenSession(OpenSessionInput::default()),
+            EnclaveManageCommand::GenerateEnclaveKey(GenerateEnclaveKeyInput),
+            EnclaveManageCommand::UnsealStoreKey(UnsealStoreKeyInput::default()),
+        ] {
+            assert!(!Command::EnclaveManage(cmd).requires_session());
+        }
+        // the commands require a session whether the operator set is configured or not
+        assert!(
+            Command::EnclaveManage(EnclaveManageCommand::QueryOperators(QueryOperatorsInput))
+                .requires_session()
+        );
+    }
+}
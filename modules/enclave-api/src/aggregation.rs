@@ -0,0 +1,117 @@
+use crate::{EnclaveCommandAPI, Result};
+use ecall_commands::{
+    AggregateMessagesInput, AggregateMessagesResponse, VerifyAggregateMessagesInput,
+};
+use log::*;
+use std::ops::Range;
+use store::transaction::CommitStore;
+
+/// The minimum number of messages in a batch that is verified by an ecall
+const MIN_BATCH_SIZE: usize = 2;
+
+/// The number of the TCSs of the enclave (`TCSNum` in enclave/Enclave.config.xml), which bounds the concurrent ecalls
+pub const ENCLAVE_TCS_NUM: usize = 2;
+
+/// Aggregate the messages after verifying their signatures with up to `parallelism` concurrent ecalls
+///
+/// `parallelism` is clamped to `ENCLAVE_TCS_NUM`. The messages are split into consecutive batches whose signatures
+/// are verified concurrently by `EnclaveCommandAPI::verify_aggregate_messages`, which neither signs nor stores anything
+/// but leaves the verified messages in the enclave. Then all the messages are aggregated by a single
+/// `EnclaveCommandAPI::aggregate_messages`, which skips the verification of those messages,
+/// so the result, the operator approval and the history are the same as without the batches.
+pub fn aggregate_messages_in_parallel<S, E>(
+    enclave: &E,
+    input: AggregateMessagesInput,
+    parallelism: usize,
+) -> Result<AggregateMessagesResponse>
+where
+    S: CommitStore,
+    E: EnclaveCommandAPI<S>,
+{
+    let batches = split_batches(input.messages.len(), parallelism.min(ENCLAVE_TCS_NUM));
+    if batches.len() == 1
+        || input.messages.len() != input.signatures.len()
+        || (!input.signers.is_empty() && input.messages.len() != input.signers.len())
+    {
+        // the enclave rejects the invalid input as usual
+        return enclave.aggregate_messages(input);
+    }
+    debug!(
+        "verify the messages in parallel: messages={} batches={}",
+        input.messages.len(),
+        batches.len()
+    );
+    std::thread::scope(|s| {
+        let input = &input;
+        let handles: Vec<_> = batches
+            .into_iter()
+            .map(|range| {
+                s.spawn(move || {
+                    let res = enclave.verify_aggregate_messages(VerifyAggregateMessagesInput {
+                        client_id: input.client_id.clone(),
+                        messages: input.messages[range.clone()].to_vec(),
+                        signatures: input.signatures[range.clone()].to_vec(),
+                        signers: range.clone().map(|i| input.signer_of(i)).collect(),
+                    });
+                    (range, res)
+                })
+            })
+            .collect();
+        for h in handles {
+            // the aggregation verifies the messages that a batch failed to, and reports the error if they are invalid
+            match h.join().expect("verification thread panicked") {
+                (range, Ok(res)) => debug!(
+                    "verified a batch of the messages: range={:?} verified={}",
+                    range, res.verified
+                ),
+                (range, Err(e)) => warn!(
+                    "failed to verify a batch of the messages: range={:?} err={:?}",
+                    range, e
+                ),
+            }
+        }
+    });
+    enclave.aggregate_messages(input)
+}
+
+/// Split `len` messages into at most `parallelism` consecutive batches of almost the same size
+///
+/// Returns a single batch if the messages are too few to be split.
+fn split_batches(len: usize, parallelism: usize) -> Vec<Range<usize>> {
+    let count = parallelism.min(len / MIN_BATCH_SIZE).max(1);
+    let (size, rem) = (len / count, len % count);
+    let mut start = 0;
+    (0..count)
+        .map(|i| {
+            let end = start + size + usize::from(i < rem);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_batches() {
+        assert_eq!(split_batches(0, 4), vec![0..0]);
+        assert_eq!(split_batches(3, 1), vec![0..3]);
+        assert_eq!(split_batches(3, 4), vec![0..3]);
+        assert_eq!(split_batches(4, 4), vec![0..2, 2..4]);
+        assert_eq!(split_batches(10, 4), vec![0..3, 3..6, 6..8, 8..10]);
+        assert_eq!(split_batches(10, 0), vec![0..10]);
+        for (len, parallelism) in [(7, 3), (100, 8), (17, 16)] {
+            let batches = split_batches(len, parallelism);
+            assert!(batches.len() <= parallelism);
+            assert_eq!(batches.first().unwrap().start, 0);
+            assert_eq!(batches.last().unwrap().end, len);
+            for w in batches.windows(2) {
+                assert_eq!(w[0].end, w[1].start);
+            }
+            assert!(batches.iter().all(|b| b.len() >= MIN_BATCH_SIZE));
+        }
+    }
+}
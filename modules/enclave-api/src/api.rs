@@ -1,4 +1,5 @@
 pub use command::EnclaveCommandAPI;
+pub(crate) use primitive::handshake;
 pub use primitive::EnclavePrimitiveAPI;
 pub use proto::EnclaveProtoAPI;
 
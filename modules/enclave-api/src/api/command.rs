@@ -1,13 +1,30 @@
-use crate::{EnclavePrimitiveAPI, Result};
+use crate::{verify_commitment_proof, EnclavePrimitiveAPI, Result, INSTANCE_UPDATE_KEY};
+use crypto::Address;
 use ecall_commands::{
-    AggregateMessagesInput, AggregateMessagesResponse, Command, CommandResponse,
-    EnclaveManageCommand, EnclaveManageResponse, GenerateEnclaveKeyInput,
-    GenerateEnclaveKeyResponse, IASRemoteAttestationInput, IASRemoteAttestationResponse,
-    InitClientInput, InitClientResponse, LightClientCommand, LightClientExecuteCommand,
-    LightClientQueryCommand, LightClientResponse, QueryClientInput, QueryClientResponse,
-    UpdateClientInput, UpdateClientResponse, VerifyMembershipInput, VerifyMembershipResponse,
+    decode_command_message, AggregateMessagesInput, AggregateMessagesResponse, BeginSessionInput,
+    ClientValidation, Command, CommandResponse, EnclaveManageCommand, EnclaveManageResponse,
+    ErrorCode, GenerateEnclaveKeyInput, GenerateEnclaveKeyResponse, IASRemoteAttestationInput,
+    IASRemoteAttestationResponse, InitClientInput, InitClientResponse, LightClientCommand,
+    LightClientExecuteCommand, LightClientQueryCommand, LightClientResponse, ListClientsInput,
+    ListClientsResponse, MigrateClientInput, MigrateClientResponse, OpenSessionInput,
+    OpenSessionResponse, PageRequest, PruneClientInput, PruneClientResponse, QueryBuildInfoInput,
+    QueryBuildInfoResponse, QueryCapabilitiesInput, QueryCapabilitiesResponse, QueryClientInput,
+    QueryClientResponse, QueryConsensusHeightsInput, QueryConsensusHeightsResponse,
+    QueryOperatorsInput, QueryOperatorsResponse, ResignLatestStateInput, ResignLatestStateResponse,
+    RevokeEnclaveKeysInput, RevokeEnclaveKeysResponse, SignLatestHeightsInput,
+    SignLatestHeightsResponse, StartInstanceInput, StartInstanceResponse, UnsealStoreKeyInput,
+    UnsealStoreKeyResponse, UpdateClientInput, UpdateClientParamsInput, UpdateClientParamsResponse,
+    UpdateClientResponse, UpdateOperatorsInput, UpdateOperatorsResponse,
+    ValidateDCAPCollateralInput, ValidateDCAPCollateralResponse, ValidateStoreInput,
+    ValidateStoreResponse, VerifyAggregateMessagesInput, VerifyAggregateMessagesResponse,
+    VerifyKeyValueInput, VerifyKeyValueResponse, VerifyMembershipBatchInput,
+    VerifyMembershipBatchResponse, VerifyMembershipInput, VerifyMembershipResponse,
     VerifyNonMembershipInput, VerifyNonMembershipResponse,
 };
+use lcp_types::{ClientId, Time};
+use log::*;
+use std::time::Duration;
+use store::host::StoreStats;
 use store::transaction::CommitStore;
 
 pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
@@ -41,9 +58,30 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         let res = match self.execute_command(
             Command::EnclaveManage(EnclaveManageCommand::IASRemoteAttestation(input)),
             None,
-        )? {
-            CommandResponse::EnclaveManage(EnclaveManageResponse::IASRemoteAttestation(res)) => res,
-            _ => unreachable!(),
+        ) {
+            Ok(CommandResponse::EnclaveManage(EnclaveManageResponse::IASRemoteAttestation(
+                res,
+            ))) => res,
+            Ok(_) => unreachable!(),
+            // the platform no longer satisfies the policy, so the keys attested on it must stop signing
+            Err(e) if e.command_error_code() == Some(ErrorCode::AttestationPolicyViolation) => {
+                let revoked = self
+                    .get_key_manager()
+                    .revoke_attested_keys(&e.to_string())?;
+                if !revoked.is_empty() {
+                    warn!(
+                        "revoked the attested keys after the attestation violated the policy: keys={:?} err={}",
+                        revoked, e
+                    );
+                    // the enclave refuses the revoked keys even if the key manager is replaced
+                    self.revoke_enclave_keys(RevokeEnclaveKeysInput {
+                        addresses: revoked,
+                        reason: e.to_string(),
+                    })?;
+                }
+                return Err(e);
+            }
+            Err(e) => return Err(e),
         };
         self.get_key_manager()
             .save_avr(target_enclave_key, res.report.clone())?;
@@ -82,6 +120,268 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         Ok(res)
     }
 
+    /// validate_dcap_collateral verifies a DCAP quote bundle against the Intel SGX Root CA in the enclave and stores it with the key
+    fn validate_dcap_collateral(
+        &self,
+        input: ValidateDCAPCollateralInput,
+    ) -> Result<ValidateDCAPCollateralResponse> {
+        let target_enclave_key = input.target_enclave_key;
+        let bundle = input.bundle.clone();
+        let res = match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::ValidateDCAPCollateral(input)),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::ValidateDCAPCollateral(res)) => {
+                res
+            }
+            _ => unreachable!(),
+        };
+        self.get_key_manager()
+            .save_dcap_bundle(target_enclave_key, &bundle, res.root_ca_hash)?;
+        Ok(res)
+    }
+
+    /// start_instance starts a new instance on the store
+    ///
+    /// Light client commands are rejected by the enclaves that started an instance earlier on the same store.
+    /// Once a command has changed the store, the enclave refuses to start without the checkpoint of its finalized sequence.
+    /// If the store has committed a prepared commit that the previous enclave did not finalize, the new one finalizes it.
+    fn start_instance(&self) -> Result<StartInstanceResponse> {
+        let key_manager = self.get_key_manager();
+        let store_checkpoint = key_manager.load_store_checkpoint()?;
+        if let Some((sequence, _)) = store_checkpoint.as_ref() {
+            debug!(
+                "start an instance from the store checkpoint: sequence={}",
+                sequence
+            );
+        }
+        let res = match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::StartInstance(StartInstanceInput {
+                store_checkpoint: store_checkpoint.map(|(_, sealed)| sealed),
+            })),
+            Some(INSTANCE_UPDATE_KEY.to_string()),
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::StartInstance(res)) => res,
+            _ => unreachable!(),
+        };
+        // the genesis sequence of a new store, or the sequence of a command that the previous enclave
+        // committed but failed to finalize
+        if let Some(sealed_sequence) = res.sealed_sequence.as_ref() {
+            key_manager.save_store_checkpoint(res.store_sequence, sealed_sequence)?;
+        }
+        Ok(res)
+    }
+
+    /// recover_hung_enclave re-creates the enclave if an ecall has been in flight longer than the timeout of the watchdog
+    ///
+    /// Returns true if the enclave is re-created. The sealed keys are loaded from the key manager for each command,
+    /// but the new enclave must start an instance before it accepts light client commands.
+    /// The commands that hung fail and their transactions are rolled back. The ones that change the store are replayed
+    /// in the new enclave from the write-ahead log of the watchdog, and the others can be retried by the relayers.
+    fn recover_hung_enclave(&self) -> Result<bool> {
+        let watchdog = match self.get_watchdog() {
+            Some(watchdog) => watchdog,
+            None => return Ok(false),
+        };
+        let elapsed = match watchdog.hung_for() {
+            Some(elapsed) => elapsed,
+            None => return Ok(false),
+        };
+        warn!(
+            "the enclave has not responded; re-create it: elapsed={:?} timeout={:?}",
+            elapsed,
+            watchdog.timeout()
+        );
+        self.recreate()?;
+        self.open_session()?;
+        let res = self.start_instance()?;
+        info!("re-created the enclave: counter={}", res.counter);
+        for pending in watchdog.take_abandoned() {
+            // a failed replay is not retried, since the command may no longer be valid on the current state
+            let res = decode_command_message(&pending.command)
+                .map_err(Into::into)
+                .and_then(|cmd| self.execute_command(cmd, pending.update_key.clone()));
+            match res {
+                Ok(_) => info!(
+                    "replayed the command abandoned by the hung enclave: update_key={:?}",
+                    pending.update_key
+                ),
+                Err(e) => warn!(
+                    "failed to replay the command abandoned by the hung enclave: update_key={:?} err={:?}",
+                    pending.update_key, e
+                ),
+            }
+        }
+        Ok(true)
+    }
+
+    /// shutdown stops the enclave without leaving a partial commit in the host store
+    ///
+    /// The mutating commands are rejected first, then the commands in flight are drained up to `drain_timeout`
+    /// and every command is rejected. The host store and the key manager are flushed to the disk before the enclave is destroyed.
+    /// Returns false if some commands were still in flight after the timeout; they fail and their transactions are rolled back.
+    fn shutdown(&self, drain_timeout: Duration) -> Result<bool> {
+        let drained = match self.get_shutdown_gate() {
+            Some(gate) => {
+                if !gate.close() {
+                    warn!("the enclave is already shutting down");
+                }
+                info!(
+                    "drain the commands in flight: in_flight={} timeout={:?}",
+                    gate.in_flight(),
+                    drain_timeout
+                );
+                let drained = gate.drain(drain_timeout);
+                if !drained {
+                    warn!(
+                        "commands are still in flight after the timeout: in_flight={}",
+                        gate.in_flight()
+                    );
+                }
+                drained
+            }
+            None => true,
+        };
+        self.use_host_store(|store| store.sync())?;
+        self.get_key_manager().checkpoint()?;
+        self.destroy_enclave()?;
+        info!("shut down the enclave: drained={}", drained);
+        Ok(drained)
+    }
+
+    /// assign_client_owner encrypts the mirrored state of the client in the host store with the key derived from
+    /// the token of the owner, or unlocks the state with the token if the client has already been assigned
+    ///
+    /// The first assignment of a client must not be called while a command updates the client.
+    fn assign_client_owner(
+        &self,
+        client_id: &ClientId,
+        owner: &str,
+        token: &[u8],
+    ) -> Result<usize> {
+        Ok(self.use_host_store(|store| store.assign_owner(client_id.as_str(), owner, token))?)
+    }
+
+    /// store_stats returns the statistics of the disk usage of the host store, or None if the store is not persistent
+    fn store_stats(&self) -> Result<Option<StoreStats>> {
+        Ok(self.use_host_store(|store| store.stats())?)
+    }
+
+    /// compact_store compacts the host store to reclaim the space of the pruned states
+    ///
+    /// The store is locked during the compaction, so the commands wait until it finishes.
+    fn compact_store(&self) -> Result<()> {
+        Ok(self.use_host_store(|store| store.compact())?)
+    }
+
+    /// update_operators replaces the operator set whose approvals are required to execute mutating commands
+    ///
+    /// The initial set must be approved by the bootstrap operator that the enclave is built with, and the next ones
+    /// by the current operators. The enclave must have started an instance, since the set is bound to the store sequence.
+    fn update_operators(&self, input: UpdateOperatorsInput) -> Result<UpdateOperatorsResponse> {
+        match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::UpdateOperators(input)),
+            Some("lcp/operators".to_string()),
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::UpdateOperators(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// revoke_enclave_keys adds the keys to the revocation record of the enclave, which refuses them to sign or to be aggregated
+    fn revoke_enclave_keys(
+        &self,
+        input: RevokeEnclaveKeysInput,
+    ) -> Result<RevokeEnclaveKeysResponse> {
+        match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::RevokeEnclaveKeys(input)),
+            Some("lcp/revoked_keys".to_string()),
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::RevokeEnclaveKeys(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// query_operators returns the current operator set
+    fn query_operators(&self) -> Result<QueryOperatorsResponse> {
+        match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::QueryOperators(QueryOperatorsInput)),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::QueryOperators(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// query_build_info returns the metadata embedded in the enclave binary when it was built
+    fn query_build_info(&self) -> Result<QueryBuildInfoResponse> {
+        match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::QueryBuildInfo(QueryBuildInfoInput)),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::QueryBuildInfo(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// unseal_store_key unseals the store encryption key sealed by the enclave
+    ///
+    /// If `sealed_key` is empty, the enclave generates a new key and returns it with its sealed form.
+    fn unseal_store_key(&self, sealed_key: Vec<u8>) -> Result<UnsealStoreKeyResponse> {
+        match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::UnsealStoreKey(UnsealStoreKeyInput {
+                sealed_key,
+            })),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::UnsealStoreKey(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// open_session proves the possession of the operator credential to the enclave and opens a session
+    ///
+    /// The credential must be one of the operators, or the bootstrap operator of the enclave until the operator set is configured.
+    /// The token of the session is attached to the subsequent commands. Opening a session replaces the previous one.
+    /// Returns None if no credential is configured, in which case the enclave rejects the commands that require a session.
+    fn open_session(&self) -> Result<Option<OpenSessionResponse>> {
+        let session = match self.get_session() {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+        if !session.has_credential() {
+            warn!("no operator credential is configured, so no session is opened");
+            return Ok(None);
+        }
+        let challenge = match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::BeginSession(BeginSessionInput)),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::BeginSession(res)) => {
+                res.challenge
+            }
+            _ => unreachable!(),
+        };
+        let signature = session.sign_challenge(&challenge)?.unwrap_or_default();
+        let res = match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::OpenSession(OpenSessionInput {
+                signature,
+            })),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::OpenSession(res)) => res,
+            _ => unreachable!(),
+        };
+        session.set_token(res.session_token);
+        info!(
+            "opened a session with the enclave: operator={}",
+            res.operator
+        );
+        Ok(Some(res))
+    }
+
     /// init_client initializes an ELC instance with given states
     fn init_client(&self, input: InitClientInput) -> Result<InitClientResponse> {
         let update_key = Some(input.any_client_state.type_url.clone());
@@ -125,6 +425,26 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         }
     }
 
+    /// verify_aggregate_messages verifies the signatures of a batch of the messages to aggregate in advance
+    ///
+    /// It is a query that neither signs nor stores anything, so it needs no operator approval and is not recorded in the history.
+    fn verify_aggregate_messages(
+        &self,
+        input: VerifyAggregateMessagesInput,
+    ) -> Result<VerifyAggregateMessagesResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Query(
+                LightClientQueryCommand::VerifyAggregateMessages(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::VerifyAggregateMessages(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     /// verify_membership verifies the existence of the state in the upstream chain and generates a message that represents membership of value in the state
     fn verify_membership(&self, input: VerifyMembershipInput) -> Result<VerifyMembershipResponse> {
         match self.execute_command(
@@ -154,6 +474,110 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         }
     }
 
+    /// verify_membership_batch verifies the existence of multiple states at the same height and generates a message for each of them
+    fn verify_membership_batch(
+        &self,
+        input: VerifyMembershipBatchInput,
+    ) -> Result<VerifyMembershipBatchResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::VerifyMembershipBatch(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::VerifyMembershipBatch(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// verify_key_value verifies the value of an arbitrary key in a store of the upstream chain, or its absence, and generates a message that represents the result
+    fn verify_key_value(&self, input: VerifyKeyValueInput) -> Result<VerifyKeyValueResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::VerifyKeyValue(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::VerifyKeyValue(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// sign_latest_heights generates a signed summary of the latest heights of the clients
+    fn sign_latest_heights(
+        &self,
+        input: SignLatestHeightsInput,
+    ) -> Result<SignLatestHeightsResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::SignLatestHeights(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::SignLatestHeights(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// update_client_params changes the parameters of the client and generates a signed notice of the change
+    fn update_client_params(
+        &self,
+        input: UpdateClientParamsInput,
+    ) -> Result<UpdateClientParamsResponse> {
+        let update_key = Some(input.client_id.to_string());
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::UpdateClientParams(input),
+            )),
+            update_key,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::UpdateClientParams(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// migrate_client moves the client to another light client implementation and generates a signed notice of the migration
+    fn migrate_client(&self, input: MigrateClientInput) -> Result<MigrateClientResponse> {
+        let update_key = Some(input.client_id.to_string());
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::MigrateClient(input),
+            )),
+            update_key,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::MigrateClient(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// resign_latest_state signs the latest state of the client again with the key at `key_address` without advancing the height
+    ///
+    /// The signed message is verified against the attestation of the key before it is returned.
+    fn resign_latest_state(
+        &self,
+        client_id: ClientId,
+        key_address: Address,
+    ) -> Result<ResignLatestStateResponse> {
+        let update_key = Some(client_id.to_string());
+        let res = match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::ResignLatestState(ResignLatestStateInput {
+                    client_id,
+                    current_timestamp: Time::now(),
+                    signer: key_address,
+                }),
+            )),
+            update_key,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::ResignLatestState(res)) => res,
+            _ => unreachable!(),
+        };
+        verify_commitment_proof(self, &res.0, Time::now())?;
+        Ok(res)
+    }
+
     /// query_client queries the client state and consensus state
     fn query_client(&self, input: QueryClientInput) -> Result<QueryClientResponse> {
         match self.execute_command(
@@ -166,4 +590,106 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
             _ => unreachable!(),
         }
     }
+
+    /// list_clients returns a page of the identifiers of the clients created in the enclave
+    fn list_clients(&self, input: ListClientsInput) -> Result<ListClientsResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Query(
+                LightClientQueryCommand::ListClients(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::ListClients(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// all_client_ids returns the identifiers of all clients by following the pages of list_clients
+    fn all_client_ids(&self) -> Result<Vec<ClientId>> {
+        let mut client_ids = Vec::new();
+        let mut pagination = PageRequest::default();
+        loop {
+            let res = self.list_clients(ListClientsInput { pagination })?;
+            client_ids.extend(res.client_ids);
+            match res.pagination.next_offset {
+                Some(offset) => pagination.offset = offset,
+                None => return Ok(client_ids),
+            }
+        }
+    }
+
+    /// query_consensus_heights returns a page of the heights of the consensus states stored for the client
+    fn query_consensus_heights(
+        &self,
+        input: QueryConsensusHeightsInput,
+    ) -> Result<QueryConsensusHeightsResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Query(
+                LightClientQueryCommand::QueryConsensusHeights(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::QueryConsensusHeights(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// query_capabilities returns the optional features of a light client implementation
+    fn query_capabilities(
+        &self,
+        input: QueryCapabilitiesInput,
+    ) -> Result<QueryCapabilitiesResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Query(
+                LightClientQueryCommand::QueryCapabilities(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::QueryCapabilities(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// validate_store decodes the stored states of a page of the clients with the current implementations
+    fn validate_store(&self, input: ValidateStoreInput) -> Result<ValidateStoreResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Query(
+                LightClientQueryCommand::ValidateStore(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::ValidateStore(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
+
+    /// validate_all_clients returns the validations of all clients by following the pages of validate_store
+    fn validate_all_clients(&self) -> Result<Vec<ClientValidation>> {
+        let mut clients = Vec::new();
+        let mut pagination = PageRequest::default();
+        loop {
+            let res = self.validate_store(ValidateStoreInput { pagination })?;
+            clients.extend(res.clients);
+            match res.pagination.next_offset {
+                Some(offset) => pagination.offset = offset,
+                None => return Ok(clients),
+            }
+        }
+    }
+
+    /// prune_client removes the consensus states of the client that have expired, and marks the client as expired if its trusting period has elapsed
+    fn prune_client(&self, input: PruneClientInput) -> Result<PruneClientResponse> {
+        let update_key = Some(input.client_id.to_string());
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::PruneClient(input),
+            )),
+            update_key,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::PruneClient(res)) => Ok(res),
+            _ => unreachable!(),
+        }
+    }
 }
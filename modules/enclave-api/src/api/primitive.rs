@@ -1,37 +1,283 @@
 use crate::{
+    command_log::LoggedCommand,
     enclave::{EnclaveInfo, HostStoreTxManager},
-    ffi, Error, Result,
+    ffi,
+    history::{append_record, latest_records, CommandRecord, PendingRecord},
+    update_history::{
+        record_update, retention, set_retention, update_record_at, update_records, UpdateRecord,
+        UpdateRetention,
+    },
+    watchdog::PendingCommand,
+    Error, Result,
 };
-use ecall_commands::{Command, CommandContext, CommandResponse, ECallCommand, EnclaveKeySelector};
-use lcp_types::Time;
+use crypto::{Address, SealedEnclaveKey};
+use ecall_commands::{
+    decode_frame, decode_response, encode_command, encode_command_message, encode_frame, Command,
+    CommandContext, CommandResponse, CommandResult, ECallCommand, EnclaveKeySelector,
+    EnclaveManageCommand, EnclaveManageResponse, FinalizeStoreSequenceInput, KeyAttestation,
+    LightClientCommand, LightClientExecuteCommand, PageRequest, StoreCheckpoint,
+};
+use keymanager::EnclaveKeyManager;
+use lcp_types::{ClientId, Height, Time};
 use log::*;
 use sgx_types::{sgx_enclave_id_t, sgx_status_t};
 use store::transaction::{CommitStore, Tx};
 
 pub trait EnclavePrimitiveAPI<S: CommitStore>: EnclaveInfo + HostStoreTxManager<S> {
+    /// finalize_store_sequence acknowledges the commit of the store sequence prepared by a command,
+    /// and records the finalized sequence as the checkpoint of the store
+    fn finalize_store_sequence(&self, sequence: u64) -> Result<()> {
+        let res = match self.execute_command(
+            Command::EnclaveManage(EnclaveManageCommand::FinalizeStoreSequence(
+                FinalizeStoreSequenceInput { sequence },
+            )),
+            None,
+        )? {
+            CommandResponse::EnclaveManage(EnclaveManageResponse::FinalizeStoreSequence(res)) => {
+                res
+            }
+            _ => unreachable!(),
+        };
+        self.get_key_manager()
+            .save_store_checkpoint(sequence, &res.sealed_sequence)?;
+        debug!("finalized the store sequence: sequence={}", sequence);
+        Ok(())
+    }
+
     /// execute_command runs a given command in the enclave
     fn execute_command(&self, cmd: Command, update_key: Option<String>) -> Result<CommandResponse> {
         debug!(
             "prepare command: inner={:?} update_key={:?}",
             cmd, update_key
         );
+        let breaker = self
+            .get_circuit_breaker()
+            .filter(|_| cmd.requires_operator_approval());
+        if let Some(reason) = breaker.and_then(|b| b.tripped()) {
+            return Err(Error::circuit_open(reason));
+        }
+        // held until the command is committed or rolled back, so a shutdown never interrupts it
+        let _gate = match self.get_shutdown_gate() {
+            Some(gate) => Some(
+                gate.enter(cmd.requires_operator_approval())
+                    .ok_or_else(Error::shutting_down)?,
+            ),
+            None => None,
+        };
+        let request_digest = match &cmd {
+            Command::LightClient(LightClientCommand::Execute(_)) => Some(
+                cmd.request_digest()
+                    .map_err(|e| Error::invalid_argument(e.to_string()))?,
+            ),
+            _ => None,
+        };
+        // the command is moved into the ecall, so keep what the log needs beforehand
+        let logged = match self.get_command_log() {
+            Some(_) => Some((encode_command_message(&cmd)?, update_key.clone())),
+            None => None,
+        };
+        // the commands that advance the store sequence in their transactions are serialized with the start of an instance,
+        // so that each of them prepares its commit on the sequence finalized after the previous one
+        let changes_store = cmd.changes_store();
+        // the commands that change the store are replayed in the new enclave if the watchdog abandons their ecalls
+        let pending = match self.get_watchdog() {
+            Some(_) if changes_store => Some(PendingCommand {
+                command: encode_command_message(&cmd)?,
+                update_key: update_key.clone(),
+            }),
+            _ => None,
+        };
+        let mut sequence_guard = if changes_store
+            || matches!(
+                cmd,
+                Command::EnclaveManage(EnclaveManageCommand::StartInstance(_))
+            ) {
+            self.get_store_sequence_lock()
+                .map(|lock| lock.lock().unwrap())
+        } else {
+            None
+        };
+        if changes_store {
+            // the enclave refuses to prepare a commit until the previous one is finalized
+            if let Some(sequence) = sequence_guard.as_mut().and_then(|guard| guard.take()) {
+                if let Err(e) = self.finalize_store_sequence(sequence) {
+                    if let Some(guard) = sequence_guard.as_mut() {
+                        **guard = Some(sequence);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        let signer = cmd.get_enclave_key();
         let current_timestamp = Time::now();
         let tx = self.begin_tx(update_key)?;
+        let record = match PendingRecord::new(self, &tx, current_timestamp, &cmd) {
+            Ok(record) => record,
+            Err(e) => {
+                self.rollback_tx(tx);
+                return Err(e);
+            }
+        };
+
+        let dry_run = cmd.is_dry_run();
+        let update_client_id = match &cmd {
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::UpdateClient(input),
+            )) => Some(input.client_id.clone()),
+            _ => None,
+        };
 
         let cctx = match cmd.get_enclave_key() {
             Some(addr) => {
-                let ski = self.get_key_manager().load(addr)?;
-                CommandContext::new(current_timestamp, Some(ski.sealed_ek), tx.get_id())
+                let km = self.get_key_manager();
+                if let Some(reason) = km.revocation_reason(addr)? {
+                    self.rollback_tx(tx);
+                    return Err(Error::revoked_enclave_key(addr.to_string(), reason));
+                }
+                let ski = km.load(addr)?;
+                let cctx = CommandContext::new(current_timestamp, Some(ski.sealed_ek), tx.get_id());
+                let cctx = match km.co_signer(addr)? {
+                    Some(co_addr) => {
+                        debug!(
+                            "co-sign with the incoming key: signer={} co_signer={}",
+                            addr, co_addr
+                        );
+                        cctx.with_co_sealed_ek(km.load(co_addr)?.sealed_ek)
+                    }
+                    None => cctx,
+                };
+                let constituent_signers = cmd.constituent_signers();
+                if constituent_signers.is_empty() {
+                    cctx
+                } else {
+                    match load_constituent_keys(km, &constituent_signers) {
+                        Ok((sealed_eks, attestations)) => {
+                            cctx.with_constituent_keys(sealed_eks, attestations)
+                        }
+                        Err(e) => {
+                            self.rollback_tx(tx);
+                            return Err(e);
+                        }
+                    }
+                }
             }
             None => CommandContext::new(current_timestamp, None, tx.get_id()),
         };
+        let cctx = match self.get_operator_approver() {
+            Some(approver) if cmd.requires_operator_approval() => {
+                cctx.with_operator_signatures(approver.approve(&cmd.operator_sign_bytes()?)?)
+            }
+            _ => cctx,
+        };
+        let cctx = match self.get_session().and_then(|s| s.token()) {
+            Some(token) => cctx.with_session_token(token),
+            None => cctx,
+        };
 
         let ecmd = ECallCommand::new(cctx, cmd);
         debug!("try to execute command: {:?}", ecmd);
-        match raw_execute_command(self.get_eid(), ecmd) {
-            Ok(res) => {
-                self.commit_tx(tx)?;
+        let res = match self.get_watchdog() {
+            Some(watchdog) => watchdog.call(
+                || self.get_eid(),
+                pending,
+                move |eid| raw_execute_command(eid, ecmd),
+            ),
+            None => raw_execute_command(self.get_eid(), ecmd),
+        };
+        match res {
+            Ok(CommandResult {
+                response: res,
+                events,
+                store_checkpoint,
+            }) => {
+                if let Some(request_digest) = request_digest.as_ref() {
+                    if let Err(e) = verify_request_binding(&res, request_digest) {
+                        self.rollback_tx(tx);
+                        return Err(e);
+                    }
+                }
+                if let Err(e) =
+                    verify_store_checkpoint(changes_store, store_checkpoint.as_ref(), signer)
+                {
+                    self.rollback_tx(tx);
+                    return Err(e);
+                }
+                if dry_run {
+                    // nothing of a dry run is persisted, including the histories
+                    self.rollback_tx(tx);
+                    debug!("dry run of the command succeeded: res={:?}", res);
+                    return Ok(res);
+                }
+                let record = match record.map(|r| r.finish(self, &tx, &res)).transpose() {
+                    Ok(record) => record,
+                    Err(e) => {
+                        self.rollback_tx(tx);
+                        return Err(e);
+                    }
+                };
+                if let Some(client_id) = update_client_id.as_ref() {
+                    match record_update(self, &tx, client_id, current_timestamp, &res) {
+                        Ok(Some(seq)) => debug!(
+                            "recorded the update in the history: client_id={} seq={}",
+                            client_id, seq
+                        ),
+                        Ok(None) => {}
+                        Err(e) => {
+                            self.rollback_tx(tx);
+                            return Err(e);
+                        }
+                    }
+                }
+                if let Err(e) = self.commit_tx(tx) {
+                    // the enclave may have signed a commitment to the state that the store failed to persist
+                    if let Some(breaker) = breaker {
+                        breaker.record_divergence(&e);
+                    }
+                    return Err(e);
+                }
                 debug!("execute_command succeeded: res={:?}", res);
+                if let Some(subscribers) = self.get_command_events() {
+                    subscribers.publish(&events);
+                }
+                if let Some(checkpoint) = store_checkpoint {
+                    // the command has been committed, so a failure here must not fail it;
+                    // the next command that changes the store retries it, and the next instance finalizes it from the store
+                    if let Err(e) = self.finalize_store_sequence(checkpoint.sequence) {
+                        error!(
+                            "failed to finalize the store sequence: sequence={} err={:?}",
+                            checkpoint.sequence, e
+                        );
+                        if let Some(guard) = sequence_guard.as_mut() {
+                            **guard = Some(checkpoint.sequence);
+                        }
+                    }
+                } else if let Some(guard) = sequence_guard.as_mut() {
+                    // the new instance has finalized the sequence that the store had committed
+                    **guard = None;
+                }
+                if let Some(mut record) = record {
+                    let seq = append_record(self, record.clone())?;
+                    debug!("recorded the command in the history: seq={}", seq);
+                    if let (Some(log), Some((command, update_key))) =
+                        (self.get_command_log(), logged)
+                    {
+                        record.seq = seq;
+                        let entry = LoggedCommand {
+                            record,
+                            update_key,
+                            command,
+                        };
+                        // the command has been committed, so a failure of the log must not fail it
+                        if let Err(e) = log.append(&entry) {
+                            error!(
+                                "failed to append the command to the log: path={:?} seq={} err={:?}",
+                                log.path(),
+                                seq,
+                                e
+                            );
+                        }
+                    }
+                }
                 Ok(res)
             }
             Err(e) => {
@@ -41,47 +287,198 @@ pub trait EnclavePrimitiveAPI<S: CommitStore>: EnclaveInfo + HostStoreTxManager<
             }
         }
     }
+
+    /// command_history returns the latest `limit` records of the executed light client commands in descending order
+    fn command_history(&self, limit: u64) -> Result<Vec<CommandRecord>> {
+        latest_records(self, limit)
+    }
+
+    /// update_history returns a page of the signed updates of the client in descending order of `seq`
+    fn update_history(
+        &self,
+        client_id: &ClientId,
+        pagination: PageRequest,
+    ) -> Result<Vec<UpdateRecord>> {
+        update_records(self, client_id, pagination)
+    }
+
+    /// update_history_at returns the latest signed update of the client to `height`
+    fn update_history_at(
+        &self,
+        client_id: &ClientId,
+        height: &Height,
+    ) -> Result<Option<UpdateRecord>> {
+        update_record_at(self, client_id, height)
+    }
+
+    /// set_update_retention sets the retention policy of the update records of the client,
+    /// and returns the number of the records pruned by it
+    fn set_update_retention(&self, client_id: &ClientId, policy: UpdateRetention) -> Result<u64> {
+        // serialized with the updates of the client, which append the records
+        let tx = self.begin_tx(Some(client_id.to_string()))?;
+        match set_retention(self, &tx, client_id, policy, Time::now()) {
+            Ok(pruned) => {
+                self.commit_tx(tx)?;
+                Ok(pruned)
+            }
+            Err(e) => {
+                self.rollback_tx(tx);
+                Err(e)
+            }
+        }
+    }
+
+    /// update_retention returns the retention policy of the update records of the client
+    fn update_retention(&self, client_id: &ClientId) -> Result<UpdateRetention> {
+        retention(self, client_id)
+    }
+}
+
+/// Load the sealed keys of the other signers of the messages to aggregate
+///
+/// Each signer must be an attested key that has not been revoked.
+fn load_constituent_keys(
+    km: &EnclaveKeyManager,
+    signers: &[Address],
+) -> Result<(Vec<SealedEnclaveKey>, Vec<KeyAttestation>)> {
+    signers
+        .iter()
+        .map(|signer| {
+            if let Some(reason) = km.revocation_reason(*signer)? {
+                return Err(Error::revoked_enclave_key(signer.to_string(), reason));
+            }
+            let ski = km.load(*signer)?;
+            // the enclave verifies the attestation against its own MRENCLAVE
+            let attestation = match ski.avr {
+                Some(avr) => KeyAttestation::IAS(avr),
+                None => KeyAttestation::DCAP(km.load_dcap_bundle(*signer)?.ok_or_else(|| {
+                    Error::invalid_argument(format!(
+                        "the signer has not been attested: signer={}",
+                        signer
+                    ))
+                })?),
+            };
+            Ok((ski.sealed_ek, attestation))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|keys| keys.into_iter().unzip())
+}
+
+/// Returns an error unless a command that changes the store returned the checkpoint signed by its enclave key
+fn verify_store_checkpoint(
+    changes_store: bool,
+    checkpoint: Option<&StoreCheckpoint>,
+    signer: Option<Address>,
+) -> Result<()> {
+    match (checkpoint, signer) {
+        (None, _) if changes_store => Err(Error::invalid_store_checkpoint(
+            "the command didn't advance the store sequence".to_string(),
+        )),
+        (Some(checkpoint), Some(signer)) => checkpoint
+            .verify(signer)
+            .map_err(|e| Error::invalid_store_checkpoint(e.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if a proof in the response is not bound to the request
+fn verify_request_binding(res: &CommandResponse, request_digest: &[u8; 32]) -> Result<()> {
+    if let CommandResponse::LightClient(res) = res {
+        for proof in res.proofs().into_iter().filter(|p| p.is_proven()) {
+            proof
+                .verify_request_binding(request_digest)
+                .map_err(|e| Error::proof_verification(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+fn raw_execute_command(eid: sgx_enclave_id_t, cmd: ECallCommand) -> Result<CommandResult> {
+    let (ret, output) = raw_ecall(eid, &encode_frame(&encode_command(&cmd)?))?;
+    let payload = decode_frame(&output)?;
+    if payload.is_empty() {
+        return Err(Error::rejected_command(ret));
+    }
+    let res = decode_response(payload)?;
+
+    if ret == sgx_status_t::SGX_SUCCESS {
+        Ok(res)
+    } else if let CommandResponse::CommandError(code, descr) = res.response {
+        Err(Error::command(ret, code, descr))
+    } else {
+        unreachable!()
+    }
 }
 
-fn raw_execute_command(eid: sgx_enclave_id_t, cmd: ECallCommand) -> Result<CommandResponse> {
+/// Check that the enclave speaks the same protocol version as the host
+pub(crate) fn handshake(eid: sgx_enclave_id_t) -> Result<()> {
+    let (ret, output) = raw_ecall(eid, &encode_frame(&[]))?;
+    decode_frame(&output)?;
+    if ret != sgx_status_t::SGX_SUCCESS {
+        return Err(Error::rejected_command(ret));
+    }
+    Ok(())
+}
+
+/// Call the enclave with the frame, injecting the next fault of the ecall boundary if any
+#[cfg(feature = "fault-injection")]
+fn raw_ecall(eid: sgx_enclave_id_t, frame: &[u8]) -> Result<(sgx_status_t, Vec<u8>)> {
+    use host::fault::{corrupt, next_fault, Boundary, Fault};
+    match next_fault(Boundary::ECall) {
+        None => deliver_ecall(eid, frame),
+        Some(Fault::Drop) => Err(Error::sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED)),
+        Some(Fault::SgxError(status)) => Err(Error::sgx_error(status)),
+        Some(Fault::Delay(delay)) => {
+            std::thread::sleep(delay);
+            deliver_ecall(eid, frame)
+        }
+        Some(Fault::Duplicate) => {
+            let _ = deliver_ecall(eid, frame);
+            deliver_ecall(eid, frame)
+        }
+        Some(Fault::CorruptRequest) => {
+            let mut frame = frame.to_vec();
+            corrupt(&mut frame);
+            deliver_ecall(eid, &frame)
+        }
+        Some(Fault::CorruptResponse) => {
+            let (ret, mut output) = deliver_ecall(eid, frame)?;
+            corrupt(&mut output);
+            Ok((ret, output))
+        }
+    }
+}
+
+#[cfg(not(feature = "fault-injection"))]
+fn raw_ecall(eid: sgx_enclave_id_t, frame: &[u8]) -> Result<(sgx_status_t, Vec<u8>)> {
+    deliver_ecall(eid, frame)
+}
+
+/// Call the enclave with the frame and returns the status and the frame of the response
+fn deliver_ecall(eid: sgx_enclave_id_t, frame: &[u8]) -> Result<(sgx_status_t, Vec<u8>)> {
     let mut output_len = 0;
     let output_maxlen = 65536;
     let mut output_buf = Vec::with_capacity(output_maxlen);
     let output_ptr = output_buf.as_mut_ptr();
     let mut ret = sgx_status_t::SGX_SUCCESS;
 
-    let command_bytes = bincode::serde::encode_to_vec(&cmd, bincode::config::standard())
-        .map_err(Error::bincode_encode)?;
     let result = unsafe {
         ffi::ecall_execute_command(
             eid,
             &mut ret,
-            command_bytes.as_ptr(),
-            command_bytes.len() as u32,
+            frame.as_ptr(),
+            frame.len() as u32,
             output_ptr,
             output_maxlen as u32,
             &mut output_len,
         )
     };
     if result != sgx_status_t::SGX_SUCCESS {
-        Err(Error::sgx_error(result))
-    } else {
-        assert!((output_len as usize) < output_maxlen);
-        unsafe {
-            output_buf.set_len(output_len as usize);
-        }
-        let res = bincode::serde::decode_borrowed_from_slice(
-            &output_buf[..output_len as usize],
-            bincode::config::standard(),
-        )
-        .map_err(Error::bincode_decode)?;
-
-        if ret == sgx_status_t::SGX_SUCCESS {
-            Ok(res)
-        } else if let CommandResponse::CommandError(descr) = res {
-            Err(Error::command(ret, descr))
-        } else {
-            unreachable!()
-        }
+        return Err(Error::sgx_error(result));
+    }
+    assert!((output_len as usize) < output_maxlen);
+    unsafe {
+        output_buf.set_len(output_len as usize);
     }
+    Ok((ret, output_buf))
 }
@@ -1,11 +1,13 @@
 use super::command::EnclaveCommandAPI;
-use crate::Result;
+use crate::{verify_commitment_proof, Result};
 use lcp_proto::lcp::service::elc::v1::{
     MsgAggregateMessages, MsgAggregateMessagesResponse, MsgCreateClient, MsgCreateClientResponse,
-    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyMembership, MsgVerifyMembershipResponse,
-    MsgVerifyNonMembership, MsgVerifyNonMembershipResponse, QueryClientRequest,
-    QueryClientResponse,
+    MsgMigrateClient, MsgMigrateClientResponse, MsgSignLatestHeights, MsgSignLatestHeightsResponse,
+    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyKeyValue, MsgVerifyKeyValueResponse,
+    MsgVerifyMembership, MsgVerifyMembershipResponse, MsgVerifyNonMembership,
+    MsgVerifyNonMembershipResponse, QueryClientRequest, QueryClientResponse,
 };
+use lcp_types::Time;
 use log::*;
 use store::transaction::CommitStore;
 
@@ -22,10 +24,12 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
 
     fn proto_update_client(&self, msg: MsgUpdateClient) -> Result<MsgUpdateClientResponse> {
         let client_id = msg.client_id.clone();
+        let dry_run = msg.dry_run;
         let res = self.update_client(msg.try_into()?)?;
         info!(
-            "update_client: client_id={} message={{{}}}",
+            "update_client: client_id={} dry_run={} message={{{}}}",
             client_id,
+            dry_run,
             res.0.message()?
         );
         Ok(res.into())
@@ -46,6 +50,7 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
     ) -> Result<MsgVerifyMembershipResponse> {
         let client_id = msg.client_id.clone();
         let res = self.verify_membership(msg.try_into()?)?;
+        verify_commitment_proof(self, &res.0, Time::now())?;
         info!(
             "verify_membership: client_id={} message={{{}}}",
             client_id,
@@ -60,6 +65,7 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
     ) -> Result<MsgVerifyNonMembershipResponse> {
         let client_id = msg.client_id.clone();
         let res = self.verify_non_membership(msg.try_into()?)?;
+        verify_commitment_proof(self, &res.0, Time::now())?;
         info!(
             "verify_non_membership: client_id={} message={{{}}}",
             client_id,
@@ -68,6 +74,39 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
         Ok(res.into())
     }
 
+    fn proto_verify_key_value(&self, msg: MsgVerifyKeyValue) -> Result<MsgVerifyKeyValueResponse> {
+        let client_id = msg.client_id.clone();
+        let res = self.verify_key_value(msg.try_into()?)?;
+        verify_commitment_proof(self, &res.0, Time::now())?;
+        info!(
+            "verify_key_value: client_id={} message={{{}}}",
+            client_id,
+            res.0.message()?
+        );
+        Ok(res.into())
+    }
+
+    fn proto_migrate_client(&self, msg: MsgMigrateClient) -> Result<MsgMigrateClientResponse> {
+        let client_id = msg.client_id.clone();
+        let res = self.migrate_client(msg.try_into()?)?;
+        info!(
+            "migrate_client: client_id={} message={{{}}}",
+            client_id,
+            res.0.message()?
+        );
+        Ok(res.into())
+    }
+
+    fn proto_sign_latest_heights(
+        &self,
+        msg: MsgSignLatestHeights,
+    ) -> Result<MsgSignLatestHeightsResponse> {
+        let res = self.sign_latest_heights(msg.try_into()?)?;
+        verify_commitment_proof(self, &res.0, Time::now())?;
+        info!("sign_latest_heights: message={{{}}}", res.0.message()?);
+        Ok(res.into())
+    }
+
     fn proto_query_client(&self, query: QueryClientRequest) -> Result<QueryClientResponse> {
         Ok(self.query_client(query.try_into()?)?.into())
     }
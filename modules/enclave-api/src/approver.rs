@@ -0,0 +1,89 @@
+use crate::{Error, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// `OperatorApprover` collects the operator signatures that approve a mutating command
+///
+/// The enclave rejects the command unless the signatures meet the threshold of the configured operator set.
+/// The operator keys must never be held by the host process, so the implementations must collect the signatures from outside of it.
+pub trait OperatorApprover: Sync + Send {
+    /// Returns the signatures over `sign_bytes`
+    fn approve(&self, sign_bytes: &[u8]) -> Result<Vec<Vec<u8>>>;
+}
+
+/// `ExternalApprover` asks an external program to collect the operator signatures
+///
+/// The program receives the hex-encoded `sign_bytes` in a line of the stdin,
+/// and must print each hex-encoded signature in a line of the stdout.
+/// A non-zero exit status means that the operators didn't approve the command.
+pub struct ExternalApprover {
+    program: String,
+    args: Vec<String>,
+}
+
+impl ExternalApprover {
+    pub fn new(program: String, args: Vec<String>) -> Self {
+        Self { program, args }
+    }
+}
+
+impl OperatorApprover for ExternalApprover {
+    fn approve(&self, sign_bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(Error::io)?;
+        {
+            let mut stdin = child
+                .stdin
+                .take()
+                .ok_or_else(|| Error::operator_approval("failed to open the stdin".into()))?;
+            writeln!(stdin, "{}", hex::encode(sign_bytes)).map_err(Error::io)?;
+        }
+        let output = child.wait_with_output().map_err(Error::io)?;
+        if !output.status.success() {
+            return Err(Error::operator_approval(format!(
+                "the approver exited with {}",
+                output.status
+            )));
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                hex::decode(line.trim_start_matches("0x")).map_err(|e| {
+                    Error::operator_approval(format!("invalid signature: line={} err={}", line, e))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> ExternalApprover {
+        ExternalApprover::new("sh".into(), vec!["-c".into(), script.into()])
+    }
+
+    #[test]
+    fn test_external_approver() {
+        // echoes the sign bytes back as two signatures
+        let approver = sh("read bz; echo $bz; echo 0x$bz");
+        assert_eq!(
+            approver.approve(&[1, 2, 3]).unwrap(),
+            vec![vec![1, 2, 3], vec![1, 2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_external_approver_rejection() {
+        assert!(sh("read bz; exit 1").approve(&[1]).is_err());
+        assert!(sh("read bz; echo zz").approve(&[1]).is_err());
+    }
+}
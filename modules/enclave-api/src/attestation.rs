@@ -0,0 +1,86 @@
+use crate::{EnclaveInfo, Result};
+use crypto::Address;
+use ecall_commands::{PageRequest, PageResponse};
+use keymanager::{AttestationRecord, EvidenceType};
+use lcp_types::Time;
+use serde::{Deserialize, Serialize};
+
+/// QueryAttestationsInput selects the attestation evidences that the key manager stores
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryAttestationsInput {
+    /// The enclave key whose evidences are returned, or all keys if None
+    pub key_address: Option<Address>,
+    /// The type of the evidences, or all types if None
+    pub evidence_type: Option<EvidenceType>,
+    /// The evidences attested before this time are excluded
+    pub min_attestation_time: Option<Time>,
+    pub pagination: PageRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryAttestationsResponse {
+    /// The evidences in descending order of the attestation time
+    pub attestations: Vec<AttestationRecord>,
+    pub pagination: PageResponse,
+}
+
+/// Returns a page of the attestation evidences that match the input
+///
+/// The evidences are read from the key manager on the host, so the query does not enter the enclave.
+pub fn query_attestations<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    input: QueryAttestationsInput,
+) -> Result<QueryAttestationsResponse> {
+    let records = enclave.get_key_manager().attestations(
+        input.key_address,
+        input.evidence_type,
+        input
+            .min_attestation_time
+            .map_or(0, |t| t.as_unix_timestamp_secs()),
+    )?;
+    let (attestations, pagination) = paginate(records, input.pagination);
+    Ok(QueryAttestationsResponse {
+        attestations,
+        pagination,
+    })
+}
+
+fn paginate<T>(items: Vec<T>, pagination: PageRequest) -> (Vec<T>, PageResponse) {
+    let total = items.len() as u64;
+    let end = pagination.offset.saturating_add(pagination.limit);
+    let page = items
+        .into_iter()
+        .skip(pagination.offset.min(total) as usize)
+        .take(pagination.limit as usize)
+        .collect();
+    (
+        page,
+        PageResponse {
+            next_offset: if end < total { Some(end) } else { None },
+            total,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        let items: Vec<u64> = (0..5).collect();
+        let (page, res) = paginate(items.clone(), PageRequest::new(0, 2).unwrap());
+        assert_eq!(page, vec![0, 1]);
+        assert_eq!(res.next_offset, Some(2));
+        assert_eq!(res.total, 5);
+
+        let (page, res) = paginate(items.clone(), PageRequest::new(4, 2).unwrap());
+        assert_eq!(page, vec![4]);
+        assert_eq!(res.next_offset, None);
+
+        let (page, res) = paginate(items, PageRequest::new(10, 2).unwrap());
+        assert!(page.is_empty());
+        assert_eq!(res.next_offset, None);
+        assert_eq!(res.total, 5);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{EnclaveCommandAPI, Error, Result};
+use ecall_commands::{UpdateClientInput, UpdateClientResponse};
+use lcp_types::{Any, Height};
+use log::*;
+use store::transaction::CommitStore;
+
+/// The maximum number of update attempts in a bisection, which bounds the retries
+/// if the enclave keeps requiring a bisection
+pub const MAX_BISECTION_ATTEMPTS: usize = 32;
+
+/// Update the client to `target_height`, bisecting the range from `trusted_height` if the enclave requires it
+///
+/// `input` must carry the header at `target_height` that is verified against `trusted_height`.
+/// If the enclave rejects an update because the validator set changed beyond the trust level,
+/// the client is updated to the highest height suggested by the enclave first, and then to the target again.
+/// `fetch_header` returns the header at the second height that is verified against the first one.
+///
+/// Returns the responses of the successful updates in order, the last of which is the update to `target_height`.
+/// On an error, the client may have been updated to some intermediate heights.
+pub fn update_client_with_bisection<S, E, F>(
+    enclave: &E,
+    input: UpdateClientInput,
+    trusted_height: Height,
+    target_height: Height,
+    mut fetch_header: F,
+) -> Result<Vec<UpdateClientResponse>>
+where
+    S: CommitStore,
+    E: EnclaveCommandAPI<S>,
+    F: FnMut(Height, Height) -> Result<Any>,
+{
+    let UpdateClientInput {
+        client_id,
+        any_header,
+        include_state,
+        state_filters,
+        detect_misbehaviour,
+        expires_at,
+        dry_run,
+        current_timestamp,
+        signer,
+    } = input;
+    bisect(
+        trusted_height,
+        target_height,
+        any_header,
+        &mut fetch_header,
+        |any_header| {
+            enclave.update_client(UpdateClientInput {
+                client_id: client_id.clone(),
+                any_header,
+                include_state,
+                state_filters: state_filters.clone(),
+                detect_misbehaviour,
+                expires_at,
+                dry_run,
+                current_timestamp,
+                signer,
+            })
+        },
+    )
+}
+
+fn bisect<R>(
+    trusted_height: Height,
+    target_height: Height,
+    target_header: Any,
+    fetch_header: &mut dyn FnMut(Height, Height) -> Result<Any>,
+    mut update: impl FnMut(Any) -> Result<R>,
+) -> Result<Vec<R>> {
+    let mut trusted_height = trusted_height;
+    // the heights to update the client to, where the last one is the next
+    let mut pending = vec![(target_height, Some(target_header))];
+    let mut responses = vec![];
+    let mut attempts = 0;
+    while let Some((height, header)) = pending.pop() {
+        attempts += 1;
+        if attempts > MAX_BISECTION_ATTEMPTS {
+            return Err(Error::bisection_limit_exceeded(
+                target_height,
+                MAX_BISECTION_ATTEMPTS,
+            ));
+        }
+        let header = match header {
+            Some(header) => header,
+            None => fetch_header(trusted_height, height)?,
+        };
+        match update(header) {
+            Ok(res) => {
+                responses.push(res);
+                trusted_height = height;
+            }
+            Err(e) => {
+                let next = e.suggested_heights().and_then(|heights| {
+                    heights
+                        .into_iter()
+                        .find(|h| *h > trusted_height && *h < height)
+                });
+                match next {
+                    Some(next) => {
+                        info!(
+                            "bisecting the update: trusted_height={} height={} next={}",
+                            trusted_height, height, next
+                        );
+                        pending.push((height, None));
+                        pending.push((next, None));
+                    }
+                    None => return Err(e),
+                }
+            }
+        }
+    }
+    Ok(responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ecall_commands::ErrorCode;
+    use sgx_types::sgx_status_t;
+
+    fn header(trusted_height: Height, height: Height) -> Any {
+        Any::new(
+            "/test.Header".into(),
+            format!("{}:{}", trusted_height, height).into_bytes(),
+        )
+    }
+
+    fn heights(any: &Any) -> (u64, u64) {
+        let s = String::from_utf8(any.to_proto().value).unwrap();
+        let (trusted, height) = s.split_once(':').unwrap();
+        (
+            trusted.parse::<Height>().unwrap().revision_height(),
+            height.parse::<Height>().unwrap().revision_height(),
+        )
+    }
+
+    /// Accepts an update only if the heights are at most `max_skip` apart
+    fn update(max_skip: u64) -> impl FnMut(Any) -> Result<u64> {
+        move |any| {
+            let (trusted, height) = heights(&any);
+            if height - trusted <= max_skip {
+                return Ok(height);
+            }
+            Err(Error::command(
+                sgx_status_t::SGX_ERROR_UNEXPECTED,
+                ErrorCode::BisectionRequired,
+                format!(
+                    "suggested_heights=1-{},1-{}",
+                    trusted + (height - trusted) / 2,
+                    trusted + 1
+                ),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_bisect() {
+        let mut fetch = |trusted, height| -> Result<Any> { Ok(header(trusted, height)) };
+
+        let res = bisect(
+            Height::new(1, 10),
+            Height::new(1, 20),
+            header(Height::new(1, 10), Height::new(1, 20)),
+            &mut fetch,
+            update(100),
+        )
+        .unwrap();
+        assert_eq!(res, vec![20]);
+
+        let res = bisect(
+            Height::new(1, 10),
+            Height::new(1, 50),
+            header(Height::new(1, 10), Height::new(1, 50)),
+            &mut fetch,
+            update(10),
+        )
+        .unwrap();
+        assert_eq!(res, vec![20, 30, 40, 50]);
+
+        // the error is returned as is if the enclave doesn't suggest a height in the range
+        let err = bisect(
+            Height::new(1, 10),
+            Height::new(1, 20),
+            header(Height::new(1, 10), Height::new(1, 20)),
+            &mut fetch,
+            |_| -> Result<u64> {
+                Err(Error::command(
+                    sgx_status_t::SGX_ERROR_UNEXPECTED,
+                    ErrorCode::InvalidValidatorSet,
+                    "".into(),
+                ))
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.command_error_code(),
+            Some(ErrorCode::InvalidValidatorSet)
+        );
+
+        // the retries are bounded
+        assert!(bisect(
+            Height::new(1, 0),
+            Height::new(1, 1 << 40),
+            header(Height::new(1, 0), Height::new(1, 1 << 40)),
+            &mut fetch,
+            update(1),
+        )
+        .is_err());
+    }
+}
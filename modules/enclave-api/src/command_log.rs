@@ -0,0 +1,179 @@
+use crate::history::CommandRecord;
+use crate::{Error, Result};
+use crypto::Keccak256;
+use ecall_commands::{decode_command_message, Command};
+use lcp_proto::lcp::ecall::v1::LoggedCommand as ProtoLoggedCommand;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// `LoggedCommand` is an entry of the command log
+///
+/// Unlike `CommandRecord`, it contains the command itself, so that the command can be replayed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoggedCommand {
+    pub record: CommandRecord,
+    /// The update key of the transaction that executed the command
+    pub update_key: Option<String>,
+    /// The command encoded with `ecall_commands::encode_command_message`, whose keccak256 is `record.input_digest`
+    pub command: Vec<u8>,
+}
+
+impl LoggedCommand {
+    /// Decode the command after checking it against the digest in the record
+    pub fn command(&self) -> Result<Command> {
+        if self.command.keccak256() != self.record.input_digest {
+            return Err(Error::invalid_argument(format!(
+                "the logged command doesn't match the input digest: seq={}",
+                self.record.seq
+            )));
+        }
+        Ok(decode_command_message(&self.command)?)
+    }
+
+    fn to_proto(&self) -> Result<ProtoLoggedCommand> {
+        Ok(ProtoLoggedCommand {
+            record: Some(self.record.to_proto()?),
+            update_key: self.update_key.clone(),
+            command: self.command.clone(),
+        })
+    }
+
+    fn from_proto(msg: ProtoLoggedCommand) -> Result<Self> {
+        Ok(Self {
+            record: CommandRecord::from_proto(
+                msg.record
+                    .ok_or_else(|| Error::invalid_argument("record is missing".into()))?,
+            )?,
+            update_key: msg.update_key,
+            command: msg.command,
+        })
+    }
+}
+
+/// `CommandLog` appends the light client commands executed by the enclave to a file
+///
+/// Each entry is a `LoggedCommand` encoded with its `lcp.ecall.v1` message and prefixed with its length as a big-endian u32.
+#[derive(Debug)]
+pub struct CommandLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl CommandLog {
+    /// Open the log at `path` to append the entries to it, creating it if it doesn't exist
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(Error::io)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn append(&self, entry: &LoggedCommand) -> Result<()> {
+        let bz = entry.to_proto()?.encode_to_vec();
+        let len = u32::try_from(bz.len())
+            .map_err(|_| Error::invalid_argument("the logged command is too large".into()))?;
+        let mut buf = len.to_be_bytes().to_vec();
+        buf.extend(bz);
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&buf).map_err(Error::io)?;
+        file.flush().map_err(Error::io)
+    }
+}
+
+/// Read all the entries of the command log at `path` in the order they were appended
+pub fn read_command_log(path: impl AsRef<Path>) -> Result<Vec<LoggedCommand>> {
+    let mut reader = BufReader::new(File::open(path).map_err(Error::io)?);
+    let mut entries = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(entries),
+            Err(e) => return Err(Error::io(e)),
+        }
+        let mut bz = vec![0u8; u32::from_be_bytes(len) as usize];
+        reader.read_exact(&mut bz).map_err(Error::io)?;
+        entries.push(LoggedCommand::from_proto(
+            ProtoLoggedCommand::decode(bz.as_slice()).map_err(Error::proto_decode)?,
+        )?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ecall_commands::{
+        encode_command_message, LightClientCommand, LightClientExecuteCommand, PruneClientInput,
+    };
+    use lcp_types::{ClientId, Time};
+    use std::str::FromStr;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn logged_command(seq: u64) -> LoggedCommand {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let cmd = Command::LightClient(LightClientCommand::Execute(
+            LightClientExecuteCommand::PruneClient(PruneClientInput {
+                client_id: client_id.clone(),
+                margin: Duration::from_secs(seq),
+                current_timestamp: Time::unix_epoch(),
+            }),
+        ));
+        let command = encode_command_message(&cmd).unwrap();
+        LoggedCommand {
+            record: CommandRecord {
+                seq,
+                timestamp: Time::unix_epoch(),
+                command_type: "prune_client".into(),
+                client_id: Some(client_id.clone()),
+                input_digest: command.keccak256(),
+                result_digest: [0; 32],
+                pre_state_digest: None,
+                post_state_digest: None,
+            },
+            update_key: Some(client_id.to_string()),
+            command,
+        }
+    }
+
+    #[test]
+    fn test_command_log() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("command-log.bin");
+        let entries = vec![logged_command(0), logged_command(1)];
+        {
+            let log = CommandLog::open(&path).unwrap();
+            log.append(&entries[0]).unwrap();
+        }
+        // a reopened log is appended to
+        let log = CommandLog::open(&path).unwrap();
+        log.append(&entries[1]).unwrap();
+        let read = read_command_log(&path).unwrap();
+        assert_eq!(read, entries);
+        assert!(matches!(
+            read[1].command().unwrap(),
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::PruneClient(_)
+            ))
+        ));
+
+        // a command that doesn't match the digest is rejected
+        let mut tampered = entries[0].clone();
+        tampered.command = entries[1].command.clone();
+        assert!(tampered.command().is_err());
+    }
+}
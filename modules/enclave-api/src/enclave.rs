@@ -1,19 +1,39 @@
-use crate::errors::Result;
+use crate::api::handshake;
+use crate::errors::{Error, Result};
+use crate::{
+    AttestationCache, CircuitBreaker, CommandEvents, CommandLog, OperatorApprover, Session,
+    ShutdownGate, Watchdog,
+};
+use crypto::Signer;
 use keymanager::EnclaveKeyManager;
+use log::*;
 use sgx_types::{metadata::metadata_t, sgx_enclave_id_t, SgxResult};
 use sgx_urts::SgxEnclave;
-use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use std::{marker::PhantomData, ops::DerefMut};
 use store::host::{HostStore, IntoCommitStore};
-use store::transaction::{CommitStore, CreatedTx, UpdateKey};
+use store::transaction::{CommitStore, CreatedTx, Tx, TxAccessor, UpdateKey};
 
 /// `Enclave` keeps an enclave id and reference to the host environement
 pub struct Enclave<S: CommitStore> {
     pub(crate) path: PathBuf,
     pub(crate) key_manager: EnclaveKeyManager,
     pub(crate) store: Arc<RwLock<HostStore>>,
-    pub(crate) sgx_enclave: SgxEnclave,
+    /// None after the enclave is destroyed by `EnclaveInfo::destroy_enclave`
+    pub(crate) sgx_enclave: RwLock<Option<SgxEnclave>>,
+    pub(crate) debug: bool,
+    pub(crate) operator_approver: Option<Box<dyn OperatorApprover>>,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+    pub(crate) command_log: Option<CommandLog>,
+    pub(crate) watchdog: Option<Watchdog>,
+    pub(crate) session: Option<Session>,
+    pub(crate) shutdown_gate: ShutdownGate,
+    pub(crate) command_events: CommandEvents,
+    /// The sequence whose commit the store has committed but the enclave has not finalized
+    pub(crate) store_sequence_lock: Mutex<Option<u64>>,
+    pub(crate) attestation_cache: AttestationCache,
     _marker: PhantomData<S>,
 }
 
@@ -28,25 +48,87 @@ impl<S: CommitStore> Enclave<S> {
             path: path.into(),
             key_manager,
             store,
-            sgx_enclave,
+            sgx_enclave: RwLock::new(Some(sgx_enclave)),
+            debug: false,
+            operator_approver: None,
+            circuit_breaker: None,
+            command_log: None,
+            watchdog: None,
+            session: Some(Session::default()),
+            shutdown_gate: Default::default(),
+            command_events: Default::default(),
+            store_sequence_lock: Default::default(),
+            attestation_cache: Default::default(),
             _marker: PhantomData::default(),
         }
     }
 
+    /// Attach the operator signatures collected by the approver to every mutating command
+    pub fn with_operator_approver(mut self, approver: Box<dyn OperatorApprover>) -> Self {
+        self.operator_approver = Some(approver);
+        self
+    }
+
+    /// Halt the mutating commands when the commits to the host store fail or diverge from the enclave
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Append the executed light client commands to the log, so that they can be replayed against a fresh enclave
+    pub fn with_command_log(mut self, log: CommandLog) -> Self {
+        self.command_log = Some(log);
+        self
+    }
+
+    /// Track the ecalls to detect the enclave hanging for longer than `timeout`
+    ///
+    /// The enclave is re-created by `EnclaveCommandAPI::recover_hung_enclave`, which the caller should run periodically.
+    pub fn with_watchdog(mut self, timeout: Duration) -> Self {
+        self.watchdog = Some(Watchdog::new(timeout));
+        self
+    }
+
+    /// Prove the possession of the operator credential to the enclave when a session is opened
+    ///
+    /// The session is opened by `EnclaveCommandAPI::open_session`, and re-opened after the enclave is re-created.
+    pub fn with_session_credential(mut self, credential: Box<dyn Signer + Sync + Send>) -> Self {
+        self.session = Some(Session::new(Some(credential)));
+        self
+    }
+
+    /// Load the enclave and check that it speaks the same protocol version as the host
     pub fn create(
         path: impl Into<PathBuf>,
         debug: bool,
         key_manager: EnclaveKeyManager,
         store: Arc<RwLock<HostStore>>,
-    ) -> SgxResult<Self> {
+    ) -> Result<Self> {
         let path = path.into();
-        let enclave = host::create_enclave(path.clone(), debug)?;
-        Ok(Self::new(path, key_manager, store, enclave))
+        let enclave = launch(&path, debug)?;
+        let mut enclave = Self::new(path, key_manager, store, enclave);
+        enclave.debug = debug;
+        Ok(enclave)
     }
 
     pub fn destroy(self) {
-        self.sgx_enclave.destroy()
+        if let Some(enclave) = self.sgx_enclave.into_inner().unwrap() {
+            enclave.destroy()
+        }
+    }
+}
+
+/// Create the enclave and check that it speaks the same protocol version as the host
+fn launch(path: &Path, debug: bool) -> Result<SgxEnclave> {
+    let enclave = host::create_enclave(path.to_path_buf(), debug).map_err(|status| {
+        let platform = host::PlatformCapabilities::probe();
+        Error::enclave_launch(status, platform.diagnose_launch_error(status))
+    })?;
+    if let Err(e) = handshake(enclave.geteid()) {
+        enclave.destroy();
+        return Err(e);
     }
+    Ok(enclave)
 }
 
 /// `EnclaveInfo` is an accessor to enclave information
@@ -57,12 +139,55 @@ pub trait EnclaveInfo: Sync + Send {
     fn metadata(&self) -> SgxResult<metadata_t>;
     /// `get_key_manager` returns a key manager for Enclave Keys
     fn get_key_manager(&self) -> &EnclaveKeyManager;
+    /// `get_operator_approver` returns the approver of mutating commands if configured
+    fn get_operator_approver(&self) -> Option<&dyn OperatorApprover> {
+        None
+    }
+    /// `get_command_log` returns the log of the executed commands if configured
+    fn get_command_log(&self) -> Option<&CommandLog> {
+        None
+    }
+    /// `get_watchdog` returns the watchdog that tracks the ecalls if configured
+    fn get_watchdog(&self) -> Option<&Watchdog> {
+        None
+    }
+    /// `get_shutdown_gate` returns the gate that rejects the commands during a shutdown
+    fn get_shutdown_gate(&self) -> Option<&ShutdownGate> {
+        None
+    }
+    /// `get_command_events` returns the subscribers to the events of the committed commands
+    fn get_command_events(&self) -> Option<&CommandEvents> {
+        None
+    }
+    /// `get_session` returns the session with the enclave if the operator credential is configured
+    fn get_session(&self) -> Option<&Session> {
+        None
+    }
+    /// `get_store_sequence_lock` returns the lock that serializes the commands that advance the store sequence,
+    /// which holds the sequence that waits to be finalized
+    fn get_store_sequence_lock(&self) -> Option<&Mutex<Option<u64>>> {
+        None
+    }
+    /// `get_attestation_cache` returns the cache of the signers whose attestation has been verified
+    fn get_attestation_cache(&self) -> Option<&AttestationCache> {
+        None
+    }
+    /// `recreate` destroys the enclave and creates it again from the same binary
+    fn recreate(&self) -> Result<()>;
+    /// `destroy_enclave` destroys the enclave, after which every ecall fails
+    fn destroy_enclave(&self) -> Result<()>;
 }
 
 impl<S: CommitStore> EnclaveInfo for Enclave<S> {
     /// `get_eid` returns the enclave id
+    ///
+    /// The id of a destroyed enclave is 0, which no enclave has.
     fn get_eid(&self) -> sgx_enclave_id_t {
-        self.sgx_enclave.geteid()
+        self.sgx_enclave
+            .read()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |enclave| enclave.geteid())
     }
     /// `metadata` returns the metadata of the enclave
     fn metadata(&self) -> SgxResult<metadata_t> {
@@ -72,6 +197,76 @@ impl<S: CommitStore> EnclaveInfo for Enclave<S> {
     fn get_key_manager(&self) -> &EnclaveKeyManager {
         &self.key_manager
     }
+    /// `get_operator_approver` returns the approver of mutating commands if configured
+    fn get_operator_approver(&self) -> Option<&dyn OperatorApprover> {
+        self.operator_approver.as_deref()
+    }
+    /// `get_command_log` returns the log of the executed commands if configured
+    fn get_command_log(&self) -> Option<&CommandLog> {
+        self.command_log.as_ref()
+    }
+    /// `get_watchdog` returns the watchdog that tracks the ecalls if configured
+    fn get_watchdog(&self) -> Option<&Watchdog> {
+        self.watchdog.as_ref()
+    }
+    /// `get_shutdown_gate` returns the gate that rejects the commands during a shutdown
+    fn get_shutdown_gate(&self) -> Option<&ShutdownGate> {
+        Some(&self.shutdown_gate)
+    }
+    /// `get_command_events` returns the subscribers to the events of the committed commands
+    fn get_command_events(&self) -> Option<&CommandEvents> {
+        Some(&self.command_events)
+    }
+    /// `get_session` returns the session with the enclave
+    fn get_session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+    /// `get_store_sequence_lock` returns the lock that serializes the commands that advance the store sequence,
+    /// which holds the sequence that waits to be finalized
+    fn get_store_sequence_lock(&self) -> Option<&Mutex<Option<u64>>> {
+        Some(&self.store_sequence_lock)
+    }
+    /// `get_attestation_cache` returns the cache of the signers whose attestation has been verified
+    fn get_attestation_cache(&self) -> Option<&AttestationCache> {
+        Some(&self.attestation_cache)
+    }
+    /// `recreate` creates the enclave again from the same binary, and destroys the old one in the background
+    ///
+    /// The new enclave is checked with the handshake before it replaces the old one.
+    /// The callers of the ecalls in flight into the old enclave fail without waiting for them if the watchdog is configured.
+    /// The session ends with the old enclave, so it must be opened again.
+    fn recreate(&self) -> Result<()> {
+        if self.shutdown_gate.is_sealed() {
+            return Err(Error::shutting_down());
+        }
+        let enclave = launch(&self.path, self.debug)?;
+        if let Some(session) = self.session.as_ref() {
+            session.clear_token();
+        }
+        self.attestation_cache.clear();
+        let swap = || std::mem::replace(&mut *self.sgx_enclave.write().unwrap(), Some(enclave));
+        let old = match self.watchdog.as_ref() {
+            Some(watchdog) => watchdog.recreated(swap),
+            None => swap(),
+        };
+        if let Some(old) = old {
+            // the destruction waits for the hung ecalls to return, which may never happen
+            std::thread::spawn(move || {
+                old.destroy();
+                info!("destroyed the old enclave after its ecalls returned");
+            });
+        }
+        Ok(())
+    }
+    /// `destroy_enclave` destroys the enclave, after which every ecall fails
+    ///
+    /// The enclave is not re-created by `recreate` once the shutdown gate is sealed.
+    fn destroy_enclave(&self) -> Result<()> {
+        if let Some(enclave) = self.sgx_enclave.write().unwrap().take() {
+            enclave.destroy();
+        }
+        Ok(())
+    }
 }
 
 /// `HostStoreTxManager` is a transaction manager for the host store
@@ -85,20 +280,56 @@ pub trait HostStoreTxManager<S: CommitStore>: CommitStoreAccessor<S> {
     }
 
     /// `commit_tx` commits the changes in the transaction
+    ///
+    /// The result is recorded in the circuit breaker if it is configured.
     fn commit_tx(&self, tx: <S::Tx as CreatedTx>::PreparedTx) -> Result<()> {
-        self.use_mut_store(|store| store.commit(tx))?;
-        Ok(())
+        let res = self.use_mut_store(|store| store.commit(tx));
+        if let Some(breaker) = self.get_circuit_breaker() {
+            match &res {
+                Ok(()) => breaker.record_success(),
+                Err(e) => breaker.record_failure(e),
+            }
+        }
+        Ok(res?)
     }
 
     /// `rollback_tx` rollbacks the changes in the transaction
     fn rollback_tx(&self, tx: <S::Tx as CreatedTx>::PreparedTx) {
         self.use_mut_store(|store| store.rollback(tx));
     }
+
+    /// `tx_get` returns a value corresponding to `key` in the transaction
+    fn tx_get(&self, tx: &<S::Tx as CreatedTx>::PreparedTx, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.use_host_store(|store| store.tx_get(tx.get_id(), key))?)
+    }
+
+    /// `tx_set` sets key-value pair in the transaction
+    fn tx_set(
+        &self,
+        tx: &<S::Tx as CreatedTx>::PreparedTx,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        Ok(self.use_host_store(|store| store.tx_set(tx.get_id(), key, value))?)
+    }
+
+    /// `tx_remove` removes key-value pair corresponding to `key` in the transaction
+    fn tx_remove(&self, tx: &<S::Tx as CreatedTx>::PreparedTx, key: &[u8]) -> Result<()> {
+        Ok(self.use_host_store(|store| store.tx_remove(tx.get_id(), key))?)
+    }
 }
 
 /// `CommitStoreAccessor` is an accessor to the host store
 pub trait CommitStoreAccessor<S: CommitStore> {
     fn use_mut_store<T>(&self, f: impl FnOnce(&mut S) -> T) -> T;
+
+    /// `use_host_store` gives access to the transactions that have been begun in the store
+    fn use_host_store<T>(&self, f: impl FnOnce(&mut HostStore) -> T) -> T;
+
+    /// `get_circuit_breaker` returns the circuit breaker that guards the mutating commands if configured
+    fn get_circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        None
+    }
 }
 
 impl<S> CommitStoreAccessor<S> for Enclave<S>
@@ -110,4 +341,12 @@ where
         let mut store = self.store.write().unwrap();
         store.deref_mut().apply(f)
     }
+
+    fn use_host_store<T>(&self, f: impl FnOnce(&mut HostStore) -> T) -> T {
+        f(self.store.write().unwrap().deref_mut())
+    }
+
+    fn get_circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
 }
@@ -1,4 +1,6 @@
+use ecall_commands::ErrorCode;
 use flex_error::*;
+use lcp_types::Height;
 use sgx_types::sgx_status_t;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,20 +23,101 @@ define_error! {
             format_args!("SGX error: {:?}", e.status)
         },
 
-        BincodeEncode
-        [TraceError<bincode::error::EncodeError>]
-        |_| { "bincode encode error" },
+        EnclaveLaunch
+        {
+            status: sgx_status_t,
+            descr: String
+        }
+        |e| {
+            format_args!("failed to launch the enclave: status={:?} descr={}", e.status, e.descr)
+        },
+
+        ProtoDecode
+        [TraceError<prost::DecodeError>]
+        |_| { "protobuf decode error" },
 
-        BincodeDecode
-        [TraceError<bincode::error::DecodeError>]
-        |_| { "bincode decode error" },
+        ProofVerification {
+            descr: String
+        }
+        |e| {
+            format_args!("proof verification error: descr={}", e.descr)
+        },
 
         Command {
             status: sgx_status_t,
+            code: ErrorCode,
             descr: String
         }
         |e| {
-            format_args!("Command error: status={:?} descr={}", e.status, e.descr)
+            format_args!("Command error: status={:?} code={} hint=\"{}\" descr={}", e.status, e.code, e.code.remediation(), e.descr)
+        },
+
+        Protocol
+        [ecall_commands::ProtocolError]
+        |_| { "host-enclave protocol error" },
+
+        RejectedCommand {
+            status: sgx_status_t
+        }
+        |e| {
+            format_args!("the enclave rejected the command frame: status={:?}", e.status)
+        },
+
+        CircuitOpen {
+            reason: String
+        }
+        |e| {
+            format_args!("the circuit breaker rejected the mutating command; repair the store and reset the breaker: reason={}", e.reason)
+        },
+
+        ShuttingDown
+        |_| { "the enclave is shutting down and rejects the command" },
+
+        EnclaveRecreated
+        {
+            replayed: bool
+        }
+        |e| {
+            format_args!("the enclave was re-created while the command was in flight, so the command was not committed: replayed={}", e.replayed)
+        },
+
+        RevokedEnclaveKey {
+            address: String,
+            reason: String
+        }
+        |e| {
+            format_args!("the enclave key has been revoked and must not sign anymore: address={} reason={}", e.address, e.reason)
+        },
+
+        BisectionLimitExceeded {
+            target_height: Height,
+            limit: usize
+        }
+        |e| {
+            format_args!("the bisection didn't reach the target height within the limit of updates: target_height={} limit={}", e.target_height, e.limit)
+        },
+
+        PrunedUpdateRecord {
+            client_id: String,
+            first_seq: u64,
+            descr: String
+        }
+        |e| {
+            format_args!("the update record has been pruned by the retention policy of the client: client_id={} first_seq={} descr={}", e.client_id, e.first_seq, e.descr)
+        },
+
+        OperatorApproval {
+            descr: String
+        }
+        |e| {
+            format_args!("failed to collect the operator approvals: descr={}", e.descr)
+        },
+
+        InvalidStoreCheckpoint {
+            descr: String
+        }
+        |e| {
+            format_args!("the enclave returned an invalid store checkpoint: descr={}", e.descr)
         },
 
         EcallCommand
@@ -56,6 +139,14 @@ define_error! {
         Commitments
         [commitments::Error]
         |_| { "Commitments error" },
+
+        Crypto
+        [crypto::Error]
+        |_| { "Crypto error" },
+
+        Io
+        [TraceError<std::io::Error>]
+        |_| { "I/O error" },
     }
 }
 
@@ -71,6 +162,12 @@ impl From<ecall_commands::InputValidationError> for Error {
     }
 }
 
+impl From<ecall_commands::ProtocolError> for Error {
+    fn from(err: ecall_commands::ProtocolError) -> Self {
+        Error::protocol(err)
+    }
+}
+
 impl From<store::Error> for Error {
     fn from(err: store::Error) -> Self {
         Error::store(err)
@@ -94,3 +191,74 @@ impl From<commitments::Error> for Error {
         Error::commitments(err)
     }
 }
+
+impl From<crypto::Error> for Error {
+    fn from(err: crypto::Error) -> Self {
+        Error::crypto(err)
+    }
+}
+
+impl Error {
+    /// Returns the error code reported by the enclave, if the error came from a command execution
+    pub fn command_error_code(&self) -> Option<ErrorCode> {
+        match self.detail() {
+            ErrorDetail::Command(e) => Some(e.code),
+            _ => None,
+        }
+    }
+    /// Returns the intermediate heights suggested by the enclave if the update requires a bisection
+    pub fn suggested_heights(&self) -> Option<Vec<Height>> {
+        match self.detail() {
+            ErrorDetail::Command(e) if e.code == ErrorCode::BisectionRequired => {
+                Some(parse_suggested_heights(&e.descr))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses the heights that the light client formats as `suggested_heights=1-50,1-25` in the description
+fn parse_suggested_heights(descr: &str) -> Vec<Height> {
+    const KEY: &str = "suggested_heights=";
+    let start = match descr.find(KEY) {
+        Some(i) => i + KEY.len(),
+        None => return vec![],
+    };
+    descr[start..]
+        .split(|c: char| !(c.is_ascii_digit() || c == '-' || c == ','))
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|h| {
+            let (number, height) = h.split_once('-')?;
+            Some(Height::new(number.parse().ok()?, height.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggested_heights() {
+        let err = Error::command(
+            sgx_status_t::SGX_ERROR_UNEXPECTED,
+            ErrorCode::BisectionRequired,
+            "LightClient(\"the validator set changed beyond the trust level: trusted_height=1-10 target_height=1-100 suggested_heights=1-55,1-32\")".into(),
+        );
+        assert_eq!(
+            err.suggested_heights(),
+            Some(vec![Height::new(1, 55), Height::new(1, 32)])
+        );
+
+        let err = Error::command(
+            sgx_status_t::SGX_ERROR_UNEXPECTED,
+            ErrorCode::InvalidValidatorSet,
+            "suggested_heights=1-55".into(),
+        );
+        assert_eq!(err.suggested_heights(), None);
+        assert!(parse_suggested_heights("suggested_heights=").is_empty());
+        assert!(parse_suggested_heights("no heights").is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+use ecall_commands::CommandEvent;
+use std::sync::RwLock;
+
+type Subscriber = Box<dyn Fn(&CommandEvent) + Send + Sync>;
+
+/// `CommandEvents` delivers the events of the committed commands to the subscribers
+///
+/// The subscribers are called synchronously in the thread that executed the command,
+/// so they should hand the events off instead of blocking on them.
+#[derive(Default)]
+pub struct CommandEvents {
+    subscribers: RwLock<Vec<Subscriber>>,
+}
+
+impl CommandEvents {
+    pub fn subscribe(&self, subscriber: impl Fn(&CommandEvent) + Send + Sync + 'static) {
+        self.subscribers.write().unwrap().push(Box::new(subscriber));
+    }
+
+    pub(crate) fn publish(&self, events: &[CommandEvent]) {
+        let subscribers = self.subscribers.read().unwrap();
+        for event in events {
+            for subscriber in subscribers.iter() {
+                subscriber(event);
+            }
+        }
+    }
+}
+
+impl core::fmt::Debug for CommandEvents {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CommandEvents")
+            .field("subscribers", &self.subscribers.read().unwrap().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lcp_types::{ClientId, Height};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_command_events() {
+        let events = CommandEvents::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        for _ in 0..2 {
+            let received = received.clone();
+            events.subscribe(move |event| received.lock().unwrap().push(event.clone()));
+        }
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let published = vec![
+            CommandEvent::ClientUpdated {
+                client_id: client_id.clone(),
+                prev_height: Some(Height::new(0, 1)),
+                post_height: Height::new(0, 2),
+            },
+            CommandEvent::ClientFrozen {
+                client_id,
+                frozen_height: None,
+            },
+        ];
+        events.publish(&published);
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 4);
+        assert_eq!(received[0], published[0]);
+        assert_eq!(received[1], published[0]);
+        assert_eq!(received[3], published[1]);
+    }
+}
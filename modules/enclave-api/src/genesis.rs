@@ -0,0 +1,267 @@
+use crate::{registration_evidence, EnclaveCommandAPI, Error, Result};
+use attestation_report::EndorsedAttestationVerificationReport;
+use commitments::{CommitmentProof, UpdateStateProxyMessage};
+use crypto::Address;
+use ecall_commands::OperatorSet;
+use lcp_proto::google::protobuf::Any;
+use lcp_proto::ibc::lightclients::lcp::v1::{ClientState, ConsensusState};
+use lcp_types::{ClientId, Mrenclave};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use store::transaction::CommitStore;
+
+pub const LCP_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.lcp.v1.ClientState";
+pub const LCP_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.lcp.v1.ConsensusState";
+
+/// GenesisParams are the parameters of the LCP client that the downstream chain decides
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenesisParams {
+    /// Seconds after the attestation time until which an enclave key is accepted
+    pub key_expiration: u64,
+    pub allowed_quote_statuses: Vec<String>,
+    pub allowed_advisory_ids: Vec<String>,
+    /// Must match the digest algorithm of the ELC client. Empty means `keccak256`
+    pub digest_algorithm: String,
+    /// Must match the signature format of the ELC client. Empty means `recoverable`
+    pub signature_format: String,
+}
+
+/// GenesisDocument is everything a downstream chain needs to instantiate the LCP client of an ELC client
+///
+/// The latest state of the ELC client is signed by an attested enclave key, so the chain can check
+/// the states in the document against the proof and the report of the key before it instantiates the client.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisDocument {
+    pub client_id: String,
+    /// hex-encoded MRENCLAVE
+    pub mrenclave: String,
+    pub latest_height: GenesisHeight,
+    /// hex-encoded ID of the latest state of the ELC client, which is the digest of its client and consensus states
+    pub state_id: String,
+    /// UNIX time in seconds of the latest state
+    pub timestamp: u64,
+    pub key_expiration: u64,
+    pub allowed_quote_statuses: Vec<String>,
+    pub allowed_advisory_ids: Vec<String>,
+    pub digest_algorithm: String,
+    pub signature_format: String,
+    /// None if the operator approvals are not required
+    pub operators: Option<GenesisOperators>,
+    /// hex-encoded protobuf `Any` of the LCP client state
+    pub client_state: String,
+    /// hex-encoded protobuf `Any` of the LCP consensus state
+    pub consensus_state: String,
+    pub proof: GenesisProof,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisHeight {
+    pub revision_number: u64,
+    pub revision_height: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisOperators {
+    /// hex-encoded addresses of the operators
+    pub operators: Vec<String>,
+    pub threshold: u64,
+    pub nonce: u64,
+}
+
+/// GenesisProof is the signature of an enclave key over the latest state and the report of the key
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisProof {
+    /// hex-encoded `UpdateStateProxyMessage` of the latest state
+    pub message: String,
+    pub signer: String,
+    pub signature: String,
+    pub report: String,
+    pub report_signature: String,
+    pub report_signing_cert: String,
+}
+
+impl GenesisDocument {
+    /// Build the document from the proof of the latest state and the attestation report of its signer
+    pub fn new(
+        client_id: &ClientId,
+        mrenclave: Mrenclave,
+        proof: &CommitmentProof,
+        evidence: EndorsedAttestationVerificationReport,
+        operators: Option<OperatorSet>,
+        params: GenesisParams,
+    ) -> Result<Self> {
+        let msg: UpdateStateProxyMessage = proof.message()?.try_into()?;
+        let client_state = ClientState {
+            mrenclave: mrenclave.to_vec(),
+            key_expiration: params.key_expiration,
+            frozen: msg.frozen_height.is_some(),
+            latest_height: Some(msg.post_height.into()),
+            allowed_quote_statuses: params.allowed_quote_statuses.clone(),
+            allowed_advisory_ids: params.allowed_advisory_ids.clone(),
+            digest_algorithm: params.digest_algorithm.clone(),
+            signature_format: params.signature_format.clone(),
+        };
+        if client_state.frozen {
+            return Err(Error::invalid_argument(format!(
+                "the client is frozen, so it cannot be instantiated: client_id={}",
+                client_id
+            )));
+        }
+        let consensus_state = ConsensusState {
+            state_id: msg.post_state_id.to_vec(),
+            timestamp: msg.timestamp.as_unix_timestamp_secs(),
+        };
+        Ok(Self {
+            client_id: client_id.to_string(),
+            mrenclave: hex::encode(mrenclave.to_vec()),
+            latest_height: GenesisHeight {
+                revision_number: msg.post_height.revision_number(),
+                revision_height: msg.post_height.revision_height(),
+            },
+            state_id: hex::encode(msg.post_state_id.to_vec()),
+            timestamp: consensus_state.timestamp,
+            key_expiration: params.key_expiration,
+            allowed_quote_statuses: params.allowed_quote_statuses,
+            allowed_advisory_ids: params.allowed_advisory_ids,
+            digest_algorithm: params.digest_algorithm,
+            signature_format: params.signature_format,
+            operators: operators.map(|set| GenesisOperators {
+                operators: set.operators.iter().map(Address::to_hex_string).collect(),
+                threshold: set.threshold,
+                nonce: set.nonce,
+            }),
+            client_state: hex::encode(
+                Any {
+                    type_url: LCP_CLIENT_STATE_TYPE_URL.to_string(),
+                    value: client_state.encode_to_vec(),
+                }
+                .encode_to_vec(),
+            ),
+            consensus_state: hex::encode(
+                Any {
+                    type_url: LCP_CONSENSUS_STATE_TYPE_URL.to_string(),
+                    value: consensus_state.encode_to_vec(),
+                }
+                .encode_to_vec(),
+            ),
+            proof: GenesisProof {
+                message: hex::encode(&proof.message),
+                signer: proof.signer.to_hex_string(),
+                signature: hex::encode(&proof.signature),
+                report: evidence.avr,
+                report_signature: hex::encode(evidence.signature),
+                report_signing_cert: hex::encode(evidence.signing_cert),
+            },
+        })
+    }
+}
+
+/// Export the genesis document of the client, whose latest state is signed again with `signer`
+///
+/// `signer` must be attested with IAS, as the LCP client only accepts AVRs as the reports of the keys.
+pub fn export_genesis<S, E>(
+    enclave: &E,
+    client_id: ClientId,
+    signer: Address,
+    params: GenesisParams,
+) -> Result<GenesisDocument>
+where
+    S: CommitStore,
+    E: EnclaveCommandAPI<S>,
+{
+    let evidence = registration_evidence(enclave, signer)?;
+    let mrenclave: Mrenclave = enclave.metadata()?.enclave_css.body.enclave_hash.m.into();
+    let operators = enclave.query_operators()?.operator_set;
+    let res = enclave.resign_latest_state(client_id.clone(), signer)?;
+    GenesisDocument::new(&client_id, mrenclave, &res.0, evidence, operators, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::{ProxyMessage, StateID, ValidationContext};
+    use lcp_types::{Height, Time};
+    use std::str::FromStr;
+
+    fn proof(frozen: bool) -> CommitmentProof {
+        let height = Height::new(1, 100);
+        let msg = UpdateStateProxyMessage {
+            prev_height: Some(height),
+            prev_state_id: Some(StateID::from([1; 32])),
+            post_height: height,
+            post_state_id: StateID::from([1; 32]),
+            frozen_height: frozen.then_some(height),
+            timestamp: Time::from_unix_timestamp_nanos(1_700_000_000_000_000_000).unwrap(),
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: vec![],
+        };
+        CommitmentProof::new(
+            ProxyMessage::from(msg).to_bytes(),
+            Address([2; 20]),
+            vec![3; 65],
+        )
+    }
+
+    fn eavr() -> EndorsedAttestationVerificationReport {
+        EndorsedAttestationVerificationReport {
+            avr: "{}".into(),
+            signature: vec![4; 4],
+            signing_cert: vec![5; 4],
+        }
+    }
+
+    #[test]
+    fn test_genesis_document() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let params = GenesisParams {
+            key_expiration: 86400,
+            allowed_advisory_ids: vec!["INTEL-SA-00334".into()],
+            ..Default::default()
+        };
+        let operators = OperatorSet {
+            operators: vec![Address([6; 20])],
+            threshold: 1,
+            nonce: 1,
+        };
+        let doc = GenesisDocument::new(
+            &client_id,
+            Mrenclave([7; 32]),
+            &proof(false),
+            eavr(),
+            Some(operators),
+            params.clone(),
+        )
+        .unwrap();
+        assert_eq!(doc.client_id, "07-tendermint-0");
+        assert_eq!(doc.latest_height.revision_height, 100);
+        assert_eq!(doc.state_id, hex::encode([1; 32]));
+        assert_eq!(doc.timestamp, 1_700_000_000);
+        assert_eq!(doc.operators.as_ref().unwrap().threshold, 1);
+
+        let any = Any::decode(hex::decode(&doc.client_state).unwrap().as_slice()).unwrap();
+        assert_eq!(any.type_url, LCP_CLIENT_STATE_TYPE_URL);
+        let client_state = ClientState::decode(any.value.as_slice()).unwrap();
+        assert_eq!(client_state.mrenclave, vec![7; 32]);
+        assert_eq!(client_state.key_expiration, 86400);
+        assert_eq!(
+            client_state.allowed_advisory_ids,
+            params.allowed_advisory_ids
+        );
+        let any = Any::decode(hex::decode(&doc.consensus_state).unwrap().as_slice()).unwrap();
+        assert_eq!(any.type_url, LCP_CONSENSUS_STATE_TYPE_URL);
+        let consensus_state = ConsensusState::decode(any.value.as_slice()).unwrap();
+        assert_eq!(consensus_state.state_id, vec![1; 32]);
+
+        // a frozen client cannot be instantiated on the downstream chain
+        assert!(GenesisDocument::new(
+            &client_id,
+            Mrenclave([7; 32]),
+            &proof(true),
+            eavr(),
+            None,
+            params,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// The default number of consecutive commit failures that trips the circuit breaker
+pub const DEFAULT_MAX_COMMIT_FAILURES: u32 = 3;
+
+/// HealthStatus is the health of the host store as seen by the transaction manager
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The last commit succeeded
+    Healthy,
+    /// Some of the latest commits failed, but the threshold is not reached yet
+    Degraded { consecutive_failures: u32 },
+    /// The circuit breaker is tripped and mutating commands are rejected until it is reset
+    Critical { reason: String },
+}
+
+impl HealthStatus {
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Self::Critical { .. })
+    }
+}
+
+impl Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Healthy => write!(f, "healthy"),
+            Self::Degraded {
+                consecutive_failures,
+            } => write!(f, "degraded: consecutive_failures={}", consecutive_failures),
+            Self::Critical { reason } => write!(f, "critical: {}", reason),
+        }
+    }
+}
+
+/// `FailoverHook` is invoked once when the circuit breaker trips
+///
+/// Operators can use it to page someone, fence the node or promote a standby instance.
+pub trait FailoverHook: Sync + Send {
+    fn on_trip(&self, status: &HealthStatus);
+}
+
+impl<F: Fn(&HealthStatus) + Sync + Send> FailoverHook for F {
+    fn on_trip(&self, status: &HealthStatus) {
+        self(status)
+    }
+}
+
+/// CircuitBreaker halts the mutating commands when the host store cannot be trusted to follow the enclave
+///
+/// It trips when the commits fail `max_failures` times in a row, or immediately when the store diverges
+/// from the enclave, i.e. the enclave has executed a command whose changes the store failed to persist.
+pub struct CircuitBreaker {
+    max_failures: u32,
+    status: Mutex<HealthStatus>,
+    hooks: Vec<Box<dyn FailoverHook>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_COMMIT_FAILURES)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(max_failures: u32) -> Self {
+        Self {
+            max_failures: max_failures.max(1),
+            status: Mutex::new(HealthStatus::Healthy),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Add a hook that is invoked when the breaker trips
+    pub fn with_hook(mut self, hook: Box<dyn FailoverHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Returns the current health status
+    pub fn status(&self) -> HealthStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Returns the reason of the trip if the breaker is tripped
+    pub fn tripped(&self) -> Option<String> {
+        match &*self.status.lock().unwrap() {
+            HealthStatus::Critical { reason } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    /// Record a successful commit
+    ///
+    /// A tripped breaker stays tripped, since a later success does not undo a divergence.
+    pub fn record_success(&self) {
+        let mut status = self.status.lock().unwrap();
+        if !status.is_critical() {
+            *status = HealthStatus::Healthy;
+        }
+    }
+
+    /// Record a failed commit, and trip the breaker if the failures reach the threshold
+    pub fn record_failure(&self, descr: impl Display) {
+        let mut status = self.status.lock().unwrap();
+        let consecutive_failures = match &*status {
+            HealthStatus::Critical { .. } => return,
+            HealthStatus::Healthy => 1,
+            HealthStatus::Degraded {
+                consecutive_failures,
+            } => consecutive_failures + 1,
+        };
+        if consecutive_failures < self.max_failures {
+            *status = HealthStatus::Degraded {
+                consecutive_failures,
+            };
+            return;
+        }
+        *status = HealthStatus::Critical {
+            reason: format!(
+                "{} consecutive commits failed: last_err={}",
+                consecutive_failures, descr
+            ),
+        };
+        let tripped = status.clone();
+        drop(status);
+        self.trip(&tripped);
+    }
+
+    /// Record that the store diverged from the enclave, which trips the breaker immediately
+    pub fn record_divergence(&self, descr: impl Display) {
+        let mut status = self.status.lock().unwrap();
+        if status.is_critical() {
+            return;
+        }
+        *status = HealthStatus::Critical {
+            reason: format!("the store diverged from the enclave: {}", descr),
+        };
+        let tripped = status.clone();
+        drop(status);
+        self.trip(&tripped);
+    }
+
+    /// Reset the breaker after the operator has repaired the store
+    pub fn reset(&self) {
+        *self.status.lock().unwrap() = HealthStatus::Healthy;
+    }
+
+    /// Invoke the hooks after the lock of the status is released, so that they can read the status
+    fn trip(&self, status: &HealthStatus) {
+        log::error!("circuit breaker tripped: {}", status);
+        for hook in self.hooks.iter() {
+            hook.on_trip(status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let trips = Arc::new(AtomicU32::new(0));
+        let counter = trips.clone();
+        let breaker = CircuitBreaker::new(2).with_hook(Box::new(move |_: &HealthStatus| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        breaker.record_failure("err");
+        assert_eq!(
+            breaker.status(),
+            HealthStatus::Degraded {
+                consecutive_failures: 1
+            }
+        );
+        breaker.record_success();
+        assert_eq!(breaker.status(), HealthStatus::Healthy);
+
+        breaker.record_failure("err");
+        breaker.record_failure("err");
+        assert!(breaker.tripped().is_some());
+        breaker.record_success();
+        breaker.record_failure("err");
+        assert!(breaker.status().is_critical());
+        assert_eq!(trips.load(Ordering::SeqCst), 1);
+
+        breaker.reset();
+        assert_eq!(breaker.status(), HealthStatus::Healthy);
+        breaker.record_divergence("commit failed");
+        assert!(breaker.tripped().is_some());
+        assert_eq!(trips.load(Ordering::SeqCst), 2);
+    }
+}
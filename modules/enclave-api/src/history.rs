@@ -0,0 +1,341 @@
+use crate::enclave::HostStoreTxManager;
+use crate::{Error, Result};
+use core::str::FromStr;
+use crypto::Keccak256;
+use ecall_commands::{
+    encode_command_message, encode_response_message, Command, CommandResponse, LightClientCommand,
+    LightClientExecuteCommand, LightClientResponse,
+};
+use lcp_proto::lcp::ecall::v1::CommandRecord as ProtoCommandRecord;
+use lcp_types::{ClientId, Time};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use store::transaction::{CommitStore, CreatedTx};
+
+/// The maximum number of records kept in the command history. Older records are pruned.
+pub const COMMAND_HISTORY_LIMIT: u64 = 1024;
+
+const COMMAND_HISTORY_UPDATE_KEY: &str = "lcp/command_history";
+const COMMAND_HISTORY_LATEST_KEY: &[u8] = b"lcp/command_history/latest";
+
+/// `CommandRecord` is a record of a light client command executed by the enclave
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    pub seq: u64,
+    pub timestamp: Time,
+    pub command_type: String,
+    pub client_id: Option<ClientId>,
+    /// keccak256 of the command encoded with `ecall_commands::encode_command_message`
+    pub input_digest: [u8; 32],
+    /// keccak256 of the response encoded with `ecall_commands::encode_response_message`, which contains the signed commitment
+    pub result_digest: [u8; 32],
+    /// keccak256 of the stored client state before the execution
+    ///
+    /// The host store is not merkleized, so the client state stands in for its root.
+    pub pre_state_digest: Option<[u8; 32]>,
+    /// keccak256 of the stored client state after the execution
+    pub post_state_digest: Option<[u8; 32]>,
+}
+
+impl CommandRecord {
+    pub(crate) fn to_proto(&self) -> Result<ProtoCommandRecord> {
+        Ok(ProtoCommandRecord {
+            seq: self.seq,
+            timestamp: encode_time(self.timestamp)?,
+            command_type: self.command_type.clone(),
+            client_id: self.client_id.as_ref().map(|id| id.to_string()),
+            input_digest: self.input_digest.to_vec(),
+            result_digest: self.result_digest.to_vec(),
+            pre_state_digest: self.pre_state_digest.map(|d| d.to_vec()),
+            post_state_digest: self.post_state_digest.map(|d| d.to_vec()),
+        })
+    }
+
+    pub(crate) fn from_proto(msg: ProtoCommandRecord) -> Result<Self> {
+        Ok(Self {
+            seq: msg.seq,
+            timestamp: decode_time(msg.timestamp)?,
+            command_type: msg.command_type,
+            client_id: msg
+                .client_id
+                .map(|id| ClientId::from_str(&id))
+                .transpose()
+                .map_err(|e| Error::invalid_argument(format!("invalid client_id: {}", e)))?,
+            input_digest: decode_digest(&msg.input_digest)?,
+            result_digest: decode_digest(&msg.result_digest)?,
+            pre_state_digest: msg
+                .pre_state_digest
+                .map(|d| decode_digest(&d))
+                .transpose()?,
+            post_state_digest: msg
+                .post_state_digest
+                .map(|d| decode_digest(&d))
+                .transpose()?,
+        })
+    }
+}
+
+/// `PendingRecord` holds the fields of a record that are known before the execution
+pub(crate) struct PendingRecord {
+    timestamp: Time,
+    command_type: &'static str,
+    client_id: Option<ClientId>,
+    input_digest: [u8; 32],
+    pre_state_digest: Option<[u8; 32]>,
+}
+
+impl PendingRecord {
+    /// Returns a pending record if the command should be recorded in the history
+    pub(crate) fn new<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+        store: &M,
+        tx: &<S::Tx as CreatedTx>::PreparedTx,
+        timestamp: Time,
+        cmd: &Command,
+    ) -> Result<Option<Self>> {
+        let (command_type, client_id) = match cmd {
+            Command::LightClient(LightClientCommand::Execute(cmd)) => match cmd {
+                LightClientExecuteCommand::InitClient(_) => ("init_client", None),
+                LightClientExecuteCommand::UpdateClient(input) => {
+                    ("update_client", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::AggregateMessages(_) => ("aggregate_messages", None),
+                LightClientExecuteCommand::VerifyMembership(input) => {
+                    ("verify_membership", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::VerifyNonMembership(input) => {
+                    ("verify_non_membership", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::VerifyMembershipBatch(input) => {
+                    ("verify_membership_batch", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::VerifyKeyValue(input) => {
+                    ("verify_key_value", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::PruneClient(input) => {
+                    ("prune_client", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::SignLatestHeights(_) => ("sign_latest_heights", None),
+                LightClientExecuteCommand::UpdateClientParams(input) => {
+                    ("update_client_params", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::ResignLatestState(input) => {
+                    ("resign_latest_state", Some(input.client_id.clone()))
+                }
+                LightClientExecuteCommand::MigrateClient(input) => {
+                    ("migrate_client", Some(input.client_id.clone()))
+                }
+            },
+            _ => return Ok(None),
+        };
+        let pre_state_digest = match client_id.as_ref() {
+            Some(client_id) => client_state_digest(store, tx, client_id)?,
+            None => None,
+        };
+        Ok(Some(Self {
+            timestamp,
+            command_type,
+            client_id,
+            input_digest: encode_command_message(cmd)?.keccak256(),
+            pre_state_digest,
+        }))
+    }
+
+    /// Complete the record with the response. This must be called before the transaction is committed.
+    pub(crate) fn finish<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+        self,
+        store: &M,
+        tx: &<S::Tx as CreatedTx>::PreparedTx,
+        res: &CommandResponse,
+    ) -> Result<CommandRecord> {
+        let client_id = match res {
+            CommandResponse::LightClient(LightClientResponse::InitClient(res)) => {
+                Some(res.client_id.clone())
+            }
+            _ => self.client_id,
+        };
+        let post_state_digest = match client_id.as_ref() {
+            Some(client_id) => client_state_digest(store, tx, client_id)?,
+            None => None,
+        };
+        Ok(CommandRecord {
+            seq: 0,
+            timestamp: self.timestamp,
+            command_type: self.command_type.to_string(),
+            client_id,
+            input_digest: self.input_digest,
+            result_digest: encode_response_message(res).keccak256(),
+            pre_state_digest: self.pre_state_digest,
+            post_state_digest,
+        })
+    }
+}
+
+/// Append the record to the history and prune the records exceeding `COMMAND_HISTORY_LIMIT`
+pub(crate) fn append_record<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    mut record: CommandRecord,
+) -> Result<u64> {
+    let tx = store.begin_tx(Some(COMMAND_HISTORY_UPDATE_KEY.to_string()))?;
+    let res = (|| -> Result<u64> {
+        let seq = match latest_seq(store, &tx)? {
+            Some(seq) => seq + 1,
+            None => 0,
+        };
+        record.seq = seq;
+        store.tx_set(&tx, record_key(seq), record.to_proto()?.encode_to_vec())?;
+        store.tx_set(
+            &tx,
+            COMMAND_HISTORY_LATEST_KEY.to_vec(),
+            seq.to_be_bytes().to_vec(),
+        )?;
+        if seq >= COMMAND_HISTORY_LIMIT {
+            store.tx_remove(&tx, &record_key(seq - COMMAND_HISTORY_LIMIT))?;
+        }
+        Ok(seq)
+    })();
+    match res {
+        Ok(seq) => {
+            store.commit_tx(tx)?;
+            Ok(seq)
+        }
+        Err(e) => {
+            store.rollback_tx(tx);
+            Err(e)
+        }
+    }
+}
+
+/// Returns the latest `limit` records in descending order of `seq`
+pub(crate) fn latest_records<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    limit: u64,
+) -> Result<Vec<CommandRecord>> {
+    let tx = store.begin_tx(None)?;
+    let res = (|| -> Result<Vec<CommandRecord>> {
+        let mut records = Vec::new();
+        if let Some(latest) = latest_seq(store, &tx)? {
+            let limit = limit.min(COMMAND_HISTORY_LIMIT).min(latest + 1);
+            for seq in (latest + 1 - limit..=latest).rev() {
+                if let Some(bz) = store.tx_get(&tx, &record_key(seq))? {
+                    records.push(CommandRecord::from_proto(
+                        ProtoCommandRecord::decode(bz.as_slice()).map_err(Error::proto_decode)?,
+                    )?);
+                }
+            }
+        }
+        Ok(records)
+    })();
+    store.rollback_tx(tx);
+    res
+}
+
+fn latest_seq<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+) -> Result<Option<u64>> {
+    match store.tx_get(tx, COMMAND_HISTORY_LATEST_KEY)? {
+        Some(bz) => Ok(Some(u64::from_be_bytes(bz.try_into().map_err(|_| {
+            Error::invalid_argument("invalid latest sequence of the command history".into())
+        })?))),
+        None => Ok(None),
+    }
+}
+
+fn client_state_digest<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+) -> Result<Option<[u8; 32]>> {
+    // same key as `ClientStatePath` in the light-client crate
+    let key = format!("clients/{}/clientState", client_id);
+    Ok(store.tx_get(tx, key.as_bytes())?.map(|bz| bz.keccak256()))
+}
+
+fn record_key(seq: u64) -> Vec<u8> {
+    format!("lcp/command_history/{:020}", seq).into_bytes()
+}
+
+/// Returns the unix timestamp of the time in nanoseconds
+pub(crate) fn encode_time(time: Time) -> Result<u64> {
+    time.as_unix_timestamp_nanos()
+        .try_into()
+        .map_err(|_| Error::invalid_argument(format!("timestamp out of range: time={:?}", time)))
+}
+
+pub(crate) fn decode_time(nanos: u64) -> Result<Time> {
+    Time::from_unix_timestamp_nanos(nanos.into())
+        .map_err(|e| Error::invalid_argument(format!("invalid timestamp: {}", e)))
+}
+
+fn decode_digest(bz: &[u8]) -> Result<[u8; 32]> {
+    bz.try_into()
+        .map_err(|_| Error::invalid_argument(format!("invalid digest length: length={}", bz.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave::CommitStoreAccessor;
+    use std::ops::DerefMut;
+    use std::sync::RwLock;
+    use store::host::{HostStore, IntoCommitStore};
+    use store::memory::MemStore;
+
+    struct TestStore(RwLock<HostStore>);
+
+    impl CommitStoreAccessor<MemStore> for TestStore {
+        fn use_mut_store<T>(&self, f: impl FnOnce(&mut MemStore) -> T) -> T {
+            self.0.write().unwrap().deref_mut().apply(f)
+        }
+
+        fn use_host_store<T>(&self, f: impl FnOnce(&mut HostStore) -> T) -> T {
+            f(self.0.write().unwrap().deref_mut())
+        }
+    }
+
+    impl HostStoreTxManager<MemStore> for TestStore {}
+
+    #[test]
+    fn test_command_history() {
+        let enclave = TestStore(RwLock::new(HostStore::Memory(MemStore::default())));
+
+        assert!(latest_records::<MemStore, _>(&enclave, 10)
+            .unwrap()
+            .is_empty());
+        let record = CommandRecord {
+            seq: 0,
+            timestamp: Time::unix_epoch(),
+            command_type: "update_client".into(),
+            client_id: None,
+            input_digest: [1; 32],
+            result_digest: [2; 32],
+            pre_state_digest: None,
+            post_state_digest: Some([3; 32]),
+        };
+        assert_eq!(
+            CommandRecord::from_proto(record.to_proto().unwrap()).unwrap(),
+            record
+        );
+        for i in 0..COMMAND_HISTORY_LIMIT + 2 {
+            assert_eq!(
+                append_record::<MemStore, _>(&enclave, record.clone()).unwrap(),
+                i
+            );
+        }
+        let records = latest_records::<MemStore, _>(&enclave, 3).unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.seq).collect::<Vec<_>>(),
+            vec![
+                COMMAND_HISTORY_LIMIT + 1,
+                COMMAND_HISTORY_LIMIT,
+                COMMAND_HISTORY_LIMIT - 1
+            ]
+        );
+        assert_eq!(
+            latest_records::<MemStore, _>(&enclave, u64::MAX)
+                .unwrap()
+                .len() as u64,
+            COMMAND_HISTORY_LIMIT
+        );
+    }
+}
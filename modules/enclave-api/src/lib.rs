@@ -1,15 +1,58 @@
+pub use aggregation::{aggregate_messages_in_parallel, ENCLAVE_TCS_NUM};
 pub use api::{EnclaveCommandAPI, EnclavePrimitiveAPI, EnclaveProtoAPI};
+pub use approver::{ExternalApprover, OperatorApprover};
+pub use attestation::{query_attestations, QueryAttestationsInput, QueryAttestationsResponse};
+pub use bisection::{update_client_with_bisection, MAX_BISECTION_ATTEMPTS};
+pub use command_log::{read_command_log, CommandLog, LoggedCommand};
 pub use enclave::{Enclave, EnclaveInfo};
-use errors::{Error, Result};
+use errors::Result;
+pub use errors::{Error, ErrorDetail};
+pub use events::CommandEvents;
+pub use genesis::{
+    export_genesis, GenesisDocument, GenesisHeight, GenesisOperators, GenesisParams, GenesisProof,
+};
+pub use health::{CircuitBreaker, FailoverHook, HealthStatus, DEFAULT_MAX_COMMIT_FAILURES};
+pub use history::{CommandRecord, COMMAND_HISTORY_LIMIT};
+#[cfg(feature = "fault-injection")]
+pub use host::fault;
+pub use keymanager::{AttestationEvidence, AttestationRecord, EvidenceType};
+pub use registration::{
+    build_cosmos_registration, build_evm_registration, registration_evidence, RegistrationTarget,
+};
+pub use replay::{replay_command, ReplayOutcome};
 #[cfg(feature = "sgx-sw")]
 pub use rsa;
+pub use session::Session;
 #[cfg(feature = "sgx-sw")]
 pub use sha2;
+pub use shutdown::ShutdownGate;
+pub use update_history::{UpdateRecord, UpdateRetention, UPDATE_HISTORY_LIMIT};
+pub use verification::{verify_commitment_proof, AttestationCache, DEFAULT_ATTESTATION_CACHE_TTL};
+pub use watchdog::Watchdog;
 
+/// The update key that serializes the commands on the instance and the sequence of the store
+pub(crate) const INSTANCE_UPDATE_KEY: &str = "lcp/instance";
+
+mod aggregation;
 mod api;
+mod approver;
+mod attestation;
+mod bisection;
+mod command_log;
 mod enclave;
 mod errors;
+mod events;
 mod ffi;
+mod genesis;
+mod health;
+mod history;
 mod memory;
+mod registration;
+mod replay;
 #[cfg(feature = "rocksdb")]
 mod rocksdb;
+mod session;
+mod shutdown;
+mod update_history;
+mod verification;
+mod watchdog;
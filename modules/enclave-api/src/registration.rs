@@ -0,0 +1,175 @@
+use crate::{EnclaveInfo, Error, Result};
+use alloy_sol_types::{sol, SolCall};
+use attestation_report::EndorsedAttestationVerificationReport;
+use crypto::Address;
+use lcp_proto::google::protobuf::Any;
+use lcp_proto::ibc::core::client::v1::MsgUpdateClient;
+use lcp_proto::ibc::lightclients::lcp::v1::RegisterEnclaveKeyMessage;
+use prost::Message;
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub const MSG_UPDATE_CLIENT_TYPE_URL: &str = "/ibc.core.client.v1.MsgUpdateClient";
+pub const REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL: &str =
+    "/ibc.lightclients.lcp.v1.RegisterEnclaveKeyMessage";
+
+/// RegistrationTarget is the kind of chain that hosts the LCP client which the key is registered to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistrationTarget {
+    /// Cosmos SDK chains with ibc-go: the key is registered with `MsgUpdateClient`
+    Cosmos,
+    /// EVM chains with lcp-solidity: the key is registered with `registerEnclaveKey`
+    Evm,
+}
+
+impl Display for RegistrationTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cosmos => write!(f, "cosmos"),
+            Self::Evm => write!(f, "evm"),
+        }
+    }
+}
+
+impl FromStr for RegistrationTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cosmos" => Ok(Self::Cosmos),
+            "evm" => Ok(Self::Evm),
+            _ => Err(Error::invalid_argument(format!(
+                "unknown registration target: expected `cosmos` or `evm`, but got `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+sol! {
+    struct EthABIRegisterEnclaveKeyMessage {
+        string report;
+        bytes signature;
+        bytes signing_cert;
+    }
+
+    function registerEnclaveKey(string clientId, EthABIRegisterEnclaveKeyMessage message);
+}
+
+/// Returns the AVR of the key that the LCP client accepts as the evidence of the registration
+///
+/// The LCP client only accepts AVRs, so a key that is attested only with DCAP cannot be registered.
+pub fn registration_evidence<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    enclave_key: Address,
+) -> Result<EndorsedAttestationVerificationReport> {
+    let km = enclave.get_key_manager();
+    match km.load(enclave_key)?.avr {
+        Some(eavr) => Ok(eavr),
+        None if km.load_dcap_bundle(enclave_key)?.is_some() => Err(Error::invalid_argument(format!(
+            "the key is attested only with DCAP, which the LCP client cannot register: enclave_key={}",
+            enclave_key
+        ))),
+        None => Err(Error::invalid_argument(format!(
+            "the key is not attested: enclave_key={}",
+            enclave_key
+        ))),
+    }
+}
+
+/// Build a `MsgUpdateClient` that submits the AVR to the LCP client on a Cosmos SDK chain
+///
+/// `signer` is the bech32 address of the account that broadcasts the transaction.
+pub fn build_cosmos_registration(
+    client_id: &str,
+    signer: &str,
+    eavr: &EndorsedAttestationVerificationReport,
+) -> Any {
+    let message = RegisterEnclaveKeyMessage {
+        report: eavr.avr.clone(),
+        signature: eavr.signature.clone(),
+        signing_cert: eavr.signing_cert.clone(),
+    };
+    let msg = MsgUpdateClient {
+        client_id: client_id.to_string(),
+        header: Some(Any {
+            type_url: REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL.to_string(),
+            value: message.encode_to_vec(),
+        }),
+        signer: signer.to_string(),
+    };
+    Any {
+        type_url: MSG_UPDATE_CLIENT_TYPE_URL.to_string(),
+        value: msg.encode_to_vec(),
+    }
+}
+
+/// Build the calldata of `registerEnclaveKey` that submits the AVR to the LCP client on an EVM chain
+pub fn build_evm_registration(
+    client_id: &str,
+    eavr: &EndorsedAttestationVerificationReport,
+) -> Vec<u8> {
+    registerEnclaveKeyCall {
+        clientId: client_id.to_string(),
+        message: EthABIRegisterEnclaveKeyMessage {
+            report: eavr.avr.clone(),
+            signature: eavr.signature.clone(),
+            signing_cert: eavr.signing_cert.clone(),
+        },
+    }
+    .abi_encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::Keccak256;
+
+    fn eavr() -> EndorsedAttestationVerificationReport {
+        EndorsedAttestationVerificationReport {
+            avr: "{}".into(),
+            signature: vec![1; 4],
+            signing_cert: vec![2; 4],
+        }
+    }
+
+    #[test]
+    fn test_cosmos_registration() {
+        let any = build_cosmos_registration("lcp-client-0", "cosmos1signer", &eavr());
+        assert_eq!(any.type_url, MSG_UPDATE_CLIENT_TYPE_URL);
+        let msg = MsgUpdateClient::decode(any.value.as_slice()).unwrap();
+        assert_eq!(msg.client_id, "lcp-client-0");
+        assert_eq!(msg.signer, "cosmos1signer");
+        let header = msg.header.unwrap();
+        assert_eq!(header.type_url, REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL);
+        let message = RegisterEnclaveKeyMessage::decode(header.value.as_slice()).unwrap();
+        assert_eq!(message.report, "{}");
+        assert_eq!(message.signature, vec![1; 4]);
+        assert_eq!(message.signing_cert, vec![2; 4]);
+    }
+
+    #[test]
+    fn test_evm_registration() {
+        let calldata = build_evm_registration("lcp-client-0", &eavr());
+        let selector = "registerEnclaveKey(string,(string,bytes,bytes))"
+            .as_bytes()
+            .keccak256();
+        assert_eq!(calldata[..4], selector[..4]);
+        let call = registerEnclaveKeyCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call.clientId, "lcp-client-0");
+        assert_eq!(call.message.report, "{}");
+    }
+
+    #[test]
+    fn test_registration_target() {
+        assert_eq!(
+            "cosmos".parse::<RegistrationTarget>().unwrap(),
+            RegistrationTarget::Cosmos
+        );
+        assert_eq!(
+            "evm".parse::<RegistrationTarget>().unwrap(),
+            RegistrationTarget::Evm
+        );
+        assert!("solana".parse::<RegistrationTarget>().is_err());
+    }
+}
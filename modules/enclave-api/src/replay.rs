@@ -0,0 +1,77 @@
+use crate::command_log::LoggedCommand;
+use crate::{EnclavePrimitiveAPI, Result};
+use crypto::Address;
+use ecall_commands::{Command, LightClientCommand, LightClientExecuteCommand};
+use lcp_types::ClientId;
+use serde::{Deserialize, Serialize};
+use store::transaction::CommitStore;
+
+/// `ReplayOutcome` is the result of replaying a logged command against an enclave
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReplayOutcome {
+    /// The sequence of the command in the history of the recording enclave
+    pub seq: u64,
+    pub command_type: String,
+    pub client_id: Option<ClientId>,
+    /// keccak256 of the client state recorded after the original execution
+    pub expected_state_digest: Option<[u8; 32]>,
+    /// keccak256 of the client state after the replay
+    pub actual_state_digest: Option<[u8; 32]>,
+    /// The error of the replay if the enclave failed to execute the command
+    pub error: Option<String>,
+}
+
+impl ReplayOutcome {
+    pub fn is_match(&self) -> bool {
+        self.error.is_none() && self.expected_state_digest == self.actual_state_digest
+    }
+}
+
+/// Execute the logged command in the enclave and compare the resulting client state with the recorded one
+///
+/// The commands are signed by `signer` instead of the key that signed the original commitments,
+/// so only the client states, not the commitments, can be compared.
+/// The enclave must have started with a store that has the same state as the recording enclave
+/// when it executed the command, which is usually a fresh one that replays the whole log in order.
+pub fn replay_command<S: CommitStore, E: EnclavePrimitiveAPI<S>>(
+    enclave: &E,
+    entry: &LoggedCommand,
+    signer: Address,
+) -> Result<ReplayOutcome> {
+    let mut cmd = entry.command()?;
+    if let Command::LightClient(LightClientCommand::Execute(cmd)) = &mut cmd {
+        match cmd {
+            LightClientExecuteCommand::InitClient(input) => input.signer = signer,
+            LightClientExecuteCommand::UpdateClient(input) => input.signer = signer,
+            LightClientExecuteCommand::AggregateMessages(input) => input.signer = signer,
+            LightClientExecuteCommand::VerifyMembership(input) => input.signer = signer,
+            LightClientExecuteCommand::VerifyNonMembership(input) => input.signer = signer,
+            LightClientExecuteCommand::VerifyMembershipBatch(input) => input.signer = signer,
+            LightClientExecuteCommand::VerifyKeyValue(input) => input.signer = signer,
+            LightClientExecuteCommand::SignLatestHeights(input) => input.signer = signer,
+            LightClientExecuteCommand::UpdateClientParams(input) => input.signer = signer,
+            LightClientExecuteCommand::ResignLatestState(input) => input.signer = signer,
+            LightClientExecuteCommand::MigrateClient(input) => input.signer = signer,
+            LightClientExecuteCommand::PruneClient(_) => {}
+        }
+    }
+    let mut outcome = ReplayOutcome {
+        seq: entry.record.seq,
+        command_type: entry.record.command_type.clone(),
+        client_id: entry.record.client_id.clone(),
+        expected_state_digest: entry.record.post_state_digest,
+        actual_state_digest: None,
+        error: None,
+    };
+    match enclave.execute_command(cmd, entry.update_key.clone()) {
+        Ok(_) => {
+            // the command has just been appended to the history of the replaying enclave
+            if let Some(record) = enclave.command_history(1)?.into_iter().next() {
+                outcome.client_id = record.client_id;
+                outcome.actual_state_digest = record.post_state_digest;
+            }
+        }
+        Err(e) => outcome.error = Some(e.to_string()),
+    }
+    Ok(outcome)
+}
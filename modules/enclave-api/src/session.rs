@@ -0,0 +1,83 @@
+use crypto::Signer;
+use ecall_commands::{session_sign_bytes, SESSION_TOKEN_SIZE};
+use std::sync::RwLock;
+
+/// `Session` holds the operator credential that the host proves to the enclave, and the token of the open session
+///
+/// The enclave executes the commands only with the token of the open session, which must be opened by an operator,
+/// or by the bootstrap operator of the enclave until the operator set is configured.
+/// So a process that loads the enclave file without the credential cannot use the sealed keys.
+#[derive(Default)]
+pub struct Session {
+    credential: Option<Box<dyn Signer + Sync + Send>>,
+    token: RwLock<Option<[u8; SESSION_TOKEN_SIZE]>>,
+}
+
+impl Session {
+    pub fn new(credential: Option<Box<dyn Signer + Sync + Send>>) -> Self {
+        Self {
+            credential,
+            token: RwLock::new(None),
+        }
+    }
+
+    /// Returns the token of the open session, or None if no session is open
+    pub fn token(&self) -> Option<[u8; SESSION_TOKEN_SIZE]> {
+        *self.token.read().unwrap()
+    }
+
+    pub(crate) fn set_token(&self, token: [u8; SESSION_TOKEN_SIZE]) {
+        *self.token.write().unwrap() = Some(token);
+    }
+
+    /// Forget the token, which the enclave no longer knows after it is re-created
+    pub(crate) fn clear_token(&self) {
+        *self.token.write().unwrap() = None;
+    }
+
+    pub(crate) fn has_credential(&self) -> bool {
+        self.credential.is_some()
+    }
+
+    /// Returns the signature of the credential over the challenge, or None if no credential is configured
+    pub(crate) fn sign_challenge(
+        &self,
+        challenge: &[u8; SESSION_TOKEN_SIZE],
+    ) -> Result<Option<Vec<u8>>, crypto::Error> {
+        self.credential
+            .as_ref()
+            .map(|credential| credential.sign(&session_sign_bytes(challenge)))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{verify_signature_address, EnclaveKey};
+
+    #[test]
+    fn test_session() {
+        let key = EnclaveKey::new().unwrap();
+        let operator = key.get_pubkey().as_address();
+        let session = Session::new(Some(Box::new(key)));
+        assert!(session.has_credential());
+        assert_eq!(session.token(), None);
+
+        let challenge = [1; SESSION_TOKEN_SIZE];
+        let signature = session.sign_challenge(&challenge).unwrap().unwrap();
+        assert_eq!(
+            verify_signature_address(&session_sign_bytes(&challenge), &signature).unwrap(),
+            operator
+        );
+
+        session.set_token([2; SESSION_TOKEN_SIZE]);
+        assert_eq!(session.token(), Some([2; SESSION_TOKEN_SIZE]));
+        session.clear_token();
+        assert_eq!(session.token(), None);
+
+        let session = Session::default();
+        assert!(!session.has_credential());
+        assert_eq!(session.sign_challenge(&challenge).unwrap(), None);
+    }
+}
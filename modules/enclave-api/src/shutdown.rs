@@ -0,0 +1,118 @@
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// `ShutdownGate` tracks the commands in flight to shut the enclave down without interrupting them
+///
+/// Once the gate is closed, the mutating commands are rejected while the queries are still served.
+/// Once it is sealed, every command is rejected, so the enclave can be destroyed.
+#[derive(Debug, Default)]
+pub struct ShutdownGate {
+    state: Mutex<GateState>,
+    idle: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    closed: bool,
+    sealed: bool,
+    in_flight: usize,
+}
+
+impl ShutdownGate {
+    /// Reject the mutating commands from now on
+    ///
+    /// Returns false if the gate has already been closed.
+    pub fn close(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        !std::mem::replace(&mut state.closed, true)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.state.lock().unwrap().sealed
+    }
+
+    /// Returns the number of the commands in flight
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight
+    }
+
+    /// Wait for the commands in flight to finish up to `timeout`, then reject every command
+    ///
+    /// Returns false if some commands are still in flight after the timeout.
+    pub fn drain(&self, timeout: Duration) -> bool {
+        let state = self.state.lock().unwrap();
+        let (mut state, res) = self
+            .idle
+            .wait_timeout_while(state, timeout, |s| s.in_flight > 0)
+            .unwrap();
+        state.closed = true;
+        state.sealed = true;
+        !res.timed_out()
+    }
+
+    /// Record the start of a command, which ends when the guard is dropped
+    ///
+    /// Returns None if the gate rejects the command.
+    pub(crate) fn enter(&self, mutating: bool) -> Option<CommandGuard<'_>> {
+        let mut state = self.state.lock().unwrap();
+        if state.sealed || (state.closed && mutating) {
+            return None;
+        }
+        state.in_flight += 1;
+        Some(CommandGuard { gate: self })
+    }
+}
+
+pub(crate) struct CommandGuard<'a> {
+    gate: &'a ShutdownGate,
+}
+
+impl<'a> Drop for CommandGuard<'a> {
+    fn drop(&mut self) {
+        let mut state = self.gate.state.lock().unwrap();
+        state.in_flight -= 1;
+        if state.in_flight == 0 {
+            self.gate.idle.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_shutdown_gate() {
+        let gate = Arc::new(ShutdownGate::default());
+        let query = gate.enter(false).unwrap();
+        let update = gate.enter(true).unwrap();
+        assert_eq!(gate.in_flight(), 2);
+
+        assert!(gate.close());
+        assert!(!gate.close());
+        // the mutating commands are rejected, but the queries are still served
+        assert!(gate.enter(true).is_none());
+        drop(gate.enter(false).unwrap());
+        drop(query);
+
+        // the commands in flight are not interrupted
+        assert!(!gate.drain(Duration::from_millis(10)));
+        assert!(gate.is_sealed());
+        assert!(gate.enter(false).is_none());
+
+        let handle = {
+            let gate = gate.clone();
+            thread::spawn(move || gate.drain(Duration::from_secs(10)))
+        };
+        thread::sleep(Duration::from_millis(10));
+        drop(update);
+        assert!(handle.join().unwrap());
+        assert_eq!(gate.in_flight(), 0);
+    }
+}
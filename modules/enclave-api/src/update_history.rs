@@ -0,0 +1,610 @@
+use crate::enclave::HostStoreTxManager;
+use crate::history::{decode_time, encode_time};
+use crate::{Error, Result};
+use commitments::{CommitmentProof, ProxyMessage};
+use core::str::FromStr;
+use ecall_commands::{
+    decode_commitment_proof, encode_commitment_proof, CommandResponse, LightClientResponse,
+    PageRequest,
+};
+use lcp_proto::lcp::ecall::v1::{
+    UpdateRecord as ProtoUpdateRecord, UpdateRetention as ProtoUpdateRetention,
+};
+use lcp_types::{ClientId, Height, Time};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use store::transaction::{CommitStore, CreatedTx};
+
+/// The maximum number of update records kept per client. Older records are pruned.
+pub const UPDATE_HISTORY_LIMIT: u64 = 1024;
+
+/// `UpdateRetention` is the retention policy of the update records of a client
+///
+/// The records that exceed the policy are pruned when a new record is committed,
+/// but the latest record is always kept.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateRetention {
+    /// The number of the latest records kept, which must be in `1..=UPDATE_HISTORY_LIMIT`
+    pub max_records: u64,
+    /// If set, the records recorded more than this duration before the latest record are pruned
+    pub max_age: Option<Duration>,
+}
+
+impl Default for UpdateRetention {
+    fn default() -> Self {
+        Self {
+            max_records: UPDATE_HISTORY_LIMIT,
+            max_age: None,
+        }
+    }
+}
+
+impl UpdateRetention {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_records == 0 || self.max_records > UPDATE_HISTORY_LIMIT {
+            return Err(Error::invalid_argument(format!(
+                "max_records must be in 1..={}: max_records={}",
+                UPDATE_HISTORY_LIMIT, self.max_records
+            )));
+        }
+        Ok(())
+    }
+
+    fn to_proto(self) -> Result<ProtoUpdateRetention> {
+        Ok(ProtoUpdateRetention {
+            max_records: self.max_records,
+            max_age: self
+                .max_age
+                .map(|max_age| {
+                    max_age.as_nanos().try_into().map_err(|_| {
+                        Error::invalid_argument(format!(
+                            "max_age out of range: max_age={:?}",
+                            max_age
+                        ))
+                    })
+                })
+                .transpose()?,
+        })
+    }
+
+    fn from_proto(msg: ProtoUpdateRetention) -> Self {
+        Self {
+            max_records: msg.max_records,
+            max_age: msg.max_age.map(Duration::from_nanos),
+        }
+    }
+}
+
+/// `UpdateRecord` is a signed `UpdateStateProxyMessage` produced by `update_client`
+///
+/// A relayer that crashed before submitting the message downstream can resubmit it from the record
+/// instead of updating the client in the enclave again.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UpdateRecord {
+    pub seq: u64,
+    pub client_id: ClientId,
+    pub prev_height: Option<Height>,
+    pub post_height: Height,
+    pub recorded_at: Time,
+    pub proof: CommitmentProof,
+}
+
+impl UpdateRecord {
+    fn to_proto(&self) -> Result<ProtoUpdateRecord> {
+        Ok(ProtoUpdateRecord {
+            seq: self.seq,
+            client_id: self.client_id.to_string(),
+            prev_height: self.prev_height.map(Into::into),
+            post_height: Some(self.post_height.into()),
+            recorded_at: encode_time(self.recorded_at)?,
+            proof: Some(encode_commitment_proof(&self.proof)),
+        })
+    }
+
+    fn from_proto(msg: ProtoUpdateRecord) -> Result<Self> {
+        Ok(Self {
+            seq: msg.seq,
+            client_id: ClientId::from_str(&msg.client_id)
+                .map_err(|e| Error::invalid_argument(format!("invalid client_id: {}", e)))?,
+            prev_height: msg.prev_height.map(Into::into),
+            post_height: msg
+                .post_height
+                .ok_or_else(|| Error::invalid_argument("post_height is missing".into()))?
+                .into(),
+            recorded_at: decode_time(msg.recorded_at)?,
+            proof: decode_commitment_proof(msg.proof)?,
+        })
+    }
+}
+
+/// Record the update in the transaction of the command, so that a committed update always has its record
+///
+/// Responses other than an `UpdateStateProxyMessage` of `update_client` are ignored.
+pub(crate) fn record_update<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+    recorded_at: Time,
+    res: &CommandResponse,
+) -> Result<Option<u64>> {
+    let proof = match res {
+        CommandResponse::LightClient(LightClientResponse::UpdateClient(res)) => &res.0,
+        _ => return Ok(None),
+    };
+    // an update that detected a misbehaviour is not a candidate for resubmission
+    let msg = match ProxyMessage::from_bytes(&proof.message) {
+        Ok(ProxyMessage::UpdateState(msg)) => msg,
+        _ => return Ok(None),
+    };
+    let seq = count(store, tx, client_id)?;
+    let record = UpdateRecord {
+        seq,
+        client_id: client_id.clone(),
+        prev_height: msg.prev_height,
+        post_height: msg.post_height,
+        recorded_at,
+        proof: proof.clone(),
+    };
+    store.tx_set(
+        tx,
+        seq_key(client_id, seq),
+        record.to_proto()?.encode_to_vec(),
+    )?;
+    store.tx_set(
+        tx,
+        height_key(client_id, &msg.post_height),
+        seq.to_be_bytes().to_vec(),
+    )?;
+    store.tx_set(tx, count_key(client_id), (seq + 1).to_be_bytes().to_vec())?;
+    prune_records(store, tx, client_id, recorded_at)?;
+    Ok(Some(seq))
+}
+
+/// Set the retention policy of the client, and prune the records that exceed it in the transaction
+///
+/// Returns the number of the pruned records.
+pub(crate) fn set_retention<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+    retention: UpdateRetention,
+    now: Time,
+) -> Result<u64> {
+    retention.validate()?;
+    store.tx_set(
+        tx,
+        retention_key(client_id),
+        retention.to_proto()?.encode_to_vec(),
+    )?;
+    prune_records(store, tx, client_id, now)
+}
+
+/// Returns the retention policy of the client, which is the default one if it is not set
+pub(crate) fn retention<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    client_id: &ClientId,
+) -> Result<UpdateRetention> {
+    let tx = store.begin_tx(None)?;
+    let res = get_retention(store, &tx, client_id);
+    store.rollback_tx(tx);
+    res
+}
+
+/// Prune the oldest records of the client that exceed its retention policy at `now`
+fn prune_records<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+    now: Time,
+) -> Result<u64> {
+    let retention = get_retention(store, tx, client_id)?;
+    let count = count(store, tx, client_id)?;
+    let first = first_seq(store, tx, client_id, count)?;
+    let mut next = first;
+    // the latest record is never pruned
+    while next + 1 < count {
+        let record = get_record(store, tx, client_id, next)?;
+        let exceeded = count - next > retention.max_records;
+        let expired = match (retention.max_age, record.as_ref()) {
+            (Some(max_age), Some(record)) => (record.recorded_at + max_age)
+                .map(|expires_at| expires_at < now)
+                .unwrap_or(false),
+            _ => false,
+        };
+        if !exceeded && !expired {
+            break;
+        }
+        if let Some(record) = record {
+            // the height may have been updated again by a newer record
+            let key = height_key(client_id, &record.post_height);
+            if store.tx_get(tx, &key)? == Some(next.to_be_bytes().to_vec()) {
+                store.tx_remove(tx, &key)?;
+            }
+            store.tx_remove(tx, &seq_key(client_id, next))?;
+        }
+        next += 1;
+    }
+    if next != first {
+        store.tx_set(tx, first_key(client_id), next.to_be_bytes().to_vec())?;
+    }
+    Ok(next - first)
+}
+
+/// Returns a page of the update records of the client in descending order of `seq`
+pub(crate) fn update_records<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    client_id: &ClientId,
+    pagination: PageRequest,
+) -> Result<Vec<UpdateRecord>> {
+    let tx = store.begin_tx(None)?;
+    let res = (|| -> Result<Vec<UpdateRecord>> {
+        let count = count(store, &tx, client_id)?;
+        let first = first_seq(store, &tx, client_id, count)?;
+        let retained = count - first;
+        if pagination.offset >= retained && pagination.offset < count {
+            return Err(Error::pruned_update_record(
+                client_id.to_string(),
+                first,
+                format!("offset={} retained={}", pagination.offset, retained),
+            ));
+        }
+        let mut records = Vec::new();
+        for i in pagination.offset..retained.min(pagination.offset.saturating_add(pagination.limit))
+        {
+            if let Some(record) = get_record(store, &tx, client_id, count - 1 - i)? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    })();
+    store.rollback_tx(tx);
+    res
+}
+
+/// Returns the latest update record of the client whose post height is `height`
+pub(crate) fn update_record_at<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    client_id: &ClientId,
+    height: &Height,
+) -> Result<Option<UpdateRecord>> {
+    let tx = store.begin_tx(None)?;
+    let res = (|| -> Result<Option<UpdateRecord>> {
+        match store.tx_get(&tx, &height_key(client_id, height))? {
+            Some(bz) => get_record(store, &tx, client_id, decode_u64(bz)?),
+            None => {
+                let count = count(store, &tx, client_id)?;
+                let first = first_seq(store, &tx, client_id, count)?;
+                if first == 0 {
+                    return Ok(None);
+                }
+                // a height below the oldest retained record may have been pruned
+                match get_record(store, &tx, client_id, first)? {
+                    Some(oldest) if *height < oldest.post_height => {
+                        Err(Error::pruned_update_record(
+                            client_id.to_string(),
+                            first,
+                            format!("height={} oldest={}", height, oldest.post_height),
+                        ))
+                    }
+                    _ => Ok(None),
+                }
+            }
+        }
+    })();
+    store.rollback_tx(tx);
+    res
+}
+
+fn count<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+) -> Result<u64> {
+    match store.tx_get(tx, &count_key(client_id))? {
+        Some(bz) => decode_u64(bz),
+        None => Ok(0),
+    }
+}
+
+/// Returns the `seq` of the oldest retained record
+///
+/// A store written before the retention policies were introduced has no key, and keeps the latest `UPDATE_HISTORY_LIMIT` records.
+fn first_seq<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+    count: u64,
+) -> Result<u64> {
+    match store.tx_get(tx, &first_key(client_id))? {
+        Some(bz) => decode_u64(bz),
+        None => Ok(count.saturating_sub(UPDATE_HISTORY_LIMIT)),
+    }
+}
+
+fn get_retention<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+) -> Result<UpdateRetention> {
+    match store.tx_get(tx, &retention_key(client_id))? {
+        Some(bz) => Ok(UpdateRetention::from_proto(
+            ProtoUpdateRetention::decode(bz.as_slice()).map_err(Error::proto_decode)?,
+        )),
+        None => Ok(Default::default()),
+    }
+}
+
+fn get_record<S: CommitStore, M: HostStoreTxManager<S> + ?Sized>(
+    store: &M,
+    tx: &<S::Tx as CreatedTx>::PreparedTx,
+    client_id: &ClientId,
+    seq: u64,
+) -> Result<Option<UpdateRecord>> {
+    store
+        .tx_get(tx, &seq_key(client_id, seq))?
+        .map(|bz| {
+            UpdateRecord::from_proto(
+                ProtoUpdateRecord::decode(bz.as_slice()).map_err(Error::proto_decode)?,
+            )
+        })
+        .transpose()
+}
+
+fn count_key(client_id: &ClientId) -> Vec<u8> {
+    format!("lcp/update_history/{}/count", client_id).into_bytes()
+}
+
+fn first_key(client_id: &ClientId) -> Vec<u8> {
+    format!("lcp/update_history/{}/first", client_id).into_bytes()
+}
+
+fn retention_key(client_id: &ClientId) -> Vec<u8> {
+    format!("lcp/update_history/{}/retention", client_id).into_bytes()
+}
+
+fn seq_key(client_id: &ClientId, seq: u64) -> Vec<u8> {
+    format!("lcp/update_history/{}/seq/{:020}", client_id, seq).into_bytes()
+}
+
+fn height_key(client_id: &ClientId, height: &Height) -> Vec<u8> {
+    format!(
+        "lcp/update_history/{}/height/{:020}-{:020}",
+        client_id,
+        height.revision_number(),
+        height.revision_height()
+    )
+    .into_bytes()
+}
+
+fn decode_u64(bz: Vec<u8>) -> Result<u64> {
+    Ok(u64::from_be_bytes(bz.try_into().map_err(|_| {
+        Error::invalid_argument("invalid counter of the update history".into())
+    })?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave::CommitStoreAccessor;
+    use crate::ErrorDetail;
+    use commitments::{UpdateStateProxyMessage, ValidationContext};
+    use core::str::FromStr;
+    use crypto::Address;
+    use ecall_commands::{AggregateMessagesResponse, UpdateClientResponse};
+    use std::ops::DerefMut;
+    use std::sync::RwLock;
+    use store::host::{HostStore, IntoCommitStore};
+    use store::memory::MemStore;
+
+    struct TestStore(RwLock<HostStore>);
+
+    impl CommitStoreAccessor<MemStore> for TestStore {
+        fn use_mut_store<T>(&self, f: impl FnOnce(&mut MemStore) -> T) -> T {
+            self.0.write().unwrap().deref_mut().apply(f)
+        }
+
+        fn use_host_store<T>(&self, f: impl FnOnce(&mut HostStore) -> T) -> T {
+            f(self.0.write().unwrap().deref_mut())
+        }
+    }
+
+    impl HostStoreTxManager<MemStore> for TestStore {}
+
+    fn update_response(post_height: u64) -> CommandResponse {
+        let msg = UpdateStateProxyMessage {
+            prev_height: Some(Height::new(0, post_height - 1)),
+            prev_state_id: Some(Default::default()),
+            post_height: Height::new(0, post_height),
+            post_state_id: Default::default(),
+            frozen_height: None,
+            timestamp: Time::unix_epoch(),
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: Default::default(),
+        };
+        CommandResponse::LightClient(LightClientResponse::UpdateClient(UpdateClientResponse(
+            CommitmentProof {
+                message: ProxyMessage::from(msg).to_bytes(),
+                signer: Address::default(),
+                signature: vec![post_height as u8],
+                co_signatures: Default::default(),
+                request_binding: None,
+                signature_scheme: Default::default(),
+            },
+        )))
+    }
+
+    fn record(store: &TestStore, client_id: &ClientId, res: &CommandResponse) -> Option<u64> {
+        record_at(store, client_id, Time::unix_epoch(), res)
+    }
+
+    fn record_at(
+        store: &TestStore,
+        client_id: &ClientId,
+        recorded_at: Time,
+        res: &CommandResponse,
+    ) -> Option<u64> {
+        let tx = store.begin_tx(Some(client_id.to_string())).unwrap();
+        let seq = record_update(store, &tx, client_id, recorded_at, res).unwrap();
+        store.commit_tx(tx).unwrap();
+        seq
+    }
+
+    #[test]
+    fn test_update_history() {
+        let store = TestStore(RwLock::new(HostStore::Memory(MemStore::default())));
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let other_id = ClientId::from_str("07-tendermint-1").unwrap();
+
+        for h in 1..=UPDATE_HISTORY_LIMIT + 2 {
+            assert_eq!(record(&store, &client_id, &update_response(h)), Some(h - 1));
+        }
+        assert_eq!(record(&store, &other_id, &update_response(10)), Some(0));
+
+        let records =
+            update_records::<MemStore, _>(&store, &client_id, PageRequest::new(1, 2).unwrap())
+                .unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.post_height).collect::<Vec<_>>(),
+            vec![
+                Height::new(0, UPDATE_HISTORY_LIMIT + 1),
+                Height::new(0, UPDATE_HISTORY_LIMIT)
+            ]
+        );
+        assert_eq!(
+            records[0].proof.signature,
+            vec![(UPDATE_HISTORY_LIMIT + 1) as u8]
+        );
+
+        // the oldest records are pruned
+        assert_eq!(
+            update_records::<MemStore, _>(
+                &store,
+                &client_id,
+                PageRequest::new(UPDATE_HISTORY_LIMIT - 1, 10).unwrap()
+            )
+            .unwrap()
+            .len(),
+            1
+        );
+        assert!(update_records::<MemStore, _>(
+            &store,
+            &client_id,
+            PageRequest::new(UPDATE_HISTORY_LIMIT, 10).unwrap()
+        )
+        .is_err());
+        assert!(update_record_at::<MemStore, _>(&store, &client_id, &Height::new(0, 2)).is_err());
+        // a height that has never been recorded is not an error
+        assert!(update_record_at::<MemStore, _>(
+            &store,
+            &client_id,
+            &Height::new(0, UPDATE_HISTORY_LIMIT + 10)
+        )
+        .unwrap()
+        .is_none());
+        let record = update_record_at::<MemStore, _>(&store, &client_id, &Height::new(0, 3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.seq, 2);
+        assert_eq!(record.prev_height, Some(Height::new(0, 2)));
+
+        let records = update_records::<MemStore, _>(&store, &other_id, Default::default()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].post_height, Height::new(0, 10));
+
+        // only the responses of update_client are recorded
+        let tx = store.begin_tx(None).unwrap();
+        let res = match update_response(1) {
+            CommandResponse::LightClient(LightClientResponse::UpdateClient(res)) => {
+                CommandResponse::LightClient(LightClientResponse::AggregateMessages(
+                    AggregateMessagesResponse(res.0, vec![], vec![]),
+                ))
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            record_update(&store, &tx, &client_id, Time::unix_epoch(), &res).unwrap(),
+            None
+        );
+        store.rollback_tx(tx);
+    }
+
+    #[test]
+    fn test_update_retention() {
+        let store = TestStore(RwLock::new(HostStore::Memory(MemStore::default())));
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let at = |secs: u64| (Time::unix_epoch() + Duration::from_secs(secs)).unwrap();
+
+        assert_eq!(
+            retention::<MemStore, _>(&store, &client_id).unwrap(),
+            UpdateRetention::default()
+        );
+        for h in 1..=10 {
+            record_at(&store, &client_id, at(h), &update_response(h));
+        }
+
+        // an invalid policy is rejected
+        let tx = store.begin_tx(None).unwrap();
+        for max_records in [0, UPDATE_HISTORY_LIMIT + 1] {
+            let policy = UpdateRetention {
+                max_records,
+                max_age: None,
+            };
+            assert!(set_retention(&store, &tx, &client_id, policy, at(10)).is_err());
+        }
+        store.rollback_tx(tx);
+
+        // setting a policy prunes the records immediately
+        let policy = UpdateRetention {
+            max_records: 5,
+            max_age: None,
+        };
+        let tx = store.begin_tx(None).unwrap();
+        assert_eq!(
+            set_retention(&store, &tx, &client_id, policy, at(10)).unwrap(),
+            5
+        );
+        store.commit_tx(tx).unwrap();
+        assert_eq!(
+            retention::<MemStore, _>(&store, &client_id).unwrap(),
+            policy
+        );
+        let records =
+            update_records::<MemStore, _>(&store, &client_id, Default::default()).unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records.last().unwrap().seq, 5);
+        let err =
+            update_records::<MemStore, _>(&store, &client_id, PageRequest::new(5, 1).unwrap())
+                .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PrunedUpdateRecord(e) if e.first_seq == 5
+        ));
+        assert!(update_record_at::<MemStore, _>(&store, &client_id, &Height::new(0, 5)).is_err());
+        assert!(
+            update_record_at::<MemStore, _>(&store, &client_id, &Height::new(0, 6))
+                .unwrap()
+                .is_some()
+        );
+
+        // the records older than max_age are pruned by the next commit, except the latest one
+        let policy = UpdateRetention {
+            max_records: 5,
+            max_age: Some(Duration::from_secs(2)),
+        };
+        let tx = store.begin_tx(None).unwrap();
+        assert_eq!(
+            set_retention(&store, &tx, &client_id, policy, at(10)).unwrap(),
+            2
+        );
+        store.commit_tx(tx).unwrap();
+        assert_eq!(
+            record_at(&store, &client_id, at(100), &update_response(11)),
+            Some(10)
+        );
+        let records =
+            update_records::<MemStore, _>(&store, &client_id, Default::default()).unwrap();
+        assert_eq!(records.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![10]);
+    }
+}
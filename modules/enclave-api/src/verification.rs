@@ -0,0 +1,201 @@
+use crate::{EnclaveInfo, Error, Result};
+use attestation_report::EndorsedAttestationVerificationReport;
+use commitments::CommitmentProof;
+use crypto::{Address, SignatureScheme};
+use lcp_types::Time;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default duration for which a verified attestation of a signer is reused
+pub const DEFAULT_ATTESTATION_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// `AttestationCache` keeps the signers whose attestation has been verified against the enclave,
+/// so that the attestation is verified once per `ttl` instead of for every proof.
+#[derive(Debug)]
+pub struct AttestationCache {
+    ttl: Duration,
+    verified_at: Mutex<HashMap<Address, Time>>,
+}
+
+impl Default for AttestationCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_ATTESTATION_CACHE_TTL)
+    }
+}
+
+impl AttestationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            verified_at: Default::default(),
+        }
+    }
+
+    /// Returns true if the attestation of the signer was verified within `ttl` before `now`
+    pub fn is_verified(&self, signer: Address, now: Time) -> bool {
+        match self.verified_at.lock().unwrap().get(&signer) {
+            Some(verified_at) => {
+                *verified_at <= now && (*verified_at + self.ttl).map_or(false, |until| now < until)
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert(&self, signer: Address, now: Time) {
+        self.verified_at.lock().unwrap().insert(signer, now);
+    }
+
+    /// Drops every entry, e.g. after the enclave is re-created from a binary that may have been replaced
+    pub fn clear(&self) {
+        self.verified_at.lock().unwrap().clear();
+    }
+}
+
+/// `verify_commitment_proof` verifies the proof on the host as the downstream verifier would,
+/// so that a misconfigured or unattested key is caught before the proof is submitted.
+///
+/// Each signature must be produced by its signer with the signature scheme of the proof, and each signer must have an attestation bound to
+/// the loaded enclave: an AVR (whose IAS signature is verified unless built with `sgx-sw`) or a validated DCAP bundle, either of which must match the MRENCLAVE of the enclave.
+/// The result of the attestation check is cached per signer if the enclave has an `AttestationCache`.
+/// A message that carries an expiry must not have expired at `now`.
+pub fn verify_commitment_proof<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    proof: &CommitmentProof,
+    now: Time,
+) -> Result<()> {
+    if !proof.is_proven() {
+        return Err(Error::proof_verification(
+            "the proof has no signature".into(),
+        ));
+    }
+    proof
+        .message()
+        .and_then(|msg| msg.check_expiry(now))
+        .map_err(|e| Error::proof_verification(e.to_string()))?;
+    let scheme = proof.signature_scheme;
+    verify_signer(
+        enclave,
+        scheme,
+        &proof.message,
+        proof.signer,
+        &proof.signature,
+        now,
+    )?;
+    for cs in proof.co_signatures.iter() {
+        verify_signer(
+            enclave,
+            scheme,
+            &proof.message,
+            cs.signer,
+            &cs.signature,
+            now,
+        )?;
+    }
+    Ok(())
+}
+
+fn verify_signer<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    scheme: SignatureScheme,
+    message: &[u8],
+    signer: Address,
+    signature: &[u8],
+    now: Time,
+) -> Result<()> {
+    scheme
+        .verify_signer(message, signature, signer)
+        .map_err(|e| Error::proof_verification(e.to_string()))?;
+
+    let cache = enclave.get_attestation_cache();
+    if cache.map_or(false, |cache| cache.is_verified(signer, now)) {
+        return Ok(());
+    }
+    verify_attestation(enclave, signer, now)?;
+    if let Some(cache) = cache {
+        cache.insert(signer, now);
+    }
+    Ok(())
+}
+
+fn verify_attestation<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    signer: Address,
+    now: Time,
+) -> Result<()> {
+    let km = enclave.get_key_manager();
+    let eki = km.load(signer)?;
+    match eki.avr {
+        Some(eavr) => verify_avr(enclave, signer, &eavr, now),
+        None => match km.load_dcap_bundle(signer)? {
+            Some(bundle) => {
+                let address = bundle.get_enclave_key_address()?;
+                if address != signer {
+                    return Err(Error::proof_verification(format!(
+                        "DCAP quote is bound to another key: signer={} quote={}",
+                        signer, address
+                    )));
+                }
+                bundle.match_metadata(&enclave.metadata()?)?;
+                Ok(())
+            }
+            None => Err(Error::proof_verification(format!(
+                "the signer is not attested: signer={}",
+                signer
+            ))),
+        },
+    }
+}
+
+fn verify_avr<E: EnclaveInfo + ?Sized>(
+    enclave: &E,
+    signer: Address,
+    eavr: &EndorsedAttestationVerificationReport,
+    now: Time,
+) -> Result<()> {
+    #[cfg(not(feature = "sgx-sw"))]
+    attestation_report::verify_report(now, eavr)?;
+    #[cfg(feature = "sgx-sw")]
+    let _ = now;
+
+    let quote = eavr.get_avr()?.parse_quote()?;
+    let address = quote.get_enclave_key_address()?;
+    if address != signer {
+        return Err(Error::proof_verification(format!(
+            "AVR is bound to another key: signer={} avr={}",
+            signer, address
+        )));
+    }
+    quote.match_metadata(&enclave.metadata()?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(secs: u64) -> Time {
+        Time::from_unix_timestamp_nanos(secs as u128 * 1_000_000_000).unwrap()
+    }
+
+    #[test]
+    fn test_attestation_cache() {
+        let cache = AttestationCache::new(Duration::from_secs(10));
+        let signer = Address([1u8; 20]);
+        assert!(!cache.is_verified(signer, time(100)));
+
+        cache.insert(signer, time(100));
+        assert!(cache.is_verified(signer, time(100)));
+        assert!(cache.is_verified(signer, time(109)));
+        assert!(!cache.is_verified(Address([2u8; 20]), time(100)));
+        // expired
+        assert!(!cache.is_verified(signer, time(110)));
+        // before the verification
+        assert!(!cache.is_verified(signer, time(99)));
+
+        cache.insert(signer, time(110));
+        assert!(cache.is_verified(signer, time(115)));
+        cache.clear();
+        assert!(!cache.is_verified(signer, time(115)));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::errors::{Error, Result};
+use sgx_types::sgx_status_t;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// `Watchdog` tracks the ecalls in flight to detect the enclave hanging
+///
+/// An ecall cannot be interrupted from the host, so a hung enclave is recovered by re-creating it:
+/// the callers of the ecalls into the old enclave fail without waiting for them, the transactions of their commands
+/// are rolled back, and the commands that change the store are replayed in the new enclave from the write-ahead log.
+#[derive(Debug)]
+pub struct Watchdog {
+    timeout: Duration,
+    next_id: AtomicU64,
+    /// the ecalls in flight keyed by id
+    in_flight: Mutex<BTreeMap<u64, InFlight>>,
+    generation: AtomicU64,
+    /// the write-ahead log of the commands whose ecalls were abandoned by a re-creation
+    abandoned: Mutex<Vec<PendingCommand>>,
+}
+
+#[derive(Debug)]
+struct InFlight {
+    /// the generation of the enclave that the ecall entered
+    generation: u64,
+    started_at: Instant,
+    command: Option<PendingCommand>,
+}
+
+/// `PendingCommand` is an entry of the write-ahead log, which is kept until the ecall of the command returns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PendingCommand {
+    /// The command encoded with `ecall_commands::encode_command_message`
+    pub command: Vec<u8>,
+    pub update_key: Option<String>,
+}
+
+impl Watchdog {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            next_id: AtomicU64::new(0),
+            in_flight: Default::default(),
+            generation: AtomicU64::new(0),
+            abandoned: Default::default(),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the number of the times the enclave has been re-created
+    pub fn recreations(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Returns the elapsed time of the oldest ecall in flight, if it exceeds the timeout
+    ///
+    /// The ecalls that entered an enclave that has since been re-created are ignored.
+    pub fn hung_for(&self) -> Option<Duration> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        self.in_flight
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|ecall| ecall.generation == generation)
+            .map(|ecall| ecall.started_at.elapsed())
+            .max()
+            .filter(|elapsed| *elapsed > self.timeout)
+    }
+
+    /// Run the ecall into the enclave with the id given by `eid` on another thread, and wait for it
+    ///
+    /// If the enclave is re-created before the ecall returns, the caller fails with `EnclaveRecreated`
+    /// without waiting for it, and `command` is moved to the write-ahead log to be replayed.
+    pub(crate) fn call<T: Send + 'static>(
+        &self,
+        eid: impl FnOnce() -> u64,
+        command: Option<PendingCommand>,
+        ecall: impl FnOnce(u64) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let logged = command.is_some();
+        // the enclave is swapped with the lock held, so the ecall enters the enclave of its generation
+        let (eid, generation) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            let eid = eid();
+            let generation = self.generation.load(Ordering::SeqCst);
+            in_flight.insert(
+                id,
+                InFlight {
+                    generation,
+                    started_at: Instant::now(),
+                    command,
+                },
+            );
+            (eid, generation)
+        };
+        let (sender, receiver) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("ecall".into())
+            .spawn(move || {
+                // the caller may have stopped waiting
+                let _ = sender.send(ecall(eid));
+            });
+        if let Err(e) = spawned {
+            self.in_flight.lock().unwrap().remove(&id);
+            return Err(Error::io(e));
+        }
+        let res = loop {
+            match receiver.recv_timeout(self.timeout) {
+                Ok(res) => break Some(res),
+                Err(RecvTimeoutError::Timeout) => {
+                    if self.generation.load(Ordering::SeqCst) != generation {
+                        break None;
+                    }
+                }
+                // the ecall thread panicked
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Some(Err(Error::sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED)))
+                }
+            }
+        };
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let ecall = in_flight.remove(&id).expect("the ecall must be in flight");
+        // a response of the old enclave must not be committed even if it arrived in the meantime
+        if ecall.generation != self.generation.load(Ordering::SeqCst) {
+            // the command has been moved to the write-ahead log by the re-creation
+            return Err(Error::enclave_recreated(logged));
+        }
+        res.expect("the generation must have changed")
+    }
+
+    /// Swap the enclave with `swap` and fail the ecalls into the old one
+    ///
+    /// The commands of the failed ecalls are moved to the write-ahead log, which `take_abandoned` drains.
+    pub(crate) fn recreated<T>(&self, swap: impl FnOnce() -> T) -> T {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let old = swap();
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst);
+        let mut abandoned = self.abandoned.lock().unwrap();
+        for ecall in in_flight.values_mut() {
+            if ecall.generation == generation {
+                abandoned.extend(ecall.command.take());
+            }
+        }
+        old
+    }
+
+    /// Returns the commands in the write-ahead log in the order they were abandoned, and clear the log
+    pub(crate) fn take_abandoned(&self) -> Vec<PendingCommand> {
+        std::mem::take(&mut *self.abandoned.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorDetail;
+
+    #[test]
+    fn test_watchdog() {
+        let watchdog = Watchdog::new(Duration::from_millis(10));
+        assert_eq!(watchdog.call(|| 1, None, |eid| Ok(eid + 1)).unwrap(), 2);
+        assert!(watchdog.in_flight.lock().unwrap().is_empty());
+
+        let command = PendingCommand {
+            command: vec![1, 2, 3],
+            update_key: Some("07-tendermint-0".into()),
+        };
+        let (release, released) = mpsc::channel::<()>();
+        std::thread::scope(|s| {
+            let hung = s.spawn(|| {
+                watchdog.call(
+                    || 1,
+                    Some(command.clone()),
+                    move |_| {
+                        let _ = released.recv();
+                        Ok(())
+                    },
+                )
+            });
+            let other = s.spawn(|| watchdog.call(|| 1, None, |_| Ok(())));
+            assert!(other.join().unwrap().is_ok());
+            std::thread::sleep(Duration::from_millis(20));
+            assert!(watchdog.hung_for().unwrap() > watchdog.timeout());
+
+            // the hung caller fails without waiting for the ecall, and its command is logged to be replayed
+            assert_eq!(watchdog.recreated(|| 2), 2);
+            let err = hung.join().unwrap().unwrap_err();
+            assert!(matches!(
+                err.detail(),
+                ErrorDetail::EnclaveRecreated(e) if e.replayed
+            ));
+            assert_eq!(watchdog.recreations(), 1);
+            assert_eq!(watchdog.hung_for(), None);
+            assert!(watchdog.in_flight.lock().unwrap().is_empty());
+            assert_eq!(watchdog.take_abandoned(), vec![command.clone()]);
+            assert!(watchdog.take_abandoned().is_empty());
+            // the ecall into the old enclave returns later
+            release.send(()).unwrap();
+        });
+
+        // the ecalls into the new enclave are not affected
+        assert_eq!(watchdog.call(|| 2, None, Ok).unwrap(), 2);
+    }
+}
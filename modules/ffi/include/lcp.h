@@ -0,0 +1,57 @@
+#ifndef LCP_H
+#define LCP_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define LCP_OK 0
+#define LCP_ERR_INVALID_ARGUMENT 1
+#define LCP_ERR_DECODE 2
+#define LCP_ERR_ENCLAVE 3
+#define LCP_ERR_PANIC 4
+
+typedef struct lcp_enclave lcp_enclave_t;
+
+/* Buffer allocated by the library. Release it with lcp_buffer_free. */
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} lcp_buffer_t;
+
+/* Returns NULL on failure. The reason is available via lcp_last_error. */
+lcp_enclave_t *lcp_enclave_open(const char *home, const char *enclave_path, bool debug);
+void lcp_enclave_close(lcp_enclave_t *enclave);
+
+void lcp_buffer_free(lcp_buffer_t buf);
+/* Writes the message of the last error that occurred in the calling thread. */
+int32_t lcp_last_error(lcp_buffer_t *out);
+
+/* Writes the 20 bytes address of the generated enclave key. */
+int32_t lcp_generate_enclave_key(const lcp_enclave_t *enclave, lcp_buffer_t *out);
+/* Writes the endorsed attestation verification report as JSON.
+ * `linkable` selects a linkable EPID quote instead of an unlinkable one. */
+int32_t lcp_ias_remote_attestation(const lcp_enclave_t *enclave,
+                                   const uint8_t *target_enclave_key, size_t target_enclave_key_len,
+                                   const uint8_t *spid, size_t spid_len,
+                                   const uint8_t *ias_key, size_t ias_key_len,
+                                   bool linkable,
+                                   lcp_buffer_t *out);
+
+/* The following functions take and write protobuf-encoded messages of lcp.service.elc.v1. */
+int32_t lcp_create_client(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+int32_t lcp_update_client(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+int32_t lcp_aggregate_messages(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+int32_t lcp_verify_membership(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+int32_t lcp_verify_non_membership(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+int32_t lcp_query_client(const lcp_enclave_t *enclave, const uint8_t *req, size_t req_len, lcp_buffer_t *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LCP_H */
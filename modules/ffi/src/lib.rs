@@ -0,0 +1,390 @@
+//! C-compatible interface to the enclave commands for relayers that are not written in Rust.
+//!
+//! The light client operations take and return protobuf-encoded messages of `lcp.service.elc.v1`,
+//! so callers can reuse the types generated for the gRPC service. See `include/lcp.h` for the C declarations.
+#![allow(clippy::missing_safety_doc)]
+use crypto::Address;
+use ecall_commands::{GenerateEnclaveKeyInput, IASRemoteAttestationInput, QuoteSignType};
+use enclave_api::{Enclave, EnclaveCommandAPI, EnclaveProtoAPI};
+use host_environment::{Environment, HomeLock};
+use keymanager::EnclaveKeyManager;
+use lcp_proto::lcp::service::elc::v1::{
+    MsgAggregateMessages, MsgCreateClient, MsgUpdateClient, MsgVerifyMembership,
+    MsgVerifyNonMembership, QueryClientRequest,
+};
+use prost::Message;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use store::{host::HostStore, rocksdb::RocksDBStore};
+
+pub const LCP_OK: i32 = 0;
+pub const LCP_ERR_INVALID_ARGUMENT: i32 = 1;
+pub const LCP_ERR_DECODE: i32 = 2;
+pub const LCP_ERR_ENCLAVE: i32 = 3;
+pub const LCP_ERR_PANIC: i32 = 4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Opaque handle to a loaded enclave
+pub struct LcpEnclave(Enclave<RocksDBStore>);
+
+/// Byte buffer allocated by this library, which must be released with `lcp_buffer_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct LcpBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl LcpBuffer {
+    fn empty() -> Self {
+        Self {
+            data: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+impl From<Vec<u8>> for LcpBuffer {
+    fn from(bz: Vec<u8>) -> Self {
+        let len = bz.len();
+        Self {
+            data: Box::into_raw(bz.into_boxed_slice()) as *mut u8,
+            len,
+        }
+    }
+}
+
+struct FfiError(i32, String);
+
+impl FfiError {
+    fn invalid_argument(msg: impl ToString) -> Self {
+        Self(LCP_ERR_INVALID_ARGUMENT, msg.to_string())
+    }
+
+    fn decode(msg: impl ToString) -> Self {
+        Self(LCP_ERR_DECODE, msg.to_string())
+    }
+
+    fn enclave(msg: impl ToString) -> Self {
+        Self(LCP_ERR_ENCLAVE, msg.to_string())
+    }
+}
+
+fn set_last_error(msg: String) {
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+/// Run `f` and write its output to `out`, converting errors and panics into status codes
+fn call(out: *mut LcpBuffer, f: impl FnOnce() -> Result<Vec<u8>, FfiError>) -> i32 {
+    if out.is_null() {
+        set_last_error("output buffer must be non-null".into());
+        return LCP_ERR_INVALID_ARGUMENT;
+    }
+    unsafe { *out = LcpBuffer::empty() };
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(bz)) => {
+            unsafe { *out = bz.into() };
+            LCP_OK
+        }
+        Ok(Err(FfiError(code, msg))) => {
+            set_last_error(msg);
+            code
+        }
+        Err(_) => {
+            set_last_error("panic occurred".into());
+            LCP_ERR_PANIC
+        }
+    }
+}
+
+unsafe fn as_slice<'a>(data: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    if len == 0 {
+        Ok(&[])
+    } else if data.is_null() {
+        Err(FfiError::invalid_argument("input must be non-null"))
+    } else {
+        Ok(std::slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn as_enclave<'a>(enclave: *const LcpEnclave) -> Result<&'a LcpEnclave, FfiError> {
+    enclave
+        .as_ref()
+        .ok_or_else(|| FfiError::invalid_argument("enclave must be non-null"))
+}
+
+unsafe fn as_path(s: *const c_char) -> Result<PathBuf, FfiError> {
+    if s.is_null() {
+        return Err(FfiError::invalid_argument("path must be non-null"));
+    }
+    Ok(PathBuf::from(
+        CStr::from_ptr(s)
+            .to_str()
+            .map_err(FfiError::invalid_argument)?,
+    ))
+}
+
+/// Decode the request, call the enclave and encode the response
+unsafe fn proto_call<Req: Message + Default, Res: Message, E: ToString>(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+    f: impl FnOnce(&Enclave<RocksDBStore>, Req) -> Result<Res, E>,
+) -> i32 {
+    call(out, || {
+        let enclave = as_enclave(enclave)?;
+        let req = Req::decode(as_slice(req, req_len)?).map_err(FfiError::decode)?;
+        let res = f(&enclave.0, req).map_err(FfiError::enclave)?;
+        Ok(res.encode_to_vec())
+    })
+}
+
+/// Load the enclave and open the state store in `home`.
+///
+/// The host environment is process-wide, so only the first call opens the store and locks `home`.
+/// Opening starts a new instance, so the enclaves opened earlier on the same store can no longer execute commands.
+/// Returns null on failure; the reason is available via `lcp_last_error`.
+#[no_mangle]
+pub unsafe extern "C" fn lcp_enclave_open(
+    home: *const c_char,
+    enclave_path: *const c_char,
+    debug: bool,
+) -> *mut LcpEnclave {
+    let res = catch_unwind(AssertUnwindSafe(|| -> Result<LcpEnclave, FfiError> {
+        let home = as_path(home)?;
+        let enclave_path = as_path(enclave_path)?;
+        if host::get_environment().is_none() {
+            let lock = HomeLock::acquire(&home).map_err(FfiError::enclave)?;
+            let store = HostStore::RocksDB(RocksDBStore::open(home.join("state")));
+            // another thread may have set the environment in the meantime, which is fine
+            let _ = host::set_environment(
+                Environment::new(home.clone(), Arc::new(RwLock::new(store))).with_home_lock(lock),
+            );
+        }
+        let env = host::get_environment().unwrap();
+        let km = EnclaveKeyManager::new(&env.home).map_err(FfiError::enclave)?;
+        let enclave = Enclave::create(&enclave_path, debug, km, env.store.clone())
+            .map_err(|e| FfiError::enclave(format!("failed to create the enclave: {}", e)))?;
+        enclave.start_instance().map_err(FfiError::enclave)?;
+        Ok(LcpEnclave(enclave))
+    }));
+    match res {
+        Ok(Ok(enclave)) => Box::into_raw(Box::new(enclave)),
+        Ok(Err(FfiError(_, msg))) => {
+            set_last_error(msg);
+            std::ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("panic occurred".into());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Destroy the enclave returned by `lcp_enclave_open`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_enclave_close(enclave: *mut LcpEnclave) {
+    if !enclave.is_null() {
+        Box::from_raw(enclave).0.destroy();
+    }
+}
+
+/// Release a buffer returned by this library
+#[no_mangle]
+pub unsafe extern "C" fn lcp_buffer_free(buf: LcpBuffer) {
+    if !buf.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buf.data, buf.len,
+        )));
+    }
+}
+
+/// Copy the message of the last error that occurred in the calling thread to `out`
+///
+/// Returns `LCP_ERR_INVALID_ARGUMENT` if no error has occurred.
+#[no_mangle]
+pub unsafe extern "C" fn lcp_last_error(out: *mut LcpBuffer) -> i32 {
+    match LAST_ERROR.with(|e| e.borrow().clone()) {
+        Some(msg) if !out.is_null() => {
+            *out = msg.into_bytes().into();
+            LCP_OK
+        }
+        _ => LCP_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/// Generate a new enclave key and write its address to `out`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_generate_enclave_key(
+    enclave: *const LcpEnclave,
+    out: *mut LcpBuffer,
+) -> i32 {
+    call(out, || {
+        let res = as_enclave(enclave)?
+            .0
+            .generate_enclave_key(GenerateEnclaveKeyInput)
+            .map_err(FfiError::enclave)?;
+        Ok(res.pub_key.as_address().0.to_vec())
+    })
+}
+
+/// Perform the remote attestation with IAS for the enclave key and write the endorsed report as JSON to `out`
+///
+/// `linkable` selects a linkable EPID quote instead of an unlinkable one.
+#[no_mangle]
+pub unsafe extern "C" fn lcp_ias_remote_attestation(
+    enclave: *const LcpEnclave,
+    target_enclave_key: *const u8,
+    target_enclave_key_len: usize,
+    spid: *const u8,
+    spid_len: usize,
+    ias_key: *const u8,
+    ias_key_len: usize,
+    linkable: bool,
+    out: *mut LcpBuffer,
+) -> i32 {
+    call(out, || {
+        let enclave = as_enclave(enclave)?;
+        let input = IASRemoteAttestationInput {
+            target_enclave_key: Address::try_from(as_slice(
+                target_enclave_key,
+                target_enclave_key_len,
+            )?)
+            .map_err(FfiError::invalid_argument)?,
+            spid: as_slice(spid, spid_len)?.to_vec(),
+            ias_key: as_slice(ias_key, ias_key_len)?.to_vec(),
+            quote_type: if linkable {
+                QuoteSignType::Linkable
+            } else {
+                QuoteSignType::Unlinkable
+            },
+            advisory_policy: None,
+            applied_mitigations: vec![],
+        };
+        let res = enclave
+            .0
+            .ias_remote_attestation(input)
+            .map_err(FfiError::enclave)?;
+        serde_json::to_vec(&res.report).map_err(FfiError::enclave)
+    })
+}
+
+/// Takes `MsgCreateClient` and writes `MsgCreateClientResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_create_client(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(enclave, req, req_len, out, |e, msg: MsgCreateClient| {
+        e.proto_create_client(msg)
+    })
+}
+
+/// Takes `MsgUpdateClient` and writes `MsgUpdateClientResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_update_client(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(enclave, req, req_len, out, |e, msg: MsgUpdateClient| {
+        e.proto_update_client(msg)
+    })
+}
+
+/// Takes `MsgAggregateMessages` and writes `MsgAggregateMessagesResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_aggregate_messages(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(
+        enclave,
+        req,
+        req_len,
+        out,
+        |e, msg: MsgAggregateMessages| e.proto_aggregate_messages(msg),
+    )
+}
+
+/// Takes `MsgVerifyMembership` and writes `MsgVerifyMembershipResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_verify_membership(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(enclave, req, req_len, out, |e, msg: MsgVerifyMembership| {
+        e.proto_verify_membership(msg)
+    })
+}
+
+/// Takes `MsgVerifyNonMembership` and writes `MsgVerifyNonMembershipResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_verify_non_membership(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(
+        enclave,
+        req,
+        req_len,
+        out,
+        |e, msg: MsgVerifyNonMembership| e.proto_verify_non_membership(msg),
+    )
+}
+
+/// Takes `QueryClientRequest` and writes `QueryClientResponse`
+#[no_mangle]
+pub unsafe extern "C" fn lcp_query_client(
+    enclave: *const LcpEnclave,
+    req: *const u8,
+    req_len: usize,
+    out: *mut LcpBuffer,
+) -> i32 {
+    proto_call(enclave, req, req_len, out, |e, msg: QueryClientRequest| {
+        e.proto_query_client(msg)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_and_errors() {
+        unsafe {
+            let mut out = LcpBuffer::empty();
+            assert_eq!(
+                lcp_update_client(std::ptr::null(), std::ptr::null(), 0, &mut out),
+                LCP_ERR_INVALID_ARGUMENT
+            );
+            assert!(out.data.is_null());
+            assert_eq!(lcp_last_error(&mut out), LCP_OK);
+            let msg = std::slice::from_raw_parts(out.data, out.len);
+            assert_eq!(msg, b"enclave must be non-null");
+            lcp_buffer_free(out);
+
+            assert_eq!(
+                lcp_update_client(std::ptr::null(), std::ptr::null(), 0, std::ptr::null_mut()),
+                LCP_ERR_INVALID_ARGUMENT
+            );
+            assert!(lcp_enclave_open(std::ptr::null(), std::ptr::null(), false).is_null());
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::Error;
+use std::{path::PathBuf, process::Command};
+
+pub const SPID_NAME: &str = "SPID";
+pub const IAS_KEY_NAME: &str = "IAS_KEY";
+
+/// Credentials for the Intel Attestation Service
+#[derive(Clone, PartialEq, Eq)]
+pub struct IASCredentials {
+    pub spid: Vec<u8>,
+    pub ias_key: Vec<u8>,
+}
+
+impl core::fmt::Debug for IASCredentials {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IASCredentials")
+            .field("spid", &"<redacted>")
+            .field("ias_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// CredentialsProvider is a source of secrets that the host passes to the enclave
+pub trait CredentialsProvider: Send + Sync {
+    /// Returns a short name of the backend for logging and error messages
+    fn name(&self) -> String;
+
+    /// Returns a credential value for the given name
+    fn get(&self, name: &str) -> Result<String, Error>;
+
+    fn ias_credentials(&self) -> Result<IASCredentials, Error> {
+        Ok(IASCredentials {
+            spid: self.get(SPID_NAME)?.into_bytes(),
+            ias_key: self.get(IAS_KEY_NAME)?.into_bytes(),
+        })
+    }
+}
+
+/// Build a provider from a spec string:
+/// - `env`
+/// - `file:<path>`
+/// - `keychain:<service>`
+/// - `vault:<path>`
+pub fn from_spec(spec: &str) -> Result<Box<dyn CredentialsProvider>, Error> {
+    let (kind, arg) = match spec.split_once(':') {
+        Some((kind, arg)) if !arg.is_empty() => (kind, Some(arg)),
+        Some(_) => return Err(Error::invalid_credentials_spec(spec.to_string())),
+        None => (spec, None),
+    };
+    match (kind, arg) {
+        ("env", None) => Ok(Box::new(EnvCredentialsProvider)),
+        ("file", Some(path)) => Ok(Box::new(FileCredentialsProvider::new(path.into()))),
+        ("keychain", Some(service)) => {
+            Ok(Box::new(KeychainCredentialsProvider::new(service.into())))
+        }
+        ("vault", Some(path)) => Ok(Box::new(VaultCredentialsProvider::new(path.into()))),
+        _ => Err(Error::invalid_credentials_spec(spec.to_string())),
+    }
+}
+
+/// Reads credentials from environment variables
+#[derive(Debug, Default)]
+pub struct EnvCredentialsProvider;
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn name(&self) -> String {
+        "env".into()
+    }
+
+    fn get(&self, name: &str) -> Result<String, Error> {
+        std::env::var(name).map_err(|_| Error::missing_credential(name.into(), self.name()))
+    }
+}
+
+/// Reads credentials from a file that contains `NAME=VALUE` lines
+///
+/// Empty lines and lines starting with `#` are ignored.
+#[derive(Debug)]
+pub struct FileCredentialsProvider {
+    path: PathBuf,
+}
+
+impl FileCredentialsProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl CredentialsProvider for FileCredentialsProvider {
+    fn name(&self) -> String {
+        format!("file:{}", self.path.display())
+    }
+
+    fn get(&self, name: &str) -> Result<String, Error> {
+        let content = std::fs::read_to_string(&self.path)?;
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(k, _)| k.trim() == name)
+            .map(|(_, v)| v.trim().to_string())
+            .ok_or_else(|| Error::missing_credential(name.into(), self.name()))
+    }
+}
+
+/// Reads credentials from the OS keychain
+///
+/// Each credential is stored as a generic password whose account is the credential name.
+/// On macOS this uses `security`, elsewhere `secret-tool` from libsecret.
+#[derive(Debug)]
+pub struct KeychainCredentialsProvider {
+    service: String,
+}
+
+impl KeychainCredentialsProvider {
+    pub fn new(service: String) -> Self {
+        Self { service }
+    }
+}
+
+impl CredentialsProvider for KeychainCredentialsProvider {
+    fn name(&self) -> String {
+        format!("keychain:{}", self.service)
+    }
+
+    fn get(&self, name: &str) -> Result<String, Error> {
+        let mut cmd = if cfg!(target_os = "macos") {
+            let mut cmd = Command::new("security");
+            cmd.args([
+                "find-generic-password",
+                "-s",
+                &self.service,
+                "-a",
+                name,
+                "-w",
+            ]);
+            cmd
+        } else {
+            let mut cmd = Command::new("secret-tool");
+            cmd.args(["lookup", "service", &self.service, "account", name]);
+            cmd
+        };
+        run_command(&mut cmd)?.ok_or_else(|| Error::missing_credential(name.into(), self.name()))
+    }
+}
+
+/// Reads credentials from a HashiCorp Vault KV secret using the `vault` CLI
+///
+/// The CLI picks up `VAULT_ADDR` and `VAULT_TOKEN` from the environment.
+/// Field names are the lower-cased credential names, e.g. `spid` and `ias_key`.
+#[derive(Debug)]
+pub struct VaultCredentialsProvider {
+    path: String,
+}
+
+impl VaultCredentialsProvider {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl CredentialsProvider for VaultCredentialsProvider {
+    fn name(&self) -> String {
+        format!("vault:{}", self.path)
+    }
+
+    fn get(&self, name: &str) -> Result<String, Error> {
+        let field = format!("-field={}", name.to_lowercase());
+        run_command(Command::new("vault").args(["kv", "get", &field, &self.path]))?
+            .ok_or_else(|| Error::missing_credential(name.into(), self.name()))
+    }
+}
+
+/// Runs the command and returns its trimmed stdout, or `None` if the command exited with a failure status
+fn run_command(cmd: &mut Command) -> Result<Option<String>, Error> {
+    let output = cmd.output().map_err(|e| {
+        Error::command_failed(
+            cmd.get_program().to_string_lossy().to_string(),
+            e.to_string(),
+        )
+    })?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!value.is_empty()).then_some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_spec() {
+        assert_eq!(from_spec("env").unwrap().name(), "env");
+        assert_eq!(
+            from_spec("file:/tmp/creds").unwrap().name(),
+            "file:/tmp/creds"
+        );
+        assert_eq!(from_spec("keychain:lcp").unwrap().name(), "keychain:lcp");
+        assert_eq!(
+            from_spec("vault:secret/lcp").unwrap().name(),
+            "vault:secret/lcp"
+        );
+        assert!(from_spec("file:").is_err());
+        assert!(from_spec("env:foo").is_err());
+        assert!(from_spec("unknown").is_err());
+    }
+
+    #[test]
+    fn test_file_credentials_provider() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("credentials");
+        std::fs::write(&path, "# IAS credentials\nSPID = abc\n\nIAS_KEY=def\n").unwrap();
+        let provider = FileCredentialsProvider::new(path.clone());
+        let creds = provider.ias_credentials().unwrap();
+        assert_eq!(creds.spid, b"abc".to_vec());
+        assert_eq!(creds.ias_key, b"def".to_vec());
+        assert!(provider.get("UNKNOWN").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::Error;
+use attestation_report::{DCAPCollateral, DCAPQuoteBundle};
+use std::io::Read;
+
+pub const INTEL_PCS_URL: &str = "https://api.trustedservices.intel.com/sgx/certification/v4";
+pub const INTEL_ROOT_CA_CRL_URL: &str =
+    "https://certificates.trustedservices.intel.com/IntelSGXRootCA.der";
+
+const TCB_INFO_ISSUER_CHAIN: &str = "TCB-Info-Issuer-Chain";
+// PCS v3 uses a different header name for the TCB info issuer chain
+const SGX_TCB_INFO_ISSUER_CHAIN: &str = "SGX-TCB-Info-Issuer-Chain";
+const QE_IDENTITY_ISSUER_CHAIN: &str = "SGX-Enclave-Identity-Issuer-Chain";
+const PCK_CRL_ISSUER_CHAIN: &str = "SGX-PCK-CRL-Issuer-Chain";
+
+/// The CA that issued the PCK certificate of the platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PckCa {
+    Processor,
+    Platform,
+}
+
+impl PckCa {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PckCa::Processor => "processor",
+            PckCa::Platform => "platform",
+        }
+    }
+}
+
+impl std::str::FromStr for PckCa {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "processor" => Ok(PckCa::Processor),
+            "platform" => Ok(PckCa::Platform),
+            _ => Err(Error::invalid_pck_ca(s.to_string())),
+        }
+    }
+}
+
+/// CollateralFetcher fetches DCAP collateral from Intel PCS or a PCCS that serves the same API
+#[derive(Debug, Clone)]
+pub struct CollateralFetcher {
+    pccs_url: String,
+    root_ca_crl_url: String,
+}
+
+impl Default for CollateralFetcher {
+    fn default() -> Self {
+        Self::new(INTEL_PCS_URL.into(), INTEL_ROOT_CA_CRL_URL.into())
+    }
+}
+
+impl CollateralFetcher {
+    /// `pccs_url` is the base URL of the certification API, e.g. `https://localhost:8081/sgx/certification/v4`
+    pub fn new(pccs_url: String, root_ca_crl_url: String) -> Self {
+        Self {
+            pccs_url: pccs_url.trim_end_matches('/').to_string(),
+            root_ca_crl_url,
+        }
+    }
+
+    /// Fetch the collateral for the platform identified by `fmspc` and bundle it with the quote
+    pub fn fetch_bundle(
+        &self,
+        quote: Vec<u8>,
+        fmspc: &str,
+        pck_ca: PckCa,
+    ) -> Result<DCAPQuoteBundle, Error> {
+        Ok(DCAPQuoteBundle {
+            quote,
+            collateral: self.fetch_collateral(fmspc, pck_ca)?,
+        })
+    }
+
+    pub fn fetch_collateral(&self, fmspc: &str, pck_ca: PckCa) -> Result<DCAPCollateral, Error> {
+        let (tcb_info, tcb_info_issuer_chain) = self.get(
+            &format!("{}/tcb?fmspc={}", self.pccs_url, fmspc),
+            &[TCB_INFO_ISSUER_CHAIN, SGX_TCB_INFO_ISSUER_CHAIN],
+        )?;
+        let (qe_identity, qe_identity_issuer_chain) = self.get(
+            &format!("{}/qe/identity", self.pccs_url),
+            &[QE_IDENTITY_ISSUER_CHAIN],
+        )?;
+        let (pck_crl, pck_crl_issuer_chain) = self.get(
+            &format!(
+                "{}/pckcrl?ca={}&encoding=der",
+                self.pccs_url,
+                pck_ca.as_str()
+            ),
+            &[PCK_CRL_ISSUER_CHAIN],
+        )?;
+        let (root_ca_crl, _) = self.get(&self.root_ca_crl_url, &[])?;
+
+        Ok(DCAPCollateral {
+            tcb_info: into_string(tcb_info)?,
+            tcb_info_issuer_chain: tcb_info_issuer_chain.unwrap_or_default(),
+            qe_identity: into_string(qe_identity)?,
+            qe_identity_issuer_chain: qe_identity_issuer_chain.unwrap_or_default(),
+            pck_crl,
+            pck_crl_issuer_chain: pck_crl_issuer_chain.unwrap_or_default(),
+            root_ca_crl: decode_crl(root_ca_crl)?,
+        })
+    }
+
+    /// GET the url and return the body and the URL-decoded value of the first header found in `chain_headers`
+    fn get(&self, url: &str, chain_headers: &[&str]) -> Result<(Vec<u8>, Option<String>), Error> {
+        let res = ureq::get(url)
+            .call()
+            .map_err(|e| Error::http_request(url.to_string(), e.to_string()))?;
+        let chain = match chain_headers.iter().find_map(|h| res.header(h)) {
+            Some(chain) => Some(percent_decode(chain)?),
+            None if chain_headers.is_empty() => None,
+            None => return Err(Error::missing_issuer_chain(url.to_string())),
+        };
+        let mut body = Vec::new();
+        res.into_reader().read_to_end(&mut body)?;
+        Ok((body, chain))
+    }
+}
+
+fn into_string(bz: Vec<u8>) -> Result<String, Error> {
+    String::from_utf8(bz).map_err(|e| Error::invalid_collateral(e.to_string()))
+}
+
+/// Intel serves the root CA CRL in DER while PCCS serves it hex-encoded
+fn decode_crl(bz: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if !bz.is_empty() && bz.iter().all(u8::is_ascii_hexdigit) {
+        hex::decode(&bz).map_err(|e| Error::invalid_collateral(e.to_string()))
+    } else {
+        Ok(bz)
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+    let bz = s.as_bytes();
+    let mut out = Vec::with_capacity(bz.len());
+    let mut i = 0;
+    while i < bz.len() {
+        if bz[i] == b'%' {
+            let hex = bz
+                .get(i + 1..i + 3)
+                .ok_or_else(|| Error::invalid_collateral(format!("invalid escape: {}", s)))?;
+            let hex =
+                std::str::from_utf8(hex).map_err(|e| Error::invalid_collateral(e.to_string()))?;
+            out.push(
+                u8::from_str_radix(hex, 16)
+                    .map_err(|e| Error::invalid_collateral(e.to_string()))?,
+            );
+            i += 3;
+        } else {
+            out.push(bz[i]);
+            i += 1;
+        }
+    }
+    into_string(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(
+            percent_decode("-----BEGIN%20CERTIFICATE-----%0AMIIC").unwrap(),
+            "-----BEGIN CERTIFICATE-----\nMIIC"
+        );
+        assert!(percent_decode("abc%2").is_err());
+        assert!(percent_decode("abc%zz").is_err());
+    }
+
+    #[test]
+    fn test_decode_crl() {
+        assert_eq!(decode_crl(b"3082".to_vec()).unwrap(), vec![0x30, 0x82]);
+        assert_eq!(decode_crl(vec![0x30, 0x82]).unwrap(), vec![0x30, 0x82]);
+    }
+}
@@ -0,0 +1,84 @@
+use flex_error::*;
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        MissingCredential
+        {
+            name: String,
+            source_name: String
+        }
+        |e| {
+            format_args!("credential not found: name={} source={}", e.name, e.source_name)
+        },
+
+        InvalidCredentialsSpec
+        {
+            spec: String
+        }
+        |e| {
+            format_args!("invalid credentials spec: spec={} (expected one of `env`, `file:<path>`, `keychain:<service>`, `vault:<path>`)", e.spec)
+        },
+
+        CommandFailed
+        {
+            command: String,
+            descr: String
+        }
+        |e| {
+            format_args!("external command failed: command={} descr={}", e.command, e.descr)
+        },
+
+        InvalidPckCa
+        {
+            ca: String
+        }
+        |e| {
+            format_args!("invalid PCK CA: ca={} (expected `processor` or `platform`)", e.ca)
+        },
+
+        HttpRequest
+        {
+            url: String,
+            descr: String
+        }
+        |e| {
+            format_args!("HTTP request failed: url={} descr={}", e.url, e.descr)
+        },
+
+        MissingIssuerChain
+        {
+            url: String
+        }
+        |e| {
+            format_args!("issuer chain header not found in the response: url={}", e.url)
+        },
+
+        InvalidCollateral
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid collateral: descr={}", e.descr)
+        },
+
+        HomeLocked
+        {
+            path: String,
+            holder: String
+        }
+        |e| {
+            format_args!("the home directory is used by another process: lock={} holder={}", e.path, e.holder)
+        },
+
+        Io
+        [TraceError<std::io::Error>]
+        |_| { "IO error" }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::io(value)
+    }
+}
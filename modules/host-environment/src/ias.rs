@@ -0,0 +1,15 @@
+/// IASEndpoint is the Intel Attestation Service that the host connects to on behalf of the enclave
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum IASEndpoint {
+    /// `api.trustedservices.intel.com`, over TLS
+    #[default]
+    Intel,
+    /// A mock IAS for tests and local development, which serves plain HTTP
+    /// and signs the reports with a certificate issued by `root_ca`
+    Mock {
+        /// The address of the mock, e.g. `127.0.0.1:8443`
+        addr: String,
+        /// The PEM-encoded root CA of the signing certificate
+        root_ca: Vec<u8>,
+    },
+}
@@ -5,14 +5,43 @@ use std::{
 
 use store::host::HostStore;
 
+pub use errors::Error;
+pub use ias::IASEndpoint;
+pub use lock::HomeLock;
+
+pub mod credentials;
+pub mod dcap;
+mod errors;
+mod ias;
+mod lock;
+
 pub struct Environment {
     pub home: PathBuf,
     pub store: Arc<RwLock<HostStore>>,
+    pub ias_endpoint: IASEndpoint,
+    home_lock: Option<HomeLock>,
 }
 
 impl Environment {
     pub fn new(home: PathBuf, store: Arc<RwLock<HostStore>>) -> Self {
-        Self { home, store }
+        Self {
+            home,
+            store,
+            ias_endpoint: IASEndpoint::default(),
+            home_lock: None,
+        }
+    }
+
+    /// Hold the lock of the home directory while the environment is alive
+    pub fn with_home_lock(mut self, lock: HomeLock) -> Self {
+        self.home_lock = Some(lock);
+        self
+    }
+
+    /// Connect to the given IAS instead of Intel's for the remote attestation
+    pub fn with_ias_endpoint(mut self, endpoint: IASEndpoint) -> Self {
+        self.ias_endpoint = endpoint;
+        self
     }
 
     pub fn get_store(&self) -> RwLockReadGuard<HostStore> {
@@ -0,0 +1,78 @@
+use crate::Error;
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+pub static HOME_LOCK_FILE: &str = "lcp.lock";
+
+/// HomeLock is an exclusive lock on the home directory
+///
+/// Only one process can hold the lock at a time, so two hosts cannot write to the same store.
+/// The lock is released when the process exits, even if it crashes.
+#[derive(Debug)]
+pub struct HomeLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl HomeLock {
+    /// Acquire the lock, or return an error immediately if another process holds it
+    pub fn acquire(home: &Path) -> Result<Self, Error> {
+        std::fs::create_dir_all(home)?;
+        let path = home.join(HOME_LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&path)?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::WouldBlock {
+                return Err(err.into());
+            }
+            let mut holder = String::new();
+            file.read_to_string(&mut holder)?;
+            return Err(Error::home_locked(
+                path.display().to_string(),
+                holder.trim().to_string(),
+            ));
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for HomeLock {
+    fn drop(&mut self) {
+        unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_home_lock() {
+        let tmp_dir = TempDir::new().unwrap();
+        let home = tmp_dir.path().join("home");
+        let lock = HomeLock::acquire(&home).unwrap();
+        // flock locks are per open file description, so a second acquisition fails even in the same process
+        assert!(matches!(
+            HomeLock::acquire(&home).unwrap_err().detail(),
+            crate::errors::ErrorDetail::HomeLocked(_)
+        ));
+        drop(lock);
+        let _ = HomeLock::acquire(&home).unwrap();
+    }
+}
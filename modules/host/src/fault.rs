@@ -0,0 +1,88 @@
+//! Fault injection at the enclave boundary for testing the recovery of the host
+//!
+//! The faults are queued for each boundary and consumed in order, one per call across it,
+//! so a test can reproduce a sequence of failures deterministically.
+use sgx_types::sgx_status_t;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static FAULT_INJECTOR: FaultInjector = FaultInjector::new();
+
+/// Boundary is the direction of the calls that a fault is injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// the calls from the host into the enclave
+    ECall,
+    /// the calls from the enclave out to the host
+    OCall,
+}
+
+/// Fault is a failure of a single call across the boundary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The request is not delivered and the caller gets `SGX_ERROR_UNEXPECTED`
+    Drop,
+    /// The request is delivered after the delay
+    Delay(Duration),
+    /// The request is delivered twice, and the caller gets the response of the second one
+    Duplicate,
+    /// A byte of the request is flipped before it is delivered
+    CorruptRequest,
+    /// A byte of the response is flipped before it is returned to the caller
+    CorruptResponse,
+    /// The request is not delivered and the caller gets the status
+    SgxError(sgx_status_t),
+}
+
+#[derive(Debug)]
+struct FaultInjector {
+    ecall: Mutex<Vec<Fault>>,
+    ocall: Mutex<Vec<Fault>>,
+}
+
+impl FaultInjector {
+    const fn new() -> Self {
+        Self {
+            ecall: Mutex::new(Vec::new()),
+            ocall: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn queue(&self, boundary: Boundary) -> &Mutex<Vec<Fault>> {
+        match boundary {
+            Boundary::ECall => &self.ecall,
+            Boundary::OCall => &self.ocall,
+        }
+    }
+}
+
+/// Inject the fault into the next `times` calls across the boundary after the faults already queued
+pub fn inject_fault(boundary: Boundary, fault: Fault, times: usize) {
+    let mut queue = FAULT_INJECTOR.queue(boundary).lock().unwrap();
+    queue.extend(std::iter::repeat(fault).take(times));
+}
+
+/// Remove the faults that have not been injected yet
+pub fn clear_faults() {
+    FAULT_INJECTOR.ecall.lock().unwrap().clear();
+    FAULT_INJECTOR.ocall.lock().unwrap().clear();
+}
+
+/// Returns the fault to inject into the current call across the boundary, if any
+pub fn next_fault(boundary: Boundary) -> Option<Fault> {
+    let mut queue = FAULT_INJECTOR.queue(boundary).lock().unwrap();
+    if queue.is_empty() {
+        return None;
+    }
+    let fault = queue.remove(0);
+    log::warn!("inject a fault: boundary={:?} fault={:?}", boundary, fault);
+    Some(fault)
+}
+
+/// Flip the bits of the middle byte of the payload
+pub fn corrupt(payload: &mut [u8]) {
+    if !payload.is_empty() {
+        let mid = payload.len() / 2;
+        payload[mid] ^= 0xff;
+    }
+}
@@ -1,5 +1,9 @@
 pub use enclave::{create_enclave, sgx_get_metadata};
 pub use ocalls::{get_environment, ocall_execute_command, set_environment, SetEnvironmentError};
+pub use platform::{probe_pccs, PlatformCapabilities, AESM_SOCKET_PATH};
 
 mod enclave;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
 mod ocalls;
+mod platform;
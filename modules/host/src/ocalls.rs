@@ -1,3 +1,5 @@
+#[cfg(feature = "fault-injection")]
+use crate::fault::Fault;
 use host_environment::Environment;
 use log::*;
 use ocall_commands::{CommandResult, OCallCommand};
@@ -37,31 +39,42 @@ pub extern "C" fn ocall_execute_command(
         return e;
     }
 
-    let cmd: OCallCommand = match bincode::serde::decode_borrowed_from_slice(
-        unsafe { slice::from_raw_parts(command, command_len as usize) },
-        bincode::config::standard(),
-    ) {
-        Ok(cmd) => cmd,
-        Err(e) => {
-            error!("failed to bincode::deserialize: {:?}", e);
-            return sgx_status_t::SGX_ERROR_UNEXPECTED;
+    let command = unsafe { slice::from_raw_parts(command, command_len as usize) };
+
+    #[cfg(feature = "fault-injection")]
+    let fault = crate::fault::next_fault(crate::fault::Boundary::OCall);
+    #[cfg(feature = "fault-injection")]
+    let corrupted;
+    #[cfg(feature = "fault-injection")]
+    let command = match fault.clone() {
+        Some(Fault::Drop) => return sgx_status_t::SGX_ERROR_UNEXPECTED,
+        Some(Fault::SgxError(status)) => return status,
+        Some(Fault::Delay(delay)) => {
+            std::thread::sleep(delay);
+            command
         }
+        Some(Fault::Duplicate) => {
+            let _ = dispatch(command);
+            command
+        }
+        Some(Fault::CorruptRequest) => {
+            corrupted = {
+                let mut bz = command.to_vec();
+                crate::fault::corrupt(&mut bz);
+                bz
+            };
+            &corrupted
+        }
+        _ => command,
     };
 
-    let (status, result) = match ocall_handler::dispatch(
-        HOST_ENVIRONMENT
-            .get()
-            .expect("you must initialize HOST_ENVIRONMENT before executing the command"),
-        cmd,
-    ) {
-        Ok(result) => (sgx_status_t::SGX_SUCCESS, result),
-        Err(e) => (
-            sgx_status_t::SGX_ERROR_UNEXPECTED,
-            CommandResult::CommandError(format!("{:?}", e)),
-        ),
+    let (status, result) = match dispatch(command) {
+        Ok(res) => res,
+        Err(status) => return status,
     };
 
-    let res = match bincode::serde::encode_to_vec(&result, bincode::config::standard()) {
+    #[allow(unused_mut)]
+    let mut res = match bincode::serde::encode_to_vec(&result, bincode::config::standard()) {
         Ok(res) => {
             if res.len() > output_buf_maxlen as usize {
                 error!(
@@ -79,12 +92,44 @@ pub extern "C" fn ocall_execute_command(
         }
     };
 
+    #[cfg(feature = "fault-injection")]
+    if fault == Some(Fault::CorruptResponse) {
+        crate::fault::corrupt(&mut res);
+    }
+
     unsafe { std::ptr::copy_nonoverlapping(res.as_ptr(), output_buf, res.len()) };
     *output_len = res.len() as u32;
 
     status
 }
 
+/// Decode the command and execute it with the host environment
+fn dispatch(command: &[u8]) -> Result<(sgx_status_t, CommandResult), sgx_status_t> {
+    let cmd: OCallCommand =
+        match bincode::serde::decode_borrowed_from_slice(command, bincode::config::standard()) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                error!("failed to bincode::deserialize: {:?}", e);
+                return Err(sgx_status_t::SGX_ERROR_UNEXPECTED);
+            }
+        };
+
+    Ok(
+        match ocall_handler::dispatch(
+            HOST_ENVIRONMENT
+                .get()
+                .expect("you must initialize HOST_ENVIRONMENT before executing the command"),
+            cmd,
+        ) {
+            Ok(result) => (sgx_status_t::SGX_SUCCESS, result),
+            Err(e) => (
+                sgx_status_t::SGX_ERROR_UNEXPECTED,
+                CommandResult::CommandError(format!("{:?}", e)),
+            ),
+        },
+    )
+}
+
 fn validate_const_ptr(ptr: *const u8, ptr_len: usize) -> SgxResult<()> {
     if ptr.is_null() || ptr_len == 0 {
         warn!("Tried to access an empty pointer - ptr.is_null()");
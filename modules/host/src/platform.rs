@@ -0,0 +1,170 @@
+use sgx_types::sgx_status_t;
+use std::fmt::Display;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The socket on which aesmd serves the launch and quoting requests
+pub const AESM_SOCKET_PATH: &str = "/var/run/aesmd/aesm.socket";
+
+/// The device files of the in-kernel driver, the DCAP driver and the legacy out-of-tree driver
+const SGX_DEVICE_PATHS: [&str; 3] = ["/dev/sgx_enclave", "/dev/sgx/enclave", "/dev/isgx"];
+
+const PCCS_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// PlatformCapabilities is what the host supports to launch and attest an enclave
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformCapabilities {
+    /// The CPU supports SGX and it is enabled in the BIOS
+    pub sgx: bool,
+    pub sgx1: bool,
+    pub sgx2: bool,
+    /// Flexible Launch Control, which DCAP attestation requires
+    pub flc: bool,
+    /// The device file of the SGX driver, if any
+    pub sgx_device: Option<PathBuf>,
+    /// aesmd accepts connections on its socket
+    pub aesm_available: bool,
+}
+
+impl PlatformCapabilities {
+    /// Probe the CPU, the driver and aesmd of the host
+    pub fn probe() -> Self {
+        let (sgx, sgx1, sgx2, flc) = probe_cpu();
+        Self {
+            sgx,
+            sgx1,
+            sgx2,
+            flc,
+            sgx_device: SGX_DEVICE_PATHS
+                .iter()
+                .map(Path::new)
+                .find(|p| p.exists())
+                .map(Path::to_path_buf),
+            aesm_available: UnixStream::connect(AESM_SOCKET_PATH).is_ok(),
+        }
+    }
+
+    /// Returns an error describing why the platform cannot produce DCAP quotes
+    pub fn check_dcap(&self) -> Result<(), String> {
+        if !self.flc {
+            return Err("FLC unsupported — use EPID (IAS) attestation instead of DCAP".into());
+        }
+        self.check_aesm()
+    }
+
+    /// Returns an error describing why the platform cannot produce EPID quotes
+    pub fn check_epid(&self) -> Result<(), String> {
+        self.check_aesm()
+    }
+
+    fn check_aesm(&self) -> Result<(), String> {
+        if !self.aesm_available {
+            return Err(format!(
+                "aesmd not running: no connection on {} (try `systemctl start aesmd`)",
+                AESM_SOCKET_PATH
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns an actionable description of a failure to launch the enclave
+    pub fn diagnose_launch_error(&self, status: sgx_status_t) -> String {
+        let hint = match status {
+            sgx_status_t::SGX_ERROR_NO_DEVICE if !self.sgx => {
+                "SGX unsupported: the CPU does not support SGX or it is disabled in the BIOS".into()
+            }
+            sgx_status_t::SGX_ERROR_NO_DEVICE => match &self.sgx_device {
+                Some(device) => format!(
+                    "cannot open the SGX device {:?}: check that the user has the permission to access it",
+                    device
+                ),
+                None => format!(
+                    "SGX driver not installed: none of {:?} exists",
+                    SGX_DEVICE_PATHS
+                ),
+            },
+            sgx_status_t::SGX_ERROR_SERVICE_UNAVAILABLE
+            | sgx_status_t::SGX_ERROR_SERVICE_TIMEOUT
+            | sgx_status_t::SGX_ERROR_SERVICE_INVALID_PRIVILEGE
+                if !self.aesm_available =>
+            {
+                self.check_aesm().unwrap_err()
+            }
+            sgx_status_t::SGX_ERROR_INVALID_LAUNCH_TOKEN
+            | sgx_status_t::SGX_ERROR_SERVICE_INVALID_PRIVILEGE
+                if !self.flc =>
+            {
+                "FLC unsupported: the enclave must be launched with a launch token from aesmd".into()
+            }
+            sgx_status_t::SGX_ERROR_ENCLAVE_FILE_ACCESS => {
+                "cannot read the enclave file: check the path and its permission".into()
+            }
+            sgx_status_t::SGX_ERROR_OUT_OF_EPC => {
+                "out of EPC: stop other enclaves or reduce the heap size of the enclave".into()
+            }
+            _ => return format!("{:?} on {}", status, self),
+        };
+        format!("{:?}: {}", status, hint)
+    }
+}
+
+impl Display for PlatformCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "platform(sgx={} sgx1={} sgx2={} flc={} sgx_device={:?} aesm_available={})",
+            self.sgx, self.sgx1, self.sgx2, self.flc, self.sgx_device, self.aesm_available
+        )
+    }
+}
+
+/// Returns an error if no TCP connection can be made to the host of the PCCS URL
+pub fn probe_pccs(pccs_url: &str) -> Result<(), String> {
+    let (scheme, rest) = pccs_url
+        .split_once("://")
+        .ok_or_else(|| format!("invalid PCCS URL: {}", pccs_url))?;
+    let authority = rest.split('/').next().unwrap_or_default();
+    let addr = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        let port = if scheme == "http" { 80 } else { 443 };
+        format!("{}:{}", authority, port)
+    };
+    let addrs = addr
+        .to_socket_addrs()
+        .map_err(|e| format!("PCCS unreachable: cannot resolve {}: {}", addr, e))?;
+    for sock_addr in addrs {
+        if TcpStream::connect_timeout(&sock_addr, PCCS_CONNECT_TIMEOUT).is_ok() {
+            return Ok(());
+        }
+    }
+    Err(format!("PCCS unreachable: cannot connect to {}", addr))
+}
+
+/// Returns whether the CPU supports SGX, SGX1, SGX2 and FLC
+#[cfg(target_arch = "x86_64")]
+fn probe_cpu() -> (bool, bool, bool, bool) {
+    use std::arch::x86_64::__cpuid_count;
+    // SAFETY: cpuid is available on every x86_64 CPU
+    let features = unsafe { __cpuid_count(0x7, 0) };
+    let sgx = features.ebx & (1 << 2) != 0;
+    let flc = features.ecx & (1 << 30) != 0;
+    if !sgx {
+        return (false, false, false, flc);
+    }
+    // SAFETY: the SGX leaf is available since the CPU supports SGX
+    let sgx_leaf = unsafe { __cpuid_count(0x12, 0) };
+    (
+        sgx,
+        sgx_leaf.eax & (1 << 0) != 0,
+        sgx_leaf.eax & (1 << 1) != 0,
+        flc,
+    )
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn probe_cpu() -> (bool, bool, bool, bool) {
+    (false, false, false, false)
+}
@@ -14,6 +14,14 @@ define_error! {
             format_args!("Unattested enclave key: descr={}", e.descr)
         },
 
+        InvalidKeyRotation
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("Invalid key rotation: descr={}", e.descr)
+        },
+
         Crypto
         [crypto::Error]
         |_| { "Crypto error" },
@@ -38,12 +46,28 @@ define_error! {
         [TraceError<rusqlite::Error>]
         |_| { "rusqlite error" },
 
+        Keystore
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("keystore error: descr={}", e.descr)
+        },
+
         MutexLock
         {
             descr: String
         }
         |e| {
             format_args!("mutex lock error: descr={}", e.descr)
+        },
+
+        InvalidEvidenceType
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid evidence type: expected `avr` or `dcap`, but got `{}`", e.descr)
         }
     }
 }
@@ -0,0 +1,219 @@
+use crate::Error;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::process::{Command, Stdio};
+
+/// Environment variable that selects the keystore which holds the wrapping key:
+/// - `keychain:<service>`: the OS keychain, which is the default with the service `lcp`
+/// - `env`: a hex-encoded 32 bytes key in `LCP_KM_WRAPPING_KEY`
+/// - `none`: sealed keys are stored as they are
+pub const KEYSTORE_SPEC_ENV: &str = "LCP_KM_KEYSTORE";
+pub const WRAPPING_KEY_ENV: &str = "LCP_KM_WRAPPING_KEY";
+pub const DEFAULT_KEYSTORE_SPEC: &str = "keychain:lcp";
+
+/// Account name of the wrapping key in the OS keychain
+const WRAPPING_KEY_ACCOUNT: &str = "km-wrapping-key";
+const WRAPPED_KEY_PREFIX: &[u8] = b"lcp-wrapped-v1:";
+
+/// KeyWrapper encrypts sealed enclave keys with a key held by the host keystore
+///
+/// The sealing key of a simulated enclave is not secret, so sealed keys of non-SGX builds
+/// are encrypted again before they are written to the database.
+pub struct KeyWrapper {
+    key: LessSafeKey,
+}
+
+impl KeyWrapper {
+    pub fn new(key: &[u8; 32]) -> Result<Self, Error> {
+        let key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| Error::keystore("invalid wrapping key".into()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// Build a wrapper from the spec in `LCP_KM_KEYSTORE`, or `DEFAULT_KEYSTORE_SPEC` if it's not set
+    pub fn from_env() -> Result<Option<Self>, Error> {
+        match std::env::var(KEYSTORE_SPEC_ENV) {
+            Ok(spec) => Self::from_spec(&spec),
+            Err(_) => Self::from_spec(DEFAULT_KEYSTORE_SPEC),
+        }
+    }
+
+    /// Build a wrapper from the keystore spec. Returns `None` if the spec is `none`.
+    pub fn from_spec(spec: &str) -> Result<Option<Self>, Error> {
+        let key = match spec.split_once(':') {
+            None if spec == "none" => return Ok(None),
+            None if spec == "env" => {
+                let key = std::env::var(WRAPPING_KEY_ENV).map_err(|_| {
+                    Error::keystore(format!("{} is not set", WRAPPING_KEY_ENV))
+                })?;
+                decode_key(&key)?
+            }
+            Some(("keychain", service)) if !service.is_empty() => keychain_wrapping_key(service)?,
+            _ => {
+                return Err(Error::keystore(format!(
+                    "invalid keystore spec: spec={} (expected one of `keychain:<service>`, `env`, `none`)",
+                    spec
+                )))
+            }
+        };
+        Ok(Some(Self::new(&key)?))
+    }
+
+    /// Returns true if the data was encrypted by a `KeyWrapper`
+    pub fn is_wrapped(data: &[u8]) -> bool {
+        data.starts_with(WRAPPED_KEY_PREFIX)
+    }
+
+    /// Encrypt the data into `prefix || nonce || ciphertext || tag`
+    pub fn wrap_key(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| Error::keystore("failed to generate a nonce".into()))?;
+        let mut in_out = data.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(WRAPPED_KEY_PREFIX),
+                &mut in_out,
+            )
+            .map_err(|_| Error::keystore("failed to wrap the key".into()))?;
+        Ok([WRAPPED_KEY_PREFIX, nonce.as_slice(), in_out.as_slice()].concat())
+    }
+
+    /// Decrypt the data returned by `wrap_key`
+    pub fn unwrap_key(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let data = data
+            .strip_prefix(WRAPPED_KEY_PREFIX)
+            .filter(|data| data.len() >= NONCE_LEN)
+            .ok_or_else(|| Error::keystore("the key is not wrapped".into()))?;
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(
+                Nonce::try_assume_unique_for_key(nonce)
+                    .map_err(|_| Error::keystore("invalid nonce".into()))?,
+                Aad::from(WRAPPED_KEY_PREFIX),
+                &mut in_out,
+            )
+            .map_err(|_| {
+                Error::keystore("failed to unwrap the key: the wrapping key may differ".into())
+            })?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// Load the wrapping key from the OS keychain, or generate and store a new one if it doesn't exist
+///
+/// On macOS this uses `security`, elsewhere `secret-tool` from libsecret.
+fn keychain_wrapping_key(service: &str) -> Result<[u8; 32], Error> {
+    let mut lookup = if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("security");
+        cmd.args([
+            "find-generic-password",
+            "-s",
+            service,
+            "-a",
+            WRAPPING_KEY_ACCOUNT,
+            "-w",
+        ]);
+        cmd
+    } else {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args([
+            "lookup",
+            "service",
+            service,
+            "account",
+            WRAPPING_KEY_ACCOUNT,
+        ]);
+        cmd
+    };
+    let output = lookup
+        .output()
+        .map_err(|e| Error::keystore(format!("failed to run the keychain command: {}", e)))?;
+    let stored = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !stored.is_empty() {
+        return decode_key(&stored);
+    }
+
+    let mut key = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| Error::keystore("failed to generate a wrapping key".into()))?;
+    let encoded = hex::encode(key);
+    let status = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args([
+                "add-generic-password",
+                "-s",
+                service,
+                "-a",
+                WRAPPING_KEY_ACCOUNT,
+                "-w",
+                &encoded,
+            ])
+            .status()
+    } else {
+        Command::new("secret-tool")
+            .args([
+                "store",
+                "--label=LCP key manager wrapping key",
+                "service",
+                service,
+                "account",
+                WRAPPING_KEY_ACCOUNT,
+            ])
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                use std::io::Write;
+                child
+                    .stdin
+                    .take()
+                    .expect("stdin must be piped")
+                    .write_all(encoded.as_bytes())?;
+                child.wait()
+            })
+    }
+    .map_err(|e| Error::keystore(format!("failed to run the keychain command: {}", e)))?;
+    if !status.success() {
+        return Err(Error::keystore(format!(
+            "failed to store the wrapping key in the keychain: service={}",
+            service
+        )));
+    }
+    Ok(key)
+}
+
+fn decode_key(s: &str) -> Result<[u8; 32], Error> {
+    hex::decode(s.trim().trim_start_matches("0x"))
+        .ok()
+        .and_then(|bz| bz.try_into().ok())
+        .ok_or_else(|| Error::keystore("the wrapping key must be 32 bytes hex".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_wrapper() {
+        let wrapper = KeyWrapper::new(&[1u8; 32]).unwrap();
+        let wrapped = wrapper.wrap_key(b"sealed key").unwrap();
+        assert!(KeyWrapper::is_wrapped(&wrapped));
+        assert_eq!(wrapper.unwrap_key(&wrapped).unwrap(), b"sealed key");
+        // nonces are random, so wrapping the same data twice yields different outputs
+        assert_ne!(wrapper.wrap_key(b"sealed key").unwrap(), wrapped);
+
+        let other = KeyWrapper::new(&[2u8; 32]).unwrap();
+        assert!(other.unwrap_key(&wrapped).is_err());
+        assert!(wrapper.unwrap_key(b"sealed key").is_err());
+
+        assert!(KeyWrapper::from_spec("none").unwrap().is_none());
+        assert!(KeyWrapper::from_spec("file:/tmp/key").is_err());
+    }
+}
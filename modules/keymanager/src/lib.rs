@@ -1,6 +1,8 @@
 pub mod errors;
+pub mod keystore;
 pub use crate::errors::Error;
-use attestation_report::EndorsedAttestationVerificationReport;
+pub use crate::keystore::KeyWrapper;
+use attestation_report::{DCAPQuoteBundle, EndorsedAttestationVerificationReport};
 use crypto::{Address, SealedEnclaveKey};
 use lcp_types::proto::lcp::service::enclave::v1::EnclaveKeyInfo as ProtoEnclaveKeyInfo;
 use lcp_types::{Mrenclave, Time};
@@ -14,6 +16,7 @@ pub static KEY_MANAGER_DB: &str = "km.sqlite";
 
 pub struct EnclaveKeyManager {
     conn: Mutex<Connection>,
+    wrapper: Option<KeyWrapper>,
 }
 
 impl EnclaveKeyManager {
@@ -21,22 +24,56 @@ impl EnclaveKeyManager {
         let km_db = home_dir.join(KEY_MANAGER_DB);
         let db_exists = km_db.exists();
         let conn = Mutex::new(Connection::open(&km_db)?);
-        let this = Self { conn };
+        #[cfg(feature = "sgx-sw")]
+        let wrapper = KeyWrapper::from_env()?;
+        #[cfg(not(feature = "sgx-sw"))]
+        let wrapper = None;
+        let this = Self { conn, wrapper };
         if !db_exists {
             this.init_db()?;
             info!("initialized Key Manager: {:?}", km_db);
         }
+        this.migrate_db()?;
         Ok(this)
     }
 
     #[cfg(test)]
     pub fn new_in_memory() -> Result<Self, Error> {
         let conn = Mutex::new(Connection::open_in_memory()?);
-        let this = Self { conn };
+        let this = Self {
+            conn,
+            wrapper: None,
+        };
         this.init_db()?;
+        this.migrate_db()?;
         Ok(this)
     }
 
+    /// Wrap the sealed keys with the wrapper before they are written to the database
+    ///
+    /// Keys that were saved without a wrapper can still be loaded.
+    pub fn with_key_wrapper(mut self, wrapper: KeyWrapper) -> Self {
+        self.wrapper = Some(wrapper);
+        self
+    }
+
+    fn encode_sealed_ek(&self, sealed_ek: &SealedEnclaveKey) -> Result<Vec<u8>, Error> {
+        match self.wrapper.as_ref() {
+            Some(wrapper) => wrapper.wrap_key(&sealed_ek.to_vec()),
+            None => Ok(sealed_ek.to_vec()),
+        }
+    }
+
+    fn decode_sealed_ek(&self, bz: &[u8]) -> Result<SealedEnclaveKey, Error> {
+        if !KeyWrapper::is_wrapped(bz) {
+            return Ok(SealedEnclaveKey::new_from_bytes(bz)?);
+        }
+        let wrapper = self.wrapper.as_ref().ok_or_else(|| {
+            Error::keystore("the sealed key is wrapped but no keystore is configured".into())
+        })?;
+        Ok(SealedEnclaveKey::new_from_bytes(&wrapper.unwrap_key(bz)?)?)
+    }
+
     fn init_db(&self) -> Result<(), Error> {
         let conn = self
             .conn
@@ -64,6 +101,53 @@ impl EnclaveKeyManager {
         Ok(())
     }
 
+    /// Create the tables that were added after the initial schema if they do not exist yet
+    ///
+    /// This is separated from `init_db` so that databases created by older versions get them too.
+    fn migrate_db(&self) -> Result<(), Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        conn.execute_batch(
+            r#"
+            BEGIN;
+            CREATE TABLE IF NOT EXISTS key_rotations (
+                outgoing_address VARCHAR NOT NULL PRIMARY KEY,
+                incoming_address VARCHAR NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (DATETIME('now', 'localtime'))
+            );
+            CREATE TABLE IF NOT EXISTS dcap_bundles (
+                ek_address VARCHAR NOT NULL PRIMARY KEY,
+                bundle TEXT NOT NULL,
+                root_ca_hash VARCHAR NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (DATETIME('now', 'localtime'))
+            );
+            CREATE TABLE IF NOT EXISTS standby_keys (
+                ek_address VARCHAR NOT NULL PRIMARY KEY,
+                created_at TEXT NOT NULL DEFAULT (DATETIME('now', 'localtime'))
+            );
+            CREATE TABLE IF NOT EXISTS store_checkpoint (
+                id INTEGER NOT NULL PRIMARY KEY CHECK (id = 0),
+                sequence INTEGER NOT NULL,
+                sealed_sequence BLOB NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT (DATETIME('now', 'localtime'))
+            );
+            CREATE TABLE IF NOT EXISTS revoked_keys (
+                ek_address VARCHAR NOT NULL PRIMARY KEY,
+                reason TEXT NOT NULL,
+                revoked_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS key_epoch (
+                id INTEGER NOT NULL PRIMARY KEY CHECK (id = 0),
+                epoch INTEGER NOT NULL
+            );
+            COMMIT;
+            "#,
+        )?;
+        Ok(())
+    }
+
     /// Load a sealed enclave key by address
     pub fn load(&self, address: Address) -> Result<SealedEnclaveKeyInfo, Error> {
         let conn = self
@@ -76,10 +160,11 @@ impl EnclaveKeyManager {
         let key_info = stmt.query_row(params![address.to_hex_string()], |row| {
             Ok(SealedEnclaveKeyInfo {
                 address,
-                sealed_ek: SealedEnclaveKey::new_from_bytes(row.get::<_, Vec<u8>>(0)?.as_slice())
+                sealed_ek: self
+                    .decode_sealed_ek(row.get::<_, Vec<u8>>(0)?.as_slice())
                     .map_err(|e| {
-                    rusqlite::Error::FromSqlConversionFailure(0, Type::Blob, e.into())
-                })?,
+                        rusqlite::Error::FromSqlConversionFailure(0, Type::Blob, e.into())
+                    })?,
                 mrenclave: Mrenclave(row.get(1)?),
                 avr: match (row.get(2), row.get(3), row.get(4)) {
                     (Ok(None), Ok(None), Ok(None)) => None,
@@ -116,7 +201,7 @@ impl EnclaveKeyManager {
         )?;
         let _ = stmt.execute(params![
             address.to_hex_string(),
-            sealed_ek.to_vec(),
+            self.encode_sealed_ek(&sealed_ek)?,
             mrenclave.deref()
         ])?;
         Ok(())
@@ -148,6 +233,8 @@ impl EnclaveKeyManager {
     }
 
     /// Returns a list of available enclave keys
+    ///
+    /// Standby keys are not available until they are activated, and revoked keys are never available.
     pub fn available_keys(&self, mrenclave: Mrenclave) -> Result<Vec<SealedEnclaveKeyInfo>, Error> {
         let conn = self
             .conn
@@ -158,6 +245,8 @@ impl EnclaveKeyManager {
             SELECT ek_address, ek_sealed, mrenclave, avr, signature, signing_cert
             FROM enclave_keys
             WHERE attested_at IS NOT NULL AND mrenclave = ?1
+                AND ek_address NOT IN (SELECT ek_address FROM standby_keys)
+                AND ek_address NOT IN (SELECT ek_address FROM revoked_keys)
             ORDER BY attested_at DESC
             "#,
         )?;
@@ -165,12 +254,11 @@ impl EnclaveKeyManager {
             .query_map(params![mrenclave.deref()], |row| {
                 Ok(SealedEnclaveKeyInfo {
                     address: Address::from_hex_string(&row.get::<_, String>(0)?).unwrap(),
-                    sealed_ek: SealedEnclaveKey::new_from_bytes(
-                        row.get::<_, Vec<u8>>(1)?.as_slice(),
-                    )
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(1, Type::Blob, e.into())
-                    })?,
+                    sealed_ek: self
+                        .decode_sealed_ek(row.get::<_, Vec<u8>>(1)?.as_slice())
+                        .map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(1, Type::Blob, e.into())
+                        })?,
                     mrenclave: Mrenclave(row.get(2)?),
                     avr: Some(EndorsedAttestationVerificationReport {
                         avr: row.get(3)?,
@@ -196,12 +284,11 @@ impl EnclaveKeyManager {
             .query_map(params![], |row| {
                 Ok(SealedEnclaveKeyInfo {
                     address: Address::from_hex_string(&row.get::<_, String>(0)?).unwrap(),
-                    sealed_ek: SealedEnclaveKey::new_from_bytes(
-                        row.get::<_, Vec<u8>>(1)?.as_slice(),
-                    )
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(1, Type::Blob, e.into())
-                    })?,
+                    sealed_ek: self
+                        .decode_sealed_ek(row.get::<_, Vec<u8>>(1)?.as_slice())
+                        .map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(1, Type::Blob, e.into())
+                        })?,
                     mrenclave: Mrenclave(row.get(2)?),
                     avr: match (row.get(3), row.get(4), row.get(5)) {
                         (Ok(None), Ok(None), Ok(None)) => None,
@@ -223,6 +310,426 @@ impl EnclaveKeyManager {
         Ok(key_infos)
     }
 
+    /// Save a DCAP quote bundle that the enclave has validated for the enclave key
+    pub fn save_dcap_bundle(
+        &self,
+        address: Address,
+        bundle: &DCAPQuoteBundle,
+        root_ca_hash: [u8; 32],
+    ) -> Result<(), Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare(
+            "INSERT OR REPLACE INTO dcap_bundles (ek_address, bundle, root_ca_hash) VALUES (?1, ?2, ?3)",
+        )?;
+        stmt.execute(params![
+            address.to_hex_string(),
+            serde_json::to_string(bundle)?,
+            hex::encode(root_ca_hash)
+        ])?;
+        Ok(())
+    }
+
+    /// Load the DCAP quote bundle of the enclave key if it exists
+    pub fn load_dcap_bundle(&self, address: Address) -> Result<Option<DCAPQuoteBundle>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare("SELECT bundle FROM dcap_bundles WHERE ek_address = ?1")?;
+        let mut rows = stmt.query(params![address.to_hex_string()])?;
+        match rows.next()? {
+            Some(row) => {
+                let bundle: String = row.get(0)?;
+                Ok(Some(serde_json::from_str(&bundle)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the attestation evidences of the enclave keys in descending order of the attestation time
+    ///
+    /// `address` and `evidence_type` narrow the evidences to the key and the type if they are given,
+    /// and the evidences attested before `min_attested_at`(UNIX time in seconds) are excluded.
+    pub fn attestations(
+        &self,
+        address: Option<Address>,
+        evidence_type: Option<EvidenceType>,
+        min_attested_at: u64,
+    ) -> Result<Vec<AttestationRecord>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let address = address.map(|a| a.to_hex_string());
+        let mut rows = Vec::new();
+        if evidence_type != Some(EvidenceType::Dcap) {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT ek_address, mrenclave, CAST(attested_at AS INTEGER), avr, signature, signing_cert
+                FROM enclave_keys
+                WHERE attested_at IS NOT NULL
+                    AND CAST(attested_at AS INTEGER) >= ?1
+                    AND (?2 IS NULL OR ek_address = ?2)
+                "#,
+            )?;
+            let avrs = stmt
+                .query_map(params![min_attested_at, address], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        Mrenclave(row.get(1)?),
+                        row.get::<_, i64>(2)?,
+                        AttestationEvidence::Avr(EndorsedAttestationVerificationReport {
+                            avr: row.get(3)?,
+                            signature: row.get(4)?,
+                            signing_cert: row.get(5)?,
+                        }),
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.extend(avrs);
+        }
+        if evidence_type != Some(EvidenceType::Avr) {
+            // the bundles are saved when the enclave has validated them, so the time is that of the attestation
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT d.ek_address, k.mrenclave, CAST(strftime('%s', d.created_at, 'utc') AS INTEGER), d.bundle
+                FROM dcap_bundles d INNER JOIN enclave_keys k ON d.ek_address = k.ek_address
+                WHERE CAST(strftime('%s', d.created_at, 'utc') AS INTEGER) >= ?1
+                    AND (?2 IS NULL OR d.ek_address = ?2)
+                "#,
+            )?;
+            let bundles = stmt
+                .query_map(params![min_attested_at, address], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        Mrenclave(row.get(1)?),
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (address, mrenclave, attested_at, bundle) in bundles {
+                rows.push((
+                    address,
+                    mrenclave,
+                    attested_at,
+                    AttestationEvidence::Dcap(serde_json::from_str(&bundle)?),
+                ));
+            }
+        }
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                EXISTS(SELECT 1 FROM standby_keys WHERE ek_address = ?1),
+                EXISTS(SELECT 1 FROM revoked_keys WHERE ek_address = ?1)
+            "#,
+        )?;
+        let mut records = rows
+            .into_iter()
+            .map(|(address, mrenclave, attested_at, evidence)| {
+                let (standby, revoked) = stmt.query_row(params![address], |row| {
+                    Ok((row.get::<_, bool>(0)?, row.get::<_, bool>(1)?))
+                })?;
+                Ok(AttestationRecord {
+                    address: Address::from_hex_string(&address)?,
+                    mrenclave,
+                    attested_at: attested_at as u64,
+                    evidence,
+                    standby,
+                    revoked,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        records.sort_by(|a, b| {
+            b.attested_at
+                .cmp(&a.attested_at)
+                .then_with(|| a.address.0.cmp(&b.address.0))
+                .then_with(|| a.evidence.evidence_type().cmp(&b.evidence.evidence_type()))
+        });
+        Ok(records)
+    }
+
+    /// Save the sealed sequence of the state store as the checkpoint unless a later one has been saved
+    ///
+    /// The checkpoint is kept apart from the state store, so that the enclave can detect a rollback of the store.
+    pub fn save_store_checkpoint(
+        &self,
+        sequence: u64,
+        sealed_sequence: &[u8],
+    ) -> Result<(), Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO store_checkpoint (id, sequence, sealed_sequence) VALUES (0, ?1, ?2) \
+             ON CONFLICT(id) DO UPDATE SET sequence = excluded.sequence, sealed_sequence = excluded.sealed_sequence, \
+             updated_at = DATETIME('now', 'localtime') WHERE excluded.sequence > store_checkpoint.sequence",
+        )?;
+        stmt.execute(params![sequence as i64, sealed_sequence])?;
+        Ok(())
+    }
+
+    /// Load the sequence and the sealed sequence of the latest checkpoint of the state store if it exists
+    pub fn load_store_checkpoint(&self) -> Result<Option<(u64, Vec<u8>)>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt =
+            conn.prepare("SELECT sequence, sealed_sequence FROM store_checkpoint WHERE id = 0")?;
+        let mut rows = stmt.query([])?;
+        match rows.next()? {
+            Some(row) => {
+                let sequence: i64 = row.get(0)?;
+                Ok(Some((sequence as u64, row.get(1)?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Begin a rotation from the outgoing key to the incoming key
+    ///
+    /// Until `end_key_rotation` is called, commitments signed by the outgoing key are co-signed by the incoming key.
+    pub fn begin_key_rotation(&self, outgoing: Address, incoming: Address) -> Result<(), Error> {
+        if outgoing == incoming {
+            return Err(Error::invalid_key_rotation(format!(
+                "outgoing and incoming keys must be different: address={}",
+                outgoing
+            )));
+        }
+        // ensure that both keys are managed by this key manager
+        let _ = self.load(outgoing)?;
+        let _ = self.load(incoming)?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare(
+            "INSERT OR REPLACE INTO key_rotations (outgoing_address, incoming_address) VALUES (?1, ?2)",
+        )?;
+        stmt.execute(params![outgoing.to_hex_string(), incoming.to_hex_string()])?;
+        Self::advance_key_epoch(&conn)?;
+        Ok(())
+    }
+
+    /// End the rotation of the outgoing key. Returns false if there is no rotation in progress.
+    pub fn end_key_rotation(&self, outgoing: Address) -> Result<bool, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare("DELETE FROM key_rotations WHERE outgoing_address = ?1")?;
+        let count = stmt.execute(params![outgoing.to_hex_string()])?;
+        if count > 0 {
+            Self::advance_key_epoch(&conn)?;
+        }
+        Ok(count > 0)
+    }
+
+    /// Returns the incoming key that co-signs with the given key if it is being rotated out
+    pub fn co_signer(&self, outgoing: Address) -> Result<Option<Address>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt =
+            conn.prepare("SELECT incoming_address FROM key_rotations WHERE outgoing_address = ?1")?;
+        let mut rows = stmt.query(params![outgoing.to_hex_string()])?;
+        match rows.next()? {
+            Some(row) => {
+                let incoming: String = row.get(0)?;
+                Ok(Some(Address::from_hex_string(&incoming)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the number of the changes of the keys that sign the commitments, i.e. the rotations and the revocations
+    ///
+    /// The commitments that the host keeps, e.g. in a cache, must not be served across a change,
+    /// since their signer may have been revoked or be missing the co-signer of a rotation.
+    pub fn key_epoch(&self) -> Result<u64, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare("SELECT epoch FROM key_epoch WHERE id = 0")?;
+        let mut rows = stmt.query([])?;
+        match rows.next()? {
+            Some(row) => Ok(row.get::<_, i64>(0)? as u64),
+            None => Ok(0),
+        }
+    }
+
+    fn advance_key_epoch(conn: &Connection) -> Result<(), Error> {
+        conn.execute(
+            "INSERT INTO key_epoch (id, epoch) VALUES (0, 1) ON CONFLICT(id) DO UPDATE SET epoch = epoch + 1",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Put the enclave key into the standby pool
+    ///
+    /// A standby key is excluded from `available_keys` until it is activated, so that it can be
+    /// generated and attested in advance of a rotation.
+    pub fn add_standby_key(&self, address: Address) -> Result<(), Error> {
+        // ensure that the key is managed by this key manager
+        let _ = self.load(address)?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt =
+            conn.prepare("INSERT OR IGNORE INTO standby_keys (ek_address) VALUES (?1)")?;
+        stmt.execute(params![address.to_hex_string()])?;
+        Ok(())
+    }
+
+    /// Returns the standby keys of the mrenclave in the order they were added, including the unattested ones
+    pub fn standby_keys(&self, mrenclave: Mrenclave) -> Result<Vec<SealedEnclaveKeyInfo>, Error> {
+        let addresses = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|e| Error::mutex_lock(e.to_string()))?;
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT s.ek_address
+                FROM standby_keys s INNER JOIN enclave_keys k ON s.ek_address = k.ek_address
+                WHERE k.mrenclave = ?1
+                ORDER BY s.created_at ASC, k.id ASC
+                "#,
+            )?;
+            let addresses = stmt
+                .query_map(params![mrenclave.deref()], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            addresses
+        };
+        addresses
+            .iter()
+            .map(|address| self.load(Address::from_hex_string(address)?))
+            .collect()
+    }
+
+    /// Take the most recently attested standby key of the mrenclave out of the pool, which makes it available
+    ///
+    /// Returns None if there is no attested standby key.
+    pub fn activate_standby_key(
+        &self,
+        mrenclave: Mrenclave,
+    ) -> Result<Option<SealedEnclaveKeyInfo>, Error> {
+        let address = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|e| Error::mutex_lock(e.to_string()))?;
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT s.ek_address
+                FROM standby_keys s INNER JOIN enclave_keys k ON s.ek_address = k.ek_address
+                WHERE k.mrenclave = ?1 AND k.attested_at IS NOT NULL
+                    AND k.ek_address NOT IN (SELECT ek_address FROM revoked_keys)
+                ORDER BY k.attested_at DESC
+                LIMIT 1
+                "#,
+            )?;
+            let mut rows = stmt.query(params![mrenclave.deref()])?;
+            let address = match rows.next()? {
+                Some(row) => row.get::<_, String>(0)?,
+                None => return Ok(None),
+            };
+            conn.execute(
+                "DELETE FROM standby_keys WHERE ek_address = ?1",
+                params![address],
+            )?;
+            address
+        };
+        Ok(Some(self.load(Address::from_hex_string(&address)?)?))
+    }
+
+    /// Revoke every attested key that has not been revoked yet, and returns the addresses of the revoked keys
+    ///
+    /// The keys share the platform whose TCB status has been found to violate the policy,
+    /// so none of them must sign a commitment anymore.
+    pub fn revoke_attested_keys(&self, reason: &str) -> Result<Vec<Address>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT ek_address FROM enclave_keys
+            WHERE attested_at IS NOT NULL
+                AND ek_address NOT IN (SELECT ek_address FROM revoked_keys)
+            "#,
+        )?;
+        let addresses = stmt
+            .query_map(params![], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let revoked_at = Time::now().as_unix_timestamp_secs();
+        for address in addresses.iter() {
+            conn.execute(
+                "INSERT OR IGNORE INTO revoked_keys (ek_address, reason, revoked_at) VALUES (?1, ?2, ?3)",
+                params![address, reason, revoked_at],
+            )?;
+        }
+        if !addresses.is_empty() {
+            Self::advance_key_epoch(&conn)?;
+        }
+        addresses
+            .iter()
+            .map(|address| Ok(Address::from_hex_string(address)?))
+            .collect()
+    }
+
+    /// Returns the reason of the revocation if the key has been revoked
+    pub fn revocation_reason(&self, address: Address) -> Result<Option<String>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare("SELECT reason FROM revoked_keys WHERE ek_address = ?1")?;
+        let mut rows = stmt.query(params![address.to_hex_string()])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the revoked keys in the order they were revoked
+    pub fn revoked_keys(&self) -> Result<Vec<RevokedKeyInfo>, Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        let mut stmt = conn.prepare(
+            "SELECT ek_address, reason, revoked_at FROM revoked_keys ORDER BY revoked_at ASC, ek_address ASC",
+        )?;
+        let keys = stmt
+            .query_map(params![], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        keys.into_iter()
+            .map(|(address, reason, revoked_at)| {
+                Ok(RevokedKeyInfo {
+                    address: Address::from_hex_string(&address)?,
+                    reason,
+                    revoked_at: revoked_at as u64,
+                })
+            })
+            .collect()
+    }
+
     /// Prune keys after the expiration time(secs) from the attestation time.
     pub fn prune(&self, expiration_time: u64) -> Result<usize, Error> {
         let conn = self
@@ -232,8 +739,25 @@ impl EnclaveKeyManager {
         let expired = (Time::now() - Duration::from_secs(expiration_time))?;
         let mut stmt = conn.prepare("DELETE FROM enclave_keys WHERE attested_at <= ?1")?;
         let count = stmt.execute(params![expired.as_unix_timestamp_secs()])?;
+        conn.execute(
+            "DELETE FROM standby_keys WHERE ek_address NOT IN (SELECT ek_address FROM enclave_keys)",
+            params![],
+        )?;
         Ok(count)
     }
+
+    /// Write the pending changes of the database back to the main file before the process exits
+    ///
+    /// The lock on the connection is held, so no key is saved until the checkpoint completes.
+    pub fn checkpoint(&self) -> Result<(), Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        // the pragma returns a row of the checkpoint status even if the journal mode is not WAL
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -244,6 +768,15 @@ pub struct SealedEnclaveKeyInfo {
     pub avr: Option<EndorsedAttestationVerificationReport>,
 }
 
+/// `RevokedKeyInfo` is a key that must not sign commitments anymore
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevokedKeyInfo {
+    pub address: Address,
+    pub reason: String,
+    /// UNIX time in seconds when the key was revoked
+    pub revoked_at: u64,
+}
+
 impl TryFrom<SealedEnclaveKeyInfo> for ProtoEnclaveKeyInfo {
     type Error = Error;
     fn try_from(value: SealedEnclaveKeyInfo) -> Result<Self, Self::Error> {
@@ -262,6 +795,69 @@ impl TryFrom<SealedEnclaveKeyInfo> for ProtoEnclaveKeyInfo {
     }
 }
 
+/// `EvidenceType` is the kind of the evidence that an enclave key has been attested with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EvidenceType {
+    /// Attestation verification report of the IAS
+    Avr,
+    /// DCAP quote with its collateral
+    Dcap,
+}
+
+impl EvidenceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Avr => "avr",
+            Self::Dcap => "dcap",
+        }
+    }
+}
+
+impl std::fmt::Display for EvidenceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for EvidenceType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "avr" => Ok(Self::Avr),
+            "dcap" => Ok(Self::Dcap),
+            _ => Err(Error::invalid_evidence_type(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AttestationEvidence {
+    Avr(EndorsedAttestationVerificationReport),
+    Dcap(DCAPQuoteBundle),
+}
+
+impl AttestationEvidence {
+    pub fn evidence_type(&self) -> EvidenceType {
+        match self {
+            Self::Avr(_) => EvidenceType::Avr,
+            Self::Dcap(_) => EvidenceType::Dcap,
+        }
+    }
+}
+
+/// `AttestationRecord` is an attestation evidence of an enclave key with the state of the key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationRecord {
+    pub address: Address,
+    pub mrenclave: Mrenclave,
+    /// UNIX time in seconds when the key was attested
+    pub attested_at: u64,
+    pub evidence: AttestationEvidence,
+    pub standby: bool,
+    pub revoked: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,6 +906,239 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrapped_keys() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        let plain = create_address();
+        let plain_ek = create_sealed_sk();
+        km.save(plain, plain_ek.clone(), mrenclave).unwrap();
+
+        let km = km.with_key_wrapper(KeyWrapper::new(&[1u8; 32]).unwrap());
+        let wrapped = create_address();
+        let wrapped_ek = create_sealed_sk();
+        km.save(wrapped, wrapped_ek.clone(), mrenclave).unwrap();
+        assert_eq!(
+            km.load(wrapped).unwrap().sealed_ek.to_vec(),
+            wrapped_ek.to_vec()
+        );
+        // keys saved before the wrapper was configured can still be loaded
+        assert_eq!(
+            km.load(plain).unwrap().sealed_ek.to_vec(),
+            plain_ek.to_vec()
+        );
+        assert_eq!(km.all_keys().unwrap().len(), 2);
+
+        let km = km.with_key_wrapper(KeyWrapper::new(&[2u8; 32]).unwrap());
+        assert!(km.load(wrapped).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        let outgoing = create_address();
+        let incoming = create_address();
+        km.save(outgoing, create_sealed_sk(), mrenclave).unwrap();
+        assert!(km.begin_key_rotation(outgoing, incoming).is_err());
+        km.save(incoming, create_sealed_sk(), mrenclave).unwrap();
+        assert!(km.begin_key_rotation(outgoing, outgoing).is_err());
+
+        assert_eq!(km.co_signer(outgoing).unwrap(), None);
+        km.begin_key_rotation(outgoing, incoming).unwrap();
+        assert_eq!(km.co_signer(outgoing).unwrap(), Some(incoming));
+        assert_eq!(km.co_signer(incoming).unwrap(), None);
+        assert!(km.end_key_rotation(outgoing).unwrap());
+        assert!(!km.end_key_rotation(outgoing).unwrap());
+        assert_eq!(km.co_signer(outgoing).unwrap(), None);
+    }
+
+    #[test]
+    fn test_standby_keys() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        assert!(km.add_standby_key(create_address()).is_err());
+
+        let active = create_address();
+        km.save(active, create_sealed_sk(), mrenclave).unwrap();
+        km.save_avr(active, create_eavr(get_time(Duration::minutes(2))))
+            .unwrap();
+        let (standby_0, standby_1, unattested) =
+            (create_address(), create_address(), create_address());
+        for (address, attested_at) in [
+            (standby_0, Some(Duration::minutes(1))),
+            (standby_1, Some(Duration::zero())),
+            (unattested, None),
+        ] {
+            km.save(address, create_sealed_sk(), mrenclave).unwrap();
+            km.add_standby_key(address).unwrap();
+            if let Some(d) = attested_at {
+                km.save_avr(address, create_eavr(get_time(d))).unwrap();
+            }
+        }
+        assert_eq!(km.all_keys().unwrap().len(), 4);
+        // the standby keys are not available until they are activated
+        let available = km.available_keys(mrenclave).unwrap();
+        assert_eq!(available.len(), 1);
+        assert_eq!(available[0].address, active);
+        assert_eq!(km.standby_keys(mrenclave).unwrap().len(), 3);
+        assert!(km.standby_keys(create_mrenclave()).unwrap().is_empty());
+
+        // the most recently attested key is activated first
+        let activated = km.activate_standby_key(mrenclave).unwrap().unwrap();
+        assert_eq!(activated.address, standby_1);
+        assert_eq!(km.available_keys(mrenclave).unwrap()[0].address, standby_1);
+        assert_eq!(
+            km.activate_standby_key(mrenclave).unwrap().unwrap().address,
+            standby_0
+        );
+        // the unattested key cannot be activated
+        assert!(km.activate_standby_key(mrenclave).unwrap().is_none());
+        let standby = km.standby_keys(mrenclave).unwrap();
+        assert_eq!(standby.len(), 1);
+        assert_eq!(standby[0].address, unattested);
+        assert_eq!(km.available_keys(mrenclave).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_dcap_bundle() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let address = create_address();
+        assert_eq!(km.load_dcap_bundle(address).unwrap(), None);
+        let bundle = DCAPQuoteBundle {
+            quote: vec![1, 2, 3],
+            ..Default::default()
+        };
+        km.save_dcap_bundle(address, &bundle, [0u8; 32]).unwrap();
+        assert_eq!(km.load_dcap_bundle(address).unwrap(), Some(bundle));
+    }
+
+    #[test]
+    fn test_store_checkpoint() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        assert_eq!(km.load_store_checkpoint().unwrap(), None);
+        km.save_store_checkpoint(2, &[2]).unwrap();
+        km.save_store_checkpoint(3, &[3]).unwrap();
+        // an earlier sequence never overwrites the checkpoint
+        km.save_store_checkpoint(1, &[1]).unwrap();
+        assert_eq!(km.load_store_checkpoint().unwrap(), Some((3, vec![3])));
+    }
+
+    #[test]
+    fn test_attestations() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        let (old, new, dcap, unattested) = (
+            create_address(),
+            create_address(),
+            create_address(),
+            create_address(),
+        );
+        for address in [old, new, dcap, unattested] {
+            km.save(address, create_sealed_sk(), mrenclave).unwrap();
+        }
+        km.save_avr(old, create_eavr(get_time(Duration::days(2))))
+            .unwrap();
+        km.save_avr(new, create_eavr(get_time(Duration::zero())))
+            .unwrap();
+        km.add_standby_key(new).unwrap();
+        let bundle = DCAPQuoteBundle {
+            quote: vec![1, 2, 3],
+            ..Default::default()
+        };
+        km.save_dcap_bundle(dcap, &bundle, [0u8; 32]).unwrap();
+
+        let records = km.attestations(None, None, 0).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records
+            .windows(2)
+            .all(|w| w[0].attested_at >= w[1].attested_at));
+        assert_eq!(records.last().unwrap().address, old);
+
+        let records = km.attestations(Some(new), None, 0).unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].standby);
+        assert!(!records[0].revoked);
+        assert_eq!(records[0].evidence.evidence_type(), EvidenceType::Avr);
+
+        let records = km.attestations(None, Some(EvidenceType::Dcap), 0).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].address, dcap);
+        assert_eq!(records[0].evidence, AttestationEvidence::Dcap(bundle));
+
+        let min_attested_at = get_time(Duration::days(1)).timestamp() as u64;
+        let records = km
+            .attestations(None, Some(EvidenceType::Avr), min_attested_at)
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].address, new);
+        assert!(km
+            .attestations(Some(unattested), None, 0)
+            .unwrap()
+            .is_empty());
+
+        assert_eq!("dcap".parse::<EvidenceType>().unwrap(), EvidenceType::Dcap);
+        assert!("epid".parse::<EvidenceType>().is_err());
+    }
+
+    #[test]
+    fn test_revoke_attested_keys() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        let (active, standby, unattested) = (create_address(), create_address(), create_address());
+        for address in [active, standby, unattested] {
+            km.save(address, create_sealed_sk(), mrenclave).unwrap();
+        }
+        km.save_avr(active, create_eavr(get_time(Duration::zero())))
+            .unwrap();
+        km.add_standby_key(standby).unwrap();
+        km.save_avr(standby, create_eavr(get_time(Duration::minutes(1))))
+            .unwrap();
+        assert_eq!(km.revocation_reason(active).unwrap(), None);
+
+        let mut revoked = km.revoke_attested_keys("INTEL-SA-00615").unwrap();
+        revoked.sort_by_key(|a| a.0);
+        let mut expected = vec![active, standby];
+        expected.sort_by_key(|a| a.0);
+        assert_eq!(revoked, expected);
+        assert_eq!(
+            km.revocation_reason(active).unwrap(),
+            Some("INTEL-SA-00615".to_string())
+        );
+        // the unattested key has never signed a commitment, so it is left to be attested
+        assert_eq!(km.revocation_reason(unattested).unwrap(), None);
+        assert!(km.available_keys(mrenclave).unwrap().is_empty());
+        assert!(km.activate_standby_key(mrenclave).unwrap().is_none());
+        assert_eq!(km.revoked_keys().unwrap().len(), 2);
+        // the keys that have been revoked are not revoked again
+        assert!(km.revoke_attested_keys("again").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_key_epoch() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let mrenclave = create_mrenclave();
+        let (outgoing, incoming) = (create_address(), create_address());
+        for address in [outgoing, incoming] {
+            km.save(address, create_sealed_sk(), mrenclave).unwrap();
+        }
+        assert_eq!(km.key_epoch().unwrap(), 0);
+        km.begin_key_rotation(outgoing, incoming).unwrap();
+        assert_eq!(km.key_epoch().unwrap(), 1);
+        assert!(km.end_key_rotation(outgoing).unwrap());
+        assert_eq!(km.key_epoch().unwrap(), 2);
+        // ending a rotation that is not in progress changes no key
+        assert!(!km.end_key_rotation(outgoing).unwrap());
+        assert_eq!(km.key_epoch().unwrap(), 2);
+
+        km.save_avr(outgoing, create_eavr(get_time(Duration::zero())))
+            .unwrap();
+        km.revoke_attested_keys("INTEL-SA-00615").unwrap();
+        assert_eq!(km.key_epoch().unwrap(), 3);
+        km.revoke_attested_keys("again").unwrap();
+        assert_eq!(km.key_epoch().unwrap(), 3);
+    }
+
     fn get_time(d: Duration) -> DateTime<Utc> {
         Utc::now().checked_sub_signed(d).unwrap()
     }
@@ -3,7 +3,7 @@ use crate::consensus_state::ConsensusState;
 use crate::errors::Error;
 use crate::message::{ClientMessage, RegisterEnclaveKeyMessage};
 use attestation_report::EndorsedAttestationVerificationReport;
-use crypto::{verify_signature_address, Address, Keccak256};
+use crypto::{Address, Keccak256};
 use light_client::commitments::{
     CommitmentPrefix, CommitmentProof, EthABIEncoder, MisbehaviourProxyMessage, ProxyMessage,
     UpdateStateProxyMessage, VerifyMembershipProxyMessage,
@@ -111,12 +111,15 @@ impl LCPClient {
         assert!(self.contains_enclave_key(ctx, &client_id, signer));
 
         // check if the `header.signer` matches the commitment prover
-        let signer2 = verify_signature_address(
-            ProxyMessage::from(message.clone()).to_bytes().as_slice(),
+        let proxy_message = ProxyMessage::from(message.clone());
+        client_state.signature_scheme.verify_signer(
+            proxy_message.clone().to_bytes().as_slice(),
             &signature,
-        )
-        .unwrap();
-        assert!(signer == signer2);
+            signer,
+        )?;
+
+        // check if the message has not expired
+        proxy_message.check_expiry(ctx.host_timestamp())?;
 
         // check if proxy's validation context matches our's context
         message.context.validate(ctx.host_timestamp())?;
@@ -181,11 +184,11 @@ impl LCPClient {
         message.context.validate(ctx.host_timestamp())?;
 
         // check if the `header.signer` matches the commitment prover
-        let signer2 = verify_signature_address(
+        client_state.signature_scheme.verify_signer(
             ProxyMessage::from(message).to_bytes().as_slice(),
             &signature,
+            signer,
         )?;
-        assert!(signer == signer2);
 
         let new_client_state = client_state.with_frozen();
         ctx.store_any_client_state(client_id, new_client_state.into())?;
@@ -208,7 +211,10 @@ impl LCPClient {
 
         // convert `proof` to CommitmentProof
         let commitment_proof = CommitmentProof::ethabi_decode(proof.as_slice()).unwrap();
-        let msg: VerifyMembershipProxyMessage = commitment_proof.message()?.try_into()?;
+        let proxy_message = commitment_proof.message()?;
+        // check if the message has not expired
+        proxy_message.check_expiry(ctx.host_timestamp())?;
+        let msg: VerifyMembershipProxyMessage = proxy_message.try_into()?;
 
         // check if `.prefix` matches the counterparty connection's prefix
         assert!(msg.prefix == prefix);
@@ -224,11 +230,20 @@ impl LCPClient {
         let consensus_state =
             ConsensusState::try_from(ctx.consensus_state(&client_id, &proof_height)?)?;
         assert!(consensus_state.state_id == msg.state_id);
+        // check if `.trusted_timestamp` matches the timestamp of the consensus state, if disclosed
+        if let Some(trusted_timestamp) = msg.trusted_timestamp {
+            assert!(consensus_state.timestamp == trusted_timestamp);
+        }
 
         // check if the `commitment_proof.signer` matches the commitment prover
-        let signer =
-            verify_signature_address(&commitment_proof.message, &commitment_proof.signature)?;
-        assert!(commitment_proof.signer == signer);
+        let client_state = ClientState::try_from(ctx.client_state(&client_id)?)?;
+        assert!(!client_state.frozen);
+        let signer = commitment_proof.signer;
+        client_state.signature_scheme.verify_signer(
+            &commitment_proof.message,
+            &commitment_proof.signature,
+            signer,
+        )?;
 
         // check if the specified signer is not expired and exists in the client state
         assert!(self.is_active_enclave_key(ctx, &client_id, signer));
@@ -280,7 +295,7 @@ impl LCPClient {
 // verify_report
 // - verifies the Attestation Verification Report
 // - calculate a key expiration with client_state and report's timestamp
-fn verify_report(
+pub(crate) fn verify_report(
     current_timestamp: Time,
     client_state: &ClientState,
     eavr: &EndorsedAttestationVerificationReport,
@@ -325,6 +340,7 @@ mod tests {
     use crate::message::UpdateClientMessage;
     use alloc::rc::Rc;
     use alloc::sync::Arc;
+    use attestation_report::encode_quote_body;
     use attestation_report::AttestationVerificationReport;
     use context::Context;
     use core::cell::RefCell;
@@ -338,6 +354,8 @@ mod tests {
         },
         Height as ICS02Height,
     };
+    use light_client::commitments::EmittedState;
+    use light_client::types::Any;
     use light_client::{commitments::prove_commitment, UpdateClientResult};
     use light_client::{LightClient, LightClientResolver, MapLightClientRegistry};
     use mock_lc::MockLightClient;
@@ -358,44 +376,8 @@ mod tests {
         let mock_client = MockLightClient::default();
 
         // 1. initializes Light Client for LCP on the downstream side
-        let lcp_client_id = {
-            let expired_at = (Time::now() + Duration::from_secs(60)).unwrap();
-            let initial_client_state = ClientState {
-                mr_enclave: [0u8; 32].to_vec(),
-                key_expiration: Duration::from_secs(60 * 60 * 24 * 7),
-                frozen: false,
-                latest_height: Height::zero(),
-            };
-            let initial_consensus_state = ConsensusState {
-                state_id: Default::default(),
-                timestamp: Time::unix_epoch(),
-            };
-
-            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek);
-            ctx.set_timestamp(Time::now());
-
-            let client_id = ClientId::from_str(&format!("{}-0", lcp_client.client_type())).unwrap();
-
-            let res = lcp_client.initialise(
-                &mut ctx,
-                client_id.clone(),
-                initial_client_state,
-                initial_consensus_state,
-            );
-            assert!(res.is_ok(), "res={:?}", res);
-            client_id
-        };
-
         // 2. register enclave key to the LCP client
-        {
-            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek);
-            ctx.set_timestamp(Time::now());
-            let header = ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage(
-                generate_dummy_eavr(&ek.get_pubkey()),
-            ));
-            let res = lcp_client.update_client(&mut ctx, lcp_client_id.clone(), header);
-            assert!(res.is_ok(), "res={:?}", res);
-        }
+        let lcp_client_id = init_lcp_client(&lcp_client, registry.clone(), ibc_store.clone(), &ek);
 
         // 3. initializes Light Client(Mock) corresponding to the upstream chain on the LCP side
         let upstream_client_id = {
@@ -525,6 +507,137 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_state_with_frozen_height() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let lcp_client = LCPClient::default();
+        let lcp_client_id = init_lcp_client(&lcp_client, registry.clone(), ibc_store.clone(), &ek);
+
+        let height = Height::new(0, 1);
+        let message = UpdateStateProxyMessage {
+            prev_height: None,
+            prev_state_id: None,
+            post_height: height,
+            post_state_id: Default::default(),
+            frozen_height: Some(height),
+            timestamp: Time::now(),
+            expires_at: None,
+            context: Default::default(),
+            emitted_states: vec![EmittedState(height, Any::new("/test".into(), vec![]))],
+        };
+        let proof = prove_commitment(&ek, ek.get_pubkey().as_address(), message.into()).unwrap();
+
+        let header = ClientMessage::UpdateClient(UpdateClientMessage {
+            proxy_message: proof.message().unwrap(),
+            signer: proof.signer,
+            signature: proof.signature,
+        });
+        let mut ctx = Context::new(registry, ibc_store, &ek);
+        ctx.set_timestamp(Time::now());
+        let res = lcp_client.update_client(&mut ctx, lcp_client_id.clone(), header);
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let client_state =
+            ClientState::try_from(ctx.client_state(&lcp_client_id).unwrap()).unwrap();
+        assert!(client_state.frozen);
+        assert_eq!(client_state.latest_height, height);
+    }
+
+    #[test]
+    fn test_update_state_expired() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let lcp_client = LCPClient::default();
+        let lcp_client_id = init_lcp_client(&lcp_client, registry.clone(), ibc_store.clone(), &ek);
+
+        let height = Height::new(0, 1);
+        let now = Time::now();
+        let message = UpdateStateProxyMessage {
+            prev_height: None,
+            prev_state_id: None,
+            post_height: height,
+            post_state_id: Default::default(),
+            frozen_height: None,
+            timestamp: now,
+            expires_at: Some((now + Duration::from_secs(60)).unwrap()),
+            context: Default::default(),
+            emitted_states: vec![EmittedState(height, Any::new("/test".into(), vec![]))],
+        };
+        let proof = prove_commitment(&ek, ek.get_pubkey().as_address(), message.into()).unwrap();
+        let header = ClientMessage::UpdateClient(UpdateClientMessage {
+            proxy_message: proof.message().unwrap(),
+            signer: proof.signer,
+            signature: proof.signature,
+        });
+
+        // the message is rejected after it has expired
+        {
+            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek);
+            ctx.set_timestamp((now + Duration::from_secs(61)).unwrap());
+            let res = lcp_client.update_client(&mut ctx, lcp_client_id.clone(), header.clone());
+            assert!(res.is_err(), "res={:?}", res);
+        }
+        // the message is accepted until it expires
+        {
+            let mut ctx = Context::new(registry, ibc_store, &ek);
+            ctx.set_timestamp((now + Duration::from_secs(60)).unwrap());
+            let res = lcp_client.update_client(&mut ctx, lcp_client_id, header);
+            assert!(res.is_ok(), "res={:?}", res);
+        }
+    }
+
+    fn init_lcp_client(
+        lcp_client: &LCPClient,
+        registry: Arc<dyn LightClientResolver>,
+        ibc_store: Rc<RefCell<MemStore>>,
+        ek: &EnclaveKey,
+    ) -> ClientId {
+        // initializes Light Client for LCP on the downstream side
+        let lcp_client_id = {
+            let expired_at = (Time::now() + Duration::from_secs(60)).unwrap();
+            let initial_client_state = ClientState {
+                mr_enclave: [0u8; 32].to_vec(),
+                key_expiration: Duration::from_secs(60 * 60 * 24 * 7),
+                frozen: false,
+                latest_height: Height::zero(),
+                signature_scheme: Default::default(),
+            };
+            let initial_consensus_state = ConsensusState {
+                state_id: Default::default(),
+                timestamp: Time::unix_epoch(),
+            };
+
+            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), ek);
+            ctx.set_timestamp(Time::now());
+
+            let client_id = ClientId::from_str(&format!("{}-0", lcp_client.client_type())).unwrap();
+
+            let res = lcp_client.initialise(
+                &mut ctx,
+                client_id.clone(),
+                initial_client_state,
+                initial_consensus_state,
+            );
+            assert!(res.is_ok(), "res={:?}", res);
+            client_id
+        };
+
+        // register enclave key to the LCP client
+        {
+            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), ek);
+            ctx.set_timestamp(Time::now());
+            let header = ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage(
+                generate_dummy_eavr(&ek.get_pubkey()),
+            ));
+            let res = lcp_client.update_client(&mut ctx, lcp_client_id.clone(), header);
+            assert!(res.is_ok(), "res={:?}", res);
+        }
+        lcp_client_id
+    }
+
     fn build_lc_registry() -> Arc<dyn LightClientResolver> {
         let registry = MapLightClientRegistry::new();
         Arc::new(registry)
@@ -539,12 +652,6 @@ mod tests {
             },
             ..Default::default()
         };
-        // transmute quote to Vec<u8>
-        let quote = unsafe {
-            core::mem::transmute_copy::<sgx_quote_t, [u8; core::mem::size_of::<sgx_quote_t>()]>(
-                &quote,
-            )
-        };
         let now = chrono::Utc::now();
         let attr = AttestationVerificationReport {
             id: "23856791181030202675484781740313693463".to_string(),
@@ -561,7 +668,7 @@ mod tests {
             // advisory_ids,
             // isv_enclave_quote_status,
             platform_info_blob: None,
-            isv_enclave_quote_body: base64::encode(&quote.as_slice()[..432]),
+            isv_enclave_quote_body: base64::encode(encode_quote_body(&quote)),
             ..Default::default()
         };
 
@@ -1,6 +1,8 @@
 use crate::errors::Error;
 use crate::prelude::*;
+use core::str::FromStr;
 use core::time::Duration;
+use crypto::{DigestAlgorithm, SignatureFormat, SignatureScheme};
 use light_client::commitments::UpdateStateProxyMessage;
 use light_client::types::proto::{
     ibc::{
@@ -21,6 +23,8 @@ pub struct ClientState {
     pub key_expiration: Duration,
     pub latest_height: Height,
     pub frozen: bool,
+    /// The scheme of the signatures over the commitments, which must match the one of the ELC client
+    pub signature_scheme: SignatureScheme,
 }
 
 impl ClientState {
@@ -28,6 +32,10 @@ impl ClientState {
         if self.latest_height < header.post_height {
             self.latest_height = header.post_height;
         }
+        // the ELC has detected that the upstream client is frozen, so the LCP client is frozen as well
+        if header.frozen_height.is_some() {
+            self.frozen = true;
+        }
         self
     }
 
@@ -49,6 +57,8 @@ impl From<ClientState> for RawClientState {
             }),
             allowed_quote_statuses: Default::default(),
             allowed_advisory_ids: Default::default(),
+            digest_algorithm: value.signature_scheme.digest.to_string(),
+            signature_format: value.signature_scheme.format.to_string(),
         }
     }
 }
@@ -63,6 +73,18 @@ impl TryFrom<RawClientState> for ClientState {
             key_expiration: Duration::from_secs(raw.key_expiration),
             frozen: raw.frozen,
             latest_height: Height::new(height.revision_number, height.revision_height),
+            signature_scheme: SignatureScheme {
+                digest: if raw.digest_algorithm.is_empty() {
+                    Default::default()
+                } else {
+                    DigestAlgorithm::from_str(&raw.digest_algorithm)?
+                },
+                format: if raw.signature_format.is_empty() {
+                    Default::default()
+                } else {
+                    SignatureFormat::from_str(&raw.signature_format)?
+                },
+            },
         })
     }
 }
@@ -36,6 +36,38 @@ define_error! {
             format_args!("Mrenclave mismatch: expected={:?} actual={:?}", e.expected, e.actual)
         },
 
+        InvalidMessage {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid message: descr={}", e.descr)
+        },
+
+        ClientFrozen
+        |_| { "the client is frozen" },
+
+        InactiveEnclaveKey {
+            key: crypto::Address
+        }
+        |e| {
+            format_args!("the enclave key is not registered or has expired: key={}", e.key)
+        },
+
+        ConsensusStateNotFound {
+            height: light_client::types::Height
+        }
+        |e| {
+            format_args!("consensus state not found: height={}", e.height)
+        },
+
+        StateIdMismatch {
+            expected: light_client::commitments::StateID,
+            actual: light_client::commitments::StateID
+        }
+        |e| {
+            format_args!("state_id mismatch: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
         AttestationReport
         [attestation_report::Error]
         |_| { "Attestation report error" },
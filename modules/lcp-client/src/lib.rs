@@ -25,3 +25,6 @@ pub mod client_state;
 pub mod consensus_state;
 pub mod errors;
 pub mod message;
+#[cfg(feature = "scale")]
+pub mod scale;
+pub mod verifier;
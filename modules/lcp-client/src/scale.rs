@@ -0,0 +1,368 @@
+//! SCALE encodings of the LCP client types
+//!
+//! A Substrate pallet stores the states and decodes the messages in SCALE, so each type here mirrors
+//! the field layout of the protobuf form in `ibc.lightclients.lcp.v1` and converts to and from it without loss.
+use crate::client_state::ClientState;
+use crate::consensus_state::ConsensusState;
+use crate::errors::Error;
+use crate::message::{RegisterEnclaveKeyMessage, UpdateClientMessage};
+use crate::prelude::*;
+use light_client::types::proto::ibc::{
+    core::client::v1::Height as ProtoHeight,
+    lightclients::lcp::v1::{
+        ClientState as RawClientState, ConsensusState as RawConsensusState,
+        RegisterEnclaveKeyMessage as RawRegisterEnclaveKeyMessage,
+        UpdateClientMessage as RawUpdateClientMessage,
+    },
+};
+use light_client::types::Height;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ScaleHeight {
+    pub revision_number: u64,
+    pub revision_height: u64,
+}
+
+impl From<ProtoHeight> for ScaleHeight {
+    fn from(value: ProtoHeight) -> Self {
+        Self {
+            revision_number: value.revision_number,
+            revision_height: value.revision_height,
+        }
+    }
+}
+
+impl From<ScaleHeight> for ProtoHeight {
+    fn from(value: ScaleHeight) -> Self {
+        Self {
+            revision_number: value.revision_number,
+            revision_height: value.revision_height,
+        }
+    }
+}
+
+impl From<Height> for ScaleHeight {
+    fn from(value: Height) -> Self {
+        Self {
+            revision_number: value.revision_number(),
+            revision_height: value.revision_height(),
+        }
+    }
+}
+
+impl From<ScaleHeight> for Height {
+    fn from(value: ScaleHeight) -> Self {
+        Height::new(value.revision_number, value.revision_height)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ScaleClientState {
+    pub mrenclave: Vec<u8>,
+    /// in seconds
+    pub key_expiration: u64,
+    pub frozen: bool,
+    pub latest_height: ScaleHeight,
+    pub allowed_quote_statuses: Vec<String>,
+    pub allowed_advisory_ids: Vec<String>,
+    /// empty means the default
+    pub digest_algorithm: String,
+    /// empty means the default
+    pub signature_format: String,
+}
+
+impl From<RawClientState> for ScaleClientState {
+    fn from(value: RawClientState) -> Self {
+        Self {
+            mrenclave: value.mrenclave,
+            key_expiration: value.key_expiration,
+            frozen: value.frozen,
+            latest_height: value.latest_height.unwrap_or_default().into(),
+            allowed_quote_statuses: value.allowed_quote_statuses,
+            allowed_advisory_ids: value.allowed_advisory_ids,
+            digest_algorithm: value.digest_algorithm,
+            signature_format: value.signature_format,
+        }
+    }
+}
+
+impl From<ScaleClientState> for RawClientState {
+    fn from(value: ScaleClientState) -> Self {
+        Self {
+            mrenclave: value.mrenclave,
+            key_expiration: value.key_expiration,
+            frozen: value.frozen,
+            latest_height: Some(value.latest_height.into()),
+            allowed_quote_statuses: value.allowed_quote_statuses,
+            allowed_advisory_ids: value.allowed_advisory_ids,
+            digest_algorithm: value.digest_algorithm,
+            signature_format: value.signature_format,
+        }
+    }
+}
+
+impl From<ClientState> for ScaleClientState {
+    fn from(value: ClientState) -> Self {
+        RawClientState::from(value).into()
+    }
+}
+
+impl TryFrom<ScaleClientState> for ClientState {
+    type Error = Error;
+
+    fn try_from(value: ScaleClientState) -> Result<Self, Self::Error> {
+        RawClientState::from(value).try_into()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ScaleConsensusState {
+    pub state_id: Vec<u8>,
+    /// UNIX time in seconds
+    pub timestamp: u64,
+}
+
+impl From<RawConsensusState> for ScaleConsensusState {
+    fn from(value: RawConsensusState) -> Self {
+        Self {
+            state_id: value.state_id,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+impl From<ScaleConsensusState> for RawConsensusState {
+    fn from(value: ScaleConsensusState) -> Self {
+        Self {
+            state_id: value.state_id,
+            timestamp: value.timestamp,
+        }
+    }
+}
+
+impl From<ConsensusState> for ScaleConsensusState {
+    fn from(value: ConsensusState) -> Self {
+        RawConsensusState::from(value).into()
+    }
+}
+
+impl TryFrom<ScaleConsensusState> for ConsensusState {
+    type Error = Error;
+
+    fn try_from(value: ScaleConsensusState) -> Result<Self, Self::Error> {
+        if value.state_id.len() != light_client::commitments::STATE_ID_SIZE {
+            return Err(Error::invalid_message(format!(
+                "invalid state_id length: {}",
+                value.state_id.len()
+            )));
+        }
+        RawConsensusState::from(value).try_into()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ScaleUpdateClientMessage {
+    /// the message of the commitment returned by the enclave
+    pub proxy_message: Vec<u8>,
+    pub signer: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl From<RawUpdateClientMessage> for ScaleUpdateClientMessage {
+    fn from(value: RawUpdateClientMessage) -> Self {
+        Self {
+            proxy_message: value.proxy_message,
+            signer: value.signer,
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<ScaleUpdateClientMessage> for RawUpdateClientMessage {
+    fn from(value: ScaleUpdateClientMessage) -> Self {
+        Self {
+            proxy_message: value.proxy_message,
+            signer: value.signer,
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<UpdateClientMessage> for ScaleUpdateClientMessage {
+    fn from(value: UpdateClientMessage) -> Self {
+        RawUpdateClientMessage::from(value).into()
+    }
+}
+
+impl TryFrom<ScaleUpdateClientMessage> for UpdateClientMessage {
+    type Error = Error;
+
+    fn try_from(value: ScaleUpdateClientMessage) -> Result<Self, Self::Error> {
+        RawUpdateClientMessage::from(value).try_into()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ScaleRegisterEnclaveKeyMessage {
+    pub report: String,
+    pub signature: Vec<u8>,
+    pub signing_cert: Vec<u8>,
+}
+
+impl From<RawRegisterEnclaveKeyMessage> for ScaleRegisterEnclaveKeyMessage {
+    fn from(value: RawRegisterEnclaveKeyMessage) -> Self {
+        Self {
+            report: value.report,
+            signature: value.signature,
+            signing_cert: value.signing_cert,
+        }
+    }
+}
+
+impl From<ScaleRegisterEnclaveKeyMessage> for RawRegisterEnclaveKeyMessage {
+    fn from(value: ScaleRegisterEnclaveKeyMessage) -> Self {
+        Self {
+            report: value.report,
+            signature: value.signature,
+            signing_cert: value.signing_cert,
+        }
+    }
+}
+
+impl From<RegisterEnclaveKeyMessage> for ScaleRegisterEnclaveKeyMessage {
+    fn from(value: RegisterEnclaveKeyMessage) -> Self {
+        RawRegisterEnclaveKeyMessage::from(value).into()
+    }
+}
+
+impl TryFrom<ScaleRegisterEnclaveKeyMessage> for RegisterEnclaveKeyMessage {
+    type Error = Error;
+
+    fn try_from(value: ScaleRegisterEnclaveKeyMessage) -> Result<Self, Self::Error> {
+        RawRegisterEnclaveKeyMessage::from(value).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use crypto::{Address, DigestAlgorithm, SignatureFormat, SignatureScheme};
+    use light_client::commitments::{
+        ProxyMessage, StateID, UpdateStateProxyMessage, ValidationContext,
+    };
+    use light_client::types::{proto::protobuf::Protobuf, Any, Time};
+    use prost::Message;
+
+    fn scale_round_trip<T: Encode + Decode + PartialEq + core::fmt::Debug>(value: &T) {
+        let bz = value.encode();
+        assert_eq!(&T::decode(&mut bz.as_slice()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_client_state() {
+        let raw = RawClientState {
+            mrenclave: [1u8; 32].to_vec(),
+            key_expiration: 60 * 60 * 24 * 7,
+            frozen: false,
+            latest_height: Some(ProtoHeight {
+                revision_number: 1,
+                revision_height: 100,
+            }),
+            allowed_quote_statuses: vec!["SW_HARDENING_NEEDED".into()],
+            allowed_advisory_ids: vec!["INTEL-SA-00615".into()],
+            digest_algorithm: DigestAlgorithm::Sha256.to_string(),
+            signature_format: SignatureFormat::Compact.to_string(),
+        };
+        let scale = ScaleClientState::from(raw.clone());
+        scale_round_trip(&scale);
+        let decoded = ScaleClientState::decode(&mut scale.encode().as_slice()).unwrap();
+        assert_eq!(
+            RawClientState::from(decoded).encode_to_vec(),
+            raw.encode_to_vec()
+        );
+
+        let client_state = ClientState {
+            mr_enclave: [2u8; 32].to_vec(),
+            key_expiration: Duration::from_secs(60),
+            latest_height: Height::new(0, 1),
+            frozen: true,
+            signature_scheme: SignatureScheme::default(),
+        };
+        let scale = ScaleClientState::from(client_state.clone());
+        scale_round_trip(&scale);
+        assert_eq!(ClientState::try_from(scale).unwrap(), client_state);
+    }
+
+    #[test]
+    fn test_consensus_state() {
+        let consensus_state = ConsensusState {
+            state_id: StateID::from([3u8; 32]),
+            timestamp: Time::from_unix_timestamp_nanos(1_700_000_000 * 1_000_000_000).unwrap(),
+        };
+        let scale = ScaleConsensusState::from(consensus_state.clone());
+        scale_round_trip(&scale);
+        assert_eq!(
+            RawConsensusState::from(scale.clone()).encode_to_vec(),
+            RawConsensusState::from(consensus_state.clone()).encode_to_vec()
+        );
+        assert_eq!(ConsensusState::try_from(scale).unwrap(), consensus_state);
+
+        let invalid = ScaleConsensusState {
+            state_id: vec![0u8; 31],
+            timestamp: 0,
+        };
+        assert!(ConsensusState::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn test_update_client_message() {
+        let msg = UpdateClientMessage {
+            signer: Address([4u8; 20]),
+            signature: vec![5u8; 65],
+            proxy_message: ProxyMessage::UpdateState(UpdateStateProxyMessage {
+                prev_height: Some(Height::new(0, 1)),
+                prev_state_id: Some(StateID::from([5u8; 32])),
+                post_height: Height::new(0, 2),
+                post_state_id: StateID::from([6u8; 32]),
+                frozen_height: None,
+                timestamp: Time::from_unix_timestamp_nanos(1_700_000_000 * 1_000_000_000).unwrap(),
+                expires_at: None,
+                context: ValidationContext::Empty,
+                emitted_states: Default::default(),
+            }),
+        };
+        let scale = ScaleUpdateClientMessage::from(msg.clone());
+        scale_round_trip(&scale);
+        assert_eq!(
+            RawUpdateClientMessage::from(scale.clone()).encode_to_vec(),
+            msg.clone().encode_vec().unwrap()
+        );
+        assert_eq!(UpdateClientMessage::try_from(scale).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_register_enclave_key_message() {
+        let raw = RawRegisterEnclaveKeyMessage {
+            report: "{}".into(),
+            signature: vec![7u8; 4],
+            signing_cert: vec![8u8; 4],
+        };
+        let scale = ScaleRegisterEnclaveKeyMessage::from(raw.clone());
+        scale_round_trip(&scale);
+        let msg = RegisterEnclaveKeyMessage::try_from(scale.clone()).unwrap();
+        assert_eq!(ScaleRegisterEnclaveKeyMessage::from(msg), scale);
+        assert_eq!(
+            RawRegisterEnclaveKeyMessage::from(scale).encode_to_vec(),
+            raw.encode_to_vec()
+        );
+        // the protobuf form in `Any` is unchanged by the SCALE round trip
+        let any: Any = crate::message::ClientMessage::RegisterEnclaveKey(
+            RegisterEnclaveKeyMessage::decode_vec(&raw.encode_to_vec()).unwrap(),
+        )
+        .into();
+        assert_eq!(any.value, raw.encode_to_vec());
+    }
+}
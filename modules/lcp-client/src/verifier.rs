@@ -0,0 +1,320 @@
+//! A verifier of the LCP commitments that does not depend on the IBC context of the host
+//!
+//! `LCPClient` reads and writes the states through the IBC context, which a Substrate pallet does not have.
+//! The functions here verify a message against the states that the caller loads from its own storage,
+//! and return the states that the caller should store.
+use crate::client_def::verify_report;
+use crate::client_state::ClientState;
+use crate::consensus_state::ConsensusState;
+use crate::errors::Error;
+use crate::message::{RegisterEnclaveKeyMessage, UpdateClientMessage};
+use crate::prelude::*;
+use crypto::{Address, Keccak256};
+use light_client::commitments::{
+    CommitmentPrefix, CommitmentProof, EthABIEncoder, ProxyMessage, StateID,
+    VerifyMembershipProxyMessage,
+};
+use light_client::types::{Height, Time};
+
+/// VerifierContext gives the verifier the states of a client that the caller stores
+pub trait VerifierContext {
+    /// Returns the current timestamp of the host
+    fn host_timestamp(&self) -> Time;
+
+    /// Returns the consensus state of the client at the height if it exists
+    fn consensus_state(&self, height: &Height) -> Option<ConsensusState>;
+
+    /// Returns the UNIX time in seconds when the enclave key expires if it has been registered to the client
+    fn enclave_key_expiration(&self, key: &Address) -> Option<u64>;
+}
+
+/// VerifiedUpdate is the states that the caller should store after a message has been verified
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifiedUpdate {
+    /// The new client state, and the new consensus state at `height`
+    UpdateState {
+        client_state: ClientState,
+        height: Height,
+        consensus_state: ConsensusState,
+    },
+    /// The client state that has been frozen by a misbehaviour
+    Misbehaviour { client_state: ClientState },
+}
+
+/// RegisteredEnclaveKey is an enclave key that the caller should register to the client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisteredEnclaveKey {
+    pub key: Address,
+    /// UNIX time in seconds
+    pub expired_at: u64,
+}
+
+/// Verify a message that updates the client, and returns the states to store
+///
+/// Unlike `LCPClient`, the signer must not have expired, and each check fails with an error instead of a panic.
+pub fn verify_update_client<C: VerifierContext + ?Sized>(
+    ctx: &C,
+    client_state: &ClientState,
+    message: UpdateClientMessage,
+) -> Result<VerifiedUpdate, Error> {
+    if client_state.frozen {
+        return Err(Error::client_frozen());
+    }
+    let now = ctx.host_timestamp();
+    message.proxy_message.validate()?;
+    message.proxy_message.check_expiry(now)?;
+    verify_active_key(ctx, &message.signer)?;
+    client_state.signature_scheme.verify_signer(
+        message.proxy_message.clone().to_bytes().as_slice(),
+        &message.signature,
+        message.signer,
+    )?;
+
+    match message.proxy_message {
+        ProxyMessage::UpdateState(msg) => {
+            if client_state.latest_height.is_zero() {
+                if msg.emitted_states.is_empty() {
+                    return Err(Error::invalid_message(
+                        "the first update must emit the initial states".into(),
+                    ));
+                }
+            } else {
+                match (msg.prev_height, msg.prev_state_id) {
+                    (Some(prev_height), Some(prev_state_id)) => {
+                        verify_state_id(ctx, &prev_height, prev_state_id)?;
+                    }
+                    _ => {
+                        return Err(Error::invalid_message(
+                            "prev_height and prev_state_id must be set".into(),
+                        ))
+                    }
+                }
+            }
+            msg.context.validate(now)?;
+            Ok(VerifiedUpdate::UpdateState {
+                client_state: client_state.clone().with_header(&msg),
+                height: msg.post_height,
+                consensus_state: ConsensusState {
+                    state_id: msg.post_state_id,
+                    timestamp: msg.timestamp,
+                },
+            })
+        }
+        ProxyMessage::Misbehaviour(msg) => {
+            for state in msg.prev_states.iter() {
+                verify_state_id(ctx, &state.height, state.state_id)?;
+            }
+            msg.context.validate(now)?;
+            Ok(VerifiedUpdate::Misbehaviour {
+                client_state: client_state.clone().with_frozen(),
+            })
+        }
+        msg => Err(Error::unexpected_header_type(format!("{}", msg))),
+    }
+}
+
+/// Verify a message that registers an enclave key, and returns the key with its expiration
+pub fn verify_register_enclave_key<C: VerifierContext + ?Sized>(
+    ctx: &C,
+    client_state: &ClientState,
+    message: RegisterEnclaveKeyMessage,
+) -> Result<RegisteredEnclaveKey, Error> {
+    let (key, attestation_time) = verify_report(ctx.host_timestamp(), client_state, &message.0)?;
+    Ok(RegisteredEnclaveKey {
+        key,
+        expired_at: (attestation_time + client_state.key_expiration)?.as_unix_timestamp_secs(),
+    })
+}
+
+/// Verify the ethabi encoded commitment proof of the existence of `value` at `path` at `proof_height`
+pub fn verify_membership<C: VerifierContext + ?Sized>(
+    ctx: &C,
+    client_state: &ClientState,
+    prefix: CommitmentPrefix,
+    path: String,
+    value: Vec<u8>,
+    proof_height: Height,
+    proof: &[u8],
+) -> Result<(), Error> {
+    let proof = CommitmentProof::ethabi_decode(proof)?;
+    let message = proof.message()?;
+    message.check_expiry(ctx.host_timestamp())?;
+    let msg: VerifyMembershipProxyMessage = message.try_into()?;
+
+    if msg.prefix != prefix {
+        return Err(Error::invalid_message("prefix mismatch".into()));
+    }
+    if msg.path != path {
+        return Err(Error::invalid_message(format!(
+            "path mismatch: expected={} actual={}",
+            path, msg.path
+        )));
+    }
+    if msg.height != proof_height {
+        return Err(Error::invalid_message(format!(
+            "height mismatch: expected={} actual={}",
+            proof_height, msg.height
+        )));
+    }
+    if msg.value != Some(value.keccak256()) {
+        return Err(Error::invalid_message("value mismatch".into()));
+    }
+    let consensus_state = verify_state_id(ctx, &proof_height, msg.state_id)?;
+    if let Some(trusted_timestamp) = msg.trusted_timestamp {
+        if consensus_state.timestamp != trusted_timestamp {
+            return Err(Error::invalid_message(format!(
+                "trusted timestamp mismatch: expected={:?} actual={:?}",
+                consensus_state.timestamp, trusted_timestamp
+            )));
+        }
+    }
+
+    client_state
+        .signature_scheme
+        .verify_signer(&proof.message, &proof.signature, proof.signer)?;
+    verify_active_key(ctx, &proof.signer)
+}
+
+fn verify_active_key<C: VerifierContext + ?Sized>(ctx: &C, key: &Address) -> Result<(), Error> {
+    match ctx.enclave_key_expiration(key) {
+        Some(expired_at) if ctx.host_timestamp().as_unix_timestamp_secs() < expired_at => Ok(()),
+        _ => Err(Error::inactive_enclave_key(*key)),
+    }
+}
+
+fn verify_state_id<C: VerifierContext + ?Sized>(
+    ctx: &C,
+    height: &Height,
+    state_id: StateID,
+) -> Result<ConsensusState, Error> {
+    let consensus_state = ctx
+        .consensus_state(height)
+        .ok_or_else(|| Error::consensus_state_not_found(*height))?;
+    if consensus_state.state_id != state_id {
+        return Err(Error::state_id_mismatch(consensus_state.state_id, state_id));
+    }
+    Ok(consensus_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use core::time::Duration;
+    use crypto::{EnclaveKey, Signer};
+    use light_client::commitments::{
+        prove_commitment, EmittedState, UpdateStateProxyMessage, ValidationContext,
+    };
+    use light_client::types::Any;
+
+    struct MockContext {
+        now: Time,
+        consensus_states: BTreeMap<Height, ConsensusState>,
+        keys: BTreeMap<[u8; 20], u64>,
+    }
+
+    impl VerifierContext for MockContext {
+        fn host_timestamp(&self) -> Time {
+            self.now
+        }
+
+        fn consensus_state(&self, height: &Height) -> Option<ConsensusState> {
+            self.consensus_states.get(height).cloned()
+        }
+
+        fn enclave_key_expiration(&self, key: &Address) -> Option<u64> {
+            self.keys.get(&key.0).cloned()
+        }
+    }
+
+    fn update_message(
+        ek: &EnclaveKey,
+        prev: Option<(Height, StateID)>,
+        post: (Height, StateID),
+    ) -> UpdateClientMessage {
+        let address = ek.pubkey().unwrap().as_address();
+        let message = ProxyMessage::UpdateState(UpdateStateProxyMessage {
+            prev_height: prev.map(|p| p.0),
+            prev_state_id: prev.map(|p| p.1),
+            post_height: post.0,
+            post_state_id: post.1,
+            frozen_height: None,
+            timestamp: Time::now(),
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: vec![EmittedState(
+                post.0,
+                Any::new("/mock".into(), Default::default()),
+            )],
+        });
+        let proof = prove_commitment(ek, address, message).unwrap();
+        UpdateClientMessage {
+            signer: proof.signer,
+            signature: proof.signature.clone(),
+            proxy_message: proof.message().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_verify_update_client() {
+        let ek = EnclaveKey::new().unwrap();
+        let address = ek.pubkey().unwrap().as_address();
+        let now = Time::now();
+        let mut ctx = MockContext {
+            now,
+            consensus_states: Default::default(),
+            keys: Default::default(),
+        };
+        let client_state = ClientState {
+            mr_enclave: [0u8; 32].to_vec(),
+            key_expiration: Duration::from_secs(60),
+            latest_height: Height::zero(),
+            frozen: false,
+            signature_scheme: Default::default(),
+        };
+        let (h1, s1) = (Height::new(0, 1), StateID::from([1u8; 32]));
+        let msg = update_message(&ek, None, (h1, s1));
+
+        // the signer has not been registered
+        assert!(verify_update_client(&ctx, &client_state, msg.clone()).is_err());
+
+        ctx.keys
+            .insert(address.0, now.as_unix_timestamp_secs() + 60);
+        let client_state = match verify_update_client(&ctx, &client_state, msg).unwrap() {
+            VerifiedUpdate::UpdateState {
+                client_state,
+                height,
+                consensus_state,
+            } => {
+                assert_eq!(height, h1);
+                assert_eq!(consensus_state.state_id, s1);
+                ctx.consensus_states.insert(height, consensus_state);
+                client_state
+            }
+            res => panic!("unexpected result: {:?}", res),
+        };
+        assert_eq!(client_state.latest_height, h1);
+
+        // the previous state must match the stored consensus state
+        let (h2, s2) = (Height::new(0, 2), StateID::from([2u8; 32]));
+        let msg = update_message(&ek, Some((h1, StateID::from([9u8; 32]))), (h2, s2));
+        assert!(verify_update_client(&ctx, &client_state, msg).is_err());
+        let msg = update_message(&ek, Some((h1, s1)), (h2, s2));
+        assert!(verify_update_client(&ctx, &client_state, msg.clone()).is_ok());
+
+        // the signature must be produced by the signer
+        let mut forged = msg.clone();
+        forged.signer = EnclaveKey::new().unwrap().pubkey().unwrap().as_address();
+        ctx.keys
+            .insert(forged.signer.0, now.as_unix_timestamp_secs() + 60);
+        assert!(verify_update_client(&ctx, &client_state, forged).is_err());
+
+        // the expired key is rejected
+        ctx.keys.insert(address.0, now.as_unix_timestamp_secs());
+        assert!(verify_update_client(&ctx, &client_state, msg.clone()).is_err());
+
+        ctx.keys
+            .insert(address.0, now.as_unix_timestamp_secs() + 60);
+        assert!(verify_update_client(&ctx, &client_state.with_frozen(), msg).is_err());
+    }
+}
@@ -2,9 +2,11 @@ use crate::commitments::{CommitmentPrefix, ProxyMessage};
 use crate::context::HostClientReader;
 use crate::errors::Error;
 use crate::prelude::*;
-use crate::types::{Any, ClientId, Height};
+use crate::registry::versioned_client_type;
+use crate::types::{Any, Capabilities, ClientId, ClientParams, Height, Time};
 use commitments::{
-    MisbehaviourProxyMessage, UpdateStateProxyMessage, VerifyMembershipProxyMessage,
+    MisbehaviourProxyMessage, UpdateStateProxyMessage, VerifyKeyValueProxyMessage,
+    VerifyMembershipProxyMessage,
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -12,6 +14,13 @@ pub trait LightClient {
     /// client_type returns a client type of the light client
     fn client_type(&self) -> String;
 
+    /// capabilities returns the optional features that the light client supports
+    ///
+    /// The default is no optional feature, so light clients must declare what they override.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
     /// latest_height returns the latest height that the light client tracks
     fn latest_height(
         &self,
@@ -27,6 +36,14 @@ pub trait LightClient {
         any_consensus_state: Any,
     ) -> Result<CreateClientResult, Error>;
 
+    /// validate_client_state returns an error if this implementation cannot decode the client state
+    ///
+    /// It lets the operators check that the states stored by a previous implementation are still readable after an upgrade.
+    fn validate_client_state(&self, any_client_state: Any) -> Result<(), Error>;
+
+    /// validate_consensus_state returns an error if this implementation cannot decode the consensus state
+    fn validate_consensus_state(&self, any_consensus_state: Any) -> Result<(), Error>;
+
     /// update_client updates the light client with a header
     fn update_client(
         &self,
@@ -57,6 +74,128 @@ pub trait LightClient {
         proof_height: Height,
         proof: Vec<u8>,
     ) -> Result<VerifyNonMembershipResult, Error>;
+
+    /// verify_membership_batch verifies the existence of multiple values at the same height
+    ///
+    /// The results are in the same order as `items`. The default implementation verifies each item independently,
+    /// so light clients whose proofs share a common part should override it to verify that part only once.
+    fn verify_membership_batch(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        items: Vec<MembershipItem>,
+        proof_height: Height,
+    ) -> Result<Vec<VerifyMembershipResult>, Error> {
+        items
+            .into_iter()
+            .map(|item| {
+                self.verify_membership(
+                    ctx,
+                    client_id.clone(),
+                    prefix.clone(),
+                    item.path,
+                    item.value,
+                    proof_height,
+                    item.proof,
+                )
+            })
+            .collect()
+    }
+
+    /// verify_key_value verifies the value of an arbitrary key in the store identified by `prefix`, or its absence if `value` is None
+    ///
+    /// The key is not interpreted as an ICS-24 path. Light clients that cannot verify raw keys return an error by default.
+    fn verify_key_value(
+        &self,
+        _ctx: &dyn HostClientReader,
+        _client_id: ClientId,
+        _prefix: CommitmentPrefix,
+        _key: Vec<u8>,
+        _value: Option<Vec<u8>>,
+        _proof_height: Height,
+        _proof: Vec<u8>,
+    ) -> Result<VerifyKeyValueResult, Error> {
+        Err(Error::key_value_verification_not_supported(
+            self.client_type(),
+        ))
+    }
+
+    /// validate_client_params returns an error if the parameters cannot be applied to the client
+    ///
+    /// The parameters are stored by the caller and override the client state in the later verifications.
+    /// Light clients that have no configurable parameters return an error by default.
+    fn validate_client_params(
+        &self,
+        _ctx: &dyn HostClientReader,
+        _client_id: &ClientId,
+        _params: &ClientParams,
+    ) -> Result<(), Error> {
+        Err(Error::client_params_not_supported(self.client_type()))
+    }
+
+    /// migrate_client_state converts a client state stored by the implementation `from_type_url` of `from_version` to the one of this implementation
+    ///
+    /// The converted state must keep the latest height and the security parameters of the client.
+    /// Light clients that cannot take over the states of another implementation return an error by default.
+    fn migrate_client_state(
+        &self,
+        from_type_url: &str,
+        from_version: u32,
+        _any_client_state: Any,
+    ) -> Result<Any, Error> {
+        Err(Error::client_migration_not_supported(
+            self.client_type(),
+            versioned_client_type(from_type_url, from_version),
+        ))
+    }
+
+    /// migrate_consensus_state converts a consensus state stored by the implementation `from_type_url` of `from_version` to the one of this implementation
+    fn migrate_consensus_state(
+        &self,
+        from_type_url: &str,
+        from_version: u32,
+        _any_consensus_state: Any,
+    ) -> Result<Any, Error> {
+        Err(Error::client_migration_not_supported(
+            self.client_type(),
+            versioned_client_type(from_type_url, from_version),
+        ))
+    }
+
+    /// latest_state returns a message whose prev and post states are both the latest state of the client
+    ///
+    /// Signing the message attests the current state again without advancing the height.
+    /// Light clients that cannot identify their latest state return an error by default.
+    fn latest_state(
+        &self,
+        _ctx: &dyn HostClientReader,
+        _client_id: &ClientId,
+    ) -> Result<UpdateStateProxyMessage, Error> {
+        Err(Error::latest_state_not_supported(self.client_type()))
+    }
+
+    /// trusting_period returns the trusting period of the client if the light client has the notion of it
+    ///
+    /// Consensus states older than the trusting period can never be used to verify a header, so they can be pruned.
+    fn trusting_period(
+        &self,
+        _ctx: &dyn HostClientReader,
+        _client_id: &ClientId,
+    ) -> Result<Option<core::time::Duration>, Error> {
+        Ok(None)
+    }
+
+    /// latest_timestamp returns the timestamp of the latest consensus state if the light client has the notion of it
+    ///
+    /// Together with `trusting_period`, it determines when the client expires without an update.
+    fn latest_timestamp(
+        &self,
+        _ctx: &dyn HostClientReader,
+        _client_id: &ClientId,
+    ) -> Result<Option<Time>, Error> {
+        Ok(None)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -109,6 +248,14 @@ impl From<MisbehaviourData> for UpdateClientResult {
     }
 }
 
+/// MembershipItem is a value and its proof of existence at a path
+#[derive(Clone, Debug, PartialEq)]
+pub struct MembershipItem {
+    pub path: String,
+    pub value: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct VerifyMembershipResult {
     /// message represents a result of the state verification
@@ -120,3 +267,9 @@ pub struct VerifyNonMembershipResult {
     /// message represents a result of the state verification
     pub message: VerifyMembershipProxyMessage,
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifyKeyValueResult {
+    /// message represents a result of the state verification
+    pub message: VerifyKeyValueProxyMessage,
+}
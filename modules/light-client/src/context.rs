@@ -1,9 +1,20 @@
-use crate::types::{Any, ClientId, Height, Time};
+use crate::types::{
+    Any, ClientId, ClientParams, Height, Time, VerificationMode, VoteExtensionPolicy,
+};
 use crate::{
     errors::Error,
-    path::{ClientConsensusStatePath, ClientStatePath, ClientTypePath, NEXT_CLIENT_SEQUENCE},
+    path::{
+        ClientConsensusStatePath, ClientStatePath, ClientTypePath, CommitmentPrefixPath,
+        ConsensusStateIndexPath, ExpiredAtPath, LightClientVersionPath, MaxClockDriftPath,
+        MessageSchemaVersionPath, ProofSpecsPath, SignatureSchemePath, TrustingPeriodPath,
+        VerificationModePath, VoteExtensionPolicyPath, CLIENT_IDS, NEXT_CLIENT_SEQUENCE,
+    },
     prelude::*,
+    registry::DEFAULT_LIGHT_CLIENT_VERSION,
 };
+use commitments::MESSAGE_SCHEMA_VERSION_1;
+use core::time::Duration;
+use crypto::SignatureScheme;
 use store::KVStore;
 
 pub trait HostContext {
@@ -67,6 +78,187 @@ pub trait ClientReader: KVStore {
             None => Ok(0),
         }
     }
+
+    /// Returns the identifiers of the clients recorded by `ClientKeeper::store_client_id`.
+    fn client_ids(&self) -> Result<Vec<ClientId>, Error> {
+        Ok(self
+            .get(CLIENT_IDS.as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the heights of the indexed consensus states in ascending order,
+    /// paired with the host timestamp when each state was stored.
+    fn consensus_state_index(&self, client_id: &ClientId) -> Result<Vec<(Height, Time)>, Error> {
+        Ok(self
+            .get(format!("{}", ConsensusStateIndexPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns an error if the consensus state at `height` is not stored
+    ///
+    /// A missing state older than the earliest indexed one is reported as pruned,
+    /// so that the caller can tell it from a height the client has never been updated to.
+    fn ensure_consensus_state(&self, client_id: &ClientId, height: &Height) -> Result<(), Error> {
+        let path = ClientConsensusStatePath::new(client_id, height);
+        if self.get(format!("{}", path).as_bytes()).is_some() {
+            return Ok(());
+        }
+        match self.consensus_state_index(client_id)?.first() {
+            Some((earliest, _)) if height < earliest => Err(Error::consensus_state_pruned(
+                client_id.clone(),
+                *height,
+                *earliest,
+            )),
+            _ => Err(Error::consensus_state_not_found(client_id.clone(), *height)),
+        }
+    }
+
+    /// Returns the commitment prefix configured for the client, if any
+    fn commitment_prefix(&self, client_id: &ClientId) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.get(format!("{}", CommitmentPrefixPath::new(client_id)).as_bytes()))
+    }
+
+    /// Returns an error if the client has a configured commitment prefix that differs from `prefix`
+    ///
+    /// Clients without a configured prefix accept any prefix.
+    fn validate_commitment_prefix(&self, client_id: &ClientId, prefix: &[u8]) -> Result<(), Error> {
+        match self.commitment_prefix(client_id)? {
+            Some(expected) if expected != prefix => Err(Error::commitment_prefix_mismatch(
+                client_id.clone(),
+                expected,
+                prefix.to_vec(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the verification mode configured for the client
+    ///
+    /// Clients without a configured mode use `VerificationMode::default()`.
+    fn verification_mode(&self, client_id: &ClientId) -> Result<VerificationMode, Error> {
+        Ok(self
+            .get(format!("{}", VerificationModePath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the policy for the vote extensions in the headers of the client
+    ///
+    /// Clients without a configured policy use `VoteExtensionPolicy::default()`.
+    fn vote_extension_policy(&self, client_id: &ClientId) -> Result<VoteExtensionPolicy, Error> {
+        Ok(self
+            .get(format!("{}", VoteExtensionPolicyPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the scheme that the enclave signs the commitments of the client with
+    ///
+    /// Clients without a configured scheme use `SignatureScheme::default()`.
+    fn signature_scheme(&self, client_id: &ClientId) -> Result<SignatureScheme, Error> {
+        Ok(self
+            .get(format!("{}", SignatureSchemePath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns the version of the message schema that the verifier of the client accepts
+    ///
+    /// Clients without a configured version use `MESSAGE_SCHEMA_VERSION_1`, which the verifiers that have not been upgraded accept.
+    fn message_schema_version(&self, client_id: &ClientId) -> Result<u16, Error> {
+        Ok(self
+            .get(format!("{}", MessageSchemaVersionPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                let mut b: [u8; 2] = Default::default();
+                b.copy_from_slice(&bz);
+                u16::from_be_bytes(b)
+            })
+            .unwrap_or(MESSAGE_SCHEMA_VERSION_1))
+    }
+
+    /// Returns the version of the light client implementation that the client is pinned to
+    ///
+    /// Clients created before the versioning was introduced are pinned to `DEFAULT_LIGHT_CLIENT_VERSION`.
+    fn light_client_version(&self, client_id: &ClientId) -> Result<u32, Error> {
+        Ok(self
+            .get(format!("{}", LightClientVersionPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                let mut b: [u8; 4] = Default::default();
+                b.copy_from_slice(&bz);
+                u32::from_be_bytes(b)
+            })
+            .unwrap_or(DEFAULT_LIGHT_CLIENT_VERSION))
+    }
+
+    /// Returns the maximum clock drift configured for the client, if any
+    ///
+    /// If configured, the light client uses it instead of the drift of the client state.
+    fn max_clock_drift(&self, client_id: &ClientId) -> Result<Option<Duration>, Error> {
+        Ok(self
+            .get(format!("{}", MaxClockDriftPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            }))
+    }
+
+    /// Returns the trusting period configured for the client by `ClientKeeper::store_client_params`, if any
+    ///
+    /// If configured, the light client uses it instead of the trusting period of the client state.
+    fn trusting_period_override(&self, client_id: &ClientId) -> Result<Option<Duration>, Error> {
+        Ok(self
+            .get(format!("{}", TrustingPeriodPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            }))
+    }
+
+    /// Returns the proto-encoded proof specs configured for the client by `ClientKeeper::store_client_params`, if any
+    fn proof_specs_override(&self, client_id: &ClientId) -> Result<Option<Vec<Vec<u8>>>, Error> {
+        Ok(self
+            .get(format!("{}", ProofSpecsPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            }))
+    }
+
+    /// Returns the time when the trusting period of the client elapsed, if the client has been marked as expired
+    fn expired_at(&self, client_id: &ClientId) -> Result<Option<Time>, Error> {
+        Ok(self
+            .get(format!("{}", ExpiredAtPath::new(client_id)).as_bytes())
+            .map(|bz| {
+                bincode::serde::decode_from_slice(&bz, bincode::config::standard())
+                    .unwrap()
+                    .0
+            }))
+    }
 }
 
 pub trait ClientKeeper: ClientReader {
@@ -107,6 +299,149 @@ pub trait ClientKeeper: ClientReader {
         Ok(())
     }
 
+    /// Called upon client creation if the client restricts the store prefix of the proofs
+    fn store_commitment_prefix(
+        &mut self,
+        client_id: ClientId,
+        prefix: Vec<u8>,
+    ) -> Result<(), Error> {
+        self.set(
+            format!("{}", CommitmentPrefixPath::new(&client_id)).into_bytes(),
+            prefix,
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation if the client restricts the headers that it accepts
+    fn store_verification_mode(
+        &mut self,
+        client_id: ClientId,
+        mode: VerificationMode,
+    ) -> Result<(), Error> {
+        let bz = bincode::serde::encode_to_vec(mode, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", VerificationModePath::new(&client_id)).into_bytes(),
+            bz,
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation if the client configures how the vote extensions are handled
+    fn store_vote_extension_policy(
+        &mut self,
+        client_id: ClientId,
+        policy: VoteExtensionPolicy,
+    ) -> Result<(), Error> {
+        let bz = bincode::serde::encode_to_vec(policy, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", VoteExtensionPolicyPath::new(&client_id)).into_bytes(),
+            bz,
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation if the client is signed with a scheme other than the default
+    fn store_signature_scheme(
+        &mut self,
+        client_id: ClientId,
+        scheme: SignatureScheme,
+    ) -> Result<(), Error> {
+        let bz = bincode::serde::encode_to_vec(scheme, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", SignatureSchemePath::new(&client_id)).into_bytes(),
+            bz,
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation if the verifier of the client accepts a message schema other than the version 1
+    fn store_message_schema_version(
+        &mut self,
+        client_id: ClientId,
+        version: u16,
+    ) -> Result<(), Error> {
+        self.set(
+            format!("{}", MessageSchemaVersionPath::new(&client_id)).into_bytes(),
+            version.to_be_bytes().to_vec(),
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation to pin the client to the version of the light client implementation
+    fn store_light_client_version(
+        &mut self,
+        client_id: ClientId,
+        version: u32,
+    ) -> Result<(), Error> {
+        self.set(
+            format!("{}", LightClientVersionPath::new(&client_id)).into_bytes(),
+            version.to_be_bytes().to_vec(),
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation if the client overrides the clock drift of the client state
+    fn store_max_clock_drift(
+        &mut self,
+        client_id: ClientId,
+        max_clock_drift: Duration,
+    ) -> Result<(), Error> {
+        let bz =
+            bincode::serde::encode_to_vec(max_clock_drift, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", MaxClockDriftPath::new(&client_id)).into_bytes(),
+            bz,
+        );
+        Ok(())
+    }
+
+    /// Called upon the update of the client parameters, where the parameters that are None are kept as is
+    fn store_client_params(
+        &mut self,
+        client_id: ClientId,
+        params: ClientParams,
+    ) -> Result<(), Error> {
+        if let Some(trusting_period) = params.trusting_period {
+            let bz = bincode::serde::encode_to_vec(trusting_period, bincode::config::standard())
+                .unwrap();
+            self.set(
+                format!("{}", TrustingPeriodPath::new(&client_id)).into_bytes(),
+                bz,
+            );
+        }
+        if let Some(max_clock_drift) = params.max_clock_drift {
+            self.store_max_clock_drift(client_id.clone(), max_clock_drift)?;
+        }
+        if let Some(proof_specs) = params.proof_specs {
+            let bz =
+                bincode::serde::encode_to_vec(&proof_specs, bincode::config::standard()).unwrap();
+            self.set(
+                format!("{}", ProofSpecsPath::new(&client_id)).into_bytes(),
+                bz,
+            );
+        }
+        Ok(())
+    }
+
+    /// Mark the client as expired, which is never reverted even if the host timestamp goes back
+    fn store_expired_at(&mut self, client_id: ClientId, expired_at: Time) -> Result<(), Error> {
+        let bz = bincode::serde::encode_to_vec(expired_at, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", ExpiredAtPath::new(&client_id)).into_bytes(),
+            bz,
+        );
+        Ok(())
+    }
+
+    /// Called upon client creation to make the client enumerable
+    fn store_client_id(&mut self, client_id: ClientId) -> Result<(), Error> {
+        let mut client_ids = self.client_ids()?;
+        client_ids.push(client_id);
+        let bz = bincode::serde::encode_to_vec(&client_ids, bincode::config::standard()).unwrap();
+        self.set(CLIENT_IDS.as_bytes().to_vec(), bz);
+        Ok(())
+    }
+
     /// Called upon client creation.
     /// Increases the counter which keeps track of how many clients have been created.
     /// Should never fail.
@@ -121,4 +456,320 @@ pub trait ClientKeeper: ClientReader {
 
 pub trait HostClientReader: HostContext + ClientReader {}
 
-pub trait HostClientKeeper: HostContext + ClientKeeper {}
+pub trait HostClientKeeper: HostContext + ClientKeeper {
+    /// Called upon successful client creation and update
+    /// Records the host timestamp when the consensus state at `height` was stored.
+    fn index_consensus_state(&mut self, client_id: &ClientId, height: Height) -> Result<(), Error> {
+        let mut index = self.consensus_state_index(client_id)?;
+        if let Err(pos) = index.binary_search_by(|(h, _)| h.cmp(&height)) {
+            index.insert(pos, (height, self.host_timestamp()));
+            self.store_consensus_state_index(client_id, &index);
+        }
+        Ok(())
+    }
+
+    /// Removes the indexed consensus states stored before `expiry` except the one at `latest_height`
+    fn prune_consensus_states(
+        &mut self,
+        client_id: &ClientId,
+        latest_height: Height,
+        expiry: Time,
+    ) -> Result<PrunedConsensusStates, Error> {
+        let mut pruned = PrunedConsensusStates::default();
+        let mut index = self.consensus_state_index(client_id)?;
+        index.retain(|(height, stored_at)| {
+            if *height == latest_height || *stored_at >= expiry {
+                return true;
+            }
+            let path = format!("{}", ClientConsensusStatePath::new(client_id, height));
+            if let Some(bz) = self.get(path.as_bytes()) {
+                pruned.reclaimed_bytes += (path.len() + bz.len()) as u64;
+                self.remove(path.as_bytes());
+            }
+            pruned.heights.push(*height);
+            false
+        });
+        if !pruned.heights.is_empty() {
+            self.store_consensus_state_index(client_id, &index);
+        }
+        Ok(pruned)
+    }
+
+    fn store_consensus_state_index(&mut self, client_id: &ClientId, index: &[(Height, Time)]) {
+        let bz = bincode::serde::encode_to_vec(index, bincode::config::standard()).unwrap();
+        self.set(
+            format!("{}", ConsensusStateIndexPath::new(client_id)).into_bytes(),
+            bz,
+        );
+    }
+}
+
+/// The result of `HostClientKeeper::prune_consensus_states`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrunedConsensusStates {
+    pub heights: Vec<Height>,
+    /// the total size of the removed keys and values
+    pub reclaimed_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ErrorDetail, ErrorKind};
+    use alloc::collections::BTreeMap;
+    use core::time::Duration;
+    use crypto::{DigestAlgorithm, SignatureFormat};
+
+    #[derive(Default)]
+    struct TestContext {
+        kvs: BTreeMap<Vec<u8>, Vec<u8>>,
+        timestamp: Option<Time>,
+    }
+
+    impl KVStore for TestContext {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.kvs.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.kvs.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.kvs.remove(key);
+        }
+    }
+
+    impl HostContext for TestContext {
+        fn host_timestamp(&self) -> Time {
+            self.timestamp.unwrap()
+        }
+    }
+
+    impl ClientReader for TestContext {}
+    impl ClientKeeper for TestContext {}
+    impl HostClientReader for TestContext {}
+    impl HostClientKeeper for TestContext {}
+
+    fn time(secs: u64) -> Time {
+        (Time::unix_epoch() + Duration::from_secs(secs)).unwrap()
+    }
+
+    #[test]
+    fn test_prune_consensus_states() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        ctx.store_client_id(client_id.clone()).unwrap();
+        assert_eq!(ctx.client_ids().unwrap(), vec![client_id.clone()]);
+
+        for n in 1..=3 {
+            let height = Height::new(0, n);
+            ctx.timestamp = Some(time(n * 100));
+            ctx.store_any_consensus_state(
+                client_id.clone(),
+                height,
+                Any::new("/test".into(), vec![n as u8]),
+            )
+            .unwrap();
+            ctx.index_consensus_state(&client_id, height).unwrap();
+        }
+
+        // the latest height is kept even if it has expired
+        let pruned = ctx
+            .prune_consensus_states(&client_id, Height::new(0, 1), time(250))
+            .unwrap();
+        assert_eq!(pruned.heights, vec![Height::new(0, 2)]);
+        assert!(pruned.reclaimed_bytes > 0);
+        assert!(ctx.consensus_state(&client_id, &Height::new(0, 2)).is_err());
+        assert!(ctx.consensus_state(&client_id, &Height::new(0, 1)).is_ok());
+        assert_eq!(
+            ctx.consensus_state_index(&client_id)
+                .unwrap()
+                .into_iter()
+                .map(|(h, _)| h)
+                .collect::<Vec<_>>(),
+            vec![Height::new(0, 1), Height::new(0, 3)]
+        );
+
+        let pruned = ctx
+            .prune_consensus_states(&client_id, Height::new(0, 1), time(250))
+            .unwrap();
+        assert_eq!(pruned, Default::default());
+    }
+
+    #[test]
+    fn test_ensure_consensus_state() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        for n in 1..=3 {
+            let height = Height::new(0, n * 10);
+            ctx.timestamp = Some(time(n * 100));
+            ctx.store_any_consensus_state(
+                client_id.clone(),
+                height,
+                Any::new("/test".into(), vec![n as u8]),
+            )
+            .unwrap();
+            ctx.index_consensus_state(&client_id, height).unwrap();
+        }
+        ctx.prune_consensus_states(&client_id, Height::new(0, 30), time(150))
+            .unwrap();
+
+        assert!(ctx
+            .ensure_consensus_state(&client_id, &Height::new(0, 20))
+            .is_ok());
+        let err = ctx
+            .ensure_consensus_state(&client_id, &Height::new(0, 10))
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ConsensusStatePruned(_)));
+        assert_eq!(err.kind(), ErrorKind::ConsensusStateNotFound);
+        let err = ctx
+            .ensure_consensus_state(&client_id, &Height::new(0, 25))
+            .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::ConsensusStateNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_validate_commitment_prefix() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        // any prefix is accepted until the client is configured with one
+        ctx.validate_commitment_prefix(&client_id, b"other")
+            .unwrap();
+
+        ctx.store_commitment_prefix(client_id.clone(), b"ibc".to_vec())
+            .unwrap();
+        ctx.validate_commitment_prefix(&client_id, b"ibc").unwrap();
+        assert!(ctx
+            .validate_commitment_prefix(&client_id, b"other")
+            .is_err());
+    }
+
+    #[test]
+    fn test_verification_mode() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(
+            ctx.verification_mode(&client_id).unwrap(),
+            VerificationMode::Skipping
+        );
+        ctx.store_verification_mode(client_id.clone(), VerificationMode::Sequential)
+            .unwrap();
+        assert_eq!(
+            ctx.verification_mode(&client_id).unwrap(),
+            VerificationMode::Sequential
+        );
+    }
+
+    #[test]
+    fn test_light_client_version() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        // clients without a pinned version use the default implementation
+        assert_eq!(
+            ctx.light_client_version(&client_id).unwrap(),
+            DEFAULT_LIGHT_CLIENT_VERSION
+        );
+        ctx.store_light_client_version(client_id.clone(), 2)
+            .unwrap();
+        assert_eq!(ctx.light_client_version(&client_id).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_max_clock_drift() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(ctx.max_clock_drift(&client_id).unwrap(), None);
+        ctx.store_max_clock_drift(client_id.clone(), Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(
+            ctx.max_clock_drift(&client_id).unwrap(),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_store_client_params() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        ctx.store_max_clock_drift(client_id.clone(), Duration::from_secs(10))
+            .unwrap();
+        ctx.store_client_params(
+            client_id.clone(),
+            ClientParams {
+                trusting_period: Some(Duration::from_secs(3600)),
+                proof_specs: Some(vec![vec![1, 2, 3]]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.trusting_period_override(&client_id).unwrap(),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            ctx.proof_specs_override(&client_id).unwrap(),
+            Some(vec![vec![1, 2, 3]])
+        );
+        // the parameters that are not given are kept
+        assert_eq!(
+            ctx.max_clock_drift(&client_id).unwrap(),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_vote_extension_policy() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(
+            ctx.vote_extension_policy(&client_id).unwrap(),
+            VoteExtensionPolicy::Ignore
+        );
+        ctx.store_vote_extension_policy(client_id.clone(), VoteExtensionPolicy::Verify)
+            .unwrap();
+        assert_eq!(
+            ctx.vote_extension_policy(&client_id).unwrap(),
+            VoteExtensionPolicy::Verify
+        );
+    }
+
+    #[test]
+    fn test_signature_scheme() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(
+            ctx.signature_scheme(&client_id).unwrap(),
+            SignatureScheme::default()
+        );
+        let scheme = SignatureScheme::new(DigestAlgorithm::Sha256, SignatureFormat::Compact);
+        ctx.store_signature_scheme(client_id.clone(), scheme)
+            .unwrap();
+        assert_eq!(ctx.signature_scheme(&client_id).unwrap(), scheme);
+    }
+
+    #[test]
+    fn test_message_schema_version() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(
+            ctx.message_schema_version(&client_id).unwrap(),
+            MESSAGE_SCHEMA_VERSION_1
+        );
+        ctx.store_message_schema_version(client_id.clone(), 2)
+            .unwrap();
+        assert_eq!(ctx.message_schema_version(&client_id).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_expired_at() {
+        let mut ctx = TestContext::default();
+        let client_id = ClientId::new("07-tendermint", 0).unwrap();
+        assert_eq!(ctx.expired_at(&client_id).unwrap(), None);
+        ctx.store_expired_at(client_id.clone(), time(100)).unwrap();
+        assert_eq!(ctx.expired_at(&client_id).unwrap(), Some(time(100)));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::types::{ClientId, Height};
+use crate::types::{ClientId, Height, Time};
 use flex_error::*;
 
 define_error! {
@@ -34,18 +34,151 @@ define_error! {
             format_args!("consensus_state not found: client_id={} height={}", e.client_id, e.height)
         },
 
+        ConsensusStatePruned
+        {
+            client_id: ClientId,
+            height: Height,
+            earliest: Height
+        }
+        |e| {
+            format_args!("consensus_state has been pruned: client_id={} height={} earliest={}", e.client_id, e.height, e.earliest)
+        },
+
+        CommitmentPrefixMismatch
+        {
+            client_id: ClientId,
+            expected: Vec<u8>,
+            actual: Vec<u8>
+        }
+        |e| {
+            format_args!("commitment prefix mismatch: client_id={} expected={:?} actual={:?}", e.client_id, String::from_utf8_lossy(&e.expected), String::from_utf8_lossy(&e.actual))
+        },
+
+        ClientExpired
+        {
+            client_id: ClientId,
+            expired_at: Time
+        }
+        |e| {
+            format_args!("the trusting period of the client has elapsed: client_id={} expired_at={}", e.client_id, e.expired_at)
+        },
+
+        KeyValueVerificationNotSupported
+        {
+            client_type: String
+        }
+        |e| {
+            format_args!("the light client doesn't support the verification of raw keys: client_type={}", e.client_type)
+        },
+
+        ClientParamsNotSupported
+        {
+            client_type: String
+        }
+        |e| {
+            format_args!("the light client doesn't support the update of the client parameters: client_type={}", e.client_type)
+        },
+
+        ClientMigrationNotSupported
+        {
+            client_type: String,
+            from: String
+        }
+        |e| {
+            format_args!("the light client doesn't support the migration from the implementation: client_type={} from={}", e.client_type, e.from)
+        },
+
+        LatestStateNotSupported
+        {
+            client_type: String
+        }
+        |e| {
+            format_args!("the light client doesn't support re-signing the latest state: client_type={}", e.client_type)
+        },
+
         LightClientSpecific
+        {
+            kind: ErrorKind
+        }
         [TraceError<Box<dyn LightClientSpecificError>>]
         |_| { "Light Client specific error" }
     }
 }
 
+impl Error {
+    /// Returns the machine-readable kind of the error
+    pub fn kind(&self) -> ErrorKind {
+        self.detail().kind()
+    }
+}
+
+impl ErrorDetail {
+    /// Returns the machine-readable kind of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorDetail::Commitment(e) => ErrorKind::from_commitment_error(&e.source),
+            ErrorDetail::ClientTypeNotFound(_) | ErrorDetail::ClientStateNotFound(_) => {
+                ErrorKind::ClientNotFound
+            }
+            ErrorDetail::ConsensusStateNotFound(_) | ErrorDetail::ConsensusStatePruned(_) => {
+                ErrorKind::ConsensusStateNotFound
+            }
+            ErrorDetail::CommitmentPrefixMismatch(_) => ErrorKind::ProofMismatch,
+            ErrorDetail::ClientExpired(_) => ErrorKind::Expired,
+            ErrorDetail::KeyValueVerificationNotSupported(_)
+            | ErrorDetail::ClientParamsNotSupported(_)
+            | ErrorDetail::ClientMigrationNotSupported(_)
+            | ErrorDetail::LatestStateNotSupported(_) => ErrorKind::Unknown,
+            ErrorDetail::LightClientSpecific(e) => e.kind,
+        }
+    }
+}
+
+/// ErrorKind classifies light client errors so that callers can react to them without parsing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Unknown,
+    ClientNotFound,
+    ConsensusStateNotFound,
+    ClientFrozen,
+    /// the trusted state is outside of the trusting period
+    Expired,
+    /// the header timestamp is ahead of the host clock beyond the allowed drift
+    HeaderFromFuture,
+    /// the header height is not newer than the trusted height
+    HeaderOlderThanTrusted,
+    InvalidHeader,
+    InvalidValidatorSet,
+    /// the validator set changed beyond the trust level since the trusted height,
+    /// so the client must be updated to intermediate heights first
+    BisectionRequired,
+    /// the membership or non-membership proof doesn't match the trusted commitment root
+    ProofMismatch,
+}
+
+impl ErrorKind {
+    pub fn from_commitment_error(detail: &crate::commitments::ErrorDetail) -> Self {
+        use crate::commitments::ErrorDetail;
+        match detail {
+            ErrorDetail::OutOfTrustingPeriod(_) => Self::Expired,
+            ErrorDetail::HeaderFromFuture(_) => Self::HeaderFromFuture,
+            _ => Self::Unknown,
+        }
+    }
+}
+
 /// Each Light Client's error type should implement this trait
-pub trait LightClientSpecificError: core::fmt::Display + core::fmt::Debug + Sync + Send {}
+pub trait LightClientSpecificError: core::fmt::Display + core::fmt::Debug + Sync + Send {
+    /// Returns the machine-readable kind of the error
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Unknown
+    }
+}
 
 impl<T: 'static + LightClientSpecificError> From<T> for Error {
     fn from(value: T) -> Self {
-        Self::light_client_specific(Box::new(value))
+        // the source is only kept in the trace, so the kind is taken before it is boxed
+        Self::light_client_specific(value.kind(), Box::new(value))
     }
 }
 
@@ -60,6 +193,15 @@ define_error! {
             format_args!("type_url not found: type_url={}", e.type_url)
         },
 
+        VersionNotFound
+        {
+            type_url: String,
+            version: u32
+        }
+        |e| {
+            format_args!("light client version not found: type_url={} version={}", e.type_url, e.version)
+        },
+
         TypeUrlAlreadyExists
         {
             type_url: String
@@ -72,3 +214,29 @@ define_error! {
         |_| { "registry is already sealed" },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct FrozenError;
+
+    impl core::fmt::Display for FrozenError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "frozen")
+        }
+    }
+
+    impl LightClientSpecificError for FrozenError {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::ClientFrozen
+        }
+    }
+
+    #[test]
+    fn test_light_client_specific_error_kind() {
+        let err: Error = FrozenError.into();
+        assert_eq!(err.kind(), ErrorKind::ClientFrozen);
+    }
+}
@@ -24,12 +24,18 @@ pub use commitments;
 pub use lcp_types as types;
 
 pub use client::{
-    CreateClientResult, LightClient, MisbehaviourData, UpdateClientResult, UpdateStateData,
-    VerifyMembershipResult, VerifyNonMembershipResult,
+    CreateClientResult, LightClient, MembershipItem, MisbehaviourData, UpdateClientResult,
+    UpdateStateData, VerifyKeyValueResult, VerifyMembershipResult, VerifyNonMembershipResult,
+};
+pub use context::{
+    ClientKeeper, ClientReader, HostClientKeeper, HostClientReader, HostContext,
+    PrunedConsensusStates,
+};
+pub use errors::{Error, ErrorDetail, ErrorKind, LightClientSpecificError, RegistryError};
+pub use registry::{
+    versioned_client_type, LightClientRegistry, LightClientResolver, MapLightClientRegistry,
+    DEFAULT_LIGHT_CLIENT_VERSION,
 };
-pub use context::{ClientKeeper, ClientReader, HostClientKeeper, HostClientReader, HostContext};
-pub use errors::{Error, ErrorDetail, LightClientSpecificError, RegistryError};
-pub use registry::{LightClientRegistry, LightClientResolver, MapLightClientRegistry};
 
 mod client;
 mod context;
@@ -2,6 +2,7 @@ use crate::types::{ClientId, Height};
 use derive_more::Display;
 
 pub static NEXT_CLIENT_SEQUENCE: &str = "nextClientSequence";
+pub static CLIENT_IDS: &str = "clientIds";
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
 #[display(fmt = "clients/{_0}/clientType")]
@@ -23,6 +24,116 @@ impl ClientStatePath {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/consensusStateIndex")]
+pub struct ConsensusStateIndexPath(pub ClientId);
+
+impl ConsensusStateIndexPath {
+    pub fn new(client_id: &ClientId) -> ConsensusStateIndexPath {
+        ConsensusStateIndexPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/commitmentPrefix")]
+pub struct CommitmentPrefixPath(pub ClientId);
+
+impl CommitmentPrefixPath {
+    pub fn new(client_id: &ClientId) -> CommitmentPrefixPath {
+        CommitmentPrefixPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/verificationMode")]
+pub struct VerificationModePath(pub ClientId);
+
+impl VerificationModePath {
+    pub fn new(client_id: &ClientId) -> VerificationModePath {
+        VerificationModePath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/lightClientVersion")]
+pub struct LightClientVersionPath(pub ClientId);
+
+impl LightClientVersionPath {
+    pub fn new(client_id: &ClientId) -> LightClientVersionPath {
+        LightClientVersionPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/maxClockDrift")]
+pub struct MaxClockDriftPath(pub ClientId);
+
+impl MaxClockDriftPath {
+    pub fn new(client_id: &ClientId) -> MaxClockDriftPath {
+        MaxClockDriftPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/trustingPeriod")]
+pub struct TrustingPeriodPath(pub ClientId);
+
+impl TrustingPeriodPath {
+    pub fn new(client_id: &ClientId) -> TrustingPeriodPath {
+        TrustingPeriodPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/proofSpecs")]
+pub struct ProofSpecsPath(pub ClientId);
+
+impl ProofSpecsPath {
+    pub fn new(client_id: &ClientId) -> ProofSpecsPath {
+        ProofSpecsPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/voteExtensionPolicy")]
+pub struct VoteExtensionPolicyPath(pub ClientId);
+
+impl VoteExtensionPolicyPath {
+    pub fn new(client_id: &ClientId) -> VoteExtensionPolicyPath {
+        VoteExtensionPolicyPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/signatureScheme")]
+pub struct SignatureSchemePath(pub ClientId);
+
+impl SignatureSchemePath {
+    pub fn new(client_id: &ClientId) -> SignatureSchemePath {
+        SignatureSchemePath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/messageSchemaVersion")]
+pub struct MessageSchemaVersionPath(pub ClientId);
+
+impl MessageSchemaVersionPath {
+    pub fn new(client_id: &ClientId) -> MessageSchemaVersionPath {
+        MessageSchemaVersionPath(client_id.clone())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display(fmt = "clients/{_0}/expiredAt")]
+pub struct ExpiredAtPath(pub ClientId);
+
+impl ExpiredAtPath {
+    pub fn new(client_id: &ClientId) -> ExpiredAtPath {
+        ExpiredAtPath(client_id.clone())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
 #[display(fmt = "clients/{client_id}/consensusStates/{epoch}-{height}")]
 pub struct ClientConsensusStatePath {
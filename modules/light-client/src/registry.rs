@@ -4,28 +4,66 @@ use crate::LightClient;
 use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
 
+/// The version of the implementations registered without an explicit version
+///
+/// Clients that were created before the versioning was introduced are pinned to it.
+pub const DEFAULT_LIGHT_CLIENT_VERSION: u32 = 1;
+
+/// Returns the versioned client type string of an implementation, e.g. `07-tendermint@v2`
+pub fn versioned_client_type(client_type: &str, version: u32) -> String {
+    format!("{}@v{}", client_type, version)
+}
+
 pub trait LightClientRegistry: LightClientResolver {
     fn put_light_client(
         &mut self,
         client_state_type_url: String,
         lc: Box<dyn LightClient>,
+    ) -> Result<(), RegistryError> {
+        self.put_versioned_light_client(client_state_type_url, DEFAULT_LIGHT_CLIENT_VERSION, lc)
+    }
+
+    /// Register an implementation of the client state type with a version
+    ///
+    /// New clients use the latest version, while existing clients keep the version they were created with.
+    fn put_versioned_light_client(
+        &mut self,
+        client_state_type_url: String,
+        version: u32,
+        lc: Box<dyn LightClient>,
     ) -> Result<(), RegistryError>;
 }
 
 pub trait LightClientResolver {
+    /// Returns the latest implementation of the client state type
+    #[allow(clippy::borrowed_box)]
+    fn get_light_client(&self, type_url: &str) -> Option<&Box<dyn LightClient>> {
+        self.get_versioned_light_client(type_url, None)
+            .map(|(_, lc)| lc)
+    }
+
+    /// Returns the implementation of `version`, or the latest one if `version` is None, with its version
     #[allow(clippy::borrowed_box)]
-    fn get_light_client(&self, type_url: &str) -> Option<&Box<dyn LightClient>>;
+    fn get_versioned_light_client(
+        &self,
+        type_url: &str,
+        version: Option<u32>,
+    ) -> Option<(u32, &Box<dyn LightClient>)>;
 }
 
 impl LightClientResolver for Arc<dyn LightClientResolver> {
-    fn get_light_client(&self, type_url: &str) -> Option<&Box<dyn LightClient>> {
-        self.as_ref().get_light_client(type_url)
+    fn get_versioned_light_client(
+        &self,
+        type_url: &str,
+        version: Option<u32>,
+    ) -> Option<(u32, &Box<dyn LightClient>)> {
+        self.as_ref().get_versioned_light_client(type_url, version)
     }
 }
 
 #[derive(Default)]
 pub struct MapLightClientRegistry {
-    registry: BTreeMap<String, Box<dyn LightClient>>,
+    registry: BTreeMap<String, BTreeMap<u32, Box<dyn LightClient>>>,
     sealed: bool,
 }
 
@@ -46,25 +84,38 @@ impl MapLightClientRegistry {
 }
 
 impl LightClientRegistry for MapLightClientRegistry {
-    fn put_light_client(
+    fn put_versioned_light_client(
         &mut self,
         client_state_type_url: String,
+        version: u32,
         lc: Box<dyn LightClient>,
     ) -> Result<(), RegistryError> {
         assert!(!self.sealed);
-        if self.get_light_client(&client_state_type_url).is_some() {
+        let versions = self
+            .registry
+            .entry(client_state_type_url.clone())
+            .or_default();
+        if versions.contains_key(&version) {
             Err(RegistryError::type_url_already_exists(
-                client_state_type_url,
+                versioned_client_type(&client_state_type_url, version),
             ))
         } else {
-            self.registry.insert(client_state_type_url, lc);
+            versions.insert(version, lc);
             Ok(())
         }
     }
 }
 
 impl LightClientResolver for MapLightClientRegistry {
-    fn get_light_client(&self, client_state_type_url: &str) -> Option<&Box<dyn LightClient>> {
-        self.registry.get(client_state_type_url)
+    fn get_versioned_light_client(
+        &self,
+        client_state_type_url: &str,
+        version: Option<u32>,
+    ) -> Option<(u32, &Box<dyn LightClient>)> {
+        let versions = self.registry.get(client_state_type_url)?;
+        match version {
+            Some(version) => versions.get(&version).map(|lc| (version, lc)),
+            None => versions.iter().next_back().map(|(v, lc)| (*v, lc)),
+        }
     }
 }
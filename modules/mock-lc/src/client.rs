@@ -14,7 +14,7 @@ use light_client::commitments::{
     gen_state_id_from_any, EmittedState, MisbehaviourProxyMessage, PrevState,
     UpdateStateProxyMessage, ValidationContext,
 };
-use light_client::types::{Any, ClientId, Height, Time};
+use light_client::types::{Any, Capabilities, ClientId, Height, Time};
 use light_client::{
     ibc::IBCContext, CreateClientResult, Error as LightClientError, HostClientReader, LightClient,
     LightClientRegistry, MisbehaviourData, UpdateClientResult, UpdateStateData,
@@ -29,6 +29,14 @@ impl LightClient for MockLightClient {
         client_type().as_str().to_string()
     }
 
+    /// The mock client does not verify any proof
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            misbehaviour: true,
+            ..Default::default()
+        }
+    }
+
     fn latest_height(
         &self,
         ctx: &dyn HostClientReader,
@@ -58,7 +66,9 @@ impl LightClient for MockLightClient {
                 prev_state_id: None,
                 post_state_id: state_id,
                 post_height: height,
+                frozen_height: client_state.frozen_height().map(Into::into),
                 timestamp,
+                expires_at: None,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, any_client_state)],
             }
@@ -67,6 +77,16 @@ impl LightClient for MockLightClient {
         })
     }
 
+    fn validate_client_state(&self, any_client_state: Any) -> Result<(), LightClientError> {
+        ClientState::try_from(any_client_state)?;
+        Ok(())
+    }
+
+    fn validate_consensus_state(&self, any_consensus_state: Any) -> Result<(), LightClientError> {
+        ConsensusState::try_from(any_consensus_state)?;
+        Ok(())
+    }
+
     fn update_client(
         &self,
         ctx: &dyn HostClientReader,
@@ -108,6 +128,31 @@ impl LightClient for MockLightClient {
     ) -> Result<VerifyNonMembershipResult, LightClientError> {
         todo!()
     }
+
+    fn latest_state(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+    ) -> Result<UpdateStateProxyMessage, LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        let height: Height = client_state.latest_height().into();
+        let consensus_state: ConsensusState =
+            ctx.consensus_state(client_id, &height)?.try_into()?;
+        let frozen_height = client_state.frozen_height().map(Into::into);
+        let timestamp: Time = consensus_state.timestamp().into();
+        let state_id = gen_state_id(client_state, consensus_state)?;
+        Ok(UpdateStateProxyMessage {
+            prev_height: Some(height),
+            prev_state_id: Some(state_id),
+            post_height: height,
+            post_state_id: state_id,
+            frozen_height,
+            timestamp,
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: vec![],
+        })
+    }
 }
 
 impl MockLightClient {
@@ -172,6 +217,7 @@ impl MockLightClient {
 
         let prev_state_id = gen_state_id(client_state, latest_consensus_state)?;
         let post_state_id = gen_state_id(new_client_state.clone(), new_consensus_state.clone())?;
+        let frozen_height = new_client_state.frozen_height().map(Into::into);
         let new_any_client_state = Any::try_from(new_client_state).unwrap();
 
         Ok(UpdateStateData {
@@ -183,7 +229,9 @@ impl MockLightClient {
                 prev_state_id: Some(prev_state_id),
                 post_height: height,
                 post_state_id,
+                frozen_height,
                 timestamp: header_timestamp,
+                expires_at: None,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, new_any_client_state)],
             },
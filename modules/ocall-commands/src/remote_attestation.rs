@@ -31,6 +31,10 @@ pub struct InitQuoteResult {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetIASSocketResult {
     pub fd: c_int,
+    /// The PEM-encoded root CA of the mock IAS that the socket is connected to instead of Intel's, if any
+    ///
+    /// The mock serves plain HTTP, and the enclave accepts it only if it is built with `sgx-sw`.
+    pub mock_root_ca: Option<Vec<u8>>,
 }
 
 #[serde_as]
@@ -3,6 +3,11 @@ use core::marker::PhantomData;
 use serde::Deserialize;
 use serde_with::{DeserializeAs, SerializeAs};
 
+/// BytesTransmuter serializes a C struct of the SGX SDK as its in-memory representation
+///
+/// This is only sound between the enclave and its host on the same machine, where both sides share the layout
+/// that the SDK functions take. A structure that is parsed from the bytes of another machine, such as a quote,
+/// must be decoded with the typed parsers in `attestation_report` instead.
 pub(crate) struct BytesTransmuter<T>(PhantomData<T>);
 
 impl<T> SerializeAs<T> for BytesTransmuter<T>
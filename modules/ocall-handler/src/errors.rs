@@ -14,6 +14,14 @@ define_error! {
             format_args!("SGX error: status={:?} descr={}", e.status, e.descr)
         },
 
+        MockIasConnection {
+            addr: String,
+            descr: String
+        }
+        |e| {
+            format_args!("failed to connect to the mock IAS: addr={} descr={}", e.addr, e.descr)
+        },
+
         Store
         [store::Error]
         |_| { "Store error" }
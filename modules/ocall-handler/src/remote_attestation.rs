@@ -4,6 +4,7 @@ use std::os::unix::io::IntoRawFd;
 use std::ptr;
 
 use crate::errors::{Error, Result};
+use host_environment::{Environment, IASEndpoint};
 use ocall_commands::{
     GetIASSocketResult, GetQuoteInput, GetQuoteResult, GetReportAttestationStatusInput,
     GetReportAttestationStatusResult, InitQuoteResult, RemoteAttestationCommand,
@@ -11,12 +12,15 @@ use ocall_commands::{
 };
 use sgx_types::*;
 
-pub fn dispatch(command: RemoteAttestationCommand) -> Result<RemoteAttestationResult> {
+pub fn dispatch(
+    env: &Environment,
+    command: RemoteAttestationCommand,
+) -> Result<RemoteAttestationResult> {
     use RemoteAttestationCommand::*;
 
     let res = match command {
         InitQuote => RemoteAttestationResult::InitQuote(init_quote()?),
-        GetIASSocket => RemoteAttestationResult::GetIASSocket(get_ias_socket()?),
+        GetIASSocket => RemoteAttestationResult::GetIASSocket(get_ias_socket(env)?),
         GetQuote(input) => RemoteAttestationResult::GetQuote(get_quote(input)?),
         GetReportAttestationStatus(input) => RemoteAttestationResult::GetReportAttestationStatus(
             get_report_attestation_status(input)?,
@@ -38,15 +42,30 @@ fn init_quote() -> Result<InitQuoteResult> {
     })
 }
 
-fn get_ias_socket() -> Result<GetIASSocketResult> {
-    let port = 443;
-    let hostname = "api.trustedservices.intel.com";
-    let addr = lookup_ipv4(hostname, port);
-    let sock = TcpStream::connect(addr).expect("[-] Connect tls server failed!");
-
-    Ok(GetIASSocketResult {
-        fd: sock.into_raw_fd(),
-    })
+fn get_ias_socket(env: &Environment) -> Result<GetIASSocketResult> {
+    match &env.ias_endpoint {
+        IASEndpoint::Intel => {
+            let port = 443;
+            let hostname = "api.trustedservices.intel.com";
+            let addr = lookup_ipv4(hostname, port);
+            let sock = TcpStream::connect(addr).expect("[-] Connect tls server failed!");
+
+            Ok(GetIASSocketResult {
+                fd: sock.into_raw_fd(),
+                mock_root_ca: None,
+            })
+        }
+        IASEndpoint::Mock { addr, root_ca } => {
+            warn!("connecting to the mock IAS: addr={}", addr);
+            let sock = TcpStream::connect(addr.as_str())
+                .map_err(|e| Error::mock_ias_connection(addr.clone(), e.to_string()))?;
+
+            Ok(GetIASSocketResult {
+                fd: sock.into_raw_fd(),
+                mock_root_ca: Some(root_ca.clone()),
+            })
+        }
+    }
 }
 
 fn get_quote(input: GetQuoteInput) -> Result<GetQuoteResult> {
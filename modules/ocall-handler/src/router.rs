@@ -5,7 +5,7 @@ use ocall_commands::{Command, CommandResult, OCallCommand};
 pub fn dispatch(env: &Environment, command: OCallCommand) -> Result<CommandResult> {
     match command.cmd {
         Command::RemoteAttestation(cmd) => Ok(CommandResult::RemoteAttestation(
-            remote_attestation::dispatch(cmd)?,
+            remote_attestation::dispatch(env, cmd)?,
         )),
         Command::Store(cmd) => Ok(CommandResult::Store(store::dispatch(env, cmd)?)),
     }
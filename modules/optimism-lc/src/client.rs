@@ -0,0 +1,503 @@
+use crate::errors::Error;
+use crate::message::Header;
+use crate::prelude::*;
+use crate::state::{
+    canonicalize_state, gen_state_id, ClientState, ConsensusState, L1ClientState, L1ConsensusState,
+    OPTIMISM_CLIENT_STATE_TYPE_URL,
+};
+use crate::trie::{verify_account_storage_root, verify_storage};
+use crypto::Keccak256;
+use lcp_proto::lcp::lightclients::optimism::v1::StorageProof;
+use light_client::commitments::{
+    CommitmentPrefix, EmittedState, UpdateStateProxyMessage, ValidationContext,
+    VerifyMembershipProxyMessage,
+};
+use light_client::types::{Any, Capabilities, ClientId, Height, Time};
+use light_client::{
+    CreateClientResult, Error as LightClientError, HostClientReader, LightClient,
+    LightClientRegistry, UpdateClientResult, UpdateStateData, VerifyMembershipResult,
+    VerifyNonMembershipResult,
+};
+use prost::Message;
+
+pub const OPTIMISM_CLIENT_TYPE: &str = "optimism";
+
+/// The format of the proofs of the IBC commitments, i.e. the storage proofs of the IBC handler contract
+pub const ETHEREUM_STORAGE_PROOF_FORMAT: &str = "ethereum-storage";
+
+/// OptimismLightClient is a light client of an OP Stack chain
+///
+/// The client does not verify the L2 consensus itself. An output root is trusted once it has been proposed
+/// to the L2OutputOracle contract on the L1 and its challenge period has passed, which is verified against
+/// the consensus state of the L1 client. The L1 client must be a client of the ethereum light client in the
+/// same enclave whose tracked account is the L2OutputOracle contract, which is checked against the address
+/// of the contract in the client state.
+#[derive(Default)]
+pub struct OptimismLightClient;
+
+impl LightClient for OptimismLightClient {
+    fn client_type(&self) -> String {
+        OPTIMISM_CLIENT_TYPE.to_string()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            proof_formats: vec![ETHEREUM_STORAGE_PROOF_FORMAT.to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn latest_height(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+    ) -> Result<Height, LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        Ok(client_state.latest_height)
+    }
+
+    fn create_client(
+        &self,
+        ctx: &dyn HostClientReader,
+        any_client_state: Any,
+        any_consensus_state: Any,
+    ) -> Result<CreateClientResult, LightClientError> {
+        let client_state = ClientState::try_from(any_client_state.clone())?;
+        let consensus_state = ConsensusState::try_from(any_consensus_state)?;
+        Self::verify_l1_client(ctx, &client_state)?;
+
+        let height = client_state.latest_height;
+        let timestamp = consensus_state.time()?;
+        let state_id = gen_state_id(canonicalize_state(&client_state), consensus_state)?;
+
+        Ok(CreateClientResult {
+            height,
+            message: UpdateStateProxyMessage {
+                prev_height: None,
+                prev_state_id: None,
+                post_height: height,
+                post_state_id: state_id,
+                frozen_height: client_state.frozen_height,
+                timestamp,
+                expires_at: None,
+                context: ValidationContext::Empty,
+                emitted_states: vec![EmittedState(height, any_client_state)],
+            }
+            .into(),
+            prove: false,
+        })
+    }
+
+    fn validate_client_state(&self, any_client_state: Any) -> Result<(), LightClientError> {
+        ClientState::try_from(any_client_state)?;
+        Ok(())
+    }
+
+    fn validate_consensus_state(&self, any_consensus_state: Any) -> Result<(), LightClientError> {
+        ConsensusState::try_from(any_consensus_state)?;
+        Ok(())
+    }
+
+    fn update_client(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        client_message: Any,
+    ) -> Result<UpdateClientResult, LightClientError> {
+        let header = Header::try_from(client_message)?;
+        Ok(self.update_state(ctx, client_id, header)?.into())
+    }
+
+    fn verify_membership(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        path: String,
+        value: Vec<u8>,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<VerifyMembershipResult, LightClientError> {
+        let (client_state, consensus_state) = Self::load_states(ctx, &client_id, proof_height)?;
+        // the IBC handler contract stores the hash of the commitment
+        let commitment = Self::verify_commitment(&client_state, &consensus_state, &path, &proof)?;
+        if commitment != Some(value.keccak256()) {
+            return Err(Error::value_mismatch(path).into());
+        }
+        Ok(VerifyMembershipResult {
+            message: Self::proxy_message(
+                client_state,
+                consensus_state,
+                prefix,
+                path,
+                Some(value.keccak256()),
+                proof_height,
+            )?,
+        })
+    }
+
+    fn verify_non_membership(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        path: String,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<VerifyNonMembershipResult, LightClientError> {
+        let (client_state, consensus_state) = Self::load_states(ctx, &client_id, proof_height)?;
+        if Self::verify_commitment(&client_state, &consensus_state, &path, &proof)?.is_some() {
+            return Err(Error::value_mismatch(path).into());
+        }
+        Ok(VerifyNonMembershipResult {
+            message: Self::proxy_message(
+                client_state,
+                consensus_state,
+                prefix,
+                path,
+                None,
+                proof_height,
+            )?,
+        })
+    }
+}
+
+impl OptimismLightClient {
+    fn update_state(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        header: Header,
+    ) -> Result<UpdateStateData, LightClientError> {
+        let (client_state, trusted_consensus_state) =
+            Self::load_states(ctx, &client_id, header.trusted_height)?;
+
+        // the output proposal must be included in the storage of the L2OutputOracle contract at the L1 height
+        Self::verify_l1_client(ctx, &client_state)?;
+        let l1_consensus_state = L1ConsensusState::decode_any(
+            &client_state.l1_client_id,
+            ctx.consensus_state(&client_state.l1_client_id, &header.l1_height)?,
+        )?;
+        let oracle_storage_root = l1_consensus_state.storage_root()?;
+        let (output_root_slot, metadata_slot) =
+            client_state.output_proposal_slots(header.l2_output_index);
+
+        let output_root = header.output_root_proof.output_root();
+        let proposed_output_root = verify_storage(
+            &oracle_storage_root,
+            &output_root_slot,
+            &header.output_root_storage_proof,
+        )?
+        .unwrap_or_default();
+        if proposed_output_root != output_root {
+            return Err(Error::output_root_mismatch(proposed_output_root, output_root).into());
+        }
+
+        // `OutputProposal` packs the uint128 timestamp into the lower half of the slot and the uint128 L2 block number into the upper half
+        let metadata = verify_storage(
+            &oracle_storage_root,
+            &metadata_slot,
+            &header.output_metadata_storage_proof,
+        )?
+        .unwrap_or_default();
+        let l2_block_number =
+            u64::try_from(u128::from_be_bytes(metadata[..16].try_into().unwrap()))
+                .map_err(|_| Error::invalid_header("L2 block number overflows".into()))?;
+        let proposed_at = u64::try_from(u128::from_be_bytes(metadata[16..].try_into().unwrap()))
+            .map_err(|_| Error::invalid_header("proposal timestamp overflows".into()))?;
+        if proposed_at == 0 {
+            return Err(Error::invalid_header(format!(
+                "output proposal not found: l2_output_index={}",
+                header.l2_output_index
+            ))
+            .into());
+        }
+        let finalized_at = proposed_at.saturating_add(client_state.finalization_period);
+        if finalized_at > l1_consensus_state.timestamp {
+            return Err(Error::output_not_finalized(
+                header.l2_output_index,
+                finalized_at,
+                l1_consensus_state.timestamp,
+            )
+            .into());
+        }
+
+        let state_root = header.output_root_proof.state_root;
+        let storage_root = verify_account_storage_root(
+            &state_root,
+            &client_state.ibc_address,
+            &header.ibc_account_proof,
+        )?;
+
+        let height = Height::new(
+            client_state.latest_height.revision_number(),
+            l2_block_number,
+        );
+        if height <= header.trusted_height {
+            return Err(Error::invalid_header(format!(
+                "header height must be greater than the trusted height: height={} trusted_height={}",
+                height, header.trusted_height
+            ))
+            .into());
+        }
+        let new_client_state = client_state.clone().with_header(height);
+        let new_consensus_state = ConsensusState {
+            output_root,
+            state_root,
+            storage_root,
+            timestamp: client_state.l2_block_timestamp(l2_block_number)?,
+            l1_height: header.l1_height,
+        };
+
+        let prev_state_id =
+            gen_state_id(canonicalize_state(&client_state), trusted_consensus_state)?;
+        let post_state_id = gen_state_id(
+            canonicalize_state(&new_client_state),
+            new_consensus_state.clone(),
+        )?;
+        let timestamp: Time = new_consensus_state.time()?;
+        let new_any_client_state: Any = new_client_state.clone().into();
+
+        Ok(UpdateStateData {
+            new_any_client_state: new_any_client_state.clone(),
+            new_any_consensus_state: new_consensus_state.into(),
+            height,
+            message: UpdateStateProxyMessage {
+                prev_height: Some(header.trusted_height),
+                prev_state_id: Some(prev_state_id),
+                post_height: height,
+                post_state_id,
+                frozen_height: new_client_state.frozen_height,
+                timestamp,
+                expires_at: None,
+                context: ValidationContext::Empty,
+                emitted_states: vec![EmittedState(height, new_any_client_state)],
+            },
+            prove: true,
+        })
+    }
+
+    /// Returns the client state and the consensus state at `height` if the client is not frozen
+    fn load_states(
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<(ClientState, ConsensusState), LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        if client_state.is_frozen() {
+            return Err(Error::client_frozen(client_id.clone()).into());
+        }
+        let consensus_state: ConsensusState =
+            ctx.consensus_state(client_id, &height)?.try_into()?;
+        Ok((client_state, consensus_state))
+    }
+
+    /// Returns an error unless the L1 client tracks the L2OutputOracle contract of the client and is not frozen
+    fn verify_l1_client(
+        ctx: &dyn HostClientReader,
+        client_state: &ClientState,
+    ) -> Result<(), LightClientError> {
+        let l1_client_state = L1ClientState::decode_any(
+            &client_state.l1_client_id,
+            ctx.client_state(&client_state.l1_client_id)?,
+        )?;
+        Ok(client_state.verify_l1_client_state(&l1_client_state)?)
+    }
+
+    /// Returns the commitment of the path in the storage of the IBC handler contract, or None if it is absent
+    fn verify_commitment(
+        client_state: &ClientState,
+        consensus_state: &ConsensusState,
+        path: &str,
+        proof: &[u8],
+    ) -> Result<Option<[u8; 32]>, Error> {
+        let proof = StorageProof::decode(proof).map_err(Error::proto_decode)?;
+        verify_storage(
+            &consensus_state.storage_root,
+            &client_state.commitment_slot(path),
+            &proof.proof,
+        )
+    }
+
+    fn proxy_message(
+        client_state: ClientState,
+        consensus_state: ConsensusState,
+        prefix: CommitmentPrefix,
+        path: String,
+        value: Option<[u8; 32]>,
+        proof_height: Height,
+    ) -> Result<VerifyMembershipProxyMessage, Error> {
+        let trusted_timestamp = consensus_state.time()?;
+        Ok(VerifyMembershipProxyMessage {
+            trusted_timestamp: Some(trusted_timestamp),
+            ..VerifyMembershipProxyMessage::new(
+                prefix,
+                path,
+                value,
+                proof_height,
+                gen_state_id(canonicalize_state(&client_state), consensus_state)?,
+            )
+        })
+    }
+}
+
+/// Register the client in the registry of an enclave
+///
+/// The enclave must also register the ethereum light client, since no client can be created without an L1 client.
+pub fn register_implementations(registry: &mut dyn LightClientRegistry) {
+    registry
+        .put_light_client(
+            OPTIMISM_CLIENT_STATE_TYPE_URL.to_string(),
+            Box::new(OptimismLightClient),
+        )
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::OPTIMISM_HEADER_TYPE_URL;
+    use crate::state::ETHEREUM_CLIENT_STATE_TYPE_URL;
+    use alloc::collections::BTreeMap;
+    use core::str::FromStr;
+    use lcp_proto::lcp::lightclients::optimism::v1::{
+        Header as RawHeader, OutputRootProof as RawOutputRootProof,
+    };
+    use light_client::{ClientKeeper, ClientReader, ErrorKind, HostContext};
+    use store::KVStore;
+
+    #[derive(Default)]
+    struct TestContext {
+        kvs: BTreeMap<Vec<u8>, Vec<u8>>,
+    }
+
+    impl KVStore for TestContext {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.kvs.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.kvs.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.kvs.remove(key);
+        }
+    }
+
+    impl HostContext for TestContext {
+        fn host_timestamp(&self) -> Time {
+            Time::unix_epoch()
+        }
+    }
+
+    impl ClientReader for TestContext {}
+    impl ClientKeeper for TestContext {}
+    impl HostClientReader for TestContext {}
+
+    const ORACLE_ADDRESS: [u8; 20] = [2u8; 20];
+
+    fn l1_client_id() -> ClientId {
+        ClientId::from_str("ethereum-0").unwrap()
+    }
+
+    fn client_state() -> ClientState {
+        ClientState {
+            chain_id: 10,
+            latest_height: Height::new(0, 1),
+            frozen_height: None,
+            l1_client_id: l1_client_id(),
+            l2_outputs_slot: [0u8; 32],
+            finalization_period: 604800,
+            l2_genesis_time: 0,
+            l2_block_time: 2,
+            ibc_address: [1u8; 20],
+            ibc_commitments_slot: [0u8; 32],
+            l2_oracle_address: ORACLE_ADDRESS,
+        }
+    }
+
+    fn consensus_state() -> ConsensusState {
+        ConsensusState {
+            output_root: [0u8; 32],
+            state_root: [0u8; 32],
+            storage_root: [0u8; 32],
+            timestamp: 2,
+            l1_height: Height::new(0, 1),
+        }
+    }
+
+    fn l1_client_state(address: [u8; 20], frozen_height: Option<Height>) -> Any {
+        Any::new(
+            ETHEREUM_CLIENT_STATE_TYPE_URL.to_string(),
+            L1ClientState {
+                ibc_address: address.to_vec(),
+                frozen_height: frozen_height.map(Into::into),
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    fn header() -> Any {
+        Any::new(
+            OPTIMISM_HEADER_TYPE_URL.to_string(),
+            RawHeader {
+                trusted_height: Some(Height::new(0, 1).into()),
+                l1_height: Some(Height::new(0, 2).into()),
+                output_root_proof: Some(RawOutputRootProof {
+                    version: vec![0u8; 32],
+                    state_root: vec![0u8; 32],
+                    message_passer_storage_root: vec![0u8; 32],
+                    latest_blockhash: vec![0u8; 32],
+                }),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_l1_client() {
+        let client = OptimismLightClient;
+        let client_id = ClientId::new(OPTIMISM_CLIENT_TYPE, 0).unwrap();
+        let mut ctx = TestContext::default();
+        ctx.store_any_client_state(client_id.clone(), client_state().into())
+            .unwrap();
+        ctx.store_any_consensus_state(
+            client_id.clone(),
+            Height::new(0, 1),
+            consensus_state().into(),
+        )
+        .unwrap();
+
+        // the L1 client tracks the L2OutputOracle contract of the client
+        ctx.store_any_client_state(l1_client_id(), l1_client_state(ORACLE_ADDRESS, None))
+            .unwrap();
+        assert!(client
+            .create_client(&ctx, client_state().into(), consensus_state().into())
+            .is_ok());
+
+        // the L1 client tracks a contract that anyone can propose an output root to
+        ctx.store_any_client_state(l1_client_id(), l1_client_state([3u8; 20], None))
+            .unwrap();
+        assert!(client
+            .create_client(&ctx, client_state().into(), consensus_state().into())
+            .is_err());
+        let res = client.update_client(&ctx, client_id.clone(), header());
+        assert!(res.is_err(), "res={:?}", res.map(|_| ()));
+
+        // the L1 client has been frozen
+        ctx.store_any_client_state(
+            l1_client_id(),
+            l1_client_state(ORACLE_ADDRESS, Some(Height::new(0, 2))),
+        )
+        .unwrap();
+        let res = client.update_client(&ctx, client_id.clone(), header());
+        assert_eq!(res.map(|_| ()).unwrap_err().kind(), ErrorKind::ClientFrozen);
+
+        // the L1 client is not an ethereum client
+        ctx.store_any_client_state(l1_client_id(), client_state().into())
+            .unwrap();
+        assert!(client.update_client(&ctx, client_id, header()).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::prelude::*;
+use flex_error::*;
+use light_client::types::{ClientId, Height};
+use light_client::{ErrorKind, LightClientSpecificError};
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        UnexpectedClientType {
+            type_url: String
+        }
+        |e| {
+            format_args!("unexpected client_type: type_url={}", e.type_url)
+        },
+
+        UnexpectedL1ConsensusStateType {
+            client_id: ClientId,
+            type_url: String
+        }
+        |e| {
+            format_args!("the L1 client must be an ethereum client: client_id={} type_url={}", e.client_id, e.type_url)
+        },
+
+        UnexpectedL1ClientStateType {
+            client_id: ClientId,
+            type_url: String
+        }
+        |e| {
+            format_args!("the L1 client must be an ethereum client: client_id={} type_url={}", e.client_id, e.type_url)
+        },
+
+        L1ClientFrozen {
+            client_id: ClientId
+        }
+        |e| {
+            format_args!("the L1 client is frozen: client_id={}", e.client_id)
+        },
+
+        L1OracleAddressMismatch {
+            client_id: ClientId,
+            expected: [u8; 20],
+            actual: Vec<u8>
+        }
+        |e| {
+            format_args!("the L1 client does not track the L2OutputOracle contract: client_id={} expected=0x{} actual=0x{}", e.client_id, hex::encode(e.expected), hex::encode(&e.actual))
+        },
+
+        InvalidClientState {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid client state: descr={}", e.descr)
+        },
+
+        InvalidConsensusState {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid consensus state: descr={}", e.descr)
+        },
+
+        InvalidHeader {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid header: descr={}", e.descr)
+        },
+
+        ClientFrozen {
+            client_id: ClientId
+        }
+        |e| {
+            format_args!("client is frozen: client_id={}", e.client_id)
+        },
+
+        OutputRootMismatch {
+            expected: [u8; 32],
+            actual: [u8; 32]
+        }
+        |e| {
+            format_args!("output root mismatch: expected=0x{} actual=0x{}", hex::encode(e.expected), hex::encode(e.actual))
+        },
+
+        OutputNotFinalized {
+            l2_output_index: u64,
+            finalized_at: u64,
+            l1_timestamp: u64
+        }
+        |e| {
+            format_args!("the output proposal can still be challenged: l2_output_index={} finalized_at={} l1_timestamp={}", e.l2_output_index, e.finalized_at, e.l1_timestamp)
+        },
+
+        HeightMismatch {
+            expected: Height,
+            actual: Height
+        }
+        |e| {
+            format_args!("height mismatch: expected={} actual={}", e.expected, e.actual)
+        },
+
+        InvalidProof {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid proof: descr={}", e.descr)
+        },
+
+        ValueMismatch {
+            path: String
+        }
+        |e| {
+            format_args!("the proven value does not match: path={}", e.path)
+        },
+
+        Rlp
+        [TraceError<rlp::DecoderError>]
+        |_| { "RLP decode error" },
+
+        ProtoDecode
+        [TraceError<prost::DecodeError>]
+        |_| { "protobuf decode error" },
+
+        Commitment
+        [light_client::commitments::Error]
+        |_| { "Commitment error" }
+    }
+}
+
+impl LightClientSpecificError for Error {
+    fn kind(&self) -> ErrorKind {
+        match self.detail() {
+            ErrorDetail::ClientFrozen(_) | ErrorDetail::L1ClientFrozen(_) => {
+                ErrorKind::ClientFrozen
+            }
+            ErrorDetail::InvalidHeader(_)
+            | ErrorDetail::OutputRootMismatch(_)
+            | ErrorDetail::OutputNotFinalized(_) => ErrorKind::InvalidHeader,
+            ErrorDetail::InvalidProof(_) | ErrorDetail::ValueMismatch(_) | ErrorDetail::Rlp(_) => {
+                ErrorKind::ProofMismatch
+            }
+            ErrorDetail::Commitment(e) => ErrorKind::from_commitment_error(&e.source),
+            _ => ErrorKind::Unknown,
+        }
+    }
+}
+
+impl From<light_client::commitments::Error> for Error {
+    fn from(err: light_client::commitments::Error) -> Self {
+        Error::commitment(err)
+    }
+}
+
+impl From<rlp::DecoderError> for Error {
+    fn from(err: rlp::DecoderError) -> Self {
+        Error::rlp(err)
+    }
+}
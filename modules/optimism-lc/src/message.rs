@@ -0,0 +1,115 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use crypto::Keccak256;
+use lcp_proto::lcp::lightclients::optimism::v1::{
+    Header as RawHeader, OutputRootProof as RawOutputRootProof,
+};
+use light_client::types::{Any, Height};
+use prost::Message;
+
+pub const OPTIMISM_HEADER_TYPE_URL: &str = "/lcp.lightclients.optimism.v1.Header";
+
+/// The version of the output roots that the client can verify
+pub const OUTPUT_ROOT_VERSION: [u8; 32] = [0u8; 32];
+
+/// Header is an output proposal of the L2OutputOracle contract with the proofs of it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub trusted_height: Height,
+    pub l1_height: Height,
+    pub l2_output_index: u64,
+    pub output_root_proof: OutputRootProof,
+    pub output_root_storage_proof: Vec<Vec<u8>>,
+    pub output_metadata_storage_proof: Vec<Vec<u8>>,
+    pub ibc_account_proof: Vec<Vec<u8>>,
+}
+
+/// OutputRootProof is the preimage of an output root
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputRootProof {
+    pub version: [u8; 32],
+    pub state_root: [u8; 32],
+    pub message_passer_storage_root: [u8; 32],
+    pub latest_blockhash: [u8; 32],
+}
+
+impl OutputRootProof {
+    /// Returns the output root, i.e. `keccak256(version ++ state_root ++ message_passer_storage_root ++ latest_blockhash)`
+    pub fn output_root(&self) -> [u8; 32] {
+        [
+            self.version,
+            self.state_root,
+            self.message_passer_storage_root,
+            self.latest_blockhash,
+        ]
+        .concat()
+        .keccak256()
+    }
+}
+
+impl TryFrom<RawOutputRootProof> for OutputRootProof {
+    type Error = Error;
+
+    fn try_from(value: RawOutputRootProof) -> Result<Self, Self::Error> {
+        let version = to_bytes32(&value.version, "version")?;
+        if version != OUTPUT_ROOT_VERSION {
+            return Err(Error::invalid_header(format!(
+                "unsupported output root version: version=0x{}",
+                hex::encode(version)
+            )));
+        }
+        Ok(Self {
+            version,
+            state_root: to_bytes32(&value.state_root, "state_root")?,
+            message_passer_storage_root: to_bytes32(
+                &value.message_passer_storage_root,
+                "message_passer_storage_root",
+            )?,
+            latest_blockhash: to_bytes32(&value.latest_blockhash, "latest_blockhash")?,
+        })
+    }
+}
+
+impl TryFrom<RawHeader> for Header {
+    type Error = Error;
+
+    fn try_from(value: RawHeader) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trusted_height: value
+                .trusted_height
+                .ok_or_else(|| Error::invalid_header("trusted_height is missing".into()))?
+                .into(),
+            l1_height: value
+                .l1_height
+                .ok_or_else(|| Error::invalid_header("l1_height is missing".into()))?
+                .into(),
+            l2_output_index: value.l2_output_index,
+            output_root_proof: value
+                .output_root_proof
+                .ok_or_else(|| Error::invalid_header("output_root_proof is missing".into()))?
+                .try_into()?,
+            output_root_storage_proof: value.output_root_storage_proof,
+            output_metadata_storage_proof: value.output_metadata_storage_proof,
+            ibc_account_proof: value.ibc_account_proof,
+        })
+    }
+}
+
+impl TryFrom<Any> for Header {
+    type Error = Error;
+
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        if value.type_url == OPTIMISM_HEADER_TYPE_URL {
+            RawHeader::decode(value.value.as_slice())
+                .map_err(Error::proto_decode)?
+                .try_into()
+        } else {
+            Err(Error::unexpected_client_type(value.type_url.clone()))
+        }
+    }
+}
+
+fn to_bytes32(bz: &[u8], name: &str) -> Result<[u8; 32], Error> {
+    bz.try_into()
+        .map_err(|_| Error::invalid_header(format!("{} must be 32 bytes: len={}", name, bz.len())))
+}
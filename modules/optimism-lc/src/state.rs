@@ -0,0 +1,416 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use core::str::FromStr;
+use crypto::Keccak256;
+use lcp_proto::ibc::core::client::v1::Height as RawHeight;
+use lcp_proto::lcp::lightclients::optimism::v1::{
+    ClientState as RawClientState, ConsensusState as RawConsensusState,
+};
+use light_client::commitments::{gen_state_id_from_any, StateID};
+use light_client::types::{Any, ClientId, Height, Time};
+use prost::Message;
+
+pub const OPTIMISM_CLIENT_STATE_TYPE_URL: &str = "/lcp.lightclients.optimism.v1.ClientState";
+pub const OPTIMISM_CONSENSUS_STATE_TYPE_URL: &str = "/lcp.lightclients.optimism.v1.ConsensusState";
+/// The type of the client states of the ethereum light client that the L1 client must be of
+pub const ETHEREUM_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.ethereum.v1.ClientState";
+/// The type of the consensus states of the ethereum light client that the L1 client must be of
+pub const ETHEREUM_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.ethereum.v1.ConsensusState";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientState {
+    pub chain_id: u64,
+    pub latest_height: Height,
+    pub frozen_height: Option<Height>,
+    /// The client of the L1 that tracks the storage of the L2OutputOracle contract
+    pub l1_client_id: ClientId,
+    pub l2_outputs_slot: [u8; 32],
+    /// The challenge period of the output proposals in seconds
+    pub finalization_period: u64,
+    pub l2_genesis_time: u64,
+    pub l2_block_time: u64,
+    pub ibc_address: [u8; 20],
+    pub ibc_commitments_slot: [u8; 32],
+    /// The address of the L2OutputOracle contract on the L1
+    pub l2_oracle_address: [u8; 20],
+}
+
+impl ClientState {
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_height.is_some()
+    }
+
+    /// Returns the storage slots of the output root and of the timestamp and the L2 block number of the output proposal
+    ///
+    /// `l2Outputs` is a dynamic array of `OutputProposal`, whose elements take two slots each from `keccak256(slot)`.
+    pub fn output_proposal_slots(&self, l2_output_index: u64) -> ([u8; 32], [u8; 32]) {
+        let base = self.l2_outputs_slot.keccak256();
+        let offset = u128::from(l2_output_index) * 2;
+        (add_slot(base, offset), add_slot(base, offset + 1))
+    }
+
+    /// Returns the storage slot of the commitment of the path in the IBC handler contract
+    pub fn commitment_slot(&self, path: &str) -> [u8; 32] {
+        let mut bz = path.as_bytes().keccak256().to_vec();
+        bz.extend(self.ibc_commitments_slot);
+        bz.keccak256()
+    }
+
+    /// Returns the timestamp in seconds of the L2 block
+    pub fn l2_block_timestamp(&self, l2_block_number: u64) -> Result<u64, Error> {
+        l2_block_number
+            .checked_mul(self.l2_block_time)
+            .and_then(|t| t.checked_add(self.l2_genesis_time))
+            .ok_or_else(|| {
+                Error::invalid_header(format!(
+                    "L2 block timestamp overflows: l2_block_number={}",
+                    l2_block_number
+                ))
+            })
+    }
+
+    /// Returns an error unless the L1 client tracks the L2OutputOracle contract of the chain and is not frozen
+    ///
+    /// Otherwise the storage roots of the L1 client may be the ones of another contract, which anyone can write
+    /// a proposal of an arbitrary output root to.
+    pub fn verify_l1_client_state(&self, l1_client_state: &L1ClientState) -> Result<(), Error> {
+        if l1_client_state.is_frozen() {
+            return Err(Error::l1_client_frozen(self.l1_client_id.clone()));
+        }
+        if l1_client_state.ibc_address != self.l2_oracle_address {
+            return Err(Error::l1_oracle_address_mismatch(
+                self.l1_client_id.clone(),
+                self.l2_oracle_address,
+                l1_client_state.ibc_address.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn with_header(mut self, height: Height) -> Self {
+        if height > self.latest_height {
+            self.latest_height = height;
+        }
+        self
+    }
+}
+
+/// Adds the offset to the big-endian 256-bit slot
+fn add_slot(mut slot: [u8; 32], offset: u128) -> [u8; 32] {
+    let mut carry = offset;
+    for b in slot.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        let sum = u128::from(*b) + (carry & 0xff);
+        *b = sum as u8;
+        carry = (carry >> 8) + (sum >> 8);
+    }
+    slot
+}
+
+impl TryFrom<RawClientState> for ClientState {
+    type Error = Error;
+
+    fn try_from(value: RawClientState) -> Result<Self, Self::Error> {
+        if value.l2_block_time == 0 {
+            return Err(Error::invalid_client_state(
+                "l2_block_time must not be zero".into(),
+            ));
+        }
+        Ok(Self {
+            chain_id: value.chain_id,
+            latest_height: value
+                .latest_height
+                .ok_or_else(|| Error::invalid_client_state("latest_height is missing".into()))?
+                .into(),
+            frozen_height: value
+                .frozen_height
+                .map(Height::from)
+                .filter(|h| !h.is_zero()),
+            l1_client_id: ClientId::from_str(&value.l1_client_id)
+                .map_err(|e| Error::invalid_client_state(e.to_string()))?,
+            l2_outputs_slot: to_bytes32(&value.l2_outputs_slot, "l2_outputs_slot")
+                .map_err(Error::invalid_client_state)?,
+            finalization_period: value.finalization_period,
+            l2_genesis_time: value.l2_genesis_time,
+            l2_block_time: value.l2_block_time,
+            ibc_address: value.ibc_address.as_slice().try_into().map_err(|_| {
+                Error::invalid_client_state(format!(
+                    "ibc_address must be 20 bytes: len={}",
+                    value.ibc_address.len()
+                ))
+            })?,
+            ibc_commitments_slot: to_bytes32(&value.ibc_commitments_slot, "ibc_commitments_slot")
+                .map_err(Error::invalid_client_state)?,
+            l2_oracle_address: value.l2_oracle_address.as_slice().try_into().map_err(|_| {
+                Error::invalid_client_state(format!(
+                    "l2_oracle_address must be 20 bytes: len={}",
+                    value.l2_oracle_address.len()
+                ))
+            })?,
+        })
+    }
+}
+
+impl From<ClientState> for RawClientState {
+    fn from(value: ClientState) -> Self {
+        Self {
+            chain_id: value.chain_id,
+            latest_height: Some(value.latest_height.into()),
+            frozen_height: value.frozen_height.map(Into::into),
+            l1_client_id: value.l1_client_id.to_string(),
+            l2_outputs_slot: value.l2_outputs_slot.to_vec(),
+            finalization_period: value.finalization_period,
+            l2_genesis_time: value.l2_genesis_time,
+            l2_block_time: value.l2_block_time,
+            ibc_address: value.ibc_address.to_vec(),
+            ibc_commitments_slot: value.ibc_commitments_slot.to_vec(),
+            l2_oracle_address: value.l2_oracle_address.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<Any> for ClientState {
+    type Error = Error;
+
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        if value.type_url == OPTIMISM_CLIENT_STATE_TYPE_URL {
+            RawClientState::decode(value.value.as_slice())
+                .map_err(Error::proto_decode)?
+                .try_into()
+        } else {
+            Err(Error::unexpected_client_type(value.type_url.clone()))
+        }
+    }
+}
+
+impl From<ClientState> for Any {
+    fn from(value: ClientState) -> Self {
+        Any::new(
+            OPTIMISM_CLIENT_STATE_TYPE_URL.to_string(),
+            RawClientState::from(value).encode_to_vec(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+    pub output_root: [u8; 32],
+    pub state_root: [u8; 32],
+    /// The storage root of the IBC handler contract
+    pub storage_root: [u8; 32],
+    /// UNIX time in seconds
+    pub timestamp: u64,
+    pub l1_height: Height,
+}
+
+impl ConsensusState {
+    pub fn time(&self) -> Result<Time, Error> {
+        Time::from_unix_timestamp_nanos(u128::from(self.timestamp) * 1_000_000_000)
+            .map_err(|e| Error::invalid_consensus_state(e.to_string()))
+    }
+}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+    type Error = Error;
+
+    fn try_from(value: RawConsensusState) -> Result<Self, Self::Error> {
+        Ok(Self {
+            output_root: to_bytes32(&value.output_root, "output_root")
+                .map_err(Error::invalid_consensus_state)?,
+            state_root: to_bytes32(&value.state_root, "state_root")
+                .map_err(Error::invalid_consensus_state)?,
+            storage_root: to_bytes32(&value.storage_root, "storage_root")
+                .map_err(Error::invalid_consensus_state)?,
+            timestamp: value.timestamp,
+            l1_height: value
+                .l1_height
+                .ok_or_else(|| Error::invalid_consensus_state("l1_height is missing".into()))?
+                .into(),
+        })
+    }
+}
+
+impl From<ConsensusState> for RawConsensusState {
+    fn from(value: ConsensusState) -> Self {
+        Self {
+            output_root: value.output_root.to_vec(),
+            state_root: value.state_root.to_vec(),
+            storage_root: value.storage_root.to_vec(),
+            timestamp: value.timestamp,
+            l1_height: Some(value.l1_height.into()),
+        }
+    }
+}
+
+impl TryFrom<Any> for ConsensusState {
+    type Error = Error;
+
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        if value.type_url == OPTIMISM_CONSENSUS_STATE_TYPE_URL {
+            RawConsensusState::decode(value.value.as_slice())
+                .map_err(Error::proto_decode)?
+                .try_into()
+        } else {
+            Err(Error::unexpected_client_type(value.type_url.clone()))
+        }
+    }
+}
+
+impl From<ConsensusState> for Any {
+    fn from(value: ConsensusState) -> Self {
+        Any::new(
+            OPTIMISM_CONSENSUS_STATE_TYPE_URL.to_string(),
+            RawConsensusState::from(value).encode_to_vec(),
+        )
+    }
+}
+
+/// L1ClientState is the part of a client state of the ethereum light client that the client depends on
+///
+/// The other fields are skipped when decoding.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, Message)]
+pub struct L1ClientState {
+    /// the address of the account whose storage root the L1 client tracks
+    #[prost(bytes = "vec", tag = "8")]
+    pub ibc_address: Vec<u8>,
+    #[prost(message, optional, tag = "14")]
+    pub frozen_height: Option<RawHeight>,
+}
+
+impl L1ClientState {
+    pub fn decode_any(client_id: &ClientId, any: Any) -> Result<Self, Error> {
+        if any.type_url != ETHEREUM_CLIENT_STATE_TYPE_URL {
+            return Err(Error::unexpected_l1_client_state_type(
+                client_id.clone(),
+                any.type_url.clone(),
+            ));
+        }
+        Self::decode(any.value.as_slice()).map_err(Error::proto_decode)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_height
+            .clone()
+            .map_or(false, |h| !Height::from(h).is_zero())
+    }
+}
+
+/// L1ConsensusState is the part of a consensus state of the ethereum light client that the client depends on
+///
+/// The other fields, e.g. the sync committees, are skipped when decoding.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, Message)]
+pub struct L1ConsensusState {
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// the storage root of the account that the L1 client tracks, which must be the L2OutputOracle contract
+    #[prost(bytes = "vec", tag = "2")]
+    pub storage_root: Vec<u8>,
+    /// UNIX time in seconds
+    #[prost(uint64, tag = "3")]
+    pub timestamp: u64,
+}
+
+impl L1ConsensusState {
+    pub fn decode_any(client_id: &ClientId, any: Any) -> Result<Self, Error> {
+        if any.type_url != ETHEREUM_CONSENSUS_STATE_TYPE_URL {
+            return Err(Error::unexpected_l1_consensus_state_type(
+                client_id.clone(),
+                any.type_url.clone(),
+            ));
+        }
+        Self::decode(any.value.as_slice()).map_err(Error::proto_decode)
+    }
+
+    pub fn storage_root(&self) -> Result<[u8; 32], Error> {
+        to_bytes32(&self.storage_root, "storage_root of the L1 consensus state")
+            .map_err(Error::invalid_consensus_state)
+    }
+}
+
+fn to_bytes32(bz: &[u8], name: &str) -> Result<[u8; 32], String> {
+    bz.try_into()
+        .map_err(|_| format!("{} must be 32 bytes: len={}", name, bz.len()))
+}
+
+// canonicalize_state canonicalizes some fields of specified client state
+// target fields: latest_height, frozen_height
+pub fn canonicalize_state(client_state: &ClientState) -> ClientState {
+    ClientState {
+        latest_height: Height::new(client_state.latest_height.revision_number(), 0),
+        frozen_height: None,
+        ..client_state.clone()
+    }
+}
+
+pub fn gen_state_id(
+    client_state: ClientState,
+    consensus_state: ConsensusState,
+) -> Result<StateID, Error> {
+    Ok(gen_state_id_from_any(
+        &client_state.into(),
+        &consensus_state.into(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_l1_client_state() {
+        let client_state = ClientState {
+            chain_id: 10,
+            latest_height: Height::new(0, 1),
+            frozen_height: None,
+            l1_client_id: ClientId::from_str("ethereum-0").unwrap(),
+            l2_outputs_slot: [0u8; 32],
+            finalization_period: 604800,
+            l2_genesis_time: 0,
+            l2_block_time: 2,
+            ibc_address: [1u8; 20],
+            ibc_commitments_slot: [0u8; 32],
+            l2_oracle_address: [2u8; 20],
+        };
+        let l1_client_state = L1ClientState {
+            ibc_address: [2u8; 20].to_vec(),
+            frozen_height: None,
+        };
+        assert!(client_state
+            .verify_l1_client_state(&l1_client_state)
+            .is_ok());
+        // a zero height is not a frozen height
+        assert!(client_state
+            .verify_l1_client_state(&L1ClientState {
+                frozen_height: Some(Height::zero().into()),
+                ..l1_client_state.clone()
+            })
+            .is_ok());
+        // the L1 client tracks another contract
+        assert!(client_state
+            .verify_l1_client_state(&L1ClientState {
+                ibc_address: [1u8; 20].to_vec(),
+                ..l1_client_state.clone()
+            })
+            .is_err());
+        assert!(client_state
+            .verify_l1_client_state(&L1ClientState {
+                frozen_height: Some(Height::new(0, 1).into()),
+                ..l1_client_state
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_add_slot() {
+        let mut slot = [0u8; 32];
+        slot[31] = 0xff;
+        let added = add_slot(slot, 1);
+        assert_eq!(added[30..], [0x01, 0x00]);
+        assert_eq!(add_slot([0xff; 32], 1), [0u8; 32]);
+        assert_eq!(add_slot(slot, 0), slot);
+    }
+}
@@ -0,0 +1,228 @@
+//! Verification of the Merkle Patricia Trie proofs of the ethereum state, i.e. the proofs that `eth_getProof` returns
+use crate::errors::Error;
+use crate::prelude::*;
+use crypto::Keccak256;
+use rlp::Rlp;
+
+/// Returns the value at the key in the trie of `root`, or None if the proof shows that the key is absent
+///
+/// `proof` is the list of the RLP encoded nodes on the path from the root to the key.
+pub fn verify_proof(
+    root: &[u8; 32],
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, Error> {
+    let nibbles = to_nibbles(key);
+    let mut path = nibbles.as_slice();
+    let mut nodes = proof.iter();
+    let mut node = next_node(&mut nodes, root)?;
+    loop {
+        let rlp = Rlp::new(&node);
+        let next = match rlp.item_count()? {
+            17 => {
+                if path.is_empty() {
+                    let value = rlp.at(16)?.data()?;
+                    return Ok(if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.to_vec())
+                    });
+                }
+                let child = rlp.at(path[0] as usize)?;
+                path = &path[1..];
+                match child_ref(&child)? {
+                    Some(child) => child,
+                    None => return Ok(None),
+                }
+            }
+            2 => {
+                let (partial, is_leaf) = decode_hex_prefix(rlp.at(0)?.data()?)?;
+                if is_leaf {
+                    return Ok(if partial == path {
+                        Some(rlp.at(1)?.data()?.to_vec())
+                    } else {
+                        None
+                    });
+                }
+                if !path.starts_with(&partial) {
+                    return Ok(None);
+                }
+                path = &path[partial.len()..];
+                child_ref(&rlp.at(1)?)?.ok_or_else(|| {
+                    Error::invalid_proof("an extension node must have a child".into())
+                })?
+            }
+            n => {
+                return Err(Error::invalid_proof(format!(
+                    "unexpected number of the items of a node: {}",
+                    n
+                )))
+            }
+        };
+        node = match next {
+            NodeRef::Hash(hash) => next_node(&mut nodes, &hash)?,
+            NodeRef::Inline(node) => node,
+        };
+    }
+}
+
+/// Returns the value of the storage slot, or None if the slot is absent
+pub fn verify_storage(
+    storage_root: &[u8; 32],
+    slot: &[u8; 32],
+    proof: &[Vec<u8>],
+) -> Result<Option<[u8; 32]>, Error> {
+    let bz = match verify_proof(storage_root, &slot.keccak256(), proof)? {
+        Some(bz) => bz,
+        None => return Ok(None),
+    };
+    // the value is RLP encoded without the leading zeros
+    let bz = Rlp::new(&bz).data()?;
+    if bz.len() > 32 {
+        return Err(Error::invalid_proof(format!(
+            "storage value must not exceed 32 bytes: len={}",
+            bz.len()
+        )));
+    }
+    let mut value = [0u8; 32];
+    value[32 - bz.len()..].copy_from_slice(bz);
+    Ok(Some(value))
+}
+
+/// Returns the storage root of the account, which must exist in the state
+pub fn verify_account_storage_root(
+    state_root: &[u8; 32],
+    address: &[u8; 20],
+    proof: &[Vec<u8>],
+) -> Result<[u8; 32], Error> {
+    let account = verify_proof(state_root, &address.keccak256(), proof)?.ok_or_else(|| {
+        Error::invalid_proof(format!(
+            "account not found: address=0x{}",
+            hex::encode(address)
+        ))
+    })?;
+    // the account is a list of nonce, balance, storage root and code hash
+    let storage_root = Rlp::new(&account).at(2)?.data()?;
+    storage_root
+        .try_into()
+        .map_err(|_| Error::invalid_proof("storage root must be 32 bytes".into()))
+}
+
+enum NodeRef {
+    Hash([u8; 32]),
+    /// a node shorter than 32 bytes is embedded in its parent instead of being referenced by its hash
+    Inline(Vec<u8>),
+}
+
+fn child_ref(child: &Rlp) -> Result<Option<NodeRef>, Error> {
+    if child.is_list() {
+        return Ok(Some(NodeRef::Inline(child.as_raw().to_vec())));
+    }
+    let data = child.data()?;
+    match data.len() {
+        0 => Ok(None),
+        32 => Ok(Some(NodeRef::Hash(data.try_into().unwrap()))),
+        n => Err(Error::invalid_proof(format!(
+            "a node reference must be a hash: len={}",
+            n
+        ))),
+    }
+}
+
+fn next_node<'a>(
+    nodes: &mut impl Iterator<Item = &'a Vec<u8>>,
+    hash: &[u8; 32],
+) -> Result<Vec<u8>, Error> {
+    let node = nodes
+        .next()
+        .ok_or_else(|| Error::invalid_proof("the proof ends before the key".into()))?;
+    if &node.keccak256() != hash {
+        return Err(Error::invalid_proof(format!(
+            "node hash mismatch: expected=0x{}",
+            hex::encode(hash)
+        )));
+    }
+    Ok(node.clone())
+}
+
+fn to_nibbles(bz: &[u8]) -> Vec<u8> {
+    bz.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or an extension node
+fn decode_hex_prefix(bz: &[u8]) -> Result<(Vec<u8>, bool), Error> {
+    let (first, rest) = bz
+        .split_first()
+        .ok_or_else(|| Error::invalid_proof("empty path of a node".into()))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(Error::invalid_proof(format!(
+            "invalid hex-prefix flag: {}",
+            flag
+        )));
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(rest));
+    Ok((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::RlpStream;
+
+    fn leaf(nibbles: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut path = vec![];
+        let rest = if nibbles.len() % 2 == 1 {
+            path.push(0x30 | nibbles[0]);
+            &nibbles[1..]
+        } else {
+            path.push(0x20);
+            nibbles
+        };
+        path.extend(rest.chunks(2).map(|c| c[0] << 4 | c[1]));
+        let mut s = RlpStream::new_list(2);
+        s.append(&path).append(&value.to_vec());
+        s.out().to_vec()
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let (k1, k2) = ([0x10u8; 32], [0x20u8; 32]);
+        let (v1, v2) = (vec![1u8; 40], vec![2u8; 40]);
+        let (l1, l2) = (
+            leaf(&to_nibbles(&k1)[1..], &v1),
+            leaf(&to_nibbles(&k2)[1..], &v2),
+        );
+
+        let mut s = RlpStream::new_list(17);
+        for i in 0..16 {
+            match i {
+                1 => s.append(&l1.keccak256().to_vec()),
+                2 => s.append(&l2.keccak256().to_vec()),
+                _ => s.append_empty_data(),
+            };
+        }
+        s.append_empty_data();
+        let branch = s.out().to_vec();
+        let root = branch.keccak256();
+
+        let proof = vec![branch.clone(), l1.clone()];
+        assert_eq!(verify_proof(&root, &k1, &proof).unwrap(), Some(v1));
+        // the proof of k1 shows the absence of a key that shares the branch but not the leaf
+        assert_eq!(verify_proof(&root, &[0x11u8; 32], &proof).unwrap(), None);
+        // the proof of k1 is not a proof of k2
+        assert!(verify_proof(&root, &k2, &proof).is_err());
+        assert_eq!(
+            verify_proof(&root, &k2, &[branch.clone(), l2]).unwrap(),
+            Some(v2)
+        );
+        // no child at the nibble
+        assert_eq!(verify_proof(&root, &[0x30u8; 32], &[branch]).unwrap(), None);
+        // the proof must start from the root
+        assert!(verify_proof(&[0u8; 32], &k1, &proof).is_err());
+    }
+}
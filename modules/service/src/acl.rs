@@ -0,0 +1,226 @@
+use anyhow::{anyhow, bail, Result};
+use hmac::Hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, path::PathBuf, sync::RwLock};
+use tonic::metadata::MetadataMap;
+
+/// gRPC metadata key that carries the token of the operator who sends the request
+pub const OWNER_TOKEN_METADATA_KEY: &str = "x-lcp-owner-token";
+
+pub static CLIENT_OWNERS_FILE: &str = "client_owners.json";
+
+/// The number of the PBKDF2-HMAC-SHA256 iterations that derive the digest of a token
+#[cfg(not(test))]
+const KDF_ROUNDS: u32 = 600_000;
+#[cfg(test)]
+const KDF_ROUNDS: u32 = 1_000;
+
+const KDF_SALT_LEN: usize = 16;
+const OWNER_DIGEST_DOMAIN: &[u8] = b"lcp-client-owner-v2";
+
+/// OwnerRecord is the persisted digest of the token of the owner of a client
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerRecord {
+    /// hex-encoded random salt of the client
+    pub salt: String,
+    /// hex-encoded PBKDF2-HMAC-SHA256 digest of the token with the salt
+    pub digest: String,
+}
+
+impl OwnerRecord {
+    fn new(token: &str) -> Self {
+        let mut salt = [0u8; KDF_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            salt: hex::encode(salt),
+            digest: hex::encode(derive_digest(token, &salt)),
+        }
+    }
+
+    fn matches(&self, token: &str) -> bool {
+        match hex::decode(&self.salt) {
+            Ok(salt) => hex::encode(derive_digest(token, &salt)) == self.digest,
+            Err(_) => false,
+        }
+    }
+}
+
+/// ClientAcl keeps the owner of each ELC client
+///
+/// The owner is recorded as a salted and stretched digest of the token presented when the client was created,
+/// so the tokens themselves are never persisted and a weak token is expensive to guess from the file.
+/// Clients created without a token have no owner and can be updated by anyone.
+pub struct ClientAcl {
+    path: PathBuf,
+    owners: RwLock<BTreeMap<String, OwnerRecord>>,
+    /// the sha256 of the tokens that have matched the owners since the ACL was loaded,
+    /// which saves the key derivation on each request of an owner
+    verified: RwLock<BTreeMap<String, [u8; 32]>>,
+}
+
+impl ClientAcl {
+    /// Load the owners from the file in the home directory, or start empty if it doesn't exist
+    pub fn load(home: &std::path::Path) -> Result<Self> {
+        let path = home.join(CLIENT_OWNERS_FILE);
+        let owners = if path.exists() {
+            serde_json::from_slice(&std::fs::read(&path)?)?
+        } else {
+            Default::default()
+        };
+        Ok(Self {
+            path,
+            owners: RwLock::new(owners),
+            verified: Default::default(),
+        })
+    }
+
+    /// Returns the token of the operator in the request metadata
+    pub fn token(metadata: &MetadataMap) -> Result<Option<&str>> {
+        match metadata.get(OWNER_TOKEN_METADATA_KEY) {
+            Some(token) => Ok(Some(token.to_str().map_err(|_| {
+                anyhow!(
+                    "{} must be a printable ASCII string",
+                    OWNER_TOKEN_METADATA_KEY
+                )
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the owner of the client, which is the digest of the token of the owner
+    pub fn owner_of(&self, client_id: &str) -> Option<String> {
+        self.owners
+            .read()
+            .unwrap()
+            .get(client_id)
+            .map(|owner| owner.digest.clone())
+    }
+
+    /// Returns the pairs of the client id and its owner
+    pub fn owners(&self) -> Vec<(String, String)> {
+        self.owners
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(client_id, owner)| (client_id.clone(), owner.digest.clone()))
+            .collect()
+    }
+
+    /// Set the owner of the client to the holder of the token and persist the owners
+    pub fn set_owner(&self, client_id: String, token: &str) -> Result<()> {
+        let mut owners = self.owners.write().unwrap();
+        owners.insert(client_id.clone(), OwnerRecord::new(token));
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&*owners)?)?;
+        self.verified
+            .write()
+            .unwrap()
+            .insert(client_id, token_hash(token));
+        Ok(())
+    }
+
+    /// Returns true if the holder of the token is allowed to modify the client
+    pub fn is_authorized(&self, client_id: &str, token: Option<&str>) -> bool {
+        let owner = match self.owners.read().unwrap().get(client_id) {
+            None => return true,
+            Some(owner) => owner.clone(),
+        };
+        let token = match token {
+            None => return false,
+            Some(token) => token,
+        };
+        if self.verified.read().unwrap().get(client_id) == Some(&token_hash(token)) {
+            return true;
+        }
+        if !owner.matches(token) {
+            return false;
+        }
+        self.verified
+            .write()
+            .unwrap()
+            .insert(client_id.to_string(), token_hash(token));
+        true
+    }
+
+    /// Returns an error unless the holder of the token is allowed to modify the client
+    ///
+    /// Every command that changes a client, i.e. an update, a misbehaviour, an aggregation or a migration, must pass this check.
+    pub fn authorize(&self, client_id: &str, token: Option<&str>) -> Result<()> {
+        // an empty client id has no owner, so it would pass the check for any client
+        if client_id.is_empty() {
+            bail!("client_id must be non-empty");
+        }
+        if !self.is_authorized(client_id, token) {
+            bail!(
+                "client is owned by another operator: client_id={}",
+                client_id
+            );
+        }
+        Ok(())
+    }
+}
+
+fn derive_digest(token: &str, salt: &[u8]) -> [u8; 32] {
+    let salt = [OWNER_DIGEST_DOMAIN, salt].concat();
+    let mut digest = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(token.as_bytes(), &salt, KDF_ROUNDS, &mut digest);
+    digest
+}
+
+fn token_hash(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tonic::metadata::MetadataValue;
+
+    #[test]
+    fn test_client_acl() {
+        let home = TempDir::new().unwrap();
+
+        let mut metadata = MetadataMap::new();
+        assert_eq!(ClientAcl::token(&metadata).unwrap(), None);
+        metadata.insert(
+            OWNER_TOKEN_METADATA_KEY,
+            MetadataValue::from_static("team-a"),
+        );
+        assert_eq!(ClientAcl::token(&metadata).unwrap(), Some("team-a"));
+
+        let acl = ClientAcl::load(home.path()).unwrap();
+        assert!(acl.is_authorized("07-tendermint-0", None));
+        acl.set_owner("07-tendermint-0".into(), "team-a").unwrap();
+        assert!(acl.is_authorized("07-tendermint-0", Some("team-a")));
+        assert!(!acl.is_authorized("07-tendermint-0", None));
+        assert!(!acl.is_authorized("07-tendermint-0", Some("other")));
+        assert!(acl.authorize("07-tendermint-0", Some("team-a")).is_ok());
+        assert!(acl.authorize("07-tendermint-0", None).is_err());
+        assert!(acl.authorize("", Some("team-a")).is_err());
+        let owner = acl.owner_of("07-tendermint-0").unwrap();
+
+        // the file keeps neither the token nor an unsalted hash of it
+        let file = std::fs::read_to_string(home.path().join(CLIENT_OWNERS_FILE)).unwrap();
+        assert!(!file.contains("team-a"));
+        assert!(!file.contains(&hex::encode(token_hash("team-a"))));
+        let records: BTreeMap<String, OwnerRecord> = serde_json::from_str(&file).unwrap();
+        assert_eq!(records["07-tendermint-0"].digest, owner);
+
+        // the same token is salted differently for another client
+        acl.set_owner("07-tendermint-1".into(), "team-a").unwrap();
+        assert_ne!(acl.owner_of("07-tendermint-1").unwrap(), owner);
+
+        // owners are persisted in the home directory, and the token is checked against the digest
+        let acl = ClientAcl::load(home.path()).unwrap();
+        assert_eq!(
+            acl.owners()[0],
+            ("07-tendermint-0".to_string(), owner.clone())
+        );
+        assert_eq!(acl.owner_of("07-tendermint-0"), Some(owner));
+        assert!(!acl.is_authorized("07-tendermint-0", Some("other")));
+        assert!(acl.is_authorized("07-tendermint-0", Some("team-a")));
+        assert!(acl.is_authorized("07-tendermint-1", Some("team-a")));
+    }
+}
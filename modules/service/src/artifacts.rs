@@ -0,0 +1,192 @@
+use anyhow::{anyhow, Result};
+use crypto::DigestAlgorithm;
+use lcp_proto::lcp::service::elc::v1::{
+    ArtifactChunk, MsgVerifyMembership, MsgVerifyMembershipResponse, VerifyMembershipArtifact,
+};
+use prost::Message;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+
+pub static ARTIFACTS_DIR: &str = "artifacts";
+
+/// The maximum size of the data in a chunk of the `Artifact` stream
+pub const ARTIFACT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// ArtifactStore keeps the artifacts of the requests in the home directory
+///
+/// An artifact is addressed by its sha256 hash, so storing the same artifact twice is a no-op.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(home: &Path) -> Self {
+        Self {
+            dir: home.join(ARTIFACTS_DIR),
+        }
+    }
+
+    /// Store the artifact and returns its hash
+    pub fn put(&self, artifact: &[u8]) -> Result<[u8; 32]> {
+        let hash = DigestAlgorithm::Sha256.digest(artifact);
+        let path = self.path(&hash);
+        if !path.exists() {
+            std::fs::create_dir_all(&self.dir)?;
+            // rename the complete file so that a reader never sees a partial artifact
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, artifact)?;
+            std::fs::rename(&tmp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    /// Store the request and the response of `verify_membership` as an artifact and set its hash to the response
+    ///
+    /// The artifact contains the response without the hash, which cannot commit to itself.
+    pub fn put_verify_membership(
+        &self,
+        request: MsgVerifyMembership,
+        response: &mut MsgVerifyMembershipResponse,
+    ) -> Result<()> {
+        let artifact = VerifyMembershipArtifact {
+            request: Some(request),
+            response: Some(response.clone()),
+        };
+        response.artifact_hash = self.put(&artifact.encode_to_vec())?.to_vec();
+        Ok(())
+    }
+
+    /// Returns the path of the artifact if it exists
+    pub fn find(&self, hash: &[u8]) -> Result<Option<PathBuf>> {
+        let hash: [u8; 32] = hash
+            .try_into()
+            .map_err(|_| anyhow!("artifact hash must be 32 bytes: len={}", hash.len()))?;
+        let path = self.path(&hash);
+        Ok(if path.exists() { Some(path) } else { None })
+    }
+
+    /// Returns a stream of the chunks of the artifact at the path
+    ///
+    /// The file is read one chunk at a time, so a large artifact is never loaded into memory as a whole.
+    pub fn stream(
+        path: PathBuf,
+        chunk_size: usize,
+    ) -> ReceiverStream<Result<ArtifactChunk, Status>> {
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            if let Err(e) = send_chunks(&path, chunk_size, &tx).await {
+                let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+
+    fn path(&self, hash: &[u8; 32]) -> PathBuf {
+        self.dir.join(hex::encode(hash))
+    }
+}
+
+async fn send_chunks(
+    path: &Path,
+    chunk_size: usize,
+    tx: &mpsc::Sender<Result<ArtifactChunk, Status>>,
+) -> Result<()> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let total_size = file.metadata().await?.len();
+    let mut offset = 0;
+    loop {
+        let mut data = Vec::with_capacity(chunk_size);
+        (&mut file)
+            .take(chunk_size as u64)
+            .read_to_end(&mut data)
+            .await?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        let len = data.len() as u64;
+        let chunk = ArtifactChunk {
+            data,
+            offset,
+            total_size,
+        };
+        if tx.send(Ok(chunk)).await.is_err() {
+            // the client has cancelled the stream
+            return Ok(());
+        }
+        offset += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_artifact_store() {
+        let home = TempDir::new().unwrap();
+        let store = ArtifactStore::new(home.path());
+
+        let artifact: Vec<u8> = (0..10u8).collect();
+        let hash = store.put(&artifact).unwrap();
+        assert_eq!(store.put(&artifact).unwrap(), hash);
+        assert!(store.find(&[0u8; 32]).unwrap().is_none());
+        assert!(store.find(&hash[..31]).is_err());
+
+        let path = store.find(&hash).unwrap().unwrap();
+        let chunks: Vec<ArtifactChunk> = ArtifactStore::stream(path, 4)
+            .map(|c| c.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            chunks.iter().map(|c| c.offset).collect::<Vec<_>>(),
+            vec![0, 4, 8]
+        );
+        assert!(chunks.iter().all(|c| c.total_size == 10));
+        assert_eq!(
+            chunks.into_iter().flat_map(|c| c.data).collect::<Vec<_>>(),
+            artifact
+        );
+    }
+
+    #[test]
+    fn test_put_verify_membership() {
+        let home = TempDir::new().unwrap();
+        let store = ArtifactStore::new(home.path());
+
+        let request = MsgVerifyMembership {
+            client_id: "07-tendermint-0".into(),
+            path: "clients/07-tendermint-0/clientState".into(),
+            value: vec![1, 2, 3],
+            store_artifact: true,
+            ..Default::default()
+        };
+        let mut response = MsgVerifyMembershipResponse {
+            message: vec![4, 5, 6],
+            signer: vec![7; 20],
+            signature: vec![8; 65],
+            ..Default::default()
+        };
+        store
+            .put_verify_membership(request.clone(), &mut response)
+            .unwrap();
+        assert_eq!(response.artifact_hash.len(), 32);
+
+        let path = store.find(&response.artifact_hash).unwrap().unwrap();
+        let artifact =
+            VerifyMembershipArtifact::decode(std::fs::read(path).unwrap().as_slice()).unwrap();
+        assert_eq!(artifact.request, Some(request));
+        assert_eq!(
+            artifact.response,
+            Some(MsgVerifyMembershipResponse {
+                artifact_hash: vec![],
+                ..response
+            })
+        );
+    }
+}
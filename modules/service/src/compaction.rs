@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use enclave_api::EnclaveCommandAPI;
+use lcp_proto::lcp::service::enclave::v1::StoreStats as ProtoStoreStats;
+use lcp_types::Time;
+use log::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use store::host::StoreStats;
+use store::transaction::CommitStore;
+
+/// Counters of the compactions of the host store since the service started
+#[derive(Debug, Default)]
+pub struct StoreMetrics {
+    pub compactions: AtomicU64,
+    pub failures: AtomicU64,
+    /// UNIX time in seconds of the last successful compaction
+    pub last_compacted_at: AtomicU64,
+}
+
+impl StoreMetrics {
+    fn record(&self, compacted_at: u64) {
+        self.compactions.fetch_add(1, Ordering::Relaxed);
+        self.last_compacted_at
+            .store(compacted_at, Ordering::Relaxed);
+    }
+}
+
+/// Compact the host store on a blocking thread, and returns the statistics before and after the compaction
+///
+/// The statistics are None if the store is not persistent.
+pub(crate) async fn compact_store<E, S>(
+    enclave: Arc<E>,
+    metrics: Arc<StoreMetrics>,
+) -> Result<(Option<StoreStats>, Option<StoreStats>)>
+where
+    S: CommitStore + 'static,
+    E: EnclaveCommandAPI<S> + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let before = enclave.store_stats()?;
+        if let Err(e) = enclave.compact_store() {
+            metrics.failures.fetch_add(1, Ordering::Relaxed);
+            error!("failed to compact the store: err={:?}", e);
+            return Err(e.into());
+        }
+        let after = enclave.store_stats()?;
+        metrics.record(Time::now().as_unix_timestamp_secs());
+        Ok((before, after))
+    })
+    .await
+    .map_err(|e| anyhow!("compaction task panicked: err={:?}", e))?
+}
+
+pub(crate) fn to_proto(stats: StoreStats) -> ProtoStoreStats {
+    ProtoStoreStats {
+        live_data_size: stats.live_data_size,
+        total_sst_files_size: stats.total_sst_files_size,
+        pending_compaction_bytes: stats.pending_compaction_bytes,
+        running_compactions: stats.running_compactions,
+        space_amplification: stats.space_amplification(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_metrics() {
+        let metrics = StoreMetrics::default();
+        metrics.record(100);
+        metrics.record(200);
+        assert_eq!(metrics.compactions.load(Ordering::Relaxed), 2);
+        assert_eq!(metrics.last_compacted_at.load(Ordering::Relaxed), 200);
+
+        let stats = to_proto(StoreStats {
+            live_data_size: 100,
+            total_sst_files_size: 250,
+            ..Default::default()
+        });
+        assert_eq!(stats.space_amplification, 2.5);
+        assert_eq!(to_proto(StoreStats::default()).space_amplification, 1.0);
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{bail, Result};
+use tonic::codec::CompressionEncoding;
+
+/// The size in bytes of the smallest response that is compressed by default
+///
+/// Compressing a smaller response costs more CPU than it saves bandwidth.
+pub const DEFAULT_MIN_COMPRESSED_RESPONSE_SIZE: usize = 1024;
+
+/// ResponseCompression configures the compression of the responses of the gRPC services
+///
+/// A response is compressed only if the client accepts one of `encodings`. The unary responses
+/// smaller than `min_response_size` are sent uncompressed, while the streamed responses are always compressed.
+#[derive(Debug, Clone)]
+pub struct ResponseCompression {
+    pub encodings: Vec<CompressionEncoding>,
+    pub min_response_size: usize,
+}
+
+impl ResponseCompression {
+    pub fn new(encodings: Vec<CompressionEncoding>) -> Self {
+        Self {
+            encodings,
+            min_response_size: DEFAULT_MIN_COMPRESSED_RESPONSE_SIZE,
+        }
+    }
+
+    pub fn with_min_response_size(mut self, size: usize) -> Self {
+        self.min_response_size = size;
+        self
+    }
+
+    /// Returns true if the unary response of the encoded size should be compressed
+    pub fn should_compress(&self, encoded_len: usize) -> bool {
+        !self.encodings.is_empty() && encoded_len >= self.min_response_size
+    }
+}
+
+/// Parse the name of a compression encoding
+///
+/// `zstd` is rejected because the gRPC library of this build only supports `gzip`.
+pub fn parse_compression_encoding(s: &str) -> Result<CompressionEncoding> {
+    match s {
+        "gzip" => Ok(CompressionEncoding::Gzip),
+        "zstd" => bail!("zstd compression is not supported by this build: supported=gzip"),
+        _ => bail!("unknown compression encoding: encoding={}", s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_compression() {
+        assert!(parse_compression_encoding("gzip").is_ok());
+        assert!(parse_compression_encoding("zstd").is_err());
+        assert!(parse_compression_encoding("br").is_err());
+
+        let compression =
+            ResponseCompression::new(vec![CompressionEncoding::Gzip]).with_min_response_size(100);
+        assert!(!compression.should_compress(99));
+        assert!(compression.should_compress(100));
+        assert!(!ResponseCompression::new(vec![]).should_compress(usize::MAX));
+    }
+}
@@ -0,0 +1,213 @@
+use lcp_proto::google::protobuf::Any;
+use lcp_proto::ibc::lightclients::tendermint::v1::Header as RawHeader;
+use lcp_proto::lcp::lightclients::tendermint::v1::ExtendedHeader as RawExtendedHeader;
+use log::*;
+use prost::Message;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+const TENDERMINT_HEADER_TYPE_URL: &str = "/ibc.lightclients.tendermint.v1.Header";
+const EXTENDED_HEADER_TYPE_URL: &str = "/lcp.lightclients.tendermint.v1.ExtendedHeader";
+
+/// The overlap below which a warning is logged
+///
+/// The skipping verification of tendermint fails if less than the trust level (1/3 by default)
+/// of the trusted voting power signs the new header, so an overlap approaching it leaves little margin.
+pub const DEFAULT_DRIFT_WARNING_RATIO: f64 = 2.0 / 3.0;
+
+/// ValidatorDrift is the change of the validator set between the trusted header and the new header
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidatorDrift {
+    pub trusted_height: u64,
+    pub height: u64,
+    /// The ratio of the voting power of the trusted validators that remain in the new validator set
+    pub overlap: f64,
+}
+
+impl ValidatorDrift {
+    /// Returns the drift between the validator sets of a tendermint header, or None for the other headers
+    pub fn from_header(header: &Any) -> Option<Self> {
+        let header = match header.type_url.as_str() {
+            TENDERMINT_HEADER_TYPE_URL => RawHeader::decode(header.value.as_slice()).ok()?,
+            EXTENDED_HEADER_TYPE_URL => {
+                RawExtendedHeader::decode(header.value.as_slice())
+                    .ok()?
+                    .header?
+            }
+            _ => return None,
+        };
+        let height = header.signed_header?.header?.height;
+        let trusted_height = header.trusted_height?.revision_height;
+        let validators = header.validator_set?.validators;
+        let trusted_validators = header.trusted_validators?.validators;
+
+        let total: i64 = trusted_validators.iter().map(|v| v.voting_power).sum();
+        if total <= 0 {
+            return None;
+        }
+        let retained: i64 = trusted_validators
+            .iter()
+            .filter(|tv| validators.iter().any(|v| v.address == tv.address))
+            .map(|tv| tv.voting_power)
+            .sum();
+        Some(Self {
+            trusted_height,
+            height: height.try_into().ok()?,
+            overlap: retained as f64 / total as f64,
+        })
+    }
+}
+
+/// ClientDrift is the history of the validator drift of a client
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientDrift {
+    pub client_id: String,
+    /// The number of the updates whose drift has been recorded
+    pub updates: u64,
+    pub last: Option<ValidatorDrift>,
+    /// The lowest overlap since the service started
+    pub min_overlap: f64,
+    /// The number of the updates whose overlap was below the warning ratio
+    pub warnings: u64,
+}
+
+/// Validator set drift of the tendermint clients since the service started
+///
+/// The operators can raise the update frequency of a client whose overlap is declining,
+/// before the overlap falls below the trust level and the updates start to fail.
+#[derive(Debug)]
+pub struct DriftMetrics {
+    warning_ratio: f64,
+    clients: Mutex<BTreeMap<String, ClientDrift>>,
+}
+
+impl Default for DriftMetrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_DRIFT_WARNING_RATIO)
+    }
+}
+
+impl DriftMetrics {
+    pub fn new(warning_ratio: f64) -> Self {
+        Self {
+            warning_ratio,
+            clients: Default::default(),
+        }
+    }
+
+    pub fn record(&self, client_id: &str, drift: ValidatorDrift) {
+        let mut clients = self.clients.lock().unwrap();
+        let entry = clients
+            .entry(client_id.to_string())
+            .or_insert_with(|| ClientDrift {
+                client_id: client_id.to_string(),
+                min_overlap: 1.0,
+                ..Default::default()
+            });
+        entry.updates += 1;
+        if drift.overlap < entry.min_overlap {
+            entry.min_overlap = drift.overlap;
+        }
+        if drift.overlap < self.warning_ratio {
+            entry.warnings += 1;
+            warn!(
+                "validator set overlap is low: client_id={} trusted_height={} height={} overlap={:.3}",
+                client_id, drift.trusted_height, drift.height, drift.overlap
+            );
+        }
+        entry.last = Some(drift);
+    }
+
+    /// Record the drift of the header if it is a tendermint header
+    pub fn record_header(&self, client_id: &str, header: &Any) {
+        if let Some(drift) = ValidatorDrift::from_header(header) {
+            self.record(client_id, drift);
+        }
+    }
+
+    /// Returns the drift of the client, or of all clients ordered by client ID if `client_id` is None
+    pub fn clients(&self, client_id: Option<&str>) -> Vec<ClientDrift> {
+        let clients = self.clients.lock().unwrap();
+        match client_id {
+            Some(client_id) => clients.get(client_id).cloned().into_iter().collect(),
+            None => clients.values().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lcp_proto::ibc::core::client::v1::Height;
+    use tendermint_proto::types::{Header as TmHeader, SignedHeader, Validator, ValidatorSet};
+
+    fn validator_set(validators: &[(u8, i64)]) -> ValidatorSet {
+        ValidatorSet {
+            validators: validators
+                .iter()
+                .map(|(address, power)| Validator {
+                    address: vec![*address; 20],
+                    voting_power: *power,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn header(trusted: &[(u8, i64)], new: &[(u8, i64)]) -> Any {
+        let header = RawHeader {
+            signed_header: Some(SignedHeader {
+                header: Some(TmHeader {
+                    height: 10,
+                    ..Default::default()
+                }),
+                commit: None,
+            }),
+            validator_set: Some(validator_set(new)),
+            trusted_height: Some(Height {
+                revision_number: 0,
+                revision_height: 5,
+            }),
+            trusted_validators: Some(validator_set(trusted)),
+        };
+        Any {
+            type_url: TENDERMINT_HEADER_TYPE_URL.to_string(),
+            value: header.encode_to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_validator_drift() {
+        let drift =
+            ValidatorDrift::from_header(&header(&[(1, 10), (2, 10)], &[(1, 10), (3, 10)])).unwrap();
+        assert_eq!(drift.trusted_height, 5);
+        assert_eq!(drift.height, 10);
+        assert_eq!(drift.overlap, 0.5);
+
+        let other = Any {
+            type_url: "/ibc.lightclients.mock.Header".to_string(),
+            value: vec![],
+        };
+        assert!(ValidatorDrift::from_header(&other).is_none());
+    }
+
+    #[test]
+    fn test_drift_metrics() {
+        let metrics = DriftMetrics::default();
+        metrics.record_header("07-tendermint-0", &header(&[(1, 10)], &[(1, 10)]));
+        metrics.record_header(
+            "07-tendermint-0",
+            &header(&[(1, 10), (2, 20)], &[(1, 10), (3, 20)]),
+        );
+        metrics.record_header("07-tendermint-1", &header(&[(1, 10)], &[(1, 10)]));
+
+        let clients = metrics.clients(Some("07-tendermint-0"));
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].updates, 2);
+        assert_eq!(clients[0].warnings, 1);
+        assert!((clients[0].min_overlap - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics.clients(None).len(), 2);
+        assert!(metrics.clients(Some("07-tendermint-2")).is_empty());
+    }
+}
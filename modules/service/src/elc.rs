@@ -1,12 +1,20 @@
+use crate::acl::ClientAcl;
+use crate::artifacts::{ArtifactStore, ARTIFACT_CHUNK_SIZE};
+use crate::hooks::{self, UpdateCommitment, UpdateKind};
+use crate::queue::Priority;
 use crate::service::AppService;
-use enclave_api::EnclaveProtoAPI;
+use enclave_api::{aggregate_messages_in_parallel, EnclaveProtoAPI};
 use lcp_proto::lcp::service::elc::v1::{
-    msg_server::Msg, query_server::Query, MsgAggregateMessages, MsgAggregateMessagesResponse,
-    MsgCreateClient, MsgCreateClientResponse, MsgUpdateClient, MsgUpdateClientResponse,
-    MsgVerifyMembership, MsgVerifyMembershipResponse, MsgVerifyNonMembership,
-    MsgVerifyNonMembershipResponse, QueryClientRequest, QueryClientResponse,
+    msg_server::Msg, query_server::Query, ArtifactChunk, MsgAggregateMessages,
+    MsgAggregateMessagesResponse, MsgCreateClient, MsgCreateClientResponse, MsgMigrateClient,
+    MsgMigrateClientResponse, MsgSignLatestHeights, MsgSignLatestHeightsResponse, MsgUpdateClient,
+    MsgUpdateClientResponse, MsgVerifyKeyValue, MsgVerifyKeyValueResponse, MsgVerifyMembership,
+    MsgVerifyMembershipResponse, MsgVerifyNonMembership, MsgVerifyNonMembershipResponse,
+    QueryArtifactRequest, QueryClientRequest, QueryClientResponse,
 };
+use std::pin::Pin;
 use store::transaction::CommitStore;
+use tokio_stream::Stream;
 use tonic::{Request, Response, Status};
 
 #[tonic::async_trait]
@@ -19,9 +27,29 @@ where
         &self,
         request: Request<MsgCreateClient>,
     ) -> Result<Response<MsgCreateClientResponse>, Status> {
+        let token = ClientAcl::token(request.metadata())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?
+            .map(str::to_string);
+        let metadata = request.metadata().clone();
+        let _permit = self.acquire(Priority::High).await;
         match self.enclave.proto_create_client(request.into_inner()) {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+            Ok(res) => {
+                if let Some(token) = token {
+                    self.acl
+                        .set_owner(res.client_id.clone(), &token)
+                        .map_err(|e| Status::internal(e.to_string()))?;
+                    self.assign_client_owner(&res.client_id, &metadata)
+                        .map_err(|e| Status::internal(e.to_string()))?;
+                }
+                self.events.commitment_generated(
+                    &res.client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
         }
     }
 
@@ -29,9 +57,45 @@ where
         &self,
         request: Request<MsgUpdateClient>,
     ) -> Result<Response<MsgUpdateClientResponse>, Status> {
+        // the header may be a misbehaviour, which freezes the client
+        let client_id = &request.get_ref().client_id;
+        self.authorize_client(client_id, request.metadata())?;
+        self.assign_client_owner(client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let client_id = client_id.clone();
+        let dry_run = request.get_ref().dry_run;
+        let header = request.get_ref().header.clone();
+        let _permit = self.acquire(Priority::High).await;
         match self.enclave.proto_update_client(request.into_inner()) {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+            // the message of a dry run is neither signed nor applied to the client
+            Ok(res) if dry_run => Ok(self.respond(res)),
+            Ok(res) => {
+                self.events.commitment_generated(
+                    &client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                self.events.client_updated(&client_id, &res.message);
+                if let Some(cache) = self.proof_cache.as_ref() {
+                    cache.client_updated(&client_id, &res.message);
+                }
+                if let Some(header) = header.as_ref() {
+                    self.drift_metrics.record_header(&client_id, header);
+                }
+                hooks::dispatch(
+                    &self.on_update_hooks,
+                    UpdateCommitment {
+                        kind: UpdateKind::UpdateClient,
+                        client_id: Some(client_id),
+                        message: res.message.clone(),
+                        signer: res.signer.clone(),
+                        signature: res.signature.clone(),
+                    },
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
         }
     }
 
@@ -39,9 +103,36 @@ where
         &self,
         request: Request<MsgAggregateMessages>,
     ) -> Result<Response<MsgAggregateMessagesResponse>, Status> {
-        match self.enclave.proto_aggregate_messages(request.into_inner()) {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+        self.authorize_client(&request.get_ref().client_id, request.metadata())?;
+        let _permit = self.acquire(Priority::High).await;
+        let res = match self.aggregation_parallelism {
+            Some(parallelism) => request
+                .into_inner()
+                .try_into()
+                .map_err(enclave_api::Error::from)
+                .and_then(|input| {
+                    aggregate_messages_in_parallel(self.enclave.as_ref(), input, parallelism)
+                })
+                .map(Into::into),
+            None => self.enclave.proto_aggregate_messages(request.into_inner()),
+        };
+        match res {
+            Ok(res) => {
+                self.events
+                    .commitment_generated("", &res.message, &res.signer, &res.signature);
+                hooks::dispatch(
+                    &self.on_update_hooks,
+                    UpdateCommitment {
+                        kind: UpdateKind::AggregateMessages,
+                        client_id: None,
+                        message: res.message.clone(),
+                        signer: res.signer.clone(),
+                        signature: res.signature.clone(),
+                    },
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
         }
     }
 
@@ -49,9 +140,46 @@ where
         &self,
         request: Request<MsgVerifyMembership>,
     ) -> Result<Response<MsgVerifyMembershipResponse>, Status> {
-        match self.enclave.proto_verify_membership(request.into_inner()) {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+        self.assign_client_owner(&request.get_ref().client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let msg = request.into_inner();
+        if let Some(res) = self.cached_verify_membership(&msg) {
+            return Ok(self.respond(res));
+        }
+        let _permit = self.acquire(Priority::Low).await;
+        let client_id = msg.client_id.clone();
+        // the enclave ignores the flag, so the artifact never passes through the ecall
+        let artifact_request = if msg.store_artifact {
+            Some(msg.clone())
+        } else {
+            None
+        };
+        // a request that stores an artifact is never served from the cache
+        let cache_request = match self.proof_cache_key_epoch() {
+            Some((cache, key_epoch)) if !msg.store_artifact => {
+                Some((cache, key_epoch, msg.clone()))
+            }
+            _ => None,
+        };
+        match self.enclave.proto_verify_membership(msg) {
+            Ok(mut res) => {
+                if let Some((cache, key_epoch, request)) = cache_request {
+                    cache.put(&request, &res, key_epoch);
+                }
+                if let Some(request) = artifact_request {
+                    self.artifacts
+                        .put_verify_membership(request, &mut res)
+                        .map_err(|e| Status::internal(e.to_string()))?;
+                }
+                self.events.commitment_generated(
+                    &client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
         }
     }
 
@@ -59,12 +187,89 @@ where
         &self,
         request: Request<MsgVerifyNonMembership>,
     ) -> Result<Response<MsgVerifyNonMembershipResponse>, Status> {
+        self.assign_client_owner(&request.get_ref().client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let _permit = self.acquire(Priority::Low).await;
+        let client_id = request.get_ref().client_id.clone();
         match self
             .enclave
             .proto_verify_non_membership(request.into_inner())
         {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+            Ok(res) => {
+                self.events.commitment_generated(
+                    &client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
+        }
+    }
+
+    async fn verify_key_value(
+        &self,
+        request: Request<MsgVerifyKeyValue>,
+    ) -> Result<Response<MsgVerifyKeyValueResponse>, Status> {
+        self.assign_client_owner(&request.get_ref().client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let _permit = self.acquire(Priority::Low).await;
+        let client_id = request.get_ref().client_id.clone();
+        match self.enclave.proto_verify_key_value(request.into_inner()) {
+            Ok(res) => {
+                self.events.commitment_generated(
+                    &client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
+        }
+    }
+
+    async fn migrate_client(
+        &self,
+        request: Request<MsgMigrateClient>,
+    ) -> Result<Response<MsgMigrateClientResponse>, Status> {
+        let client_id = &request.get_ref().client_id;
+        self.authorize_client(client_id, request.metadata())?;
+        self.assign_client_owner(client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let client_id = client_id.clone();
+        let _permit = self.acquire(Priority::High).await;
+        match self.enclave.proto_migrate_client(request.into_inner()) {
+            Ok(res) => {
+                self.events.commitment_generated(
+                    &client_id,
+                    &res.message,
+                    &res.signer,
+                    &res.signature,
+                );
+                // the proofs were produced by the previous implementation
+                if let Some(cache) = self.proof_cache.as_ref() {
+                    cache.invalidate_client(&client_id);
+                }
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
+        }
+    }
+
+    async fn sign_latest_heights(
+        &self,
+        request: Request<MsgSignLatestHeights>,
+    ) -> Result<Response<MsgSignLatestHeightsResponse>, Status> {
+        let _permit = self.acquire(Priority::Low).await;
+        match self.enclave.proto_sign_latest_heights(request.into_inner()) {
+            Ok(res) => {
+                self.events
+                    .commitment_generated("", &res.message, &res.signer, &res.signature);
+                Ok(self.respond(res))
+            }
+            Err(e) => Err(self.aborted(e)),
         }
     }
 }
@@ -79,9 +284,32 @@ where
         &self,
         request: Request<QueryClientRequest>,
     ) -> Result<Response<QueryClientResponse>, Status> {
+        self.assign_client_owner(&request.get_ref().client_id, request.metadata())
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let _permit = self.acquire(Priority::Low).await;
         match self.enclave.proto_query_client(request.into_inner()) {
-            Ok(res) => Ok(Response::new(res)),
-            Err(e) => Err(Status::aborted(e.to_string())),
+            Ok(res) => Ok(self.respond(res)),
+            Err(e) => Err(self.aborted(e)),
+        }
+    }
+
+    type ArtifactStream = Pin<Box<dyn Stream<Item = Result<ArtifactChunk, Status>> + Send>>;
+
+    async fn artifact(
+        &self,
+        request: Request<QueryArtifactRequest>,
+    ) -> Result<Response<Self::ArtifactStream>, Status> {
+        let hash = request.into_inner().hash;
+        match self.artifacts.find(&hash) {
+            Ok(Some(path)) => Ok(Response::new(Box::pin(ArtifactStore::stream(
+                path,
+                ARTIFACT_CHUNK_SIZE,
+            )))),
+            Ok(None) => Err(Status::not_found(format!(
+                "artifact not found: hash={}",
+                hex::encode(hash)
+            ))),
+            Err(e) => Err(Status::invalid_argument(e.to_string())),
         }
     }
 }
@@ -1,11 +1,22 @@
+use crate::compaction::to_proto;
+use crate::drift::ValidatorDrift;
 use crate::service::AppService;
+use crate::telemetry::ErrorCategory;
 use crypto::Address;
-use enclave_api::EnclaveProtoAPI;
+use ecall_commands::PageRequest;
+use enclave_api::{
+    query_attestations, AttestationEvidence, AttestationRecord, EnclaveCommandAPI, EnclaveProtoAPI,
+    EvidenceType, QueryAttestationsInput,
+};
 use lcp_proto::lcp::service::enclave::v1::{
-    query_server::Query, EnclaveKeyInfo, QueryAvailableEnclaveKeysRequest,
+    query_server::Query, Attestation, ClientValidatorDrift, EnclaveKeyInfo, ErrorCount,
+    QueryAttestationsRequest, QueryAttestationsResponse, QueryAvailableEnclaveKeysRequest,
     QueryAvailableEnclaveKeysResponse, QueryEnclaveKeyRequest, QueryEnclaveKeyResponse,
+    QueryErrorMetricsRequest, QueryErrorMetricsResponse, QueryStoreStatsRequest,
+    QueryStoreStatsResponse, QueryValidatorDriftRequest, QueryValidatorDriftResponse,
 };
-use lcp_types::Mrenclave;
+use lcp_types::{Mrenclave, Time};
+use std::sync::atomic::Ordering;
 use store::transaction::CommitStore;
 use tonic::{Request, Response, Status};
 
@@ -27,12 +38,12 @@ where
                 Mrenclave::try_from(req.into_inner().mrenclave)
                     .map_err(|e| Status::aborted(e.to_string()))?,
             )
-            .map_err(|e| Status::aborted(e.to_string()))?;
+            .map_err(|e| self.key_manager_error(e))?;
         for key in keys {
             res.keys
                 .push(EnclaveKeyInfo::try_from(key).map_err(|e| Status::aborted(e.to_string()))?);
         }
-        Ok(Response::new(res))
+        Ok(self.respond(res))
     }
 
     async fn enclave_key(
@@ -45,8 +56,160 @@ where
             .enclave
             .get_key_manager()
             .load(addr)
-            .map_err(|e| Status::aborted(e.to_string()))?;
+            .map_err(|e| self.key_manager_error(e))?;
         let key = EnclaveKeyInfo::try_from(key).map_err(|e| Status::aborted(e.to_string()))?;
-        Ok(Response::new(QueryEnclaveKeyResponse { key: Some(key) }))
+        Ok(self.respond(QueryEnclaveKeyResponse { key: Some(key) }))
+    }
+
+    async fn error_metrics(
+        &self,
+        _: Request<QueryErrorMetricsRequest>,
+    ) -> Result<Response<QueryErrorMetricsResponse>, Status> {
+        let counts = self
+            .error_metrics
+            .counts()
+            .into_iter()
+            .map(|c| ErrorCount {
+                category: c.category.to_string(),
+                kind: c.kind,
+                count: c.count,
+            })
+            .collect();
+        Ok(self.respond(QueryErrorMetricsResponse { counts }))
+    }
+
+    async fn store_stats(
+        &self,
+        _: Request<QueryStoreStatsRequest>,
+    ) -> Result<Response<QueryStoreStatsResponse>, Status> {
+        let stats = self.enclave.store_stats().map_err(|e| self.aborted(e))?;
+        Ok(self.respond(QueryStoreStatsResponse {
+            stats: stats.map(to_proto),
+            compactions: self.store_metrics.compactions.load(Ordering::Relaxed),
+            last_compacted_at: self.store_metrics.last_compacted_at.load(Ordering::Relaxed),
+        }))
+    }
+
+    async fn attestations(
+        &self,
+        req: Request<QueryAttestationsRequest>,
+    ) -> Result<Response<QueryAttestationsResponse>, Status> {
+        let req = req.into_inner();
+        let key_address = if req.enclave_key_address.is_empty() {
+            None
+        } else {
+            Some(
+                Address::try_from(req.enclave_key_address.as_slice())
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            )
+        };
+        let evidence_type = if req.evidence_type.is_empty() {
+            None
+        } else {
+            Some(
+                req.evidence_type
+                    .parse::<EvidenceType>()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            )
+        };
+        let min_attestation_time = if req.min_attestation_time == 0 {
+            None
+        } else {
+            Some(
+                Time::from_unix_timestamp_nanos(req.min_attestation_time as u128 * 1_000_000_000)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            )
+        };
+        let limit = if req.limit == 0 {
+            PageRequest::DEFAULT_LIMIT
+        } else {
+            req.limit
+        };
+        let pagination = PageRequest::new(req.offset, limit)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let res = query_attestations(
+            self.enclave.as_ref(),
+            QueryAttestationsInput {
+                key_address,
+                evidence_type,
+                min_attestation_time,
+                pagination,
+            },
+        )
+        .map_err(|e| self.key_manager_error(e))?;
+        Ok(self.respond(QueryAttestationsResponse {
+            attestations: res
+                .attestations
+                .into_iter()
+                .map(to_proto_attestation)
+                .collect::<Result<_, _>>()?,
+            total: res.pagination.total,
+            next_offset: res.pagination.next_offset.unwrap_or_default(),
+        }))
+    }
+
+    async fn validator_drift(
+        &self,
+        req: Request<QueryValidatorDriftRequest>,
+    ) -> Result<Response<QueryValidatorDriftResponse>, Status> {
+        let client_id = req.into_inner().client_id;
+        let clients = self
+            .drift_metrics
+            .clients((!client_id.is_empty()).then_some(client_id.as_str()))
+            .into_iter()
+            .map(|c| {
+                let last = c.last.unwrap_or(ValidatorDrift {
+                    trusted_height: 0,
+                    height: 0,
+                    overlap: 1.0,
+                });
+                ClientValidatorDrift {
+                    client_id: c.client_id,
+                    updates: c.updates,
+                    last_trusted_height: last.trusted_height,
+                    last_height: last.height,
+                    last_overlap: last.overlap,
+                    min_overlap: c.min_overlap,
+                    warnings: c.warnings,
+                }
+            })
+            .collect();
+        Ok(self.respond(QueryValidatorDriftResponse { clients }))
+    }
+}
+
+fn to_proto_attestation(record: AttestationRecord) -> Result<Attestation, Status> {
+    let mut attestation = Attestation {
+        enclave_key_address: record.address.into(),
+        mrenclave: record.mrenclave.to_vec(),
+        evidence_type: record.evidence.evidence_type().to_string(),
+        attestation_time: record.attested_at,
+        standby: record.standby,
+        revoked: record.revoked,
+        ..Default::default()
+    };
+    match record.evidence {
+        AttestationEvidence::Avr(eavr) => {
+            attestation.report = eavr.avr;
+            attestation.signature = eavr.signature;
+            attestation.signing_cert = eavr.signing_cert;
+        }
+        AttestationEvidence::Dcap(bundle) => {
+            attestation.dcap_bundle =
+                serde_json::to_string(&bundle).map_err(|e| Status::internal(e.to_string()))?;
+        }
+    }
+    Ok(attestation)
+}
+
+impl<E, S> AppService<E, S>
+where
+    S: CommitStore + 'static,
+    E: EnclaveProtoAPI<S> + 'static,
+{
+    fn key_manager_error(&self, err: impl std::fmt::Display) -> Status {
+        self.error_metrics
+            .record(ErrorCategory::KeyManager, "KeyManager");
+        Status::aborted(err.to_string())
     }
 }
@@ -0,0 +1,347 @@
+use crate::service::AppService;
+use commitments::{ProxyMessage, ValidationContext};
+use ecall_commands::CommandEvent;
+use enclave_api::EnclaveProtoAPI;
+use lcp_proto::lcp::service::events::v1::{
+    event, events_server::Events, ClientCreated, ClientFrozen, ClientMigrated, ClientNearExpiry,
+    ClientUpdated, CommitmentGenerated, Event, StateEmitted, SubscribeEventsRequest,
+};
+use lcp_types::{Height, Time};
+use log::*;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use store::transaction::CommitStore;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// The number of events buffered for each subscriber
+///
+/// A subscriber that falls behind by more than this misses the oldest events.
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// EventBus broadcasts the events of the service to the subscribers of the `Events` service
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    latest_states: Arc<Mutex<BTreeMap<String, LatestState>>>,
+}
+
+/// The latest state of a client that is known to expire
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LatestState {
+    height: Height,
+    expires_at: Time,
+    notified: bool,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_BUS_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            latest_states: Default::default(),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Publish the event to the current subscribers
+    pub fn publish(&self, event: event::Event) {
+        // an error only means that there are no subscribers
+        let _ = self.sender.send(Event {
+            timestamp: Time::now().as_unix_timestamp_secs(),
+            event: Some(event),
+        });
+    }
+
+    /// Publish a `CommitmentGenerated` event for a message signed by the enclave
+    pub(crate) fn commitment_generated(
+        &self,
+        client_id: &str,
+        message: &[u8],
+        signer: &[u8],
+        signature: &[u8],
+    ) {
+        let message_type = match ProxyMessage::from_bytes(message) {
+            Ok(msg) => msg.message_type(),
+            Err(e) => {
+                warn!("failed to decode the signed message: err={:?}", e);
+                return;
+            }
+        };
+        self.publish(event::Event::CommitmentGenerated(CommitmentGenerated {
+            client_id: client_id.to_string(),
+            message_type: message_type.into(),
+            message: message.to_vec(),
+            signer: signer.to_vec(),
+            signature: signature.to_vec(),
+        }));
+    }
+
+    /// Publish a `ClientUpdated` event for the `UpdateState` message of the client
+    ///
+    /// If the message carries a trusting period, the expiry of the new state is tracked by `expiring_clients`.
+    pub(crate) fn client_updated(&self, client_id: &str, message: &[u8]) {
+        let msg = match ProxyMessage::from_bytes(message) {
+            Ok(ProxyMessage::UpdateState(msg)) => msg,
+            Ok(_) => return,
+            Err(e) => {
+                warn!("failed to decode the signed message: err={:?}", e);
+                return;
+            }
+        };
+        if let ValidationContext::TrustingPeriod(ctx) = &msg.context {
+            match msg.timestamp + ctx.trusting_period() {
+                Ok(expires_at) => {
+                    self.latest_states.lock().unwrap().insert(
+                        client_id.to_string(),
+                        LatestState {
+                            height: msg.post_height,
+                            expires_at,
+                            notified: false,
+                        },
+                    );
+                }
+                Err(e) => warn!(
+                    "failed to calculate the expiry: client_id={} err={:?}",
+                    client_id, e
+                ),
+            }
+        }
+        self.publish(event::Event::ClientUpdated(ClientUpdated {
+            client_id: client_id.to_string(),
+            prev_height: msg.prev_height.map(Into::into),
+            post_height: Some(msg.post_height.into()),
+            post_timestamp: msg.timestamp.as_unix_timestamp_secs(),
+        }));
+    }
+
+    /// Publish the event that the enclave reported for a committed command
+    ///
+    /// `CommandEvent::ClientUpdated` is not published, since `client_updated` publishes the update
+    /// with the timestamp of the signed message.
+    pub(crate) fn command_event(&self, event: &CommandEvent) {
+        let event = match event.clone() {
+            CommandEvent::ClientCreated {
+                client_id,
+                client_type,
+                height,
+            } => event::Event::ClientCreated(ClientCreated {
+                client_id: client_id.to_string(),
+                client_type,
+                height: Some(height.into()),
+            }),
+            CommandEvent::ClientUpdated { .. } => return,
+            CommandEvent::StateEmitted {
+                client_id,
+                height,
+                state,
+            } => event::Event::StateEmitted(StateEmitted {
+                client_id: client_id.to_string(),
+                height: Some(height.into()),
+                state: Some(state.into()),
+            }),
+            CommandEvent::ClientFrozen {
+                client_id,
+                frozen_height,
+            } => event::Event::ClientFrozen(ClientFrozen {
+                client_id: client_id.to_string(),
+                frozen_height: frozen_height.map(Into::into),
+            }),
+            CommandEvent::ClientMigrated {
+                client_id,
+                from_type_url,
+                from_version,
+                to_type_url,
+                to_version,
+                height,
+            } => event::Event::ClientMigrated(ClientMigrated {
+                client_id: client_id.to_string(),
+                from_type_url,
+                from_version,
+                to_type_url,
+                to_version,
+                height: Some(height.into()),
+            }),
+        };
+        self.publish(event);
+    }
+
+    /// Publish a `ClientNearExpiry` event for each client whose latest state expires before `deadline`
+    ///
+    /// Each state is notified only once. Returns the number of the published events.
+    pub(crate) fn expiring_clients(&self, deadline: Time) -> usize {
+        let mut latest_states = self.latest_states.lock().unwrap();
+        let mut count = 0;
+        for (client_id, state) in latest_states.iter_mut() {
+            if state.notified || state.expires_at > deadline {
+                continue;
+            }
+            state.notified = true;
+            count += 1;
+            self.publish(event::Event::ClientNearExpiry(ClientNearExpiry {
+                client_id: client_id.clone(),
+                latest_height: Some(state.height.into()),
+                expires_at: state.expires_at.as_unix_timestamp_secs(),
+            }));
+        }
+        count
+    }
+}
+
+/// Returns the client that the event relates to
+fn event_client_id(event: &Event) -> Option<&str> {
+    match event.event.as_ref()? {
+        event::Event::ClientUpdated(ev) => Some(&ev.client_id),
+        event::Event::CommitmentGenerated(ev) if !ev.client_id.is_empty() => Some(&ev.client_id),
+        event::Event::ClientNearExpiry(ev) => Some(&ev.client_id),
+        event::Event::ClientCreated(ev) => Some(&ev.client_id),
+        event::Event::StateEmitted(ev) => Some(&ev.client_id),
+        event::Event::ClientFrozen(ev) => Some(&ev.client_id),
+        event::Event::ClientMigrated(ev) => Some(&ev.client_id),
+        _ => None,
+    }
+}
+
+#[tonic::async_trait]
+impl<E, S> Events for AppService<E, S>
+where
+    S: CommitStore + 'static,
+    E: EnclaveProtoAPI<S> + 'static,
+{
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<Event, Status>> + Send>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let client_ids = request.into_inner().client_ids;
+        let stream =
+            BroadcastStream::new(self.events.subscribe()).filter_map(move |res| match res {
+                Ok(event) => match event_client_id(&event) {
+                    Some(client_id)
+                        if !client_ids.is_empty()
+                            && !client_ids.iter().any(|id| id == client_id) =>
+                    {
+                        None
+                    }
+                    _ => Some(Ok(event)),
+                },
+                Err(e) => {
+                    warn!("subscriber lagged behind the events: err={}", e);
+                    None
+                }
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::{TrustingPeriodContext, UpdateStateProxyMessage};
+    use lcp_types::ClientId;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    #[test]
+    fn test_event_bus() {
+        let bus = EventBus::new(16);
+        let mut rx = bus.subscribe();
+
+        let timestamp = Time::from_unix_timestamp_nanos(1_700_000_000_000_000_000).unwrap();
+        let message = ProxyMessage::from(UpdateStateProxyMessage {
+            prev_height: Some(Height::new(0, 1)),
+            prev_state_id: None,
+            post_height: Height::new(0, 2),
+            post_state_id: Default::default(),
+            frozen_height: None,
+            timestamp,
+            expires_at: None,
+            context: TrustingPeriodContext::new(
+                Duration::from_secs(100),
+                Duration::from_secs(10),
+                timestamp,
+                timestamp,
+            )
+            .into(),
+            emitted_states: vec![],
+        })
+        .to_bytes();
+        bus.commitment_generated("client-0", &message, &[0; 20], &[]);
+        bus.client_updated("client-0", &message);
+
+        let ev = rx.try_recv().unwrap();
+        assert_eq!(event_client_id(&ev), Some("client-0"));
+        match ev.event {
+            Some(event::Event::CommitmentGenerated(ev)) => {
+                // MESSAGE_TYPE_UPDATE_STATE
+                assert_eq!(ev.message_type, 1);
+                assert_eq!(ev.message, message);
+            }
+            ev => panic!("unexpected event: {:?}", ev),
+        }
+        let ev = rx.try_recv().unwrap();
+        match ev.event {
+            Some(event::Event::ClientUpdated(ev)) => {
+                assert_eq!(ev.post_height, Some(Height::new(0, 2).into()));
+                assert_eq!(ev.post_timestamp, timestamp.as_unix_timestamp_secs());
+            }
+            ev => panic!("unexpected event: {:?}", ev),
+        }
+
+        // the state expires at `timestamp + 100s`
+        assert_eq!(
+            bus.expiring_clients((timestamp + Duration::from_secs(50)).unwrap()),
+            0
+        );
+        let deadline = (timestamp + Duration::from_secs(100)).unwrap();
+        assert_eq!(bus.expiring_clients(deadline), 1);
+        // the same state is not notified twice
+        assert_eq!(bus.expiring_clients(deadline), 0);
+        match rx.try_recv().unwrap().event {
+            Some(event::Event::ClientNearExpiry(ev)) => {
+                assert_eq!(ev.client_id, "client-0");
+                assert_eq!(ev.expires_at, timestamp.as_unix_timestamp_secs() + 100);
+            }
+            ev => panic!("unexpected event: {:?}", ev),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_command_event() {
+        let bus = EventBus::new(16);
+        let mut rx = bus.subscribe();
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+
+        bus.command_event(&CommandEvent::ClientUpdated {
+            client_id: client_id.clone(),
+            prev_height: None,
+            post_height: Height::new(0, 2),
+        });
+        bus.command_event(&CommandEvent::ClientFrozen {
+            client_id,
+            frozen_height: Some(Height::new(0, 2)),
+        });
+        let ev = rx.try_recv().unwrap();
+        assert_eq!(event_client_id(&ev), Some("07-tendermint-0"));
+        match ev.event {
+            Some(event::Event::ClientFrozen(ev)) => {
+                assert_eq!(ev.frozen_height, Some(Height::new(0, 2).into()));
+            }
+            ev => panic!("unexpected event: {:?}", ev),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+}
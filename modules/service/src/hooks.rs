@@ -0,0 +1,329 @@
+use anyhow::{bail, Result};
+use commitments::ProxyMessage;
+use ecall_commands::CommandEvent;
+use log::*;
+use serde_json::json;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// UpdateKind is the request that produced an `UpdateCommitment`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateKind {
+    UpdateClient,
+    AggregateMessages,
+}
+
+impl UpdateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UpdateClient => "update_client",
+            Self::AggregateMessages => "aggregate_messages",
+        }
+    }
+}
+
+/// UpdateCommitment is the commitment signed by the enclave for a successful update
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateCommitment {
+    pub kind: UpdateKind,
+    /// The updated client, or None for an aggregated message
+    pub client_id: Option<String>,
+    pub message: Vec<u8>,
+    pub signer: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl UpdateCommitment {
+    /// Returns the JSON that the webhooks and the commands receive, where the bytes are hex-encoded
+    pub fn to_json(&self) -> serde_json::Value {
+        let message_type = ProxyMessage::from_bytes(&self.message)
+            .map(|msg| msg.message_type())
+            .ok();
+        json!({
+            "kind": self.kind.as_str(),
+            "client_id": self.client_id,
+            "message_type": message_type,
+            "message": hex::encode(&self.message),
+            "signer": hex::encode(&self.signer),
+            "signature": hex::encode(&self.signature),
+        })
+    }
+}
+
+/// Returns the JSON of the event that the webhooks and the commands receive, where the bytes are hex-encoded
+pub fn command_event_to_json(event: &CommandEvent) -> serde_json::Value {
+    match event {
+        CommandEvent::ClientCreated {
+            client_id,
+            client_type,
+            height,
+        } => json!({
+            "event": "client_created",
+            "client_id": client_id.to_string(),
+            "client_type": client_type,
+            "height": height.to_string(),
+        }),
+        CommandEvent::ClientUpdated {
+            client_id,
+            prev_height,
+            post_height,
+        } => json!({
+            "event": "client_updated",
+            "client_id": client_id.to_string(),
+            "prev_height": prev_height.map(|h| h.to_string()),
+            "post_height": post_height.to_string(),
+        }),
+        CommandEvent::StateEmitted {
+            client_id,
+            height,
+            state,
+        } => json!({
+            "event": "state_emitted",
+            "client_id": client_id.to_string(),
+            "height": height.to_string(),
+            "type_url": state.type_url,
+            "value": hex::encode(&state.value),
+        }),
+        CommandEvent::ClientFrozen {
+            client_id,
+            frozen_height,
+        } => json!({
+            "event": "client_frozen",
+            "client_id": client_id.to_string(),
+            "frozen_height": frozen_height.map(|h| h.to_string()),
+        }),
+        CommandEvent::ClientMigrated {
+            client_id,
+            from_type_url,
+            from_version,
+            to_type_url,
+            to_version,
+            height,
+        } => json!({
+            "event": "client_migrated",
+            "client_id": client_id.to_string(),
+            "from_type_url": from_type_url,
+            "from_version": from_version,
+            "to_type_url": to_type_url,
+            "to_version": to_version,
+            "height": height.to_string(),
+        }),
+    }
+}
+
+/// `OnUpdateHook` is invoked after each successful `update_client` and `aggregate_messages`,
+/// and with each event of the state changes that the enclave reports for a committed command
+///
+/// The hooks run on a blocking thread apart from the request, so a slow or failing hook
+/// neither delays nor fails the request. An error is only logged.
+pub trait OnUpdateHook: Sync + Send {
+    fn on_update(&self, commitment: &UpdateCommitment) -> Result<()>;
+
+    fn on_event(&self, _event: &CommandEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<F: Fn(&UpdateCommitment) -> Result<()> + Sync + Send> OnUpdateHook for F {
+    fn on_update(&self, commitment: &UpdateCommitment) -> Result<()> {
+        self(commitment)
+    }
+}
+
+/// WebhookHook POSTs the JSON of the commitment to the URL
+#[derive(Clone, Debug)]
+pub struct WebhookHook {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl WebhookHook {
+    pub fn new(url: String, timeout: Duration) -> Self {
+        Self {
+            url,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+}
+
+impl WebhookHook {
+    fn post(&self, json: &serde_json::Value) -> Result<()> {
+        self.agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&json.to_string())?;
+        Ok(())
+    }
+}
+
+impl OnUpdateHook for WebhookHook {
+    fn on_update(&self, commitment: &UpdateCommitment) -> Result<()> {
+        self.post(&commitment.to_json())
+    }
+
+    fn on_event(&self, event: &CommandEvent) -> Result<()> {
+        self.post(&command_event_to_json(event))
+    }
+}
+
+/// CommandHook runs the shell command with the JSON of the commitment on its stdin
+///
+/// This is how the commitments are published to a message queue, e.g. `nats pub lcp.updates`.
+#[derive(Clone, Debug)]
+pub struct CommandHook {
+    command: String,
+}
+
+impl CommandHook {
+    pub fn new(command: String) -> Self {
+        Self { command }
+    }
+}
+
+impl CommandHook {
+    fn run(&self, json: &serde_json::Value) -> Result<()> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(json.to_string().as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("the command failed: status={}", status);
+        }
+        Ok(())
+    }
+}
+
+impl OnUpdateHook for CommandHook {
+    fn on_update(&self, commitment: &UpdateCommitment) -> Result<()> {
+        self.run(&commitment.to_json())
+    }
+
+    fn on_event(&self, event: &CommandEvent) -> Result<()> {
+        self.run(&command_event_to_json(event))
+    }
+}
+
+/// Run the hooks on a blocking thread of the runtime
+pub(crate) fn dispatch(hooks: &[Arc<dyn OnUpdateHook>], commitment: UpdateCommitment) {
+    if hooks.is_empty() {
+        return;
+    }
+    let hooks = hooks.to_vec();
+    tokio::task::spawn_blocking(move || {
+        for hook in hooks.iter() {
+            if let Err(e) = hook.on_update(&commitment) {
+                warn!(
+                    "on-update hook failed: kind={} client_id={:?} err={}",
+                    commitment.kind.as_str(),
+                    commitment.client_id,
+                    e
+                );
+            }
+        }
+    });
+}
+
+/// Run the hooks with the event on a blocking thread of the runtime
+///
+/// This is called from the thread that executed the command, which may be outside the runtime.
+pub(crate) fn dispatch_event(
+    handle: &tokio::runtime::Handle,
+    hooks: &[Arc<dyn OnUpdateHook>],
+    event: CommandEvent,
+) {
+    if hooks.is_empty() {
+        return;
+    }
+    let hooks = hooks.to_vec();
+    handle.spawn_blocking(move || {
+        for hook in hooks.iter() {
+            if let Err(e) = hook.on_event(&event) {
+                warn!(
+                    "on-update hook failed on the event: client_id={} err={}",
+                    event.client_id(),
+                    e
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let hooks: Vec<Arc<dyn OnUpdateHook>> = vec![
+            Arc::new(|_: &UpdateCommitment| -> Result<()> { bail!("unreachable endpoint") }),
+            {
+                let received = received.clone();
+                Arc::new(move |c: &UpdateCommitment| -> Result<()> {
+                    received.lock().unwrap().push(c.clone());
+                    Ok(())
+                })
+            },
+        ];
+        let commitment = UpdateCommitment {
+            kind: UpdateKind::UpdateClient,
+            client_id: Some("07-tendermint-0".to_string()),
+            message: vec![1, 2],
+            signer: vec![3],
+            signature: vec![4],
+        };
+        dispatch(&hooks, commitment.clone());
+        // the first hook fails, which must not prevent the second one from running
+        for _ in 0..100 {
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*received.lock().unwrap(), vec![commitment.clone()]);
+
+        let json = commitment.to_json();
+        assert_eq!(json["kind"], "update_client");
+        assert_eq!(json["client_id"], "07-tendermint-0");
+        assert_eq!(json["message"], "0102");
+        // the message is not a valid proxy message
+        assert!(json["message_type"].is_null());
+    }
+
+    #[test]
+    fn test_command_event_to_json() {
+        use lcp_types::{ClientId, Height};
+        use std::str::FromStr;
+
+        let json = command_event_to_json(&CommandEvent::ClientUpdated {
+            client_id: ClientId::from_str("07-tendermint-0").unwrap(),
+            prev_height: None,
+            post_height: Height::new(1, 10),
+        });
+        assert_eq!(json["event"], "client_updated");
+        assert_eq!(json["client_id"], "07-tendermint-0");
+        assert!(json["prev_height"].is_null());
+        assert_eq!(json["post_height"], "1-10");
+
+        let json = command_event_to_json(&CommandEvent::ClientMigrated {
+            client_id: ClientId::from_str("07-tendermint-0").unwrap(),
+            from_type_url: "/ibc.lightclients.tendermint.v1.ClientState".into(),
+            from_version: 1,
+            to_type_url: "/ibc.lightclients.tendermint.v1.ClientState".into(),
+            to_version: 2,
+            height: Height::new(1, 10),
+        });
+        assert_eq!(json["event"], "client_migrated");
+        assert_eq!(json["from_version"], 1);
+        assert_eq!(json["to_version"], 2);
+        assert_eq!(json["height"], "1-10");
+    }
+}
@@ -0,0 +1,147 @@
+use crate::telemetry::{ErrorCategory, ErrorMetrics};
+use anyhow::Result;
+use crypto::Address;
+use ecall_commands::GenerateEnclaveKeyInput;
+use enclave_api::EnclaveCommandAPI;
+use lcp_types::Mrenclave;
+use log::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use store::transaction::CommitStore;
+
+/// Configuration of the key pool that keeps standby keys generated and attested in advance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyPoolConfig {
+    /// Number of the standby keys to keep
+    pub size: usize,
+    /// Interval between the refills of the pool
+    pub interval: Duration,
+}
+
+/// `KeyAttestor` performs the remote attestation of a standby key, e.g. with IAS
+///
+/// The report must be saved to the key manager of the enclave, as `ias_remote_attestation` does.
+pub trait KeyAttestor<E>: Sync + Send {
+    fn attest(&self, enclave: &E, target_enclave_key: Address) -> Result<()>;
+}
+
+impl<E, F: Fn(&E, Address) -> Result<()> + Sync + Send> KeyAttestor<E> for F {
+    fn attest(&self, enclave: &E, target_enclave_key: Address) -> Result<()> {
+        self(enclave, target_enclave_key)
+    }
+}
+
+/// Cumulative counters of the key pool since the service started
+#[derive(Debug, Default)]
+pub struct KeyPoolMetrics {
+    pub runs: AtomicU64,
+    pub generated_keys: AtomicU64,
+    pub attested_keys: AtomicU64,
+    pub failures: AtomicU64,
+    /// Number of the attested standby keys after the last run
+    pub ready_keys: AtomicU64,
+}
+
+/// Fill the standby pool of the running enclave up to `config.size` attested keys once
+///
+/// The standby keys whose attestation failed in a previous run are attested again before new keys are generated.
+pub fn fill_key_pool<E, S>(
+    enclave: &E,
+    config: &KeyPoolConfig,
+    attestor: &dyn KeyAttestor<E>,
+    metrics: &KeyPoolMetrics,
+    errors: &ErrorMetrics,
+) -> Result<()>
+where
+    S: CommitStore,
+    E: EnclaveCommandAPI<S>,
+{
+    let km = enclave.get_key_manager();
+    let mrenclave: Mrenclave = enclave.metadata()?.enclave_css.body.enclave_hash.m.into();
+    let standby = km.standby_keys(mrenclave)?;
+    let mut ready = standby.iter().filter(|k| k.avr.is_some()).count();
+    let mut unattested: Vec<Address> = standby
+        .iter()
+        .filter(|k| k.avr.is_none())
+        .map(|k| k.address)
+        .collect();
+    for _ in standby.len()..config.size {
+        match enclave.generate_enclave_key(GenerateEnclaveKeyInput::default()) {
+            Ok(res) => {
+                let address = res.pub_key.as_address();
+                km.add_standby_key(address)?;
+                metrics.generated_keys.fetch_add(1, Ordering::Relaxed);
+                unattested.push(address);
+            }
+            Err(e) => {
+                metrics.failures.fetch_add(1, Ordering::Relaxed);
+                errors.record_enclave_error(&e);
+                error!("failed to generate a standby key: err={:?}", e);
+            }
+        }
+    }
+    for address in unattested {
+        if ready >= config.size {
+            break;
+        }
+        match attestor.attest(enclave, address) {
+            Ok(()) => {
+                ready += 1;
+                metrics.attested_keys.fetch_add(1, Ordering::Relaxed);
+                info!("attested a standby key: address={}", address);
+            }
+            Err(e) => {
+                metrics.failures.fetch_add(1, Ordering::Relaxed);
+                errors.record(ErrorCategory::Attestation, "StandbyKey");
+                error!(
+                    "failed to attest a standby key: address={} err={:?}",
+                    address, e
+                );
+            }
+        }
+    }
+    metrics.runs.fetch_add(1, Ordering::Relaxed);
+    metrics.ready_keys.store(ready as u64, Ordering::Relaxed);
+    if ready < config.size {
+        warn!(
+            "the key pool is not full: ready_keys={} size={}",
+            ready, config.size
+        );
+    }
+    Ok(())
+}
+
+/// Run `fill_key_pool` every `config.interval` until the runtime shuts down
+pub(crate) async fn run_key_pool<E, S>(
+    enclave: Arc<E>,
+    config: KeyPoolConfig,
+    attestor: Arc<dyn KeyAttestor<E>>,
+    metrics: Arc<KeyPoolMetrics>,
+    errors: Arc<ErrorMetrics>,
+) where
+    S: CommitStore + 'static,
+    E: EnclaveCommandAPI<S> + 'static,
+{
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        let (enclave, config, attestor, metrics, errors) = (
+            enclave.clone(),
+            config.clone(),
+            attestor.clone(),
+            metrics.clone(),
+            errors.clone(),
+        );
+        // ecalls and the attestation block the current thread
+        let res = tokio::task::spawn_blocking(move || {
+            fill_key_pool::<E, S>(&*enclave, &config, &*attestor, &metrics, &errors)
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("failed to fill the key pool: err={:?}", e),
+            Err(e) => error!("key pool task panicked: err={:?}", e),
+        }
+    }
+}
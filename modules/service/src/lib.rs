@@ -1,5 +1,38 @@
+mod acl;
+mod artifacts;
+mod compaction;
+mod compression;
+mod drift;
 mod elc;
 mod enclave;
+mod events;
+mod hooks;
+mod keypool;
+mod proof_cache;
+mod pruner;
+mod queue;
 mod service;
+mod shutdown;
+mod telemetry;
+mod watchdog;
+mod watcher;
 
+pub use crate::acl::{ClientAcl, OWNER_TOKEN_METADATA_KEY};
+pub use crate::artifacts::{ArtifactStore, ARTIFACT_CHUNK_SIZE};
+pub use crate::compaction::StoreMetrics;
+pub use crate::compression::{
+    parse_compression_encoding, ResponseCompression, DEFAULT_MIN_COMPRESSED_RESPONSE_SIZE,
+};
+pub use crate::drift::{ClientDrift, DriftMetrics, ValidatorDrift, DEFAULT_DRIFT_WARNING_RATIO};
+pub use crate::events::{EventBus, EVENT_BUS_CAPACITY};
+pub use crate::hooks::{
+    command_event_to_json, CommandHook, OnUpdateHook, UpdateCommitment, UpdateKind, WebhookHook,
+};
+pub use crate::keypool::{fill_key_pool, KeyAttestor, KeyPoolConfig, KeyPoolMetrics};
+pub use crate::proof_cache::{ProofCache, ProofCacheConfig, ProofCacheMetrics};
+pub use crate::pruner::{prune_clients, PrunerConfig, PrunerMetrics};
+pub use crate::queue::{Priority, PriorityMetrics, QueueMetrics};
 pub use crate::service::{run_service, AppService};
+pub use crate::shutdown::DEFAULT_DRAIN_TIMEOUT;
+pub use crate::telemetry::{classify, ErrorCategory, ErrorCount, ErrorMetrics};
+pub use crate::watcher::{check_attested_keys, check_revoked_keys, EventWatcherConfig};
@@ -0,0 +1,398 @@
+use commitments::ProxyMessage;
+use crypto::Keccak256;
+use lcp_proto::lcp::service::elc::v1::{MsgVerifyMembership, MsgVerifyMembershipResponse};
+use lcp_types::Time;
+use log::*;
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Configuration of the cache of the `verify_membership` responses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofCacheConfig {
+    /// A response is served from the cache for this duration after the ecall
+    pub ttl: Duration,
+    /// The oldest responses are evicted over this number
+    pub max_entries: usize,
+}
+
+/// Cumulative counters of the proof cache since the service started
+#[derive(Debug, Default)]
+pub struct ProofCacheMetrics {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+    pub invalidations: AtomicU64,
+}
+
+/// ProofCache keeps the signed responses of `verify_membership` keyed by the client, the proof height and the path,
+/// so that a relayer re-requesting an identical proof is answered without an ecall
+///
+/// A cached response is returned only for a request identical to the one it was made for. The entries of a client
+/// are dropped when it is frozen, and all the entries are dropped when the key epoch of the key manager changes,
+/// i.e. when a key is rotated or revoked.
+#[derive(Debug)]
+pub struct ProofCache {
+    config: ProofCacheConfig,
+    entries: Mutex<Entries>,
+    /// the sequence number of the next cached response, which orders the eviction
+    next_sequence: AtomicU64,
+    metrics: Arc<ProofCacheMetrics>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// the key epoch at which the entries were cached
+    key_epoch: u64,
+    map: HashMap<CacheKey, CachedProof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    client_id: String,
+    proof_height: (u64, u64),
+    path: String,
+}
+
+#[derive(Clone, Debug)]
+struct CachedProof {
+    request_hash: [u8; 32],
+    response: MsgVerifyMembershipResponse,
+    cached_at: Instant,
+    sequence: u64,
+    /// the expiry of the commitment in unix seconds, or 0 if it never expires
+    expires_at: u64,
+}
+
+impl CacheKey {
+    fn new(msg: &MsgVerifyMembership) -> Self {
+        Self {
+            client_id: msg.client_id.clone(),
+            proof_height: msg
+                .proof_height
+                .as_ref()
+                .map_or((0, 0), |h| (h.revision_number, h.revision_height)),
+            path: msg.path.clone(),
+        }
+    }
+}
+
+impl ProofCache {
+    pub fn new(config: ProofCacheConfig, metrics: Arc<ProofCacheMetrics>) -> Self {
+        Self {
+            config,
+            entries: Default::default(),
+            next_sequence: AtomicU64::new(0),
+            metrics,
+        }
+    }
+
+    /// Returns the cached response to the request if it is identical to the cached one and has not expired
+    ///
+    /// `key_epoch` is the current key epoch of the key manager. A request that stores an artifact is never served from the cache.
+    pub fn get(
+        &self,
+        msg: &MsgVerifyMembership,
+        key_epoch: u64,
+    ) -> Option<MsgVerifyMembershipResponse> {
+        if msg.store_artifact {
+            return None;
+        }
+        let key = CacheKey::new(msg);
+        let mut entries = self.entries.lock().unwrap();
+        self.observe_key_epoch(&mut entries, key_epoch);
+        let res = match entries.map.get(&key) {
+            Some(entry) if !self.is_expired(entry) => {
+                (entry.request_hash == request_hash(msg)).then(|| entry.response.clone())
+            }
+            Some(_) => {
+                entries.map.remove(&key);
+                None
+            }
+            None => None,
+        };
+        match res {
+            Some(_) => self.metrics.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.metrics.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        res
+    }
+
+    /// Cache the response of the ecall for the request
+    ///
+    /// `key_epoch` must be read before the ecall, so that a response signed across a key change is not cached.
+    pub fn put(
+        &self,
+        msg: &MsgVerifyMembership,
+        response: &MsgVerifyMembershipResponse,
+        key_epoch: u64,
+    ) {
+        if msg.store_artifact || self.config.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        self.observe_key_epoch(&mut entries, key_epoch);
+        if entries.key_epoch != key_epoch {
+            return;
+        }
+        if entries.map.len() >= self.config.max_entries {
+            entries.map.retain(|_, entry| !self.is_expired(entry));
+        }
+        if entries.map.len() >= self.config.max_entries {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.sequence)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.map.remove(&oldest);
+            }
+        }
+        entries.map.insert(
+            CacheKey::new(msg),
+            CachedProof {
+                request_hash: request_hash(msg),
+                response: response.clone(),
+                cached_at: Instant::now(),
+                sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+                expires_at: msg.expires_at,
+            },
+        );
+    }
+
+    /// Drop the cached response to the request, e.g. after its signer turned out to be unusable
+    pub fn remove(&self, msg: &MsgVerifyMembership) {
+        if self
+            .entries
+            .lock()
+            .unwrap()
+            .map
+            .remove(&CacheKey::new(msg))
+            .is_some()
+        {
+            self.metrics.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop the cached responses of the client
+    pub fn invalidate_client(&self, client_id: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        let len = entries.map.len();
+        entries.map.retain(|key, _| key.client_id != client_id);
+        let removed = len - entries.map.len();
+        if removed > 0 {
+            debug!(
+                "invalidated the cached proofs: client_id={} count={}",
+                client_id, removed
+            );
+            self.metrics
+                .invalidations
+                .fetch_add(removed as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop the cached responses of the client if the signed message of its update froze it
+    pub(crate) fn client_updated(&self, client_id: &str, message: &[u8]) {
+        let frozen = match ProxyMessage::from_bytes(message) {
+            Ok(ProxyMessage::UpdateState(msg)) => msg.frozen_height.is_some(),
+            Ok(ProxyMessage::Misbehaviour(_)) => true,
+            Ok(_) => false,
+            Err(e) => {
+                warn!("failed to decode the signed message: err={:?}", e);
+                // the state of the client is unknown
+                true
+            }
+        };
+        if frozen {
+            self.invalidate_client(client_id);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all the entries if a key has been rotated or revoked since they were cached
+    fn observe_key_epoch(&self, entries: &mut Entries, key_epoch: u64) {
+        if key_epoch <= entries.key_epoch {
+            return;
+        }
+        if !entries.map.is_empty() {
+            debug!(
+                "invalidated the cached proofs on a key change: key_epoch={} count={}",
+                key_epoch,
+                entries.map.len()
+            );
+            self.metrics
+                .invalidations
+                .fetch_add(entries.map.len() as u64, Ordering::Relaxed);
+            entries.map.clear();
+        }
+        entries.key_epoch = key_epoch;
+    }
+
+    fn is_expired(&self, entry: &CachedProof) -> bool {
+        entry.cached_at.elapsed() >= self.config.ttl
+            || (entry.expires_at != 0 && entry.expires_at <= Time::now().as_unix_timestamp_secs())
+    }
+}
+
+fn request_hash(msg: &MsgVerifyMembership) -> [u8; 32] {
+    msg.encode_to_vec().keccak256()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::{UpdateStateProxyMessage, ValidationContext};
+    use lcp_proto::ibc::core::client::v1::Height;
+
+    fn request(path: &str, value: &[u8]) -> MsgVerifyMembership {
+        MsgVerifyMembership {
+            client_id: "07-tendermint-0".to_string(),
+            prefix: b"ibc".to_vec(),
+            path: path.to_string(),
+            value: value.to_vec(),
+            proof_height: Some(Height {
+                revision_number: 0,
+                revision_height: 10,
+            }),
+            proof: vec![1, 2, 3],
+            signer: vec![0; 20],
+            ..Default::default()
+        }
+    }
+
+    fn response(message: &[u8]) -> MsgVerifyMembershipResponse {
+        MsgVerifyMembershipResponse {
+            message: message.to_vec(),
+            signer: vec![0; 20],
+            signature: vec![1; 65],
+            ..Default::default()
+        }
+    }
+
+    fn cache(ttl: Duration, max_entries: usize) -> ProofCache {
+        ProofCache::new(
+            ProofCacheConfig { ttl, max_entries },
+            Arc::new(Default::default()),
+        )
+    }
+
+    #[test]
+    fn test_proof_cache() {
+        let cache = cache(Duration::from_secs(60), 10);
+        let req = request(
+            "commitments/ports/transfer/channels/channel-0/sequences/1",
+            b"v",
+        );
+        assert_eq!(cache.get(&req, 0), None);
+        cache.put(&req, &response(b"msg"), 0);
+        assert_eq!(cache.get(&req, 0), Some(response(b"msg")));
+
+        // the same key with another value is not served from the cache
+        let other = request(
+            "commitments/ports/transfer/channels/channel-0/sequences/1",
+            b"w",
+        );
+        assert_eq!(cache.get(&other, 0), None);
+
+        let artifact = MsgVerifyMembership {
+            store_artifact: true,
+            ..req.clone()
+        };
+        assert_eq!(cache.get(&artifact, 0), None);
+        assert_eq!(cache.metrics.hits.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.metrics.misses.load(Ordering::Relaxed), 2);
+
+        cache.invalidate_client("07-tendermint-1");
+        assert_eq!(cache.len(), 1);
+        cache.invalidate_client("07-tendermint-0");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_proof_cache_expiry() {
+        let cache = cache(Duration::ZERO, 10);
+        let req = request("a", b"v");
+        cache.put(&req, &response(b"msg"), 0);
+        assert_eq!(cache.get(&req, 0), None);
+        assert!(cache.is_empty());
+
+        // a commitment that has expired is not served even within the ttl
+        let cache = self::cache(Duration::from_secs(60), 10);
+        let expired = MsgVerifyMembership {
+            expires_at: 1,
+            ..request("a", b"v")
+        };
+        cache.put(&expired, &response(b"msg"), 0);
+        assert_eq!(cache.get(&expired, 0), None);
+    }
+
+    #[test]
+    fn test_proof_cache_eviction() {
+        let cache = cache(Duration::from_secs(60), 2);
+        for path in ["a", "b", "c"] {
+            cache.put(&request(path, b"v"), &response(path.as_bytes()), 0);
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&request("a", b"v"), 0), None);
+        assert_eq!(cache.get(&request("c", b"v"), 0), Some(response(b"c")));
+    }
+
+    #[test]
+    fn test_proof_cache_invalidation_on_freeze() {
+        let cache = cache(Duration::from_secs(60), 10);
+        let req = request("a", b"v");
+        let update = |frozen_height| {
+            ProxyMessage::from(UpdateStateProxyMessage {
+                prev_height: None,
+                prev_state_id: None,
+                post_height: lcp_types::Height::new(0, 11),
+                post_state_id: Default::default(),
+                frozen_height,
+                timestamp: Time::unix_epoch(),
+                expires_at: None,
+                context: ValidationContext::Empty,
+                emitted_states: vec![],
+            })
+            .to_bytes()
+        };
+        cache.put(&req, &response(b"msg"), 0);
+        cache.client_updated("07-tendermint-0", &update(None));
+        assert_eq!(cache.get(&req, 0), Some(response(b"msg")));
+
+        cache.client_updated(
+            "07-tendermint-0",
+            &update(Some(lcp_types::Height::new(0, 11))),
+        );
+        assert_eq!(cache.get(&req, 0), None);
+        assert_eq!(cache.metrics.invalidations.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_proof_cache_invalidation_on_key_change() {
+        let cache = cache(Duration::from_secs(60), 10);
+        let (a, b) = (request("a", b"v"), request("b", b"v"));
+        cache.put(&a, &response(b"a"), 3);
+        cache.put(&b, &response(b"b"), 3);
+        assert_eq!(cache.get(&a, 3), Some(response(b"a")));
+
+        // a key was rotated or revoked after the responses were cached
+        assert_eq!(cache.get(&a, 4), None);
+        assert_eq!(cache.get(&b, 4), None);
+        assert!(cache.is_empty());
+        assert_eq!(cache.metrics.invalidations.load(Ordering::Relaxed), 2);
+
+        // a response signed before the key change is not cached after it
+        cache.put(&a, &response(b"a"), 3);
+        assert!(cache.is_empty());
+        cache.put(&a, &response(b"a"), 4);
+        assert_eq!(cache.get(&a, 4), Some(response(b"a")));
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use ecall_commands::PruneClientInput;
+use enclave_api::EnclaveCommandAPI;
+use lcp_types::Time;
+use log::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use store::transaction::CommitStore;
+
+/// Configuration of the pruner that periodically removes expired consensus states
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrunerConfig {
+    /// Interval between the pruning runs
+    pub interval: Duration,
+    /// Consensus states are retained for the trusting period of the client plus this margin
+    pub margin: Duration,
+}
+
+/// Cumulative counters of the pruner since the service started
+#[derive(Debug, Default)]
+pub struct PrunerMetrics {
+    pub runs: AtomicU64,
+    pub failures: AtomicU64,
+    pub pruned_states: AtomicU64,
+    pub reclaimed_bytes: AtomicU64,
+    /// Number of the expired clients at the last run
+    pub expired_clients: AtomicU64,
+}
+
+/// Prune the expired consensus states of all clients once
+///
+/// A failure to prune a client is logged and counted, and does not prevent the other clients from being pruned.
+pub fn prune_clients<E, S>(
+    enclave: &E,
+    config: &PrunerConfig,
+    metrics: &PrunerMetrics,
+) -> Result<()>
+where
+    S: CommitStore,
+    E: EnclaveCommandAPI<S>,
+{
+    let client_ids = enclave.all_client_ids()?;
+    let (mut pruned_states, mut reclaimed_bytes, mut expired_clients) = (0, 0, 0);
+    for client_id in client_ids {
+        match enclave.prune_client(PruneClientInput {
+            client_id: client_id.clone(),
+            margin: config.margin,
+            current_timestamp: Time::now(),
+        }) {
+            Ok(res) => {
+                if !res.pruned_heights.is_empty() {
+                    debug!(
+                        "pruned consensus states: client_id={} heights={:?} reclaimed_bytes={}",
+                        client_id, res.pruned_heights, res.reclaimed_bytes
+                    );
+                }
+                if res.expired {
+                    warn!("client expired: client_id={}", client_id);
+                    expired_clients += 1;
+                }
+                pruned_states += res.pruned_heights.len() as u64;
+                reclaimed_bytes += res.reclaimed_bytes;
+            }
+            Err(e) => {
+                metrics.failures.fetch_add(1, Ordering::Relaxed);
+                error!(
+                    "failed to prune client: client_id={} err={:?}",
+                    client_id, e
+                );
+            }
+        }
+    }
+    metrics.runs.fetch_add(1, Ordering::Relaxed);
+    metrics
+        .expired_clients
+        .store(expired_clients, Ordering::Relaxed);
+    metrics
+        .pruned_states
+        .fetch_add(pruned_states, Ordering::Relaxed);
+    metrics
+        .reclaimed_bytes
+        .fetch_add(reclaimed_bytes, Ordering::Relaxed);
+    info!(
+        "prune: pruned_states={} reclaimed_bytes={} total_pruned_states={} total_reclaimed_bytes={}",
+        pruned_states,
+        reclaimed_bytes,
+        metrics.pruned_states.load(Ordering::Relaxed),
+        metrics.reclaimed_bytes.load(Ordering::Relaxed)
+    );
+    Ok(())
+}
+
+/// Run `prune_clients` every `config.interval` until the runtime shuts down
+pub(crate) async fn run_pruner<E, S>(
+    enclave: Arc<E>,
+    config: PrunerConfig,
+    metrics: Arc<PrunerMetrics>,
+) where
+    S: CommitStore + 'static,
+    E: EnclaveCommandAPI<S> + 'static,
+{
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        let (enclave, config, metrics) = (enclave.clone(), config.clone(), metrics.clone());
+        // ecalls block the current thread
+        let res = tokio::task::spawn_blocking(move || {
+            prune_clients::<E, S>(&*enclave, &config, &metrics)
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("failed to prune clients: err={:?}", e),
+            Err(e) => error!("pruner task panicked: err={:?}", e),
+        }
+    }
+}
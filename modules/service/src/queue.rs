@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Priority of a request to the enclave
+///
+/// The requests that update the clients are time-sensitive, so they are served before the queries
+/// when the enclave is saturated, e.g. while a relayer catches up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    /// `create_client`, `update_client`, `aggregate_messages` and `migrate_client`
+    High,
+    /// The verifications and the queries
+    Low,
+}
+
+/// Metrics of the requests of a priority
+#[derive(Debug, Default)]
+pub struct PriorityMetrics {
+    /// Number of the requests waiting for a slot
+    pub depth: AtomicU64,
+    /// Number of the requests that waited for a slot
+    pub dequeued: AtomicU64,
+    /// Cumulative time in microseconds that the requests waited for a slot
+    pub wait_micros: AtomicU64,
+    /// Longest time in microseconds that a request waited for a slot
+    pub max_wait_micros: AtomicU64,
+}
+
+impl PriorityMetrics {
+    fn record_wait(&self, wait: Duration) {
+        let micros = wait.as_micros() as u64;
+        self.dequeued.fetch_add(1, Ordering::Relaxed);
+        self.wait_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_wait_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+}
+
+/// Cumulative metrics of the request queue since the service started
+#[derive(Debug, Default)]
+pub struct QueueMetrics {
+    pub high: PriorityMetrics,
+    pub low: PriorityMetrics,
+}
+
+impl QueueMetrics {
+    fn of(&self, priority: Priority) -> &PriorityMetrics {
+        match priority {
+            Priority::High => &self.high,
+            Priority::Low => &self.low,
+        }
+    }
+}
+
+/// RequestQueue limits the number of concurrent requests to the enclave
+///
+/// When all the slots are in use, a request waits in the queue of its priority, and a freed slot is
+/// given to the oldest request of the highest priority.
+#[derive(Debug)]
+pub struct RequestQueue {
+    capacity: usize,
+    state: Mutex<QueueState>,
+    metrics: Arc<QueueMetrics>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    in_flight: usize,
+    high: VecDeque<oneshot::Sender<()>>,
+    low: VecDeque<oneshot::Sender<()>>,
+}
+
+impl RequestQueue {
+    /// Create a queue that serves up to `capacity` requests concurrently
+    pub fn new(capacity: usize, metrics: Arc<QueueMetrics>) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            state: Default::default(),
+            metrics,
+        }
+    }
+
+    /// Wait for a slot, which is released when the returned permit is dropped
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> QueuePermit {
+        let rx = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_flight < self.capacity {
+                state.in_flight += 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                match priority {
+                    Priority::High => state.high.push_back(tx),
+                    Priority::Low => state.low.push_back(tx),
+                }
+                self.metrics
+                    .of(priority)
+                    .depth
+                    .fetch_add(1, Ordering::Relaxed);
+                Some(rx)
+            }
+        };
+        if let Some(rx) = rx {
+            let started_at = Instant::now();
+            let mut waiter = Waiter {
+                queue: self.clone(),
+                priority,
+                rx: Some(rx),
+            };
+            waiter
+                .rx
+                .as_mut()
+                .unwrap()
+                .await
+                .expect("a waiter is never dropped while it is in the queue");
+            waiter.rx = None;
+            self.metrics.of(priority).record_wait(started_at.elapsed());
+        }
+        QueuePermit {
+            queue: self.clone(),
+        }
+    }
+
+    /// Remove the cancelled waiters of the priority from the queue
+    ///
+    /// A waiter that `release` has already removed is not counted again.
+    fn remove_cancelled(&self, priority: Priority) {
+        let mut state = self.state.lock().unwrap();
+        let waiters = match priority {
+            Priority::High => &mut state.high,
+            Priority::Low => &mut state.low,
+        };
+        let len = waiters.len();
+        waiters.retain(|tx| !tx.is_closed());
+        self.metrics
+            .of(priority)
+            .depth
+            .fetch_sub((len - waiters.len()) as u64, Ordering::Relaxed);
+    }
+
+    /// Give the slot to the next waiter, or free it if no one waits
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let next = match state.high.pop_front() {
+                Some(tx) => Some((Priority::High, tx)),
+                None => state.low.pop_front().map(|tx| (Priority::Low, tx)),
+            };
+            match next {
+                Some((priority, tx)) => {
+                    self.metrics
+                        .of(priority)
+                        .depth
+                        .fetch_sub(1, Ordering::Relaxed);
+                    // the waiter may have been cancelled, then the slot is given to the next one
+                    if tx.send(()).is_ok() {
+                        return;
+                    }
+                }
+                None => {
+                    state.in_flight -= 1;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// QueuePermit holds a slot of the queue until it is dropped
+#[derive(Debug)]
+pub struct QueuePermit {
+    queue: Arc<RequestQueue>,
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        self.queue.release()
+    }
+}
+
+/// Waiter leaves the queue if the request is cancelled while it waits, or returns the slot if it was already given to it
+struct Waiter {
+    queue: Arc<RequestQueue>,
+    priority: Priority,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            rx.close();
+            if rx.try_recv().is_ok() {
+                self.queue.release();
+            } else {
+                self.queue.remove_cancelled(self.priority);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_queue() {
+        let metrics = Arc::new(QueueMetrics::default());
+        let queue = Arc::new(RequestQueue::new(1, metrics.clone()));
+        let permit = queue.acquire(Priority::Low).await;
+
+        let order = Arc::new(Mutex::new(vec![]));
+        let spawn = |priority: Priority| {
+            let (queue, order) = (queue.clone(), order.clone());
+            tokio::spawn(async move {
+                let _permit = queue.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+            })
+        };
+        let low = spawn(Priority::Low);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let high = spawn(Priority::High);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(metrics.high.depth.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.low.depth.load(Ordering::Relaxed), 1);
+
+        // the high priority request is served first even though it was queued later
+        drop(permit);
+        high.await.unwrap();
+        low.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), vec![Priority::High, Priority::Low]);
+        assert_eq!(metrics.high.depth.load(Ordering::Relaxed), 0);
+        assert_eq!(metrics.high.dequeued.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.low.dequeued.load(Ordering::Relaxed), 1);
+        assert!(metrics.low.max_wait_micros.load(Ordering::Relaxed) > 0);
+
+        // a cancelled request does not hold a slot
+        let permit = queue.acquire(Priority::High).await;
+        let cancelled = spawn(Priority::Low);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancelled.abort();
+        let _ = cancelled.await;
+        assert_eq!(metrics.low.depth.load(Ordering::Relaxed), 0);
+        assert!(queue.state.lock().unwrap().low.is_empty());
+        drop(permit);
+        let _permit = queue.acquire(Priority::Low).await;
+        assert_eq!(queue.state.lock().unwrap().in_flight, 1);
+    }
+}
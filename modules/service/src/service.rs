@@ -1,13 +1,41 @@
-use anyhow::Result;
-use enclave_api::EnclaveProtoAPI;
+use crate::acl::ClientAcl;
+use crate::artifacts::ArtifactStore;
+use crate::compaction::StoreMetrics;
+use crate::compression::ResponseCompression;
+use crate::drift::DriftMetrics;
+use crate::events::EventBus;
+use crate::hooks::{self, OnUpdateHook};
+use crate::keypool::{run_key_pool, KeyAttestor, KeyPoolConfig, KeyPoolMetrics};
+use crate::proof_cache::{ProofCache, ProofCacheConfig, ProofCacheMetrics};
+use crate::pruner::{run_pruner, PrunerConfig, PrunerMetrics};
+use crate::queue::{Priority, QueueMetrics, QueuePermit, RequestQueue};
+use crate::shutdown::{shutdown_enclave, watch_signals, ShutdownSignal, DEFAULT_DRAIN_TIMEOUT};
+use crate::telemetry::ErrorMetrics;
+use crate::watchdog::run_watchdog;
+use crate::watcher::{run_event_watcher, EventWatcherConfig};
+use anyhow::{anyhow, Result};
+use ecall_commands::CommandEvent;
+use enclave_api::{EnclaveCommandAPI, EnclaveInfo, EnclaveProtoAPI};
 use lcp_proto::lcp::service::{
-    elc::v1::{msg_server::MsgServer as ELCMsgServer, query_server::QueryServer as ELCQueryServer},
-    enclave::v1::query_server::QueryServer as EnclaveQueryServer,
+    elc::v1::{
+        msg_server::MsgServer as ELCMsgServer, query_server::QueryServer as ELCQueryServer,
+        MsgVerifyMembership, MsgVerifyMembershipResponse,
+    },
+    enclave::v1::{
+        msg_server::MsgServer as EnclaveMsgServer, query_server::QueryServer as EnclaveQueryServer,
+    },
+    events::v1::events_server::EventsServer,
+};
+use lcp_types::ClientId;
+use log::*;
+use std::{
+    marker::PhantomData, net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc, time::Duration,
 };
-use std::{marker::PhantomData, net::SocketAddr, path::PathBuf, sync::Arc};
 use store::transaction::CommitStore;
 use tokio::runtime::Runtime;
+use tonic::metadata::MetadataMap;
 use tonic::transport::Server;
+use tonic::{Response, Status};
 
 pub struct AppService<E, S>
 where
@@ -16,6 +44,30 @@ where
 {
     pub(crate) home: PathBuf,
     pub(crate) enclave: Arc<E>,
+    pub(crate) acl: Arc<ClientAcl>,
+    pub(crate) pruner: Option<PrunerConfig>,
+    pub(crate) pruner_metrics: Arc<PrunerMetrics>,
+    pub(crate) events: EventBus,
+    pub(crate) event_watcher: Option<EventWatcherConfig>,
+    pub(crate) on_update_hooks: Vec<Arc<dyn OnUpdateHook>>,
+    pub(crate) queue: Option<Arc<RequestQueue>>,
+    pub(crate) queue_metrics: Arc<QueueMetrics>,
+    pub(crate) key_pool: Option<(KeyPoolConfig, Arc<dyn KeyAttestor<E>>)>,
+    pub(crate) key_pool_metrics: Arc<KeyPoolMetrics>,
+    pub(crate) error_metrics: Arc<ErrorMetrics>,
+    pub(crate) watchdog_interval: Option<Duration>,
+    pub(crate) shutdown: Arc<ShutdownSignal>,
+    pub(crate) drain_timeout: Duration,
+    pub(crate) shutdown_rpc: bool,
+    pub(crate) store_metrics: Arc<StoreMetrics>,
+    pub(crate) compaction_rpc: bool,
+    pub(crate) owner_encryption: bool,
+    pub(crate) aggregation_parallelism: Option<usize>,
+    pub(crate) drift_metrics: Arc<DriftMetrics>,
+    pub(crate) artifacts: ArtifactStore,
+    pub(crate) response_compression: Option<ResponseCompression>,
+    pub(crate) proof_cache: Option<Arc<ProofCache>>,
+    pub(crate) proof_cache_metrics: Arc<ProofCacheMetrics>,
     _marker: PhantomData<S>,
 }
 
@@ -28,6 +80,30 @@ where
         Self {
             home: self.home.clone(),
             enclave: self.enclave.clone(),
+            acl: self.acl.clone(),
+            pruner: self.pruner.clone(),
+            pruner_metrics: self.pruner_metrics.clone(),
+            events: self.events.clone(),
+            event_watcher: self.event_watcher.clone(),
+            on_update_hooks: self.on_update_hooks.clone(),
+            queue: self.queue.clone(),
+            queue_metrics: self.queue_metrics.clone(),
+            key_pool: self.key_pool.clone(),
+            key_pool_metrics: self.key_pool_metrics.clone(),
+            error_metrics: self.error_metrics.clone(),
+            watchdog_interval: self.watchdog_interval,
+            shutdown: self.shutdown.clone(),
+            drain_timeout: self.drain_timeout,
+            shutdown_rpc: self.shutdown_rpc,
+            store_metrics: self.store_metrics.clone(),
+            compaction_rpc: self.compaction_rpc,
+            owner_encryption: self.owner_encryption,
+            aggregation_parallelism: self.aggregation_parallelism,
+            drift_metrics: self.drift_metrics.clone(),
+            artifacts: self.artifacts.clone(),
+            response_compression: self.response_compression.clone(),
+            proof_cache: self.proof_cache.clone(),
+            proof_cache_metrics: self.proof_cache_metrics.clone(),
             _marker: Default::default(),
         }
     }
@@ -38,13 +114,267 @@ where
     S: CommitStore + 'static,
     E: EnclaveProtoAPI<S> + 'static,
 {
-    pub fn new<P: Into<PathBuf>>(home: P, enclave: E) -> Self {
-        AppService {
-            home: home.into(),
+    pub fn new<P: Into<PathBuf>>(home: P, enclave: E) -> Result<Self> {
+        let home = home.into();
+        let acl = ClientAcl::load(&home)?;
+        let artifacts = ArtifactStore::new(&home);
+        Ok(AppService {
+            home,
             enclave: Arc::new(enclave),
+            acl: Arc::new(acl),
+            pruner: None,
+            pruner_metrics: Default::default(),
+            events: Default::default(),
+            event_watcher: None,
+            on_update_hooks: Vec::new(),
+            queue: None,
+            queue_metrics: Default::default(),
+            key_pool: None,
+            key_pool_metrics: Default::default(),
+            error_metrics: Default::default(),
+            watchdog_interval: None,
+            shutdown: Default::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            shutdown_rpc: false,
+            store_metrics: Default::default(),
+            compaction_rpc: false,
+            owner_encryption: false,
+            aggregation_parallelism: None,
+            drift_metrics: Default::default(),
+            artifacts,
+            response_compression: None,
+            proof_cache: None,
+            proof_cache_metrics: Default::default(),
             _marker: Default::default(),
+        })
+    }
+
+    /// Enable the pruner that periodically removes the expired consensus states of all clients
+    pub fn with_pruner(mut self, config: PrunerConfig) -> Self {
+        self.pruner = Some(config);
+        self
+    }
+
+    pub fn pruner_metrics(&self) -> Arc<PrunerMetrics> {
+        self.pruner_metrics.clone()
+    }
+
+    /// Enable the watcher that publishes the key attestations and the clients near expiry to the event stream
+    pub fn with_event_watcher(mut self, config: EventWatcherConfig) -> Self {
+        self.event_watcher = Some(config);
+        self
+    }
+
+    /// Add a hook that is invoked with the commitment of each successful `update_client` and `aggregate_messages`
+    pub fn with_on_update_hook(mut self, hook: Arc<dyn OnUpdateHook>) -> Self {
+        self.on_update_hooks.push(hook);
+        self
+    }
+
+    /// Limit the number of concurrent requests to the enclave, where the updates are served before the queries
+    pub fn with_request_queue(mut self, max_concurrent_requests: usize) -> Self {
+        self.queue = Some(Arc::new(RequestQueue::new(
+            max_concurrent_requests,
+            self.queue_metrics.clone(),
+        )));
+        self
+    }
+
+    pub fn queue_metrics(&self) -> Arc<QueueMetrics> {
+        self.queue_metrics.clone()
+    }
+
+    /// Keep `config.size` standby keys generated and attested by `attestor`, ready to be activated
+    pub fn with_key_pool(
+        mut self,
+        config: KeyPoolConfig,
+        attestor: Arc<dyn KeyAttestor<E>>,
+    ) -> Self {
+        self.key_pool = Some((config, attestor));
+        self
+    }
+
+    pub fn key_pool_metrics(&self) -> Arc<KeyPoolMetrics> {
+        self.key_pool_metrics.clone()
+    }
+
+    /// Check every `interval` if the enclave has hung, and re-create it if so
+    ///
+    /// The enclave must be configured with `Enclave::with_watchdog`, which sets the timeout.
+    pub fn with_watchdog(mut self, interval: Duration) -> Self {
+        self.watchdog_interval = Some(interval);
+        self
+    }
+
+    /// Wait up to `timeout` for the commands in flight to finish when the service shuts down
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Accept the `Shutdown` RPC of the enclave `Msg` service, which anyone who can reach the service can call
+    pub fn with_shutdown_rpc(mut self) -> Self {
+        self.shutdown_rpc = true;
+        self
+    }
+
+    /// Accept the `CompactStore` RPC of the enclave `Msg` service, which blocks the commands while the store is compacted
+    pub fn with_compaction_rpc(mut self) -> Self {
+        self.compaction_rpc = true;
+        self
+    }
+
+    /// Verify the messages of `aggregate_messages` with up to `parallelism` concurrent ecalls before they are aggregated
+    ///
+    /// `parallelism` is clamped to the number of the TCSs of the enclave.
+    pub fn with_aggregation_parallelism(mut self, parallelism: usize) -> Self {
+        self.aggregation_parallelism = Some(parallelism);
+        self
+    }
+
+    pub fn store_metrics(&self) -> Arc<StoreMetrics> {
+        self.store_metrics.clone()
+    }
+
+    /// Warn of the tendermint updates whose validator set overlap is below `ratio`
+    pub fn with_drift_warning_ratio(mut self, ratio: f64) -> Self {
+        self.drift_metrics = Arc::new(DriftMetrics::new(ratio));
+        self
+    }
+
+    pub fn drift_metrics(&self) -> Arc<DriftMetrics> {
+        self.drift_metrics.clone()
+    }
+
+    /// Compress the responses for the clients that accept one of the encodings of `compression`
+    pub fn with_response_compression(mut self, compression: ResponseCompression) -> Self {
+        self.response_compression = Some(compression);
+        self
+    }
+
+    /// Encrypt the mirrored state of each owned client in the host store with the key derived from the token of its owner
+    ///
+    /// The service never keeps the tokens, so the state of an owned client is locked after a restart
+    /// until a request of its owner supplies the token again. The host store must be encrypted.
+    pub fn with_owner_encryption(mut self) -> Self {
+        self.owner_encryption = true;
+        self
+    }
+
+    /// Reject the request unless the operator in the request metadata owns the client
+    ///
+    /// The handlers of all requests that change a client call this before the enclave is called,
+    /// so a request that doesn't name its client is rejected.
+    pub(crate) fn authorize_client(
+        &self,
+        client_id: &str,
+        metadata: &MetadataMap,
+    ) -> Result<(), Status> {
+        if client_id.is_empty() {
+            return Err(Status::invalid_argument("client_id must be non-empty"));
+        }
+        let token =
+            ClientAcl::token(metadata).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.acl
+            .authorize(client_id, token)
+            .map_err(|e| Status::permission_denied(e.to_string()))
+    }
+
+    /// Supply the token in the request metadata to the host store if it belongs to the owner of the client
+    ///
+    /// The first call for a client re-encrypts its mirrored state, and the later ones unlock it with the token.
+    pub(crate) fn assign_client_owner(
+        &self,
+        client_id: &str,
+        metadata: &MetadataMap,
+    ) -> Result<()> {
+        if !self.owner_encryption {
+            return Ok(());
+        }
+        let (owner, token) = match (self.acl.owner_of(client_id), ClientAcl::token(metadata)?) {
+            (Some(owner), Some(token)) if self.acl.is_authorized(client_id, Some(token)) => {
+                (owner, token)
+            }
+            _ => return Ok(()),
+        };
+        let client_id =
+            ClientId::from_str(client_id).map_err(|e| anyhow!("invalid client id: {:?}", e))?;
+        self.enclave
+            .assign_client_owner(&client_id, &owner, token.as_bytes())?;
+        Ok(())
+    }
+
+    /// Serve an identical `verify_membership` request from a cache for `config.ttl` without an ecall
+    pub fn with_proof_cache(mut self, config: ProofCacheConfig) -> Self {
+        self.proof_cache = Some(Arc::new(ProofCache::new(
+            config,
+            self.proof_cache_metrics.clone(),
+        )));
+        self
+    }
+
+    pub fn proof_cache_metrics(&self) -> Arc<ProofCacheMetrics> {
+        self.proof_cache_metrics.clone()
+    }
+
+    /// Returns the key epoch of the key manager that the proof cache is checked against, if the cache is enabled
+    ///
+    /// If the epoch cannot be read, the cache is bypassed.
+    pub(crate) fn proof_cache_key_epoch(&self) -> Option<(Arc<ProofCache>, u64)> {
+        let cache = self.proof_cache.as_ref()?;
+        match self.enclave.get_key_manager().key_epoch() {
+            Ok(key_epoch) => Some((cache.clone(), key_epoch)),
+            Err(e) => {
+                warn!("failed to read the key epoch: err={:?}", e);
+                None
+            }
         }
     }
+
+    /// Returns the cached response to the request if the cache is enabled and no key has changed since it was cached
+    pub(crate) fn cached_verify_membership(
+        &self,
+        msg: &MsgVerifyMembership,
+    ) -> Option<MsgVerifyMembershipResponse> {
+        let (cache, key_epoch) = self.proof_cache_key_epoch()?;
+        cache.get(msg, key_epoch)
+    }
+
+    pub fn error_metrics(&self) -> Arc<ErrorMetrics> {
+        self.error_metrics.clone()
+    }
+
+    /// Count the error of the enclave and convert it to the status returned to the client
+    pub(crate) fn aborted(&self, err: enclave_api::Error) -> Status {
+        self.error_metrics.record_enclave_error(&err);
+        Status::aborted(err.to_string())
+    }
+
+    /// Wrap the message in a response that is sent uncompressed if it is below the compression threshold
+    pub(crate) fn respond<T: prost::Message>(&self, msg: T) -> Response<T> {
+        let compress = self
+            .response_compression
+            .as_ref()
+            .map_or(false, |c| c.should_compress(msg.encoded_len()));
+        let mut res = Response::new(msg);
+        if !compress {
+            res.disable_compression();
+        }
+        res
+    }
+
+    /// Wait for a slot of the request queue if it is enabled
+    pub(crate) async fn acquire(&self, priority: Priority) -> Option<QueuePermit> {
+        match self.queue.as_ref() {
+            Some(queue) => Some(queue.acquire(priority).await),
+            None => None,
+        }
+    }
+
+    /// Returns the bus of the events streamed by the `Events` service
+    pub fn events(&self) -> EventBus {
+        self.events.clone()
+    }
 }
 
 pub fn run_service<E, S>(srv: AppService<E, S>, rt: Arc<Runtime>, addr: SocketAddr) -> Result<()>
@@ -52,22 +382,107 @@ where
     S: CommitStore,
     E: EnclaveProtoAPI<S>,
 {
-    let elc_msg_srv = ELCMsgServer::new(srv.clone());
-    let elc_query_srv = ELCQueryServer::new(srv.clone());
-    let enclave_srv = EnclaveQueryServer::new(srv);
+    let pruner = srv
+        .pruner
+        .clone()
+        .map(|config| run_pruner::<E, S>(srv.enclave.clone(), config, srv.pruner_metrics.clone()));
+    let key_pool = srv.key_pool.clone().map(|(config, attestor)| {
+        run_key_pool::<E, S>(
+            srv.enclave.clone(),
+            config,
+            attestor,
+            srv.key_pool_metrics.clone(),
+            srv.error_metrics.clone(),
+        )
+    });
+    let watchdog = srv
+        .watchdog_interval
+        .map(|interval| run_watchdog::<E, S>(srv.enclave.clone(), interval));
+    let event_watcher = srv
+        .event_watcher
+        .clone()
+        .map(|config| run_event_watcher(srv.enclave.clone(), srv.events.clone(), config));
+    if let Some(subscribers) = srv.enclave.get_command_events() {
+        let (events, on_update_hooks, proof_cache, handle) = (
+            srv.events.clone(),
+            srv.on_update_hooks.clone(),
+            srv.proof_cache.clone(),
+            rt.handle().clone(),
+        );
+        subscribers.subscribe(move |event| {
+            // the cached proofs of a migrated client refer to the state ids before the migration
+            if let (CommandEvent::ClientMigrated { client_id, .. }, Some(cache)) =
+                (event, proof_cache.as_ref())
+            {
+                cache.invalidate_client(client_id.as_str());
+            }
+            events.command_event(event);
+            hooks::dispatch_event(&handle, &on_update_hooks, event.clone());
+        });
+    }
+    let enclave = srv.enclave.clone();
+    let shutdown = srv.shutdown.clone();
+    let drain_timeout = srv.drain_timeout;
+    let encodings = srv
+        .response_compression
+        .as_ref()
+        .map_or(vec![], |c| c.encodings.clone());
+    let mut elc_msg_srv = ELCMsgServer::new(srv.clone());
+    let mut elc_query_srv = ELCQueryServer::new(srv.clone());
+    let mut enclave_srv = EnclaveQueryServer::new(srv.clone());
+    let mut enclave_msg_srv = EnclaveMsgServer::new(srv.clone());
+    let mut events_srv = EventsServer::new(srv);
+    for encoding in encodings {
+        elc_msg_srv = elc_msg_srv
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+        elc_query_srv = elc_query_srv
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+        enclave_srv = enclave_srv
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+        enclave_msg_srv = enclave_msg_srv
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+        events_srv = events_srv
+            .send_compressed(encoding)
+            .accept_compressed(encoding);
+    }
     let reflection = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(lcp_proto::FILE_DESCRIPTOR_SET)
         .build()
         .expect("failed to create gRPC reflection servicer");
     rt.block_on(async {
-        Server::builder()
+        if let Some(pruner) = pruner {
+            tokio::spawn(pruner);
+        }
+        if let Some(key_pool) = key_pool {
+            tokio::spawn(key_pool);
+        }
+        if let Some(event_watcher) = event_watcher {
+            tokio::spawn(event_watcher);
+        }
+        if let Some(watchdog) = watchdog {
+            tokio::spawn(watchdog);
+        }
+        tokio::spawn(watch_signals(shutdown.clone(), drain_timeout));
+        let server = Server::builder()
             .add_service(elc_msg_srv)
             .add_service(elc_query_srv)
             .add_service(enclave_srv)
+            .add_service(enclave_msg_srv)
+            .add_service(events_srv)
             .add_service(reflection)
-            .serve(addr)
-            .await
-            .unwrap();
+            .serve(addr);
+        tokio::select! {
+            res = server => res.unwrap(),
+            drain_timeout = shutdown.requested() => {
+                // the queries are served while the mutating commands are drained,
+                // then the server is dropped with the streams of the subscribers
+                shutdown_enclave(enclave, drain_timeout).await;
+            }
+        }
     });
     Ok(())
 }
@@ -0,0 +1,159 @@
+use crate::compaction::{compact_store, to_proto};
+use crate::service::AppService;
+use enclave_api::{EnclaveCommandAPI, EnclaveProtoAPI};
+use lcp_proto::lcp::service::enclave::v1::{
+    msg_server::Msg, MsgCompactStore, MsgCompactStoreResponse, MsgShutdown, MsgShutdownResponse,
+};
+use log::*;
+use std::sync::Arc;
+use std::time::Duration;
+use store::transaction::CommitStore;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tonic::{Request, Response, Status};
+
+/// Default time to wait for the commands in flight to finish before the enclave is destroyed
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// ShutdownSignal carries the first shutdown request with its drain timeout to the service
+#[derive(Debug)]
+pub(crate) struct ShutdownSignal {
+    sender: watch::Sender<Option<Duration>>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self {
+            sender: watch::channel(None).0,
+        }
+    }
+}
+
+impl ShutdownSignal {
+    /// Request a shutdown, and returns false if it has already been requested
+    pub(crate) fn request(&self, drain_timeout: Duration) -> bool {
+        self.sender.send_if_modified(|requested| {
+            if requested.is_some() {
+                return false;
+            }
+            *requested = Some(drain_timeout);
+            true
+        })
+    }
+
+    /// Wait for a shutdown request and returns its drain timeout
+    pub(crate) async fn requested(&self) -> Duration {
+        let mut receiver = self.sender.subscribe();
+        loop {
+            if let Some(drain_timeout) = *receiver.borrow_and_update() {
+                return drain_timeout;
+            }
+            // the sender is never dropped while `self` is borrowed
+            let _ = receiver.changed().await;
+        }
+    }
+}
+
+/// Request a shutdown when the process receives SIGTERM or SIGINT
+pub(crate) async fn watch_signals(shutdown: Arc<ShutdownSignal>, drain_timeout: Duration) {
+    let (mut sigterm, mut sigint) = match (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::interrupt()),
+    ) {
+        (Ok(sigterm), Ok(sigint)) => (sigterm, sigint),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("failed to install the signal handlers: err={:?}", e);
+            return;
+        }
+    };
+    let name = tokio::select! {
+        _ = sigterm.recv() => "SIGTERM",
+        _ = sigint.recv() => "SIGINT",
+    };
+    info!("received {}: shut down the service", name);
+    shutdown.request(drain_timeout);
+}
+
+/// Shut down the enclave on a blocking thread, as draining the commands in flight may take up to `drain_timeout`
+pub(crate) async fn shutdown_enclave<E, S>(enclave: Arc<E>, drain_timeout: Duration)
+where
+    S: CommitStore + 'static,
+    E: EnclaveCommandAPI<S> + 'static,
+{
+    let res = tokio::task::spawn_blocking(move || enclave.shutdown(drain_timeout)).await;
+    match res {
+        Ok(Ok(true)) => info!("shut down the enclave gracefully"),
+        Ok(Ok(false)) => warn!("shut down the enclave before the commands in flight finished"),
+        Ok(Err(e)) => error!("failed to shut down the enclave: err={:?}", e),
+        Err(e) => error!("shutdown task panicked: err={:?}", e),
+    }
+}
+
+#[tonic::async_trait]
+impl<E, S> Msg for AppService<E, S>
+where
+    S: CommitStore + 'static,
+    E: EnclaveProtoAPI<S> + 'static,
+{
+    async fn shutdown(
+        &self,
+        request: Request<MsgShutdown>,
+    ) -> Result<Response<MsgShutdownResponse>, Status> {
+        if !self.shutdown_rpc {
+            return Err(Status::permission_denied(
+                "the shutdown RPC is disabled on this service",
+            ));
+        }
+        let drain_timeout = match request.into_inner().drain_timeout_secs {
+            0 => self.drain_timeout,
+            secs => Duration::from_secs(secs),
+        };
+        if !self.shutdown.request(drain_timeout) {
+            return Err(Status::failed_precondition(
+                "the service is already shutting down",
+            ));
+        }
+        info!(
+            "received a shutdown request: drain_timeout={:?}",
+            drain_timeout
+        );
+        Ok(Response::new(MsgShutdownResponse {}))
+    }
+
+    async fn compact_store(
+        &self,
+        _: Request<MsgCompactStore>,
+    ) -> Result<Response<MsgCompactStoreResponse>, Status> {
+        if !self.compaction_rpc {
+            return Err(Status::permission_denied(
+                "the compaction RPC is disabled on this service",
+            ));
+        }
+        let (before, after) = compact_store(self.enclave.clone(), self.store_metrics.clone())
+            .await
+            .map_err(|e| Status::aborted(e.to_string()))?;
+        Ok(Response::new(MsgCompactStoreResponse {
+            before: before.map(to_proto),
+            after: after.map(to_proto),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_signal() {
+        let shutdown = Arc::new(ShutdownSignal::default());
+        let waiter = {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move { shutdown.requested().await })
+        };
+        assert!(shutdown.request(Duration::from_secs(1)));
+        // the first request wins
+        assert!(!shutdown.request(Duration::from_secs(2)));
+        assert_eq!(waiter.await.unwrap(), Duration::from_secs(1));
+        assert_eq!(shutdown.requested().await, Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,206 @@
+use enclave_api::{Error as EnclaveError, ErrorDetail as EnclaveErrorDetail};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// The component where an error occurred
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorCategory {
+    /// The command handler rejected the request, e.g. an invalid input or a proof that is not bound to the request
+    Handler,
+    /// The light client failed to verify a header or a proof
+    LightClient,
+    /// The host store failed, or the mutating commands are halted after the failures
+    Store,
+    /// The remote attestation of an enclave key failed
+    Attestation,
+    /// The key manager failed to load or save an enclave key
+    KeyManager,
+    /// The enclave failed to execute the command, e.g. an SGX error
+    Enclave,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Handler => "handler",
+            Self::LightClient => "light_client",
+            Self::Store => "store",
+            Self::Attestation => "attestation",
+            Self::KeyManager => "key_manager",
+            Self::Enclave => "enclave",
+        }
+    }
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Returns the category and the kind of an error of the enclave
+///
+/// The kind of a command error is the name of its `ErrorCode`, so that it matches the code returned to the relayer.
+pub fn classify(err: &EnclaveError) -> (ErrorCategory, String) {
+    let (category, kind) = match err.detail() {
+        EnclaveErrorDetail::Command(e) => {
+            let category = match e.code.code() {
+                code if code >= 200 => ErrorCategory::Attestation,
+                code if code >= 100 => ErrorCategory::LightClient,
+                _ => ErrorCategory::Handler,
+            };
+            return (category, format!("{:?}", e.code));
+        }
+        EnclaveErrorDetail::InvalidArgument(_) | EnclaveErrorDetail::EcallCommand(_) => {
+            (ErrorCategory::Handler, "InvalidInput")
+        }
+        EnclaveErrorDetail::ProofVerification(_) | EnclaveErrorDetail::Commitments(_) => {
+            (ErrorCategory::Handler, "ProofVerification")
+        }
+        EnclaveErrorDetail::Store(_) => (ErrorCategory::Store, "Store"),
+        EnclaveErrorDetail::CircuitOpen(_) => (ErrorCategory::Store, "CircuitOpen"),
+        EnclaveErrorDetail::Io(_) => (ErrorCategory::Store, "Io"),
+        EnclaveErrorDetail::AttestationReport(_) => (ErrorCategory::Attestation, "Report"),
+        EnclaveErrorDetail::KeyManager(_) => (ErrorCategory::KeyManager, "KeyManager"),
+        EnclaveErrorDetail::Crypto(_) => (ErrorCategory::KeyManager, "Crypto"),
+        EnclaveErrorDetail::RevokedEnclaveKey(_) => (ErrorCategory::KeyManager, "RevokedKey"),
+        EnclaveErrorDetail::SgxError(_) | EnclaveErrorDetail::EnclaveLaunch(_) => {
+            (ErrorCategory::Enclave, "Sgx")
+        }
+        EnclaveErrorDetail::RejectedCommand(_) | EnclaveErrorDetail::Protocol(_) => {
+            (ErrorCategory::Enclave, "Protocol")
+        }
+        EnclaveErrorDetail::ShuttingDown(_) => (ErrorCategory::Enclave, "ShuttingDown"),
+        EnclaveErrorDetail::EnclaveRecreated(_) => (ErrorCategory::Enclave, "Recreated"),
+        EnclaveErrorDetail::ProtoDecode(_) => (ErrorCategory::Enclave, "Encoding"),
+    };
+    (category, kind.to_string())
+}
+
+/// ErrorCount is the number of the errors of a kind since the service started
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorCount {
+    pub category: ErrorCategory,
+    pub kind: String,
+    pub count: u64,
+}
+
+/// Cumulative counts of the errors by category and kind since the service started
+///
+/// An alert can watch the counts to detect, e.g., rising proof verification failures or store errors.
+#[derive(Debug, Default)]
+pub struct ErrorMetrics {
+    counts: Mutex<BTreeMap<(ErrorCategory, String), u64>>,
+}
+
+impl ErrorMetrics {
+    pub fn record(&self, category: ErrorCategory, kind: &str) {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry((category, kind.to_string()))
+            .or_default() += 1;
+    }
+
+    /// Record an error of the enclave under the category and the kind returned by `classify`
+    pub fn record_enclave_error(&self, err: &EnclaveError) {
+        let (category, kind) = classify(err);
+        self.record(category, &kind);
+    }
+
+    /// Returns the counts ordered by category and kind
+    pub fn counts(&self) -> Vec<ErrorCount> {
+        self.counts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((category, kind), count)| ErrorCount {
+                category: *category,
+                kind: kind.clone(),
+                count: *count,
+            })
+            .collect()
+    }
+
+    /// Returns the total count of the errors of the category
+    pub fn total(&self, category: ErrorCategory) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((c, _), _)| *c == category)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ecall_commands::ErrorCode;
+    use sgx_types::sgx_status_t;
+
+    #[test]
+    fn test_error_metrics() {
+        let metrics = ErrorMetrics::default();
+        let proof_mismatch = EnclaveError::command(
+            sgx_status_t::SGX_ERROR_UNEXPECTED,
+            ErrorCode::ProofMismatch,
+            "root mismatch".into(),
+        );
+        assert_eq!(
+            classify(&proof_mismatch),
+            (ErrorCategory::LightClient, "ProofMismatch".to_string())
+        );
+        assert_eq!(
+            classify(&EnclaveError::command(
+                sgx_status_t::SGX_ERROR_UNEXPECTED,
+                ErrorCode::AttestationPolicyViolation,
+                "advisory is not allowed".into(),
+            )),
+            (
+                ErrorCategory::Attestation,
+                "AttestationPolicyViolation".to_string()
+            )
+        );
+        metrics.record_enclave_error(&proof_mismatch);
+        metrics.record_enclave_error(&proof_mismatch);
+        metrics.record_enclave_error(&EnclaveError::command(
+            sgx_status_t::SGX_ERROR_UNEXPECTED,
+            ErrorCode::InvalidInput,
+            "bad request".into(),
+        ));
+        metrics.record_enclave_error(&EnclaveError::circuit_open("commit failed".into()));
+        metrics.record(ErrorCategory::Attestation, "Ias");
+
+        assert_eq!(
+            metrics.counts(),
+            vec![
+                ErrorCount {
+                    category: ErrorCategory::Handler,
+                    kind: "InvalidInput".into(),
+                    count: 1
+                },
+                ErrorCount {
+                    category: ErrorCategory::LightClient,
+                    kind: "ProofMismatch".into(),
+                    count: 2
+                },
+                ErrorCount {
+                    category: ErrorCategory::Store,
+                    kind: "CircuitOpen".into(),
+                    count: 1
+                },
+                ErrorCount {
+                    category: ErrorCategory::Attestation,
+                    kind: "Ias".into(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(metrics.total(ErrorCategory::LightClient), 2);
+        assert_eq!(metrics.total(ErrorCategory::Enclave), 0);
+    }
+}
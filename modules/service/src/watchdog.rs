@@ -0,0 +1,27 @@
+use enclave_api::EnclaveCommandAPI;
+use log::*;
+use std::sync::Arc;
+use std::time::Duration;
+use store::transaction::CommitStore;
+
+/// Check the watchdog of the enclave every `interval` and re-create the enclave if it has hung
+///
+/// The check runs on a blocking thread other than the ones of the hung ecalls.
+pub(crate) async fn run_watchdog<E, S>(enclave: Arc<E>, interval: Duration)
+where
+    S: CommitStore + 'static,
+    E: EnclaveCommandAPI<S> + 'static,
+{
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        let enclave = enclave.clone();
+        let res = tokio::task::spawn_blocking(move || enclave.recover_hung_enclave()).await;
+        match res {
+            Ok(Ok(true)) => warn!("recovered the hung enclave"),
+            Ok(Ok(false)) => {}
+            Ok(Err(e)) => error!("failed to recover the hung enclave: err={:?}", e),
+            Err(e) => error!("watchdog task panicked: err={:?}", e),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::events::EventBus;
+use anyhow::Result;
+use enclave_api::EnclaveInfo;
+use lcp_proto::lcp::service::{
+    enclave::v1::EnclaveKeyInfo,
+    events::v1::{event, KeyAttested, KeyRevoked},
+};
+use lcp_types::Time;
+use log::*;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Configuration of the watcher that publishes the events which are not triggered by a request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventWatcherConfig {
+    /// Interval between the checks
+    pub interval: Duration,
+    /// A `ClientNearExpiry` event is published when the latest state of a client expires within this duration
+    pub expiry_threshold: Duration,
+}
+
+impl Default for EventWatcherConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            expiry_threshold: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Publish a `KeyAttested` event for each attested key that is not in `known`, and returns all the attested keys
+///
+/// The attestation is performed by another process, so the key manager is polled to find new reports.
+/// If `known` is None, no events are published.
+pub fn check_attested_keys<E: EnclaveInfo>(
+    enclave: &E,
+    events: &EventBus,
+    known: Option<&BTreeSet<[u8; 20]>>,
+) -> Result<BTreeSet<[u8; 20]>> {
+    let mut attested = BTreeSet::new();
+    for key in enclave.get_key_manager().all_keys()? {
+        if key.avr.is_none() {
+            continue;
+        }
+        attested.insert(key.address.0);
+        if known.map_or(true, |known| known.contains(&key.address.0)) {
+            continue;
+        }
+        let mrenclave = key.mrenclave.to_vec();
+        let info = EnclaveKeyInfo::try_from(key)?;
+        events.publish(event::Event::KeyAttested(KeyAttested {
+            enclave_key_address: info.enclave_key_address,
+            mrenclave,
+            attestation_time: info.attestation_time,
+        }));
+    }
+    Ok(attested)
+}
+
+/// Publish a `KeyRevoked` event for each revoked key that is not in `known`, and returns all the revoked keys
+///
+/// If `known` is None, no events are published.
+pub fn check_revoked_keys<E: EnclaveInfo>(
+    enclave: &E,
+    events: &EventBus,
+    known: Option<&BTreeSet<[u8; 20]>>,
+) -> Result<BTreeSet<[u8; 20]>> {
+    let mut revoked = BTreeSet::new();
+    for key in enclave.get_key_manager().revoked_keys()? {
+        revoked.insert(key.address.0);
+        if known.map_or(true, |known| known.contains(&key.address.0)) {
+            continue;
+        }
+        error!(
+            "the enclave key has been revoked: address={} reason={}",
+            key.address, key.reason
+        );
+        events.publish(event::Event::KeyRevoked(KeyRevoked {
+            enclave_key_address: key.address.into(),
+            reason: key.reason,
+            revoked_at: key.revoked_at,
+        }));
+    }
+    Ok(revoked)
+}
+
+/// Run the checks every `config.interval` until the runtime shuts down
+///
+/// The keys that are already attested or revoked when the watcher starts are not published.
+pub(crate) async fn run_event_watcher<E>(
+    enclave: Arc<E>,
+    events: EventBus,
+    config: EventWatcherConfig,
+) where
+    E: EnclaveInfo + 'static,
+{
+    let attested: Arc<Mutex<Option<BTreeSet<[u8; 20]>>>> = Default::default();
+    let revoked: Arc<Mutex<Option<BTreeSet<[u8; 20]>>>> = Default::default();
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        match Time::now() + config.expiry_threshold {
+            Ok(deadline) => {
+                events.expiring_clients(deadline);
+            }
+            Err(e) => error!("failed to calculate the expiry deadline: err={:?}", e),
+        }
+        let (enclave, events, attested, revoked) = (
+            enclave.clone(),
+            events.clone(),
+            attested.clone(),
+            revoked.clone(),
+        );
+        // the key manager blocks the current thread
+        let res = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut attested = attested.lock().unwrap();
+            *attested = Some(check_attested_keys(&*enclave, &events, attested.as_ref())?);
+            let mut revoked = revoked.lock().unwrap();
+            *revoked = Some(check_revoked_keys(&*enclave, &events, revoked.as_ref())?);
+            Ok(())
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("failed to check the enclave keys: err={:?}", e),
+            Err(e) => error!("event watcher task panicked: err={:?}", e),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::prelude::*;
+use crate::Result;
+use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter};
+use core::str;
+
+/// `StoreSnapshot` is all the committed key-value pairs of a store, ordered by key
+pub type StoreSnapshot = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// `SnapshotStore` is a store that can dump its committed key-value pairs
+pub trait SnapshotStore {
+    /// `snapshot` returns the key-value pairs committed to the store
+    ///
+    /// The values are returned in plaintext even if the store encrypts them at rest.
+    fn snapshot(&self) -> Result<StoreSnapshot>;
+}
+
+/// `Change` is a change of the value of a key between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(Vec<u8>),
+    Removed(Vec<u8>),
+    Modified { prev: Vec<u8>, post: Vec<u8> },
+}
+
+/// `StoreDiff` is the key-level diff between two snapshots of a store
+///
+/// Taking a snapshot before and after a commit, e.g. of `update_client`, shows which
+/// client states and consensus states the commit wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreDiff(BTreeMap<Vec<u8>, Change>);
+
+impl StoreDiff {
+    /// `compute` returns the changes from `prev` to `post`
+    pub fn compute(prev: &StoreSnapshot, post: &StoreSnapshot) -> Self {
+        let mut changes = BTreeMap::new();
+        for (key, prev_value) in prev.iter() {
+            match post.get(key) {
+                Some(post_value) if post_value == prev_value => {}
+                Some(post_value) => {
+                    changes.insert(
+                        key.clone(),
+                        Change::Modified {
+                            prev: prev_value.clone(),
+                            post: post_value.clone(),
+                        },
+                    );
+                }
+                None => {
+                    changes.insert(key.clone(), Change::Removed(prev_value.clone()));
+                }
+            }
+        }
+        for (key, post_value) in post.iter() {
+            if !prev.contains_key(key) {
+                changes.insert(key.clone(), Change::Added(post_value.clone()));
+            }
+        }
+        Self(changes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&Change> {
+        self.0.get(key)
+    }
+
+    /// `iter` returns the changes ordered by key
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &Change)> {
+        self.0.iter()
+    }
+
+    /// `with_prefix` returns the changes of the keys that start with `prefix`, e.g. `clients/07-tendermint-0/`
+    pub fn with_prefix(&self, prefix: &[u8]) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|(k, _)| k.starts_with(prefix))
+                .map(|(k, c)| (k.clone(), c.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Renders a change per line, where `+`, `-` and `~` mean an added, a removed and a modified key respectively
+impl Display for StoreDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for (key, change) in self.0.iter() {
+            let key = display_key(key);
+            match change {
+                Change::Added(v) => writeln!(f, "+ {} ({} bytes)", key, v.len())?,
+                Change::Removed(v) => writeln!(f, "- {} ({} bytes)", key, v.len())?,
+                Change::Modified { prev, post } => writeln!(
+                    f,
+                    "~ {} ({} bytes -> {} bytes)",
+                    key,
+                    prev.len(),
+                    post.len()
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the key as is if it is a printable string like a path of the light client store, otherwise its hex
+fn display_key(key: &[u8]) -> String {
+    match str::from_utf8(key) {
+        Ok(s) if !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic()) => s.to_string(),
+        _ => format!(
+            "0x{}",
+            key.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(non_snake_case)]
+    fn B(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_store_diff() {
+        let prev: StoreSnapshot = [
+            (B("clients/07-tendermint-0/clientState"), B("cs1")),
+            (B("clients/07-tendermint-0/consensusStates/0-1"), B("cons1")),
+            (B("clients/07-tendermint-1/clientState"), B("cs")),
+        ]
+        .into_iter()
+        .collect();
+        let mut post = prev.clone();
+        post.insert(B("clients/07-tendermint-0/clientState"), B("cs2"));
+        post.insert(B("clients/07-tendermint-0/consensusStates/0-2"), B("cons2"));
+        post.remove(&B("clients/07-tendermint-1/clientState"));
+        post.insert(vec![0, 1], vec![2]);
+
+        let diff = StoreDiff::compute(&prev, &post);
+        assert_eq!(diff.len(), 4);
+        assert_eq!(
+            diff.get(b"clients/07-tendermint-0/clientState"),
+            Some(&Change::Modified {
+                prev: B("cs1"),
+                post: B("cs2")
+            })
+        );
+        assert_eq!(
+            diff.get(b"clients/07-tendermint-0/consensusStates/0-2"),
+            Some(&Change::Added(B("cons2")))
+        );
+        assert_eq!(
+            diff.get(b"clients/07-tendermint-1/clientState"),
+            Some(&Change::Removed(B("cs")))
+        );
+        assert_eq!(
+            diff.get(b"clients/07-tendermint-0/consensusStates/0-1"),
+            None
+        );
+        assert_eq!(diff.with_prefix(b"clients/07-tendermint-0/").len(), 2);
+        assert_eq!(
+            diff.to_string(),
+            "+ 0x0001 (1 bytes)\n\
+             ~ clients/07-tendermint-0/clientState (3 bytes -> 3 bytes)\n\
+             + clients/07-tendermint-0/consensusStates/0-2 (5 bytes)\n\
+             - clients/07-tendermint-1/clientState (2 bytes)\n"
+        );
+
+        assert!(StoreDiff::compute(&post, &post).is_empty());
+    }
+}
@@ -0,0 +1,539 @@
+use crate::prelude::*;
+use crate::{Error, KVStore, Result};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::Hmac;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// The length of the random nonce that is prepended to each encrypted value
+pub const NONCE_LEN: usize = 12;
+
+/// The key of the entry that is used to check if the store is opened with the right secret
+pub const CIPHER_CHECK_KEY: &[u8] = b"\x00lcp/store/cipher-check";
+
+/// The length of the random salt of the key derivation, which the store keeps in plaintext
+pub const KDF_SALT_LEN: usize = 16;
+
+/// The number of the PBKDF2-HMAC-SHA256 iterations that derive the key from the secret of the operator
+#[cfg(not(test))]
+const KDF_ROUNDS: u32 = 600_000;
+// the unoptimized tests derive many keys
+#[cfg(test)]
+const KDF_ROUNDS: u32 = 1_000;
+
+const KEY_DERIVATION_DOMAIN: &[u8] = b"lcp-store-encryption-v2";
+const OWNER_KEY_DERIVATION_DOMAIN: &[u8] = b"lcp-store-owner-token-v2";
+const OWNER_TOKEN_DIGEST_DOMAIN: &[u8] = b"lcp-store-owner-token-digest";
+pub(crate) const CIPHER_CHECK_VALUE: &[u8] = b"lcp";
+
+/// StoreKey is the source of the key that encrypts the values of a store
+#[derive(Clone)]
+pub enum StoreKey {
+    /// A secret provided by the operator, which is stretched with the salt of the store
+    Secret(Vec<u8>),
+    /// A random key that the enclave keeps sealed on the disk and unseals for the host
+    Sealed([u8; 32]),
+}
+
+impl core::fmt::Debug for StoreKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Secret(_) => write!(f, "StoreKey::Secret(..)"),
+            Self::Sealed(_) => write!(f, "StoreKey::Sealed(..)"),
+        }
+    }
+}
+
+impl StoreKey {
+    /// Returns the cipher of the store with the salt
+    ///
+    /// The salt is used only to derive the key from a secret, as the key unsealed by the enclave is already uniformly random.
+    pub fn cipher(&self, salt: &[u8]) -> StoreCipher {
+        match self {
+            Self::Secret(secret) => StoreCipher::from_secret(secret, salt),
+            Self::Sealed(key) => StoreCipher::new(*key),
+        }
+    }
+}
+
+/// Returns a random salt for the key derivation of a new store
+pub fn new_kdf_salt() -> [u8; KDF_SALT_LEN] {
+    let mut salt = [0u8; KDF_SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// StoreCipher encrypts the values of a persistent store with AES-256-GCM
+///
+/// Keys are stored in plaintext so that the backend can still look them up,
+/// but each value is bound to its key as associated data, so that values cannot be swapped between keys.
+///
+/// The values of a client that is assigned to an owner are encrypted with a key derived from the token of the owner,
+/// so neither the secret of the store nor the key of one owner can decrypt the states of the clients of the others.
+/// The assignments are shared between the clones of the cipher.
+#[derive(Clone)]
+pub struct StoreCipher {
+    aead: Aes256Gcm,
+    /// the ciphers of the owners keyed by client id, which are None until the owners supply their tokens
+    owners: Arc<RwLock<BTreeMap<String, Option<OwnerCipher>>>>,
+}
+
+/// OwnerCipher is the cipher of an owner who has supplied the token
+#[derive(Clone)]
+struct OwnerCipher {
+    aead: Aes256Gcm,
+    /// the digest of the token, which checks the token again without the key derivation
+    token_digest: [u8; 32],
+}
+
+impl core::fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "StoreCipher(..)")
+    }
+}
+
+impl StoreCipher {
+    /// Create a cipher with a 256-bit key
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            aead: Aes256Gcm::new(&key.into()),
+            owners: Default::default(),
+        }
+    }
+
+    /// Create a cipher with a key derived from a secret provided by the operator and the salt of the store
+    ///
+    /// The secret is stretched with PBKDF2-HMAC-SHA256, so that a weak secret is expensive to guess from a copy of the store.
+    pub fn from_secret(secret: &[u8], salt: &[u8]) -> Self {
+        let salt = [KEY_DERIVATION_DOMAIN, salt].concat();
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(secret, &salt, KDF_ROUNDS, &mut key);
+        Self::new(key)
+    }
+
+    /// Returns the key derived from the token of an owner and the salt of the assignment,
+    /// which decrypts only the values of the clients of the owner
+    ///
+    /// The token is stretched with PBKDF2-HMAC-SHA256 like the secret of the store,
+    /// so that a weak token is expensive to guess from a copy of the store. The store never persists the token.
+    pub fn owner_key(token: &[u8], salt: &[u8]) -> [u8; 32] {
+        let salt = [OWNER_KEY_DERIVATION_DOMAIN, salt].concat();
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(token, &salt, KDF_ROUNDS, &mut key);
+        key
+    }
+
+    /// Encrypt and decrypt the values of the client with the key of the owner from now on
+    ///
+    /// The caller must have re-encrypted the values that were written before with the key,
+    /// and checked that the key is derived from `token`.
+    pub fn assign_owner(&self, client_id: &str, owner_key: [u8; 32], token: &[u8]) {
+        let owner = OwnerCipher {
+            aead: Aes256Gcm::new(&owner_key.into()),
+            token_digest: token_digest(client_id, token),
+        };
+        self.owners
+            .write()
+            .unwrap()
+            .insert(client_id.to_string(), Some(owner));
+    }
+
+    /// Returns true if the owner of the client has supplied `token` since the store was opened
+    pub fn is_unlocked_with(&self, client_id: &str, token: &[u8]) -> bool {
+        matches!(
+            self.owners.read().unwrap().get(client_id),
+            Some(Some(owner)) if owner.token_digest == token_digest(client_id, token)
+        )
+    }
+
+    /// Mark the client as assigned to an owner who has not supplied the token yet
+    ///
+    /// The values of the client can be neither read nor written until `assign_owner` is called with the key of the owner.
+    pub fn lock_owner(&self, client_id: &str) {
+        self.owners
+            .write()
+            .unwrap()
+            .entry(client_id.to_string())
+            .or_insert(None);
+    }
+
+    /// Returns true if the client is assigned to an owner
+    pub fn has_owner(&self, client_id: &str) -> bool {
+        self.owners.read().unwrap().contains_key(client_id)
+    }
+
+    /// Returns true if the client is assigned to an owner who has not supplied the token yet
+    pub fn is_locked(&self, client_id: &str) -> bool {
+        matches!(self.owners.read().unwrap().get(client_id), Some(None))
+    }
+
+    /// Returns the cipher of the key, or an `OwnerLocked` error if the key belongs to a client whose owner has not supplied the token
+    fn aead_of(&self, key: &[u8]) -> Result<Aes256Gcm> {
+        let client_id = match client_id_of(key) {
+            Some(client_id) => client_id,
+            None => return Ok(self.aead.clone()),
+        };
+        match self.owners.read().unwrap().get(client_id) {
+            Some(Some(owner)) => Ok(owner.aead.clone()),
+            Some(None) => Err(Error::owner_locked(client_id.to_string())),
+            None => Ok(self.aead.clone()),
+        }
+    }
+
+    /// Returns `nonce || ciphertext` of the value
+    ///
+    /// Returns an `OwnerLocked` error if the key belongs to a client whose owner has not supplied the token,
+    /// as the value must not be written with any other key than the one of the owner.
+    pub fn encrypt(&self, key: &[u8], value: &[u8]) -> Result<Vec<u8>> {
+        Ok(encrypt(&self.aead_of(key)?, key, value))
+    }
+
+    /// Returns the value of `nonce || ciphertext` that was encrypted under `key`
+    pub fn decrypt(&self, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        decrypt(&self.aead_of(key)?, key, data)
+    }
+
+    /// Check the entry at `CIPHER_CHECK_KEY` in the store, and write it if the store doesn't have it yet
+    ///
+    /// This detects a wrong secret when the store is opened instead of at the first read.
+    pub(crate) fn check(&self, store: &mut dyn KVStore, writable: bool) -> Result<()> {
+        match store.get(CIPHER_CHECK_KEY) {
+            Some(data) => {
+                if self.decrypt(CIPHER_CHECK_KEY, &data)? != CIPHER_CHECK_VALUE {
+                    return Err(Error::decryption(
+                        "unexpected value of the cipher check entry".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            None if writable => {
+                store.set(
+                    CIPHER_CHECK_KEY.to_vec(),
+                    self.encrypt(CIPHER_CHECK_KEY, CIPHER_CHECK_VALUE)?,
+                );
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the client id of a key under `clients/{client_id}/`
+pub fn client_id_of(key: &[u8]) -> Option<&str> {
+    let rest = key.strip_prefix(b"clients/")?;
+    let end = rest.iter().position(|b| *b == b'/')?;
+    core::str::from_utf8(&rest[..end]).ok()
+}
+
+fn token_digest(client_id: &str, token: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(OWNER_TOKEN_DIGEST_DOMAIN);
+    hasher.update((client_id.len() as u64).to_be_bytes());
+    hasher.update(client_id.as_bytes());
+    hasher.update(token);
+    hasher.finalize().into()
+}
+
+fn encrypt(aead: &Aes256Gcm, key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = aead
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: value,
+                aad: key,
+            },
+        )
+        .expect("AES-GCM encryption never fails for values of a valid length");
+    let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(&nonce);
+    out.extend(ciphertext);
+    out
+}
+
+fn decrypt(aead: &Aes256Gcm, key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < NONCE_LEN {
+        return Err(Error::decryption(format!(
+            "the value is too short: len={}",
+            data.len()
+        )));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    aead.decrypt(
+        Nonce::from_slice(nonce),
+        Payload {
+            msg: ciphertext,
+            aad: key,
+        },
+    )
+    .map_err(|_| Error::decryption("the value is corrupted or the secret is wrong".to_string()))
+}
+
+/// EncryptedKVStore encrypts the values written to the inner store and decrypts the values read from it
+///
+/// `KVStore` cannot return an error, so a value that fails to decrypt is read as None, a value that fails to encrypt
+/// is not written, and the first error is kept until the caller takes it with `check`.
+pub struct EncryptedKVStore<'a> {
+    inner: InnerKVStore<'a>,
+    cipher: &'a StoreCipher,
+    error: RefCell<Option<Error>>,
+}
+
+enum InnerKVStore<'a> {
+    Ref(&'a dyn KVStore),
+    Mut(&'a mut dyn KVStore),
+}
+
+impl<'a> EncryptedKVStore<'a> {
+    pub fn new(inner: &'a mut dyn KVStore, cipher: &'a StoreCipher) -> Self {
+        Self {
+            inner: InnerKVStore::Mut(inner),
+            cipher,
+            error: Default::default(),
+        }
+    }
+
+    /// Create a store that only supports reads, which is enough for `TxAccessor::run_in_tx`
+    pub fn read_only(inner: &'a dyn KVStore, cipher: &'a StoreCipher) -> Self {
+        Self {
+            inner: InnerKVStore::Ref(inner),
+            cipher,
+            error: Default::default(),
+        }
+    }
+
+    /// Returns the value of the key, or an error if the value cannot be decrypted
+    ///
+    /// The values of a client whose owner has not supplied the token cannot be read even if they are missing,
+    /// so that a locked client is not taken for a missing one.
+    pub fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.cipher.aead_of(key)?;
+        let data = match &self.inner {
+            InnerKVStore::Ref(inner) => inner.get(key),
+            InnerKVStore::Mut(inner) => inner.get(key),
+        };
+        data.map(|data| self.cipher.decrypt(key, &data)).transpose()
+    }
+
+    /// Returns the first error of the values that `get` failed to decrypt or `set` failed to encrypt
+    pub fn check(&self) -> Result<()> {
+        match self.error.borrow_mut().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn KVStore {
+        match &mut self.inner {
+            InnerKVStore::Mut(inner) => &mut **inner,
+            InnerKVStore::Ref(_) => unreachable!("the store is read-only"),
+        }
+    }
+}
+
+impl<'a> KVStore for EncryptedKVStore<'a> {
+    /// A value of a client whose owner has not supplied the token is not written, and the error is returned by `check`
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        match self.cipher.encrypt(&key, &value) {
+            Ok(value) => self.inner_mut().set(key, value),
+            Err(e) => {
+                self.error.borrow_mut().get_or_insert(e);
+            }
+        }
+    }
+
+    /// A value that fails to decrypt, including a value of a client whose owner has not supplied the token,
+    /// is read as None, and the error is returned by `check`
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.try_get(key) {
+            Ok(value) => value,
+            Err(e) => {
+                self.error.borrow_mut().get_or_insert(e);
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.inner_mut().remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorDetail;
+    use crate::memory::MemStore;
+
+    const SALT: &[u8] = b"salt";
+
+    #[test]
+    fn test_store_cipher() {
+        let cipher = StoreCipher::from_secret(b"secret", SALT);
+        let data = cipher.encrypt(b"key", b"value").unwrap();
+        assert_ne!(&data[NONCE_LEN..], b"value");
+        assert_eq!(cipher.decrypt(b"key", &data).unwrap(), b"value");
+        // the value is bound to the key
+        assert!(cipher.decrypt(b"other", &data).is_err());
+        // the value cannot be decrypted with another secret
+        assert!(StoreCipher::from_secret(b"wrong", SALT)
+            .decrypt(b"key", &data)
+            .is_err());
+    }
+
+    #[test]
+    fn test_encrypted_kv_store() {
+        let cipher = StoreCipher::from_secret(b"secret", SALT);
+        let mut mem = MemStore::default();
+        {
+            let mut store = EncryptedKVStore::new(&mut mem, &cipher);
+            store.set(b"key".to_vec(), b"value".to_vec());
+            assert_eq!(store.get(b"key"), Some(b"value".to_vec()));
+        }
+        assert_ne!(mem.get(b"key"), Some(b"value".to_vec()));
+
+        assert!(cipher.check(&mut mem, true).is_ok());
+        assert!(cipher.check(&mut mem, false).is_ok());
+        assert!(StoreCipher::from_secret(b"wrong", SALT)
+            .check(&mut mem, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_owner_cipher() {
+        let cipher = StoreCipher::from_secret(b"secret", SALT);
+        let (k0, k1, k2) = (
+            b"clients/a/clientState".as_slice(),
+            b"clients/a/consensusStates/1".as_slice(),
+            b"clients/b/clientState".as_slice(),
+        );
+        let before = cipher.encrypt(k0, b"v0").unwrap();
+        let owner_key = StoreCipher::owner_key(b"token-a", SALT);
+        // the key depends on the salt as well as the token
+        assert_ne!(owner_key, StoreCipher::owner_key(b"token-a", b"other"));
+        cipher.assign_owner("a", owner_key, b"token-a");
+        assert!(cipher.is_unlocked_with("a", b"token-a"));
+        assert!(!cipher.is_unlocked_with("a", b"token-b"));
+        assert!(!cipher.is_unlocked_with("b", b"token-a"));
+        assert!(cipher.has_owner("a"));
+        assert!(!cipher.has_owner("b"));
+        // the value that was written before the assignment must have been re-encrypted
+        assert!(cipher.decrypt(k0, &before).is_err());
+
+        let data = cipher.encrypt(k1, b"v1").unwrap();
+        assert_eq!(cipher.decrypt(k1, &data).unwrap(), b"v1");
+        // only the key derived from the token of the owner decrypts the value
+        let owner = StoreCipher::new(owner_key);
+        assert_eq!(owner.decrypt(k1, &data).unwrap(), b"v1");
+        assert!(StoreCipher::new(StoreCipher::owner_key(b"token-b", SALT))
+            .decrypt(k1, &data)
+            .is_err());
+        assert!(StoreCipher::from_secret(b"secret", SALT)
+            .decrypt(k1, &data)
+            .is_err());
+
+        // the other clients are still encrypted with the key of the store
+        let data = cipher.encrypt(k2, b"v2").unwrap();
+        assert!(owner.decrypt(k2, &data).is_err());
+        assert_eq!(
+            StoreCipher::from_secret(b"secret", SALT)
+                .decrypt(k2, &data)
+                .unwrap(),
+            b"v2"
+        );
+
+        assert_eq!(client_id_of(k1), Some("a"));
+        assert_eq!(client_id_of(b"clients/a"), None);
+        assert_eq!(client_id_of(CIPHER_CHECK_KEY), None);
+    }
+
+    #[test]
+    fn test_locked_owner() {
+        let cipher = StoreCipher::from_secret(b"secret", SALT);
+        let key = b"clients/a/clientState".as_slice();
+        let owner_key = StoreCipher::owner_key(b"token-a", SALT);
+        let data = StoreCipher::new(owner_key).encrypt(key, b"v0").unwrap();
+
+        // the store knows the assignment but not the token
+        cipher.lock_owner("a");
+        assert!(cipher.has_owner("a"));
+        assert!(cipher.is_locked("a"));
+        let is_locked =
+            |e: Error| matches!(e.detail(), ErrorDetail::OwnerLocked(e) if e.client_id == "a");
+        assert!(is_locked(cipher.decrypt(key, &data).unwrap_err()));
+        assert!(is_locked(cipher.encrypt(key, b"v1").unwrap_err()));
+        let mut mem = MemStore::default();
+        mem.set(key.to_vec(), data.clone());
+        {
+            let mut store = EncryptedKVStore::new(&mut mem, &cipher);
+            // a locked client is not taken for a missing one
+            assert!(is_locked(store.try_get(key).unwrap_err()));
+            assert!(is_locked(
+                store.try_get(b"clients/a/consensusStates/1").unwrap_err()
+            ));
+            assert_eq!(store.get(key), None);
+            assert!(is_locked(store.check().unwrap_err()));
+            store.set(key.to_vec(), b"v1".to_vec());
+            assert!(is_locked(store.check().unwrap_err()));
+        }
+        assert_eq!(mem.get(key), Some(data.clone()));
+
+        // the owner supplies the token
+        cipher.assign_owner("a", owner_key, b"token-a");
+        assert!(!cipher.is_locked("a"));
+        assert_eq!(cipher.decrypt(key, &data).unwrap(), b"v0");
+        // the assignment is not locked again
+        cipher.lock_owner("a");
+        assert!(!cipher.is_locked("a"));
+    }
+
+    #[test]
+    fn test_store_key() {
+        let data = StoreKey::Secret(b"secret".to_vec())
+            .cipher(SALT)
+            .encrypt(b"key", b"value")
+            .unwrap();
+        // the key depends on the salt of the store as well as the secret
+        assert!(StoreKey::Secret(b"secret".to_vec())
+            .cipher(b"other")
+            .decrypt(b"key", &data)
+            .is_err());
+        assert_eq!(
+            StoreCipher::from_secret(b"secret", SALT)
+                .decrypt(b"key", &data)
+                .unwrap(),
+            b"value"
+        );
+
+        // the unsealed key is used as-is
+        let data = StoreKey::Sealed([1; 32])
+            .cipher(SALT)
+            .encrypt(b"key", b"value")
+            .unwrap();
+        assert_eq!(
+            StoreCipher::new([1; 32]).decrypt(b"key", &data).unwrap(),
+            b"value"
+        );
+        assert_ne!(new_kdf_salt(), new_kdf_salt());
+    }
+
+    #[test]
+    fn test_corrupted_value() {
+        let cipher = StoreCipher::from_secret(b"secret", SALT);
+        let mut mem = MemStore::default();
+        mem.set(b"key".to_vec(), vec![0; NONCE_LEN + 16]);
+        let store = EncryptedKVStore::read_only(&mem, &cipher);
+        assert!(store.try_get(b"key").is_err());
+        assert!(store.check().is_ok());
+        // the value is read as None instead of a panic, and the error is kept for the caller
+        assert_eq!(store.get(b"key"), None);
+        assert!(store.check().is_err());
+        assert!(store.check().is_ok());
+        assert_eq!(store.try_get(b"missing").unwrap(), None);
+    }
+}
@@ -29,6 +29,27 @@ define_error! {
         |e| { format_args!("The tx doesn't support an operation {}", e.descr) },
 
         InvalidUpdateKeyLength { length: usize }
-        |e| { format_args!("Invalid UpdateKey length: {}", e.length) }
+        |e| { format_args!("Invalid UpdateKey length: {}", e.length) },
+
+        Decryption { descr: String }
+        |e| { format_args!("Decryption error: {}", e.descr) },
+
+        OwnerLocked { client_id: String }
+        |e| { format_args!("the owner of the client has not supplied the token: client_id={}", e.client_id) },
+
+        InvalidSyncPolicy { descr: String }
+        |e| { format_args!("Invalid sync policy: {}", e.descr) },
+
+        IterateDb { descr: String }
+        |e| { format_args!("Iterate DB error: {}", e.descr) },
+
+        SyncDb { descr: String }
+        |e| { format_args!("Sync DB error: {}", e.descr) },
+
+        CompactDb { descr: String }
+        |e| { format_args!("Compact DB error: {}", e.descr) },
+
+        DbProperty { descr: String }
+        |e| { format_args!("DB property error: {}", e.descr) }
     }
 }
@@ -1,8 +1,34 @@
+use crate::diff::{SnapshotStore, StoreSnapshot};
 use crate::memory::MemStore;
 use crate::prelude::*;
 use crate::transaction::{CommitStore, TxAccessor};
 use crate::{KVStore, Result, TxId};
 
+/// `StoreStats` are the statistics of the disk usage of a persistent store
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Estimated size in bytes of the live data
+    pub live_data_size: u64,
+    /// Total size in bytes of the SST files, including the obsolete entries that have not been compacted yet
+    pub total_sst_files_size: u64,
+    /// Estimated bytes that the compactions have to rewrite to settle the LSM tree
+    pub pending_compaction_bytes: u64,
+    pub running_compactions: u64,
+}
+
+impl StoreStats {
+    /// `space_amplification` returns the ratio of the size on disk to the size of the live data
+    ///
+    /// Returns 1.0 for an empty store.
+    pub fn space_amplification(&self) -> f64 {
+        if self.live_data_size == 0 {
+            1.0
+        } else {
+            self.total_sst_files_size as f64 / self.live_data_size as f64
+        }
+    }
+}
+
 /// `HostStore` defines store implementations on host
 pub enum HostStore {
     #[cfg(feature = "rocksdbstore")]
@@ -10,6 +36,47 @@ pub enum HostStore {
     Memory(crate::memory::MemStore),
 }
 
+impl HostStore {
+    /// `sync` syncs the committed writes to the disk, which is a no-op for the memory store
+    pub fn sync(&mut self) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdbstore")]
+            HostStore::RocksDB(store) => store.sync(),
+            HostStore::Memory(_) => Ok(()),
+        }
+    }
+
+    /// `stats` returns the statistics of the disk usage, which are only available for the RocksDB store
+    pub fn stats(&self) -> Result<Option<StoreStats>> {
+        match self {
+            #[cfg(feature = "rocksdbstore")]
+            HostStore::RocksDB(store) => store.stats().map(Some),
+            HostStore::Memory(_) => Ok(None),
+        }
+    }
+
+    /// `compact` compacts the whole key space to reclaim the space of the removed entries, which is a no-op for the memory store
+    pub fn compact(&mut self) -> Result<()> {
+        match self {
+            #[cfg(feature = "rocksdbstore")]
+            HostStore::RocksDB(store) => store.compact(),
+            HostStore::Memory(_) => Ok(()),
+        }
+    }
+
+    /// `assign_owner` encrypts the values of the client with the key derived from the token of the owner,
+    /// which requires an encrypted RocksDB store
+    pub fn assign_owner(&mut self, client_id: &str, owner: &str, token: &[u8]) -> Result<usize> {
+        match self {
+            #[cfg(feature = "rocksdbstore")]
+            HostStore::RocksDB(store) => store.assign_owner(client_id, owner, token),
+            HostStore::Memory(_) => Err(crate::Error::not_supported_operation(
+                "the memory store is not encrypted".into(),
+            )),
+        }
+    }
+}
+
 /// `IntoCommitStore` converts self into CommitStore
 pub trait IntoCommitStore<S: CommitStore> {
     /// `apply` applies `f` to CommitStore
@@ -35,6 +102,16 @@ impl IntoCommitStore<MemStore> for HostStore {
     }
 }
 
+impl SnapshotStore for HostStore {
+    fn snapshot(&self) -> Result<StoreSnapshot> {
+        match self {
+            #[cfg(feature = "rocksdbstore")]
+            HostStore::RocksDB(store) => store.snapshot(),
+            HostStore::Memory(store) => store.snapshot(),
+        }
+    }
+}
+
 impl TxAccessor for HostStore {
     fn run_in_tx<T>(&self, tx_id: TxId, f: impl FnOnce(&dyn KVStore) -> T) -> Result<T> {
         match self {
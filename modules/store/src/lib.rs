@@ -23,6 +23,9 @@ pub use crate::errors::{Error, Result};
 pub use crate::store::{KVStore, TxId};
 
 pub mod cache;
+pub mod diff;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 mod errors;
 #[cfg(feature = "std")]
 pub mod host;
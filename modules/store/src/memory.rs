@@ -1,3 +1,4 @@
+use crate::diff::{SnapshotStore, StoreSnapshot};
 use crate::prelude::*;
 use crate::store::TxId;
 use crate::transaction::{CommitStore, CreatedTx, Tx, TxAccessor};
@@ -37,6 +38,19 @@ impl TxAccessor for MemStore {
     }
 }
 
+impl SnapshotStore for MemStore {
+    fn snapshot(&self) -> Result<StoreSnapshot> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .committed_data
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
 impl CommitStore for MemStore {
     type Tx = MemTx;
 
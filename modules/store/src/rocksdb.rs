@@ -1,16 +1,90 @@
+use crate::diff::{SnapshotStore, StoreSnapshot};
+use crate::encryption::{
+    new_kdf_salt, EncryptedKVStore, StoreCipher, StoreKey, CIPHER_CHECK_KEY, CIPHER_CHECK_VALUE,
+};
+use crate::host::StoreStats;
 use crate::transaction::{CommitStore, CreatedTx, Tx, TxAccessor, UpdateKey};
 use crate::{Error, KVStore, Result, TxId};
 use core::marker::PhantomData;
 use log::*;
 use ouroboros::self_referencing;
 use rocksdb::{
-    Error as RocksDBError, SnapshotWithThreadMode, Transaction, TransactionDB, TransactionOptions,
-    WriteOptions, DB,
+    Direction, Error as RocksDBError, IteratorMode, SnapshotWithThreadMode, Transaction,
+    TransactionDB, TransactionOptions, WriteBatchWithTransaction, WriteOptions, DB,
 };
 use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
+use std::str::FromStr;
 use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The prefix of the entries that the store writes for itself, which are not a part of the snapshot
+pub const RESERVED_KEY_PREFIX: &[u8] = b"\x00lcp/store/";
+
+/// The key of the entry that `RocksDBStore::sync` writes to sync the WAL to the disk
+pub const SYNC_MARKER_KEY: &[u8] = b"\x00lcp/store/sync-marker";
+
+/// The key of the entry that keeps the salt of the key derivation of an encrypted store in plaintext
+pub const KDF_SALT_KEY: &[u8] = b"\x00lcp/store/kdf-salt";
+
+/// The prefix of the entries that keep the owner of each client whose values are encrypted for the owner
+pub const OWNER_KEY_PREFIX: &[u8] = b"\x00lcp/store/owners/";
+
+/// The prefix of the entries that are encrypted with the key of the owner of each client to check the token of the owner
+pub const OWNER_CHECK_KEY_PREFIX: &[u8] = b"\x00lcp/store/owner-checks/";
+
+/// The prefix of the entries that keep the salt of the key derivation of the owner of each client in plaintext
+pub const OWNER_SALT_KEY_PREFIX: &[u8] = b"\x00lcp/store/owner-salts/";
+
+/// SyncPolicy decides when the writes to the DB are synced to the disk
+///
+/// All the writes of an update transaction are committed as one atomic batch regardless of the policy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Every write is synced, including the writes outside transactions
+    Always,
+    /// Every commit of an update transaction is synced
+    #[default]
+    OnCommit,
+    /// A commit is synced only if the interval has passed since the last synced commit,
+    /// so the commits in between are synced together with the next one
+    Interval(Duration),
+}
+
+impl SyncPolicy {
+    fn sync_on_write(&self) -> bool {
+        matches!(self, Self::Always)
+    }
+
+    fn sync_on_commit(&self, last_synced_at: Option<Instant>) -> bool {
+        match self {
+            Self::Always | Self::OnCommit => true,
+            Self::Interval(interval) => {
+                last_synced_at.map_or(true, |synced_at| synced_at.elapsed() >= *interval)
+            }
+        }
+    }
+}
+
+impl FromStr for SyncPolicy {
+    type Err = Error;
+
+    /// Parse `always`, `on-commit` or `interval:<seconds>`
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "always" => Ok(Self::Always),
+            "on-commit" => Ok(Self::OnCommit),
+            _ => match s.strip_prefix("interval:").map(u64::from_str) {
+                Some(Ok(secs)) if secs > 0 => Ok(Self::Interval(Duration::from_secs(secs))),
+                _ => Err(Error::invalid_sync_policy(format!(
+                    "expected `always`, `on-commit` or `interval:<seconds>`: {}",
+                    s
+                ))),
+            },
+        }
+    }
+}
 
 /// `RocksDBStore` is a store implementation with RocksDB
 #[self_referencing]
@@ -21,6 +95,10 @@ pub struct RocksDBStore {
     #[covariant]
     txs: HashMap<TxId, StoreTransaction<'this>>,
     mutex: HashMap<UpdateKey, Rc<Mutex<()>>>,
+    /// If set, the values are encrypted at rest
+    cipher: Option<StoreCipher>,
+    sync_policy: SyncPolicy,
+    last_synced_at: Option<Instant>,
 }
 
 unsafe impl Send for RocksDBStore {}
@@ -28,15 +106,34 @@ unsafe impl Sync for RocksDBStore {}
 
 impl RocksDBStore {
     pub fn create(db: TransactionDB) -> Self {
+        Self::build(InnerDB::TransactionDB(db), None)
+    }
+
+    fn build(db: InnerDB, cipher: Option<StoreCipher>) -> Self {
         RocksDBStoreBuilder {
-            db: InnerDB::TransactionDB(db),
+            db,
             latest_tx_id: Default::default(),
             txs_builder: |_| Default::default(),
             mutex: Default::default(),
+            cipher,
+            sync_policy: Default::default(),
+            last_synced_at: None,
         }
         .build()
     }
 
+    /// Set the policy to sync the writes to the disk
+    pub fn with_sync(mut self, sync_policy: SyncPolicy) -> Self {
+        self.with_sync_policy_mut(|p| *p = sync_policy);
+        self
+    }
+
+    fn write_options(&self) -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(self.borrow_sync_policy().sync_on_write());
+        opts
+    }
+
     pub fn open<P: AsRef<Path>>(path: P) -> Self {
         if !path.as_ref().exists() {
             info!("initialize a database: {:?}", path.as_ref());
@@ -50,13 +147,228 @@ impl RocksDBStore {
             drop(store);
         }
         let db = DB::open_for_read_only(&Default::default(), path, false).unwrap();
-        RocksDBStoreBuilder {
-            db: InnerDB::ReadOnlyDB(db),
-            latest_tx_id: Default::default(),
-            txs_builder: |_| Default::default(),
-            mutex: Default::default(),
+        Self::build(InnerDB::ReadOnlyDB(db), None)
+    }
+
+    /// Open a database whose values are encrypted with a key from `key`
+    ///
+    /// Returns an error if the database was encrypted with another key.
+    /// A database that was written without encryption cannot be opened with this.
+    pub fn open_encrypted<P: AsRef<Path>>(path: P, key: StoreKey) -> Result<Self> {
+        if !path.as_ref().exists() {
+            info!("initialize an encrypted database: {:?}", path.as_ref());
         }
-        .build()
+        let db = TransactionDB::open_default(path).unwrap();
+        let salt = match db.get(KDF_SALT_KEY).unwrap() {
+            Some(salt) => salt,
+            // a salt generated now cannot derive the key of the values that have been written
+            None if db.get(CIPHER_CHECK_KEY).unwrap().is_some() => {
+                return Err(Error::decryption(
+                    "the salt of the key derivation is missing".to_string(),
+                ))
+            }
+            None => {
+                let salt = new_kdf_salt().to_vec();
+                db.put(KDF_SALT_KEY, &salt).unwrap();
+                salt
+            }
+        };
+        let cipher = key.cipher(&salt);
+        let store = Self::build(InnerDB::TransactionDB(db), Some(cipher.clone()));
+        cipher.check(&mut store.borrow_db(), true)?;
+        store.load_owners(&cipher)?;
+        Ok(store)
+    }
+
+    /// Open a database whose values are encrypted with a key from `key` in read-only mode
+    pub fn open_read_only_encrypted<P: AsRef<Path>>(path: P, key: StoreKey) -> Result<Self> {
+        if !path.as_ref().exists() {
+            let store = Self::open_encrypted(path.as_ref(), key.clone())?;
+            drop(store);
+        }
+        let db = DB::open_for_read_only(&Default::default(), path, false).unwrap();
+        let salt = db.get(KDF_SALT_KEY).unwrap().ok_or_else(|| {
+            Error::decryption("the salt of the key derivation is missing".to_string())
+        })?;
+        let cipher = key.cipher(&salt);
+        let store = Self::build(InnerDB::ReadOnlyDB(db), Some(cipher.clone()));
+        cipher.check(&mut store.borrow_db(), false)?;
+        store.load_owners(&cipher)?;
+        Ok(store)
+    }
+
+    /// Returns the value of the key, or an error if the value cannot be decrypted
+    pub fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self.borrow_cipher() {
+            Some(cipher) => EncryptedKVStore::read_only(&self.borrow_db(), cipher).try_get(key),
+            None => Ok(self.borrow_db().get(key)),
+        }
+    }
+
+    /// Assign the client to the owner, and encrypt its values with the key derived from the token of the owner
+    ///
+    /// The values that have been written are re-encrypted in the same batch as the assignment is persisted.
+    /// The key is derived from the token with a random salt of the client. The token is never persisted,
+    /// so the values of the client cannot be read after the store is reopened until this is called again with the token,
+    /// which only checks it against the assignment.
+    /// A client cannot be re-assigned to another owner. Returns the number of the re-encrypted values.
+    pub fn assign_owner(&mut self, client_id: &str, owner: &str, token: &[u8]) -> Result<usize> {
+        let cipher = match self.borrow_cipher() {
+            Some(cipher) => cipher.clone(),
+            None => {
+                return Err(Error::not_supported_operation(
+                    "assigning an owner requires an encrypted store".into(),
+                ))
+            }
+        };
+        let owner_entry = [OWNER_KEY_PREFIX, client_id.as_bytes()].concat();
+        let check_entry = [OWNER_CHECK_KEY_PREFIX, client_id.as_bytes()].concat();
+        let salt_entry = [OWNER_SALT_KEY_PREFIX, client_id.as_bytes()].concat();
+        match self.try_get(&owner_entry)? {
+            // the token has been checked since the store was opened
+            Some(current)
+                if current == owner.as_bytes() && cipher.is_unlocked_with(client_id, token) =>
+            {
+                return Ok(0);
+            }
+            Some(current) if current == owner.as_bytes() => {
+                let salt = self.borrow_db().get(&salt_entry).ok_or_else(|| {
+                    Error::decryption(format!(
+                        "the salt of the owner is missing: client_id={}",
+                        client_id
+                    ))
+                })?;
+                let owner_key = StoreCipher::owner_key(token, &salt);
+                let owner_cipher = StoreCipher::new(owner_key);
+                let check = self.borrow_db().get(&check_entry).ok_or_else(|| {
+                    Error::decryption(format!(
+                        "the check entry of the owner is missing: client_id={}",
+                        client_id
+                    ))
+                })?;
+                if owner_cipher.decrypt(&check_entry, &check)? != CIPHER_CHECK_VALUE {
+                    return Err(Error::decryption(format!(
+                        "unexpected value of the check entry of the owner: client_id={}",
+                        client_id
+                    )));
+                }
+                cipher.assign_owner(client_id, owner_key, token);
+                return Ok(0);
+            }
+            Some(_) => {
+                return Err(Error::not_supported_operation(format!(
+                    "the client is assigned to another owner: client_id={}",
+                    client_id
+                )))
+            }
+            None => {}
+        }
+        let db = match self.borrow_db() {
+            InnerDB::TransactionDB(db) => db,
+            InnerDB::ReadOnlyDB(_) => {
+                return Err(Error::not_supported_operation(
+                    "assigning an owner requires a writable store".into(),
+                ))
+            }
+        };
+        let salt = new_kdf_salt();
+        let owner_key = StoreCipher::owner_key(token, &salt);
+        let owner_cipher = StoreCipher::new(owner_key);
+        let prefix = format!("clients/{}/", client_id).into_bytes();
+        let mut batch = WriteBatchWithTransaction::<true>::default();
+        let mut count = 0;
+        for (key, value) in self.borrow_db().entries_with_prefix(&prefix)? {
+            let value = cipher.decrypt(&key, &value)?;
+            batch.put(&key, owner_cipher.encrypt(&key, &value)?);
+            count += 1;
+        }
+        batch.put(
+            &owner_entry,
+            cipher.encrypt(&owner_entry, owner.as_bytes())?,
+        );
+        batch.put(
+            &check_entry,
+            owner_cipher.encrypt(&check_entry, CIPHER_CHECK_VALUE)?,
+        );
+        batch.put(&salt_entry, salt);
+        db.write_opt(batch, &self.write_options())
+            .map_err(|e| Error::commit_tx(e.into_string()))?;
+        cipher.assign_owner(client_id, owner_key, token);
+        info!(
+            "assigned the client to the owner: client_id={} values={}",
+            client_id, count
+        );
+        Ok(count)
+    }
+
+    /// Lock the values of the assigned clients until their owners supply the tokens
+    fn load_owners(&self, cipher: &StoreCipher) -> Result<()> {
+        for (key, _) in self.borrow_db().entries_with_prefix(OWNER_KEY_PREFIX)? {
+            let client_id = core::str::from_utf8(&key[OWNER_KEY_PREFIX.len()..])
+                .map_err(|_| Error::decryption(format!("invalid owner entry: key={:?}", key)))?;
+            cipher.lock_owner(client_id);
+        }
+        Ok(())
+    }
+
+    /// Sync the committed writes to the disk regardless of the sync policy
+    ///
+    /// A synced write flushes the WAL including the preceding writes that were not synced.
+    /// The transactions in progress are not affected, and their writes are discarded if the process exits before they are committed.
+    pub fn sync(&mut self) -> Result<()> {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(true);
+        self.with_mut(|fields| {
+            if !fields.txs.is_empty() {
+                warn!(
+                    "sync the store with transactions in progress: txs={}",
+                    fields.txs.len()
+                );
+            }
+            match fields.db {
+                InnerDB::TransactionDB(db) => db
+                    .put_opt(SYNC_MARKER_KEY, [], &opts)
+                    .map_err(|e| Error::sync_db(e.to_string()))?,
+                // nothing is written through a read-only DB
+                InnerDB::ReadOnlyDB(_) => return Ok(()),
+            }
+            *fields.last_synced_at = Some(Instant::now());
+            Ok(())
+        })
+    }
+
+    /// Returns the statistics of the disk usage reported by RocksDB
+    pub fn stats(&self) -> Result<StoreStats> {
+        let db = self.borrow_db();
+        Ok(StoreStats {
+            live_data_size: db.property_int_value("rocksdb.estimate-live-data-size")?,
+            total_sst_files_size: db.property_int_value("rocksdb.total-sst-files-size")?,
+            pending_compaction_bytes: db
+                .property_int_value("rocksdb.estimate-pending-compaction-bytes")?,
+            running_compactions: db.property_int_value("rocksdb.num-running-compactions")?,
+        })
+    }
+
+    /// Compact the whole key space, which drops the removed entries from the SST files
+    ///
+    /// The compaction runs in the calling thread and may take a while on a large store,
+    /// so it should be triggered after a large prune rather than periodically.
+    pub fn compact(&mut self) -> Result<()> {
+        let before = self.stats()?;
+        match self.borrow_db() {
+            InnerDB::TransactionDB(db) => db.compact_range::<&[u8], &[u8]>(None, None),
+            InnerDB::ReadOnlyDB(_) => {
+                return Err(Error::not_supported_operation(
+                    "a read-only store cannot be compacted".into(),
+                ))
+            }
+        }
+        let after = self.stats()?;
+        info!(
+            "compacted the store: total_sst_files_size={}->{} live_data_size={}",
+            before.total_sst_files_size, after.total_sst_files_size, after.live_data_size
+        );
+        Ok(())
     }
 
     pub fn finalize_tx<T>(
@@ -84,26 +396,82 @@ impl RocksDBStore {
 }
 
 impl KVStore for RocksDBStore {
+    /// A value that cannot be encrypted is not written, e.g. a value of a client whose owner has not supplied the token
     fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
-        self.borrow_db().set(key, value).unwrap()
+        let mut db = WritableDB {
+            db: self.borrow_db(),
+            opts: self.write_options(),
+        };
+        match self.borrow_cipher() {
+            Some(cipher) => {
+                let mut store = EncryptedKVStore::new(&mut db, cipher);
+                store.set(key.clone(), value);
+                if let Err(e) = store.check() {
+                    error!(
+                        "failed to encrypt a value in the store: key={} err={}",
+                        String::from_utf8_lossy(&key),
+                        e
+                    );
+                }
+            }
+            None => db.set(key, value),
+        }
     }
 
+    /// Returns None for a value that cannot be decrypted, which `try_get` returns as an error
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.borrow_db().get(key)
+        self.try_get(key).unwrap_or_else(|e| {
+            error!(
+                "failed to decrypt a value in the store: key={} err={}",
+                String::from_utf8_lossy(key),
+                e
+            );
+            None
+        })
     }
 
     fn remove(&mut self, key: &[u8]) {
-        self.borrow_db().remove(key)
+        WritableDB {
+            db: self.borrow_db(),
+            opts: self.write_options(),
+        }
+        .remove(key)
+    }
+}
+
+impl SnapshotStore for RocksDBStore {
+    fn snapshot(&self) -> Result<StoreSnapshot> {
+        let mut snapshot = StoreSnapshot::new();
+        for (key, value) in self.borrow_db().entries()? {
+            if key.starts_with(RESERVED_KEY_PREFIX) {
+                continue;
+            }
+            let value = match self.borrow_cipher() {
+                Some(cipher) => cipher.decrypt(&key, &value)?,
+                None => value,
+            };
+            snapshot.insert(key, value);
+        }
+        Ok(snapshot)
     }
 }
 
 impl TxAccessor for RocksDBStore {
     fn run_in_tx<T>(&self, tx_id: TxId, f: impl FnOnce(&dyn KVStore) -> T) -> Result<T> {
-        self.with_txs(|txs| {
-            let stx = txs
+        self.with(|fields| {
+            let stx = fields
+                .txs
                 .get(&tx_id)
                 .ok_or_else(|| Error::tx_id_not_found(tx_id))?;
-            Ok(f(stx))
+            match fields.cipher {
+                Some(cipher) => {
+                    let store = EncryptedKVStore::read_only(stx, cipher);
+                    let res = f(&store);
+                    store.check()?;
+                    Ok(res)
+                }
+                None => Ok(f(stx)),
+            }
         })
     }
 
@@ -112,11 +480,20 @@ impl TxAccessor for RocksDBStore {
         tx_id: TxId,
         f: impl FnOnce(&mut dyn KVStore) -> T,
     ) -> Result<T> {
-        self.with_txs_mut(|txs| {
-            let stx = txs
+        self.with_mut(|fields| {
+            let stx = fields
+                .txs
                 .get_mut(&tx_id)
                 .ok_or_else(|| Error::tx_id_not_found(tx_id))?;
-            Ok(f(stx))
+            match fields.cipher {
+                Some(cipher) => {
+                    let mut store = EncryptedKVStore::new(stx, cipher);
+                    let res = f(&mut store);
+                    store.check()?;
+                    Ok(res)
+                }
+                None => Ok(f(stx)),
+            }
         })
     }
 }
@@ -170,9 +547,14 @@ impl CommitStore for RocksDBStore {
                 }),
                 InnerDB::TransactionDB(db) => {
                     if tx.is_update_tx() {
+                        let mut write_opt = WriteOptions::default();
+                        if fields.sync_policy.sync_on_commit(*fields.last_synced_at) {
+                            write_opt.set_sync(true);
+                            *fields.last_synced_at = Some(Instant::now());
+                        }
                         StoreTransaction::Update(
                             UpdateTransactionBuilder {
-                                tx: db.transaction_opt(&WriteOptions::default(), &tx_opt),
+                                tx: db.transaction_opt(&write_opt, &tx_opt),
                                 snapshot_builder: |tx| tx.snapshot(),
                             }
                             .build(),
@@ -233,6 +615,89 @@ impl InnerDB {
             Self::ReadOnlyDB(db) => db.delete(key).unwrap(),
         }
     }
+
+    /// Returns the integer value of the property, where a property the DB doesn't report is zero
+    pub(crate) fn property_int_value(&self, name: &str) -> Result<u64> {
+        match self {
+            Self::TransactionDB(db) => db.property_int_value(name),
+            Self::ReadOnlyDB(db) => db.property_int_value(name),
+        }
+        .map(|v| v.unwrap_or_default())
+        .map_err(|e| Error::db_property(format!("name={} err={}", name, e)))
+    }
+
+    /// Returns all the committed key-value pairs as stored in the DB
+    pub(crate) fn entries(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let iter: Box<dyn Iterator<Item = _>> = match self {
+            Self::TransactionDB(db) => Box::new(db.iterator(IteratorMode::Start)),
+            Self::ReadOnlyDB(db) => Box::new(db.iterator(IteratorMode::Start)),
+        };
+        iter.map(|kv| {
+            kv.map(|(k, v)| (k.into_vec(), v.into_vec()))
+                .map_err(|e| Error::iterate_db(e.to_string()))
+        })
+        .collect()
+    }
+
+    /// Returns the committed key-value pairs whose keys start with `prefix`
+    pub(crate) fn entries_with_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mode = IteratorMode::From(prefix, Direction::Forward);
+        let iter: Box<dyn Iterator<Item = _>> = match self {
+            Self::TransactionDB(db) => Box::new(db.iterator(mode)),
+            Self::ReadOnlyDB(db) => Box::new(db.iterator(mode)),
+        };
+        let mut entries = Vec::new();
+        for kv in iter {
+            let (k, v) = kv.map_err(|e| Error::iterate_db(e.to_string()))?;
+            if !k.starts_with(prefix) {
+                break;
+            }
+            entries.push((k.into_vec(), v.into_vec()));
+        }
+        Ok(entries)
+    }
+}
+
+impl KVStore for &InnerDB {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        InnerDB::set(self, key, value).unwrap()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        InnerDB::get(self, key)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        InnerDB::remove(self, key)
+    }
+}
+
+/// WritableDB writes to the DB with the options of the sync policy
+struct WritableDB<'a> {
+    db: &'a InnerDB,
+    opts: WriteOptions,
+}
+
+impl<'a> KVStore for WritableDB<'a> {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        match self.db {
+            InnerDB::TransactionDB(db) => db.put_opt(key, value, &self.opts),
+            InnerDB::ReadOnlyDB(db) => db.put_opt(key, value, &self.opts),
+        }
+        .unwrap()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.db.get(key)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        match self.db {
+            InnerDB::TransactionDB(db) => db.delete_opt(key, &self.opts),
+            InnerDB::ReadOnlyDB(db) => db.delete_opt(key, &self.opts),
+        }
+        .unwrap()
+    }
 }
 
 /// StoreTransaction implements multiple transaction types
@@ -460,6 +925,8 @@ impl<T> RocksDBTx<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diff::{Change, StoreDiff};
+    use crate::errors::ErrorDetail;
     use alloc::sync::Arc;
     use core::time::Duration;
     use std::{
@@ -554,6 +1021,131 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encrypted_store() {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        {
+            let mut store = RocksDBStore::open_encrypted(
+                tmp_dir.as_ref(),
+                StoreKey::Secret(b"secret".to_vec()),
+            )
+            .unwrap();
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_set(tx.get_id(), key(0), value(0)).unwrap();
+            store.commit(tx).unwrap();
+        }
+        {
+            // the value is not stored in plaintext
+            let store = RocksDBStore::open(tmp_dir.as_ref());
+            let raw = store.get(&key(0)).unwrap();
+            assert_ne!(raw, value(0));
+        }
+        assert!(RocksDBStore::open_encrypted(
+            tmp_dir.as_ref(),
+            StoreKey::Secret(b"wrong".to_vec())
+        )
+        .is_err());
+        let mut store =
+            RocksDBStore::open_encrypted(tmp_dir.as_ref(), StoreKey::Secret(b"secret".to_vec()))
+                .unwrap();
+        let tx = store.create_transaction(None).unwrap();
+        let tx = tx.prepare().unwrap();
+        store.begin(&tx).unwrap();
+        assert_eq!(store.tx_get(tx.get_id(), &key(0)).unwrap(), Some(value(0)));
+        store.commit(tx).unwrap();
+        drop(store);
+
+        {
+            // corrupt the value on the disk
+            let mut store = RocksDBStore::open(tmp_dir.as_ref());
+            let mut raw = store.get(&key(0)).unwrap();
+            let last = raw.len() - 1;
+            raw[last] ^= 1;
+            store.set(key(0), raw);
+        }
+        let mut store =
+            RocksDBStore::open_encrypted(tmp_dir.as_ref(), StoreKey::Secret(b"secret".to_vec()))
+                .unwrap();
+        assert!(store.try_get(&key(0)).is_err());
+        assert_eq!(store.get(&key(0)), None);
+        let tx = store.create_transaction(None).unwrap();
+        let tx = tx.prepare().unwrap();
+        store.begin(&tx).unwrap();
+        assert!(store.tx_get(tx.get_id(), &key(0)).is_err());
+        store.commit(tx).unwrap();
+        drop(store);
+
+        {
+            // the key cannot be derived without the salt
+            let mut store = RocksDBStore::open(tmp_dir.as_ref());
+            store.remove(KDF_SALT_KEY);
+        }
+        assert!(RocksDBStore::open_encrypted(
+            tmp_dir.as_ref(),
+            StoreKey::Secret(b"secret".to_vec())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_assign_owner() {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        let client_key = |id: &str| format!("clients/{}/clientState", id).into_bytes();
+        {
+            let mut store = RocksDBStore::open_encrypted(
+                tmp_dir.as_ref(),
+                StoreKey::Secret(b"secret".to_vec()),
+            )
+            .unwrap();
+            store.set(client_key("a"), value(0));
+            store.set(client_key("b"), value(1));
+            assert_eq!(store.assign_owner("a", "owner-a", b"token-a").unwrap(), 1);
+            assert_eq!(store.assign_owner("a", "owner-a", b"token-a").unwrap(), 0);
+            assert!(store.assign_owner("a", "owner-a", b"token-b").is_err());
+            assert!(store.assign_owner("a", "owner-b", b"token-b").is_err());
+            assert_eq!(store.get(&client_key("a")), Some(value(0)));
+            // the assignment is not a part of the snapshot
+            assert_eq!(store.snapshot().unwrap().len(), 2);
+        }
+        {
+            // the value is re-encrypted with the key derived from the token
+            let store = RocksDBStore::open(tmp_dir.as_ref());
+            let raw = store.get(&client_key("a")).unwrap();
+            let owner_salt = store.get(&[OWNER_SALT_KEY_PREFIX, b"a"].concat()).unwrap();
+            let owner = StoreCipher::new(StoreCipher::owner_key(b"token-a", &owner_salt));
+            assert_eq!(owner.decrypt(&client_key("a"), &raw).unwrap(), value(0));
+            let salt = store.get(KDF_SALT_KEY).unwrap();
+            assert!(StoreCipher::from_secret(b"secret", &salt)
+                .decrypt(&client_key("a"), &raw)
+                .is_err());
+        }
+        // the client is locked until the owner supplies the token again
+        let mut store = RocksDBStore::open_read_only_encrypted(
+            tmp_dir.as_ref(),
+            StoreKey::Secret(b"secret".to_vec()),
+        )
+        .unwrap();
+        assert_eq!(store.get(&client_key("a")), None);
+        assert!(matches!(
+            store.try_get(&client_key("a")).unwrap_err().detail(),
+            ErrorDetail::OwnerLocked(_)
+        ));
+        assert_eq!(store.get(&client_key("b")), Some(value(1)));
+        assert!(store.assign_owner("a", "owner-a", b"token-b").is_err());
+        assert_eq!(store.get(&client_key("a")), None);
+        assert_eq!(store.assign_owner("a", "owner-a", b"token-a").unwrap(), 0);
+        assert_eq!(store.get(&client_key("a")), Some(value(0)));
+        // a new assignment requires a writable store
+        assert!(store.assign_owner("b", "owner-b", b"token-b").is_err());
+        assert!(RocksDBStore::open(tmp_dir.as_ref())
+            .assign_owner("b", "owner-b", b"token-b")
+            .is_err());
+    }
+
     #[test]
     fn test_concurrent_write_tx_with_same_update_key_1() {
         let (_tmp_dir, store, [r1, r2]) = get_test_helpers::<2>(vec![]);
@@ -756,6 +1348,131 @@ mod tests {
             .eq(&Some(value(0))));
     }
 
+    #[test]
+    fn test_sync_policy() {
+        assert_eq!("always".parse::<SyncPolicy>().unwrap(), SyncPolicy::Always);
+        assert_eq!(
+            "on-commit".parse::<SyncPolicy>().unwrap(),
+            SyncPolicy::OnCommit
+        );
+        assert_eq!(
+            "interval:5".parse::<SyncPolicy>().unwrap(),
+            SyncPolicy::Interval(Duration::from_secs(5))
+        );
+        assert!("interval:0".parse::<SyncPolicy>().is_err());
+        assert!("never".parse::<SyncPolicy>().is_err());
+
+        let interval = SyncPolicy::Interval(Duration::from_secs(60));
+        assert!(interval.sync_on_commit(None));
+        assert!(!interval.sync_on_commit(Some(Instant::now())));
+
+        for policy in [SyncPolicy::Always, interval] {
+            let tmp_dir = TempDir::new().unwrap();
+            let mut store = RocksDBStore::open(tmp_dir.as_ref()).with_sync(policy);
+            store.set(key(0), value(0));
+            for i in 1..3 {
+                let tx = store
+                    .create_transaction(Some("test".into()))
+                    .unwrap()
+                    .prepare()
+                    .unwrap();
+                store.begin(&tx).unwrap();
+                store.tx_set(tx.get_id(), key(i), value(i)).unwrap();
+                store.commit(tx).unwrap();
+            }
+            for i in 0..3 {
+                assert_eq!(store.get(&key(i)), Some(value(i)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sync() {
+        let tmp_dir = TempDir::new().unwrap();
+        {
+            let mut store = RocksDBStore::open(tmp_dir.as_ref());
+            store.set(key(0), value(0));
+            let tx = store
+                .create_transaction(Some("test".into()))
+                .unwrap()
+                .prepare()
+                .unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_set(tx.get_id(), key(1), value(1)).unwrap();
+            store.sync().unwrap();
+            assert!(store.borrow_last_synced_at().is_some());
+            // the marker entry is not a part of the snapshot
+            assert_eq!(store.snapshot().unwrap().len(), 1);
+        }
+        // the transaction that was not committed is discarded
+        let store = RocksDBStore::open(tmp_dir.as_ref());
+        assert_eq!(store.get(&key(0)), Some(value(0)));
+        assert_eq!(store.get(&key(1)), None);
+    }
+
+    #[test]
+    fn test_compact() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store = RocksDBStore::open(tmp_dir.as_ref());
+        for i in 0..1000 {
+            store.set(key(i), value(i));
+        }
+        store.compact().unwrap();
+        let before = store.stats().unwrap();
+        assert!(before.total_sst_files_size > 0);
+
+        for i in 0..1000 {
+            store.remove(&key(i));
+        }
+        store.compact().unwrap();
+        let after = store.stats().unwrap();
+        assert!(after.total_sst_files_size < before.total_sst_files_size);
+        assert_eq!(after.running_compactions, 0);
+
+        // a read-only store cannot be compacted
+        drop(store);
+        let mut store = RocksDBStore::open_read_only(tmp_dir.as_ref());
+        assert!(store.stats().is_ok());
+        assert!(store.compact().is_err());
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store =
+            RocksDBStore::open_encrypted(tmp_dir.as_ref(), StoreKey::Secret(b"secret".to_vec()))
+                .unwrap();
+        store.set(key(0), value(0));
+        store.set(key(1), value(1));
+        let prev = store.snapshot().unwrap();
+        // the cipher check entry is not a part of the snapshot
+        assert_eq!(prev.len(), 2);
+        assert_eq!(prev.get(&key(0)), Some(&value(0)));
+
+        let tx = store
+            .create_transaction(Some("test".into()))
+            .unwrap()
+            .prepare()
+            .unwrap();
+        store.begin(&tx).unwrap();
+        store.tx_set(tx.get_id(), key(0), value(2)).unwrap();
+        store.tx_remove(tx.get_id(), &key(1)).unwrap();
+        store.tx_set(tx.get_id(), key(3), value(3)).unwrap();
+        store.commit(tx).unwrap();
+
+        let diff = StoreDiff::compute(&prev, &store.snapshot().unwrap());
+        assert_eq!(diff.len(), 3);
+        assert_eq!(
+            diff.get(&key(0)),
+            Some(&Change::Modified {
+                prev: value(0),
+                post: value(2)
+            })
+        );
+        assert_eq!(diff.get(&key(1)), Some(&Change::Removed(value(1))));
+        assert_eq!(diff.get(&key(3)), Some(&Change::Added(value(3))));
+    }
+
     fn key(idx: u64) -> Vec<u8> {
         key_s(idx).into_bytes()
     }
@@ -1,7 +1,12 @@
 use crate::errors::Error;
 use crate::message::{ClientMessage, Header, Misbehaviour};
 use crate::prelude::*;
-use crate::state::{canonicalize_state, gen_state_id, ClientState, ConsensusState};
+use crate::state::{
+    canonicalize_state, decode_proof_specs, gen_state_id, migrate_client_state,
+    migrate_consensus_state, with_params, ClientState, ConsensusState,
+};
+use crate::vote_extension::check_vote_extensions;
+use alloc::collections::BTreeSet;
 use core::str::FromStr;
 use crypto::Keccak256;
 use ibc::clients::ics07_tendermint::client_state::{
@@ -22,17 +27,27 @@ use ibc::core::ics23_commitment::commitment::{
     CommitmentPrefix as IBCCommitmentPrefix, CommitmentProofBytes as IBCCommitmentProofBytes,
     CommitmentRoot,
 };
+use ibc::core::ics23_commitment::error::CommitmentError;
 use ibc::core::ics23_commitment::merkle::{apply_prefix, MerkleProof};
+use ibc::core::ics23_commitment::specs::ProofSpecs;
 use ibc::core::ics24_host::Path;
+use ics23::{
+    calculate_existence_root, commitment_proof::Proof, HostFunctionsManager, NonExistenceProof,
+    ProofSpec,
+};
 use lcp_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
 use light_client::commitments::{
     CommitmentPrefix, EmittedState, MisbehaviourProxyMessage, PrevState, TrustingPeriodContext,
-    UpdateStateProxyMessage, ValidationContext, VerifyMembershipProxyMessage,
+    UpdateStateProxyMessage, ValidationContext, VerifyKeyValueProxyMessage,
+    VerifyMembershipProxyMessage,
+};
+use light_client::types::{
+    Any, Capabilities, ClientId, ClientParams, Height, Time, VerificationMode, ICS23_PROOF_FORMAT,
 };
-use light_client::types::{Any, ClientId, Height, Time};
 use light_client::{
     ibc::IBCContext, CreateClientResult, Error as LightClientError, HostClientReader, LightClient,
-    LightClientRegistry, UpdateClientResult, VerifyMembershipResult,
+    LightClientRegistry, MembershipItem, UpdateClientResult, VerifyKeyValueResult,
+    VerifyMembershipResult,
 };
 use light_client::{MisbehaviourData, UpdateStateData, VerifyNonMembershipResult};
 use log::*;
@@ -45,6 +60,17 @@ impl LightClient for TendermintLightClient {
         client_type().as_str().to_string()
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            misbehaviour: true,
+            upgrade: false,
+            proof_formats: vec![ICS23_PROOF_FORMAT.to_string()],
+            multipart_headers: false,
+            key_value_verification: true,
+            client_params: true,
+        }
+    }
+
     fn latest_height(
         &self,
         ctx: &dyn HostClientReader,
@@ -64,7 +90,7 @@ impl LightClient for TendermintLightClient {
         let consensus_state = ConsensusState::try_from(any_consensus_state)?;
         let _ = client_state
             .initialise(consensus_state.0.clone().into())
-            .map_err(Error::ics02)?;
+            .map_err(Error::from)?;
 
         let canonical_client_state = canonicalize_state(&client_state);
         let height = client_state.latest_height().into();
@@ -78,7 +104,9 @@ impl LightClient for TendermintLightClient {
                 prev_state_id: None,
                 post_height: height,
                 post_state_id: state_id,
+                frozen_height: client_state.frozen_height().map(Into::into),
                 timestamp,
+                expires_at: None,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, any_client_state)],
             }
@@ -87,6 +115,48 @@ impl LightClient for TendermintLightClient {
         })
     }
 
+    fn validate_client_state(&self, any_client_state: Any) -> Result<(), LightClientError> {
+        ClientState::try_from(any_client_state)?;
+        Ok(())
+    }
+
+    fn validate_consensus_state(&self, any_consensus_state: Any) -> Result<(), LightClientError> {
+        ConsensusState::try_from(any_consensus_state)?;
+        Ok(())
+    }
+
+    /// Only the clients of the previous versions of this implementation can be migrated.
+    /// Their states are kept as they are, since the versions share the encoding.
+    fn migrate_client_state(
+        &self,
+        from_type_url: &str,
+        from_version: u32,
+        any_client_state: Any,
+    ) -> Result<Any, LightClientError> {
+        if from_type_url != TENDERMINT_CLIENT_STATE_TYPE_URL {
+            return Err(LightClientError::client_migration_not_supported(
+                self.client_type(),
+                light_client::versioned_client_type(from_type_url, from_version),
+            ));
+        }
+        Ok(migrate_client_state(any_client_state)?)
+    }
+
+    fn migrate_consensus_state(
+        &self,
+        from_type_url: &str,
+        from_version: u32,
+        any_consensus_state: Any,
+    ) -> Result<Any, LightClientError> {
+        if from_type_url != TENDERMINT_CLIENT_STATE_TYPE_URL {
+            return Err(LightClientError::client_migration_not_supported(
+                self.client_type(),
+                light_client::versioned_client_type(from_type_url, from_version),
+            ));
+        }
+        Ok(migrate_consensus_state(any_consensus_state)?)
+    }
+
     fn update_client(
         &self,
         ctx: &dyn HostClientReader,
@@ -95,6 +165,10 @@ impl LightClient for TendermintLightClient {
     ) -> Result<UpdateClientResult, LightClientError> {
         match ClientMessage::try_from(any_client_message)? {
             ClientMessage::Header(h) => Ok(self.update_state(ctx, client_id, h)?.into()),
+            ClientMessage::ExtendedHeader(h) => {
+                check_vote_extensions(ctx.vote_extension_policy(&client_id)?, &h)?;
+                Ok(self.update_state(ctx, client_id, h.header)?.into())
+            }
             ClientMessage::Misbehaviour(m) => {
                 Ok(self.submit_misbehaviour(ctx, client_id, m)?.into())
             }
@@ -115,11 +189,11 @@ impl LightClient for TendermintLightClient {
             Self::validate_args(ctx, client_id.clone(), prefix, path, proof_height, proof)?;
 
         client_state
-            .verify_height(proof_height.try_into().map_err(Error::ics02)?)
-            .map_err(|e| Error::ics02(e.into()))?;
+            .verify_height(proof_height.try_into().map_err(Error::from)?)
+            .map_err(|e| Error::from(ICS02Error::from(e)))?;
 
         verify_membership(
-            &client_state,
+            &proof_specs(ctx, &client_id, &client_state)?,
             &prefix,
             &proof,
             consensus_state.root(),
@@ -134,13 +208,16 @@ impl LightClient for TendermintLightClient {
         })?;
 
         Ok(VerifyMembershipResult {
-            message: VerifyMembershipProxyMessage::new(
-                prefix.into_vec(),
-                path.to_string(),
-                Some(value.keccak256()),
-                proof_height,
-                gen_state_id(canonicalize_state(&client_state), consensus_state)?,
-            ),
+            message: VerifyMembershipProxyMessage {
+                trusted_timestamp: Some(consensus_state.timestamp.into()),
+                ..VerifyMembershipProxyMessage::new(
+                    prefix.into_vec(),
+                    path.to_string(),
+                    Some(value.keccak256()),
+                    proof_height,
+                    gen_state_id(canonicalize_state(&client_state), consensus_state)?,
+                )
+            },
         })
     }
 
@@ -157,11 +234,11 @@ impl LightClient for TendermintLightClient {
             Self::validate_args(ctx, client_id.clone(), prefix, path, proof_height, proof)?;
 
         client_state
-            .verify_height(proof_height.try_into().map_err(Error::ics02)?)
-            .map_err(|e| Error::ics02(e.into()))?;
+            .verify_height(proof_height.try_into().map_err(Error::from)?)
+            .map_err(|e| Error::from(ICS02Error::from(e)))?;
 
         verify_non_membership(
-            &client_state,
+            &proof_specs(ctx, &client_id, &client_state)?,
             &prefix,
             &proof,
             consensus_state.root(),
@@ -175,13 +252,190 @@ impl LightClient for TendermintLightClient {
         })?;
 
         Ok(VerifyNonMembershipResult {
-            message: VerifyMembershipProxyMessage::new(
-                prefix.into_vec(),
-                path.to_string(),
-                None,
-                proof_height,
-                gen_state_id(canonicalize_state(&client_state), consensus_state)?,
-            ),
+            message: VerifyMembershipProxyMessage {
+                trusted_timestamp: Some(consensus_state.timestamp.into()),
+                ..VerifyMembershipProxyMessage::new(
+                    prefix.into_vec(),
+                    path.to_string(),
+                    None,
+                    proof_height,
+                    gen_state_id(canonicalize_state(&client_state), consensus_state)?,
+                )
+            },
+        })
+    }
+
+    fn verify_membership_batch(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        items: Vec<MembershipItem>,
+        proof_height: Height,
+    ) -> Result<Vec<VerifyMembershipResult>, LightClientError> {
+        let (client_state, consensus_state) =
+            Self::load_states(ctx, client_id.clone(), proof_height)?;
+        client_state
+            .verify_height(proof_height.try_into().map_err(Error::from)?)
+            .map_err(|e| Error::from(ICS02Error::from(e)))?;
+        let ibc_prefix: IBCCommitmentPrefix = prefix.clone().try_into().map_err(Error::ics23)?;
+        let trusted_timestamp: Time = consensus_state.timestamp.into();
+
+        let mut verifier = MembershipBatchVerifier::new(
+            proof_specs(ctx, &client_id, &client_state)?,
+            consensus_state.root(),
+        );
+        let mut paths = Vec::with_capacity(items.len());
+        for item in items {
+            let proof: IBCCommitmentProofBytes = item.proof.try_into().map_err(Error::ics23)?;
+            let path: Path = Path::from_str(&item.path).unwrap();
+            verifier
+                .verify(&ibc_prefix, &proof, path.clone(), item.value.clone())
+                .map_err(|e| {
+                    Error::ics03(ICS03Error::ClientStateVerificationFailure {
+                        client_id: client_id.clone().into(),
+                        client_error: e,
+                    })
+                })?;
+            paths.push((path, item.value));
+        }
+
+        let state_id = gen_state_id(canonicalize_state(&client_state), consensus_state)?;
+        Ok(paths
+            .into_iter()
+            .map(|(path, value)| VerifyMembershipResult {
+                message: VerifyMembershipProxyMessage {
+                    trusted_timestamp: Some(trusted_timestamp),
+                    ..VerifyMembershipProxyMessage::new(
+                        prefix.clone(),
+                        path.to_string(),
+                        Some(value.keccak256()),
+                        proof_height,
+                        state_id,
+                    )
+                },
+            })
+            .collect())
+    }
+
+    fn verify_key_value(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        key: Vec<u8>,
+        value: Option<Vec<u8>>,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<VerifyKeyValueResult, LightClientError> {
+        let (client_state, consensus_state) =
+            Self::load_states(ctx, client_id.clone(), proof_height)?;
+        client_state
+            .verify_height(proof_height.try_into().map_err(Error::from)?)
+            .map_err(|e| Error::from(ICS02Error::from(e)))?;
+        let proof: IBCCommitmentProofBytes = proof.try_into().map_err(Error::ics23)?;
+
+        verify_raw_key_value(
+            &proof_specs(ctx, &client_id, &client_state)?,
+            &prefix,
+            &proof,
+            consensus_state.root(),
+            &key,
+            value.as_deref(),
+        )
+        .map_err(|e| {
+            Error::ics03(ICS03Error::ClientStateVerificationFailure {
+                client_id: client_id.clone().into(),
+                client_error: e,
+            })
+        })?;
+
+        Ok(VerifyKeyValueResult {
+            message: VerifyKeyValueProxyMessage {
+                trusted_timestamp: Some(consensus_state.timestamp.into()),
+                ..VerifyKeyValueProxyMessage::new(
+                    prefix,
+                    key,
+                    value.map(|v| v.keccak256()),
+                    proof_height,
+                    gen_state_id(canonicalize_state(&client_state), consensus_state)?,
+                )
+            },
+        })
+    }
+
+    fn trusting_period(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+    ) -> Result<Option<core::time::Duration>, LightClientError> {
+        match ctx.trusting_period_override(client_id)? {
+            Some(trusting_period) => Ok(Some(trusting_period)),
+            None => {
+                let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+                Ok(Some(client_state.trusting_period))
+            }
+        }
+    }
+
+    fn validate_client_params(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+        params: &ClientParams,
+    ) -> Result<(), LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        // the parameters that are not given keep the current values, which may have been overridden before
+        let current = verifying_client_state(ctx, client_id, &client_state)?;
+        let current_opts = current.as_light_client_options().unwrap();
+        let proof_specs = match params.proof_specs.as_ref() {
+            Some(specs) => decode_proof_specs(specs)?,
+            None => current.proof_specs.clone(),
+        };
+        let _ = with_params(
+            &client_state,
+            params
+                .trusting_period
+                .unwrap_or(current_opts.trusting_period),
+            params.max_clock_drift.unwrap_or(current_opts.clock_drift),
+            proof_specs,
+        )?;
+        Ok(())
+    }
+
+    fn latest_timestamp(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+    ) -> Result<Option<Time>, LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        let consensus_state: ConsensusState = ctx
+            .consensus_state(client_id, &client_state.latest_height().into())?
+            .try_into()?;
+        Ok(Some(consensus_state.timestamp.into()))
+    }
+
+    fn latest_state(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+    ) -> Result<UpdateStateProxyMessage, LightClientError> {
+        let client_state: ClientState = ctx.client_state(client_id)?.try_into()?;
+        let height: Height = client_state.latest_height().into();
+        let consensus_state: ConsensusState =
+            ctx.consensus_state(client_id, &height)?.try_into()?;
+        let timestamp: Time = consensus_state.timestamp.into();
+        let state_id = gen_state_id(canonicalize_state(&client_state), consensus_state)?;
+        Ok(UpdateStateProxyMessage {
+            prev_height: Some(height),
+            prev_state_id: Some(state_id),
+            post_height: height,
+            post_state_id: state_id,
+            frozen_height: client_state.frozen_height().map(Into::into),
+            timestamp,
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: vec![],
         })
     }
 }
@@ -204,10 +458,23 @@ impl TendermintLightClient {
         ),
         LightClientError,
     > {
+        let (client_state, consensus_state) = Self::load_states(ctx, client_id, proof_height)?;
+        let proof: IBCCommitmentProofBytes = proof.try_into().map_err(Error::ics23)?;
+        let prefix: IBCCommitmentPrefix = counterparty_prefix.try_into().map_err(Error::ics23)?;
+        let path: Path = Path::from_str(&path).unwrap();
+        Ok((client_state, consensus_state, prefix, path, proof))
+    }
+
+    /// Returns the client state and the consensus state at `proof_height` if the client is not frozen
+    fn load_states(
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        proof_height: Height,
+    ) -> Result<(ClientState, ConsensusState), LightClientError> {
         let client_state: ClientState = ctx.client_state(&client_id)?.try_into()?;
 
         if client_state.is_frozen() {
-            return Err(Error::ics02(ICS02Error::ClientFrozen {
+            return Err(Error::from(ICS02Error::ClientFrozen {
                 client_id: client_id.into(),
             })
             .into());
@@ -215,11 +482,7 @@ impl TendermintLightClient {
 
         let consensus_state: ConsensusState =
             ctx.consensus_state(&client_id, &proof_height)?.try_into()?;
-
-        let proof: IBCCommitmentProofBytes = proof.try_into().map_err(Error::ics23)?;
-        let prefix: IBCCommitmentPrefix = counterparty_prefix.try_into().map_err(Error::ics23)?;
-        let path: Path = Path::from_str(&path).unwrap();
-        Ok((client_state, consensus_state, prefix, path, proof))
+        Ok((client_state, consensus_state))
     }
 
     fn update_state(
@@ -232,7 +495,7 @@ impl TendermintLightClient {
         let client_state: ClientState = ctx.client_state(&client_id)?.try_into()?;
 
         if client_state.is_frozen() {
-            return Err(Error::ics02(ICS02Error::ClientFrozen {
+            return Err(Error::from(ICS02Error::ClientFrozen {
                 client_id: client_id.into(),
             })
             .into());
@@ -242,7 +505,7 @@ impl TendermintLightClient {
         let latest_consensus_state: ConsensusState = ctx
             .consensus_state(&client_id, &client_state.latest_height().into())
             .map_err(|_| {
-                Error::ics02(ICS02Error::ConsensusStateNotFound {
+                Error::from(ICS02Error::ConsensusStateNotFound {
                     client_id: client_id.clone().into(),
                     height: client_state.latest_height(),
                 })
@@ -255,56 +518,83 @@ impl TendermintLightClient {
         let duration = now
             .duration_since(latest_consensus_state.timestamp().into_tm_time().unwrap())
             .map_err(|_| {
-                Error::ics02(ICS02Error::InvalidConsensusStateTimestamp {
+                Error::from(ICS02Error::InvalidConsensusStateTimestamp {
                     time1: latest_consensus_state.timestamp(),
                     time2: now.into(),
                 })
             })?;
 
-        if client_state.expired(duration) {
-            return Err(Error::ics02(ICS02Error::HeaderNotWithinTrustPeriod {
+        // The parameters configured for the client take precedence over the ones in the client state.
+        // They are only applied to the verification, so the client state to be persisted is unchanged.
+        let lc_opts = client_state.as_light_client_options().unwrap();
+        let verifying_client_state = verifying_client_state(ctx, &client_id, &client_state)?;
+        let verifying_opts = verifying_client_state.as_light_client_options().unwrap();
+
+        if verifying_client_state.expired(duration) {
+            return Err(Error::from(ICS02Error::HeaderNotWithinTrustPeriod {
                 latest_time: latest_consensus_state.timestamp(),
                 update_time: header.timestamp(),
             })
             .into());
         }
 
-        let height = header.height().into();
+        let height: Height = header.height().into();
         let header_timestamp: Time = header.timestamp().into();
+        let trusted_height: Height = header.trusted_height.into();
+        if height <= trusted_height {
+            return Err(Error::header_not_newer_than_trusted(height, trusted_height).into());
+        }
+        check_verification_mode(
+            ctx.verification_mode(&client_id)?,
+            height,
+            trusted_height,
+            client_state.latest_height().into(),
+        )?;
 
         let trusted_consensus_state: ConsensusState = ctx
             .consensus_state(&client_id, &header.trusted_height.into())
             .map_err(|_| {
-                Error::ics02(ICS02Error::ConsensusStateNotFound {
+                Error::from(ICS02Error::ConsensusStateNotFound {
                     client_id: client_id.clone().into(),
                     height: header.trusted_height,
                 })
             })?
             .try_into()?;
 
+        check_validator_sets(&header, &trusted_consensus_state)?;
+
         // Use client_state to validate the new header against the latest consensus_state.
         // This function will return the new client_state (its latest_height changed) and a
         // consensus_state obtained from header. These will be later persisted by the keeper.
         let UpdatedState {
             client_state: new_client_state,
             consensus_state: new_consensus_state,
-        } = client_state
+        } = verifying_client_state
             .check_header_and_update_state(
                 &IBCContext::<TendermintClientState, TendermintConsensusState>::new(ctx),
                 client_id.into(),
                 Any::from(header.clone()).into(),
             )
             .map_err(|e| {
-                Error::ics02(ICS02Error::HeaderVerificationFailure {
-                    reason: e.to_string(),
-                })
+                let reason = e.to_string();
+                let suggested_heights = bisection_heights(trusted_height, height);
+                if is_insufficient_trust(&reason) && !suggested_heights.is_empty() {
+                    Error::bisection_required(trusted_height, height, suggested_heights)
+                } else {
+                    Error::from(ICS02Error::HeaderVerificationFailure { reason })
+                }
             })?;
 
-        let new_client_state = ClientState(
-            downcast_client_state::<TendermintClientState>(new_client_state.as_ref())
-                .unwrap()
-                .clone(),
-        );
+        let new_client_state = with_params(
+            &ClientState(
+                downcast_client_state::<TendermintClientState>(new_client_state.as_ref())
+                    .unwrap()
+                    .clone(),
+            ),
+            lc_opts.trusting_period,
+            lc_opts.clock_drift,
+            client_state.proof_specs.clone(),
+        )?;
         let new_consensus_state = ConsensusState(
             downcast_consensus_state::<TendermintConsensusState>(new_consensus_state.as_ref())
                 .unwrap()
@@ -312,7 +602,6 @@ impl TendermintLightClient {
         );
 
         let trusted_state_timestamp: Time = trusted_consensus_state.timestamp().into();
-        let lc_opts = client_state.as_light_client_options().unwrap();
 
         let prev_state_id =
             gen_state_id(canonicalize_state(&client_state), trusted_consensus_state)?;
@@ -320,6 +609,7 @@ impl TendermintLightClient {
             canonicalize_state(&new_client_state),
             new_consensus_state.clone(),
         )?;
+        let frozen_height = new_client_state.frozen_height().map(Into::into);
         Ok(UpdateStateData {
             new_any_client_state: new_client_state.into(),
             new_any_consensus_state: new_consensus_state.into(),
@@ -329,10 +619,12 @@ impl TendermintLightClient {
                 prev_state_id: Some(prev_state_id),
                 post_height: height,
                 post_state_id,
+                frozen_height,
                 timestamp: header_timestamp,
+                expires_at: None,
                 context: TrustingPeriodContext::new(
-                    lc_opts.trusting_period,
-                    lc_opts.clock_drift,
+                    verifying_opts.trusting_period,
+                    verifying_opts.clock_drift,
                     header_timestamp,
                     trusted_state_timestamp,
                 )
@@ -353,29 +645,37 @@ impl TendermintLightClient {
         let client_state: ClientState = ctx.client_state(&client_id)?.try_into()?;
 
         if client_state.is_frozen() {
-            return Err(Error::ics02(ICS02Error::ClientFrozen {
+            return Err(Error::from(ICS02Error::ClientFrozen {
                 client_id: client_id.into(),
             })
             .into());
         }
 
-        let new_client_state = client_state
+        let lc_opts = client_state.as_light_client_options().unwrap();
+        let verifying_client_state = verifying_client_state(ctx, &client_id, &client_state)?;
+
+        let new_client_state = verifying_client_state
             .check_misbehaviour_and_update_state(
                 &IBCContext::<TendermintClientState, TendermintConsensusState>::new(ctx),
                 client_id.clone().into(),
                 Any::from(misbehaviour.clone()).into(),
             )
             .map_err(|e| {
-                Error::ics02(ICS02Error::HeaderVerificationFailure {
+                Error::from(ICS02Error::HeaderVerificationFailure {
                     reason: e.to_string(),
                 })
             })?;
 
-        let new_client_state = ClientState(
-            downcast_client_state::<TendermintClientState>(new_client_state.as_ref())
-                .unwrap()
-                .clone(),
-        );
+        let new_client_state = with_params(
+            &ClientState(
+                downcast_client_state::<TendermintClientState>(new_client_state.as_ref())
+                    .unwrap()
+                    .clone(),
+            ),
+            lc_opts.trusting_period,
+            lc_opts.clock_drift,
+            client_state.proof_specs.clone(),
+        )?;
 
         let prev_states = self.make_prev_states(
             ctx,
@@ -406,11 +706,11 @@ impl TendermintLightClient {
     ) -> Result<Vec<PrevState>, LightClientError> {
         let mut prev_states = Vec::new();
         for height in heights {
-            let ibc_height = height.try_into().map_err(Error::ics02)?;
+            let ibc_height = height.try_into().map_err(Error::from)?;
             let consensus_state: ConsensusState = ctx
                 .consensus_state(client_id, &height)
                 .map_err(|_| {
-                    Error::ics02(ICS02Error::ConsensusStateNotFound {
+                    Error::from(ICS02Error::ConsensusStateNotFound {
                         client_id: client_id.clone().into(),
                         height: ibc_height,
                     })
@@ -426,6 +726,114 @@ impl TendermintLightClient {
     }
 }
 
+/// Returns an error if the header at `height` cannot be verified against `trusted_height` in the mode
+fn check_verification_mode(
+    mode: VerificationMode,
+    height: Height,
+    trusted_height: Height,
+    latest_height: Height,
+) -> Result<(), Error> {
+    let adjacent = trusted_height.add(1).map_or(false, |h| h == height);
+    let allowed = match mode {
+        VerificationMode::Skipping => true,
+        VerificationMode::Sequential => adjacent && trusted_height == latest_height,
+        VerificationMode::Adjacent => adjacent,
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(Error::verification_mode_violation(
+            mode,
+            height,
+            trusted_height,
+            latest_height,
+        ))
+    }
+}
+
+/// The maximum number of intermediate heights suggested for a bisection
+const MAX_SUGGESTED_HEIGHTS: usize = 4;
+
+/// Returns true if the verifier rejected the header because the validators trusted at the trusted height
+/// don't have enough voting power in the commit of the header
+///
+/// ibc-rs reports the tendermint verification failure as a string, so the reason is matched by its message.
+/// Checks that the validator sets in the header match the hashes committed in the signed header
+/// and in the trusted consensus state, so that a mismatch is reported as a typed error.
+fn check_validator_sets(
+    header: &Header,
+    trusted_consensus_state: &ConsensusState,
+) -> Result<(), Error> {
+    let validators_hash = header.validator_set.hash();
+    if validators_hash != header.signed_header.header.validators_hash {
+        return Err(Error::invalid_validator_set(format!(
+            "the validator set does not match the header: expected={} actual={}",
+            header.signed_header.header.validators_hash, validators_hash
+        )));
+    }
+    let trusted_validators_hash = header.trusted_validator_set.hash();
+    if trusted_validators_hash != trusted_consensus_state.next_validators_hash {
+        return Err(Error::invalid_validator_set(format!(
+            "the trusted validator set does not match the trusted consensus state: expected={} actual={}",
+            trusted_consensus_state.next_validators_hash, trusted_validators_hash
+        )));
+    }
+    Ok(())
+}
+
+fn is_insufficient_trust(reason: &str) -> bool {
+    reason.contains("not enough trust") || reason.contains("insufficient validators overlap")
+}
+
+/// Returns the intermediate heights to update the client to before `target_height`, in the order of preference
+///
+/// The first height is the midpoint of the range as in the bisection of the tendermint light client, and
+/// the following ones halve the distance to `trusted_height` in case the first one cannot be verified either.
+/// It is empty if the heights are adjacent or in different revisions, where no bisection is possible.
+fn bisection_heights(trusted_height: Height, target_height: Height) -> Vec<Height> {
+    let mut heights = vec![];
+    if trusted_height.revision_number() != target_height.revision_number() {
+        return heights;
+    }
+    let trusted = trusted_height.revision_height();
+    let mut upper = target_height.revision_height();
+    while heights.len() < MAX_SUGGESTED_HEIGHTS && upper > trusted + 1 {
+        let pivot = trusted + (upper - trusted) / 2;
+        heights.push(Height::new(trusted_height.revision_number(), pivot));
+        upper = pivot;
+    }
+    heights
+}
+
+/// Returns the client state to verify with, where the parameters configured for the client take precedence
+fn verifying_client_state(
+    ctx: &dyn HostClientReader,
+    client_id: &ClientId,
+    client_state: &ClientState,
+) -> Result<ClientState, LightClientError> {
+    let lc_opts = client_state.as_light_client_options().unwrap();
+    Ok(with_params(
+        client_state,
+        ctx.trusting_period_override(client_id)?
+            .unwrap_or(lc_opts.trusting_period),
+        ctx.max_clock_drift(client_id)?
+            .unwrap_or(lc_opts.clock_drift),
+        proof_specs(ctx, client_id, client_state)?,
+    )?)
+}
+
+/// Returns the proof specs configured for the client, or the ones of the client state
+fn proof_specs(
+    ctx: &dyn HostClientReader,
+    client_id: &ClientId,
+    client_state: &ClientState,
+) -> Result<ProofSpecs, LightClientError> {
+    match ctx.proof_specs_override(client_id)? {
+        Some(specs) => Ok(decode_proof_specs(&specs)?),
+        None => Ok(client_state.proof_specs.clone()),
+    }
+}
+
 pub fn register_implementations(registry: &mut dyn LightClientRegistry) {
     registry
         .put_light_client(
@@ -435,8 +843,15 @@ pub fn register_implementations(registry: &mut dyn LightClientRegistry) {
         .unwrap()
 }
 
+/// Decode the ICS-23 merkle proof from the proof bytes submitted by the relayer
+pub fn decode_merkle_proof(proof: &IBCCommitmentProofBytes) -> Result<MerkleProof, ICS02Error> {
+    Ok(RawMerkleProof::try_from(proof.clone())
+        .map_err(ICS02Error::InvalidCommitmentProof)?
+        .into())
+}
+
 fn verify_membership(
-    client_state: &ClientState,
+    proof_specs: &ProofSpecs,
     prefix: &IBCCommitmentPrefix,
     proof: &IBCCommitmentProofBytes,
     root: &CommitmentRoot,
@@ -444,34 +859,206 @@ fn verify_membership(
     value: Vec<u8>,
 ) -> Result<(), ICS02Error> {
     let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
-    let merkle_proof: MerkleProof = RawMerkleProof::try_from(proof.clone())
-        .map_err(ICS02Error::InvalidCommitmentProof)?
-        .into();
+    let merkle_proof = decode_merkle_proof(proof)?;
 
     merkle_proof
-        .verify_membership(
-            &client_state.proof_specs,
-            root.clone().into(),
-            merkle_path,
-            value,
-            0,
-        )
+        .verify_membership(proof_specs, root.clone().into(), merkle_path, value, 0)
         .map_err(ICS02Error::Ics23Verification)
 }
 
 fn verify_non_membership(
-    client_state: &ClientState,
+    proof_specs: &ProofSpecs,
     prefix: &IBCCommitmentPrefix,
     proof: &IBCCommitmentProofBytes,
     root: &CommitmentRoot,
     path: impl Into<Path>,
 ) -> Result<(), ICS02Error> {
     let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
-    let merkle_proof: MerkleProof = RawMerkleProof::try_from(proof.clone())
-        .map_err(ICS02Error::InvalidCommitmentProof)?
-        .into();
+    let merkle_proof = decode_merkle_proof(proof)?;
 
     merkle_proof
-        .verify_non_membership(&client_state.proof_specs, root.clone().into(), merkle_path)
+        .verify_non_membership(proof_specs, root.clone().into(), merkle_path)
         .map_err(ICS02Error::Ics23Verification)
 }
+
+/// Verifies the proof of a raw key in the store `prefix`, or of its absence if `value` is None
+///
+/// This is equivalent to the ICS-23 verification of a path, except that the key doesn't have to be a UTF-8 ICS-24 path.
+fn verify_raw_key_value(
+    proof_specs: &ProofSpecs,
+    prefix: &[u8],
+    proof: &IBCCommitmentProofBytes,
+    root: &CommitmentRoot,
+    key: &[u8],
+    value: Option<&[u8]>,
+) -> Result<(), ICS02Error> {
+    let merkle_proof = decode_merkle_proof(proof)?;
+    let specs: Vec<ProofSpec> = proof_specs.clone().into();
+    // keys are ordered from leaf to root in the same order as the proofs
+    let keys = [key, prefix];
+    if merkle_proof.proofs.len() != keys.len() || specs.len() != keys.len() {
+        return Err(ICS02Error::Ics23Verification(
+            CommitmentError::InvalidMerkleProof,
+        ));
+    }
+    let invalid_proof = || ICS02Error::Ics23Verification(CommitmentError::InvalidMerkleProof);
+    let verification_failure =
+        || ICS02Error::Ics23Verification(CommitmentError::VerificationFailure);
+
+    let (leaf_proof, leaf_spec) = (&merkle_proof.proofs[0], &specs[0]);
+    let mut subroot = match (value, &leaf_proof.proof) {
+        (Some(value), Some(Proof::Exist(existence_proof))) => {
+            if value.is_empty() {
+                return Err(ICS02Error::Ics23Verification(
+                    CommitmentError::EmptyVerifiedValue,
+                ));
+            }
+            let subroot = calculate_existence_root::<HostFunctionsManager>(existence_proof)
+                .map_err(|_| invalid_proof())?;
+            if !ics23::verify_membership::<HostFunctionsManager>(
+                leaf_proof, leaf_spec, &subroot, key, value,
+            ) {
+                return Err(verification_failure());
+            }
+            subroot
+        }
+        (None, Some(Proof::Nonexist(non_existence_proof))) => {
+            let subroot = calculate_non_existence_root(non_existence_proof)?;
+            if !ics23::verify_non_membership::<HostFunctionsManager>(
+                leaf_proof, leaf_spec, &subroot, key,
+            ) {
+                return Err(verification_failure());
+            }
+            subroot
+        }
+        _ => return Err(invalid_proof()),
+    };
+    for ((proof, spec), key) in merkle_proof
+        .proofs
+        .iter()
+        .zip(specs.iter())
+        .zip(keys.iter())
+        .skip(1)
+    {
+        let parent = match &proof.proof {
+            Some(Proof::Exist(existence_proof)) => {
+                calculate_existence_root::<HostFunctionsManager>(existence_proof)
+                    .map_err(|_| invalid_proof())?
+            }
+            _ => return Err(invalid_proof()),
+        };
+        if !ics23::verify_membership::<HostFunctionsManager>(proof, spec, &parent, key, &subroot) {
+            return Err(verification_failure());
+        }
+        subroot = parent;
+    }
+    if subroot != root.as_bytes() {
+        return Err(verification_failure());
+    }
+    Ok(())
+}
+
+fn calculate_non_existence_root(proof: &NonExistenceProof) -> Result<Vec<u8>, ICS02Error> {
+    let existence_proof =
+        proof
+            .left
+            .as_ref()
+            .or(proof.right.as_ref())
+            .ok_or(ICS02Error::Ics23Verification(
+                CommitmentError::InvalidMerkleProof,
+            ))?;
+    calculate_existence_root::<HostFunctionsManager>(existence_proof)
+        .map_err(|_| ICS02Error::Ics23Verification(CommitmentError::InvalidMerkleProof))
+}
+
+/// Verifies membership proofs against the same root, sharing the verification of the upper levels
+///
+/// The multi-store proofs of the paths under the same prefix only differ in the first level,
+/// so once a subroot is proven to lead to the root, the rest of its path is not verified again.
+struct MembershipBatchVerifier<'a> {
+    specs: Vec<ProofSpec>,
+    root: &'a CommitmentRoot,
+    /// (level, value) pairs from which the path to the root has been verified
+    verified: BTreeSet<(usize, Vec<u8>)>,
+}
+
+impl<'a> MembershipBatchVerifier<'a> {
+    fn new(proof_specs: ProofSpecs, root: &'a CommitmentRoot) -> Self {
+        Self {
+            specs: proof_specs.into(),
+            root,
+            verified: Default::default(),
+        }
+    }
+
+    fn verify(
+        &mut self,
+        prefix: &IBCCommitmentPrefix,
+        proof: &IBCCommitmentProofBytes,
+        path: impl Into<Path>,
+        value: Vec<u8>,
+    ) -> Result<(), ICS02Error> {
+        let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+        let merkle_proof = decode_merkle_proof(proof)?;
+        let num = merkle_proof.proofs.len();
+        if num == 0 || self.specs.len() != num || merkle_path.key_path.len() != num {
+            return Err(ICS02Error::Ics23Verification(
+                CommitmentError::InvalidMerkleProof,
+            ));
+        }
+        if value.is_empty() {
+            return Err(ICS02Error::Ics23Verification(
+                CommitmentError::EmptyVerifiedValue,
+            ));
+        }
+
+        // keys are ordered from root to leaf, while proofs are ordered from leaf to root
+        let mut value = value;
+        let mut pending = Vec::with_capacity(num);
+        for (level, ((proof, spec), key)) in merkle_proof
+            .proofs
+            .iter()
+            .zip(self.specs.iter())
+            .zip(merkle_path.key_path.iter().rev())
+            .enumerate()
+        {
+            if level > 0 && self.verified.contains(&(level, value.clone())) {
+                self.verified.extend(pending);
+                return Ok(());
+            }
+            let subroot = match &proof.proof {
+                Some(Proof::Exist(existence_proof)) => calculate_existence_root::<
+                    HostFunctionsManager,
+                >(existence_proof)
+                .map_err(|_| ICS02Error::Ics23Verification(CommitmentError::InvalidMerkleProof))?,
+                _ => {
+                    return Err(ICS02Error::Ics23Verification(
+                        CommitmentError::InvalidMerkleProof,
+                    ))
+                }
+            };
+            if !ics23::verify_membership::<HostFunctionsManager>(
+                proof,
+                spec,
+                &subroot,
+                key.as_bytes(),
+                &value,
+            ) {
+                return Err(ICS02Error::Ics23Verification(
+                    CommitmentError::VerificationFailure,
+                ));
+            }
+            if level > 0 {
+                pending.push((level, value));
+            }
+            value = subroot;
+        }
+        if value != self.root.as_bytes() {
+            return Err(ICS02Error::Ics23Verification(
+                CommitmentError::VerificationFailure,
+            ));
+        }
+        self.verified.extend(pending);
+        Ok(())
+    }
+}
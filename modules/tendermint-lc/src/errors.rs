@@ -1,6 +1,8 @@
 use crate::prelude::*;
 use flex_error::*;
-use light_client::LightClientSpecificError;
+use ibc::core::ics02_client::error::ClientError;
+use light_client::types::{Height, VerificationMode};
+use light_client::{ErrorKind, LightClientSpecificError};
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
@@ -12,7 +14,79 @@ define_error! {
             format_args!("unexpected client_type: type_url={}", e.type_url)
         },
 
-        Ics02
+        HeaderNotNewerThanTrusted {
+            height: Height,
+            trusted_height: Height
+        }
+        |e| {
+            format_args!("header height must be greater than the trusted height: height={} trusted_height={}", e.height, e.trusted_height)
+        },
+
+        VerificationModeViolation {
+            mode: VerificationMode,
+            height: Height,
+            trusted_height: Height,
+            latest_height: Height
+        }
+        |e| {
+            format_args!("header is not allowed by the verification mode: mode={} height={} trusted_height={} latest_height={}", e.mode, e.height, e.trusted_height, e.latest_height)
+        },
+
+        InvalidExtendedHeader {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid extended header: descr={}", e.descr)
+        },
+
+        VoteExtensionsRejected {
+            height: Height
+        }
+        |e| {
+            format_args!("the vote extension policy of the client rejects the headers with vote extensions: height={}", e.height)
+        },
+
+        InvalidVoteExtension {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid vote extension: descr={}", e.descr)
+        },
+
+        BisectionRequired {
+            trusted_height: Height,
+            target_height: Height,
+            suggested_heights: Vec<Height>
+        }
+        |e| {
+            format_args!("the validator set changed beyond the trust level, so the client must be updated to an intermediate height first: trusted_height={} target_height={} suggested_heights={}", e.trusted_height, e.target_height, join_heights(&e.suggested_heights))
+        },
+
+        InvalidClientParams {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid client params: descr={}", e.descr)
+        },
+
+        InvalidValidatorSet {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid validator set: descr={}", e.descr)
+        },
+
+        StateMigration {
+            type_url: String,
+            descr: String
+        }
+        |e| {
+            format_args!("the state cannot be migrated: type_url={} descr={}", e.type_url, e.descr)
+        },
+
+        Ics02 {
+            kind: ErrorKind
+        }
         [TraceError<ibc::core::ics02_client::error::ClientError>]
         |_| { "ICS02 client error" },
 
@@ -34,7 +108,53 @@ define_error! {
     }
 }
 
-impl LightClientSpecificError for Error {}
+impl LightClientSpecificError for Error {
+    fn kind(&self) -> ErrorKind {
+        match self.detail() {
+            ErrorDetail::UnexpectedClientType(_)
+            | ErrorDetail::InvalidClientParams(_)
+            | ErrorDetail::StateMigration(_) => ErrorKind::Unknown,
+            ErrorDetail::HeaderNotNewerThanTrusted(_) => ErrorKind::HeaderOlderThanTrusted,
+            ErrorDetail::VerificationModeViolation(_)
+            | ErrorDetail::InvalidExtendedHeader(_)
+            | ErrorDetail::VoteExtensionsRejected(_)
+            | ErrorDetail::InvalidVoteExtension(_) => ErrorKind::InvalidHeader,
+            ErrorDetail::BisectionRequired(_) => ErrorKind::BisectionRequired,
+            ErrorDetail::InvalidValidatorSet(_) => ErrorKind::InvalidValidatorSet,
+            ErrorDetail::Ics02(e) => e.kind,
+            ErrorDetail::Ics03(_) | ErrorDetail::Ics04(_) | ErrorDetail::Ics23(_) => {
+                ErrorKind::ProofMismatch
+            }
+            ErrorDetail::Commitment(e) => ErrorKind::from_commitment_error(&e.source),
+        }
+    }
+}
+
+impl From<ClientError> for Error {
+    /// Classify the ICS02 error while it is available, since the trace does not keep it typed
+    fn from(err: ClientError) -> Self {
+        let kind = match &err {
+            ClientError::ClientFrozen { .. } => ErrorKind::ClientFrozen,
+            ClientError::ConsensusStateNotFound { .. } => ErrorKind::ConsensusStateNotFound,
+            ClientError::HeaderNotWithinTrustPeriod { .. } => ErrorKind::Expired,
+            ClientError::HeaderVerificationFailure { .. } => ErrorKind::InvalidHeader,
+            ClientError::InvalidCommitmentProof(_) | ClientError::Ics23Verification(_) => {
+                ErrorKind::ProofMismatch
+            }
+            _ => ErrorKind::Unknown,
+        };
+        Error::ics02(kind, err)
+    }
+}
+
+/// Formats the heights as a comma-separated list, which the host parses to retry the update
+fn join_heights(heights: &[Height]) -> String {
+    heights
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
 
 impl From<light_client::commitments::Error> for Error {
     fn from(err: light_client::commitments::Error) -> Self {
@@ -1,4 +1,5 @@
 use crate::errors::Error;
+use crate::prelude::*;
 use core::ops::Deref;
 use ibc::clients::ics07_tendermint::header::{
     Header as TendermintHeader, TENDERMINT_HEADER_TYPE_URL,
@@ -7,11 +8,19 @@ use ibc::clients::ics07_tendermint::misbehaviour::{
     Misbehaviour as TendermintMisbehaviour, TENDERMINT_MISBEHAVIOUR_TYPE_URL,
 };
 use lcp_proto::google::protobuf::Any as ProtoAny;
+use lcp_proto::ibc::lightclients::tendermint::v1::Header as RawHeader;
+use lcp_proto::lcp::lightclients::tendermint::v1::{
+    ExtendedCommitSig as RawExtendedCommitSig, ExtendedHeader as RawExtendedHeader,
+};
 use light_client::types::Any;
+use prost::Message;
+
+pub const EXTENDED_HEADER_TYPE_URL: &str = "/lcp.lightclients.tendermint.v1.ExtendedHeader";
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum ClientMessage {
     Header(Header),
+    ExtendedHeader(ExtendedHeader),
     Misbehaviour(Misbehaviour),
 }
 
@@ -19,6 +28,7 @@ impl From<ClientMessage> for Any {
     fn from(value: ClientMessage) -> Self {
         match value {
             ClientMessage::Header(header) => header.into(),
+            ClientMessage::ExtendedHeader(header) => header.into(),
             ClientMessage::Misbehaviour(misbehaviour) => misbehaviour.into(),
         }
     }
@@ -30,6 +40,8 @@ impl TryFrom<Any> for ClientMessage {
     fn try_from(value: Any) -> Result<Self, Self::Error> {
         if value.type_url == TENDERMINT_HEADER_TYPE_URL {
             Ok(Self::Header(Header::try_from(value)?))
+        } else if value.type_url == EXTENDED_HEADER_TYPE_URL {
+            Ok(Self::ExtendedHeader(ExtendedHeader::try_from(value)?))
         } else if value.type_url == TENDERMINT_MISBEHAVIOUR_TYPE_URL {
             Ok(Self::Misbehaviour(Misbehaviour::try_from(value)?))
         } else {
@@ -72,13 +84,79 @@ impl TryFrom<Any> for Header {
     fn try_from(value: Any) -> Result<Self, Self::Error> {
         let any: ProtoAny = value.into();
         if any.type_url == TENDERMINT_HEADER_TYPE_URL {
-            Ok(Self(TendermintHeader::try_from(any).map_err(Error::ics02)?))
+            Ok(Self(TendermintHeader::try_from(any).map_err(Error::from)?))
         } else {
             Err(Error::unexpected_client_type(any.type_url))
         }
     }
 }
 
+/// ExtendedHeader is a header whose commit is an extended commit of ABCI++
+///
+/// The state of the client is updated with `header` as usual, and the vote extensions are only
+/// checked against the vote extension policy of the client.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ExtendedHeader {
+    pub header: Header,
+    pub extended_signatures: Vec<ExtendedCommitSig>,
+}
+
+/// ExtendedCommitSig is the vote extension that a validator signed along with its precommit
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExtendedCommitSig {
+    pub validator_address: Vec<u8>,
+    pub extension: Vec<u8>,
+    pub extension_signature: Vec<u8>,
+}
+
+impl From<ExtendedHeader> for Any {
+    fn from(value: ExtendedHeader) -> Self {
+        let raw = RawExtendedHeader {
+            header: Some(RawHeader::from(value.header.0)),
+            extended_signatures: value
+                .extended_signatures
+                .into_iter()
+                .map(|sig| RawExtendedCommitSig {
+                    validator_address: sig.validator_address,
+                    extension: sig.extension,
+                    extension_signature: sig.extension_signature,
+                })
+                .collect(),
+        };
+        Any::new(EXTENDED_HEADER_TYPE_URL.to_string(), raw.encode_to_vec())
+    }
+}
+
+impl TryFrom<Any> for ExtendedHeader {
+    type Error = Error;
+
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        if value.type_url != EXTENDED_HEADER_TYPE_URL {
+            return Err(Error::unexpected_client_type(value.type_url));
+        }
+        let raw = RawExtendedHeader::decode(value.value.as_slice())
+            .map_err(|e| Error::invalid_extended_header(e.to_string()))?;
+        let raw_header = raw
+            .header
+            .ok_or_else(|| Error::invalid_extended_header("header must be non-nil".into()))?;
+        Ok(Self {
+            header: Header::try_from(Any::new(
+                TENDERMINT_HEADER_TYPE_URL.to_string(),
+                raw_header.encode_to_vec(),
+            ))?,
+            extended_signatures: raw
+                .extended_signatures
+                .into_iter()
+                .map(|sig| ExtendedCommitSig {
+                    validator_address: sig.validator_address,
+                    extension: sig.extension,
+                    extension_signature: sig.extension_signature,
+                })
+                .collect(),
+        })
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Misbehaviour(pub(crate) TendermintMisbehaviour);
 
@@ -114,7 +192,7 @@ impl TryFrom<Any> for Misbehaviour {
         let any: ProtoAny = value.into();
         if any.type_url == TENDERMINT_MISBEHAVIOUR_TYPE_URL {
             Ok(Self(
-                TendermintMisbehaviour::try_from(any).map_err(Error::ics02)?,
+                TendermintMisbehaviour::try_from(any).map_err(Error::from)?,
             ))
         } else {
             Err(Error::unexpected_client_type(any.type_url))
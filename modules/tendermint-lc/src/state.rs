@@ -1,5 +1,7 @@
 use crate::errors::Error;
+use crate::prelude::*;
 use core::ops::Deref;
+use core::time::Duration;
 use ibc::clients::ics07_tendermint::{
     client_state::{
         AllowUpdate, ClientState as TendermintClientState, TENDERMINT_CLIENT_STATE_TYPE_URL,
@@ -8,10 +10,16 @@ use ibc::clients::ics07_tendermint::{
         ConsensusState as TendermintConsensusState, TENDERMINT_CONSENSUS_STATE_TYPE_URL,
     },
 };
+use ibc::core::ics02_client::client_state::ClientState as Ics02ClientState;
+use ibc::core::ics23_commitment::specs::ProofSpecs;
+use ics23::ProofSpec;
 use lcp_proto::google::protobuf::Any as ProtoAny;
-use lcp_proto::ibc::lightclients::tendermint::v1::ClientState as RawTmClientState;
+use lcp_proto::ibc::lightclients::tendermint::v1::{
+    ClientState as RawTmClientState, ConsensusState as RawTmConsensusState,
+};
 use light_client::commitments::{gen_state_id_from_any, StateID};
 use light_client::types::{Any, Height};
+use prost::Message;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct ClientState(pub(crate) TendermintClientState);
@@ -31,7 +39,7 @@ impl TryFrom<Any> for ClientState {
         let any: ProtoAny = value.into();
         if any.type_url == TENDERMINT_CLIENT_STATE_TYPE_URL {
             Ok(Self(
-                TendermintClientState::try_from(any).map_err(Error::ics02)?,
+                TendermintClientState::try_from(any).map_err(Error::from)?,
             ))
         } else {
             Err(Error::unexpected_client_type(any.type_url))
@@ -63,7 +71,7 @@ impl TryFrom<Any> for ConsensusState {
         let any: ProtoAny = value.into();
         if any.type_url == TENDERMINT_CONSENSUS_STATE_TYPE_URL {
             Ok(Self(
-                TendermintConsensusState::try_from(any).map_err(Error::ics02)?,
+                TendermintConsensusState::try_from(any).map_err(Error::from)?,
             ))
         } else {
             Err(Error::unexpected_client_type(any.type_url))
@@ -104,6 +112,86 @@ pub fn canonicalize_state(client_state: &ClientState) -> ClientState {
     ClientState(tm)
 }
 
+// with_params returns a copy of specified client state with the verification parameters replaced
+// the other fields including latest_height and frozen_height are kept as is
+pub fn with_params(
+    client_state: &ClientState,
+    trusting_period: Duration,
+    max_clock_drift: Duration,
+    proof_specs: ProofSpecs,
+) -> Result<ClientState, Error> {
+    let raw_state: RawTmClientState = client_state.0.clone().try_into().unwrap();
+    #[allow(deprecated)]
+    let tm = TendermintClientState::new(
+        client_state.chain_id.clone(),
+        client_state.trust_level,
+        trusting_period,
+        client_state.unbonding_period,
+        max_clock_drift,
+        client_state.latest_height(),
+        proof_specs,
+        client_state.upgrade_path.clone(),
+        AllowUpdate {
+            after_expiry: raw_state.allow_update_after_expiry,
+            after_misbehaviour: raw_state.allow_update_after_misbehaviour,
+        },
+        client_state.frozen_height(),
+    )
+    .map_err(|e| Error::invalid_client_params(e.to_string()))?;
+    Ok(ClientState(tm))
+}
+
+/// Check a client state stored by a previous version of this implementation before it is taken over
+///
+/// The versions share the encoding, so the state is kept as is. It is rejected if it has a field
+/// that this version doesn't know, which would be dropped silently by re-encoding it.
+pub fn migrate_client_state(any_client_state: Any) -> Result<Any, Error> {
+    ClientState::try_from(any_client_state.clone())?;
+    check_known_fields::<RawTmClientState>(&any_client_state)?;
+    Ok(any_client_state)
+}
+
+/// Check a consensus state stored by a previous version of this implementation before it is taken over
+///
+/// See `migrate_client_state` for the conditions.
+pub fn migrate_consensus_state(any_consensus_state: Any) -> Result<Any, Error> {
+    ConsensusState::try_from(any_consensus_state.clone())?;
+    check_known_fields::<RawTmConsensusState>(&any_consensus_state)?;
+    Ok(any_consensus_state)
+}
+
+// check_known_fields returns an error if re-encoding the state changes its bytes,
+// i.e. the state has a field that `T` doesn't know or is not encoded canonically
+fn check_known_fields<T: Message + Default>(any: &Any) -> Result<(), Error> {
+    let any: ProtoAny = any.clone().into();
+    let raw = T::decode(any.value.as_slice())
+        .map_err(|e| Error::state_migration(any.type_url.clone(), e.to_string()))?;
+    if raw.encode_to_vec() != any.value {
+        return Err(Error::state_migration(
+            any.type_url,
+            "the state has fields that this version doesn't know".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Decode the proto-encoded ICS-23 proof specs configured for a client
+pub fn decode_proof_specs(specs: &[Vec<u8>]) -> Result<ProofSpecs, Error> {
+    if specs.is_empty() {
+        return Err(Error::invalid_client_params(
+            "proof specs must not be empty".into(),
+        ));
+    }
+    Ok(specs
+        .iter()
+        .map(|bz| {
+            ProofSpec::decode(bz.as_slice())
+                .map_err(|e| Error::invalid_client_params(format!("invalid proof spec: {}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into())
+}
+
 pub fn gen_state_id(
     client_state: ClientState,
     consensus_state: ConsensusState,
@@ -113,3 +201,91 @@ pub fn gen_state_id(
         &consensus_state.try_into().unwrap(),
     )?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ibc::core::ics02_client::trust_threshold::TrustThreshold;
+    use ibc::core::ics24_host::identifier::ChainId;
+    use lcp_proto::google::protobuf::Timestamp;
+    use lcp_proto::ibc::core::commitment::v1::MerkleRoot;
+
+    fn client_state() -> Any {
+        #[allow(deprecated)]
+        let client_state = TendermintClientState::new(
+            ChainId::new("ibc".to_string(), 0),
+            TrustThreshold::ONE_THIRD,
+            Duration::from_secs(60 * 60),
+            Duration::from_secs(2 * 60 * 60),
+            Duration::from_secs(10),
+            Height::new(0, 10).try_into().unwrap(),
+            ProofSpecs::cosmos(),
+            vec!["upgrade".to_string(), "upgradedIBCState".to_string()],
+            AllowUpdate {
+                after_expiry: false,
+                after_misbehaviour: false,
+            },
+            None,
+        )
+        .unwrap();
+        ClientState(client_state).into()
+    }
+
+    fn consensus_state() -> Any {
+        let consensus_state = RawTmConsensusState {
+            timestamp: Some(Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            root: Some(MerkleRoot {
+                hash: vec![1u8; 32],
+            }),
+            next_validators_hash: vec![2u8; 32],
+        };
+        Any::new(
+            TENDERMINT_CONSENSUS_STATE_TYPE_URL.to_string(),
+            consensus_state.encode_to_vec(),
+        )
+    }
+
+    // appends a varint field with a number that the tendermint states don't define
+    fn with_unknown_field(any: Any) -> Any {
+        let any: ProtoAny = any.into();
+        let mut value = any.value;
+        value.extend([0xf8, 0x07, 0x01]);
+        Any::new(any.type_url, value)
+    }
+
+    #[test]
+    fn test_migrate_states() {
+        let any_client_state = client_state();
+        let migrated = migrate_client_state(any_client_state.clone()).unwrap();
+        assert_eq!(migrated, any_client_state);
+        let client_state = ClientState::try_from(migrated).unwrap();
+        assert_eq!(
+            Height::from(client_state.latest_height()),
+            Height::new(0, 10)
+        );
+
+        let any_consensus_state = consensus_state();
+        let migrated = migrate_consensus_state(any_consensus_state.clone()).unwrap();
+        assert_eq!(migrated, any_consensus_state);
+        assert!(ConsensusState::try_from(migrated).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_states_with_unknown_fields() {
+        let any_client_state = with_unknown_field(client_state());
+        // the field is dropped silently by decoding the state
+        assert!(ClientState::try_from(any_client_state.clone()).is_ok());
+        assert!(migrate_client_state(any_client_state).is_err());
+
+        let any_consensus_state = with_unknown_field(consensus_state());
+        assert!(ConsensusState::try_from(any_consensus_state.clone()).is_ok());
+        assert!(migrate_consensus_state(any_consensus_state).is_err());
+
+        // the states of another client type
+        assert!(migrate_client_state(consensus_state()).is_err());
+        assert!(migrate_consensus_state(client_state()).is_err());
+    }
+}
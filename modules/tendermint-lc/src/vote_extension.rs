@@ -0,0 +1,121 @@
+use crate::errors::Error;
+use crate::message::ExtendedHeader;
+use crate::prelude::*;
+use alloc::collections::BTreeSet;
+use ibc::core::ics02_client::header::Header as Ics02Header;
+use light_client::types::VoteExtensionPolicy;
+use prost::encoding::encode_varint;
+use tendermint::block::CommitSig;
+use tendermint::Signature;
+
+/// Returns the bytes that a validator signs for a vote extension
+///
+/// This is the length-delimited protobuf encoding of `CanonicalVoteExtension` of CometBFT, where the
+/// fields with the default values are omitted.
+pub fn vote_extension_sign_bytes(
+    extension: &[u8],
+    height: u64,
+    round: u32,
+    chain_id: &str,
+) -> Vec<u8> {
+    let mut bz = Vec::new();
+    if !extension.is_empty() {
+        bz.push(0x0a);
+        encode_varint(extension.len() as u64, &mut bz);
+        bz.extend_from_slice(extension);
+    }
+    if height != 0 {
+        bz.push(0x11);
+        bz.extend_from_slice(&(height as i64).to_le_bytes());
+    }
+    if round != 0 {
+        bz.push(0x19);
+        bz.extend_from_slice(&(round as i64).to_le_bytes());
+    }
+    if !chain_id.is_empty() {
+        bz.push(0x22);
+        encode_varint(chain_id.len() as u64, &mut bz);
+        bz.extend_from_slice(chain_id.as_bytes());
+    }
+    let mut sign_bytes = Vec::with_capacity(bz.len() + 2);
+    encode_varint(bz.len() as u64, &mut sign_bytes);
+    sign_bytes.extend(bz);
+    sign_bytes
+}
+
+/// Check the vote extensions of the header against the policy of the client
+///
+/// With `VoteExtensionPolicy::Verify`, every validator that committed the block must have exactly one
+/// vote extension, whose signature is verified with the key in the validator set of the header.
+pub(crate) fn check_vote_extensions(
+    policy: VoteExtensionPolicy,
+    header: &ExtendedHeader,
+) -> Result<(), Error> {
+    match policy {
+        VoteExtensionPolicy::Ignore => return Ok(()),
+        VoteExtensionPolicy::Reject if header.extended_signatures.is_empty() => return Ok(()),
+        VoteExtensionPolicy::Reject => {
+            return Err(Error::vote_extensions_rejected(
+                header.header.height().into(),
+            ))
+        }
+        VoteExtensionPolicy::Verify => {}
+    }
+
+    let signed_header = &header.header.signed_header;
+    let commit = &signed_header.commit;
+    let mut committed: BTreeSet<Vec<u8>> = commit
+        .signatures
+        .iter()
+        .filter_map(|sig| match sig {
+            CommitSig::BlockIdFlagCommit {
+                validator_address, ..
+            } => Some(validator_address.as_bytes().to_vec()),
+            _ => None,
+        })
+        .collect();
+    for sig in header.extended_signatures.iter() {
+        // each validator is removed once verified, so that a duplicate is rejected
+        if !committed.remove(&sig.validator_address) {
+            return Err(Error::invalid_vote_extension(format!(
+                "validator did not commit the block or has a duplicate vote extension: address={:?}",
+                sig.validator_address
+            )));
+        }
+        let validator = header
+            .header
+            .validator_set
+            .validators()
+            .iter()
+            .find(|v| v.address.as_bytes() == sig.validator_address.as_slice())
+            .ok_or_else(|| {
+                Error::invalid_vote_extension(format!(
+                    "validator not found in the validator set: address={:?}",
+                    sig.validator_address
+                ))
+            })?;
+        let signature = Signature::try_from(sig.extension_signature.as_slice())
+            .map_err(|e| Error::invalid_vote_extension(e.to_string()))?;
+        let sign_bytes = vote_extension_sign_bytes(
+            &sig.extension,
+            commit.height.value(),
+            commit.round.value(),
+            signed_header.header.chain_id.as_str(),
+        );
+        validator
+            .verify_signature(&sign_bytes, &signature)
+            .map_err(|e| {
+                Error::invalid_vote_extension(format!(
+                    "invalid signature: address={:?} err={}",
+                    sig.validator_address, e
+                ))
+            })?;
+    }
+    if !committed.is_empty() {
+        return Err(Error::invalid_vote_extension(format!(
+            "missing vote extensions of the validators that committed the block: count={}",
+            committed.len()
+        )));
+    }
+    Ok(())
+}
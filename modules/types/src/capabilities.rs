@@ -0,0 +1,47 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The format of the ICS-23 commitment proofs of Cosmos SDK chains
+pub const ICS23_PROOF_FORMAT: &str = "ics23";
+
+/// Capabilities are the optional features that a light client implementation supports
+///
+/// The host tooling checks them instead of assuming the behavior of each client type.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// `update_client` accepts a misbehaviour as the client message and freezes the client on it
+    pub misbehaviour: bool,
+    /// The client can be upgraded to the client state that the counterparty chain commits to before an upgrade
+    pub upgrade: bool,
+    /// The formats of the proofs that the state verifications accept, e.g. `ics23`
+    pub proof_formats: Vec<String>,
+    /// `update_client` accepts a header that is split into multiple parts
+    pub multipart_headers: bool,
+    /// `verify_key_value` verifies the values of raw keys
+    pub key_value_verification: bool,
+    /// The operators can change the parameters of the client after its creation
+    pub client_params: bool,
+}
+
+impl Capabilities {
+    /// Returns true if the state verifications accept the proofs of the format
+    pub fn supports_proof_format(&self, format: &str) -> bool {
+        self.proof_formats.iter().any(|f| f == format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_proof_format() {
+        let capabilities = Capabilities {
+            proof_formats: vec![ICS23_PROOF_FORMAT.to_string()],
+            ..Default::default()
+        };
+        assert!(capabilities.supports_proof_format("ics23"));
+        assert!(!capabilities.supports_proof_format("mpt"));
+        assert!(!Capabilities::default().supports_proof_format("ics23"));
+    }
+}
@@ -53,6 +53,16 @@ define_error! {
             |e| {
                 format_args!("mrenclave: bytes length must be 32, but got {:?}", e.bz)
             },
+        InvalidVerificationMode
+            { mode: String }
+            |e| {
+                format_args!("invalid verification mode: expected one of `skipping`, `sequential` or `adjacent`, but got `{}`", e.mode)
+            },
+        InvalidVoteExtensionPolicy
+            { policy: String }
+            |e| {
+                format_args!("invalid vote extension policy: expected one of `ignore`, `verify` or `reject`, but got `{}`", e.policy)
+            },
         HexParseError
             [TraceError<hex::FromHexError>]
             |_| { "hex parse error" },
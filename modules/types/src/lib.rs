@@ -2,20 +2,26 @@
 extern crate alloc;
 
 pub use any::Any;
+pub use capabilities::{Capabilities, ICS23_PROOF_FORMAT};
 pub use errors::{TimeError, TypeError};
 pub use height::Height;
 pub use host::ClientId;
 /// re-export
 pub use lcp_proto as proto;
+pub use params::ClientParams;
 pub use sgx::Mrenclave;
 pub use time::{nanos_to_duration, Time, MAX_UNIX_TIMESTAMP_NANOS};
+pub use verification::{VerificationMode, VoteExtensionPolicy};
 
 mod any;
+mod capabilities;
 mod errors;
 mod height;
 mod host;
+mod params;
 mod sgx;
 mod time;
+mod verification;
 
 mod prelude {
     pub use core::prelude::v1::*;
@@ -0,0 +1,41 @@
+use crate::prelude::*;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// ClientParams are the policy values of a client that the operators can change after its creation
+///
+/// They override the corresponding values of the client state in the verification only,
+/// so that the state IDs of the client are unchanged. A field that is None keeps the current value.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientParams {
+    /// The trusting period of the client, e.g. after the counterparty chain shortens its unbonding period
+    pub trusting_period: Option<Duration>,
+    /// The maximum clock drift between the header time and the current time that the client tolerates
+    pub max_clock_drift: Option<Duration>,
+    /// The specs of the commitment proofs, each of which is a proto-encoded `cosmos.ics23.v1.ProofSpec`
+    pub proof_specs: Option<Vec<Vec<u8>>>,
+}
+
+impl ClientParams {
+    /// Returns true if no parameter is changed
+    pub fn is_empty(&self) -> bool {
+        self.trusting_period.is_none()
+            && self.max_clock_drift.is_none()
+            && self.proof_specs.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_params_is_empty() {
+        assert!(ClientParams::default().is_empty());
+        assert!(!ClientParams {
+            max_clock_drift: Some(Duration::from_secs(10)),
+            ..Default::default()
+        }
+        .is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::errors::TypeError;
+use crate::prelude::*;
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// VerificationMode restricts the headers that a light client accepts to update a client
+///
+/// It lets operators trade the verification cost against the security assumptions per chain.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationMode {
+    /// A header can skip heights from any stored trusted height if the trusted validators that
+    /// signed it have at least the trust level of the client state
+    #[default]
+    Skipping,
+    /// A header must be adjacent to the latest height of the client
+    Sequential,
+    /// A header must be adjacent to its trusted height, which can be any stored height
+    Adjacent,
+}
+
+impl VerificationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skipping => "skipping",
+            Self::Sequential => "sequential",
+            Self::Adjacent => "adjacent",
+        }
+    }
+}
+
+impl Display for VerificationMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for VerificationMode {
+    type Err = TypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skipping" => Ok(Self::Skipping),
+            "sequential" => Ok(Self::Sequential),
+            "adjacent" => Ok(Self::Adjacent),
+            _ => Err(TypeError::invalid_verification_mode(s.to_string())),
+        }
+    }
+}
+
+/// VoteExtensionPolicy determines how a light client handles the vote extensions of ABCI++ in a header
+///
+/// The vote extensions are not part of the state that a client tracks, so the policy only affects
+/// which headers are accepted.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteExtensionPolicy {
+    /// The vote extensions are dropped without verification
+    #[default]
+    Ignore,
+    /// Each validator that committed the block must have signed a valid vote extension
+    Verify,
+    /// A header with vote extensions is rejected
+    Reject,
+}
+
+impl VoteExtensionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Verify => "verify",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+impl Display for VoteExtensionPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for VoteExtensionPolicy {
+    type Err = TypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(Self::Ignore),
+            "verify" => Ok(Self::Verify),
+            "reject" => Ok(Self::Reject),
+            _ => Err(TypeError::invalid_vote_extension_policy(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_mode_str() {
+        for mode in [
+            VerificationMode::Skipping,
+            VerificationMode::Sequential,
+            VerificationMode::Adjacent,
+        ] {
+            assert_eq!(mode.as_str().parse::<VerificationMode>().unwrap(), mode);
+        }
+        assert!("bisection".parse::<VerificationMode>().is_err());
+        assert_eq!(VerificationMode::default(), VerificationMode::Skipping);
+    }
+
+    #[test]
+    fn test_vote_extension_policy_str() {
+        for policy in [
+            VoteExtensionPolicy::Ignore,
+            VoteExtensionPolicy::Verify,
+            VoteExtensionPolicy::Reject,
+        ] {
+            assert_eq!(
+                policy.as_str().parse::<VoteExtensionPolicy>().unwrap(),
+                policy
+            );
+        }
+        assert!("require".parse::<VoteExtensionPolicy>().is_err());
+        assert_eq!(VoteExtensionPolicy::default(), VoteExtensionPolicy::Ignore);
+    }
+}
@@ -86,7 +86,42 @@ impl CompileCmd {
             r#"#[cfg_attr(feature = "json-schema", derive(::schemars::JsonSchema))]"#;
         let attrs_ord = "#[derive(Eq, PartialOrd, Ord)]";
         let attrs_serde_default = r#"#[serde(default)]"#;
-        let compilation = tonic_build::configure()
+        // the client messages are shared with the CosmWasm LCP client contracts,
+        // whose JSON messages encode bytes in base64 like `cosmwasm_std::Binary`
+        let attrs_cosmwasm = r#"#[cfg_attr(
+    feature = "cosmwasm",
+    derive(::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)
+)]
+#[cfg_attr(feature = "cosmwasm", serde(deny_unknown_fields))]"#;
+        let attrs_cosmwasm_binary = r#"#[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]"#;
+        let cosmwasm_messages = [
+            ".ibc.lightclients.lcp.v1.UpdateClientMessage",
+            ".ibc.lightclients.lcp.v1.RegisterEnclaveKeyMessage",
+            ".ibc.lightclients.lcp.v1.VerifyMembershipMessage",
+        ];
+        let cosmwasm_binary_fields = [
+            ".ibc.lightclients.lcp.v1.UpdateClientMessage.proxy_message",
+            ".ibc.lightclients.lcp.v1.UpdateClientMessage.signer",
+            ".ibc.lightclients.lcp.v1.UpdateClientMessage.signature",
+            ".ibc.lightclients.lcp.v1.RegisterEnclaveKeyMessage.signature",
+            ".ibc.lightclients.lcp.v1.RegisterEnclaveKeyMessage.signing_cert",
+            ".ibc.lightclients.lcp.v1.VerifyMembershipMessage.proxy_message",
+            ".ibc.lightclients.lcp.v1.VerifyMembershipMessage.signer",
+            ".ibc.lightclients.lcp.v1.VerifyMembershipMessage.signature",
+        ];
+        let config = cosmwasm_messages
+            .iter()
+            .fold(tonic_build::configure(), |config, path| {
+                config.type_attribute(path, attrs_cosmwasm)
+            });
+        let config = cosmwasm_binary_fields.iter().fold(config, |config, path| {
+            config.field_attribute(path, attrs_cosmwasm_binary)
+        });
+        let compilation = config
             .build_client(true)
             .compile_well_known_types(true)
             .client_mod_attribute(".", r#"#[cfg(feature = "client")]"#)
@@ -100,8 +135,10 @@ impl CompileCmd {
             .type_attribute(".ibc.core.client.v1.Height", attrs_ord)
             .type_attribute(".ibc.core.client.v1.Height", attrs_jsonschema)
             .field_attribute(".ibc.core.client.v1.Height", attrs_serde_default)
+            .type_attribute(".lcp.ecall.v1", attrs_serde)
             .type_attribute(".lcp.service.enclave.v1", attrs_serde)
             .type_attribute(".lcp.service.elc.v1", attrs_serde)
+            .type_attribute(".lcp.service.elc.v1beta", attrs_serde)
             .file_descriptor_set_path(descriptor_path)
             .compile(&protos, &includes);
 
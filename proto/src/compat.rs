@@ -0,0 +1,322 @@
+//! Conversions between the versioned ELC packages.
+//!
+//! `lcp.service.elc.v1beta` is wire-compatible with `lcp.service.elc.v1`. Downgrading
+//! fails if a field that only exists in v1 is set, since v1beta would silently ignore it,
+//! e.g. a dry run would become a real update. Upgrading is fallible only where v1
+//! declares a field as non-nullable that v1beta producers may have omitted.
+#![allow(deprecated)]
+
+use crate::lcp::service::elc::{v1, v1beta};
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatError {
+    /// a field that is required in the target version is missing in the source message
+    MissingField {
+        message: &'static str,
+        field: &'static str,
+    },
+    /// a field that is set in the source message doesn't exist in the target version
+    UnsupportedField {
+        message: &'static str,
+        field: &'static str,
+    },
+}
+
+impl fmt::Display for CompatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatError::MissingField { message, field } => {
+                write!(
+                    f,
+                    "missing required field: message={} field={}",
+                    message, field
+                )
+            }
+            CompatError::UnsupportedField { message, field } => {
+                write!(
+                    f,
+                    "field is not supported by the target version: message={} field={}",
+                    message, field
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CompatError {}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+macro_rules! impl_bidirectional_from {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl From<v1beta::$name> for v1::$name {
+            fn from(msg: v1beta::$name) -> Self {
+                Self { $($field: msg.$field),* }
+            }
+        }
+
+        impl From<v1::$name> for v1beta::$name {
+            fn from(msg: v1::$name) -> Self {
+                Self { $($field: msg.$field),* }
+            }
+        }
+    };
+    // fields after `;` only exist in v1: they are defaulted on upgrade and must be unset on downgrade
+    ($name:ident { $($field:ident),* $(,)? ; $($v1_field:ident),* $(,)? }) => {
+        impl From<v1beta::$name> for v1::$name {
+            fn from(msg: v1beta::$name) -> Self {
+                Self {
+                    $($field: msg.$field,)*
+                    $($v1_field: Default::default(),)*
+                }
+            }
+        }
+
+        impl TryFrom<v1::$name> for v1beta::$name {
+            type Error = CompatError;
+            fn try_from(msg: v1::$name) -> Result<Self, Self::Error> {
+                $(
+                    if !is_default(&msg.$v1_field) {
+                        return Err(CompatError::UnsupportedField {
+                            message: stringify!($name),
+                            field: stringify!($v1_field),
+                        });
+                    }
+                )*
+                Ok(Self { $($field: msg.$field),* })
+            }
+        }
+    };
+}
+
+impl_bidirectional_from!(MsgCreateClient {
+    client_state,
+    consensus_state,
+    signer;
+    commitment_prefix,
+    verification_mode,
+    prove_genesis,
+    max_clock_drift,
+    vote_extension_policy,
+    digest_algorithm,
+    signature_format,
+    message_schema_version
+});
+impl_bidirectional_from!(MsgCreateClientResponse {
+    client_id,
+    message,
+    signer,
+    signature;
+    co_signers,
+    co_signatures
+});
+impl_bidirectional_from!(MsgUpdateClient {
+    client_id,
+    header,
+    include_state,
+    signer;
+    state_filters,
+    detect_misbehaviour,
+    expires_at,
+    dry_run
+});
+impl_bidirectional_from!(MsgUpdateClientResponse {
+    message,
+    signer,
+    signature;
+    co_signers,
+    co_signatures
+});
+impl_bidirectional_from!(MsgAggregateMessages {
+    signer,
+    messages,
+    signatures;
+    any_messages,
+    signers,
+    client_id
+});
+impl_bidirectional_from!(MsgAggregateMessagesResponse {
+    message,
+    signer,
+    signature;
+    co_signers,
+    co_signatures,
+    constituent_signers,
+    constituent_signers_signature
+});
+impl_bidirectional_from!(MsgVerifyMembershipResponse {
+    message,
+    signer,
+    signature;
+    co_signers,
+    co_signatures,
+    artifact_hash
+});
+impl_bidirectional_from!(MsgVerifyNonMembershipResponse {
+    message,
+    signer,
+    signature;
+    co_signers,
+    co_signatures
+});
+
+impl TryFrom<v1beta::MsgVerifyMembership> for v1::MsgVerifyMembership {
+    type Error = CompatError;
+    fn try_from(msg: v1beta::MsgVerifyMembership) -> Result<Self, Self::Error> {
+        if msg.proof_height.is_none() {
+            return Err(CompatError::MissingField {
+                message: "MsgVerifyMembership",
+                field: "proof_height",
+            });
+        }
+        Ok(Self {
+            client_id: msg.client_id,
+            prefix: msg.prefix,
+            path: msg.path,
+            value: msg.value,
+            proof_height: msg.proof_height,
+            proof: msg.proof,
+            signer: msg.signer,
+            expires_at: Default::default(),
+            consensus_height: None,
+            store_artifact: false,
+        })
+    }
+}
+
+impl TryFrom<v1::MsgVerifyMembership> for v1beta::MsgVerifyMembership {
+    type Error = CompatError;
+    fn try_from(msg: v1::MsgVerifyMembership) -> Result<Self, Self::Error> {
+        let field = if msg.expires_at != 0 {
+            Some("expires_at")
+        } else if msg.consensus_height.is_some() {
+            Some("consensus_height")
+        } else if msg.store_artifact {
+            Some("store_artifact")
+        } else {
+            None
+        };
+        if let Some(field) = field {
+            return Err(CompatError::UnsupportedField {
+                message: "MsgVerifyMembership",
+                field,
+            });
+        }
+        Ok(Self {
+            client_id: msg.client_id,
+            prefix: msg.prefix,
+            path: msg.path,
+            value: msg.value,
+            proof_height: msg.proof_height,
+            proof: msg.proof,
+            signer: msg.signer,
+        })
+    }
+}
+
+impl TryFrom<v1beta::MsgVerifyNonMembership> for v1::MsgVerifyNonMembership {
+    type Error = CompatError;
+    fn try_from(msg: v1beta::MsgVerifyNonMembership) -> Result<Self, Self::Error> {
+        if msg.proof_height.is_none() {
+            return Err(CompatError::MissingField {
+                message: "MsgVerifyNonMembership",
+                field: "proof_height",
+            });
+        }
+        Ok(Self {
+            client_id: msg.client_id,
+            prefix: msg.prefix,
+            path: msg.path,
+            proof_height: msg.proof_height,
+            proof: msg.proof,
+            signer: msg.signer,
+            expires_at: Default::default(),
+        })
+    }
+}
+
+impl TryFrom<v1::MsgVerifyNonMembership> for v1beta::MsgVerifyNonMembership {
+    type Error = CompatError;
+    fn try_from(msg: v1::MsgVerifyNonMembership) -> Result<Self, Self::Error> {
+        if msg.expires_at != 0 {
+            return Err(CompatError::UnsupportedField {
+                message: "MsgVerifyNonMembership",
+                field: "expires_at",
+            });
+        }
+        Ok(Self {
+            client_id: msg.client_id,
+            prefix: msg.prefix,
+            path: msg.path,
+            proof_height: msg.proof_height,
+            proof: msg.proof,
+            signer: msg.signer,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ibc::core::client::v1::Height;
+    use prost::Message;
+
+    #[test]
+    fn test_wire_compatibility() {
+        let msg = v1::MsgUpdateClient {
+            client_id: "07-tendermint-0".into(),
+            header: None,
+            include_state: true,
+            signer: vec![1u8; 20],
+            state_filters: vec![],
+            detect_misbehaviour: false,
+            expires_at: 0,
+            dry_run: false,
+        };
+        let bz = msg.encode_to_vec();
+        let beta = v1beta::MsgUpdateClient::decode(bz.as_slice()).unwrap();
+        assert_eq!(beta, msg.clone().try_into().unwrap());
+        assert_eq!(v1::MsgUpdateClient::from(beta), msg);
+    }
+
+    #[test]
+    fn test_downgrade_rejects_v1_fields() {
+        let msg = v1::MsgUpdateClient {
+            client_id: "07-tendermint-0".into(),
+            dry_run: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            v1beta::MsgUpdateClient::try_from(msg),
+            Err(CompatError::UnsupportedField {
+                message: "MsgUpdateClient",
+                field: "dry_run",
+            })
+        );
+        let msg = v1::MsgVerifyMembership {
+            client_id: "07-tendermint-0".into(),
+            store_artifact: true,
+            ..Default::default()
+        };
+        assert!(v1beta::MsgVerifyMembership::try_from(msg).is_err());
+    }
+
+    #[test]
+    fn test_verify_membership_requires_proof_height() {
+        let mut msg = v1beta::MsgVerifyMembership {
+            client_id: "07-tendermint-0".into(),
+            proof_height: None,
+            ..Default::default()
+        };
+        assert!(v1::MsgVerifyMembership::try_from(msg.clone()).is_err());
+        msg.proof_height = Some(Height {
+            revision_number: 0,
+            revision_height: 1,
+        });
+        assert!(v1::MsgVerifyMembership::try_from(msg).is_ok());
+    }
+}
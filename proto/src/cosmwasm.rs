@@ -0,0 +1,71 @@
+//! CosmWasm-compatible JSON encodings of the LCP client messages
+//!
+//! The messages are generated from `ibc.lightclients.lcp.v1` with the serde attributes of the `cosmwasm` feature,
+//! so that a CosmWasm LCP client contract shares the definitions with the enclave and the relayer.
+//! As `cosmwasm_std::Binary`, the bytes fields are encoded in base64.
+
+pub use crate::ibc::lightclients::lcp::v1::{
+    RegisterEnclaveKeyMessage, UpdateClientMessage, VerifyMembershipMessage,
+};
+use serde::{Deserialize, Serialize};
+
+/// The execute messages of a CosmWasm LCP client contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ::schemars::JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ExecuteMsg {
+    RegisterEnclaveKey(RegisterEnclaveKeyMessage),
+    UpdateClient(UpdateClientMessage),
+    VerifyMembership(VerifyMembershipMessage),
+}
+
+/// serde functions that encode bytes in base64 as `cosmwasm_std::Binary`
+pub mod binary {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bz: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bz))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        base64::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute_msg_json() {
+        let msg = ExecuteMsg::UpdateClient(UpdateClientMessage {
+            proxy_message: vec![1, 2, 3],
+            signer: vec![0xff; 20],
+            signature: vec![],
+        });
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "update_client": {
+                    "proxy_message": "AQID",
+                    "signer": "//////////////////////////8=",
+                    "signature": ""
+                }
+            })
+        );
+        assert_eq!(serde_json::from_value::<ExecuteMsg>(json).unwrap(), msg);
+
+        // an unknown field is rejected as by the contracts
+        assert!(serde_json::from_value::<ExecuteMsg>(serde_json::json!({
+            "verify_membership": {
+                "proxy_message": "",
+                "signer": "",
+                "signature": "",
+                "height": 1
+            }
+        }))
+        .is_err());
+    }
+}
@@ -44,16 +44,46 @@ pub mod ibc {
 }
 
 pub mod lcp {
+    pub mod ecall {
+        pub mod v1 {
+            include_proto!("lcp.ecall.v1.rs");
+        }
+    }
+    pub mod lightclients {
+        pub mod optimism {
+            pub mod v1 {
+                include_proto!("lcp.lightclients.optimism.v1.rs");
+            }
+        }
+        pub mod tendermint {
+            pub mod v1 {
+                include_proto!("lcp.lightclients.tendermint.v1.rs");
+            }
+        }
+    }
     pub mod service {
         pub mod enclave {
             pub mod v1 {
                 include_proto!("lcp.service.enclave.v1.rs");
             }
         }
+        pub mod events {
+            pub mod v1 {
+                include_proto!("lcp.service.events.v1.rs");
+            }
+        }
         pub mod elc {
             pub mod v1 {
                 include_proto!("lcp.service.elc.v1.rs");
             }
+            #[allow(deprecated)]
+            pub mod v1beta {
+                include_proto!("lcp.service.elc.v1beta.rs");
+            }
         }
     }
 }
+
+pub mod compat;
+#[cfg(feature = "cosmwasm")]
+pub mod cosmwasm;
@@ -1,23 +1,89 @@
+#[cfg_attr(
+    feature = "cosmwasm",
+    derive(::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)
+)]
+#[cfg_attr(feature = "cosmwasm", serde(deny_unknown_fields))]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateClientMessage {
     #[prost(bytes = "vec", tag = "1")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
     pub proxy_message: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "2")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
     pub signature: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(
+    feature = "cosmwasm",
+    derive(::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)
+)]
+#[cfg_attr(feature = "cosmwasm", serde(deny_unknown_fields))]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RegisterEnclaveKeyMessage {
     #[prost(string, tag = "1")]
     pub report: ::prost::alloc::string::String,
     #[prost(bytes = "vec", tag = "2")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
     pub signature: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
     pub signing_cert: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(
+    feature = "cosmwasm",
+    derive(::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)
+)]
+#[cfg_attr(feature = "cosmwasm", serde(deny_unknown_fields))]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipMessage {
+    /// the message of the commitment returned by the enclave
+    #[prost(bytes = "vec", tag = "1")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
+    pub proxy_message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    #[cfg_attr(
+        feature = "cosmwasm",
+        serde(with = "crate::cosmwasm::binary"),
+        schemars(with = "String")
+    )]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClientState {
@@ -37,6 +103,12 @@ pub struct ClientState {
     /// e.g. INTEL-SA-XXXXX
     #[prost(string, repeated, tag = "6")]
     pub allowed_advisory_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// the digest algorithm of the commitments, which must match the one of the ELC client. empty means `keccak256`
+    #[prost(string, tag = "7")]
+    pub digest_algorithm: ::prost::alloc::string::String,
+    /// the signature format of the commitments, which must match the one of the ELC client. empty means `recoverable`
+    #[prost(string, tag = "8")]
+    pub signature_format: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -0,0 +1,1311 @@
+/// ECallCommand is a command that the host submits to the enclave.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ECallCommand {
+    #[prost(message, optional, tag = "1")]
+    pub ctx: ::core::option::Option<CommandContext>,
+    #[prost(oneof = "e_call_command::Cmd", tags = "3, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 50, 51, 52, 53, 54, 55")]
+    pub cmd: ::core::option::Option<e_call_command::Cmd>,
+}
+/// Nested message and enum types in `ECallCommand`.
+pub mod e_call_command {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Cmd {
+        #[prost(message, tag = "3")]
+        UpdateClient(super::UpdateClient),
+        #[prost(message, tag = "10")]
+        GenerateEnclaveKey(super::GenerateEnclaveKey),
+        #[prost(message, tag = "11")]
+        IasRemoteAttestation(super::IasRemoteAttestation),
+        /// only accepted by the enclaves built with the `sgx-sw` feature
+        #[prost(message, tag = "12")]
+        SimulateRemoteAttestation(super::SimulateRemoteAttestation),
+        #[prost(message, tag = "13")]
+        ValidateDcapCollateral(super::ValidateDcapCollateral),
+        #[prost(message, tag = "14")]
+        StartInstance(super::StartInstance),
+        #[prost(message, tag = "15")]
+        UpdateOperators(super::UpdateOperators),
+        #[prost(message, tag = "16")]
+        QueryOperators(super::QueryOperators),
+        #[prost(message, tag = "17")]
+        QueryBuildInfo(super::QueryBuildInfo),
+        #[prost(message, tag = "18")]
+        BeginSession(super::BeginSession),
+        #[prost(message, tag = "19")]
+        OpenSession(super::OpenSession),
+        #[prost(message, tag = "20")]
+        RevokeEnclaveKeys(super::RevokeEnclaveKeys),
+        #[prost(message, tag = "21")]
+        UnsealStoreKey(super::UnsealStoreKey),
+        #[prost(message, tag = "22")]
+        FinalizeStoreSequence(super::FinalizeStoreSequence),
+        #[prost(message, tag = "30")]
+        InitClient(super::InitClient),
+        #[prost(message, tag = "31")]
+        AggregateMessages(super::AggregateMessages),
+        #[prost(message, tag = "32")]
+        VerifyMembership(super::VerifyMembership),
+        #[prost(message, tag = "33")]
+        VerifyNonMembership(super::VerifyNonMembership),
+        #[prost(message, tag = "34")]
+        VerifyMembershipBatch(super::VerifyMembershipBatch),
+        #[prost(message, tag = "35")]
+        VerifyKeyValue(super::VerifyKeyValue),
+        #[prost(message, tag = "36")]
+        PruneClient(super::PruneClient),
+        #[prost(message, tag = "37")]
+        SignLatestHeights(super::SignLatestHeights),
+        #[prost(message, tag = "38")]
+        UpdateClientParams(super::UpdateClientParams),
+        #[prost(message, tag = "39")]
+        ResignLatestState(super::ResignLatestState),
+        #[prost(message, tag = "40")]
+        MigrateClient(super::MigrateClient),
+        #[prost(message, tag = "50")]
+        QueryClient(super::QueryClient),
+        #[prost(message, tag = "51")]
+        ListClients(super::ListClients),
+        #[prost(message, tag = "52")]
+        QueryConsensusHeights(super::QueryConsensusHeights),
+        #[prost(message, tag = "53")]
+        QueryCapabilities(super::QueryCapabilities),
+        #[prost(message, tag = "54")]
+        ValidateStore(super::ValidateStore),
+        #[prost(message, tag = "55")]
+        VerifyAggregateMessages(super::VerifyAggregateMessages),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandContext {
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "1")]
+    pub current_timestamp: u64,
+    /// empty if the command doesn't use an enclave key
+    #[prost(bytes = "vec", tag = "2")]
+    pub sealed_ek: ::prost::alloc::vec::Vec<u8>,
+    /// the incoming key that co-signs commitments while `sealed_ek` is being rotated out
+    #[prost(bytes = "vec", tag = "3")]
+    pub co_sealed_ek: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub operator_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(uint64, tag = "5")]
+    pub tx_id: u64,
+    /// the token of the session opened by the host, or empty if no session is open
+    #[prost(bytes = "vec", tag = "6")]
+    pub session_token: ::prost::alloc::vec::Vec<u8>,
+    /// the keys of the other signers of the messages to aggregate
+    #[prost(bytes = "vec", repeated, tag = "7")]
+    pub constituent_sealed_eks: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the attestation of each key in `constituent_sealed_eks`
+    #[prost(message, repeated, tag = "9")]
+    pub constituent_attestations: ::prost::alloc::vec::Vec<KeyAttestation>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KeyAttestation {
+    #[prost(oneof = "key_attestation::Attestation", tags = "1, 2")]
+    pub attestation: ::core::option::Option<key_attestation::Attestation>,
+}
+/// Nested message and enum types in `KeyAttestation`.
+pub mod key_attestation {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Attestation {
+        #[prost(message, tag = "1")]
+        Ias(super::EndorsedAttestationVerificationReport),
+        #[prost(message, tag = "2")]
+        Dcap(super::DcapQuoteBundle),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EndorsedAttestationVerificationReport {
+    /// the JSON of the report as returned by IAS
+    #[prost(string, tag = "1")]
+    pub avr: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signing_cert: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DcapQuoteBundle {
+    #[prost(bytes = "vec", tag = "1")]
+    pub quote: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub collateral: ::core::option::Option<DcapCollateral>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DcapCollateral {
+    #[prost(string, tag = "1")]
+    pub tcb_info: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub tcb_info_issuer_chain: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub qe_identity: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub qe_identity_issuer_chain: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "5")]
+    pub pck_crl: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "6")]
+    pub pck_crl_issuer_chain: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "7")]
+    pub root_ca_crl: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GenerateEnclaveKey {}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IasRemoteAttestation {
+    #[prost(bytes = "vec", tag = "1")]
+    pub target_enclave_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub spid: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub ias_key: ::prost::alloc::vec::Vec<u8>,
+    /// `linkable` or `unlinkable`
+    #[prost(string, tag = "4")]
+    pub quote_type: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "5")]
+    pub advisory_policy: ::core::option::Option<AdvisoryPolicy>,
+    #[prost(string, repeated, tag = "6")]
+    pub applied_mitigations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AdvisoryPolicy {
+    #[prost(message, repeated, tag = "1")]
+    pub advisories: ::prost::alloc::vec::Vec<AdvisoryRule>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AdvisoryRule {
+    #[prost(string, tag = "1")]
+    pub advisory_id: ::prost::alloc::string::String,
+    /// unix timestamp in seconds
+    #[prost(uint64, optional, tag = "2")]
+    pub allowed_until: ::core::option::Option<u64>,
+    #[prost(string, repeated, tag = "3")]
+    pub required_mitigations: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SimulateRemoteAttestation {
+    #[prost(bytes = "vec", tag = "1")]
+    pub target_enclave_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, repeated, tag = "2")]
+    pub advisory_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub isv_enclave_quote_status: ::prost::alloc::string::String,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateDcapCollateral {
+    #[prost(bytes = "vec", tag = "1")]
+    pub target_enclave_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub bundle: ::core::option::Option<DcapQuoteBundle>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StartInstance {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub store_checkpoint: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateOperators {
+    #[prost(message, optional, tag = "1")]
+    pub operator_set: ::core::option::Option<OperatorSet>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OperatorSet {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub operators: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(uint64, tag = "2")]
+    pub threshold: u64,
+    #[prost(uint64, tag = "3")]
+    pub nonce: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryOperators {}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryBuildInfo {}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BeginSession {}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenSession {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeEnclaveKeys {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub addresses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnsealStoreKey {
+    /// empty to generate a new key
+    #[prost(bytes = "vec", tag = "1")]
+    pub sealed_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FinalizeStoreSequence {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InitClient {
+    #[prost(message, optional, tag = "1")]
+    pub client_state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+    #[prost(message, optional, tag = "2")]
+    pub consensus_state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub commitment_prefix: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// `skipping`, `sequential` or `adjacent`, or empty if not set
+    #[prost(string, tag = "4")]
+    pub verification_mode: ::prost::alloc::string::String,
+    #[prost(bool, tag = "5")]
+    pub prove_genesis: bool,
+    /// nanoseconds
+    #[prost(uint64, optional, tag = "6")]
+    pub max_clock_drift: ::core::option::Option<u64>,
+    /// `ignore`, `verify` or `reject`, or empty if not set
+    #[prost(string, tag = "7")]
+    pub vote_extension_policy: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "8")]
+    pub signature_scheme: ::core::option::Option<SignatureScheme>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "9")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "10")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// the version of the message schema that the verifier of the client accepts, or 1 if not set
+    #[prost(uint32, optional, tag = "11")]
+    pub message_schema_version: ::core::option::Option<u32>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignatureScheme {
+    /// `keccak256` or `sha256`
+    #[prost(string, tag = "1")]
+    pub digest: ::prost::alloc::string::String,
+    /// `recoverable`, `ethereum` or `compact`
+    #[prost(string, tag = "2")]
+    pub format: ::prost::alloc::string::String,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClient {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub header: ::core::option::Option<super::super::super::google::protobuf::Any>,
+    #[prost(bool, tag = "3")]
+    pub include_state: bool,
+    #[prost(string, repeated, tag = "4")]
+    pub state_filters: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bool, tag = "5")]
+    pub detect_misbehaviour: bool,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, optional, tag = "6")]
+    pub expires_at: ::core::option::Option<u64>,
+    #[prost(bool, tag = "7")]
+    pub dry_run: bool,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "8")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "9")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AggregateMessages {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "3")]
+    pub messages: ::prost::alloc::vec::Vec<super::super::super::google::protobuf::Any>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "6")]
+    pub current_timestamp: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembership {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub path: ::core::option::Option<CommitmentPath>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub proof_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, optional, tag = "7")]
+    pub expires_at: ::core::option::Option<u64>,
+    #[prost(message, optional, tag = "8")]
+    pub consensus_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "9")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "10")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyNonMembership {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub path: ::core::option::Option<CommitmentPath>,
+    #[prost(message, optional, tag = "4")]
+    pub proof_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, optional, tag = "6")]
+    pub expires_at: ::core::option::Option<u64>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "7")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "8")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipBatch {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub proof_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(message, repeated, tag = "4")]
+    pub items: ::prost::alloc::vec::Vec<VerifyMembershipItem>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, optional, tag = "5")]
+    pub expires_at: ::core::option::Option<u64>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "6")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "7")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipItem {
+    #[prost(message, optional, tag = "1")]
+    pub path: ::core::option::Option<CommitmentPath>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+}
+/// CommitmentPath is an ICS-24 path, or a path that is not defined in ICS-24
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommitmentPath {
+    #[prost(oneof = "commitment_path::Path", tags = "1, 2")]
+    pub path: ::core::option::Option<commitment_path::Path>,
+}
+/// Nested message and enum types in `CommitmentPath`.
+pub mod commitment_path {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Path {
+        /// e.g. `clients/07-tendermint-0/clientState`
+        #[prost(string, tag = "1")]
+        Ics24(::prost::alloc::string::String),
+        /// a UTF-8 string that the enclave passes to the light client as is
+        #[prost(bytes = "vec", tag = "2")]
+        Raw(::prost::alloc::vec::Vec<u8>),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyKeyValue {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    /// unset to verify the absence of the key
+    #[prost(bytes = "vec", optional, tag = "4")]
+    pub value: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, optional, tag = "5")]
+    pub proof_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, optional, tag = "7")]
+    pub expires_at: ::core::option::Option<u64>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "8")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "9")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PruneClient {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    /// nanoseconds
+    #[prost(uint64, tag = "2")]
+    pub margin: u64,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "3")]
+    pub current_timestamp: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignLatestHeights {
+    #[prost(string, repeated, tag = "1")]
+    pub client_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "2")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClientParams {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub params: ::core::option::Option<ClientParams>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "3")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientParams {
+    /// nanoseconds
+    #[prost(uint64, optional, tag = "1")]
+    pub trusting_period: ::core::option::Option<u64>,
+    /// nanoseconds
+    #[prost(uint64, optional, tag = "2")]
+    pub max_clock_drift: ::core::option::Option<u64>,
+    #[prost(message, optional, tag = "3")]
+    pub proof_specs: ::core::option::Option<ProofSpecs>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProofSpecs {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub specs: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResignLatestState {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "2")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MigrateClient {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub target_type_url: ::prost::alloc::string::String,
+    #[prost(uint32, optional, tag = "3")]
+    pub target_version: ::core::option::Option<u32>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "4")]
+    pub current_timestamp: u64,
+    #[prost(bytes = "vec", tag = "5")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryClient {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClients {
+    #[prost(message, optional, tag = "1")]
+    pub pagination: ::core::option::Option<PageRequest>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryConsensusHeights {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageRequest>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryCapabilities {
+    #[prost(oneof = "query_capabilities::Target", tags = "1, 2")]
+    pub target: ::core::option::Option<query_capabilities::Target>,
+}
+/// Nested message and enum types in `QueryCapabilities`.
+pub mod query_capabilities {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Target {
+        #[prost(string, tag = "1")]
+        ClientId(::prost::alloc::string::String),
+        #[prost(string, tag = "2")]
+        ClientStateTypeUrl(::prost::alloc::string::String),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateStore {
+    #[prost(message, optional, tag = "1")]
+    pub pagination: ::core::option::Option<PageRequest>,
+}
+/// verifies the signatures of a batch of the messages to aggregate without signing or storing anything
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyAggregateMessages {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub messages: ::prost::alloc::vec::Vec<super::super::super::google::protobuf::Any>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PageRequest {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+    #[prost(uint64, tag = "2")]
+    pub limit: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PageResponse {
+    #[prost(uint64, optional, tag = "1")]
+    pub next_offset: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "2")]
+    pub total: u64,
+}
+/// CommandResponse is the response of the enclave to an ECallCommand.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandResponse {
+    #[prost(oneof = "command_response::Res", tags = "2, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 50, 51, 52, 53, 54, 55")]
+    pub res: ::core::option::Option<command_response::Res>,
+    /// the events of the state changes made by the command
+    #[prost(message, repeated, tag = "5")]
+    pub events: ::prost::alloc::vec::Vec<CommandEvent>,
+    /// the store checkpoint that the command advanced in its transaction, or unset if none
+    #[prost(message, optional, tag = "6")]
+    pub store_checkpoint: ::core::option::Option<StoreCheckpoint>,
+}
+/// Nested message and enum types in `CommandResponse`.
+pub mod command_response {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Res {
+        #[prost(message, tag = "2")]
+        Error(super::CommandError),
+        #[prost(message, tag = "10")]
+        GenerateEnclaveKey(super::GenerateEnclaveKeyResponse),
+        #[prost(message, tag = "11")]
+        IasRemoteAttestation(super::IasRemoteAttestationResponse),
+        #[prost(message, tag = "12")]
+        SimulateRemoteAttestation(super::SimulateRemoteAttestationResponse),
+        #[prost(message, tag = "13")]
+        ValidateDcapCollateral(super::ValidateDcapCollateralResponse),
+        #[prost(message, tag = "14")]
+        StartInstance(super::StartInstanceResponse),
+        #[prost(message, tag = "15")]
+        UpdateOperators(super::UpdateOperatorsResponse),
+        #[prost(message, tag = "16")]
+        QueryOperators(super::QueryOperatorsResponse),
+        #[prost(message, tag = "17")]
+        QueryBuildInfo(super::QueryBuildInfoResponse),
+        #[prost(message, tag = "18")]
+        BeginSession(super::BeginSessionResponse),
+        #[prost(message, tag = "19")]
+        OpenSession(super::OpenSessionResponse),
+        #[prost(message, tag = "20")]
+        RevokeEnclaveKeys(super::RevokeEnclaveKeysResponse),
+        #[prost(message, tag = "21")]
+        UnsealStoreKey(super::UnsealStoreKeyResponse),
+        #[prost(message, tag = "22")]
+        FinalizeStoreSequence(super::FinalizeStoreSequenceResponse),
+        #[prost(message, tag = "30")]
+        InitClient(super::InitClientResponse),
+        #[prost(message, tag = "31")]
+        UpdateClient(super::UpdateClientResponse),
+        #[prost(message, tag = "32")]
+        AggregateMessages(super::AggregateMessagesResponse),
+        #[prost(message, tag = "33")]
+        VerifyMembership(super::VerifyMembershipResponse),
+        #[prost(message, tag = "34")]
+        VerifyNonMembership(super::VerifyNonMembershipResponse),
+        #[prost(message, tag = "35")]
+        VerifyMembershipBatch(super::VerifyMembershipBatchResponse),
+        #[prost(message, tag = "36")]
+        VerifyKeyValue(super::VerifyKeyValueResponse),
+        #[prost(message, tag = "37")]
+        PruneClient(super::PruneClientResponse),
+        #[prost(message, tag = "38")]
+        SignLatestHeights(super::SignLatestHeightsResponse),
+        #[prost(message, tag = "39")]
+        UpdateClientParams(super::UpdateClientParamsResponse),
+        #[prost(message, tag = "40")]
+        ResignLatestState(super::ResignLatestStateResponse),
+        #[prost(message, tag = "41")]
+        MigrateClient(super::MigrateClientResponse),
+        #[prost(message, tag = "50")]
+        QueryClient(super::QueryClientResponse),
+        #[prost(message, tag = "51")]
+        ListClients(super::ListClientsResponse),
+        #[prost(message, tag = "52")]
+        QueryConsensusHeights(super::QueryConsensusHeightsResponse),
+        #[prost(message, tag = "53")]
+        QueryCapabilities(super::QueryCapabilitiesResponse),
+        #[prost(message, tag = "54")]
+        ValidateStore(super::ValidateStoreResponse),
+        #[prost(message, tag = "55")]
+        VerifyAggregateMessages(super::VerifyAggregateMessagesResponse),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandError {
+    #[prost(uint32, tag = "1")]
+    pub code: u32,
+    #[prost(string, tag = "2")]
+    pub descr: ::prost::alloc::string::String,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandEvent {
+    #[prost(oneof = "command_event::Event", tags = "1, 2, 3, 4, 5")]
+    pub event: ::core::option::Option<command_event::Event>,
+}
+/// Nested message and enum types in `CommandEvent`.
+pub mod command_event {
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        #[prost(message, tag = "1")]
+        ClientCreated(super::ClientCreated),
+        #[prost(message, tag = "2")]
+        ClientUpdated(super::ClientUpdated),
+        #[prost(message, tag = "3")]
+        StateEmitted(super::StateEmitted),
+        #[prost(message, tag = "4")]
+        ClientFrozen(super::ClientFrozen),
+        #[prost(message, tag = "5")]
+        ClientMigrated(super::ClientMigrated),
+    }
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientCreated {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub client_type: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientUpdated {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub prev_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(message, optional, tag = "3")]
+    pub post_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StateEmitted {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(message, optional, tag = "3")]
+    pub state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientFrozen {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub frozen_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientMigrated {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub from_type_url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub from_version: u32,
+    #[prost(string, tag = "4")]
+    pub to_type_url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "5")]
+    pub to_version: u32,
+    #[prost(message, optional, tag = "6")]
+    pub height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StoreCheckpoint {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sealed_sequence: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommitmentProof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub co_signatures: ::prost::alloc::vec::Vec<CoSignature>,
+    #[prost(message, optional, tag = "5")]
+    pub request_binding: ::core::option::Option<RequestBinding>,
+    #[prost(message, optional, tag = "6")]
+    pub signature_scheme: ::core::option::Option<SignatureScheme>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CoSignature {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RequestBinding {
+    #[prost(bytes = "vec", tag = "1")]
+    pub request_digest: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GenerateEnclaveKeyResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub pub_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sealed_ek: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IasRemoteAttestationResponse {
+    #[prost(message, optional, tag = "1")]
+    pub report: ::core::option::Option<EndorsedAttestationVerificationReport>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SimulateRemoteAttestationResponse {
+    #[prost(message, optional, tag = "1")]
+    pub avr: ::core::option::Option<AttestationVerificationReport>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AttestationVerificationReport {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub timestamp: ::prost::alloc::string::String,
+    #[prost(int64, tag = "3")]
+    pub version: i64,
+    #[prost(string, tag = "4")]
+    pub isv_enclave_quote_status: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub isv_enclave_quote_body: ::prost::alloc::string::String,
+    #[prost(int64, optional, tag = "6")]
+    pub revocation_reason: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "7")]
+    pub pse_manifest_status: ::core::option::Option<i64>,
+    #[prost(string, optional, tag = "8")]
+    pub pse_manifest_hash: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "9")]
+    pub platform_info_blob: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "10")]
+    pub nonce: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bytes = "vec", optional, tag = "11")]
+    pub epid_pseudonym: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(string, tag = "12")]
+    pub advisory_url: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "13")]
+    pub advisory_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateDcapCollateralResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub root_ca_hash: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StartInstanceResponse {
+    #[prost(uint64, tag = "1")]
+    pub counter: u64,
+    #[prost(uint64, tag = "2")]
+    pub store_sequence: u64,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub sealed_sequence: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateOperatorsResponse {}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryOperatorsResponse {
+    /// unset if the operator approvals are not required
+    #[prost(message, optional, tag = "1")]
+    pub operator_set: ::core::option::Option<OperatorSet>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryBuildInfoResponse {
+    #[prost(message, optional, tag = "1")]
+    pub build_info: ::core::option::Option<BuildInfo>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BuildInfo {
+    #[prost(string, tag = "1")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub git_commit: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub build_profile: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub sgx_sdk_version: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub rustc_version: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "6")]
+    pub features: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, tag = "7")]
+    pub protocol_version: u32,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BeginSessionResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub challenge: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenSessionResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub session_token: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub operator: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeEnclaveKeysResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub revoked: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnsealStoreKeyResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sealed_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FinalizeStoreSequenceResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub sealed_sequence: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InitClientResponse {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClientResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AggregateMessagesResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub constituent_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub constituent_signers_signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyNonMembershipResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipBatchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub proofs: ::prost::alloc::vec::Vec<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyKeyValueResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PruneClientResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub pruned_heights: ::prost::alloc::vec::Vec<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(uint64, tag = "2")]
+    pub reclaimed_bytes: u64,
+    #[prost(bool, tag = "3")]
+    pub expired: bool,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignLatestHeightsResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClientParamsResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResignLatestStateResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MigrateClientResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryClientResponse {
+    #[prost(message, optional, tag = "1")]
+    pub client_state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+    #[prost(message, optional, tag = "2")]
+    pub consensus_state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClientsResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub client_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageResponse>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryConsensusHeightsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub heights: ::prost::alloc::vec::Vec<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageResponse>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryCapabilitiesResponse {
+    #[prost(string, tag = "1")]
+    pub client_type: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub version: u32,
+    #[prost(message, optional, tag = "3")]
+    pub capabilities: ::core::option::Option<Capabilities>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Capabilities {
+    #[prost(bool, tag = "1")]
+    pub misbehaviour: bool,
+    #[prost(bool, tag = "2")]
+    pub upgrade: bool,
+    #[prost(string, repeated, tag = "3")]
+    pub proof_formats: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bool, tag = "4")]
+    pub multipart_headers: bool,
+    #[prost(bool, tag = "5")]
+    pub key_value_verification: bool,
+    #[prost(bool, tag = "6")]
+    pub client_params: bool,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidateStoreResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub clients: ::prost::alloc::vec::Vec<ClientValidation>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageResponse>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyAggregateMessagesResponse {
+    #[prost(uint64, tag = "1")]
+    pub verified: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientValidation {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub consensus_states: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub incompatibilities: ::prost::alloc::vec::Vec<StateIncompatibility>,
+    #[prost(uint64, tag = "4")]
+    pub incompatible_states: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StateIncompatibility {
+    #[prost(message, optional, tag = "1")]
+    pub height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// CommandRecord is a record of a light client command in the command history of the host.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommandRecord {
+    #[prost(uint64, tag = "1")]
+    pub seq: u64,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+    #[prost(string, tag = "3")]
+    pub command_type: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "4")]
+    pub client_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// keccak256 of the ECallCommand of the command without the context
+    #[prost(bytes = "vec", tag = "5")]
+    pub input_digest: ::prost::alloc::vec::Vec<u8>,
+    /// keccak256 of the CommandResponse of the command without the events and the checkpoint
+    #[prost(bytes = "vec", tag = "6")]
+    pub result_digest: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub pre_state_digest: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "8")]
+    pub post_state_digest: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+/// LoggedCommand is an entry of the command log of the host.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoggedCommand {
+    #[prost(message, optional, tag = "1")]
+    pub record: ::core::option::Option<CommandRecord>,
+    #[prost(string, optional, tag = "2")]
+    pub update_key: ::core::option::Option<::prost::alloc::string::String>,
+    /// the ECallCommand of the command without the context
+    #[prost(bytes = "vec", tag = "3")]
+    pub command: ::prost::alloc::vec::Vec<u8>,
+}
+/// UpdateRecord is a signed UpdateStateProxyMessage in the update history of the host.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateRecord {
+    #[prost(uint64, tag = "1")]
+    pub seq: u64,
+    #[prost(string, tag = "2")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub prev_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    #[prost(message, optional, tag = "4")]
+    pub post_height: ::core::option::Option<super::super::super::ibc::core::client::v1::Height>,
+    /// unix timestamp in nanoseconds
+    #[prost(uint64, tag = "5")]
+    pub recorded_at: u64,
+    #[prost(message, optional, tag = "6")]
+    pub proof: ::core::option::Option<CommitmentProof>,
+}
+/// UpdateRetention is the retention policy of the update records of a client.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateRetention {
+    #[prost(uint64, tag = "1")]
+    pub max_records: u64,
+    /// nanoseconds
+    #[prost(uint64, optional, tag = "2")]
+    pub max_age: ::core::option::Option<u64>,
+}
@@ -0,0 +1,111 @@
+/// ClientState is the state of a client of an OP Stack chain
+///
+/// The output roots of the L2 are verified against the storage of the L2OutputOracle contract on the L1,
+/// which is tracked by another client of the ethereum light client in the same enclave.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientState {
+    #[prost(uint64, tag = "1")]
+    pub chain_id: u64,
+    #[prost(message, optional, tag = "2")]
+    pub latest_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(message, optional, tag = "3")]
+    pub frozen_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    /// the client of the L1 whose storage root is the one of the L2OutputOracle contract
+    #[prost(string, tag = "4")]
+    pub l1_client_id: ::prost::alloc::string::String,
+    /// the storage slot of `l2Outputs` in the L2OutputOracle contract
+    #[prost(bytes = "vec", tag = "5")]
+    pub l2_outputs_slot: ::prost::alloc::vec::Vec<u8>,
+    /// the time in seconds after which a proposed output root can no longer be challenged
+    #[prost(uint64, tag = "6")]
+    pub finalization_period: u64,
+    /// the timestamp of the L2 genesis block in seconds
+    #[prost(uint64, tag = "7")]
+    pub l2_genesis_time: u64,
+    /// the interval of the L2 blocks in seconds
+    #[prost(uint64, tag = "8")]
+    pub l2_block_time: u64,
+    /// the address of the IBC handler contract on the L2
+    #[prost(bytes = "vec", tag = "9")]
+    pub ibc_address: ::prost::alloc::vec::Vec<u8>,
+    /// the storage slot of the commitments mapping in the IBC handler contract
+    #[prost(bytes = "vec", tag = "10")]
+    pub ibc_commitments_slot: ::prost::alloc::vec::Vec<u8>,
+    /// the address of the L2OutputOracle contract on the L1, which must be the account that the L1 client tracks
+    #[prost(bytes = "vec", tag = "11")]
+    pub l2_oracle_address: ::prost::alloc::vec::Vec<u8>,
+}
+/// ConsensusState is the state of the L2 at a block whose output root has been finalized on the L1
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConsensusState {
+    #[prost(bytes = "vec", tag = "1")]
+    pub output_root: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub state_root: ::prost::alloc::vec::Vec<u8>,
+    /// the storage root of the IBC handler contract
+    #[prost(bytes = "vec", tag = "3")]
+    pub storage_root: ::prost::alloc::vec::Vec<u8>,
+    /// the timestamp of the L2 block in seconds
+    #[prost(uint64, tag = "4")]
+    pub timestamp: u64,
+    /// the height of the L1 client at which the output root has been verified
+    #[prost(message, optional, tag = "5")]
+    pub l1_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+}
+/// Header is an output proposal of the L2OutputOracle contract with its proofs
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Header {
+    #[prost(message, optional, tag = "1")]
+    pub trusted_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    /// the height of the L1 client whose storage root includes the output proposal
+    #[prost(message, optional, tag = "2")]
+    pub l1_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(uint64, tag = "3")]
+    pub l2_output_index: u64,
+    #[prost(message, optional, tag = "4")]
+    pub output_root_proof: ::core::option::Option<OutputRootProof>,
+    /// the storage proof of the output root of the proposal
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub output_root_storage_proof: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the storage proof of the timestamp and the L2 block number of the proposal
+    #[prost(bytes = "vec", repeated, tag = "6")]
+    pub output_metadata_storage_proof: ::prost::alloc::vec::Vec<
+        ::prost::alloc::vec::Vec<u8>,
+    >,
+    /// the account proof of the IBC handler contract against the L2 state root
+    #[prost(bytes = "vec", repeated, tag = "7")]
+    pub ibc_account_proof: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// OutputRootProof is the preimage of an output root of version 0
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutputRootProof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub version: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub state_root: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub message_passer_storage_root: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub latest_blockhash: ::prost::alloc::vec::Vec<u8>,
+}
+/// StorageProof is a proof of a value, or of its absence, in the storage of the IBC handler contract
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StorageProof {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub proof: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
@@ -0,0 +1,26 @@
+/// ExtendedHeader is a tendermint header whose commit is an extended commit of ABCI++
+///
+/// The commit of the header is verified as usual, and the vote extensions are handled according to
+/// the vote extension policy of the client.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtendedHeader {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<
+        super::super::super::super::ibc::lightclients::tendermint::v1::Header,
+    >,
+    /// the vote extensions of the validators that committed the block
+    #[prost(message, repeated, tag = "2")]
+    pub extended_signatures: ::prost::alloc::vec::Vec<ExtendedCommitSig>,
+}
+/// ExtendedCommitSig is the vote extension of a precommit in the commit of the header
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtendedCommitSig {
+    #[prost(bytes = "vec", tag = "1")]
+    pub validator_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub extension: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub extension_signature: ::prost::alloc::vec::Vec<u8>,
+}
@@ -21,6 +21,37 @@ pub struct QueryClientResponse {
         super::super::super::super::google::protobuf::Any,
     >,
 }
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryArtifactRequest {
+    /// sha256 hash of the artifact
+    #[prost(bytes = "vec", tag = "1")]
+    pub hash: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArtifactChunk {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// offset of the data in the artifact
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    /// size of the whole artifact
+    #[prost(uint64, tag = "3")]
+    pub total_size: u64,
+}
+/// VerifyMembershipArtifact is the encoding of the artifact that the service stores for MsgVerifyMembership
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipArtifact {
+    #[prost(message, optional, tag = "1")]
+    pub request: ::core::option::Option<MsgVerifyMembership>,
+    #[prost(message, optional, tag = "2")]
+    pub response: ::core::option::Option<MsgVerifyMembershipResponse>,
+}
 /// Generated client implementations.
 #[cfg(feature = "client")]
 pub mod query_client {
@@ -111,6 +142,29 @@ pub mod query_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Artifact streams the artifact that the service stored for a request with `store_artifact`
+        pub async fn artifact(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueryArtifactRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::ArtifactChunk>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Query/Artifact",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -125,6 +179,17 @@ pub mod query_server {
             &self,
             request: tonic::Request<super::QueryClientRequest>,
         ) -> Result<tonic::Response<super::QueryClientResponse>, tonic::Status>;
+        /// Server streaming response type for the Artifact method.
+        type ArtifactStream: futures_core::Stream<
+                Item = Result<super::ArtifactChunk, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Artifact streams the artifact that the service stored for a request with `store_artifact`
+        async fn artifact(
+            &self,
+            request: tonic::Request<super::QueryArtifactRequest>,
+        ) -> Result<tonic::Response<Self::ArtifactStream>, tonic::Status>;
     }
     /// Query defines the ELC Query service.
     #[derive(Debug)]
@@ -222,6 +287,45 @@ pub mod query_server {
                     };
                     Box::pin(fut)
                 }
+                "/lcp.service.elc.v1.Query/Artifact" => {
+                    #[allow(non_camel_case_types)]
+                    struct ArtifactSvc<T: Query>(pub Arc<T>);
+                    impl<
+                        T: Query,
+                    > tonic::server::ServerStreamingService<super::QueryArtifactRequest>
+                    for ArtifactSvc<T> {
+                        type Response = super::ArtifactChunk;
+                        type ResponseStream = T::ArtifactStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueryArtifactRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).artifact(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ArtifactSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -280,6 +384,36 @@ pub struct MsgCreateClient {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "3")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// if non-empty, membership proofs for the client must target the store with this prefix
+    #[prost(bytes = "vec", tag = "4")]
+    pub commitment_prefix: ::prost::alloc::vec::Vec<u8>,
+    /// if non-empty, one of `skipping`, `sequential` or `adjacent`, which restricts the headers that the client accepts
+    /// the default is `skipping`
+    #[prost(string, tag = "5")]
+    pub verification_mode: ::prost::alloc::string::String,
+    /// if true, the enclave signs a CreateClient message that attests the initial state of the client
+    #[prost(bool, tag = "6")]
+    pub prove_genesis: bool,
+    /// if non-zero, the maximum clock drift in seconds that the client tolerates between the header time and
+    /// the current time of the enclave, which overrides the one of the client state
+    #[prost(uint64, tag = "7")]
+    pub max_clock_drift: u64,
+    /// if non-empty, one of `ignore`, `verify` or `reject`, which determines how the vote extensions of ABCI++
+    /// in the headers are handled. the default is `ignore`
+    #[prost(string, tag = "8")]
+    pub vote_extension_policy: ::prost::alloc::string::String,
+    /// if non-empty, one of `keccak256` or `sha256`, which is the digest of the commitments that the enclave signs
+    /// for the client. the default is `keccak256`
+    #[prost(string, tag = "9")]
+    pub digest_algorithm: ::prost::alloc::string::String,
+    /// if non-empty, one of `recoverable`, `ethereum` or `compact`, which is the format of the signatures of the
+    /// commitments for the client. the default is `recoverable`
+    #[prost(string, tag = "10")]
+    pub signature_format: ::prost::alloc::string::String,
+    /// if non-zero, the version of the message schema that the verifier of the client accepts. the default is 1,
+    /// whose messages carry neither an expiry, a frozen height nor a trusted timestamp
+    #[prost(uint32, tag = "11")]
+    pub message_schema_version: u32,
 }
 /// MsgCreateClientResponse defines the Msg/CreateClient response type.
 #[derive(::serde::Serialize, ::serde::Deserialize)]
@@ -294,6 +428,11 @@ pub struct MsgCreateClientResponse {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "4")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "6")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
 /// MsgUpdateClient defines an sdk.Msg to update a IBC client state using
 /// the given header.
@@ -315,6 +454,22 @@ pub struct MsgUpdateClient {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "4")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// type URLs of the client states to keep in the emitted states
+    /// a filter ending with `*` matches by prefix. If empty, all emitted states are kept
+    #[prost(string, repeated, tag = "5")]
+    pub state_filters: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// if true, a header that conflicts with a stored consensus state results in
+    /// a misbehaviour commitment that freezes the client
+    #[prost(bool, tag = "6")]
+    pub detect_misbehaviour: bool,
+    /// unix timestamp in seconds after which the commitment should be rejected by verifiers
+    /// 0 means the commitment never expires
+    #[prost(uint64, tag = "7")]
+    pub expires_at: u64,
+    /// if true, the header is verified and the would-be message is returned without
+    /// a signature, and the state of the client is not changed
+    #[prost(bool, tag = "8")]
+    pub dry_run: bool,
 }
 /// MsgUpdateClientResponse defines the Msg/UpdateClient response type.
 #[derive(::serde::Serialize, ::serde::Deserialize)]
@@ -327,6 +482,11 @@ pub struct MsgUpdateClientResponse {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -338,6 +498,18 @@ pub struct MsgAggregateMessages {
     pub messages: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
     #[prost(bytes = "vec", repeated, tag = "3")]
     pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the messages wrapped in Any, which can be set instead of `messages`
+    #[prost(message, repeated, tag = "4")]
+    pub any_messages: ::prost::alloc::vec::Vec<
+        super::super::super::super::google::protobuf::Any,
+    >,
+    /// the signer of each message if the messages are signed by different enclave keys, e.g. across a key rotation
+    /// if empty, all messages must be signed by `signer`
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the client of the messages, whose signature scheme the messages and the aggregate are signed with
+    #[prost(string, tag = "6")]
+    pub client_id: ::prost::alloc::string::String,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -349,6 +521,17 @@ pub struct MsgAggregateMessagesResponse {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the distinct signers of the aggregated messages
+    #[prost(bytes = "vec", repeated, tag = "6")]
+    pub constituent_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the signature of `signer` over the signers, which binds them to the message
+    #[prost(bytes = "vec", tag = "7")]
+    pub constituent_signers_signature: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -371,6 +554,20 @@ pub struct MsgVerifyMembership {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "7")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in seconds after which the commitment should be rejected by verifiers
+    /// 0 means the commitment never expires
+    #[prost(uint64, tag = "8")]
+    pub expires_at: u64,
+    /// height of the consensus state to verify the proof against
+    /// if set, proof_height must be zero or equal to it
+    #[prost(message, optional, tag = "9")]
+    pub consensus_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    /// if true, the service stores the request and the response as an artifact on the host,
+    /// which can be retrieved with the `Artifact` query by the hash in the response
+    #[prost(bool, tag = "10")]
+    pub store_artifact: bool,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -382,6 +579,14 @@ pub struct MsgVerifyMembershipResponse {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// sha256 hash of the stored artifact if `store_artifact` is true in the request
+    #[prost(bytes = "vec", tag = "6")]
+    pub artifact_hash: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -402,6 +607,10 @@ pub struct MsgVerifyNonMembership {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "6")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in seconds after which the commitment should be rejected by verifiers
+    /// 0 means the commitment never expires
+    #[prost(uint64, tag = "7")]
+    pub expires_at: u64,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -413,6 +622,117 @@ pub struct MsgVerifyNonMembershipResponse {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "3")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgSignLatestHeights {
+    /// clients to include in the summary; if empty, all clients are included
+    #[prost(string, repeated, tag = "1")]
+    pub client_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// enclave key for signing
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgSignLatestHeightsResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgVerifyKeyValue {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    /// the store that contains the key
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    /// an arbitrary key in the store, which is not interpreted as an ICS-24 path
+    #[prost(bytes = "vec", tag = "3")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    /// the value of the key
+    /// empty means that the absence of the key is verified
+    #[prost(bytes = "vec", tag = "4")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub proof_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(bytes = "vec", tag = "6")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+    /// enclave key for signing
+    #[prost(bytes = "vec", tag = "7")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// unix timestamp in seconds after which the commitment should be rejected by verifiers
+    /// 0 means the commitment never expires
+    #[prost(uint64, tag = "8")]
+    pub expires_at: u64,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgVerifyKeyValueResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgMigrateClient {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    /// type URL of the client state that the target implementation is registered for
+    #[prost(string, tag = "2")]
+    pub target_type_url: ::prost::alloc::string::String,
+    /// version of the target implementation
+    /// 0 means the latest registered version
+    #[prost(uint32, tag = "3")]
+    pub target_version: u32,
+    /// enclave key for signing
+    #[prost(bytes = "vec", tag = "4")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgMigrateClientResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// signers and signatures of the incoming enclave keys during a key rotation
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub co_signers: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "5")]
+    pub co_signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
 /// Generated client implementations.
 #[cfg(feature = "client")]
@@ -591,6 +911,66 @@ pub mod msg_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// SignLatestHeights defines a rpc handler method for MsgSignLatestHeights
+        pub async fn sign_latest_heights(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MsgSignLatestHeights>,
+        ) -> Result<tonic::Response<super::MsgSignLatestHeightsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Msg/SignLatestHeights",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// VerifyKeyValue defines a rpc handler method for MsgVerifyKeyValue
+        pub async fn verify_key_value(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MsgVerifyKeyValue>,
+        ) -> Result<tonic::Response<super::MsgVerifyKeyValueResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Msg/VerifyKeyValue",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// MigrateClient defines a rpc handler method for MsgMigrateClient
+        pub async fn migrate_client(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MsgMigrateClient>,
+        ) -> Result<tonic::Response<super::MsgMigrateClientResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Msg/MigrateClient",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -629,6 +1009,21 @@ pub mod msg_server {
             tonic::Response<super::MsgVerifyNonMembershipResponse>,
             tonic::Status,
         >;
+        /// SignLatestHeights defines a rpc handler method for MsgSignLatestHeights
+        async fn sign_latest_heights(
+            &self,
+            request: tonic::Request<super::MsgSignLatestHeights>,
+        ) -> Result<tonic::Response<super::MsgSignLatestHeightsResponse>, tonic::Status>;
+        /// VerifyKeyValue defines a rpc handler method for MsgVerifyKeyValue
+        async fn verify_key_value(
+            &self,
+            request: tonic::Request<super::MsgVerifyKeyValue>,
+        ) -> Result<tonic::Response<super::MsgVerifyKeyValueResponse>, tonic::Status>;
+        /// MigrateClient defines a rpc handler method for MsgMigrateClient
+        async fn migrate_client(
+            &self,
+            request: tonic::Request<super::MsgMigrateClient>,
+        ) -> Result<tonic::Response<super::MsgMigrateClientResponse>, tonic::Status>;
     }
     /// Msg defines the ELC Msg service.
     #[derive(Debug)]
@@ -882,6 +1277,126 @@ pub mod msg_server {
                     };
                     Box::pin(fut)
                 }
+                "/lcp.service.elc.v1.Msg/SignLatestHeights" => {
+                    #[allow(non_camel_case_types)]
+                    struct SignLatestHeightsSvc<T: Msg>(pub Arc<T>);
+                    impl<
+                        T: Msg,
+                    > tonic::server::UnaryService<super::MsgSignLatestHeights>
+                    for SignLatestHeightsSvc<T> {
+                        type Response = super::MsgSignLatestHeightsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MsgSignLatestHeights>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).sign_latest_heights(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SignLatestHeightsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/lcp.service.elc.v1.Msg/VerifyKeyValue" => {
+                    #[allow(non_camel_case_types)]
+                    struct VerifyKeyValueSvc<T: Msg>(pub Arc<T>);
+                    impl<
+                        T: Msg,
+                    > tonic::server::UnaryService<super::MsgVerifyKeyValue>
+                    for VerifyKeyValueSvc<T> {
+                        type Response = super::MsgVerifyKeyValueResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MsgVerifyKeyValue>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).verify_key_value(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = VerifyKeyValueSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/lcp.service.elc.v1.Msg/MigrateClient" => {
+                    #[allow(non_camel_case_types)]
+                    struct MigrateClientSvc<T: Msg>(pub Arc<T>);
+                    impl<
+                        T: Msg,
+                    > tonic::server::UnaryService<super::MsgMigrateClient>
+                    for MigrateClientSvc<T> {
+                        type Response = super::MsgMigrateClientResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MsgMigrateClient>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).migrate_client(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = MigrateClientSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
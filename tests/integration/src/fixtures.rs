@@ -0,0 +1,278 @@
+use crate::relayer::Relayer;
+use crate::types::merkle_proof_to_bytes;
+use anyhow::{anyhow, bail, Result};
+use crypto::Address;
+use ecall_commands::{
+    CommitmentProofPair, InitClientInput, UpdateClientInput, VerifyMembershipInput,
+};
+use enclave_api::EnclaveCommandAPI;
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use ibc::core::ics24_host::path::{ChannelEndPath, Path};
+use ibc::Height;
+use lcp_proto::protobuf::Protobuf;
+use lcp_types::{Any, ClientId, Time};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path as FsPath;
+use std::time::Duration;
+use store::transaction::CommitStore;
+
+/// HeaderFixture is a header sequence of a chain recorded with the proofs at the updated heights
+///
+/// A fixture is replayed against the enclave without the nodes, and the recorded timestamps are given
+/// to the commands, so the verification of the headers is deterministic.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HeaderFixture {
+    pub chain_id: String,
+    pub client_state: FixtureAny,
+    pub consensus_state: FixtureAny,
+    /// The timestamp given to `init_client`
+    pub created_at: Time,
+    pub steps: Vec<FixtureStep>,
+}
+
+/// FixtureStep is an update of the client with a header, followed by the verifications at the updated height
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FixtureStep {
+    pub header: FixtureAny,
+    pub current_timestamp: Time,
+    /// If set, the update must fail with an error whose message contains this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_error: Option<String>,
+    #[serde(default)]
+    pub memberships: Vec<MembershipFixture>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MembershipFixture {
+    pub prefix: String,
+    pub path: String,
+    /// hex-encoded value
+    pub value: String,
+    pub proof_height: Height,
+    /// hex-encoded commitment proof
+    pub proof: String,
+}
+
+/// FixtureAny is an `Any` whose value is hex-encoded to keep the fixture readable
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureAny {
+    pub type_url: String,
+    pub value: String,
+}
+
+impl From<Any> for FixtureAny {
+    fn from(any: Any) -> Self {
+        let any = any.to_proto();
+        Self {
+            type_url: any.type_url,
+            value: hex::encode(any.value),
+        }
+    }
+}
+
+impl TryFrom<&FixtureAny> for Any {
+    type Error = anyhow::Error;
+    fn try_from(any: &FixtureAny) -> Result<Self> {
+        Ok(Any::new(any.type_url.clone(), hex::decode(&any.value)?))
+    }
+}
+
+impl HeaderFixture {
+    pub fn load<P: AsRef<FsPath>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bz = fs::read(path).map_err(|e| anyhow!("failed to read {:?}: {}", path, e))?;
+        Ok(serde_json::from_slice(&bz)?)
+    }
+
+    pub fn save<P: AsRef<FsPath>>(&self, path: P) -> Result<()> {
+        Ok(fs::write(path, serde_json::to_vec_pretty(self)?)?)
+    }
+
+    /// Record the headers to update a client created at `initial_height` to each of `target_heights` in order
+    ///
+    /// If `channel` is given, the proof of the channel end at each target height is recorded too.
+    pub fn record(
+        rly: &mut Relayer,
+        chain_id: &str,
+        initial_height: Height,
+        target_heights: &[Height],
+        channel: Option<(PortId, ChannelId)>,
+    ) -> Result<Self> {
+        let (client_state, consensus_state) = rly.fetch_state_as_any(initial_height)?;
+        let mut fixture = Self {
+            chain_id: chain_id.to_string(),
+            client_state: client_state.into(),
+            consensus_state: consensus_state.into(),
+            created_at: Time::now(),
+            steps: vec![],
+        };
+        let mut trusted_height = initial_height;
+        for target_height in target_heights.iter().copied() {
+            for header in rly.create_headers(trusted_height, target_height)? {
+                fixture.steps.push(FixtureStep {
+                    header: header.into(),
+                    current_timestamp: Time::now(),
+                    expected_error: None,
+                    memberships: vec![],
+                });
+            }
+            if let Some((port_id, channel_id)) = channel.clone() {
+                let (channel_end, proof, proof_height) = rly.query_channel_proof(
+                    port_id.clone(),
+                    channel_id.clone(),
+                    Some(target_height),
+                )?;
+                // the proof is verified with the consensus state of the last header of the target height
+                fixture
+                    .steps
+                    .last_mut()
+                    .unwrap()
+                    .memberships
+                    .push(MembershipFixture {
+                        prefix: "ibc".to_string(),
+                        path: Path::ChannelEnd(ChannelEndPath(port_id, channel_id)).to_string(),
+                        value: hex::encode(channel_end.encode_vec()?),
+                        proof_height,
+                        proof: hex::encode(merkle_proof_to_bytes(proof)?),
+                    });
+            }
+            trusted_height = target_height;
+        }
+        info!(
+            "recorded a fixture: chain_id={} initial_height={} steps={}",
+            chain_id,
+            initial_height,
+            fixture.steps.len()
+        );
+        Ok(fixture)
+    }
+
+    /// Replay the fixture against the enclave on a new client, and returns the ID of the client
+    pub fn replay<S, E>(&self, enclave: &E, signer: Address) -> Result<ClientId>
+    where
+        S: CommitStore,
+        E: EnclaveCommandAPI<S>,
+    {
+        let client_id = enclave
+            .init_client(InitClientInput {
+                any_client_state: (&self.client_state).try_into()?,
+                any_consensus_state: (&self.consensus_state).try_into()?,
+                commitment_prefix: Some("ibc".try_into()?),
+                verification_mode: None,
+                prove_genesis: false,
+                max_clock_drift: Some(Duration::from_secs(10)),
+                vote_extension_policy: None,
+                signature_scheme: None,
+                message_schema_version: None,
+                current_timestamp: self.created_at,
+                signer,
+            })?
+            .client_id;
+        for (i, step) in self.steps.iter().enumerate() {
+            let res = enclave.update_client(UpdateClientInput {
+                client_id: client_id.clone(),
+                any_header: (&step.header).try_into()?,
+                current_timestamp: step.current_timestamp,
+                include_state: false,
+                state_filters: vec![],
+                detect_misbehaviour: false,
+                expires_at: None,
+                dry_run: false,
+                signer,
+            });
+            match (res, step.expected_error.as_ref()) {
+                (Ok(_), None) => {}
+                (Err(e), Some(expected)) if e.to_string().contains(expected.as_str()) => {
+                    debug!("step failed as expected: step={} err={}", i, e);
+                    continue;
+                }
+                (Ok(_), Some(expected)) => {
+                    bail!(
+                        "step succeeded unexpectedly: step={} expected={}",
+                        i,
+                        expected
+                    )
+                }
+                (Err(e), _) => bail!("step failed: step={} err={:?}", i, e),
+            }
+            for membership in step.memberships.iter() {
+                enclave.verify_membership(VerifyMembershipInput {
+                    client_id: client_id.clone(),
+                    prefix: membership.prefix.as_str().try_into()?,
+                    path: membership.path.parse()?,
+                    value: hex::decode(&membership.value)?,
+                    proof: CommitmentProofPair(
+                        membership.proof_height.into(),
+                        hex::decode(&membership.proof)?,
+                    ),
+                    expires_at: None,
+                    consensus_height: None,
+                    current_timestamp: step.current_timestamp,
+                    signer,
+                })?;
+            }
+        }
+        info!(
+            "replayed a fixture: chain_id={} client_id={} steps={}",
+            self.chain_id,
+            client_id,
+            self.steps.len()
+        );
+        Ok(client_id)
+    }
+}
+
+/// Load the fixtures in the directory, which are the files with the `json` extension, in the order of their names
+pub fn load_fixtures<P: AsRef<FsPath>>(dir: P) -> Result<Vec<(String, HeaderFixture)>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| path.extension().map_or(false, |ext| ext == "json"));
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| Ok((path.display().to_string(), HeaderFixture::load(&path)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fixture_roundtrip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let any = Any::new("/test.Header".to_string(), vec![1, 2, 3]);
+        let fixture = HeaderFixture {
+            chain_id: "ibc0".to_string(),
+            client_state: any.clone().into(),
+            consensus_state: any.clone().into(),
+            created_at: Time::from_unix_timestamp_nanos(1_700_000_000_000_000_000).unwrap(),
+            steps: vec![FixtureStep {
+                header: any.clone().into(),
+                current_timestamp: Time::from_unix_timestamp_nanos(1_700_000_010_000_000_000)
+                    .unwrap(),
+                expected_error: Some("header expired".to_string()),
+                memberships: vec![MembershipFixture {
+                    prefix: "ibc".to_string(),
+                    path: "channelEnds/ports/transfer/channels/channel-0".to_string(),
+                    value: hex::encode([4, 5]),
+                    proof_height: Height::new(0, 10).unwrap(),
+                    proof: hex::encode([6, 7]),
+                }],
+            }],
+        };
+        fixture.save(tmp_dir.path().join("b.json")).unwrap();
+        fixture.save(tmp_dir.path().join("a.json")).unwrap();
+        fs::write(tmp_dir.path().join("README"), "not a fixture").unwrap();
+
+        let fixtures = load_fixtures(tmp_dir.path()).unwrap();
+        assert_eq!(fixtures.len(), 2);
+        assert!(fixtures[0].0.ends_with("a.json"));
+        assert_eq!(fixtures[0].1, fixture);
+        assert_eq!(Any::try_from(&fixtures[0].1.steps[0].header).unwrap(), any);
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod config;
 #[cfg(test)]
+mod fixtures;
+#[cfg(test)]
 mod relayer;
 #[cfg(test)]
 mod types;
@@ -8,23 +10,23 @@ mod types;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::{load_fixtures, HeaderFixture};
     use crate::relayer::Relayer;
+    use crate::types::merkle_proof_to_bytes;
     use anyhow::{anyhow, bail};
     use commitments::UpdateStateProxyMessage;
+    use crypto::{Address, EnclaveKey};
     use ecall_commands::{
         AggregateMessagesInput, CommitmentProofPair, GenerateEnclaveKeyInput, InitClientInput,
-        UpdateClientInput, VerifyMembershipInput,
+        PageRequest, QueryConsensusHeightsInput, UpdateClientInput, VerifyMembershipInput,
     };
     use enclave_api::{Enclave, EnclaveCommandAPI};
     use host_environment::Environment;
     use ibc::{
-        core::{
-            ics23_commitment::{commitment::CommitmentProofBytes, merkle::MerkleProof},
-            ics24_host::{
-                identifier::{ChannelId, PortId},
-                path::ChannelEndPath,
-                Path,
-            },
+        core::ics24_host::{
+            identifier::{ChannelId, ConnectionId, PortId},
+            path::ChannelEndPath,
+            Path,
         },
         Height as IBCHeight,
     };
@@ -37,13 +39,19 @@ mod tests {
     use lcp_types::{Height, Time};
     use log::*;
     use std::sync::{Arc, RwLock};
-    use std::{ops::Add, str::FromStr, time::Duration};
+    use std::{str::FromStr, time::Duration};
     use store::{host::HostStore, memory::MemStore};
     use tempfile::TempDir;
     use tokio::runtime::Runtime as TokioRuntime;
 
     static ENCLAVE_FILE: &str = "../../bin/enclave.signed.so";
     static ENV_SETUP_NODES: &str = "SETUP_NODES";
+    /// The hex-encoded key of the bootstrap operator that the enclave is built with, which opens the session
+    static ENV_OPERATOR_KEY: &str = "LCP_OPERATOR_KEY";
+    /// If set, the fixtures in the directory are replayed instead of running the test against the nodes
+    static ENV_REPLAY_FIXTURES: &str = "REPLAY_FIXTURES";
+    /// If set, the headers that the test submits are also recorded as a fixture to the path
+    static ENV_RECORD_FIXTURE: &str = "RECORD_FIXTURE";
 
     struct ELCStateVerificationTest {
         enclave: Enclave<store::memory::MemStore>,
@@ -73,22 +81,42 @@ mod tests {
     fn test_elc_state_verification() {
         let tmp_dir = TempDir::new().unwrap();
         let home = tmp_dir.path().to_str().unwrap().to_string();
-        host::set_environment(Environment::new(
+        let env = Environment::new(
             home.into(),
             Arc::new(RwLock::new(HostStore::Memory(MemStore::default()))),
-        ))
-        .unwrap();
+        );
+        // the enclave built with `sgx-sw` runs the IAS remote attestation against a mock IAS
+        #[cfg(feature = "sgx-sw")]
+        let mock_ias =
+            mock_ias::MockIAS::spawn("127.0.0.1:0", mock_ias::MockIASConfig::default()).unwrap();
+        #[cfg(feature = "sgx-sw")]
+        let env = env.with_ias_endpoint(mock_ias.endpoint());
+        host::set_environment(env).unwrap();
 
         let env = host::get_environment().unwrap();
         let km = EnclaveKeyManager::new(&env.home).unwrap();
         let enclave = Enclave::create(ENCLAVE_FILE, false, km, env.store.clone()).unwrap();
+        let enclave = enclave.with_session_credential(Box::new(operator_key()));
+        enclave.open_session().unwrap();
+        enclave.start_instance().unwrap();
 
+        if let Ok(dir) = std::env::var(ENV_REPLAY_FIXTURES) {
+            replay_fixtures(&enclave, &dir).unwrap();
+            return;
+        }
         match std::env::var(ENV_SETUP_NODES).map(|v| v.to_lowercase()) {
             Ok(v) if v == "false" => run_test(&enclave).unwrap(),
             _ => run_binary_channel_test(&ELCStateVerificationTest { enclave }).unwrap(),
         }
     }
 
+    fn operator_key() -> EnclaveKey {
+        let secret = std::env::var(ENV_OPERATOR_KEY)
+            .unwrap_or_else(|_| panic!("{} must be set", ENV_OPERATOR_KEY));
+        let bz = hex::decode(secret.trim().trim_start_matches("0x")).unwrap();
+        EnclaveKey::from_secret(&bz.try_into().unwrap()).unwrap()
+    }
+
     fn run_test(enclave: &Enclave<store::memory::MemStore>) -> Result<(), anyhow::Error> {
         env_logger::init();
         let rt = Arc::new(TokioRuntime::new()?);
@@ -96,10 +124,58 @@ mod tests {
         verify(rly, enclave)
     }
 
-    fn verify(
-        mut rly: Relayer,
+    fn replay_fixtures(
         enclave: &Enclave<store::memory::MemStore>,
+        dir: &str,
     ) -> Result<(), anyhow::Error> {
+        let _ = env_logger::try_init();
+        let signer = setup_enclave_key(enclave)?;
+        let fixtures = load_fixtures(dir)?;
+        if fixtures.is_empty() {
+            bail!("no fixture found: dir={}", dir);
+        }
+        for (name, fixture) in fixtures {
+            info!("replay a fixture: name={}", name);
+            fixture
+                .replay(enclave, signer)
+                .map_err(|e| anyhow!("failed to replay the fixture: name={} err={:?}", name, e))?;
+        }
+        Ok(())
+    }
+
+    fn record_fixture(
+        rly: &mut Relayer,
+        enclave: &Enclave<store::memory::MemStore>,
+        signer: Address,
+        path: &str,
+    ) -> Result<(), anyhow::Error> {
+        let initial_height = rly.query_latest_height()?.decrement()?;
+        let mut target_heights = vec![];
+        for _ in 0..3 {
+            target_heights.push(wait_block_advance(rly)?);
+        }
+        let chain_id = std::env::var("TEST_NODE_CHAIN_ID").unwrap_or_else(|_| "ibc0".into());
+        let fixture = HeaderFixture::record(
+            rly,
+            &chain_id,
+            initial_height,
+            &target_heights,
+            Some((
+                PortId::from_str("transfer")?,
+                ChannelId::from_str("channel-0")?,
+            )),
+        )?;
+        // the fixture must be replayable before it is saved
+        fixture.replay(enclave, signer)?;
+        fixture.save(path)?;
+        info!("saved a fixture: path={}", path);
+        Ok(())
+    }
+
+    /// Generate an enclave key and attest it, and returns its address
+    fn setup_enclave_key(
+        enclave: &Enclave<store::memory::MemStore>,
+    ) -> Result<Address, anyhow::Error> {
         if cfg!(feature = "sgx-sw") {
             info!("this test is running in SW mode");
         } else {
@@ -120,6 +196,9 @@ mod tests {
                     target_enclave_key: signer,
                     spid: std::env::var("SPID")?.as_bytes().to_vec(),
                     ias_key: std::env::var("IAS_KEY")?.as_bytes().to_vec(),
+                    quote_type: Default::default(),
+                    advisory_policy: None,
+                    applied_mitigations: vec![],
                 }) {
                     Ok(res) => res.report,
                     Err(e) => {
@@ -128,6 +207,23 @@ mod tests {
                 };
         }
         #[cfg(feature = "sgx-sw")]
+        {
+            let _ =
+                match enclave.ias_remote_attestation(ecall_commands::IASRemoteAttestationInput {
+                    target_enclave_key: signer,
+                    spid: [b'0'; 32].to_vec(),
+                    ias_key: [b'0'; 32].to_vec(),
+                    quote_type: Default::default(),
+                    advisory_policy: None,
+                    applied_mitigations: vec![],
+                }) {
+                    Ok(res) => res.report,
+                    Err(e) => {
+                        bail!("IAS Remote Attestation with the mock IAS Failed {:?}!", e);
+                    }
+                };
+        }
+        #[cfg(feature = "sgx-sw")]
         {
             use enclave_api::rsa::{pkcs1v15::SigningKey, rand_core::OsRng};
             use enclave_api::sha2::Sha256;
@@ -146,6 +242,17 @@ mod tests {
                 }
             };
         }
+        Ok(signer)
+    }
+
+    fn verify(
+        mut rly: Relayer,
+        enclave: &Enclave<store::memory::MemStore>,
+    ) -> Result<(), anyhow::Error> {
+        let signer = setup_enclave_key(enclave)?;
+        if let Ok(path) = std::env::var(ENV_RECORD_FIXTURE) {
+            record_fixture(&mut rly, enclave, signer, &path)?;
+        }
 
         let (client_id, last_height) = {
             // XXX use non-latest height here
@@ -160,6 +267,13 @@ mod tests {
             let res = enclave.init_client(InitClientInput {
                 any_client_state: client_state,
                 any_consensus_state: consensus_state,
+                commitment_prefix: Some("ibc".try_into()?),
+                verification_mode: None,
+                prove_genesis: false,
+                max_clock_drift: Some(Duration::from_secs(10)), // for gaiad's clock drift
+                vote_extension_policy: None,
+                signature_scheme: None,
+                message_schema_version: None,
                 current_timestamp: Time::now(),
                 signer,
             })?;
@@ -173,11 +287,46 @@ mod tests {
         let last_height = {
             let post_height = last_height.increment();
             let target_header = rly.create_header(last_height, post_height)?;
+            // a dry run returns the would-be message without a signature or a state change
+            let res = enclave.update_client(UpdateClientInput {
+                client_id: client_id.clone(),
+                any_header: target_header.clone(),
+                current_timestamp: Time::now(),
+                include_state: true,
+                state_filters: vec![],
+                detect_misbehaviour: false,
+                expires_at: None,
+                dry_run: true,
+                signer,
+            })?;
+            assert!(!res.0.is_proven());
+            let msg: UpdateStateProxyMessage = res.0.message().unwrap().try_into()?;
+            assert!(msg.post_height == Height::from(post_height));
+
+            // the client accepts the message schema version 1, which cannot carry an expiry
+            assert!(enclave
+                .update_client(UpdateClientInput {
+                    client_id: client_id.clone(),
+                    any_header: target_header.clone(),
+                    current_timestamp: Time::now(),
+                    include_state: true,
+                    state_filters: vec![],
+                    detect_misbehaviour: false,
+                    expires_at: Some((Time::now() + Duration::from_secs(60))?),
+                    dry_run: true,
+                    signer,
+                })
+                .is_err());
+
             let res = enclave.update_client(UpdateClientInput {
                 client_id: client_id.clone(),
                 any_header: target_header,
                 current_timestamp: Time::now(),
                 include_state: true,
+                state_filters: vec![],
+                detect_misbehaviour: false,
+                expires_at: None,
+                dry_run: false,
                 signer,
             })?;
             info!("update_client's result is {:?}", res);
@@ -187,6 +336,15 @@ mod tests {
             assert!(msg.prev_height == Some(Height::from(last_height)));
             assert!(msg.post_height == Height::from(post_height));
             assert!(msg.emitted_states.len() == 1);
+
+            // the latest state is signed again without advancing the height
+            let res = enclave.resign_latest_state(client_id.clone(), signer)?;
+            assert!(res.0.is_proven());
+            let resigned: UpdateStateProxyMessage = res.0.message().unwrap().try_into()?;
+            assert!(resigned.prev_height == Some(msg.post_height));
+            assert!(resigned.post_height == msg.post_height);
+            assert!(resigned.prev_state_id == Some(msg.post_state_id));
+            assert!(resigned.post_state_id == msg.post_state_id);
             post_height
         };
         info!("current last_height is {}", last_height);
@@ -203,45 +361,83 @@ mod tests {
 
             let _ = enclave.verify_membership(VerifyMembershipInput {
                 client_id: client_id.clone(),
-                prefix: "ibc".into(),
-                path: Path::ChannelEnd(ChannelEndPath(port_id, channel_id)).to_string(),
+                prefix: "ibc".try_into()?,
+                path: Path::ChannelEnd(ChannelEndPath(port_id, channel_id))
+                    .to_string()
+                    .parse()?,
                 value: res.0.encode_vec()?,
                 proof: CommitmentProofPair(
                     res.2.try_into().map_err(|e| anyhow!("{:?}", e))?,
                     merkle_proof_to_bytes(res.1)?,
                 ),
+                expires_at: None,
+                consensus_height: None,
+                current_timestamp: Time::now(),
                 signer,
             })?;
         }
 
+        {
+            let inputs = rly.connection_handshake_proofs(
+                client_id.clone(),
+                "ibc".try_into()?,
+                ConnectionId::from_str("connection-0")?,
+                signer,
+                Some(last_height),
+            )?;
+            assert_eq!(inputs.len(), 3);
+            for input in inputs {
+                let _ = enclave.verify_membership(input)?;
+            }
+        }
+
         let last_height = {
             let mut lh = last_height;
             let mut proofs = vec![];
             for _ in 0..10 {
                 let target_height = wait_block_advance(&mut rly)?;
-                let target_header = rly.create_header(lh, target_height)?;
-                let res = enclave.update_client(UpdateClientInput {
-                    client_id: client_id.clone(),
-                    any_header: target_header,
-                    current_timestamp: Time::now().add(Duration::from_secs(10))?, // for gaiad's clock drift
-                    include_state: false,
-                    signer,
-                })?;
-                info!("update_client's result is {:?}", res);
+                let stored_heights = enclave
+                    .query_consensus_heights(QueryConsensusHeightsInput {
+                        client_id: client_id.clone(),
+                        pagination: PageRequest::new(0, PageRequest::MAX_LIMIT)?,
+                    })?
+                    .heights
+                    .into_iter()
+                    .map(IBCHeight::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (trusted_height, headers) =
+                    rly.create_headers_from_stored(&stored_heights, target_height)?;
+                assert_eq!(trusted_height, lh);
+                for header in headers {
+                    let res = enclave.update_client(UpdateClientInput {
+                        client_id: client_id.clone(),
+                        any_header: header,
+                        current_timestamp: Time::now(),
+                        include_state: false,
+                        state_filters: vec![],
+                        detect_misbehaviour: false,
+                        expires_at: None,
+                        dry_run: false,
+                        signer,
+                    })?;
+                    info!("update_client's result is {:?}", res);
+                    proofs.push(res.0);
+                }
                 lh = target_height;
-                proofs.push(res.0);
             }
             let messages = proofs
                 .iter()
-                .map(|p| p.message().map(|m| m.to_bytes()))
+                .map(|p| p.message().map(Into::into))
                 .collect::<Result<_, _>>()?;
             let signatures = proofs.into_iter().map(|p| p.signature).collect();
 
             let res = enclave.aggregate_messages(AggregateMessagesInput {
+                client_id: client_id.clone(),
                 messages,
                 signatures,
+                signers: vec![],
                 signer,
-                current_timestamp: Time::now().add(Duration::from_secs(10))?,
+                current_timestamp: Time::now(),
             })?;
             let msg: UpdateStateProxyMessage = res.0.message().unwrap().try_into()?;
             assert!(msg.prev_height == Some(Height::from(last_height)));
@@ -251,6 +447,11 @@ mod tests {
         };
         info!("current last_height is {}", last_height);
 
+        let validations = enclave.validate_all_clients()?;
+        assert_eq!(validations.len(), 1);
+        assert!(validations[0].is_compatible(), "{:?}", validations[0]);
+        assert!(validations[0].consensus_states > 0);
+
         Ok(())
     }
 
@@ -266,9 +467,4 @@ mod tests {
         }
         Ok(height)
     }
-
-    fn merkle_proof_to_bytes(proof: MerkleProof) -> Result<Vec<u8>, anyhow::Error> {
-        let proof = CommitmentProofBytes::try_from(proof)?;
-        Ok(proof.into())
-    }
 }
@@ -1,69 +1,222 @@
 use crate::types::{
-    relayer_header_to_any, to_ibc_channel, to_ibc_client_state, to_ibc_consensus_state,
-    to_ibc_height, to_relayer_channel_id, to_relayer_client_state, to_relayer_height,
-    to_relayer_port_id,
+    merkle_proof_to_bytes, relayer_any_to_any, relayer_header_to_any, to_ibc_channel,
+    to_ibc_client_state, to_ibc_connection, to_ibc_consensus_state, to_ibc_height,
+    to_relayer_channel_id, to_relayer_client_id, to_relayer_client_state, to_relayer_connection_id,
+    to_relayer_height, to_relayer_port_id,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
+pub use chain_rpc::RetryConfig;
+use chain_rpc::{ChainRpc, CometBftRpc};
+use crypto::Address;
+use ecall_commands::{CommitmentPrefix, CommitmentProofPair, VerifyMembershipInput};
 use ibc::clients::ics07_tendermint::client_state::ClientState;
 use ibc::clients::ics07_tendermint::consensus_state::ConsensusState;
+use ibc::core::ics03_connection::connection::ConnectionEnd;
 use ibc::core::ics04_channel::channel::ChannelEnd;
 use ibc::core::ics23_commitment::merkle::MerkleProof;
-use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use ibc::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use ibc::core::ics24_host::path::{
+    ChannelEndPath, ClientConsensusStatePath, ClientStatePath, ConnectionPath, Path,
+};
 use ibc::Height;
+use ibc_proto_relayer::google::protobuf::Any as IBCRelayerAny;
 use ibc_relayer::chain::{
     client::ClientSettings,
     cosmos::{client::Settings, CosmosSdkChain},
     endpoint::ChainEndpoint,
-    requests::{IncludeProof, QueryChannelRequest, QueryHeight},
+    requests::{
+        IncludeProof, QueryChannelRequest, QueryClientStateRequest, QueryConnectionRequest,
+        QueryConsensusStateRequest, QueryHeight,
+    },
 };
 use ibc_relayer::client_state::AnyClientState;
 use ibc_relayer::config::ChainConfig;
 use ibc_relayer::light_client::tendermint::LightClient as TmLightClient;
 use ibc_relayer::light_client::{tendermint::LightClient, LightClient as IBCLightClient};
+use ibc_relayer_types::core::ics02_client::client_state::ClientState as _;
 use lcp_proto::google::protobuf::Any as ProtoAny;
-use lcp_types::Any;
+use lcp_proto::protobuf::Protobuf;
+use lcp_types::{Any, ClientId as ELCClientId, Time};
+use log::*;
 use std::sync::Arc;
-use tendermint_rpc::{Client, HttpClient};
 use tokio::runtime::Runtime as TokioRuntime;
 
 pub struct Relayer {
     tmlc: LightClient,
     chain: CosmosSdkChain,
+    rpc: Box<dyn ChainRpc>,
+    retry: RetryConfig,
 
     client_state: Option<ClientState>,
 }
 
-/// Initialize the light client for the given chain using the given HTTP client
+/// Returns the highest height of the consensus states stored in the ELC that can be used
+/// as the trusted height of a header for `target_height`
+///
+/// `stored_heights` must be in ascending order, as returned by `query_consensus_heights`.
+/// Heights below `earliest_height` are skipped since the node has pruned the blocks at them.
+pub fn select_trusted_height(
+    stored_heights: &[Height],
+    target_height: Height,
+    earliest_height: Height,
+) -> Option<Height> {
+    stored_heights
+        .iter()
+        .rev()
+        .find(|h| **h < target_height && **h >= earliest_height)
+        .copied()
+}
+
+/// Initialize the light client for the given chain using the given RPC
 /// to fetch the node identifier to be used as peer id in the light client.
-async fn init_light_client(rpc_client: &HttpClient, config: &ChainConfig) -> TmLightClient {
+fn init_light_client(rpc: &dyn ChainRpc, config: &ChainConfig) -> TmLightClient {
     use tendermint_light_client_verifier::types::PeerId;
 
-    let peer_id: PeerId = rpc_client.status().await.map(|s| s.node_info.id).unwrap();
+    let peer_id: PeerId = rpc.status().map(|s| s.node_info.id).unwrap();
     TmLightClient::from_config(config, peer_id).unwrap()
 }
 
 impl Relayer {
     pub fn new(cc: ChainConfig, rt: Arc<TokioRuntime>) -> Result<Relayer> {
         let chain = CosmosSdkChain::bootstrap(cc.clone(), rt.clone()).unwrap();
-        let rpc_client = HttpClient::new(cc.rpc_addr.clone()).unwrap();
-        let tmlc = rt.block_on(init_light_client(&rpc_client, &cc));
+        let rpc = CometBftRpc::new(&[cc.rpc_addr.to_string()], rt)?;
+        let tmlc = init_light_client(&rpc, &cc);
         Ok(Self {
             tmlc,
             chain,
+            rpc: Box::new(rpc),
+            retry: RetryConfig::default(),
             client_state: None,
         })
     }
 
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the access to the RPC of the chain, e.g. with one that fails over to other providers
+    pub fn with_rpc(mut self, rpc: Box<dyn ChainRpc>) -> Self {
+        self.rpc = rpc;
+        self
+    }
+
+    /// Create a header for `target_height` that is verifiable with the consensus state at `trusted_height`
+    ///
+    /// This fails if the validator set changed too much to skip from `trusted_height` to `target_height`;
+    /// use `create_headers` to get the intermediate headers in that case.
     pub fn create_header(&mut self, trusted_height: Height, target_height: Height) -> Result<Any> {
-        let (target, supporting) = self.chain.build_header(
-            to_relayer_height(trusted_height),
-            to_relayer_height(target_height),
-            &AnyClientState::Tendermint(to_relayer_client_state(
-                self.client_state.clone().unwrap(),
-            )),
-        )?;
-        assert!(supporting.is_empty());
-        Ok(relayer_header_to_any(target))
+        let mut headers = self.create_headers(trusted_height, target_height)?;
+        if headers.len() != 1 {
+            bail!(
+                "intermediate headers are required to skip from {} to {}: headers={}",
+                trusted_height,
+                target_height,
+                headers.len()
+            );
+        }
+        Ok(headers.pop().unwrap())
+    }
+
+    /// Create the headers to update the client from `trusted_height` to `target_height`
+    ///
+    /// If the validator set changed too much for skipping verification, the headers at the intermediate
+    /// heights found by the bisection of the light client precede the target header.
+    /// The headers must be submitted in order, each one trusting the height of the previous one.
+    pub fn create_headers(
+        &mut self,
+        trusted_height: Height,
+        target_height: Height,
+    ) -> Result<Vec<Any>> {
+        let client_state =
+            AnyClientState::Tendermint(to_relayer_client_state(self.client_state.clone().unwrap()));
+        let (target, supporting) = self.with_retry("build_header", |rly| {
+            Ok(rly.chain.build_header(
+                to_relayer_height(trusted_height),
+                to_relayer_height(target_height),
+                &client_state,
+            )?)
+        })?;
+        if !supporting.is_empty() {
+            info!(
+                "fall back to intermediate headers: trusted_height={} target_height={} intermediate={}",
+                trusted_height,
+                target_height,
+                supporting.len()
+            );
+        }
+        Ok(supporting
+            .into_iter()
+            .chain(std::iter::once(target))
+            .map(relayer_header_to_any)
+            .collect())
+    }
+
+    /// Select the trusted height from the heights stored in the ELC, and create the headers for `target_height`
+    ///
+    /// Returns the selected trusted height and the headers to submit in order.
+    pub fn create_headers_from_stored(
+        &mut self,
+        stored_heights: &[Height],
+        target_height: Height,
+    ) -> Result<(Height, Vec<Any>)> {
+        let earliest_height = self.query_earliest_height()?;
+        let trusted_height =
+            match select_trusted_height(stored_heights, target_height, earliest_height) {
+                Some(height) => height,
+                None => bail!(
+                    "no stored height is available as the trusted height: target_height={} earliest_height={} stored_heights={:?}",
+                    target_height,
+                    earliest_height,
+                    stored_heights
+                ),
+            };
+        debug!(
+            "selected the trusted height: trusted_height={} target_height={}",
+            trusted_height, target_height
+        );
+        Ok((
+            trusted_height,
+            self.create_headers(trusted_height, target_height)?,
+        ))
+    }
+
+    /// Returns the lowest height of the blocks that the node has not pruned
+    pub fn query_earliest_height(&mut self) -> Result<Height> {
+        let revision_number = self.query_latest_height()?.revision_number();
+        // the RPC retries the transient failures by itself
+        let status = self.rpc.status()?;
+        Ok(Height::new(
+            revision_number,
+            status.sync_info.earliest_block_height.value(),
+        )?)
+    }
+
+    /// Run `f` until it succeeds or the retries are exhausted, waiting with exponential backoff in between
+    fn with_retry<T>(
+        &mut self,
+        name: &str,
+        mut f: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match f(self) {
+                Ok(res) => return Ok(res),
+                Err(e) if attempt < self.retry.max_retries => {
+                    let backoff = self.retry.backoff(attempt);
+                    warn!(
+                        "{} failed, retrying: attempt={} backoff={:?} err={:?}",
+                        name,
+                        attempt + 1,
+                        backoff,
+                        e
+                    );
+                    std::thread::sleep(backoff);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     pub fn fetch_state(&mut self, height: Height) -> Result<(ClientState, ConsensusState)> {
@@ -118,4 +271,189 @@ impl Relayer {
             height.increment(),
         ))
     }
+
+    pub fn query_connection_proof(
+        &self,
+        connection_id: ConnectionId,
+        height: Option<Height>, // height of consensus state
+    ) -> Result<(ConnectionEnd, MerkleProof, Height)> {
+        let height = self.query_height(height)?;
+        let req = QueryConnectionRequest {
+            connection_id: to_relayer_connection_id(connection_id),
+            height: QueryHeight::Specific(to_relayer_height(height)),
+        };
+        let res = self.chain.query_connection(req, IncludeProof::Yes)?;
+        Ok((
+            to_ibc_connection(res.0),
+            MerkleProof {
+                proofs: res.1.unwrap().proofs,
+            },
+            height.increment(),
+        ))
+    }
+
+    /// Returns the client state of the counterparty chain stored in this chain
+    pub fn query_client_state_proof(
+        &self,
+        client_id: ClientId,
+        height: Option<Height>, // height of consensus state
+    ) -> Result<(Any, Height, MerkleProof, Height)> {
+        let height = self.query_height(height)?;
+        let req = QueryClientStateRequest {
+            client_id: to_relayer_client_id(client_id),
+            height: QueryHeight::Specific(to_relayer_height(height)),
+        };
+        let res = self.chain.query_client_state(req, IncludeProof::Yes)?;
+        let latest_height = to_ibc_height(res.0.latest_height());
+        Ok((
+            relayer_any_to_any(IBCRelayerAny::from(res.0)),
+            latest_height,
+            MerkleProof {
+                proofs: res.1.unwrap().proofs,
+            },
+            height.increment(),
+        ))
+    }
+
+    /// Returns the consensus state of the counterparty chain stored in this chain
+    pub fn query_consensus_state_proof(
+        &self,
+        client_id: ClientId,
+        consensus_height: Height,
+        height: Option<Height>, // height of consensus state
+    ) -> Result<(Any, MerkleProof, Height)> {
+        let height = self.query_height(height)?;
+        let req = QueryConsensusStateRequest {
+            client_id: to_relayer_client_id(client_id),
+            consensus_height: to_relayer_height(consensus_height),
+            query_height: QueryHeight::Specific(to_relayer_height(height)),
+        };
+        let res = self.chain.query_consensus_state(req, IncludeProof::Yes)?;
+        Ok((
+            relayer_any_to_any(IBCRelayerAny::from(res.0)),
+            MerkleProof {
+                proofs: res.1.unwrap().proofs,
+            },
+            height.increment(),
+        ))
+    }
+
+    /// Build the inputs to verify the proofs required by the counterparty chain
+    /// for ConnOpenTry/ConnOpenAck: the connection end, the client state of the counterparty chain
+    /// and its consensus state at the latest height. All proofs are queried at the same height.
+    pub fn connection_handshake_proofs(
+        &self,
+        elc_client_id: ELCClientId,
+        prefix: CommitmentPrefix,
+        connection_id: ConnectionId,
+        signer: Address,
+        height: Option<Height>, // height of consensus state
+    ) -> Result<Vec<VerifyMembershipInput>> {
+        let height = Some(self.query_height(height)?.increment());
+        let (connection, connection_proof, proof_height) =
+            self.query_connection_proof(connection_id.clone(), height)?;
+        let client_id = connection.client_id().clone();
+        let (client_state, consensus_height, client_state_proof, _) =
+            self.query_client_state_proof(client_id.clone(), height)?;
+        let (consensus_state, consensus_state_proof, _) =
+            self.query_consensus_state_proof(client_id.clone(), consensus_height, height)?;
+
+        let input = |path: Path, value: Vec<u8>, proof: MerkleProof| -> Result<_> {
+            Ok(VerifyMembershipInput {
+                client_id: elc_client_id.clone(),
+                prefix: prefix.clone(),
+                path: path.to_string().parse()?,
+                value,
+                proof: CommitmentProofPair(proof_height.into(), merkle_proof_to_bytes(proof)?),
+                expires_at: None,
+                consensus_height: None,
+                current_timestamp: Time::now(),
+                signer,
+            })
+        };
+        Ok(vec![
+            input(
+                Path::Connection(ConnectionPath(connection_id)),
+                connection.encode_vec()?,
+                connection_proof,
+            )?,
+            input(
+                Path::ClientState(ClientStatePath(client_id.clone())),
+                client_state.encode_vec()?,
+                client_state_proof,
+            )?,
+            input(
+                Path::ClientConsensusState(ClientConsensusStatePath {
+                    client_id,
+                    epoch: consensus_height.revision_number(),
+                    height: consensus_height.revision_height(),
+                }),
+                consensus_state.encode_vec()?,
+                consensus_state_proof,
+            )?,
+        ])
+    }
+
+    /// Build the inputs to verify the proof required by the counterparty chain
+    /// for ChanOpenTry/ChanOpenAck/ChanOpenConfirm
+    pub fn channel_handshake_proofs(
+        &self,
+        elc_client_id: ELCClientId,
+        prefix: CommitmentPrefix,
+        port_id: PortId,
+        channel_id: ChannelId,
+        signer: Address,
+        height: Option<Height>, // height of consensus state
+    ) -> Result<Vec<VerifyMembershipInput>> {
+        let (channel, proof, proof_height) =
+            self.query_channel_proof(port_id.clone(), channel_id.clone(), height)?;
+        Ok(vec![VerifyMembershipInput {
+            client_id: elc_client_id,
+            prefix,
+            path: Path::ChannelEnd(ChannelEndPath(port_id, channel_id))
+                .to_string()
+                .parse()?,
+            value: channel.encode_vec()?,
+            proof: CommitmentProofPair(proof_height.into(), merkle_proof_to_bytes(proof)?),
+            expires_at: None,
+            consensus_height: None,
+            current_timestamp: Time::now(),
+            signer,
+        }])
+    }
+
+    /// Returns the height to query the states at: the previous height of the consensus state
+    fn query_height(&self, height: Option<Height>) -> Result<Height> {
+        Ok(match height {
+            Some(height) => height.decrement()?,
+            None => self.query_latest_height()?.decrement()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_select_trusted_height() {
+        let h = |height| Height::new(0, height).unwrap();
+        let stored = [h(10), h(20), h(30)];
+        assert_eq!(select_trusted_height(&stored, h(25), h(1)), Some(h(20)));
+        assert_eq!(select_trusted_height(&stored, h(35), h(1)), Some(h(30)));
+        // the node has pruned the blocks up to the height 25
+        assert_eq!(select_trusted_height(&stored, h(35), h(25)), Some(h(30)));
+        assert_eq!(select_trusted_height(&stored, h(28), h(25)), None);
+        assert_eq!(select_trusted_height(&stored, h(10), h(1)), None);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = RetryConfig::default();
+        assert_eq!(retry.backoff(0), Duration::from_millis(500));
+        assert_eq!(retry.backoff(2), Duration::from_secs(2));
+        assert_eq!(retry.backoff(10), retry.max_backoff);
+        assert_eq!(retry.backoff(u32::MAX), retry.max_backoff);
+    }
 }
@@ -4,21 +4,29 @@ use ibc::{
         consensus_state::ConsensusState as TendermintConsensusState,
     },
     core::{
+        ics03_connection::connection::ConnectionEnd,
         ics04_channel::channel::ChannelEnd,
-        ics24_host::identifier::{ChainId, ChannelId, PortId},
+        ics23_commitment::{commitment::CommitmentProofBytes, merkle::MerkleProof},
+        ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
     },
     Height,
 };
 use ibc_proto_relayer::{
     google::protobuf::Any as IBCRelayerAny, protobuf::Protobuf as RelayerProtobuf,
 };
-use ibc_relayer_types::core::ics24_host::identifier::{ChannelId as RChannelId, PortId as RPortId};
+use ibc_relayer_types::core::ics24_host::identifier::{
+    ChannelId as RChannelId, ClientId as RClientId, ConnectionId as RConnectionId,
+    PortId as RPortId,
+};
 use ibc_relayer_types::{
     clients::ics07_tendermint::{
         client_state::ClientState as RTendermintClientState,
         consensus_state::ConsensusState as RTendermintConsensusState, header::Header as RHeader,
     },
-    core::ics04_channel::channel::ChannelEnd as RChannelEnd,
+    core::{
+        ics03_connection::connection::ConnectionEnd as RConnectionEnd,
+        ics04_channel::channel::ChannelEnd as RChannelEnd,
+    },
 };
 use ibc_relayer_types::{core::ics24_host::identifier::ChainId as RChainId, Height as RHeight};
 use lcp_proto::{google::protobuf::Any as ProtoAny, protobuf::Protobuf};
@@ -35,12 +43,20 @@ pub(crate) fn relayer_header_to_any(value: RHeader) -> Any {
     Any::new(any.type_url, any.value)
 }
 
+pub(crate) fn relayer_any_to_any(value: IBCRelayerAny) -> Any {
+    Any::new(value.type_url, value.value)
+}
+
 /// relayer-types to ibc
 
 pub(crate) fn to_ibc_channel(value: RChannelEnd) -> ChannelEnd {
     ChannelEnd::decode_vec(&value.encode_vec().unwrap()).unwrap()
 }
 
+pub(crate) fn to_ibc_connection(value: RConnectionEnd) -> ConnectionEnd {
+    ConnectionEnd::decode_vec(&value.encode_vec().unwrap()).unwrap()
+}
+
 pub(crate) fn to_ibc_height(value: RHeight) -> Height {
     Height::new(value.revision_number(), value.revision_height()).unwrap()
 }
@@ -81,6 +97,14 @@ pub(crate) fn to_relayer_port_id(value: PortId) -> RPortId {
     RPortId::from_str(value.as_str()).unwrap()
 }
 
+pub(crate) fn to_relayer_connection_id(value: ConnectionId) -> RConnectionId {
+    RConnectionId::from_str(value.as_str()).unwrap()
+}
+
+pub(crate) fn to_relayer_client_id(value: ClientId) -> RClientId {
+    RClientId::from_str(value.as_str()).unwrap()
+}
+
 pub(crate) fn to_relayer_client_state(value: TendermintClientState) -> RTendermintClientState {
     let any = ProtoAny::from(value);
     RTendermintClientState::try_from(IBCRelayerAny {
@@ -89,3 +113,10 @@ pub(crate) fn to_relayer_client_state(value: TendermintClientState) -> RTendermi
     })
     .unwrap()
 }
+
+/// ibc to lcp types
+
+pub(crate) fn merkle_proof_to_bytes(proof: MerkleProof) -> Result<Vec<u8>, anyhow::Error> {
+    let proof = CommitmentProofBytes::try_from(proof)?;
+    Ok(proof.into())
+}
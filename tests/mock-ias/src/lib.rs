@@ -0,0 +1,404 @@
+use anyhow::{anyhow, bail, Result};
+use host_environment::IASEndpoint;
+use log::*;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use serde_json::json;
+use sha2::Sha256;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// The PEM-encoded root CA that issues the signing certificate of the mock
+pub const MOCK_IAS_ROOT_CA: &str = include_str!("../certs/root.crt");
+const MOCK_IAS_SIGNING_CERT: &str = include_str!("../certs/signing.crt");
+const MOCK_IAS_SIGNING_KEY: &str = include_str!("../certs/signing.key");
+
+const SIGRL_PATH_PREFIXES: [&str; 2] = [
+    "/sgx/dev/attestation/v4/sigrl/",
+    "/sgx/attestation/v4/sigrl/",
+];
+const REPORT_PATHS: [&str; 2] = [
+    "/sgx/dev/attestation/v4/report",
+    "/sgx/attestation/v4/report",
+];
+
+/// The size of the quote body that IAS includes in the report, i.e. the quote without the signature
+const QUOTE_BODY_SIZE: usize = 432;
+const MAX_HEADERS: usize = 32;
+
+/// MockIASConfig is what the mock IAS responds with
+#[derive(Clone, Debug)]
+pub struct MockIASConfig {
+    pub signing_key: RsaPrivateKey,
+    /// The PEM-encoded chain of the signing certificate, which starts with the signing certificate itself
+    pub signing_cert_chain: String,
+    /// The PEM-encoded root CA of the chain, which the enclave verifies the reports against
+    pub root_ca: String,
+    pub isv_enclave_quote_status: String,
+    pub advisory_ids: Vec<String>,
+    /// The SigRL of every EPID group
+    pub sigrl: Vec<u8>,
+}
+
+impl Default for MockIASConfig {
+    /// The certificates in `certs`, which are generated by `make gencerts-mock-ias`
+    fn default() -> Self {
+        Self {
+            signing_key: RsaPrivateKey::from_pkcs8_pem(MOCK_IAS_SIGNING_KEY)
+                .expect("the signing key of the mock IAS must be a PKCS#8 RSA key"),
+            signing_cert_chain: format!("{}{}", MOCK_IAS_SIGNING_CERT, MOCK_IAS_ROOT_CA),
+            root_ca: MOCK_IAS_ROOT_CA.to_string(),
+            isv_enclave_quote_status: "OK".to_string(),
+            advisory_ids: vec![],
+            sigrl: vec![],
+        }
+    }
+}
+
+/// MockIAS is a local server that implements the `sigrl` and `report` APIs of the Intel Attestation Service
+///
+/// It serves plain HTTP and accepts any subscription key and any quote, so that the EPID remote attestation
+/// can run in CI and sw-sim environments. The server stops when it is dropped.
+pub struct MockIAS {
+    addr: SocketAddr,
+    root_ca: String,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockIAS {
+    /// Start the server on the given address, e.g. `127.0.0.1:0` for an ephemeral port
+    pub fn spawn<A: ToSocketAddrs>(addr: A, config: MockIASConfig) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let root_ca = config.root_ca.clone();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let shutdown = shutdown.clone();
+            let server = Server::new(config);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = server.serve(stream) {
+                                warn!("mock IAS: failed to serve a request: {}", e);
+                            }
+                        }
+                        Err(e) => warn!("mock IAS: failed to accept a connection: {}", e),
+                    }
+                }
+            })
+        };
+        info!("mock IAS is listening on {}", addr);
+        Ok(Self {
+            addr,
+            root_ca,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the endpoint for the host environment to connect to this server instead of Intel's
+    pub fn endpoint(&self) -> IASEndpoint {
+        IASEndpoint::Mock {
+            addr: self.addr.to_string(),
+            root_ca: self.root_ca.as_bytes().to_vec(),
+        }
+    }
+
+    /// Block until the server stops, which never happens unless it fails to accept connections
+    pub fn join(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockIAS {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.shutdown.store(true, Ordering::SeqCst);
+            // wake up the accept loop so that it sees the flag
+            let _ = TcpStream::connect(self.addr);
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Server {
+    config: MockIASConfig,
+    signing_key: SigningKey<Sha256>,
+    report_count: AtomicU64,
+}
+
+impl Server {
+    fn new(config: MockIASConfig) -> Self {
+        Self {
+            signing_key: SigningKey::<Sha256>::new(config.signing_key.clone()),
+            config,
+            report_count: AtomicU64::new(0),
+        }
+    }
+
+    fn serve(&self, mut stream: TcpStream) -> Result<()> {
+        let resp = match read_request(&mut stream) {
+            Ok(req) => self.handle(req),
+            Err(e) => Response::new(400, "Bad Request").with_body(e.to_string()),
+        };
+        stream.write_all(&resp.to_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    fn handle(&self, req: Request) -> Response {
+        debug!("mock IAS: {} {}", req.method, req.path);
+        // IAS rejects the requests without a subscription key
+        if req.header("Ocp-Apim-Subscription-Key").is_none() {
+            return Response::new(401, "Unauthorized");
+        }
+        if req.method == "GET" {
+            if SIGRL_PATH_PREFIXES
+                .iter()
+                .any(|prefix| req.path.starts_with(prefix))
+            {
+                return Response::new(200, "OK").with_body(base64::encode(&self.config.sigrl));
+            }
+        } else if req.method == "POST" && REPORT_PATHS.contains(&req.path.as_str()) {
+            return match self.create_report(&req.body) {
+                Ok(resp) => resp,
+                Err(e) => Response::new(400, "Bad Request").with_body(e.to_string()),
+            };
+        }
+        Response::new(404, "Not Found")
+    }
+
+    /// Create a report that IAS would return for the quote in the request, and sign it
+    fn create_report(&self, body: &[u8]) -> Result<Response> {
+        let req: serde_json::Value = serde_json::from_slice(body)?;
+        let quote = req
+            .get("isvEnclaveQuote")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("missing isvEnclaveQuote"))?;
+        let quote = base64::decode(quote).map_err(|e| anyhow!("invalid isvEnclaveQuote: {}", e))?;
+        if quote.len() < QUOTE_BODY_SIZE {
+            bail!("the quote is too short: len={}", quote.len());
+        }
+
+        let mut avr = json!({
+            "id": self.report_count.fetch_add(1, Ordering::SeqCst).to_string(),
+            "timestamp": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+            "version": 4,
+            "advisoryURL": "https://security-center.intel.com",
+            "advisoryIDs": self.config.advisory_ids,
+            "isvEnclaveQuoteStatus": self.config.isv_enclave_quote_status,
+            "isvEnclaveQuoteBody": base64::encode(&quote[..QUOTE_BODY_SIZE]),
+        });
+        if let Some(nonce) = req.get("nonce") {
+            avr["nonce"] = nonce.clone();
+        }
+        let avr = avr.to_string();
+        let signature = self.signing_key.sign(avr.as_bytes()).to_vec();
+
+        Ok(Response::new(200, "OK")
+            .with_header("X-IASReport-Signature", base64::encode(signature))
+            .with_header(
+                "X-IASReport-Signing-Certificate",
+                percent_encode(&self.config.signing_cert_chain),
+            )
+            .with_header("Content-Type", "application/json".to_string())
+            .with_body(avr))
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read a request and its body, whose length is given by `Content-Length`
+fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            bail!("the connection is closed before the request is complete");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut parsed = httparse::Request::new(&mut headers);
+        let header_len = match parsed.parse(&buf)? {
+            httparse::Status::Complete(len) => len,
+            httparse::Status::Partial => continue,
+        };
+        let headers: Vec<(String, String)> = parsed
+            .headers
+            .iter()
+            .map(|h| {
+                (
+                    h.name.to_string(),
+                    String::from_utf8_lossy(h.value).trim().to_string(),
+                )
+            })
+            .collect();
+        let content_length = headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case("Content-Length"))
+            .map(|(_, v)| v.parse::<usize>())
+            .transpose()?
+            .unwrap_or_default();
+        if buf.len() < header_len + content_length {
+            continue;
+        }
+        return Ok(Request {
+            method: parsed.method.unwrap_or_default().to_string(),
+            path: parsed.path.unwrap_or_default().to_string(),
+            headers,
+            body: buf[header_len..header_len + content_length].to_vec(),
+        });
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn new(status: u16, reason: &'static str) -> Self {
+        Self {
+            status,
+            reason,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    fn with_header(mut self, name: &'static str, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    fn with_body(mut self, body: String) -> Self {
+        self.body = body;
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut resp = format!("HTTP/1.1 {} {}\r\n", self.status, self.reason);
+        for (name, value) in self.headers.iter() {
+            resp.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        resp.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        resp.into_bytes()
+    }
+}
+
+/// Percent-encode the PEM chain as IAS does for the `X-IASReport-Signing-Certificate` header
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use attestation_report::{
+        parse_ias_report_response, parse_ias_sigrl_response, verify_report_with_root_ca,
+    };
+    use lcp_types::Time;
+
+    fn request(addr: SocketAddr, req: String) -> Vec<u8> {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(req.as_bytes()).unwrap();
+        let mut resp = Vec::new();
+        stream.read_to_end(&mut resp).unwrap();
+        resp
+    }
+
+    #[test]
+    fn test_mock_ias() {
+        let ias = MockIAS::spawn(
+            "127.0.0.1:0",
+            MockIASConfig {
+                sigrl: vec![1, 2, 3],
+                advisory_ids: vec!["INTEL-SA-00219".to_string()],
+                isv_enclave_quote_status: "GROUP_OUT_OF_DATE".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let resp = request(
+            ias.addr(),
+            "GET /sgx/dev/attestation/v4/sigrl/00000b22 HTTP/1.1\r\nHOST: api.trustedservices.intel.com\r\nOcp-Apim-Subscription-Key: key\r\nConnection: Close\r\n\r\n".to_string(),
+        );
+        assert_eq!(parse_ias_sigrl_response(&resp).unwrap(), vec![1, 2, 3]);
+
+        let quote = base64::encode([7u8; 1116]);
+        let body = format!("{{\"isvEnclaveQuote\":\"{}\"}}\r\n", quote);
+        let report_request = |key_header: &str| {
+            format!(
+                "POST /sgx/dev/attestation/v4/report HTTP/1.1\r\nHOST: api.trustedservices.intel.com\r\n{}Content-Length:{}\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n{}",
+                key_header,
+                body.len(),
+                body
+            )
+        };
+        let resp = request(
+            ias.addr(),
+            report_request("Ocp-Apim-Subscription-Key:key\r\n"),
+        );
+        let eavr = parse_ias_report_response(&resp).unwrap();
+        verify_report_with_root_ca(Time::now(), &eavr, MOCK_IAS_ROOT_CA.as_bytes()).unwrap();
+        let avr = eavr.get_avr().unwrap();
+        assert_eq!(avr.isv_enclave_quote_status, "GROUP_OUT_OF_DATE");
+        assert_eq!(avr.advisory_ids, vec!["INTEL-SA-00219".to_string()]);
+        assert_eq!(
+            base64::decode(avr.isv_enclave_quote_body).unwrap(),
+            vec![7u8; QUOTE_BODY_SIZE]
+        );
+        assert!(avr.attestation_time().is_ok());
+        // the report is not issued by Intel
+        assert!(attestation_report::verify_report(Time::now(), &eavr).is_err());
+
+        // missing subscription key
+        assert!(parse_ias_report_response(&request(ias.addr(), report_request(""))).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use mock_ias::{MockIAS, MockIASConfig, MOCK_IAS_ROOT_CA};
+
+/// Serve the mock IAS on the address given as the first argument, or `127.0.0.1:8443` by default
+///
+/// Run `lcp` built with `sgx-sw` with `--mock_ias <addr> --mock_ias_root_ca tests/mock-ias/certs/root.crt`
+/// to run the IAS remote attestation against it.
+fn main() -> Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8443".to_string());
+    let ias = MockIAS::spawn(addr, MockIASConfig::default())?;
+    println!("mock IAS is listening on {}", ias.addr());
+    println!("root CA:\n{}", MOCK_IAS_ROOT_CA);
+    ias.join();
+    Ok(())
+}
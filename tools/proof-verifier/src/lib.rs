@@ -0,0 +1,200 @@
+//! Verification of a commitment proof as a downstream LCP client would do it, without an enclave.
+//!
+//! This is a debugging aid for the developers of the downstream clients: it takes the proxy message,
+//! the signature and the AVR of the signer, and reports the decoded message or the first check that fails.
+use anyhow::{anyhow, bail, Result};
+use attestation_report::{
+    verify_report, verify_report_with_root_ca, EndorsedAttestationVerificationReport,
+};
+use commitments::ProxyMessage;
+use crypto::{Address, SignatureScheme};
+use lcp_types::{Mrenclave, Time};
+use serde::Serialize;
+use std::time::Duration;
+
+const QUOTE_STATUS_OK: &str = "OK";
+
+/// VerifyParams are the parameters of the LCP client that the proof is verified against
+#[derive(Clone, Debug)]
+pub struct VerifyParams {
+    pub signature_scheme: SignatureScheme,
+    /// PEM-encoded root CA of the report signing certificate. None means the one of Intel
+    pub root_ca: Option<Vec<u8>>,
+    /// If set, the MRENCLAVE in the report must match
+    pub mrenclave: Option<Mrenclave>,
+    /// If set, the report must have been issued within this duration before `now`
+    pub key_expiration: Option<Duration>,
+    /// Quote statuses accepted in addition to `OK`
+    pub allowed_quote_statuses: Vec<String>,
+    pub now: Time,
+}
+
+/// VerifiedProof is the decoded message of a valid proof and the attestation of its signer
+#[derive(Debug, Serialize)]
+pub struct VerifiedProof {
+    pub message_type: u16,
+    /// The proxy message in its serde representation
+    pub message: serde_json::Value,
+    pub signer: String,
+    pub signature_scheme: String,
+    pub mrenclave: String,
+    pub isv_enclave_quote_status: String,
+    pub advisory_ids: Vec<String>,
+    /// UNIX time in seconds of the attestation
+    pub attested_at: u64,
+    /// UNIX time in seconds after which the key is rejected, if `key_expiration` is given
+    pub key_expired_at: Option<u64>,
+}
+
+/// Verify the signature over the message by the key attested in the report
+pub fn verify_proof(
+    message: &[u8],
+    signature: &[u8],
+    eavr: &EndorsedAttestationVerificationReport,
+    params: &VerifyParams,
+) -> Result<VerifiedProof> {
+    match params.root_ca.as_ref() {
+        Some(root_ca) => verify_report_with_root_ca(params.now, eavr, root_ca),
+        None => verify_report(params.now, eavr),
+    }
+    .map_err(|e| anyhow!("failed to verify the report signature: {}", e))?;
+    let avr = eavr.get_avr()?;
+    let quote = avr.parse_quote()?;
+
+    let mrenclave = Mrenclave::from(quote.get_mrenclave());
+    if let Some(expected) = params.mrenclave.as_ref() {
+        if *expected != mrenclave {
+            bail!(
+                "mrenclave mismatch: expected={} actual={}",
+                expected,
+                mrenclave
+            );
+        }
+    }
+    if quote.status != QUOTE_STATUS_OK && !params.allowed_quote_statuses.contains(&quote.status) {
+        bail!("quote status is not allowed: status={}", quote.status);
+    }
+    let key_expired_at = match params.key_expiration {
+        Some(expiration) => {
+            let expired_at = (quote.attestation_time + expiration)?;
+            if params.now > expired_at {
+                bail!(
+                    "the key has expired: attestation_time={} expired_at={}",
+                    quote.attestation_time,
+                    expired_at
+                );
+            }
+            Some(expired_at.as_unix_timestamp_secs())
+        }
+        None => None,
+    };
+
+    let signer = quote.get_enclave_key_address()?;
+    let msg = verify_message(
+        message,
+        signature,
+        signer,
+        params.signature_scheme,
+        params.now,
+    )?;
+    Ok(VerifiedProof {
+        message_type: msg.message_type(),
+        message: serde_json::to_value(&msg)?,
+        signer: signer.to_hex_string(),
+        signature_scheme: params.signature_scheme.to_string(),
+        mrenclave: mrenclave.to_hex_string(),
+        isv_enclave_quote_status: quote.status,
+        advisory_ids: avr.advisory_ids,
+        attested_at: quote.attestation_time.as_unix_timestamp_secs(),
+        key_expired_at,
+    })
+}
+
+/// Decode the message and verify that the signature over it was produced by the signer
+///
+/// A message that carries an expiry must not have expired at `now`.
+pub fn verify_message(
+    message: &[u8],
+    signature: &[u8],
+    signer: Address,
+    scheme: SignatureScheme,
+    now: Time,
+) -> Result<ProxyMessage> {
+    let msg = ProxyMessage::from_bytes(message)
+        .map_err(|e| anyhow!("failed to decode the message: {}", e))?;
+    scheme
+        .verify_signer(message, signature, signer)
+        .map_err(|e| anyhow!("failed to verify the signature: {}", e))?;
+    msg.check_expiry(now)
+        .map_err(|e| anyhow!("the message has expired: {}", e))?;
+    Ok(msg)
+}
+
+/// Parse a signature scheme in the form of `<digest>-<format>`, e.g. `keccak256-recoverable`
+pub fn parse_signature_scheme(s: &str) -> Result<SignatureScheme> {
+    let (digest, format) = s
+        .split_once('-')
+        .ok_or_else(|| anyhow!("the scheme must be `<digest>-<format>`: {}", s))?;
+    Ok(SignatureScheme::new(
+        digest.parse().map_err(|e| anyhow!("{:?}", e))?,
+        format.parse().map_err(|e| anyhow!("{:?}", e))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::{StateID, UpdateStateProxyMessage, ValidationContext};
+    use crypto::{EnclaveKey, Signer};
+    use lcp_types::Height;
+
+    #[test]
+    fn test_verify_message() {
+        let ek = EnclaveKey::from_secret(&[1; 32]).unwrap();
+        let signer = ek.pubkey().unwrap().as_address();
+        let now = Time::from_unix_timestamp_nanos(1_700_000_000_000_000_000).unwrap();
+        let message = ProxyMessage::from(UpdateStateProxyMessage {
+            prev_height: None,
+            prev_state_id: None,
+            post_height: Height::new(1, 100),
+            post_state_id: StateID::from([2; 32]),
+            frozen_height: None,
+            timestamp: now,
+            expires_at: None,
+            context: ValidationContext::Empty,
+            emitted_states: vec![],
+        })
+        .to_bytes();
+
+        for scheme in ["keccak256-recoverable", "sha256-compact"] {
+            let scheme = parse_signature_scheme(scheme).unwrap();
+            let signature = ek.sign_with_scheme(scheme, &message).unwrap();
+            let msg = verify_message(&message, &signature, signer, scheme, now).unwrap();
+            assert_eq!(msg.to_bytes(), message);
+            // the signature must be verified with the scheme it was produced with
+            let other = SignatureScheme::default();
+            if other != scheme {
+                assert!(verify_message(&message, &signature, signer, other, now).is_err());
+            }
+        }
+        let signature = ek.sign(&message).unwrap();
+        assert!(verify_message(
+            &message,
+            &signature,
+            Address([3; 20]),
+            SignatureScheme::default(),
+            now
+        )
+        .is_err());
+        assert!(verify_message(
+            &message[1..],
+            &signature,
+            signer,
+            SignatureScheme::default(),
+            now
+        )
+        .is_err());
+        assert!(parse_signature_scheme("keccak256").is_err());
+        assert!(parse_signature_scheme("sha3-recoverable").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Result};
+use attestation_report::EndorsedAttestationVerificationReport;
+use clap::Parser;
+use lcp_types::{Mrenclave, Time};
+use proof_verifier::{parse_signature_scheme, verify_proof, VerifyParams};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Verify a commitment proof of LCP without an enclave, and print the decoded message as JSON
+#[derive(Debug, Parser)]
+#[clap(name = "lcp-verify-proof", version)]
+struct Cli {
+    /// hex-encoded proxy message, which is `CommitmentProof.message`
+    #[clap(long = "message", help = "hex-encoded proxy message")]
+    message: String,
+    /// hex-encoded signature over the message
+    #[clap(long = "signature", help = "hex-encoded signature over the message")]
+    signature: String,
+    /// Path to a JSON file that contains the endorsed AVR of the signer
+    #[clap(
+        long = "avr",
+        help = "Path to a JSON file that contains the endorsed AVR of the signer"
+    )]
+    avr: PathBuf,
+    /// Signature scheme of the client, in the form of `<digest>-<format>`
+    #[clap(
+        long = "signature_scheme",
+        default_value = "keccak256-recoverable",
+        help = "Signature scheme of the client: `<keccak256|sha256>-<recoverable|ethereum|compact>`"
+    )]
+    signature_scheme: String,
+    /// Path to the PEM-encoded root CA of the report signing certificate
+    /// If not set, the root CA of the Intel SGX attestation report signing is used.
+    #[clap(
+        long = "root_ca",
+        help = "Path to the PEM-encoded root CA of the report signing certificate, e.g. the one of a mock IAS"
+    )]
+    root_ca: Option<PathBuf>,
+    /// Expected MRENCLAVE of the attested enclave
+    #[clap(
+        long = "mrenclave",
+        help = "Expected MRENCLAVE(hex) of the attested enclave"
+    )]
+    mrenclave: Option<String>,
+    /// Key expiration in seconds from the attestation time
+    #[clap(
+        long = "key_expiration",
+        help = "Key expiration in seconds from the attestation time"
+    )]
+    key_expiration: Option<u64>,
+    /// Quote statuses accepted in addition to `OK`
+    #[clap(
+        long = "allowed_quote_statuses",
+        value_delimiter = ',',
+        help = "Quote statuses accepted in addition to `OK`"
+    )]
+    allowed_quote_statuses: Vec<String>,
+    /// UNIX time in seconds to verify the proof at, which defaults to the current time
+    #[clap(
+        long = "now",
+        help = "UNIX time in seconds to verify the proof at instead of the current time"
+    )]
+    now: Option<u64>,
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(
+        s.trim().strip_prefix("0x").unwrap_or(s.trim()),
+    )?)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let eavr: EndorsedAttestationVerificationReport = serde_json::from_slice(
+        &std::fs::read(&cli.avr).map_err(|e| anyhow!("failed to read {:?}: {}", cli.avr, e))?,
+    )?;
+    let params = VerifyParams {
+        signature_scheme: parse_signature_scheme(&cli.signature_scheme)?,
+        root_ca: cli.root_ca.as_ref().map(std::fs::read).transpose()?,
+        mrenclave: cli
+            .mrenclave
+            .as_deref()
+            .map(|s| Ok::<_, anyhow::Error>(Mrenclave::try_from(decode_hex(s)?)?))
+            .transpose()?,
+        key_expiration: cli.key_expiration.map(Duration::from_secs),
+        allowed_quote_statuses: cli.allowed_quote_statuses.clone(),
+        now: match cli.now {
+            Some(secs) => Time::from_unix_timestamp_nanos(secs as u128 * 1_000_000_000)?,
+            None => Time::now(),
+        },
+    };
+    let proof = verify_proof(
+        &decode_hex(&cli.message)?,
+        &decode_hex(&cli.signature)?,
+        &eavr,
+        &params,
+    )?;
+    println!("{}", serde_json::to_string_pretty(&proof)?);
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+/// Print the schema of the proxy messages as JSON, or write it to the path given as the first argument
+fn main() -> Result<()> {
+    let json = serde_json::to_string_pretty(&commitments::message_schema())?;
+    match std::env::args().nth(1) {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}
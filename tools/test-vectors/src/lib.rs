@@ -0,0 +1,210 @@
+//! Canonical test vectors for the proxy messages and their commitment proofs.
+//!
+//! Every vector is generated from fixed inputs and signed with a fixed test key, so the output
+//! is deterministic and can be checked into downstream verifier implementations.
+use anyhow::Result;
+use commitments::{
+    prove_commitment, EmittedState, EthABIEncoder, MisbehaviourProxyMessage, PrevState,
+    ProxyMessage, StateID, TrustingPeriodContext, UpdateStateProxyMessage,
+    VerifyMembershipProxyMessage,
+};
+use crypto::{EnclaveKey, Keccak256, Signer};
+use lcp_types::{Any, Height, Time};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Secret of the test key. NEVER use this key outside of tests.
+pub const TEST_SIGNER_SECRET: [u8; 32] = [0x01; 32];
+
+const BASE_TIMESTAMP_SECS: u128 = 1_700_000_000;
+
+#[derive(Debug, Serialize)]
+pub struct TestVector {
+    pub name: String,
+    pub message_type: u16,
+    /// The proxy message in its serde representation
+    pub input: serde_json::Value,
+    /// `ProxyMessage::to_bytes()`
+    pub message: String,
+    /// keccak256 of `message`, which is the digest signed by the signer
+    pub digest: String,
+    pub signer: String,
+    /// 65 bytes signature: r || s || v
+    pub signature: String,
+    /// ethabi encoded `CommitmentProof`
+    pub proof: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestVectors {
+    pub signer_secret: String,
+    pub signer: String,
+    pub vectors: Vec<TestVector>,
+}
+
+/// Generate the test vectors for all proxy message types
+pub fn generate() -> Result<TestVectors> {
+    let ek = EnclaveKey::from_secret(&TEST_SIGNER_SECRET)?;
+    let signer = ek.pubkey()?.as_address();
+    let vectors = messages()?
+        .into_iter()
+        .map(|(name, msg)| -> Result<TestVector> {
+            let input = serde_json::to_value(&msg)?;
+            let message_type = msg.message_type();
+            let message = msg.clone().to_bytes();
+            let proof = prove_commitment(&ek, signer, msg)?;
+            Ok(TestVector {
+                name: name.to_string(),
+                message_type,
+                input,
+                digest: to_hex(&message.keccak256()),
+                message: to_hex(&message),
+                signer: signer.to_hex_string(),
+                signature: to_hex(&proof.signature),
+                proof: to_hex(&proof.ethabi_encode()),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TestVectors {
+        signer_secret: to_hex(&TEST_SIGNER_SECRET),
+        signer: signer.to_hex_string(),
+        vectors,
+    })
+}
+
+fn messages() -> Result<Vec<(&'static str, ProxyMessage)>> {
+    let client_state = Any::new(
+        "/ibc.lightclients.tendermint.v1.ClientState".into(),
+        vec![0x0a, 0x04, 0x74, 0x65, 0x73, 0x74],
+    );
+    let header = Any::new(
+        "/ibc.lightclients.tendermint.v1.Header".into(),
+        vec![0x0a, 0x00],
+    );
+    let timestamp = |offset_secs: u128| {
+        Time::from_unix_timestamp_nanos((BASE_TIMESTAMP_SECS + offset_secs) * 1_000_000_000)
+    };
+    let update_state = UpdateStateProxyMessage {
+        prev_height: None,
+        prev_state_id: None,
+        post_height: Height::new(0, 1),
+        post_state_id: StateID::from([0x01; 32]),
+        frozen_height: None,
+        timestamp: timestamp(0)?,
+        expires_at: None,
+        context: Default::default(),
+        emitted_states: vec![],
+    };
+    let update_state_with_context = UpdateStateProxyMessage {
+        prev_height: Some(Height::new(0, 1)),
+        prev_state_id: Some(StateID::from([0x01; 32])),
+        post_height: Height::new(0, 2),
+        post_state_id: StateID::from([0x02; 32]),
+        frozen_height: None,
+        timestamp: timestamp(100)?,
+        expires_at: None,
+        context: TrustingPeriodContext::new(
+            Duration::from_secs(14 * 24 * 60 * 60),
+            Duration::from_secs(10),
+            timestamp(100)?,
+            timestamp(0)?,
+        )
+        .into(),
+        emitted_states: vec![EmittedState(Height::new(0, 2), client_state.clone())],
+    };
+    let update_state_frozen = UpdateStateProxyMessage {
+        frozen_height: Some(Height::new(0, 2)),
+        ..update_state_with_context.clone()
+    };
+    let update_state_with_expiry = UpdateStateProxyMessage {
+        expires_at: Some(timestamp(3600)?),
+        ..update_state_with_context.clone()
+    };
+    let verify_membership = VerifyMembershipProxyMessage {
+        prefix: b"ibc".to_vec(),
+        path: "clients/07-tendermint-0/clientState".into(),
+        value: Some([0x03; 32]),
+        height: Height::new(0, 2),
+        state_id: StateID::from([0x02; 32]),
+        expires_at: None,
+        trusted_timestamp: Some(timestamp(100)?),
+    };
+    let verify_non_membership = VerifyMembershipProxyMessage {
+        value: None,
+        ..verify_membership.clone()
+    };
+    let verify_membership_with_expiry = VerifyMembershipProxyMessage {
+        expires_at: Some(timestamp(3600)?),
+        ..verify_membership.clone()
+    };
+    let verify_membership_undisclosed = VerifyMembershipProxyMessage {
+        trusted_timestamp: None,
+        ..verify_membership.clone()
+    };
+    let misbehaviour = MisbehaviourProxyMessage {
+        prev_states: vec![
+            PrevState {
+                height: Height::new(0, 1),
+                state_id: StateID::from([0x01; 32]),
+            },
+            PrevState {
+                height: Height::new(0, 2),
+                state_id: StateID::from([0x02; 32]),
+            },
+        ],
+        context: Default::default(),
+        client_message: header,
+    };
+    Ok(vec![
+        ("update_state", update_state.into()),
+        (
+            "update_state_with_context",
+            update_state_with_context.into(),
+        ),
+        ("update_state_frozen", update_state_frozen.into()),
+        ("update_state_with_expiry", update_state_with_expiry.into()),
+        ("verify_membership", verify_membership.into()),
+        ("verify_non_membership", verify_non_membership.into()),
+        (
+            "verify_membership_with_expiry",
+            verify_membership_with_expiry.into(),
+        ),
+        (
+            "verify_membership_undisclosed",
+            verify_membership_undisclosed.into(),
+        ),
+        ("misbehaviour", misbehaviour.into()),
+    ])
+}
+
+fn to_hex(bz: &[u8]) -> String {
+    format!("0x{}", hex::encode(bz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commitments::CommitmentProof;
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let v1 = serde_json::to_string(&generate().unwrap()).unwrap();
+        let v2 = serde_json::to_string(&generate().unwrap()).unwrap();
+        assert_eq!(v1, v2);
+    }
+
+    #[test]
+    fn test_vectors_roundtrip() {
+        let vectors = generate().unwrap();
+        for v in vectors.vectors {
+            let proof = hex::decode(v.proof.strip_prefix("0x").unwrap()).unwrap();
+            let proof = CommitmentProof::ethabi_decode(&proof).unwrap();
+            assert_eq!(proof.signer.to_hex_string(), vectors.signer);
+            let message = proof.message().unwrap();
+            assert_eq!(message.message_type(), v.message_type, "{}", v.name);
+            let signer =
+                crypto::verify_signature_address(&proof.message, &proof.signature).unwrap();
+            assert_eq!(signer, proof.signer, "{}", v.name);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+/// Print the test vectors as JSON, or write them to the path given as the first argument
+fn main() -> Result<()> {
+    let json = serde_json::to_string_pretty(&test_vectors::generate()?)?;
+    match std::env::args().nth(1) {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}